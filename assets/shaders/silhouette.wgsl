@@ -0,0 +1,17 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+@group(2) @binding(0) var<uniform> color: vec4<f32>;
+@group(2) @binding(1) var<uniform> uv_rect: vec4<f32>;
+@group(2) @binding(2) var sprite_texture: texture_2d<f32>;
+@group(2) @binding(3) var sprite_sampler: sampler;
+
+// Only the shape of the sprite is kept, every pixel it covers gets the same flat color
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    let uv = uv_rect.xy + mesh.uv * uv_rect.zw;
+    let alpha = textureSample(sprite_texture, sprite_sampler, uv).a;
+    if alpha < 0.5 {
+        discard;
+    }
+    return color;
+}
@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use dark_arts_defense::ai::{aggro, behavior};
+use dark_arts_defense::test_utils::{TestApp, FRAME_SECONDS};
+use dark_arts_defense::units::team::Team;
+use dark_arts_defense::units::unit_types::UnitType;
+use dark_arts_defense::velocity;
+
+const UNIT_COUNTS: [usize; 3] = [1_000, 5_000, 10_000];
+// Half of them on either side of the altar, close enough that the front lines are fighting
+const FIELD_HALF_WIDTH: f32 = 2000.0;
+const FIELD_HALF_HEIGHT: f32 = 1200.0;
+
+// Knights coming in from the right and the summoner's cats holding the left, the same every run
+fn populated_app(count: usize) -> TestApp {
+    let mut rng = ChaCha8Rng::seed_from_u64(count as u64);
+    let units = (0..count)
+        .map(|index| {
+            let (unit_type, team, side) = if index % 2 == 0 {
+                (UnitType::Knight, Team::Good, 1.0)
+            } else {
+                (UnitType::Cat, Team::Evil, -1.0)
+            };
+            let position = Vec2::new(
+                side * rng.gen_range(0.0..FIELD_HALF_WIDTH),
+                rng.gen_range(-FIELD_HALF_HEIGHT..FIELD_HALF_HEIGHT),
+            );
+            (unit_type, team, position)
+        })
+        .collect();
+
+    let mut app = TestApp::new();
+    app.spawn_units(units);
+    // Lets everything that reacts to a unit being added have its go before anything is measured
+    app.tick(2);
+    app
+}
+
+// Deciding what every unit wants to do, which is the part that looks at every other unit
+fn behavior_selection(c: &mut Criterion) {
+    let mut group = c.benchmark_group("behavior_selection");
+    group.sample_size(10);
+    for count in UNIT_COUNTS {
+        let mut app = populated_app(count);
+        let mut schedule = Schedule::default();
+        schedule.add_systems(
+            (
+                aggro::record_threat,
+                aggro::update_aggro,
+                behavior::behavior_state_machine,
+            )
+                .chain(),
+        );
+
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| schedule.run(&mut app.app.world));
+        });
+    }
+    group.finish();
+}
+
+// Turning the behaviors into velocities and moving everything by them
+fn movement(c: &mut Criterion) {
+    let mut group = c.benchmark_group("movement");
+    group.sample_size(10);
+    for count in UNIT_COUNTS {
+        let mut app = populated_app(count);
+        let mut schedule = Schedule::default();
+        schedule.add_systems(
+            (
+                (
+                    behavior::execute_behavior_move_origo,
+                    behavior::execute_behavior_wander,
+                    behavior::execute_behavior_chase,
+                    behavior::execute_behavior_flee,
+                ),
+                velocity::translate,
+            )
+                .chain(),
+        );
+
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| {
+                app.app
+                    .world
+                    .resource_mut::<Time>()
+                    .advance_by(Duration::from_secs_f32(FRAME_SECONDS));
+                schedule.run(&mut app.app.world);
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, behavior_selection, movement);
+criterion_main!(benches);
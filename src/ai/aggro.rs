@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::events::Damaged;
+use crate::units::flying::{CanTargetAir, Flying};
+use crate::units::health::Health;
+use crate::units::team::{AllianceMatrix, CurrentTeam};
+
+// Fraction of every threat entry that fades away per second, so old grudges get forgotten
+const THREAT_DECAY_PER_SECOND: f32 = 0.2;
+// Entries below this are dropped from the table
+const MIN_THREAT: f32 = 0.5;
+const DEFAULT_ACQUISITION_RADIUS: f32 = 320.0;
+const DEFAULT_LEASH_DISTANCE: f32 = 640.0;
+
+// Who a unit is fighting and why. Targets are picked up within the acquisition radius, and the
+// unit gives up once it has been dragged further than the leash distance from where the fight
+// started, after which it ignores everything for a moment and goes back to what it was doing.
+#[derive(Component, Clone, Debug)]
+pub struct Aggro {
+    pub acquisition_radius: f32,
+    pub leash_distance: f32,
+    pub leash_cooldown: f32,
+    pub threat: HashMap<Entity, f32>,
+    pub target: Option<Entity>,
+    // Where the unit was when it picked its current fight
+    pub anchor: Option<Vec2>,
+    pub leashed: Timer,
+}
+
+impl Aggro {
+    pub fn new(acquisition_radius: f32, leash_distance: f32) -> Self {
+        let leash_cooldown = 3.0;
+        let mut leashed = Timer::from_seconds(leash_cooldown, TimerMode::Once);
+        leashed.tick(leashed.duration());
+        Self {
+            acquisition_radius,
+            leash_distance,
+            leash_cooldown,
+            threat: HashMap::new(),
+            target: None,
+            anchor: None,
+            leashed,
+        }
+    }
+
+    pub fn is_leashed(&self) -> bool {
+        !self.leashed.finished()
+    }
+
+    fn leash(&mut self) {
+        self.threat.clear();
+        self.target = None;
+        self.anchor = None;
+        self.leashed = Timer::from_seconds(self.leash_cooldown, TimerMode::Once);
+    }
+}
+
+impl Default for Aggro {
+    fn default() -> Self {
+        Self::new(DEFAULT_ACQUISITION_RADIUS, DEFAULT_LEASH_DISTANCE)
+    }
+}
+
+// Units that draw more attention than the damage they deal, so they can keep enemies off the rest
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ThreatMultiplier(pub f32);
+
+pub fn record_threat(
+    mut damaged_reader: EventReader<Damaged>,
+    multiplier_query: Query<&ThreatMultiplier>,
+    mut aggro_query: Query<&mut Aggro>,
+) {
+    for damaged in damaged_reader.read() {
+        let Some(source) = damaged.source else {
+            continue;
+        };
+        let Ok(mut aggro) = aggro_query.get_mut(damaged.target) else {
+            continue;
+        };
+        if aggro.is_leashed() {
+            continue;
+        }
+
+        let multiplier = multiplier_query.get(source).map_or(1.0, |threat| threat.0);
+        *aggro.threat.entry(source).or_insert(0.0) += damaged.amount as f32 * multiplier;
+    }
+}
+
+pub fn update_aggro(
+    time: Res<Time>,
+    alliances: Res<AllianceMatrix>,
+    mut query: Query<(&mut Aggro, &Transform, &CurrentTeam, Has<CanTargetAir>)>,
+    others_query: Query<(Entity, &Transform, &CurrentTeam, &Health, Has<Flying>)>,
+) {
+    let decay = (1.0 - THREAT_DECAY_PER_SECOND * time.delta_seconds()).max(0.0);
+    for (mut aggro, transform, team, can_target_air) in query.iter_mut() {
+        if !aggro.leashed.tick(time.delta()).finished() {
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+        let is_target = |entity: Entity| {
+            others_query.get(entity).is_ok_and(
+                |(_, _, other_team, other_health, other_is_flying)| {
+                    team.is_hostile_to(other_team, &alliances)
+                        && !other_health.is_dead()
+                        && (can_target_air || !other_is_flying)
+                },
+            )
+        };
+
+        aggro.threat.retain(|entity, threat| {
+            *threat *= decay;
+            *threat >= MIN_THREAT && is_target(*entity)
+        });
+
+        if let Some(anchor) = aggro.anchor {
+            if position.distance(anchor) > aggro.leash_distance {
+                aggro.leash();
+                continue;
+            }
+        }
+
+        // Whoever hurt it the most, the closest one in reach if nobody has yet
+        let most_threatening = aggro
+            .threat
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(entity, _)| *entity);
+        let closest = others_query
+            .iter()
+            .filter(|(entity, ..)| is_target(*entity))
+            .map(|(entity, other_transform, ..)| {
+                (
+                    entity,
+                    other_transform.translation.truncate().distance(position),
+                )
+            })
+            .filter(|(_, distance)| *distance <= aggro.acquisition_radius)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(entity, _)| entity);
+
+        let target = most_threatening.or(closest);
+        if target.is_some() && aggro.target.is_none() {
+            aggro.anchor = Some(position);
+        } else if target.is_none() {
+            aggro.anchor = None;
+        }
+        aggro.target = target;
+    }
+}
@@ -1,28 +1,68 @@
+use std::sync::Mutex;
+
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
 use rand::Rng;
 
+use super::aggro::Aggro;
+use super::formations::FormationSlots;
+use super::sleep::Asleep;
 use crate::{
-    dark_arts_defense::{GameEvent, RandomSeed},
+    events::{BehaviorChanged, Convert, Damage, Heal},
+    map::{plugin::CurrentMap, tilemap::TileMap},
+    movement,
+    player::{
+        command_mode::{PatrolRoute, RallyPoint},
+        plugin::Player,
+    },
+    pool::EntityPools,
+    rng::GameRng,
+    utils::timing::Cooldown,
     units::{
+        altar::{DarkAltar, ALTAR_SIEGE_DISTANCE},
+        attack::{spawn_projectile, AttackStats, ProjectileType},
+        damage::{apply_area_damage, DamageKind},
+        flying::{CanTargetAir, Flying},
         health::Health,
-        team::{CurrentTeam, Team},
+        imp::spawn_explosion,
+        morale::Morale,
+        stat_modifiers::{Stat, StatModifiers},
+        team::{AllianceMatrix, CurrentTeam, Team},
+        unit_types::Warrior,
     },
     velocity::Velocity,
+    vfx::flash::{FlashLimiter, VfxSettings},
 };
 
-const ATTACK_DISTANCE_MAX: f32 = 96.0;
-const ATTACK_DISTANCE_MID: f32 = 72.0;
-const ATTACK_DISTANCE_MIN: f32 = 48.0;
+// Attackers close in until they're this far into their range, and back off below the minimum
+const ATTACK_DISTANCE_MID: f32 = 0.75;
+const ATTACK_DISTANCE_MIN: f32 = 0.5;
+// Units heading for a spot, a rally point or a slot, ease off within this so they settle on it
+const SETTLE_RADIUS: f32 = 64.0;
+// Of the flee distance, how close a threat has to get to a unit that has just calmed down
+const RECOVERED_FLEE_DISTANCE: f32 = 0.5;
+// Fleeing units huddle up this close behind whoever they ran to
+const SHELTER_DISTANCE: f32 = 72.0;
+// How far from straight away from the threats a unit will run to get to cover, as the cosine
+// between the two. Past that the cover is on the wrong side and it just runs.
+const SHELTER_MAX_DETOUR: f32 = -0.3;
 
 #[derive(Clone, Debug)]
 pub enum Behavior {
-    Idle(IdleBehavior),           // Do nothing
-    MoveOrigo(MoveOrigoBehavior), // Special case for enemies with no targets in range, move towards origo instead
-    Wander(WanderBehavior),       // Friendly units wander around when waiting for enemies
-    Chase(ChaseBehavior),         // Both friendly and enemy units chase their targets
-    Flee(FleeBehavior),           // The acolyte tries to flee from enemies
-    Attack(AttackBehavior),       // Attack when in range
-    Dead(DeadBehavior),           // Dead units do nothing
+    Idle(IdleBehavior),                       // Do nothing
+    MoveOrigo(MoveOrigoBehavior),             // Special case for enemies with no targets in range, move towards origo instead
+    Wander(WanderBehavior),                   // Friendly units wander around when waiting for enemies
+    MoveToRally(MoveToRallyBehavior),         // Friendly units head for the rally point the player set
+    FollowFormation(FollowFormationBehavior), // Friendly units escort the player in formation
+    Guard(GuardBehavior),                     // Friendly units stand guard where the player told them to
+    Patrol(PatrolBehavior),                   // Walk a route of waypoints, stopping at each
+    Chase(ChaseBehavior),                     // Both friendly and enemy units chase their targets
+    Flee(FleeBehavior),                       // The acolyte tries to flee from enemies
+    Heal(HealBehavior),                       // Healers channel on the most hurt unit on their side
+    Attack(AttackBehavior),                   // Attack when in range
+    Kamikaze(KamikazeBehavior),               // Imps run into the closest enemy and explode
+    Spawning(SpawningBehavior),               // Summons rising out of the circle can't act yet
+    Dead(DeadBehavior),                       // Dead units do nothing
 }
 
 impl Default for Behavior {
@@ -31,12 +71,171 @@ impl Default for Behavior {
     }
 }
 
+impl Behavior {
+    pub fn is_same_kind(&self, other: &Behavior) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Behavior::Idle(_) => "Idle",
+            Behavior::MoveOrigo(_) => "Marching",
+            Behavior::Wander(_) => "Wandering",
+            Behavior::MoveToRally(_) => "Rallying",
+            Behavior::FollowFormation(_) => "In formation",
+            Behavior::Guard(_) => "Guarding",
+            Behavior::Patrol(_) => "Patrolling",
+            Behavior::Chase(_) => "Chasing",
+            Behavior::Flee(_) => "Fleeing",
+            Behavior::Heal(_) => "Healing",
+            Behavior::Attack(_) => "Attacking",
+            Behavior::Kamikaze(_) => "Kamikaze",
+            Behavior::Spawning(_) => "Rising",
+            Behavior::Dead(_) => "Dead",
+        }
+    }
+
+    // Runs once on the frame the state machine switches into this behavior
+    fn on_enter(&self, entity: &mut EntityCommands) {
+        // Start out waiting rather than picking up a stale walk from the last time around, so
+        // units stop for a bit after arriving at a rally point or losing their target
+        if let Behavior::Wander(behavior) = self {
+            let behavior = behavior.clone();
+            entity.add(move |entity: Entity, world: &mut World| {
+                if let Some(mut wander) = world.get_mut::<WanderBehavior>(entity) {
+                    *wander = behavior;
+                }
+            });
+        }
+        if let Behavior::Flee(_) = self {
+            entity.add(|entity: Entity, world: &mut World| {
+                if let Some(mut flee) = world.get_mut::<FleeBehavior>(entity) {
+                    flee.panic = flee.panic_seconds;
+                }
+            });
+        }
+        // Back from a fight, it picks the route up again from whichever waypoint is closest
+        if let Behavior::Patrol(_) = self {
+            entity.add(|entity: Entity, world: &mut World| {
+                if let Some(mut patrol) = world.get_mut::<PatrolBehavior>(entity) {
+                    patrol.next = None;
+                    patrol.pause = 0.0;
+                }
+            });
+        }
+    }
+
+    // Runs once on the frame the state machine switches away from this behavior
+    fn on_exit(&self, entity: &mut EntityCommands) {
+        // Let a swing that never landed go, instead of playing it out mid flight
+        if let Behavior::Attack(_) = self {
+            entity.add(|entity: Entity, world: &mut World| {
+                if let Some(mut attack) = world.get_mut::<AttackBehavior>(entity) {
+                    attack.is_attacking = false;
+                }
+            });
+        }
+        if let Behavior::Flee(_) = self {
+            entity.add(|entity: Entity, world: &mut World| {
+                if let Some(mut flee) = world.get_mut::<FleeBehavior>(entity) {
+                    flee.panic = 0.0;
+                    flee.recovered = flee.recovery_seconds;
+                }
+            });
+        }
+        // A channel that gets interrupted is lost, the next one starts from nothing
+        if let Behavior::Heal(_) = self {
+            entity.add(|entity: Entity, world: &mut World| {
+                if let Some(mut heal) = world.get_mut::<HealBehavior>(entity) {
+                    heal.target = None;
+                    heal.channel = 0.0;
+                }
+            });
+        }
+    }
+}
+
 #[derive(Component, Clone, Copy, Debug)]
 pub struct IdleBehavior {}
 
 #[derive(Component, Clone, Copy, Debug)]
 pub struct MoveOrigoBehavior {}
 
+#[derive(Component, Clone, Copy, Debug)]
+pub struct MoveToRallyBehavior {
+    // Close enough, so the whole army doesn't try to stand on the exact same spot
+    pub arrive_distance: f32,
+}
+
+impl Default for MoveToRallyBehavior {
+    fn default() -> Self {
+        MoveToRallyBehavior {
+            arrive_distance: 96.0,
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct GuardBehavior {
+    // Enemies inside this, around whatever is being guarded, are chased down, the rest ignored
+    pub radius: f32,
+    // Close enough to its post, so the guards stand around it instead of on top of it
+    pub hold_distance: f32,
+}
+
+impl Default for GuardBehavior {
+    fn default() -> Self {
+        GuardBehavior {
+            radius: 240.0,
+            hold_distance: 80.0,
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PatrolBehavior {
+    // Stands at every waypoint for this long before heading on to the next one
+    pub pause_seconds: f32,
+    pub arrive_distance: f32,
+    // The waypoint it's headed for, None until it has picked the closest one
+    pub next: Option<usize>,
+    pub pause: f32,
+}
+
+impl Default for PatrolBehavior {
+    fn default() -> Self {
+        PatrolBehavior {
+            pause_seconds: 1.5,
+            arrive_distance: 24.0,
+            next: None,
+            pause: 0.0,
+        }
+    }
+}
+
+// A route of the unit's own, enemies get theirs from the level. The player's units walk the one
+// drawn in command mode instead.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Waypoints(pub Vec<Vec2>);
+
+// The altar, summoner or structure the unit was told to stand guard around in command mode
+#[derive(Component, Clone, Copy, Debug)]
+pub struct GuardAssignment(pub Entity);
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct FollowFormationBehavior {
+    // Stands still once this close to its slot instead of jittering around it
+    pub slot_tolerance: f32,
+}
+
+impl Default for FollowFormationBehavior {
+    fn default() -> Self {
+        FollowFormationBehavior {
+            slot_tolerance: 12.0,
+        }
+    }
+}
+
 #[derive(Component, Clone, Debug)]
 pub struct WanderBehavior {
     pub wait_time: f32,
@@ -66,32 +265,122 @@ impl Default for WanderBehavior {
 pub struct ChaseBehavior {}
 
 #[derive(Component, Clone, Copy, Debug)]
-pub struct FleeBehavior {}
+pub struct FleeBehavior {
+    // Only flee while morale is below this, fighters use it to break and run once things go bad
+    pub max_morale: f32,
+    // Keeps running this long after the last threat is out of range instead of turning around
+    // the moment it's clear, which used to have acolytes flicker back and forth at the edge
+    pub panic_seconds: f32,
+    // Once it has calmed down, only something coming a lot closer sets it off again for this long
+    pub recovery_seconds: f32,
+    // What's left of either, counted down on the unit's own component
+    pub panic: f32,
+    pub recovered: f32,
+}
+
+impl Default for FleeBehavior {
+    fn default() -> Self {
+        FleeBehavior {
+            max_morale: f32::MAX,
+            panic_seconds: 1.0,
+            recovery_seconds: 2.0,
+            panic: 0.0,
+            recovered: 0.0,
+        }
+    }
+}
+
+impl FleeBehavior {
+    pub fn routing() -> Self {
+        FleeBehavior {
+            max_morale: 20.0,
+            panic_seconds: 2.0,
+            recovery_seconds: 4.0,
+            ..default()
+        }
+    }
+
+    fn wants_to_flee(&self, morale: Option<&Morale>) -> bool {
+        morale.is_none_or(|morale| morale.value < self.max_morale)
+    }
+
+    fn flee_distance_factor(&self) -> f32 {
+        if self.recovered > 0.0 {
+            RECOVERED_FLEE_DISTANCE
+        } else {
+            1.0
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct HealBehavior {
+    pub range: f32,
+    pub amount: i32,
+    // Stands still and channels this long for every heal that lands
+    pub channel_seconds: f32,
+    // Who it's channeling on and how far along it is, the beam is drawn from these
+    pub target: Option<Entity>,
+    pub channel: f32,
+}
+
+impl Default for HealBehavior {
+    fn default() -> Self {
+        HealBehavior {
+            range: 220.0,
+            amount: 12,
+            channel_seconds: 1.0,
+            target: None,
+            channel: 0.0,
+        }
+    }
+}
 
+fn get_morale_flee_distance(window: &Window, morale: Option<&Morale>) -> f32 {
+    get_flee_distance(window) * morale.map_or(1.0, |morale| morale.flee_distance_factor())
+}
+
+// The numbers a unit fights with live in its AttackStats, this only keeps track of the swing
 #[derive(Component, Clone, Debug)]
 pub struct AttackBehavior {
-    pub cooldown: f32,
-    pub random_cooldown_offset: f32,
-    pub random_attack_offset: u8,
-    pub damage: u8,
     pub is_attacking: bool,
-    pub timer: Timer,
+    pub cooldown: Cooldown,
 }
 
 impl Default for AttackBehavior {
     fn default() -> Self {
-        let attack_cooldown = 4.0;
         AttackBehavior {
-            cooldown: attack_cooldown,
-            random_cooldown_offset: 0.5,
-            random_attack_offset: 5,
-            damage: 10,
             is_attacking: false,
-            timer: Timer::from_seconds(attack_cooldown, TimerMode::Once),
+            cooldown: Cooldown::started(AttackStats::default().cooldown),
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct KamikazeBehavior {
+    pub contact_distance: f32,
+    pub explosion_radius: f32,
+    pub damage: i32,
+    pub damage_kind: DamageKind,
+}
+
+impl Default for KamikazeBehavior {
+    fn default() -> Self {
+        KamikazeBehavior {
+            contact_distance: 32.0,
+            explosion_radius: 128.0,
+            damage: 60,
+            damage_kind: DamageKind::Fire,
         }
     }
 }
 
+// Only ever added to a unit while it's rising, see units::spawning
+#[derive(Component, Clone, Copy, Debug)]
+pub struct SpawningBehavior {
+    pub remaining: f32,
+}
+
 #[derive(Component, Clone, Debug)]
 pub struct DeadBehavior;
 
@@ -105,7 +394,15 @@ impl Default for SupportedBehaviors {
     fn default() -> Self {
         SupportedBehaviors(vec![
             (Behavior::Wander(WanderBehavior::default()), 5),
+            (
+                Behavior::FollowFormation(FollowFormationBehavior::default()),
+                6,
+            ),
+            (Behavior::MoveToRally(MoveToRallyBehavior::default()), 7),
+            (Behavior::Patrol(PatrolBehavior::default()), 8),
             (Behavior::Chase(ChaseBehavior {}), 10),
+            (Behavior::Guard(GuardBehavior::default()), 11),
+            (Behavior::Flee(FleeBehavior::routing()), 12),
             (Behavior::Attack(AttackBehavior::default()), 15),
             (Behavior::Dead(DeadBehavior {}), 20),
         ])
@@ -118,6 +415,87 @@ pub struct BehaviorBundle {
     pub supported_behaviors: SupportedBehaviors,
 }
 
+// Lets the same setup go through commands for a single unit, or straight onto the world for a
+// whole batch of them that's already being spawned from inside a command
+pub trait InsertBundle {
+    fn insert_bundle(&mut self, bundle: impl Bundle);
+}
+
+impl InsertBundle for EntityCommands<'_> {
+    fn insert_bundle(&mut self, bundle: impl Bundle) {
+        self.insert(bundle);
+    }
+}
+
+impl InsertBundle for EntityWorldMut<'_> {
+    fn insert_bundle(&mut self, bundle: impl Bundle) {
+        self.insert(bundle);
+    }
+}
+
+impl BehaviorBundle {
+    // Inserts the bundle along with the component of every supported behavior
+    pub fn insert_into(self, entity: &mut impl InsertBundle) {
+        self.insert_behavior_components(entity);
+        entity.insert_bundle(self);
+    }
+
+    // The component of every supported behavior, which is where the execute systems keep their
+    // per unit state
+    pub fn insert_behavior_components(&self, entity: &mut impl InsertBundle) {
+        self.supported_behaviors.0.iter().for_each(|behavior| {
+            match behavior {
+                (Behavior::Idle(behavior), _) => {
+                    entity.insert_bundle(*behavior);
+                }
+                (Behavior::MoveOrigo(behavior), _) => {
+                    entity.insert_bundle(*behavior);
+                }
+                (Behavior::Wander(behavior), _) => {
+                    entity.insert_bundle(behavior.clone());
+                }
+                (Behavior::MoveToRally(behavior), _) => {
+                    entity.insert_bundle(*behavior);
+                }
+                (Behavior::FollowFormation(behavior), _) => {
+                    entity.insert_bundle(*behavior);
+                }
+                (Behavior::Guard(behavior), _) => {
+                    entity.insert_bundle(*behavior);
+                }
+                (Behavior::Patrol(behavior), _) => {
+                    entity.insert_bundle(*behavior);
+                }
+                (Behavior::Chase(behavior), _) => {
+                    entity.insert_bundle(*behavior);
+                }
+                (Behavior::Flee(behavior), _) => {
+                    entity.insert_bundle(*behavior);
+                }
+                (Behavior::Heal(behavior), _) => {
+                    entity.insert_bundle(*behavior);
+                }
+                (Behavior::Attack(behavior), _) => {
+                    entity.insert_bundle(behavior.clone());
+                }
+                (Behavior::Kamikaze(behavior), _) => {
+                    entity.insert_bundle(*behavior);
+                }
+                (Behavior::Spawning(behavior), _) => {
+                    entity.insert_bundle(*behavior);
+                }
+                (Behavior::Dead(behavior), _) => {
+                    entity.insert_bundle(behavior.clone());
+                }
+            };
+        });
+    }
+}
+
+// Units that can be won over to another team, along with the behaviors they get once they are
+#[derive(Component, Clone)]
+pub struct Convertible(pub BehaviorBundle);
+
 fn get_flee_distance(window: &Window) -> f32 {
     window.width() * 0.15
 }
@@ -126,7 +504,13 @@ fn get_chase_distance(window: &Window) -> f32 {
     window.width() * 0.4
 }
 
+// Flying units can only be targeted by units that are able to hit air
+fn can_reach_layer(can_target_air: bool, other_is_flying: bool) -> bool {
+    can_target_air || !other_is_flying
+}
+
 fn is_other_valid_target(
+    alliances: &AllianceMatrix,
     team: &CurrentTeam,
     other_health: &Health,
     other_team: &CurrentTeam,
@@ -134,7 +518,7 @@ fn is_other_valid_target(
     other_transform: &Transform,
     distance: f32,
 ) -> bool {
-    if team.is_friendly(other_team) {
+    if !team.is_hostile_to(other_team, alliances) {
         return false;
     }
 
@@ -147,89 +531,225 @@ fn is_other_valid_target(
     distance_to_other.length() < distance
 }
 
+type StateMachineData = (
+    Entity,
+    &'static mut CurrentBehavior,
+    &'static SupportedBehaviors,
+    &'static Transform,
+    &'static CurrentTeam,
+    &'static Health,
+    Has<CanTargetAir>,
+    Option<&'static Morale>,
+    Option<&'static Aggro>,
+    Option<&'static AttackStats>,
+    Option<&'static SpawningBehavior>,
+    Option<&'static FleeBehavior>,
+    Option<&'static Waypoints>,
+    Option<&'static GuardAssignment>,
+);
+
+#[allow(clippy::too_many_arguments)]
 pub fn behavior_state_machine(
-    mut query: Query<(
-        &mut CurrentBehavior,
-        &SupportedBehaviors,
-        &Transform,
-        &CurrentTeam,
-        &Health,
-    )>,
-    others_query: Query<(&Transform, &CurrentTeam, &Health)>,
+    mut commands: Commands,
+    mut behavior_changed_writer: EventWriter<BehaviorChanged>,
+    alliances: Res<AllianceMatrix>,
+    mut query: Query<StateMachineData, Without<Asleep>>,
+    others_query: Query<(&Transform, &CurrentTeam, &Health, Has<Flying>)>,
     window_query: Query<&Window>,
+    altar_query: Query<&Health, With<DarkAltar>>,
+    rally_point: Res<RallyPoint>,
+    patrol_route: Res<PatrolRoute>,
+    post_query: Query<&Transform>,
+    wounded_query: Query<(Entity, &Transform, &CurrentTeam, &Health)>,
+    formation_slots: Res<FormationSlots>,
 ) {
-    for (mut current_behavior, supported_behaviors, transform, team, health) in query.iter_mut() {
-        let window = &window_query.single();
-        let mut behaviors_that_want_to_be_active = supported_behaviors
-            .0
-            .iter()
-            .filter(|behavior| {
-                let behavior_wants_to_be_active =
-                    match behavior {
+    let altar_standing = altar_query.iter().any(|health| !health.is_dead());
+    // Every unit looks at every other unit here, so they're spread over the task pool and only
+    // the ones that switch are handed back to send events and commands for
+    let changes = Mutex::new(Vec::new());
+    query.par_iter_mut().for_each(
+        |(
+            entity,
+            mut current_behavior,
+            supported_behaviors,
+            transform,
+            team,
+            health,
+            can_target_air,
+            morale,
+            aggro,
+            attack_stats,
+            spawning,
+            flee,
+            waypoints,
+            guard_assignment,
+        )| {
+            let attack_range = attack_stats.copied().unwrap_or_default().range;
+            let window = &window_query.single();
+            let mut behaviors_that_want_to_be_active = supported_behaviors
+                .0
+                .iter()
+                .filter(|behavior| {
+                    let behavior_wants_to_be_active = match behavior {
                         (Behavior::Idle(_b), _p) => true,
+                        // With the altar still standing, enemies march all the way up to it
                         (Behavior::MoveOrigo(_b), _p) => {
                             let window = window_query.single();
                             let distance_to_origo = transform.translation.truncate().length();
-                            distance_to_origo > window.height() * 0.3
+                            altar_standing || distance_to_origo > window.height() * 0.3
                         }
                         (Behavior::Wander(_b), _p) => true,
+                        // Slots are only handed out to the player's units while a formation is on
+                        (Behavior::FollowFormation(_b), _p) => {
+                            formation_slots.0.contains_key(&entity)
+                        }
+                        // Only the player's own units take orders, and they still stop to fight
+                        // anything in the way since chasing and attacking outrank this
+                        (Behavior::MoveToRally(b), _p) => {
+                            team.0 == Team::Evil
+                                && rally_point.0.is_some_and(|rally| {
+                                    transform.translation.truncate().distance(rally)
+                                        > b.arrive_distance
+                                })
+                        }
+                        // Outranks chasing, guards only go after what comes close to their post
+                        // and that's up to the guard behavior itself
+                        (Behavior::Guard(_b), _p) => {
+                            guard_post(guard_assignment, &post_query).is_some()
+                        }
+                        // Under chasing, so anything that comes close enough breaks off the patrol
+                        (Behavior::Patrol(_b), _p) => {
+                            !route_for(team, waypoints, &patrol_route).is_empty()
+                        }
+                        // Units with aggro leave picking a fight to the threat table
+                        (Behavior::Chase(_b), _p) if aggro.is_some() => {
+                            aggro.is_some_and(|aggro| aggro.target.is_some())
+                        }
+                        (Behavior::Attack(_b), _p) if aggro.is_some() => aggro
+                            .and_then(|aggro| aggro.target)
+                            .and_then(|target| others_query.get(target).ok())
+                            .is_some_and(|(other_transform, ..)| {
+                                transform
+                                    .translation
+                                    .truncate()
+                                    .distance(other_transform.translation.truncate())
+                                    < attack_range
+                            }),
                         (Behavior::Chase(_b), _p) => others_query.iter().any(
-                            |(other_transform, other_team, other_health)| {
-                                is_other_valid_target(
-                                    team,
-                                    other_health,
-                                    other_team,
-                                    transform,
-                                    other_transform,
-                                    get_chase_distance(window),
-                                )
+                            |(other_transform, other_team, other_health, other_is_flying)| {
+                                can_reach_layer(can_target_air, other_is_flying)
+                                    && is_other_valid_target(
+                                        &alliances,
+                                        team,
+                                        other_health,
+                                        other_team,
+                                        transform,
+                                        other_transform,
+                                        get_chase_distance(window),
+                                    )
                             },
                         ),
-                        (Behavior::Flee(_b), _p) => others_query.iter().any(
-                            |(other_transform, other_team, other_health)| {
-                                is_other_valid_target(
-                                    team,
-                                    other_health,
-                                    other_team,
-                                    transform,
-                                    other_transform,
-                                    get_flee_distance(window),
-                                )
+                        // Like spawning, the state of the panic is on the component
+                        (Behavior::Flee(b), _p) => {
+                            let flee = flee.unwrap_or(b);
+                            let flee_distance = get_morale_flee_distance(window, morale)
+                                * flee.flee_distance_factor();
+                            flee.panic > 0.0
+                                || (b.wants_to_flee(morale)
+                                    && others_query.iter().any(
+                                        |(other_transform, other_team, other_health, _)| {
+                                            is_other_valid_target(
+                                                &alliances,
+                                                team,
+                                                other_health,
+                                                other_team,
+                                                transform,
+                                                other_transform,
+                                                flee_distance,
+                                            )
+                                        },
+                                    ))
+                        }
+                        (Behavior::Heal(b), _p) => {
+                            heal_target(entity, transform, team, b, None, &wounded_query).is_some()
+                        }
+                        (Behavior::Attack(_b), _p) => others_query.iter().any(
+                            |(other_transform, other_team, other_health, other_is_flying)| {
+                                can_reach_layer(can_target_air, other_is_flying)
+                                    && is_other_valid_target(
+                                        &alliances,
+                                        team,
+                                        other_health,
+                                        other_team,
+                                        transform,
+                                        other_transform,
+                                        attack_range,
+                                    )
                             },
                         ),
-                        (Behavior::Attack(_b), _p) => others_query.iter().any(
-                            |(other_transform, other_team, other_health)| {
-                                is_other_valid_target(
-                                    team,
-                                    other_health,
-                                    other_team,
-                                    transform,
-                                    other_transform,
-                                    ATTACK_DISTANCE_MAX,
-                                )
+                        (Behavior::Kamikaze(_b), _p) => others_query.iter().any(
+                            |(other_transform, other_team, other_health, other_is_flying)| {
+                                can_reach_layer(can_target_air, other_is_flying)
+                                    && is_other_valid_target(
+                                        &alliances,
+                                        team,
+                                        other_health,
+                                        other_team,
+                                        transform,
+                                        other_transform,
+                                        get_chase_distance(window),
+                                    )
                             },
                         ),
+                        // The list only has a copy from when it started, the component counts down
+                        (Behavior::Spawning(_b), _p) => {
+                            !health.is_dead()
+                                && spawning.is_some_and(|spawning| spawning.remaining > 0.0)
+                        }
                         (Behavior::Dead(_b), _p) => health.is_dead(),
                     };
 
-                behavior_wants_to_be_active
-            })
-            .cloned()
-            .collect::<Vec<(Behavior, u8)>>();
+                    behavior_wants_to_be_active
+                })
+                .cloned()
+                .collect::<Vec<(Behavior, u8)>>();
+
+            behaviors_that_want_to_be_active.sort_by_key(|b| std::cmp::Reverse(b.1));
+            let highest_prio_behavior = &behaviors_that_want_to_be_active[0].0;
+
+            if !current_behavior.0.is_same_kind(highest_prio_behavior) {
+                changes.lock().unwrap().push(BehaviorChanged {
+                    entity,
+                    from: current_behavior.0.clone(),
+                    to: highest_prio_behavior.clone(),
+                });
+            }
 
-        behaviors_that_want_to_be_active.sort_by(|a, b| b.1.cmp(&a.1));
-        let highest_prio_behavior = &behaviors_that_want_to_be_active[0].0;
+            current_behavior.0 = highest_prio_behavior.clone();
+        },
+    );
 
-        current_behavior.0 = highest_prio_behavior.clone();
+    // In the same order whichever thread got to them first, so a seeded run plays out the same
+    let mut changes = changes.into_inner().unwrap();
+    changes.sort_by_key(|change| change.entity);
+    for change in changes {
+        let mut entity_commands = commands.entity(change.entity);
+        change.from.on_exit(&mut entity_commands);
+        change.to.on_enter(&mut entity_commands);
+        behavior_changed_writer.send(change);
     }
 }
 
-pub fn execute_behavior_idle(mut query: Query<(&CurrentBehavior, &IdleBehavior, &mut Velocity)>) {
-    for (current_behavior, _, mut velocity) in query.iter_mut() {
-        if let Behavior::Idle(_) = current_behavior.0 {
-            velocity.0 = Vec2::ZERO;
-        }
-    }
+pub fn execute_behavior_idle(
+    mut query: Query<(&CurrentBehavior, &IdleBehavior, &mut Velocity), Without<Asleep>>,
+) {
+    query
+        .par_iter_mut()
+        .for_each(|(current_behavior, _, mut velocity)| {
+            if let Behavior::Idle(_) = current_behavior.0 {
+                velocity.0 = Vec2::ZERO;
+            }
+        });
 }
 
 pub fn execute_behavior_move_origo(
@@ -238,19 +758,37 @@ pub fn execute_behavior_move_origo(
         &MoveOrigoBehavior,
         &mut Velocity,
         &Transform,
+        Option<&Flying>,
     )>,
+    altar_query: Query<&Health, With<DarkAltar>>,
+    maps: Res<Assets<TileMap>>,
+    current_map: Res<CurrentMap>,
 ) {
-    for (current_behavior, _, mut velocity, transform) in query.iter_mut() {
-        if let Behavior::MoveOrigo(_) = current_behavior.0 {
-            let direction = -transform.translation.truncate();
-            velocity.0 = direction.normalize_or_zero();
-        }
-    }
+    let altar_standing = altar_query.iter().any(|health| !health.is_dead());
+    let map = current_map.get(&maps);
+    query
+        .par_iter_mut()
+        .for_each(|(current_behavior, _, mut velocity, transform, flying)| {
+            if let Behavior::MoveOrigo(_) = current_behavior.0 {
+                let position = transform.translation.truncate();
+                let direction = -position;
+                // Ground units follow the map around rocks and water, flyers go straight for it
+                let path = map
+                    .filter(|_| flying.is_none())
+                    .and_then(|map| map.direction_to_origo(position));
+                velocity.0 = if altar_standing && direction.length() < ALTAR_SIEGE_DISTANCE * 0.75 {
+                    Vec2::ZERO
+                } else {
+                    path.unwrap_or(direction.normalize_or_zero())
+                };
+            }
+        });
 }
 
 pub fn execute_behavior_wander(
     time: Res<Time>,
-    mut query: Query<(&CurrentBehavior, &mut WanderBehavior, &mut Velocity)>,
+    mut rng: ResMut<GameRng>,
+    mut query: Query<(&CurrentBehavior, &mut WanderBehavior, &mut Velocity), Without<Asleep>>,
 ) {
     for (current_behavior, mut wander_behavior, mut velocity) in query.iter_mut() {
         if let Behavior::Wander(_) = current_behavior.0 {
@@ -263,29 +801,31 @@ pub fn execute_behavior_wander(
                     wander_behavior.is_wandering = false;
                     wander_behavior.wait_timer = Timer::from_seconds(
                         wander_behavior.wait_time
-                            + rand::random::<f32>() * wander_behavior.random_time_offset,
+                            + rng.0.gen::<f32>() * wander_behavior.random_time_offset,
                         TimerMode::Once,
                     );
 
                     velocity.0 = Vec2::ZERO;
                 }
-            } else if wander_behavior
+            } else if !wander_behavior
                 .wait_timer
                 .tick(time.delta())
                 .just_finished()
             {
+                velocity.0 = Vec2::ZERO;
+            } else {
                 wander_behavior.is_wandering = true;
                 wander_behavior.wander_timer = Timer::from_seconds(
                     wander_behavior.wander_time
-                        + rand::random::<f32>() * wander_behavior.random_time_offset,
+                        + rng.0.gen::<f32>() * wander_behavior.random_time_offset,
                     TimerMode::Once,
                 );
 
                 // randomize the direction of the velocity, and normalize it, then half it,
                 // because the units should move slower when is_wandering
                 velocity.0 = Vec2::new(
-                    rand::random::<f32>() * 2.0 - 1.0,
-                    rand::random::<f32>() * 2.0 - 1.0,
+                    rng.0.gen::<f32>() * 2.0 - 1.0,
+                    rng.0.gen::<f32>() * 2.0 - 1.0,
                 )
                 .normalize()
                     * 0.5;
@@ -294,35 +834,259 @@ pub fn execute_behavior_wander(
     }
 }
 
-pub fn execute_behavior_chase(
+pub fn execute_behavior_move_to_rally(
+    rally_point: Res<RallyPoint>,
     mut query: Query<(
         &CurrentBehavior,
-        &ChaseBehavior,
+        &MoveToRallyBehavior,
         &Transform,
-        &CurrentTeam,
         &mut Velocity,
     )>,
-    window_query: Query<&Window>,
-    others_query: Query<(&Transform, &CurrentTeam, &Health)>,
 ) {
+    let Some(rally) = rally_point.0 else {
+        return;
+    };
+
     query
-        .iter_mut()
-        .for_each(|(current_behavior, _, transform, team, mut velocity)| {
+        .par_iter_mut()
+        .for_each(|(current_behavior, _, transform, mut velocity)| {
+            if let Behavior::MoveToRally(_) = current_behavior.0 {
+                velocity.0 =
+                    movement::arrive(transform.translation.truncate(), rally, SETTLE_RADIUS);
+            }
+        });
+}
+
+// The player's units all walk the one route, everyone else the one they were sent out on
+fn route_for<'a>(
+    team: &CurrentTeam,
+    waypoints: Option<&'a Waypoints>,
+    patrol_route: &'a PatrolRoute,
+) -> &'a [Vec2] {
+    if team.0 == Team::Evil {
+        &patrol_route.0
+    } else {
+        waypoints.map_or(&[], |waypoints| &waypoints.0)
+    }
+}
+
+type PatrolData = (
+    &'static CurrentBehavior,
+    &'static mut PatrolBehavior,
+    &'static Transform,
+    &'static CurrentTeam,
+    &'static mut Velocity,
+    Option<&'static Waypoints>,
+);
+
+pub fn execute_behavior_patrol(
+    time: Res<Time>,
+    patrol_route: Res<PatrolRoute>,
+    mut query: Query<PatrolData>,
+) {
+    // A new route from the summoner, start over from wherever everyone is
+    let route_changed = patrol_route.is_changed();
+    query.par_iter_mut().for_each(
+        |(current_behavior, mut patrol, transform, team, mut velocity, waypoints)| {
+            let Behavior::Patrol(_) = current_behavior.0 else {
+                return;
+            };
+            let route = route_for(team, waypoints, &patrol_route);
+            if route_changed && team.0 == Team::Evil {
+                patrol.next = None;
+            }
+
+            let position = transform.translation.truncate();
+            let next = patrol.next.filter(|next| *next < route.len()).or_else(|| {
+                route
+                    .iter()
+                    .enumerate()
+                    .min_by(|a, b| {
+                        position
+                            .distance_squared(*a.1)
+                            .total_cmp(&position.distance_squared(*b.1))
+                    })
+                    .map(|(index, _)| index)
+            });
+            patrol.next = next;
+            let Some(next) = next else {
+                velocity.0 = Vec2::ZERO;
+                return;
+            };
+
+            if patrol.pause > 0.0 {
+                patrol.pause -= time.delta_seconds();
+                velocity.0 = Vec2::ZERO;
+                return;
+            }
+
+            let waypoint = route[next];
+            if position.distance(waypoint) <= patrol.arrive_distance {
+                // Round and round, from the last waypoint it's back to the first
+                patrol.next = Some((next + 1) % route.len());
+                patrol.pause = patrol.pause_seconds;
+                velocity.0 = Vec2::ZERO;
+            } else {
+                velocity.0 = movement::arrive(position, waypoint, SETTLE_RADIUS);
+            }
+        },
+    );
+}
+
+// Wherever the guarded altar, summoner or structure is, as long as it's still around
+fn guard_post(
+    guard_assignment: Option<&GuardAssignment>,
+    post_query: &Query<&Transform>,
+) -> Option<Vec2> {
+    guard_assignment
+        .and_then(|assignment| post_query.get(assignment.0).ok())
+        .map(|transform| transform.translation.truncate())
+}
+
+type GuardData = (
+    &'static CurrentBehavior,
+    &'static GuardBehavior,
+    &'static Transform,
+    &'static CurrentTeam,
+    &'static mut Velocity,
+    Has<CanTargetAir>,
+    Option<&'static GuardAssignment>,
+    Has<AttackBehavior>,
+);
+
+pub fn execute_behavior_guard(
+    alliances: Res<AllianceMatrix>,
+    post_query: Query<&Transform>,
+    mut query: Query<GuardData>,
+    others_query: Query<(&Transform, &CurrentTeam, &Health, Has<Flying>)>,
+) {
+    query.par_iter_mut().for_each(
+        |(
+            current_behavior,
+            guard,
+            transform,
+            team,
+            mut velocity,
+            can_target_air,
+            guard_assignment,
+            can_attack,
+        )| {
+            let Behavior::Guard(_) = current_behavior.0 else {
+                return;
+            };
+            let Some(post) = guard_post(guard_assignment, &post_query) else {
+                return;
+            };
+
+            let position = transform.translation.truncate();
+            let intruder = others_query
+                .iter()
+                .filter(
+                    |(other_transform, other_team, other_health, other_is_flying)| {
+                        can_reach_layer(can_target_air, *other_is_flying)
+                            && team.is_hostile_to(other_team, &alliances)
+                            && !other_health.is_dead()
+                            && other_transform.translation.truncate().distance(post) < guard.radius
+                    },
+                )
+                .map(|(other_transform, ..)| other_transform.translation.truncate())
+                .min_by(|a, b| {
+                    position
+                        .distance_squared(*a)
+                        .total_cmp(&position.distance_squared(*b))
+                });
+
+            // Attacking outranks guarding, so this only has to get the guard there. Units that
+            // can't fight back only keep watch, going after intruders would just run them into
+            // what they flee from.
+            velocity.0 = match intruder {
+                Some(intruder) if can_attack => movement::seek(position, intruder),
+                _ if position.distance(post) > guard.hold_distance => {
+                    movement::arrive(position, post, SETTLE_RADIUS)
+                }
+                _ => Vec2::ZERO,
+            };
+        },
+    );
+}
+
+pub fn execute_behavior_follow_formation(
+    formation_slots: Res<FormationSlots>,
+    mut query: Query<(
+        Entity,
+        &CurrentBehavior,
+        &FollowFormationBehavior,
+        &Transform,
+        &mut Velocity,
+    )>,
+) {
+    query.par_iter_mut().for_each(
+        |(entity, current_behavior, follow, transform, mut velocity)| {
+            if let Behavior::FollowFormation(_) = current_behavior.0 {
+                let Some(slot) = formation_slots.0.get(&entity) else {
+                    return;
+                };
+
+                let position = transform.translation.truncate();
+                velocity.0 = if position.distance(*slot) <= follow.slot_tolerance {
+                    Vec2::ZERO
+                } else {
+                    movement::arrive(position, *slot, SETTLE_RADIUS)
+                };
+            }
+        },
+    );
+}
+
+type ChaseData = (
+    &'static CurrentBehavior,
+    &'static ChaseBehavior,
+    &'static Transform,
+    &'static CurrentTeam,
+    &'static mut Velocity,
+    Has<CanTargetAir>,
+    Option<&'static Aggro>,
+);
+
+pub fn execute_behavior_chase(
+    alliances: Res<AllianceMatrix>,
+    mut query: Query<ChaseData>,
+    window_query: Query<&Window>,
+    others_query: Query<(&Transform, &CurrentTeam, &Health, Has<Flying>)>,
+) {
+    query.par_iter_mut().for_each(
+        |(current_behavior, _, transform, team, mut velocity, can_target_air, aggro)| {
             if let Behavior::Chase(_) = current_behavior.0 {
+                // Units with aggro go after whoever tops their threat table, however far away
+                if let Some((target_transform, ..)) = aggro
+                    .and_then(|aggro| aggro.target)
+                    .and_then(|target| others_query.get(target).ok())
+                {
+                    velocity.0 = movement::seek(
+                        transform.translation.truncate(),
+                        target_transform.translation.truncate(),
+                    );
+                    return;
+                }
+
                 let window = window_query.single();
                 let mut enemies_within_range = others_query
                     .iter()
-                    .filter(|(other_transform, other_team, other_health)| {
-                        is_other_valid_target(
-                            team,
-                            other_health,
-                            other_team,
-                            transform,
-                            other_transform,
-                            get_chase_distance(window),
-                        )
-                    })
-                    .collect::<Vec<(&Transform, &CurrentTeam, &Health)>>();
+                    .filter(
+                        |(other_transform, other_team, other_health, other_is_flying)| {
+                            can_reach_layer(can_target_air, *other_is_flying)
+                                && is_other_valid_target(
+                                    &alliances,
+                                    team,
+                                    other_health,
+                                    other_team,
+                                    transform,
+                                    other_transform,
+                                    get_chase_distance(window),
+                                )
+                        },
+                    )
+                    .collect::<Vec<(&Transform, &CurrentTeam, &Health, bool)>>();
 
                 enemies_within_range.sort_by(|a, b| {
                     let distance_to_a =
@@ -335,148 +1099,699 @@ pub fn execute_behavior_chase(
                         .unwrap()
                 });
 
-                if let Some((enemy_transform, _t, _h)) = enemies_within_range.first() {
-                    let direction =
-                        enemy_transform.translation.truncate() - transform.translation.truncate();
-                    velocity.0 = direction.normalize_or_zero();
+                if let Some((enemy_transform, _t, _h, _f)) = enemies_within_range.first() {
+                    velocity.0 = movement::seek(
+                        transform.translation.truncate(),
+                        enemy_transform.translation.truncate(),
+                    );
                 }
             }
-        });
+        },
+    );
 }
 
-pub fn execute_behavior_flee(
-    window_query: Query<&Window>,
+// The most hurt unit on the same side within range, healers don't heal themselves. Sticks with
+// the one it's already channeling on until that one is topped up or out of reach.
+fn heal_target(
+    entity: Entity,
+    transform: &Transform,
+    team: &CurrentTeam,
+    heal: &HealBehavior,
+    current: Option<Entity>,
+    wounded_query: &Query<(Entity, &Transform, &CurrentTeam, &Health)>,
+) -> Option<Entity> {
+    let position = transform.translation.truncate();
+    let wounded = |(other, other_transform, other_team, other_health): &(
+        Entity,
+        &Transform,
+        &CurrentTeam,
+        &Health,
+    )| {
+        *other != entity
+            && other_team.0 == team.0
+            && !other_health.is_dead()
+            && other_health.current < other_health.max
+            && other_transform.translation.truncate().distance(position) < heal.range
+    };
+
+    if let Some(current) = current.and_then(|current| wounded_query.get(current).ok()) {
+        if wounded(&current) {
+            return Some(current.0);
+        }
+    }
+    wounded_query
+        .iter()
+        .filter(wounded)
+        .min_by(|a, b| a.3.fraction().total_cmp(&b.3.fraction()))
+        .map(|(other, ..)| other)
+}
+
+pub fn execute_behavior_heal(
+    time: Res<Time>,
+    mut heal_writer: EventWriter<Heal>,
     mut query: Query<(
+        Entity,
         &CurrentBehavior,
-        &FleeBehavior,
+        &mut HealBehavior,
         &Transform,
         &CurrentTeam,
         &mut Velocity,
     )>,
+    wounded_query: Query<(Entity, &Transform, &CurrentTeam, &Health)>,
+) {
+    for (entity, current_behavior, mut heal, transform, team, mut velocity) in query.iter_mut() {
+        let Behavior::Heal(_) = current_behavior.0 else {
+            continue;
+        };
+        velocity.0 = Vec2::ZERO;
+
+        let target = heal_target(entity, transform, team, &heal, heal.target, &wounded_query);
+        if target != heal.target {
+            heal.target = target;
+            heal.channel = 0.0;
+        }
+        let Some(target) = target else {
+            continue;
+        };
+
+        heal.channel += time.delta_seconds();
+        if heal.channel >= heal.channel_seconds {
+            heal.channel -= heal.channel_seconds;
+            heal_writer.send(Heal {
+                target,
+                amount: heal.amount,
+                source: Some(entity),
+            });
+        }
+    }
+}
+
+type FleeData = (
+    Entity,
+    &'static CurrentBehavior,
+    &'static mut FleeBehavior,
+    &'static Transform,
+    &'static CurrentTeam,
+    &'static mut Velocity,
+    Option<&'static Morale>,
+);
+type ShelterFilter = Or<(With<Player>, With<Warrior>)>;
+
+// Runs for the nearest Warrior or the summoner on its own side, unless they're over where the
+// threats are, and stays close behind them until it has calmed down
+pub fn execute_behavior_flee(
+    alliances: Res<AllianceMatrix>,
+    time: Res<Time>,
+    window_query: Query<&Window>,
+    mut query: Query<FleeData>,
     others_query: Query<(&Transform, &CurrentTeam, &Health)>,
+    shelter_query: Query<(Entity, &Transform, &CurrentTeam, &Health), ShelterFilter>,
 ) {
     let window = window_query.single();
-    query
-        .iter_mut()
-        .for_each(|(current_behavior, _, transform, team, mut velocity)| {
-            if let Behavior::Flee(_) = current_behavior.0 {
-                let enemies_within_range = others_query
-                    .iter()
-                    .filter(|(other_transform, other_team, other_health)| {
-                        is_other_valid_target(
-                            team,
-                            other_health,
-                            other_team,
-                            transform,
-                            other_transform,
-                            get_flee_distance(window),
-                        )
-                    })
-                    .collect::<Vec<(&Transform, &CurrentTeam, &Health)>>();
+    let shelters: Vec<(Entity, Vec2, Team)> = shelter_query
+        .iter()
+        .filter(|(_, _, _, health)| !health.is_dead())
+        .map(|(entity, transform, team, _)| (entity, transform.translation.truncate(), team.0))
+        .collect();
 
+    query.par_iter_mut().for_each(
+        |(entity, current_behavior, mut flee, transform, team, mut velocity, morale)| {
+            flee.recovered = (flee.recovered - time.delta_seconds()).max(0.0);
+            let Behavior::Flee(_) = current_behavior.0 else {
+                return;
+            };
+
+            let position = transform.translation.truncate();
+            let enemies_within_range = others_query
+                .iter()
+                .filter(|(other_transform, other_team, other_health)| {
+                    is_other_valid_target(
+                        &alliances,
+                        team,
+                        other_health,
+                        other_team,
+                        transform,
+                        other_transform,
+                        get_morale_flee_distance(window, morale),
+                    )
+                })
+                .collect::<Vec<(&Transform, &CurrentTeam, &Health)>>();
+
+            let away = if enemies_within_range.is_empty() {
+                flee.panic -= time.delta_seconds();
+                None
+            } else {
+                flee.panic = flee.panic_seconds;
                 let center_of_mass = enemies_within_range.iter().fold(
                     (Vec2::ZERO, 0.0),
                     |mut acc, (other_transform, _, _)| {
-                        let distance_to_other = (transform.translation.truncate()
-                            - other_transform.translation.truncate())
-                        .length();
+                        let distance_to_other =
+                            (position - other_transform.translation.truncate()).length();
                         let weight = 1.0 / distance_to_other;
                         acc.0 += other_transform.translation.truncate() * weight;
                         acc.1 += weight;
                         acc
                     },
                 );
-
                 let flee_from = center_of_mass.0 / center_of_mass.1;
-                velocity.0 = (transform.translation.truncate() - flee_from).normalize_or_zero();
+                Some((position - flee_from).normalize_or_zero())
             };
-        });
+
+            let shelter = shelters
+                .iter()
+                .filter(|(other, _, other_team)| *other != entity && *other_team == team.0)
+                .map(|(_, other_position, _)| *other_position)
+                .min_by(|a, b| {
+                    position
+                        .distance_squared(*a)
+                        .total_cmp(&position.distance_squared(*b))
+                });
+
+            velocity.0 = match (away, shelter) {
+                (Some(away), Some(shelter))
+                    if movement::seek(position, shelter).dot(away) >= SHELTER_MAX_DETOUR =>
+                {
+                    movement::arrive(position, shelter, SHELTER_DISTANCE)
+                }
+                (Some(away), _) => away,
+                (None, Some(shelter)) => movement::arrive(position, shelter, SHELTER_DISTANCE),
+                // Nothing in sight and nowhere to go, it keeps running the way it was until the
+                // panic wears off
+                (None, None) => velocity.0,
+            };
+        },
+    );
 }
 
+type AttackData = (
+    Entity,
+    &'static CurrentBehavior,
+    &'static mut AttackBehavior,
+    &'static Transform,
+    &'static CurrentTeam,
+    &'static mut Velocity,
+    Has<CanTargetAir>,
+    Option<&'static StatModifiers>,
+    Option<&'static Aggro>,
+    &'static AttackStats,
+);
+
+#[allow(clippy::too_many_arguments)]
 pub fn execute_behavior_attack(
+    alliances: Res<AllianceMatrix>,
     time: Res<Time>,
-    mut rng: ResMut<RandomSeed>,
-    mut query: Query<(
-        &CurrentBehavior,
-        &mut AttackBehavior,
-        &Transform,
-        &CurrentTeam,
-        &mut Velocity,
-    )>,
-    mut others_query: Query<(&Transform, &CurrentTeam, &mut Health)>,
-    mut event_writer: EventWriter<GameEvent>,
+    mut rng: ResMut<GameRng>,
+    mut query: Query<AttackData>,
+    others_query: Query<(Entity, &Transform, &CurrentTeam, &Health, Has<Flying>)>,
+    mut damage_writer: EventWriter<Damage>,
+    mut commands: Commands,
+    mut pools: ResMut<EntityPools>,
 ) {
-    query.iter_mut().for_each(
-        |(current_behavior, mut attack_behavior, transform, team, mut velocity)| {
+    // Finding a target is the expensive part and runs in parallel, the rolls for the swings that
+    // are ready come after on their own so the GameRng is drawn from in the same order every time
+    let swings = Mutex::new(Vec::new());
+    query.par_iter_mut().for_each(
+        |(
+            entity,
+            current_behavior,
+            mut attack_behavior,
+            transform,
+            team,
+            mut velocity,
+            can_target_air,
+            stats,
+            aggro,
+            attack_stats,
+        )| {
             if let Behavior::Attack(_) = current_behavior.0 {
+                let aggro_target = aggro.and_then(|aggro| aggro.target);
                 let mut enemies_within_range = others_query
-                    .iter_mut()
-                    .filter(|(other_transform, other_team, other_health)| {
-                        is_other_valid_target(
-                            team,
-                            other_health,
-                            other_team,
-                            transform,
-                            other_transform,
-                            ATTACK_DISTANCE_MAX,
-                        )
-                    })
-                    .collect::<Vec<(&Transform, &CurrentTeam, Mut<Health>)>>();
+                    .iter()
+                    .filter(
+                        |(other, other_transform, other_team, other_health, other_is_flying)| {
+                            aggro_target.is_none_or(|target| target == *other)
+                                && can_reach_layer(can_target_air, *other_is_flying)
+                                && is_other_valid_target(
+                                    &alliances,
+                                    team,
+                                    other_health,
+                                    other_team,
+                                    transform,
+                                    other_transform,
+                                    attack_stats.range,
+                                )
+                        },
+                    )
+                    .collect::<Vec<(Entity, &Transform, &CurrentTeam, &Health, bool)>>();
 
                 enemies_within_range.sort_by(|a, b| {
                     let distance_to_a =
-                        transform.translation.truncate() - a.0.translation.truncate();
+                        transform.translation.truncate() - a.1.translation.truncate();
                     let distance_to_b =
-                        transform.translation.truncate() - b.0.translation.truncate();
+                        transform.translation.truncate() - b.1.translation.truncate();
                     distance_to_a
                         .length()
                         .partial_cmp(&distance_to_b.length())
                         .unwrap()
                 });
 
-                if let Some((enemy_transform, enemy_team, enemy_health)) =
-                    enemies_within_range.first_mut()
-                {
+                if let Some((enemy, enemy_transform, _, _, _)) = enemies_within_range.first() {
                     let direction =
                         enemy_transform.translation.truncate() - transform.translation.truncate();
 
-                    velocity.0 = if direction.length() > ATTACK_DISTANCE_MID {
+                    velocity.0 = if direction.length() > attack_stats.range * ATTACK_DISTANCE_MID {
                         direction.normalize_or_zero()
-                    } else if direction.length() > ATTACK_DISTANCE_MIN {
+                    } else if direction.length() > attack_stats.range * ATTACK_DISTANCE_MIN {
                         velocity.0
                     } else {
                         Vec2::ZERO
                     };
 
-                    if attack_behavior.timer.tick(time.delta()).just_finished() {
-                        let final_damage = std::cmp::min(
-                            rng.0.gen_range(
-                                attack_behavior.damage
-                                    ..=attack_behavior.damage
-                                        + attack_behavior.random_attack_offset,
-                            ),
-                            enemy_health.0,
-                        );
-                        enemy_health.0 -= final_damage;
-                        if enemy_health.is_dead() && enemy_team.0 == Team::Good {
-                            event_writer.send(GameEvent::IncreaseScore);
-                        }
-
-                        let new_cooldown = attack_behavior.cooldown
-                            + rand::random::<f32>() * attack_behavior.random_cooldown_offset;
-                        attack_behavior.timer = Timer::from_seconds(new_cooldown, TimerMode::Once);
-                        attack_behavior.is_attacking = true;
+                    let attack_speed =
+                        stats.map_or(1.0, |stats| stats.apply(Stat::AttackSpeed, 1.0));
+                    if attack_behavior
+                        .cooldown
+                        .tick(time.delta().mul_f32(attack_speed))
+                        .is_ready()
+                    {
+                        swings.lock().unwrap().push((entity, *enemy));
                     }
                 }
             }
         },
     );
+
+    let mut swings = swings.into_inner().unwrap();
+    swings.sort_by_key(|(entity, _)| *entity);
+    for (entity, enemy) in swings {
+        let Ok((_, _, mut attack_behavior, transform, _, _, _, stats, _, attack_stats)) =
+            query.get_mut(entity)
+        else {
+            continue;
+        };
+
+        let amount = rng
+            .0
+            .gen_range(attack_stats.damage..=attack_stats.damage + attack_stats.damage_variance);
+        let amount = stats.map_or(amount, |stats| {
+            stats.apply(Stat::Damage, amount as f32).round() as i32
+        });
+        let critical = rng
+            .0
+            .gen_bool(attack_stats.crit_chance.clamp(0.0, 1.0) as f64);
+        let amount = if critical {
+            (amount as f32 * attack_stats.crit_multiplier).round() as i32
+        } else {
+            amount
+        };
+        let damage = Damage {
+            target: enemy,
+            amount,
+            kind: attack_stats.damage_kind,
+            armor_piercing: false,
+            source: Some(entity),
+            critical,
+        };
+        match attack_stats.projectile {
+            ProjectileType::Melee => {
+                damage_writer.send(damage);
+            }
+            ProjectileType::Bolt => {
+                spawn_projectile(
+                    &mut commands,
+                    &mut pools,
+                    transform.translation.truncate(),
+                    damage,
+                );
+            }
+        }
+
+        let new_cooldown =
+            attack_stats.cooldown + rng.0.gen::<f32>() * attack_stats.cooldown_variance;
+        attack_behavior.cooldown.start_for(new_cooldown);
+        attack_behavior.is_attacking = true;
+    }
 }
 
-pub fn execute_behavior_dead(mut query: Query<(&CurrentBehavior, &DeadBehavior, &mut Velocity)>) {
-    for (current_behavior, _, mut velocity) in query.iter_mut() {
-        if let Behavior::Dead(_) = current_behavior.0 {
+type KamikazeData = (
+    Entity,
+    &'static CurrentBehavior,
+    &'static KamikazeBehavior,
+    &'static Transform,
+    &'static CurrentTeam,
+    &'static mut Velocity,
+    &'static Health,
+    Option<&'static StatModifiers>,
+);
+type TargetData = (
+    Entity,
+    &'static Transform,
+    &'static CurrentTeam,
+    &'static Health,
+    Has<Flying>,
+);
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_behavior_kamikaze(
+    alliances: Res<AllianceMatrix>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut flash_limiter: ResMut<FlashLimiter>,
+    vfx_settings: Res<VfxSettings>,
+    window_query: Query<&Window>,
+    mut query: Query<KamikazeData>,
+    others_query: Query<TargetData, Without<KamikazeBehavior>>,
+    mut damage_writer: EventWriter<Damage>,
+) {
+    let window = window_query.single();
+    for (
+        entity,
+        current_behavior,
+        kamikaze_behavior,
+        transform,
+        team,
+        mut velocity,
+        health,
+        stats,
+    ) in query.iter_mut()
+    {
+        if let Behavior::Kamikaze(_) = current_behavior.0 {
+            if health.is_dead() {
+                continue;
+            }
+
+            let closest_enemy = others_query
+                .iter()
+                .filter(
+                    |(_, other_transform, other_team, other_health, other_is_flying)| {
+                        !other_is_flying
+                            && is_other_valid_target(
+                                &alliances,
+                                team,
+                                other_health,
+                                other_team,
+                                transform,
+                                other_transform,
+                                get_chase_distance(window),
+                            )
+                    },
+                )
+                .map(|(_, other_transform, _, _, _)| {
+                    other_transform.translation.truncate() - transform.translation.truncate()
+                })
+                .min_by(|a, b| a.length().partial_cmp(&b.length()).unwrap());
+
+            let Some(direction) = closest_enemy else {
+                continue;
+            };
+
+            if direction.length() > kamikaze_behavior.contact_distance {
+                velocity.0 = direction.normalize_or_zero();
+                continue;
+            }
+
+            let center = transform.translation.truncate();
+            let damage = stats.map_or(kamikaze_behavior.damage, |stats| {
+                stats
+                    .apply(Stat::Damage, kamikaze_behavior.damage as f32)
+                    .round() as i32
+            });
+            apply_area_damage(
+                &alliances,
+                center,
+                kamikaze_behavior.explosion_radius,
+                damage,
+                kamikaze_behavior.damage_kind,
+                team,
+                others_query
+                    .iter()
+                    .map(|(other, other_transform, other_team, other_health, _)| {
+                        (other, other_transform, other_team, other_health)
+                    }),
+                &mut damage_writer,
+            );
+            spawn_explosion(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                &mut flash_limiter,
+                &vfx_settings,
+                center,
+                kamikaze_behavior.explosion_radius,
+            );
+
+            // Goes up in its own blast through the damage pipeline like any other death, so it
+            // still leaves particles, a dent in the morale around it and a gravestone behind
             velocity.0 = Vec2::ZERO;
+            damage_writer.send(Damage {
+                target: entity,
+                amount: health.current,
+                kind: kamikaze_behavior.damage_kind,
+                armor_piercing: true,
+                source: None,
+                critical: false,
+            });
+        }
+    }
+}
+
+// Swaps the team of converted units and hands them the behaviors of their new side, the state
+// machine then picks a fitting behavior for them on the next frame.
+pub fn apply_conversions(
+    mut commands: Commands,
+    mut convert_reader: EventReader<Convert>,
+    mut query: Query<(&mut CurrentTeam, &Health, &Convertible, &mut Velocity)>,
+) {
+    for convert in convert_reader.read() {
+        let Ok((mut team, health, convertible, mut velocity)) = query.get_mut(convert.target)
+        else {
+            continue;
+        };
+
+        if health.is_dead() || team.0 == convert.team {
+            continue;
         }
+
+        team.0 = convert.team;
+        velocity.0 = Vec2::ZERO;
+
+        let mut entity = commands.entity(convert.target);
+        convertible.0.clone().insert_into(&mut entity);
+        entity.remove::<Convertible>();
+    }
+}
+
+pub fn execute_behavior_spawning(
+    time: Res<Time>,
+    mut query: Query<(&CurrentBehavior, &mut SpawningBehavior, &mut Velocity)>,
+) {
+    query
+        .par_iter_mut()
+        .for_each(|(current_behavior, mut spawning, mut velocity)| {
+            if let Behavior::Spawning(_) = current_behavior.0 {
+                velocity.0 = Vec2::ZERO;
+                spawning.remaining -= time.delta_seconds();
+            }
+        });
+}
+
+pub fn execute_behavior_dead(mut query: Query<(&CurrentBehavior, &DeadBehavior, &mut Velocity)>) {
+    query
+        .par_iter_mut()
+        .for_each(|(current_behavior, _, mut velocity)| {
+            if let Behavior::Dead(_) = current_behavior.0 {
+                velocity.0 = Vec2::ZERO;
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::{GuardAssignment, GuardBehavior, HealBehavior, Waypoints};
+    use crate::events::{Damage, UnitDied};
+    use crate::test_utils::TestApp;
+    use crate::units::damage::DamageKind;
+    use crate::units::health::Health;
+    use crate::units::team::Team;
+    use crate::units::unit_types::UnitType;
+
+    #[test]
+    fn knight_marches_on_origo_with_nothing_around() {
+        let mut app = TestApp::new();
+        let knight = app.spawn_unit(UnitType::Knight, Team::Good, Vec2::new(900.0, 0.0));
+
+        app.tick(5);
+
+        app.assert_behavior(knight, "Marching");
+        assert!(app.position(knight).x < 900.0);
+    }
+
+    #[test]
+    fn knight_chases_a_cat_that_comes_close() {
+        let mut app = TestApp::new();
+        let knight = app.spawn_unit(UnitType::Knight, Team::Good, Vec2::new(900.0, 0.0));
+        let cat = app.spawn_unit(UnitType::Cat, Team::Evil, Vec2::new(-900.0, 0.0));
+        app.tick(5);
+        app.assert_behavior(knight, "Marching");
+
+        let close = app.position(knight) - Vec2::new(150.0, 0.0);
+        app.set_position(cat, close);
+        app.tick(5);
+
+        app.assert_behavior(knight, "Chasing");
+    }
+
+    #[test]
+    fn killed_knight_stays_dead() {
+        let mut app = TestApp::new();
+        let knight = app.spawn_unit(UnitType::Knight, Team::Good, Vec2::new(900.0, 0.0));
+        app.tick(5);
+
+        app.send_event(Damage {
+            target: knight,
+            amount: 10_000,
+            kind: DamageKind::Physical,
+            armor_piercing: true,
+            source: None,
+            critical: false,
+        });
+        app.tick(5);
+        let position = app.position(knight);
+        app.tick(30);
+
+        app.assert_behavior(knight, "Dead");
+        assert_eq!(app.position(knight), position);
+    }
+
+    #[test]
+    fn acolyte_runs_to_a_warrior_and_calms_down_once_it_is_safe() {
+        let mut app = TestApp::new();
+        let acolyte = app.spawn_unit(UnitType::Acolyte, Team::Evil, Vec2::new(-600.0, 0.0));
+        app.spawn_unit(UnitType::Warrior, Team::Evil, Vec2::new(-600.0, -250.0));
+        let knight = app.spawn_unit(UnitType::Knight, Team::Good, Vec2::new(-350.0, 0.0));
+        app.tick_seconds(0.5);
+
+        // Straight away from the knight would have kept it level with where it started
+        app.assert_behavior(acolyte, "Fleeing");
+        assert!(app.position(acolyte).y < -20.0);
+
+        app.set_position(knight, Vec2::new(3000.0, 0.0));
+        app.tick_seconds(0.5);
+        app.assert_behavior(acolyte, "Fleeing");
+
+        app.tick_seconds(1.0);
+        assert_ne!(app.behavior(acolyte), "Fleeing");
+    }
+
+    #[test]
+    fn guarding_cat_waits_for_the_knight_to_come_to_its_post() {
+        let mut app = TestApp::new();
+        let post = app.app.world.spawn(Transform::default()).id();
+        let cat = app.spawn_unit(UnitType::Cat, Team::Evil, Vec2::ZERO);
+        app.app.world.entity_mut(cat).insert(GuardAssignment(post));
+        app.spawn_unit(UnitType::Knight, Team::Good, Vec2::new(600.0, 0.0));
+        app.tick_seconds(0.5);
+
+        // Close enough to chase, but not close enough to the post
+        app.assert_behavior(cat, "Guarding");
+        assert!(app.position(cat).x < 50.0);
+
+        app.tick_seconds(2.0);
+        app.assert_behavior(cat, "Attacking");
+    }
+
+    #[test]
+    fn guarding_acolyte_holds_its_post_instead_of_going_after_intruders() {
+        let mut app = TestApp::new();
+        let post = app.app.world.spawn(Transform::default()).id();
+        let acolyte = app.spawn_unit(UnitType::Acolyte, Team::Evil, Vec2::ZERO);
+        app.app
+            .world
+            .entity_mut(acolyte)
+            .insert(GuardAssignment(post));
+        // Wide enough that the knight is inside it while still too far off to flee from
+        app.get_mut::<GuardBehavior>(acolyte).radius = 1000.0;
+        app.spawn_unit(UnitType::Knight, Team::Good, Vec2::new(800.0, 0.0));
+        app.tick_seconds(0.5);
+
+        app.assert_behavior(acolyte, "Guarding");
+        assert!(app.position(acolyte).distance(Vec2::ZERO) < 1.0);
+    }
+
+    #[test]
+    fn patrolling_knight_walks_its_route_until_a_cat_comes_close() {
+        let mut app = TestApp::new();
+        let knight = app.spawn_unit(UnitType::Knight, Team::Good, Vec2::new(900.0, 0.0));
+        let cat = app.spawn_unit(UnitType::Cat, Team::Evil, Vec2::new(-900.0, 0.0));
+        let route = vec![Vec2::new(900.0, 300.0), Vec2::new(900.0, -300.0)];
+        app.app.world.entity_mut(knight).insert(Waypoints(route));
+        app.tick_seconds(0.5);
+
+        // Up to the closest waypoint instead of marching on the altar
+        app.assert_behavior(knight, "Patrolling");
+        assert!(app.position(knight).y > 50.0);
+        assert!(app.position(knight).x > 850.0);
+
+        let close = app.position(knight) - Vec2::new(150.0, 0.0);
+        app.set_position(cat, close);
+        app.tick(5);
+        app.assert_behavior(knight, "Chasing");
+    }
+
+    #[test]
+    fn dark_priest_heals_the_most_hurt_unit_in_range() {
+        let mut app = TestApp::new();
+        let priest = app.spawn_unit(UnitType::DarkPriest, Team::Evil, Vec2::ZERO);
+        let scratched = app.spawn_unit(UnitType::Warrior, Team::Evil, Vec2::new(100.0, 0.0));
+        let wounded = app.spawn_unit(UnitType::Warrior, Team::Evil, Vec2::new(-100.0, 0.0));
+        // Once their max health has been rolled
+        app.tick(2);
+        app.get_mut::<Health>(scratched).current -= 10;
+        app.get_mut::<Health>(wounded).current -= 100;
+        let scratched_health = app.get::<Health>(scratched).current;
+        let wounded_health = app.get::<Health>(wounded).current;
+        app.tick_seconds(1.5);
+
+        app.assert_behavior(priest, "Healing");
+        assert_eq!(app.get::<HealBehavior>(priest).target, Some(wounded));
+        assert_eq!(app.get::<Health>(wounded).current, wounded_health + 12);
+        assert_eq!(app.get::<Health>(scratched).current, scratched_health);
+    }
+
+    #[test]
+    fn dark_priest_shoots_down_a_gargoyle() {
+        let mut app = TestApp::new();
+        let priest = app.spawn_unit(UnitType::DarkPriest, Team::Evil, Vec2::ZERO);
+        let gargoyle = app.spawn_unit(UnitType::Gargoyle, Team::Good, Vec2::new(180.0, 0.0));
+        app.tick(2);
+        let health = app.get::<Health>(gargoyle).current;
+        app.tick_seconds(0.5);
+
+        app.assert_behavior(priest, "Attacking");
+        app.tick_seconds(3.0);
+        assert!(app.get::<Health>(gargoyle).current < health);
+    }
+    #[test]
+    fn imp_dies_in_its_own_explosion_like_any_other_unit() {
+        let mut app = TestApp::new();
+        let imp = app.spawn_unit(UnitType::Imp, Team::Evil, Vec2::ZERO);
+        app.spawn_unit(UnitType::Knight, Team::Good, Vec2::new(20.0, 0.0));
+        // Checked every frame, the event doesn't stay around for long
+        for _ in 0..10 {
+            app.tick(1);
+            if app.get::<Health>(imp).is_dead() {
+                break;
+            }
+        }
+
+        assert!(app.get::<Health>(imp).is_dead());
+        let died = app.app.world.resource::<Events<UnitDied>>();
+        assert!(died
+            .get_reader()
+            .read(died)
+            .any(|died| died.entity == imp && died.killer.is_none()));
     }
 }
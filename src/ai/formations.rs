@@ -0,0 +1,161 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::events::GameEvent;
+use crate::game_view::GameAction;
+use crate::player::plugin::Player;
+use crate::settings::bindings::Binding;
+use crate::settings::config::GameSettings;
+use crate::units::health::Health;
+use crate::units::team::{CurrentTeam, Team};
+use crate::velocity::Velocity;
+
+use super::behavior::FollowFormationBehavior;
+
+const SLOT_SPACING: f32 = 56.0;
+const MIN_RING_RADIUS: f32 = 96.0;
+// The line forms up this far ahead of the necromancer
+const LINE_DISTANCE: f32 = 80.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FormationShape {
+    Off,
+    #[default]
+    Ring,
+    Wedge,
+    Line,
+}
+
+impl FormationShape {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FormationShape::Off => "Off",
+            FormationShape::Ring => "Ring",
+            FormationShape::Wedge => "Wedge",
+            FormationShape::Line => "Line",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            FormationShape::Off => FormationShape::Ring,
+            FormationShape::Ring => FormationShape::Wedge,
+            FormationShape::Wedge => FormationShape::Line,
+            FormationShape::Line => FormationShape::Off,
+        }
+    }
+
+    // Offset of slot `index` out of `count`, relative to the player facing `forward`
+    pub fn slot_offset(&self, index: usize, count: usize, forward: Vec2) -> Option<Vec2> {
+        let right = Vec2::new(forward.y, -forward.x);
+        match self {
+            FormationShape::Off => None,
+            FormationShape::Ring => {
+                let radius = (count as f32 * SLOT_SPACING / TAU).max(MIN_RING_RADIUS);
+                let angle = index as f32 / count as f32 * TAU;
+                Some(Vec2::from_angle(angle) * radius)
+            }
+            // The necromancer at the tip, the two arms trailing behind to either side
+            FormationShape::Wedge => {
+                let rank = (index / 2 + 1) as f32;
+                let side = if index.is_multiple_of(2) { -1.0 } else { 1.0 };
+                Some((-forward + right * side * 0.75) * rank * SLOT_SPACING)
+            }
+            FormationShape::Line => {
+                let centered = index as f32 - (count as f32 - 1.0) * 0.5;
+                Some(forward * LINE_DISTANCE + right * centered * SLOT_SPACING)
+            }
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct Formation {
+    pub shape: FormationShape,
+    // The way the player last moved, wedges and lines face this way
+    pub forward: Vec2,
+}
+
+impl Default for Formation {
+    fn default() -> Self {
+        Self {
+            shape: FormationShape::default(),
+            forward: Vec2::Y,
+        }
+    }
+}
+
+// Where in the world every unit in the formation should stand this frame
+#[derive(Resource, Default)]
+pub struct FormationSlots(pub HashMap<Entity, Vec2>);
+
+// F cycles through the formations, off included
+pub fn system(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<GameSettings>,
+    formation: Res<Formation>,
+    mut actions: EventWriter<GameAction>,
+) {
+    if settings.bindings.just_pressed(&keys, Binding::Formation) {
+        actions.send(GameAction::Formation(formation.shape.next()));
+    }
+}
+
+pub fn apply_formation_actions(
+    mut actions: EventReader<GameAction>,
+    mut formation: ResMut<Formation>,
+) {
+    for action in actions.read() {
+        if let GameAction::Formation(shape) = action {
+            info!("Formation: {}", shape.name());
+            formation.shape = *shape;
+        }
+    }
+}
+
+pub fn assign_formation_slots(
+    mut formation: ResMut<Formation>,
+    mut slots: ResMut<FormationSlots>,
+    player_query: Query<(&Transform, &Velocity), With<Player>>,
+    units_query: Query<(Entity, &CurrentTeam, &Health), With<FollowFormationBehavior>>,
+) {
+    slots.0.clear();
+    let Some((player_transform, player_velocity)) = player_query.iter().next() else {
+        return;
+    };
+
+    if player_velocity.0 != Vec2::ZERO {
+        formation.forward = player_velocity.0.normalize();
+    }
+
+    // Sorted so everyone keeps the same slot from one frame to the next
+    let mut units = units_query
+        .iter()
+        .filter(|(_, team, health)| team.0 == Team::Evil && !health.is_dead())
+        .map(|(entity, _, _)| entity)
+        .collect::<Vec<_>>();
+    units.sort();
+
+    let center = player_transform.translation.truncate();
+    for (index, entity) in units.iter().enumerate() {
+        if let Some(offset) = formation
+            .shape
+            .slot_offset(index, units.len(), formation.forward)
+        {
+            slots.0.insert(*entity, center + offset);
+        }
+    }
+}
+
+pub fn reset_formation_system(
+    mut event_reader: EventReader<GameEvent>,
+    mut formation: ResMut<Formation>,
+) {
+    for event in event_reader.read() {
+        if let GameEvent::StartGame = event {
+            formation.forward = Vec2::Y;
+        }
+    }
+}
@@ -1,23 +1,66 @@
 use bevy::prelude::*;
 
-use crate::ai::behavior;
+use crate::ai::{aggro, behavior, formations, sleep};
+use crate::player::relics::not_choosing;
+use crate::schedule::{FrameSet, SimSet};
+use crate::ui::nameplate::not_renaming;
 
 pub struct AiPlugin;
 
 impl Plugin for AiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                behavior::behavior_state_machine,
-                behavior::execute_behavior_idle,
-                behavior::execute_behavior_move_origo,
-                behavior::execute_behavior_wander,
-                behavior::execute_behavior_chase,
-                behavior::execute_behavior_flee,
-                behavior::execute_behavior_attack,
-                behavior::execute_behavior_dead,
-            ),
-        );
+        app.init_resource::<formations::Formation>()
+            .init_resource::<formations::FormationSlots>()
+            .init_resource::<sleep::SleepCheck>()
+            .add_systems(
+                Update,
+                (
+                    behavior::apply_conversions,
+                    formations::system
+                        .run_if(not_renaming)
+                        .run_if(not_choosing)
+                        .in_set(FrameSet::Input),
+                    formations::reset_formation_system,
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    (
+                        formations::apply_formation_actions,
+                        formations::assign_formation_slots,
+                    )
+                        .chain()
+                        .in_set(SimSet::Input),
+                    (
+                        (
+                            sleep::update_sleep,
+                            sleep::wake_up,
+                            aggro::record_threat,
+                            aggro::update_aggro,
+                            behavior::behavior_state_machine,
+                        )
+                            .chain(),
+                        (
+                            behavior::execute_behavior_idle,
+                            behavior::execute_behavior_move_origo,
+                            behavior::execute_behavior_wander,
+                            behavior::execute_behavior_move_to_rally,
+                            behavior::execute_behavior_follow_formation,
+                            behavior::execute_behavior_guard,
+                            behavior::execute_behavior_patrol,
+                            behavior::execute_behavior_chase,
+                            behavior::execute_behavior_flee,
+                            behavior::execute_behavior_heal,
+                            behavior::execute_behavior_attack,
+                            behavior::execute_behavior_kamikaze,
+                            behavior::execute_behavior_spawning,
+                            behavior::execute_behavior_dead,
+                        ),
+                    )
+                        .chain()
+                        .in_set(SimSet::Ai),
+                ),
+            );
     }
 }
@@ -0,0 +1,180 @@
+use std::sync::Mutex;
+
+use bevy::prelude::*;
+
+use super::behavior::{Behavior, CurrentBehavior, GuardAssignment};
+use super::formations::FormationSlots;
+use crate::camera::{self, CameraController};
+use crate::player::command_mode::{PatrolRoute, RallyPoint};
+use crate::render_scale::WorldCamera;
+use crate::units::health::Health;
+use crate::units::team::{AllianceMatrix, CurrentTeam, Team};
+use crate::velocity::{Motion, Velocity};
+
+// Who gets to sleep is only worked out a few times a second, nothing is close enough to notice
+const SLEEP_CHECK_SECONDS: f32 = 0.25;
+// Of the window width. Nothing hostile within the first and a unit can doze off, anything
+// within the second wakes it back up. Both are further out than anything chases from, so a unit
+// is awake before it could be chasing or chased, and the gap keeps it from flickering.
+const SLEEP_DISTANCE: f32 = 0.6;
+const WAKE_DISTANCE: f32 = 0.5;
+// Units just past the edge of the view still count as seen, so nobody freezes as they leave it
+const VIEW_MARGIN: f32 = 64.0;
+
+// Left out of the behavior, animation and movement systems. Only units that are idling or
+// wandering far from anything hostile and out of view get it, late in a run that's most of the
+// summoner's army waiting around the altar while the fighting is somewhere else.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Asleep;
+
+#[derive(Resource)]
+pub struct SleepCheck(Timer);
+
+impl Default for SleepCheck {
+    fn default() -> Self {
+        Self(Timer::from_seconds(
+            SLEEP_CHECK_SECONDS,
+            TimerMode::Repeating,
+        ))
+    }
+}
+
+// Doing nothing in particular, which is what a unit wakes up still doing
+fn can_sleep(behavior: &Behavior) -> bool {
+    matches!(behavior, Behavior::Idle(_) | Behavior::Wander(_))
+}
+
+type SleepData = (
+    Entity,
+    &'static CurrentBehavior,
+    &'static Transform,
+    &'static CurrentTeam,
+    &'static Health,
+    &'static mut Velocity,
+    Option<&'static mut Motion>,
+    Has<Asleep>,
+);
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_sleep(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut sleep_check: ResMut<SleepCheck>,
+    alliances: Res<AllianceMatrix>,
+    window_query: Query<&Window>,
+    camera_query: Query<&CameraController, With<WorldCamera>>,
+    mut query: Query<SleepData>,
+    others_query: Query<(&Transform, &CurrentTeam, &Health)>,
+) {
+    if !sleep_check.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    // Without a camera, the same as in the tests, nothing is ever in view
+    let view = camera_query
+        .get_single()
+        .ok()
+        .map(|camera| camera::view_rect(camera, window, VIEW_MARGIN));
+    let hostiles: Vec<(Vec2, Team)> = others_query
+        .iter()
+        .filter(|(_, _, health)| !health.is_dead())
+        .map(|(transform, team, _)| (transform.translation.truncate(), team.0))
+        .collect();
+
+    let changes = Mutex::new(Vec::new());
+    query.par_iter_mut().for_each(
+        |(entity, current_behavior, transform, team, health, mut velocity, motion, asleep)| {
+            let position = transform.translation.truncate();
+            let in_view = view.is_some_and(|view| view.contains(position));
+            let distance = if asleep {
+                WAKE_DISTANCE
+            } else {
+                SLEEP_DISTANCE
+            } * window.width();
+            let hostile_close = hostiles.iter().any(|(other_position, other_team)| {
+                alliances.is_hostile(team.0, *other_team)
+                    && position.distance_squared(*other_position) < distance * distance
+            });
+
+            let should_sleep =
+                can_sleep(&current_behavior.0) && !health.is_dead() && !in_view && !hostile_close;
+            if should_sleep && !asleep {
+                // Wanderers would otherwise still be walking whenever they wake up
+                velocity.0 = Vec2::ZERO;
+                if let Some(mut motion) = motion {
+                    motion.current = Vec2::ZERO;
+                }
+                changes.lock().unwrap().push((entity, true));
+            } else if !should_sleep && asleep {
+                changes.lock().unwrap().push((entity, false));
+            }
+        },
+    );
+
+    for (entity, sleep) in changes.into_inner().unwrap() {
+        if sleep {
+            commands.entity(entity).insert(Asleep);
+        } else {
+            commands.entity(entity).remove::<Asleep>();
+        }
+    }
+}
+
+type WakeData = (
+    Entity,
+    &'static CurrentTeam,
+    Ref<'static, Health>,
+    Option<Ref<'static, GuardAssignment>>,
+);
+
+// Anything that has to be answered right away doesn't wait for the next check. Hits and heals
+// show up as a change to Health, and orders from the summoner go to everyone they're for.
+pub fn wake_up(
+    mut commands: Commands,
+    rally_point: Res<RallyPoint>,
+    patrol_route: Res<PatrolRoute>,
+    formation_slots: Res<FormationSlots>,
+    query: Query<WakeData, With<Asleep>>,
+) {
+    let rallying = (rally_point.is_changed() && rally_point.0.is_some())
+        || (patrol_route.is_changed() && !patrol_route.0.is_empty());
+    for (entity, team, health, guard_assignment) in query.iter() {
+        if health.is_changed()
+            || guard_assignment.is_some_and(|assignment| assignment.is_changed())
+            || (rallying && team.0 == Team::Evil)
+            || formation_slots.0.contains_key(&entity)
+        {
+            commands.entity(entity).remove::<Asleep>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::Asleep;
+    use crate::test_utils::TestApp;
+    use crate::units::team::Team;
+    use crate::units::unit_types::UnitType;
+
+    #[test]
+    fn lone_cat_dozes_off_and_wakes_when_a_knight_comes_close() {
+        let mut app = TestApp::new();
+        let cat = app.spawn_unit(UnitType::Cat, Team::Evil, Vec2::new(-900.0, 0.0));
+        let knight = app.spawn_unit(UnitType::Knight, Team::Good, Vec2::new(2500.0, 0.0));
+        app.tick_seconds(0.5);
+
+        assert!(app.app.world.get::<Asleep>(cat).is_some());
+        let position = app.position(cat);
+
+        app.set_position(knight, Vec2::new(-300.0, 0.0));
+        app.tick_seconds(0.5);
+
+        assert!(app.app.world.get::<Asleep>(cat).is_none());
+        app.assert_behavior(cat, "Chasing");
+        assert_ne!(app.position(cat), position);
+    }
+}
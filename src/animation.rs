@@ -1,6 +1,34 @@
-use crate::{ai::behavior::AttackBehavior, units::health::Health, velocity::Velocity};
-use bevy::prelude::*;
+use crate::{
+    ai::{behavior::AttackBehavior, sleep::Asleep},
+    aseprite::{sidecar_path, AsepriteSheet, ImportedSheets},
+    camera::{self, CameraController},
+    events::{AnimationFinished, PlayAnimation, SpellCast, UnitSummoned},
+    movement::Movement,
+    player::{hurt::Hurt, plugin::Player},
+    render_scale::WorldCamera,
+    time_of_day::DayNight,
+    units::health::Health,
+    units::stat_modifiers::{Stat, StatModifiers},
+    validate::asset_root,
+    velocity::{Motion, Velocity},
+};
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashMap};
+use std::{borrow::Cow, time::SystemTime};
 
+// How long the outgoing animation takes to fade out while the incoming one fades in
+const CROSSFADE_SECONDS: f32 = 0.1;
+// Slower than this and a unit keeps facing wherever it was last going
+const FACING_DEAD_ZONE: f32 = 1.0;
+// What every sheet played at before they could each pick their own
+pub const DEFAULT_FPS: f32 = 10.0;
+// A unit barely pushing the stick still looks like it's walking
+const MIN_WALK_ANIMATION_SPEED: f32 = 0.3;
+const SHEET_POLL_SECONDS: f32 = 1.0;
+// Units this far past the edge of the view still animate, so nothing walks in on a stale frame
+const ANIMATION_VIEW_MARGIN: f32 = 128.0;
+
+// The ones the state machine picks between on its own, anything else a unit has a sheet for is
+// Named and only plays when asked for with PlayAnimation
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub enum AnimationType {
     #[default]
@@ -9,21 +37,116 @@ pub enum AnimationType {
     Hit,
     Death,
     Attack,
+    Named(Cow<'static, str>),
+}
+
+impl AnimationType {
+    pub const CAST: AnimationType = AnimationType::named("cast");
+    pub const SPAWN: AnimationType = AnimationType::named("spawn");
+    pub const CELEBRATE: AnimationType = AnimationType::named("celebrate");
+
+    pub const fn named(name: &'static str) -> Self {
+        AnimationType::Named(Cow::Borrowed(name))
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            AnimationType::Idle => "idle",
+            AnimationType::Walk => "walk",
+            AnimationType::Hit => "hit",
+            AnimationType::Death => "death",
+            AnimationType::Attack => "attack",
+            AnimationType::Named(name) => name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Facing {
+    Left,
+    #[default]
+    Right,
+    Up,
+    Down,
+}
+
+impl Facing {
+    // Whichever axis the unit is moving along the most wins
+    pub fn from_velocity(velocity: Vec2) -> Option<Self> {
+        if velocity.length() < FACING_DEAD_ZONE {
+            None
+        } else if velocity.x.abs() >= velocity.y.abs() {
+            Some(if velocity.x < 0.0 {
+                Facing::Left
+            } else {
+                Facing::Right
+            })
+        } else {
+            Some(if velocity.y < 0.0 {
+                Facing::Down
+            } else {
+                Facing::Up
+            })
+        }
+    }
 }
 
 #[derive(Component, Clone, Default)]
 pub struct Animation {
     pub animation_type: AnimationType,
+    // Sheets exported with several animations on them start some of them partway in
+    pub first_atlas_index: usize,
     pub last_atlas_index: usize,
     pub is_looping: bool,
     pub frame_timer: Timer,
+    // How much of this animation is showing, 1 once the unit has fully switched over to it
+    pub blend: f32,
+    // Only shown while the unit faces this way, None for the sheet that's flipped to face left
+    pub direction: Option<Facing>,
+    // Multiplies the sheet's own frame rate, kept up to date by update_animation_speed
+    pub speed: f32,
+    // Played once this one is done, only for the ones that don't loop
+    pub on_finish: Option<AnimationType>,
+}
+
+impl Animation {
+    pub fn is_current(&self, current_animation: &CurrentAnimation) -> bool {
+        self.animation_type == current_animation.animation_type
+            && self.direction == current_animation.direction
+    }
 }
 
 #[derive(Component, Debug, Clone, PartialEq, Eq, Default)]
 pub struct CurrentAnimation {
     pub animation_type: AnimationType,
+    pub facing: Facing,
+    // Kept from the last time the unit went sideways, so walking up or down doesn't flip it back
+    pub facing_left: bool,
+    // The facing of the sheet that's playing, None when the unit has no sheet for its facing
+    pub direction: Option<Facing>,
+    // The one-shot that finished and handed over to the animation playing now. The state machine
+    // leaves it be for as long as it keeps asking for the finished one, instead of replaying it.
+    pub chained_from: Option<AnimationType>,
+    pub requested: Option<RequestedAnimation>,
 }
 
+// Plays over whatever the state machine picks until it's done, the unit dies, or the unit goes
+// from what it was doing when it was asked to something else
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestedAnimation {
+    pub animation_type: AnimationType,
+    pub over: AnimationType,
+}
+
+// Color applied to every animation child of a unit, lets units share sprite sheets
+#[derive(Component, Clone, Copy)]
+pub struct Tint(pub Color);
+
+// On units the camera can't see. Their looping animations hold on the frame they were at until
+// the unit is back in view, the ones that end still play out since deaths and hits wait on them.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct OffScreen;
+
 #[derive(Bundle, Clone, Default)]
 pub struct AnimationBundle {
     /// Specifies the rendering properties of the sprite, such as color tint and flip.
@@ -50,69 +173,219 @@ pub struct AnimatedChildSpawnParams {
     pub texture_path: String,
     pub tile_size: Vec2,
     pub grid: (usize, usize),
+    pub first_atlas_index: usize,
     pub last_atlas_index: usize,
     pub animation_type: AnimationType,
     pub is_looping: bool,
     pub is_locked: bool,
+    pub direction: Option<Facing>,
+    pub fps: f32,
+    pub on_finish: Option<AnimationType>,
+}
+
+impl AnimatedChildSpawnParams {
+    // For units that have a sheet drawn for a facing instead of flipping the sideways one
+    pub fn with_direction(mut self, direction: Facing) -> Self {
+        self.direction = Some(direction);
+        self
+    }
+
+    pub fn with_fps(mut self, fps: f32) -> Self {
+        self.fps = fps;
+        self
+    }
+
+    // None holds the last frame once it's done, the way Death does
+    pub fn with_on_finish(mut self, on_finish: Option<AnimationType>) -> Self {
+        self.on_finish = on_finish;
+        self
+    }
 }
 
 impl From<(&str, Vec2, (usize, usize), usize, AnimationType, bool, bool)>
     for AnimatedChildSpawnParams
 {
     fn from(item: (&str, Vec2, (usize, usize), usize, AnimationType, bool, bool)) -> Self {
+        // One-shots go back to standing around, except dying
+        let on_finish = (!item.5 && item.4 != AnimationType::Death).then_some(AnimationType::Idle);
         Self {
             texture_path: item.0.to_owned(),
             tile_size: item.1,
             grid: item.2,
+            first_atlas_index: 0,
             last_atlas_index: item.3,
             animation_type: item.4,
             is_looping: item.5,
             is_locked: item.6,
+            direction: None,
+            fps: DEFAULT_FPS,
+            on_finish,
+        }
+    }
+}
+
+// The sheet, the size of a frame in it and how many frames across and down
+type AtlasKey = (String, [u32; 2], (usize, usize));
+
+// Every unit of a kind cuts its sheets up the same way, so they all share the one layout instead
+// of each summon adding its own copy
+#[derive(Resource, Default)]
+pub struct AtlasCache {
+    layouts: HashMap<AtlasKey, Handle<TextureAtlasLayout>>,
+}
+
+impl AtlasCache {
+    pub fn sheets(&self) -> impl Iterator<Item = &str> {
+        self.layouts.keys().map(|(path, _, _)| path.as_str())
+    }
+}
+
+// What spawning animated units needs to get at the layouts, cached or not, and the Aseprite
+// exports that say how the sheets are cut up
+#[derive(SystemParam)]
+pub struct AtlasLayouts<'w> {
+    layouts: ResMut<'w, Assets<TextureAtlasLayout>>,
+    cache: ResMut<'w, AtlasCache>,
+    sheets: Res<'w, Assets<AsepriteSheet>>,
+    imported: Res<'w, ImportedSheets>,
+}
+
+impl AtlasLayouts<'_> {
+    pub fn import(&self, params: AnimatedChildSpawnParams) -> AnimatedChildSpawnParams {
+        self.imported.import(&self.sheets, params)
+    }
+
+    pub fn get_or_add(&mut self, params: &AnimatedChildSpawnParams) -> Handle<TextureAtlasLayout> {
+        let key = (
+            params.texture_path.clone(),
+            params.tile_size.to_array().map(f32::to_bits),
+            params.grid,
+        );
+        if let Some(layout) = self.cache.layouts.get(&key) {
+            return layout.clone();
+        }
+
+        let layout = self.layouts.add(TextureAtlasLayout::from_grid(
+            params.tile_size,
+            params.grid.0,
+            params.grid.1,
+            None,
+            None,
+        ));
+        self.cache.layouts.insert(key, layout.clone());
+        layout
+    }
+}
+
+// Remembers when each sheet was last saved, only around in debug builds
+#[derive(Resource)]
+pub struct SheetWatcher {
+    timer: Timer,
+    modified: HashMap<String, SystemTime>,
+}
+
+impl Default for SheetWatcher {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(SHEET_POLL_SECONDS, TimerMode::Repeating),
+            modified: HashMap::new(),
+        }
+    }
+}
+
+// Bevy's own file watcher needs a feature that pulls in more dependencies, looking at the few
+// sheets that are in use once a second does the job. The image is swapped under the handle every
+// sprite already has, a sheet whose grid changed needs a restart.
+pub fn reload_changed_sheets(
+    time: Res<Time<Real>>,
+    asset_server: Res<AssetServer>,
+    cache: Res<AtlasCache>,
+    mut watcher: ResMut<SheetWatcher>,
+) {
+    if !watcher.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let root = asset_root();
+    let exports: Vec<String> = cache.sheets().map(sidecar_path).collect();
+    for path in cache.sheets().chain(exports.iter().map(String::as_str)) {
+        let Ok(modified) = std::fs::metadata(root.join(path)).and_then(|meta| meta.modified())
+        else {
+            continue;
+        };
+        // The first look only remembers it, the sheet was loaded fresh with the unit
+        if let Some(previous) = watcher.modified.insert(path.to_owned(), modified) {
+            if previous != modified {
+                info!("Reloading {}", path);
+                asset_server.reload(path.to_owned());
+            }
         }
     }
 }
 
 pub fn spawn_animated_children(
     asset_server: &Res<AssetServer>,
-    texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
+    texture_atlas_layouts: &mut AtlasLayouts,
     parent: &mut ChildBuilder,
     children_params: Vec<AnimatedChildSpawnParams>,
 ) {
-    children_params.into_iter().for_each(|child_param| {
-        let layout = TextureAtlasLayout::from_grid(
-            child_param.tile_size,
-            child_param.grid.0,
-            child_param.grid.1,
-            None,
-            None,
-        );
-
-        let texture_atlas_layout = texture_atlas_layouts.add(layout);
-        parent.spawn(AnimationBundle {
-            texture: asset_server.load(child_param.texture_path),
-            atlas: TextureAtlas {
-                layout: texture_atlas_layout,
-                index: 0,
-            },
-            transform: Transform::default(),
-            animation: Animation {
-                animation_type: child_param.animation_type,
-                last_atlas_index: child_param.last_atlas_index,
-                is_looping: child_param.is_looping,
-                frame_timer: Timer::from_seconds(0.1, TimerMode::Once),
-            },
-            ..Default::default()
+    resolve_animated_children(asset_server, texture_atlas_layouts, children_params)
+        .into_iter()
+        .for_each(|bundle| {
+            parent.spawn(bundle);
         });
-    });
+}
+
+// The sprites of a unit with their textures and atlases already looked up, so a batch of the
+// same unit only pays for the import, the layout lookup and the asset server once
+pub fn resolve_animated_children(
+    asset_server: &Res<AssetServer>,
+    texture_atlas_layouts: &mut AtlasLayouts,
+    children_params: Vec<AnimatedChildSpawnParams>,
+) -> Vec<AnimationBundle> {
+    children_params
+        .into_iter()
+        .map(|child_param| {
+            let child_param = texture_atlas_layouts.import(child_param);
+            let texture_atlas_layout = texture_atlas_layouts.get_or_add(&child_param);
+            // Units start out idle, there's nothing to fade in from
+            let blend = if child_param.animation_type == AnimationType::default()
+                && child_param.direction.is_none()
+            {
+                1.0
+            } else {
+                0.0
+            };
+            AnimationBundle {
+                texture: asset_server.load(child_param.texture_path),
+                atlas: TextureAtlas {
+                    layout: texture_atlas_layout,
+                    index: child_param.first_atlas_index,
+                },
+                transform: Transform::default(),
+                animation: Animation {
+                    animation_type: child_param.animation_type,
+                    first_atlas_index: child_param.first_atlas_index,
+                    last_atlas_index: child_param.last_atlas_index,
+                    is_looping: child_param.is_looping,
+                    frame_timer: Timer::from_seconds(1.0 / child_param.fps, TimerMode::Once),
+                    blend,
+                    direction: child_param.direction,
+                    speed: 1.0,
+                    on_finish: child_param.on_finish,
+                },
+                ..Default::default()
+            }
+        })
+        .collect()
 }
 
 // Don't we just love hacky game jam code?
 fn get_animation_type(
     health: &Health,
+    hurt: bool,
     velocity: &Velocity,
-    children: &Children,
     attack_behavior: Option<&mut AttackBehavior>,
-    child_query: &mut Query<(&mut Sprite, &mut Animation, &mut TextureAtlas)>,
 ) -> AnimationType {
     let run_attack = match attack_behavior {
         Some(attack_behavior) => attack_behavior.is_attacking,
@@ -121,111 +394,290 @@ fn get_animation_type(
 
     if health.is_dead() {
         AnimationType::Death
+    } else if hurt {
+        AnimationType::Hit
     } else if run_attack {
         AnimationType::Attack
     } else if velocity.0.length() > 0.0 {
-        for child in children.iter() {
-            if let Ok((mut sprite, _, _)) = child_query.get_mut(*child) {
-                if velocity.0.x != 0.0 {
-                    sprite.flip_x = velocity.0.x < 0.0;
-                }
-            }
-        }
-
         AnimationType::Walk
     } else {
         AnimationType::Idle
     }
 }
 
+// Keeps facing the same way through attacks, hits and standing still, only moving turns a unit
+fn update_facing(
+    current_animation: &mut Mut<CurrentAnimation>,
+    velocity: &Velocity,
+    children: &Children,
+    child_query: &mut Query<(&mut Sprite, &mut Animation, &mut TextureAtlas)>,
+) {
+    if let Some(facing) = Facing::from_velocity(velocity.0) {
+        if current_animation.facing != facing {
+            current_animation.facing = facing;
+        }
+        if velocity.0.x != 0.0 && current_animation.facing_left != (velocity.0.x < 0.0) {
+            current_animation.facing_left = velocity.0.x < 0.0;
+        }
+    }
+
+    for child in children.iter() {
+        if let Ok((mut sprite, animation, _)) = child_query.get_mut(*child) {
+            // A sheet drawn for a facing is already the right way around
+            let flip_x = animation.direction.is_none() && current_animation.facing_left;
+            if sprite.flip_x != flip_x {
+                sprite.flip_x = flip_x;
+            }
+        }
+    }
+}
+
 fn update_current_animation(
-    current_animation: &mut CurrentAnimation,
+    current_animation: &mut Mut<CurrentAnimation>,
     animation_type: AnimationType,
     children: &Children,
     child_query: &mut Query<(&mut Sprite, &mut Animation, &mut TextureAtlas)>,
 ) {
-    if current_animation.animation_type == animation_type {
+    if current_animation.chained_from.as_ref() == Some(&animation_type) {
+        return;
+    }
+
+    let facing = current_animation.facing;
+    let has_directional_sheet = children.iter().any(|child| {
+        child_query.get(*child).is_ok_and(|(_, animation, _)| {
+            animation.animation_type == animation_type && animation.direction == Some(facing)
+        })
+    });
+    let direction = has_directional_sheet.then_some(facing);
+    if current_animation.animation_type == animation_type
+        && current_animation.direction == direction
+    {
         return;
     }
 
     current_animation.animation_type = animation_type;
+    current_animation.direction = direction;
+    current_animation.chained_from = None;
     for child in children.iter() {
         if let Ok((_, mut animation, mut atlas)) = child_query.get_mut(*child) {
-            if animation.animation_type == current_animation.animation_type {
-                atlas.index = 0;
+            if animation.is_current(current_animation) {
+                atlas.index = animation.first_atlas_index;
                 animation.frame_timer.reset();
             }
         }
     }
 }
 
-pub fn animation_state_machine(
-    mut query: Query<
-        (&mut CurrentAnimation, &Health, &Velocity, &Children),
-        Without<AttackBehavior>,
-    >,
-    mut query_with_attack: Query<(
-        &mut CurrentAnimation,
-        &Health,
-        &Velocity,
-        &mut AttackBehavior,
-        &Children,
-    )>,
+fn apply_state_animation(
+    current_animation: &mut Mut<CurrentAnimation>,
+    animation_type: AnimationType,
+    children: &Children,
+    child_query: &mut Query<(&mut Sprite, &mut Animation, &mut TextureAtlas)>,
+) {
+    if let Some(requested) = &current_animation.requested {
+        if animation_type != AnimationType::Death && animation_type == requested.over {
+            return;
+        }
+        current_animation.requested = None;
+    }
+
+    update_current_animation(current_animation, animation_type, children, child_query);
+}
+
+// Units that don't have the animation asked for just carry on, so gameplay can ask any unit
+pub fn play_requested_animations(
+    mut requests: EventReader<PlayAnimation>,
+    mut query: Query<(&mut CurrentAnimation, &Health, &Children)>,
     mut child_query: Query<(&mut Sprite, &mut Animation, &mut TextureAtlas)>,
 ) {
-    for (mut current_animation, health, velocity, children) in query.iter_mut() {
+    for request in requests.read() {
+        let Ok((mut current_animation, health, children)) = query.get_mut(request.entity) else {
+            continue;
+        };
+        let has_animation = children.iter().any(|child| {
+            child_query
+                .get(*child)
+                .is_ok_and(|(_, animation, _)| animation.animation_type == request.animation_type)
+        });
+        if health.is_dead() || !has_animation {
+            continue;
+        }
+
+        let over = current_animation
+            .requested
+            .take()
+            .map_or(current_animation.animation_type.clone(), |requested| {
+                requested.over
+            });
         update_current_animation(
             &mut current_animation,
-            get_animation_type(health, velocity, children, None, &mut child_query),
+            request.animation_type.clone(),
+            children,
+            &mut child_query,
+        );
+        current_animation.requested = Some(RequestedAnimation {
+            animation_type: request.animation_type.clone(),
+            over,
+        });
+    }
+}
+
+// Summons step out of the ritual and the summoner casts, for the units that have sheets for it
+pub fn request_gameplay_animations(
+    mut summoned_reader: EventReader<UnitSummoned>,
+    mut cast_reader: EventReader<SpellCast>,
+    player_query: Query<Entity, With<Player>>,
+    mut play_writer: EventWriter<PlayAnimation>,
+) {
+    for summoned in summoned_reader.read() {
+        play_writer.send(PlayAnimation {
+            entity: summoned.entity,
+            animation_type: AnimationType::SPAWN,
+        });
+    }
+    if cast_reader.read().count() > 0 {
+        for entity in player_query.iter() {
+            play_writer.send(PlayAnimation {
+                entity,
+                animation_type: AnimationType::CAST,
+            });
+        }
+    }
+}
+
+type AnimatedData = (
+    &'static mut CurrentAnimation,
+    &'static Health,
+    Has<Hurt>,
+    &'static Velocity,
+    &'static Children,
+);
+type NotAttackingFilter = (Without<AttackBehavior>, Without<Asleep>);
+type AnimatedAttackData = (
+    &'static mut CurrentAnimation,
+    &'static Health,
+    Has<Hurt>,
+    &'static Velocity,
+    &'static mut AttackBehavior,
+    &'static Children,
+);
+
+pub fn animation_state_machine(
+    mut query: Query<AnimatedData, NotAttackingFilter>,
+    mut query_with_attack: Query<AnimatedAttackData, Without<Asleep>>,
+    mut child_query: Query<(&mut Sprite, &mut Animation, &mut TextureAtlas)>,
+) {
+    for (mut current_animation, health, hurt, velocity, children) in query.iter_mut() {
+        update_facing(&mut current_animation, velocity, children, &mut child_query);
+        apply_state_animation(
+            &mut current_animation,
+            get_animation_type(health, hurt, velocity, None),
             children,
             &mut child_query,
         );
     }
-    for (mut current_animation, health, velocity, mut attack_behavior, children) in
+    for (mut current_animation, health, hurt, velocity, mut attack_behavior, children) in
         query_with_attack.iter_mut()
     {
-        update_current_animation(
+        update_facing(&mut current_animation, velocity, children, &mut child_query);
+        apply_state_animation(
             &mut current_animation,
-            get_animation_type(
-                health,
-                velocity,
-                children,
-                Some(&mut attack_behavior),
-                &mut child_query,
-            ),
+            get_animation_type(health, hurt, velocity, Some(&mut attack_behavior)),
             children,
             &mut child_query,
         );
     }
 }
 
+// Only worth working out with a camera to see through, without one everything keeps animating
+pub fn has_world_view(camera_query: Query<(), With<WorldCamera>>) -> bool {
+    !camera_query.is_empty()
+}
+
+pub fn mark_offscreen_units(
+    mut commands: Commands,
+    window_query: Query<&Window>,
+    camera_query: Query<&CameraController, With<WorldCamera>>,
+    query: Query<(Entity, &GlobalTransform, Has<OffScreen>), With<CurrentAnimation>>,
+) {
+    let (Ok(window), Ok(camera)) = (window_query.get_single(), camera_query.get_single()) else {
+        return;
+    };
+    let view = camera::view_rect(camera, window, ANIMATION_VIEW_MARGIN);
+
+    for (entity, transform, off_screen) in query.iter() {
+        let seen = view.contains(transform.translation().truncate());
+        if seen && off_screen {
+            commands.entity(entity).remove::<OffScreen>();
+        } else if !seen && !off_screen {
+            commands.entity(entity).insert(OffScreen);
+        }
+    }
+}
+
+type AnimateAttackData = (
+    Entity,
+    &'static mut CurrentAnimation,
+    &'static Children,
+    &'static mut AttackBehavior,
+    Has<OffScreen>,
+);
+type AnimateData = (
+    Entity,
+    &'static mut CurrentAnimation,
+    &'static Children,
+    Has<OffScreen>,
+);
+type AnimatingUnit<'a> = (
+    Entity,
+    Mut<'a, CurrentAnimation>,
+    &'a Children,
+    Option<Mut<'a, AttackBehavior>>,
+    bool,
+);
+
 pub fn animate_sprite(
     time: Res<Time>,
-    mut query_with: Query<(&CurrentAnimation, &Children, &mut AttackBehavior)>,
-    query_without: Query<(&CurrentAnimation, &Children), Without<AttackBehavior>>,
-    mut child_query: Query<(&mut Animation, &mut TextureAtlas)>,
+    mut query_with: Query<AnimateAttackData, Without<Asleep>>,
+    mut query_without: Query<AnimateData, NotAttackingFilter>,
+    mut child_query: Query<(&mut Sprite, &mut Animation, &mut TextureAtlas)>,
+    mut finished_writer: EventWriter<AnimationFinished>,
 ) {
-    let combined_children: Vec<(&CurrentAnimation, &Children, Option<Mut<AttackBehavior>>)> =
-        query_with
-            .iter_mut()
-            .map(|(current_anim, children, attack_behavior)| {
-                (current_anim, children, Some(attack_behavior))
-            }) // Retain Mut<AttackBehavior>
-            .chain(
-                query_without
-                    .iter()
-                    .map(|(current_anim, children)| (current_anim, children, None)),
-            ) // Append children without AttackBehavior
-            .collect();
-
-    for (current_anim, children, mut attack_behavior) in combined_children {
+    let combined_children: Vec<AnimatingUnit> = query_with
+        .iter_mut()
+        .map(
+            |(entity, current_anim, children, attack_behavior, off_screen)| {
+                (
+                    entity,
+                    current_anim,
+                    children,
+                    Some(attack_behavior),
+                    off_screen,
+                )
+            },
+        ) // Retain Mut<AttackBehavior>
+        .chain(
+            query_without
+                .iter_mut()
+                .map(|(entity, current_anim, children, off_screen)| {
+                    (entity, current_anim, children, None, off_screen)
+                }),
+        ) // Append children without AttackBehavior
+        .collect();
+
+    for (entity, mut current_anim, children, mut attack_behavior, off_screen) in combined_children {
+        let mut chain = None;
         for child in children.iter() {
-            if let Ok((mut animation, mut atlas)) = child_query.get_mut(*child) {
-                if current_anim.animation_type != animation.animation_type {
+            if let Ok((_, mut animation, mut atlas)) = child_query.get_mut(*child) {
+                if !animation.is_current(&current_anim) {
+                    continue;
+                }
+                if off_screen && animation.is_looping {
                     continue;
                 }
 
-                if animation.frame_timer.tick(time.delta()).just_finished() {
+                let delta = time.delta().mul_f32(animation.speed);
+                if animation.frame_timer.tick(delta).just_finished() {
                     atlas.index = if atlas.index == animation.last_atlas_index {
                         if let Some(ref mut attack_behavior) = attack_behavior {
                             if attack_behavior.is_attacking {
@@ -235,8 +687,21 @@ pub fn animate_sprite(
 
                         if animation.is_looping {
                             animation.frame_timer.reset();
-                            0
+                            animation.first_atlas_index
                         } else {
+                            // The timer isn't reset on the last frame, so this only happens once
+                            finished_writer.send(AnimationFinished {
+                                entity,
+                                animation_type: animation.animation_type.clone(),
+                            });
+                            if current_anim.requested.as_ref().is_some_and(|requested| {
+                                requested.animation_type == animation.animation_type
+                            }) {
+                                current_anim.requested = None;
+                            }
+                            if let Some(next) = animation.on_finish.clone() {
+                                chain = Some((animation.animation_type.clone(), next));
+                            }
                             animation.last_atlas_index
                         }
                     } else {
@@ -246,21 +711,106 @@ pub fn animate_sprite(
                 }
             }
         }
+
+        if let Some((finished, next)) = chain {
+            update_current_animation(&mut current_anim, next, children, &mut child_query);
+            current_anim.chained_from = Some(finished);
+        }
     }
 }
 
+type AnimationSpeedData = (
+    &'static CurrentAnimation,
+    &'static Velocity,
+    Option<&'static Motion>,
+    Option<&'static Movement>,
+    Option<&'static StatModifiers>,
+    &'static Children,
+);
+
+// Walking plays as fast as the unit is actually moving, and attacks keep up with how often the
+// unit gets to swing, so haste and frenzy show on the sprite too
+pub fn update_animation_speed(
+    query: Query<AnimationSpeedData>,
+    mut animation_query: Query<&mut Animation>,
+) {
+    for (current_animation, velocity, motion, movement, stats, children) in query.iter() {
+        let speed = match current_animation.animation_type {
+            AnimationType::Walk => {
+                let base = movement.map_or(0.0, |movement| movement.speed);
+                let walking = match motion {
+                    // Already has speed modifiers in it, and slows with the unit as it stops
+                    Some(motion) if base > 0.0 => motion.current.length() / base,
+                    _ if base > 0.0 => {
+                        velocity.0.length()
+                            * stats.map_or(1.0, |stats| stats.apply(Stat::MoveSpeed, base) / base)
+                    }
+                    _ => velocity.0.length(),
+                };
+                walking.max(MIN_WALK_ANIMATION_SPEED)
+            }
+            AnimationType::Attack => stats.map_or(1.0, |stats| stats.apply(Stat::AttackSpeed, 1.0)),
+            _ => 1.0,
+        };
+
+        for &child in children.iter() {
+            if let Ok(mut animation) = animation_query.get_mut(child) {
+                if animation.is_current(current_animation) && animation.speed != speed {
+                    animation.speed = speed;
+                }
+            }
+        }
+    }
+}
+
+// Whenever CurrentAnimation switches, the child that was showing fades out as the new one fades
+// in, both stay visible until the blend is done
 pub fn update_animation_visibility(
+    time: Res<Time>,
     query: Query<(&Children, &CurrentAnimation)>,
-    mut animation_query: Query<(Entity, &mut Visibility, &Animation)>,
+    mut animation_query: Query<(&mut Visibility, &mut Animation)>,
 ) {
+    let step = time.delta_seconds() / CROSSFADE_SECONDS;
     for (children, current_animation) in query.iter() {
         for &child in children.iter() {
-            if let Ok((_, mut visibility, animation)) = animation_query.get_mut(child) {
-                *visibility = if current_animation.animation_type == animation.animation_type {
+            if let Ok((mut visibility, mut animation)) = animation_query.get_mut(child) {
+                animation.blend = if animation.is_current(current_animation) {
+                    (animation.blend + step).min(1.0)
+                } else {
+                    (animation.blend - step).max(0.0)
+                };
+
+                let shown = if animation.blend > 0.0 {
                     Visibility::Visible
                 } else {
                     Visibility::Hidden
                 };
+                if *visibility != shown {
+                    *visibility = shown;
+                }
+            }
+        }
+    }
+}
+
+// Runs every frame since the ambient light of the day/night cycle keeps changing
+pub fn apply_tint(
+    day_night: Res<DayNight>,
+    query: Query<(Option<&Tint>, &Children)>,
+    mut sprite_query: Query<(&mut Sprite, &Animation)>,
+) {
+    let ambient = day_night.ambient_light();
+    for (tint, children) in query.iter() {
+        let tint = tint.map_or(Color::WHITE, |tint| tint.0);
+        let color = Color::rgba(
+            tint.r() * ambient.r(),
+            tint.g() * ambient.g(),
+            tint.b() * ambient.b(),
+            tint.a(),
+        );
+        for &child in children.iter() {
+            if let Ok((mut sprite, animation)) = sprite_query.get_mut(child) {
+                sprite.color = color.with_a(color.a() * animation.blend);
             }
         }
     }
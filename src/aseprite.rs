@@ -0,0 +1,324 @@
+use std::collections::HashMap as JsonMap;
+use std::fmt;
+use std::path::Path;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::{BoxedFuture, HashMap};
+use serde::Deserialize;
+
+use crate::animation::{AnimatedChildSpawnParams, AnimationType};
+use crate::gamestate::create_player_children_spawn_params;
+use crate::units::unit_types::UnitType;
+use crate::validate::asset_root;
+
+const SIDECAR_EXTENSION: &str = "aseprite.json";
+
+// What Aseprite writes with File > Export Sprite Sheet and the json data on. Frames can be
+// exported as an array or as a hash, the hash loses its order so frames are put back in order
+// by where they are on the sheet.
+#[derive(Deserialize)]
+struct AsepriteExport {
+    frames: AsepriteFrames,
+    meta: AsepriteMeta,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AsepriteFrames {
+    Array(Vec<AsepriteFrame>),
+    Hash(JsonMap<String, AsepriteFrame>),
+}
+
+#[derive(Deserialize, Clone, Copy)]
+struct AsepriteFrame {
+    frame: AsepriteRect,
+    duration: u32,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+struct AsepriteRect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize)]
+struct AsepriteMeta {
+    image: String,
+    size: AsepriteSize,
+    #[serde(default, rename = "frameTags")]
+    frame_tags: Vec<AsepriteTag>,
+}
+
+#[derive(Deserialize)]
+struct AsepriteSize {
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize)]
+struct AsepriteTag {
+    name: String,
+    from: usize,
+    to: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct SheetTag {
+    pub name: String,
+    pub from: usize,
+    pub to: usize,
+}
+
+// A sheet the way the animations need it, read out of the export instead of written by hand
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct AsepriteSheet {
+    // Relative to the assets folder, the same way units refer to their sheets
+    pub image: String,
+    pub tile_size: Vec2,
+    pub grid: (usize, usize),
+    pub tags: Vec<SheetTag>,
+    // In milliseconds, Aseprite lets every frame have its own
+    pub durations: Vec<u32>,
+}
+
+impl AsepriteSheet {
+    pub fn parse(bytes: &[u8], path: &str) -> Result<Self, AsepriteError> {
+        let export: AsepriteExport = serde_json::from_slice(bytes)?;
+        let mut frames = match export.frames {
+            AsepriteFrames::Array(frames) => frames,
+            AsepriteFrames::Hash(frames) => frames.into_values().collect(),
+        };
+        frames.sort_by_key(|frame| (frame.frame.y, frame.frame.x));
+
+        let Some(first) = frames.first() else {
+            return Err(AsepriteError::Layout("has no frames".to_owned()));
+        };
+        let (width, height) = (first.frame.w, first.frame.h);
+        if width == 0 || height == 0 {
+            return Err(AsepriteError::Layout("has empty frames".to_owned()));
+        }
+        let columns = (export.meta.size.w / width) as usize;
+        let rows = (export.meta.size.h / height) as usize;
+
+        // The atlas is cut up as an even grid, so that's how the frames have to be laid out
+        for (index, frame) in frames.iter().enumerate() {
+            let rect = frame.frame;
+            let on_grid = rect.w == width
+                && rect.h == height
+                && rect.x % width == 0
+                && rect.y % height == 0
+                && (rect.y / height) as usize * columns + (rect.x / width) as usize == index;
+            if !on_grid {
+                return Err(AsepriteError::Layout(format!(
+                    "frame {} is off the grid, export without trimming or packing",
+                    index
+                )));
+            }
+        }
+
+        let tags = export
+            .meta
+            .frame_tags
+            .into_iter()
+            .map(|tag| SheetTag {
+                name: tag.name,
+                from: tag.from,
+                to: tag.to,
+            })
+            .collect::<Vec<_>>();
+        if let Some(tag) = tags
+            .iter()
+            .find(|tag| tag.from > tag.to || tag.to >= frames.len())
+        {
+            return Err(AsepriteError::Layout(format!(
+                "tag {} goes past the last frame",
+                tag.name
+            )));
+        }
+
+        // The image is next to the export, wherever that is in the assets folder
+        let image = Path::new(path)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join(&export.meta.image)
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        Ok(Self {
+            image,
+            tile_size: Vec2::new(width as f32, height as f32),
+            grid: (columns, rows),
+            tags,
+            durations: frames.iter().map(|frame| frame.duration).collect(),
+        })
+    }
+
+    // The tag named after the animation, or the whole sheet when it has no tags at all
+    pub fn frames(&self, animation_type: &AnimationType) -> Option<(usize, usize)> {
+        if self.tags.is_empty() {
+            return Some((0, self.durations.len() - 1));
+        }
+
+        self.tags
+            .iter()
+            .find(|tag| tag.name.eq_ignore_ascii_case(animation_type.name()))
+            .map(|tag| (tag.from, tag.to))
+    }
+
+    // The animation only gets the one frame rate, so frames that were timed differently average out
+    pub fn fps(&self, from: usize, to: usize) -> f32 {
+        let total: u32 = self.durations[from..=to].iter().sum();
+        (to - from + 1) as f32 * 1000.0 / total.max(1) as f32
+    }
+
+    pub fn spawn_params(
+        &self,
+        animation_type: AnimationType,
+        is_looping: bool,
+    ) -> Option<AnimatedChildSpawnParams> {
+        let (from, to) = self.frames(&animation_type)?;
+        let mut params = AnimatedChildSpawnParams::from((
+            self.image.as_str(),
+            self.tile_size,
+            self.grid,
+            to,
+            animation_type,
+            is_looping,
+            false,
+        ))
+        .with_fps(self.fps(from, to));
+        params.first_atlas_index = from;
+        Some(params)
+    }
+
+    // Keeps what the unit says about how the animation plays, takes the frames from the export
+    pub fn apply_to(&self, params: &mut AnimatedChildSpawnParams) -> bool {
+        let Some((from, to)) = self.frames(&params.animation_type) else {
+            return false;
+        };
+
+        params.tile_size = self.tile_size;
+        params.grid = self.grid;
+        params.first_atlas_index = from;
+        params.last_atlas_index = to;
+        params.fps = self.fps(from, to);
+        true
+    }
+}
+
+// Where the export for a sheet goes, player/player_walk.png reads player/player_walk.aseprite.json
+pub fn sidecar_path(texture_path: &str) -> String {
+    let stem = texture_path.strip_suffix(".png").unwrap_or(texture_path);
+    format!("{}.{}", stem, SIDECAR_EXTENSION)
+}
+
+// Every sheet the player and the units animate with, each once
+pub fn unit_sheet_paths() -> Vec<String> {
+    let mut paths: Vec<String> = create_player_children_spawn_params()
+        .into_iter()
+        .chain(
+            UnitType::ALL
+                .iter()
+                .flat_map(|unit_type| unit_type.children_spawn_params()),
+        )
+        .map(|params| params.texture_path)
+        .collect();
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+// The exports that were found next to the sheets, by the path of the sheet
+#[derive(Resource, Default)]
+pub struct ImportedSheets(pub HashMap<String, Handle<AsepriteSheet>>);
+
+impl ImportedSheets {
+    // Sheets without an export, or whose export hasn't loaded yet, keep their hand-written params
+    pub fn import(
+        &self,
+        sheets: &Assets<AsepriteSheet>,
+        mut params: AnimatedChildSpawnParams,
+    ) -> AnimatedChildSpawnParams {
+        if let Some(sheet) = self
+            .0
+            .get(&params.texture_path)
+            .and_then(|handle| sheets.get(handle))
+        {
+            sheet.apply_to(&mut params);
+        }
+        params
+    }
+}
+
+// Only the sheets with an export on disk are asked for, the rest would just log a failed load
+pub fn load_imported_sheets(asset_server: Res<AssetServer>, mut imported: ResMut<ImportedSheets>) {
+    let root = asset_root();
+    for path in unit_sheet_paths() {
+        let sidecar = sidecar_path(&path);
+        if root.join(&sidecar).exists() {
+            imported.0.insert(path, asset_server.load(sidecar));
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AsepriteError {
+    Io(std::io::Error),
+    Deserialize(serde_json::Error),
+    Layout(String),
+}
+
+impl fmt::Display for AsepriteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AsepriteError::Io(error) => write!(f, "io error: {}", error),
+            AsepriteError::Deserialize(error) => write!(f, "could not read export: {}", error),
+            AsepriteError::Layout(problem) => write!(f, "sheet {}", problem),
+        }
+    }
+}
+
+impl std::error::Error for AsepriteError {}
+
+impl From<std::io::Error> for AsepriteError {
+    fn from(error: std::io::Error) -> Self {
+        AsepriteError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for AsepriteError {
+    fn from(error: serde_json::Error) -> Self {
+        AsepriteError::Deserialize(error)
+    }
+}
+
+#[derive(Default)]
+pub struct AsepriteSheetLoader;
+
+impl AssetLoader for AsepriteSheetLoader {
+    type Asset = AsepriteSheet;
+    type Settings = ();
+    type Error = AsepriteError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let path = load_context.path().to_string_lossy().into_owned();
+            AsepriteSheet::parse(&bytes, &path)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[SIDECAR_EXTENSION]
+    }
+}
@@ -0,0 +1,150 @@
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+
+use crate::juice;
+use crate::photo_mode::photo_mode_off;
+use crate::player::plugin::Player;
+use crate::schedule::FrameSet;
+
+// A zoom of 1 shows the whole arena, anything lower is closer in
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 1.0;
+const ZOOM_PER_LINE: f32 = 0.05;
+// Pixel scrolling touchpads send way more events than a wheel does lines
+const PIXELS_PER_LINE: f32 = 40.0;
+// At the stick fully pushed, per second
+const GAMEPAD_ZOOM_SPEED: f32 = 0.75;
+const GAMEPAD_DEAD_ZONE: f32 = 0.2;
+// How quickly the camera catches up, higher is snappier
+const FOLLOW_SHARPNESS: f32 = 6.0;
+const ZOOM_SHARPNESS: f32 = 12.0;
+
+// Where the camera is looking and how far in, the screen shake goes on top of this
+#[derive(Component, Debug, Clone, Copy)]
+pub struct CameraController {
+    pub position: Vec2,
+    pub zoom: f32,
+    pub target_zoom: f32,
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        Self {
+            position: Vec2::ZERO,
+            zoom: MAX_ZOOM,
+            target_zoom: MAX_ZOOM,
+        }
+    }
+}
+
+// The arena is the part of the world the summoner can walk around in, which is the window
+pub fn arena_half_size(window: &Window) -> Vec2 {
+    Vec2::new(window.width(), window.height()) * 0.5
+}
+
+// The part of the world on screen before the screen shake, grown by the margin on every side
+pub fn view_rect(controller: &CameraController, window: &Window, margin: f32) -> Rect {
+    let half_size = arena_half_size(window) * controller.zoom + margin;
+    Rect::from_center_half_size(controller.position, half_size)
+}
+
+// Keeps the view from showing anything past the edges of the arena, fully zoomed out that pins
+// the camera to the middle
+fn clamp_to_arena(position: Vec2, zoom: f32, window: &Window) -> Vec2 {
+    let arena = arena_half_size(window);
+    let room = (arena - arena * zoom).max(Vec2::ZERO);
+    position.clamp(-room, room)
+}
+
+pub fn zoom_input(
+    time: Res<Time>,
+    mut wheel_reader: EventReader<MouseWheel>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut query: Query<&mut CameraController>,
+) {
+    // Scrolling up zooms in
+    let mut zoom_delta: f32 = wheel_reader
+        .read()
+        .map(|wheel| match wheel.unit {
+            MouseScrollUnit::Line => -wheel.y * ZOOM_PER_LINE,
+            MouseScrollUnit::Pixel => -wheel.y / PIXELS_PER_LINE * ZOOM_PER_LINE,
+        })
+        .sum();
+    for gamepad in gamepads.iter() {
+        let stick = axes
+            .get(GamepadAxis::new(gamepad, GamepadAxisType::RightStickY))
+            .unwrap_or(0.0);
+        if stick.abs() > GAMEPAD_DEAD_ZONE {
+            zoom_delta -= stick * GAMEPAD_ZOOM_SPEED * time.delta_seconds();
+        }
+    }
+    if zoom_delta == 0.0 {
+        return;
+    }
+
+    for mut controller in query.iter_mut() {
+        controller.target_zoom = (controller.target_zoom + zoom_delta).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+}
+
+pub fn follow_player(
+    time: Res<Time>,
+    window_query: Query<&Window>,
+    player_query: Query<&Transform, With<Player>>,
+    mut camera_query: Query<(&mut CameraController, &mut OrthographicProjection)>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let target = player_query
+        .get_single()
+        .map_or(Vec2::ZERO, |transform| transform.translation.truncate());
+    // Framerate independent easing, the same fraction of the way there every second
+    let follow = 1.0 - (-FOLLOW_SHARPNESS * time.delta_seconds()).exp();
+    let zoom = 1.0 - (-ZOOM_SHARPNESS * time.delta_seconds()).exp();
+
+    for (mut controller, mut projection) in camera_query.iter_mut() {
+        controller.zoom += (controller.target_zoom - controller.zoom) * zoom;
+        let position = controller.position.lerp(target, follow);
+        controller.position = clamp_to_arena(position, controller.zoom, window);
+        if projection.scale != controller.zoom {
+            projection.scale = controller.zoom;
+        }
+    }
+}
+
+// A new run or a checkpoint starts with the camera already on the summoner, instead of sliding
+// over from wherever the last one died
+pub fn snap_to_new_player(
+    window_query: Query<&Window>,
+    player_query: Query<&Transform, Added<Player>>,
+    mut camera_query: Query<&mut CameraController>,
+) {
+    let (Ok(window), Ok(transform)) = (window_query.get_single(), player_query.get_single()) else {
+        return;
+    };
+
+    for mut controller in camera_query.iter_mut() {
+        controller.position =
+            clamp_to_arena(transform.translation.truncate(), controller.zoom, window);
+    }
+}
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                zoom_input.in_set(FrameSet::Input),
+                (snap_to_new_player, follow_player)
+                    .chain()
+                    .run_if(photo_mode_off)
+                    .in_set(FrameSet::Presentation)
+                    .before(juice::apply_screen_shake),
+            ),
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+
+use bevy::prelude::*;
+use bevy::render::render_resource::TextureFormat;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::window::PrimaryWindow;
+
+use crate::save::checkpoints::CheckpointSettings;
+
+use super::gif;
+use super::screenshot::{capture_path, SCREENSHOT_KEY};
+
+// What SHIFT+F12 saves, the last few seconds at a rate and size that keeps the file shareable
+const CLIP_SECONDS: f32 = 5.0;
+const CLIP_FPS: f32 = 10.0;
+const CLIP_WIDTH: u32 = 480;
+
+#[derive(Clone)]
+pub struct ClipFrame {
+    width: u16,
+    height: u16,
+    // A palette index per pixel, turned down to the clip's size as soon as it's read back
+    indices: Vec<u8>,
+}
+
+// Filled from the render thread's screenshot callbacks, so it's shared rather than owned
+#[derive(Resource)]
+pub struct ClipBuffer {
+    frames: Arc<Mutex<VecDeque<ClipFrame>>>,
+    timer: Timer,
+}
+
+impl Default for ClipBuffer {
+    fn default() -> Self {
+        Self {
+            frames: Arc::new(Mutex::new(VecDeque::new())),
+            timer: Timer::from_seconds(1.0 / CLIP_FPS, TimerMode::Repeating),
+        }
+    }
+}
+
+impl ClipBuffer {
+    fn capacity() -> usize {
+        (CLIP_SECONDS * CLIP_FPS).round() as usize
+    }
+}
+
+// Nearest neighbour, the game is pixel art and it's about to lose most of its colors anyway
+fn shrink(image: &Image) -> Option<ClipFrame> {
+    let bgra = match image.texture_descriptor.format {
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => true,
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => false,
+        _ => return None,
+    };
+    let (width, height) = (image.width(), image.height());
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    let step = (width as f32 / CLIP_WIDTH as f32).max(1.0);
+    let clip_width = ((width as f32 / step) as u32).max(1);
+    let clip_height = ((height as f32 / step) as u32).max(1);
+    let mut indices = Vec::with_capacity((clip_width * clip_height) as usize);
+    for y in 0..clip_height {
+        let source_y = ((y as f32 * step) as u32).min(height - 1);
+        for x in 0..clip_width {
+            let source_x = ((x as f32 * step) as u32).min(width - 1);
+            let offset = ((source_y * width + source_x) * 4) as usize;
+            let pixel = image.data.get(offset..offset + 4)?;
+            let (red, green, blue) = if bgra {
+                (pixel[2], pixel[1], pixel[0])
+            } else {
+                (pixel[0], pixel[1], pixel[2])
+            };
+            indices.push(gif::quantize(red, green, blue));
+        }
+    }
+
+    Some(ClipFrame {
+        width: clip_width as u16,
+        height: clip_height as u16,
+        indices,
+    })
+}
+
+pub fn record_clip_frames(
+    time: Res<Time<Real>>,
+    mut buffer: ResMut<ClipBuffer>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+) {
+    if !buffer.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+
+    let frames = buffer.frames.clone();
+    // Fails when F12 already asked for this frame, the clip just skips it
+    let _ = screenshots.take_screenshot(window, move |image| {
+        let Some(frame) = shrink(&image) else {
+            return;
+        };
+        let mut frames = frames.lock().unwrap_or_else(PoisonError::into_inner);
+        frames.push_back(frame);
+        while frames.len() > ClipBuffer::capacity() {
+            frames.pop_front();
+        }
+    });
+}
+
+pub fn clear_clip_buffer(buffer: Res<ClipBuffer>) {
+    buffer
+        .frames
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clear();
+}
+
+// Encoding takes a moment, it's done off to the side so the game doesn't hitch
+pub fn export_clip(
+    keys: Res<ButtonInput<KeyCode>>,
+    checkpoint_settings: Res<CheckpointSettings>,
+    buffer: Res<ClipBuffer>,
+) {
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if !shift || !keys.just_pressed(SCREENSHOT_KEY) {
+        return;
+    }
+
+    let frames: Vec<ClipFrame> = buffer
+        .frames
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .cloned()
+        .collect();
+    let Some(last) = frames.last().cloned() else {
+        info!("Nothing recorded to save a clip of yet");
+        return;
+    };
+    let Some(path) = capture_path(&checkpoint_settings, "clip", "gif") else {
+        return;
+    };
+
+    thread::spawn(move || {
+        // A resized window leaves frames of another size behind, those are dropped
+        let frames: Vec<Vec<u8>> = frames
+            .into_iter()
+            .filter(|frame| frame.width == last.width && frame.height == last.height)
+            .map(|frame| frame.indices)
+            .collect();
+        let delay = (100.0 / CLIP_FPS).round() as u16;
+        let bytes = gif::encode(last.width, last.height, &frames, delay);
+        match fs::write(&path, bytes) {
+            Ok(()) => info!("Clip saved to {}", path.display()),
+            Err(error) => warn!("Could not save {}: {}", path.display(), error),
+        }
+    });
+}
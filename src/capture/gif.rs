@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+// Enough shades of each channel to tell the teams and the tiles apart, and it keeps every frame
+// on the same palette so nothing has to be worked out per clip. 6 * 7 * 6 = 252, green gets the
+// extra shade since that's what the eye picks up best.
+const RED_LEVELS: u32 = 6;
+const GREEN_LEVELS: u32 = 7;
+const BLUE_LEVELS: u32 = 6;
+// Codes start out one bit wider than a palette index
+const MIN_CODE_SIZE: u8 = 8;
+const CLEAR_CODE: u16 = 1 << MIN_CODE_SIZE;
+const END_CODE: u16 = CLEAR_CODE + 1;
+const MAX_CODE: u16 = 4095;
+const MAX_BLOCK: usize = 255;
+
+fn level(value: u8, levels: u32) -> u32 {
+    (value as u32 * (levels - 1) + 127) / 255
+}
+
+// The palette index closest to the color
+pub fn quantize(red: u8, green: u8, blue: u8) -> u8 {
+    let red = level(red, RED_LEVELS);
+    let green = level(green, GREEN_LEVELS);
+    let blue = level(blue, BLUE_LEVELS);
+    (red * GREEN_LEVELS * BLUE_LEVELS + green * BLUE_LEVELS + blue) as u8
+}
+
+fn palette() -> Vec<u8> {
+    let shade = |level: u32, levels: u32| (level * 255 / (levels - 1)) as u8;
+    let mut palette = Vec::with_capacity(256 * 3);
+    for red in 0..RED_LEVELS {
+        for green in 0..GREEN_LEVELS {
+            for blue in 0..BLUE_LEVELS {
+                palette.push(shade(red, RED_LEVELS));
+                palette.push(shade(green, GREEN_LEVELS));
+                palette.push(shade(blue, BLUE_LEVELS));
+            }
+        }
+    }
+    // The color table is always a power of two long, the rest is never used
+    palette.resize(256 * 3, 0);
+    palette
+}
+
+// Packs codes of changing widths together, lowest bit first
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+// The same way giflib does it, the code size goes up as soon as the next code won't fit and the
+// table starts over once it's full
+fn compress(indices: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter {
+        bytes: Vec::new(),
+        buffer: 0,
+        bits: 0,
+    };
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut size = MIN_CODE_SIZE + 1;
+    let mut next = END_CODE + 1;
+
+    let output = |writer: &mut BitWriter, code: u16, size: &mut u8, next: u16| {
+        writer.write(code, *size);
+        if next >= 1 << *size && *size < 12 {
+            *size += 1;
+        }
+    };
+
+    output(&mut writer, CLEAR_CODE, &mut size, next);
+    let Some((&first, rest)) = indices.split_first() else {
+        output(&mut writer, END_CODE, &mut size, next);
+        return writer.finish();
+    };
+
+    let mut prefix = first as u16;
+    for &index in rest {
+        if let Some(&code) = table.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+
+        output(&mut writer, prefix, &mut size, next);
+        if next >= MAX_CODE {
+            output(&mut writer, CLEAR_CODE, &mut size, next);
+            table.clear();
+            size = MIN_CODE_SIZE + 1;
+            next = END_CODE + 1;
+        } else {
+            table.insert((prefix, index), next);
+            next += 1;
+        }
+        prefix = index as u16;
+    }
+    output(&mut writer, prefix, &mut size, next);
+    output(&mut writer, END_CODE, &mut size, next);
+    writer.finish()
+}
+
+fn push_u16(bytes: &mut Vec<u8>, value: u16) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+// Every frame is a palette index per pixel, the same size as the others. Loops forever.
+pub fn encode(width: u16, height: u16, frames: &[Vec<u8>], delay_centiseconds: u16) -> Vec<u8> {
+    let mut bytes = b"GIF89a".to_vec();
+    push_u16(&mut bytes, width);
+    push_u16(&mut bytes, height);
+    // A global table of 256 colors, 8 bits per channel
+    bytes.extend_from_slice(&[0xf7, 0, 0]);
+    bytes.extend_from_slice(&palette());
+    // The netscape extension is what makes it loop
+    bytes.extend_from_slice(&[0x21, 0xff, 0x0b]);
+    bytes.extend_from_slice(b"NETSCAPE2.0");
+    bytes.extend_from_slice(&[0x03, 0x01, 0x00, 0x00, 0x00]);
+
+    for frame in frames {
+        // Each frame replaces the one before it whole
+        bytes.extend_from_slice(&[0x21, 0xf9, 0x04, 0x04]);
+        push_u16(&mut bytes, delay_centiseconds);
+        bytes.extend_from_slice(&[0x00, 0x00]);
+
+        bytes.push(0x2c);
+        push_u16(&mut bytes, 0);
+        push_u16(&mut bytes, 0);
+        push_u16(&mut bytes, width);
+        push_u16(&mut bytes, height);
+        bytes.push(0x00);
+
+        bytes.push(MIN_CODE_SIZE);
+        for block in compress(frame).chunks(MAX_BLOCK) {
+            bytes.push(block.len() as u8);
+            bytes.extend_from_slice(block);
+        }
+        bytes.push(0x00);
+    }
+
+    bytes.push(0x3b);
+    bytes
+}
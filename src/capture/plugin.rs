@@ -0,0 +1,23 @@
+use bevy::prelude::*;
+
+use crate::capture::{clips, screenshot};
+use crate::dark_arts_defense::AppState;
+
+pub struct CapturePlugin;
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<clips::ClipBuffer>()
+            .add_systems(OnEnter(AppState::Playing), clips::clear_clip_buffer)
+            .add_systems(
+                Update,
+                (
+                    screenshot::screenshot_hotkey,
+                    clips::export_clip,
+                    // Only the run is worth recording, and asking for a frame isn't free
+                    clips::record_clip_frames.run_if(in_state(AppState::Playing)),
+                )
+                    .chain(),
+            );
+    }
+}
@@ -0,0 +1,57 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::window::PrimaryWindow;
+
+use crate::save::checkpoints::CheckpointSettings;
+
+pub const SCREENSHOT_KEY: KeyCode = KeyCode::F12;
+const CAPTURE_DIRECTORY: &str = "captures";
+
+// Next to the checkpoints, named for when it was taken so none of them get overwritten
+pub fn capture_path(settings: &CheckpointSettings, name: &str, extension: &str) -> Option<PathBuf> {
+    let directory = settings.directory.join(CAPTURE_DIRECTORY);
+    if let Err(error) = fs::create_dir_all(&directory) {
+        warn!("Could not create {}: {}", directory.display(), error);
+        return None;
+    }
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis());
+    Some(directory.join(format!("{}-{}.{}", name, millis, extension)))
+}
+
+// Of whatever ends up in the window this frame, bevy writes the png once it's read back
+pub fn save_screenshot(
+    settings: &CheckpointSettings,
+    name: &str,
+    screenshots: &mut ScreenshotManager,
+    window: Entity,
+) {
+    let Some(path) = capture_path(settings, name, "png") else {
+        return;
+    };
+    if let Err(error) = screenshots.save_screenshot_to_disk(window, &path) {
+        warn!("Could not save {}: {}", path.display(), error);
+    }
+}
+
+// F12 anywhere, the ui and all. SHIFT+F12 saves a clip instead.
+pub fn screenshot_hotkey(
+    keys: Res<ButtonInput<KeyCode>>,
+    checkpoint_settings: Res<CheckpointSettings>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+) {
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if shift || !keys.just_pressed(SCREENSHOT_KEY) {
+        return;
+    }
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    save_screenshot(&checkpoint_settings, "screenshot", &mut screenshots, window);
+}
@@ -0,0 +1,188 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::console::registry::CommandRegistry;
+use crate::enemies::enemy_spawner::EnemySpawner;
+use crate::enemies::spawn_queue::{SpawnQueue, SpawnRequest};
+use crate::events::Damage;
+use crate::levels::definition::ActiveLevel;
+use crate::mana::Mana;
+use crate::player::plugin::Player;
+use crate::rng::GameRng;
+use crate::units::damage::DamageKind;
+use crate::units::health::Health;
+use crate::units::team::{AllianceMatrix, CurrentTeam, Team};
+use crate::units::unit_types::{CurrentUnitType, UnitType};
+
+// Nobody needs more than this in one go, and it keeps a typo from freezing the game
+const MAX_SPAWN_COUNT: usize = 100;
+// How far from the summoner allies turn up
+const ALLY_SPAWN_RADIUS: f32 = 120.0;
+
+fn parse_unit_type(word: &str) -> Option<UnitType> {
+    let word = word.to_lowercase();
+    UnitType::ALL
+        .into_iter()
+        .find(|unit_type| unit_type.name().replace(' ', "").to_lowercase() == word)
+}
+
+// The ones the waves are made of, the rest are summoned by the player
+fn default_team(unit_type: UnitType) -> Team {
+    match unit_type {
+        UnitType::Knight | UnitType::Gargoyle | UnitType::ArmoredKnight | UnitType::Assassin => {
+            Team::Good
+        }
+        _ => Team::Evil,
+    }
+}
+
+fn parse_side(word: Option<&&str>) -> Result<Option<Team>, String> {
+    match word.map(|word| word.to_lowercase()).as_deref() {
+        None | Some("all") => Ok(None),
+        Some("enemy") | Some("enemies") => Ok(Some(Team::Good)),
+        Some("ally") | Some("allies") => Ok(Some(Team::Evil)),
+        Some(other) => Err(format!("Expected enemy, ally or all, got {}", other)),
+    }
+}
+
+pub fn help(_args: &[&str], world: &mut World) -> Result<String, String> {
+    let registry = world.resource::<CommandRegistry>();
+    Ok(registry.usages().collect::<Vec<_>>().join("\n"))
+}
+
+pub fn spawn(args: &[&str], world: &mut World) -> Result<String, String> {
+    let Some(name) = args.first() else {
+        return Err("Usage: spawn <unit> [count] [enemy|ally]".to_owned());
+    };
+    let unit_type = parse_unit_type(name).ok_or_else(|| {
+        let names: Vec<String> = UnitType::ALL
+            .iter()
+            .map(|unit_type| unit_type.name().replace(' ', "").to_lowercase())
+            .collect();
+        format!("Unknown unit {}, try one of {}", name, names.join(", "))
+    })?;
+    let count = match args.get(1) {
+        Some(count) => count
+            .parse::<usize>()
+            .map_err(|_| format!("{} isn't a count", count))?,
+        None => 1,
+    }
+    .min(MAX_SPAWN_COUNT);
+    let team = parse_side(args.get(2))?.unwrap_or(default_team(unit_type));
+
+    let play_area = world
+        .query::<&Window>()
+        .iter(world)
+        .next()
+        .map(|window| Vec2::new(window.width(), window.height()))
+        .ok_or("There's no window to spawn in")?;
+    let player_position = world
+        .query_filtered::<&Transform, With<Player>>()
+        .iter(world)
+        .next()
+        .map(|transform| transform.translation.truncate());
+
+    // Enemies come in from the edges like a wave would, allies turn up around the summoner
+    let positions: Vec<Vec2> = world.resource_scope(|world, mut rng: Mut<GameRng>| {
+        let level = world.resource::<ActiveLevel>();
+        (0..count)
+            .map(|_| match (team, player_position) {
+                (Team::Evil, Some(player_position)) => {
+                    let angle = rng.0.gen_range(0.0..std::f32::consts::TAU);
+                    let distance = rng.0.gen_range(0.0..ALLY_SPAWN_RADIUS);
+                    player_position + Vec2::from_angle(angle) * distance
+                }
+                _ => level.spawn_position(&mut rng, play_area),
+            })
+            .collect()
+    });
+
+    let mut spawn_queue = world.resource_mut::<SpawnQueue>();
+    for position in positions {
+        spawn_queue.push(SpawnRequest {
+            unit_type,
+            team,
+            position,
+            bounty: None,
+            patrol: None,
+        });
+    }
+    Ok(format!(
+        "Spawning {} {} ({:?})",
+        count,
+        unit_type.name(),
+        team
+    ))
+}
+
+pub fn mana(args: &[&str], world: &mut World) -> Result<String, String> {
+    let Some(amount) = args.first() else {
+        return Err("Usage: mana <amount>".to_owned());
+    };
+    let amount = amount
+        .parse::<u32>()
+        .map_err(|_| format!("{} isn't an amount", amount))?
+        .min(u8::MAX as u32) as u8;
+
+    let mut query = world.query_filtered::<&mut Mana, With<Player>>();
+    let mut mana = query
+        .get_single_mut(world)
+        .map_err(|_| "There's no summoner to give mana to")?;
+    mana.max_mana = mana.max_mana.max(amount);
+    mana.current_mana = amount;
+    Ok(format!("Mana is {}/{}", mana.current_mana, mana.max_mana))
+}
+
+// Goes through the damage pipeline like anything else, so deaths, loot and the kill feed all see
+// it happen
+pub fn killall(args: &[&str], world: &mut World) -> Result<String, String> {
+    let side = parse_side(args.first())?;
+    let alliances = world.resource::<AllianceMatrix>().clone();
+
+    let targets: Vec<(Entity, i32)> = world
+        .query_filtered::<(Entity, &CurrentTeam, &Health), (With<CurrentUnitType>, Without<Player>)>()
+        .iter(world)
+        .filter(|(_, _, health)| !health.is_dead())
+        .filter(|(_, team, _)| match side {
+            None => true,
+            Some(Team::Evil) => !alliances.is_hostile(Team::Evil, team.0),
+            Some(_) => alliances.is_hostile(Team::Evil, team.0),
+        })
+        .map(|(entity, _, health)| (entity, health.max.max(1).saturating_mul(100)))
+        .collect();
+
+    let count = targets.len();
+    for (target, amount) in targets {
+        world.send_event(Damage {
+            target,
+            amount,
+            kind: DamageKind::Physical,
+            armor_piercing: true,
+            source: None,
+            critical: false,
+        });
+    }
+    Ok(format!("Killing {} units", count))
+}
+
+// Skips ahead so the given wave is the next one, and starts it right away
+pub fn wave(args: &[&str], world: &mut World) -> Result<String, String> {
+    let Some(wave) = args.first() else {
+        return Err("Usage: wave <number>".to_owned());
+    };
+    let wave = wave
+        .parse::<u32>()
+        .ok()
+        .filter(|wave| *wave > 0)
+        .ok_or_else(|| format!("{} isn't a wave", wave))?;
+
+    let mut query = world.query::<&mut EnemySpawner>();
+    let mut spawner = query
+        .iter_mut(world)
+        .next()
+        .ok_or("There's no run going to skip waves in")?;
+    spawner.wave = wave - 1;
+    let interval = spawner.wave_charge.interval();
+    spawner.wave_charge.set_elapsed(interval);
+    Ok(format!("Wave {} is coming", wave))
+}
@@ -0,0 +1,233 @@
+use std::collections::VecDeque;
+
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy::sprite::Anchor;
+
+use crate::console::commands;
+use crate::console::registry::{CommandRegistry, ConsoleCommandsExt};
+use crate::render_scale::{UiView, UI_LAYER};
+
+const CONSOLE_KEY: KeyCode = KeyCode::Backquote;
+const MAX_LOG_LINES: usize = 12;
+const MAX_HISTORY: usize = 32;
+const MAX_INPUT_LENGTH: usize = 80;
+const FONT_SIZE: f32 = 20.0;
+const LINE_HEIGHT: f32 = 24.0;
+const MARGIN: f32 = 12.0;
+// Over everything else the ui draws
+const CONSOLE_Z: f32 = 50.0;
+const BACKDROP_COLOR: Color = Color::rgba(0.05, 0.02, 0.08, 0.85);
+const ERROR_COLOR: Color = Color::rgb(1.0, 0.45, 0.4);
+
+// Only built with the debug_tools feature, backtick drops it down over the top of the screen
+#[derive(Resource, Default)]
+pub struct Console {
+    pub open: bool,
+    input: String,
+    log: VecDeque<(String, bool)>,
+    history: Vec<String>,
+    // How far back up the history the input was taken from, if it was
+    browsing: Option<usize>,
+    submitted: Vec<String>,
+}
+
+impl Console {
+    fn log(&mut self, line: String, error: bool) {
+        for line in line.lines() {
+            self.log.push_back((line.to_owned(), error));
+        }
+        while self.log.len() > MAX_LOG_LINES {
+            self.log.pop_front();
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ConsoleBackdrop;
+
+#[derive(Component)]
+pub struct ConsoleText;
+
+#[derive(Resource)]
+pub struct ConsoleFont(Handle<Font>);
+
+// Runs before anything else reads the keyboard, and while it's open the keys are taken away so
+// typing "spawn" doesn't also walk the summoner around
+pub fn console_input(
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut console: ResMut<Console>,
+) {
+    if keys.just_pressed(CONSOLE_KEY) {
+        console.open = !console.open;
+        characters.clear();
+        keys.reset_all();
+        return;
+    }
+    if !console.open {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        console.open = false;
+    } else if keys.just_pressed(KeyCode::Enter) {
+        let line = std::mem::take(&mut console.input).trim().to_owned();
+        console.browsing = None;
+        if !line.is_empty() {
+            console.log(format!("> {}", line), false);
+            if console.history.last() != Some(&line) {
+                console.history.push(line.clone());
+                if console.history.len() > MAX_HISTORY {
+                    console.history.remove(0);
+                }
+            }
+            console.submitted.push(line);
+        }
+    } else if keys.just_pressed(KeyCode::Backspace) {
+        console.input.pop();
+    } else if keys.just_pressed(KeyCode::ArrowUp) || keys.just_pressed(KeyCode::ArrowDown) {
+        let newest = console.history.len();
+        let index = console.browsing.unwrap_or(newest);
+        let index = if keys.just_pressed(KeyCode::ArrowUp) {
+            index.saturating_sub(1)
+        } else {
+            (index + 1).min(newest)
+        };
+        console.browsing = (index < newest).then_some(index);
+        console.input = console.history.get(index).cloned().unwrap_or_default();
+    }
+
+    for event in characters.read() {
+        for character in event.char.chars() {
+            if !character.is_control()
+                && character != '`'
+                && console.input.chars().count() < MAX_INPUT_LENGTH
+            {
+                console.input.push(character);
+            }
+        }
+    }
+    keys.reset_all();
+}
+
+// Commands get the whole world, so this can't share a frame with anything else
+pub fn run_console_commands(world: &mut World) {
+    let submitted = std::mem::take(&mut world.resource_mut::<Console>().submitted);
+    if submitted.is_empty() {
+        return;
+    }
+
+    let registry = world.resource::<CommandRegistry>().clone();
+    for line in submitted {
+        let (message, error) = match registry.run(&line, world) {
+            Ok(message) => (message, false),
+            Err(message) => (message, true),
+        };
+        if !message.is_empty() {
+            world.resource_mut::<Console>().log(message, error);
+        }
+    }
+}
+
+pub fn setup_console(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: BACKDROP_COLOR,
+                anchor: Anchor::TopCenter,
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        ConsoleBackdrop,
+        RenderLayers::layer(UI_LAYER),
+    ));
+    commands.spawn((
+        Text2dBundle {
+            text: Text::default(),
+            text_anchor: Anchor::TopLeft,
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        ConsoleText,
+        RenderLayers::layer(UI_LAYER),
+    ));
+    commands.insert_resource(ConsoleFont(
+        asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+    ));
+}
+
+type BackdropFilter = (With<ConsoleBackdrop>, Without<ConsoleText>);
+
+pub fn update_console(
+    console: Res<Console>,
+    font: Res<ConsoleFont>,
+    ui_view: Res<UiView>,
+    mut backdrop_query: Query<(&mut Sprite, &mut Transform, &mut Visibility), BackdropFilter>,
+    mut text_query: Query<(&mut Text, &mut Transform, &mut Visibility), With<ConsoleText>>,
+) {
+    let visibility = if console.open {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+    let height = (MAX_LOG_LINES + 1) as f32 * LINE_HEIGHT + MARGIN * 2.0;
+    let top = ui_view.half_size.y;
+
+    for (mut sprite, mut transform, mut backdrop_visibility) in backdrop_query.iter_mut() {
+        *backdrop_visibility = visibility;
+        sprite.custom_size = Some(Vec2::new(ui_view.size().x, height));
+        transform.translation = Vec3::new(0.0, top, CONSOLE_Z);
+    }
+
+    if !console.is_changed() && !ui_view.is_changed() {
+        return;
+    }
+    let style = |color: Color| TextStyle {
+        font: font.0.clone(),
+        font_size: FONT_SIZE,
+        color,
+    };
+    for (mut text, mut transform, mut text_visibility) in text_query.iter_mut() {
+        *text_visibility = visibility;
+        transform.translation =
+            Vec3::new(-ui_view.half_size.x + MARGIN, top - MARGIN, CONSOLE_Z + 1.0);
+
+        // Padded out so the prompt always sits on the bottom line
+        let padding = MAX_LOG_LINES - console.log.len();
+        text.sections = std::iter::repeat_with(|| TextSection::new("\n", style(Color::WHITE)))
+            .take(padding)
+            .chain(console.log.iter().map(|(line, error)| {
+                let color = if *error { ERROR_COLOR } else { Color::WHITE };
+                TextSection::new(format!("{}\n", line), style(color))
+            }))
+            .chain(std::iter::once(TextSection::new(
+                format!("> {}_", console.input),
+                style(Color::WHITE),
+            )))
+            .collect();
+    }
+}
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .add_console_command("help", "help", commands::help)
+            .add_console_command(
+                "spawn",
+                "spawn <unit> [count] [enemy|ally]",
+                commands::spawn,
+            )
+            .add_console_command("mana", "mana <amount>", commands::mana)
+            .add_console_command("killall", "killall [enemy|ally|all]", commands::killall)
+            .add_console_command("wave", "wave <number>", commands::wave)
+            .add_systems(Startup, setup_console)
+            .add_systems(PreUpdate, console_input.after(InputSystem))
+            .add_systems(Update, (run_console_commands, update_console).chain());
+    }
+}
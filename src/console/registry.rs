@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+
+// Gets the words after the command's name and the whole world, and says what it did or what was
+// wrong with what it was given
+pub type CommandFn = fn(&[&str], &mut World) -> Result<String, String>;
+
+#[derive(Clone, Copy)]
+pub struct ConsoleCommand {
+    pub usage: &'static str,
+    pub run: CommandFn,
+}
+
+// Every command the console knows, sorted so help lists them the same way every time
+#[derive(Resource, Default, Clone)]
+pub struct CommandRegistry {
+    commands: BTreeMap<&'static str, ConsoleCommand>,
+}
+
+impl CommandRegistry {
+    pub fn register(&mut self, name: &'static str, usage: &'static str, run: CommandFn) {
+        if self
+            .commands
+            .insert(name, ConsoleCommand { usage, run })
+            .is_some()
+        {
+            warn!("Console command {} was registered twice", name);
+        }
+    }
+
+    pub fn usages(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.commands.values().map(|command| command.usage)
+    }
+
+    pub fn run(&self, line: &str, world: &mut World) -> Result<String, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((name, args)) = words.split_first() else {
+            return Ok(String::new());
+        };
+        let Some(command) = self.commands.get(name.to_lowercase().as_str()) else {
+            return Err(format!("Unknown command {}, try help", name));
+        };
+        (command.run)(args, world)
+    }
+}
+
+// How other modules add their own commands:
+//   app.add_console_command("heal", "heal <amount>", heal_command);
+pub trait ConsoleCommandsExt {
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        run: CommandFn,
+    ) -> &mut Self;
+}
+
+impl ConsoleCommandsExt for App {
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        run: CommandFn,
+    ) -> &mut Self {
+        self.init_resource::<CommandRegistry>();
+        self.world
+            .resource_mut::<CommandRegistry>()
+            .register(name, usage, run);
+        self
+    }
+}
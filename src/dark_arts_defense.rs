@@ -1,50 +1,208 @@
 use bevy::prelude::*;
+use bevy::sprite::Material2dPlugin;
 
 use crate::ai;
 use crate::animation;
+use crate::aseprite;
+use crate::camera;
+use crate::capture;
+use crate::difficulty::Difficulty;
 use crate::enemies;
+use crate::events::{self, GameEvent};
+use crate::game_mode::GameMode;
+use crate::game_view;
 use crate::gamestate;
+use crate::juice;
+use crate::level_assets;
+use crate::levels;
+use crate::levels::definition::{ActiveLevel, LevelDefinition, Levels};
+use crate::mana;
+use crate::map;
+use crate::map::plugin::CurrentMap;
+use crate::meta;
+use crate::movement;
+use crate::narrative;
+use crate::photo_mode;
+use crate::pickups;
 use crate::player;
+use crate::pool;
+use crate::render_scale;
+use crate::rng::{self, GameRng, RunSeed};
+use crate::save;
+use crate::settings;
+use crate::schedule::{self, FrameSet, SimSet};
+use crate::silhouette;
+use crate::stats;
+use crate::structures;
+use crate::time_of_day;
+use crate::tutorial;
 use crate::ui;
-use crate::units::acolyte;
+use crate::units;
 use crate::velocity;
-use rand::{rngs::StdRng, SeedableRng};
+use crate::vfx;
 
-#[derive(Resource)]
-pub struct RandomSeed(pub StdRng);
-
-#[derive(Event)]
-pub enum GameEvent {
-    StartGame,
-    GameOver,
-    IncreaseScore,
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AppState {
+    #[default]
+    LevelSelect,
+    Upgrades,
+    Playing,
 }
 
 pub struct DarkArtsDefensePlugin;
 
 impl Plugin for DarkArtsDefensePlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(RandomSeed(StdRng::seed_from_u64(12345123454321_u64)))
+        schedule::configure_schedule(app);
+
+        let run_seed = RunSeed::from_args();
+        app.insert_resource(GameRng::from_seed(run_seed.current))
+            .insert_resource(run_seed)
+            .init_resource::<level_assets::LevelAssets>()
+            .init_resource::<Difficulty>()
+            .init_resource::<GameMode>()
+            .init_state::<AppState>()
             .add_plugins((
+                events::EventsPlugin,
                 player::plugin::PlayerPlugin,
                 enemies::plugin::EnemyPlugin,
                 ai::plugin::AiPlugin,
                 ui::plugin::UiPlugin,
+                save::plugin::SavePlugin,
+                stats::plugin::StatsPlugin,
+                meta::plugin::MetaPlugin,
+                pickups::plugin::PickupsPlugin,
+                units::plugin::UnitsPlugin,
+                structures::plugin::StructuresPlugin,
+                map::plugin::MapPlugin,
+                levels::plugin::LevelsPlugin,
+                juice::JuicePlugin,
+                Material2dPlugin::<silhouette::SilhouetteMaterial>::default(),
+            ))
+            .add_plugins((vfx::plugin::VfxPlugin, camera::CameraPlugin))
+            .add_plugins((
+                settings::plugin::SettingsPlugin,
+                tutorial::TutorialPlugin,
+                narrative::plugin::NarrativePlugin,
+                photo_mode::PhotoModePlugin,
+                capture::plugin::CapturePlugin,
             ))
-            .add_event::<GameEvent>()
-            .add_systems(Startup, gamestate::init_game_system)
+            .add_event::<game_view::GameAction>()
+            .init_resource::<game_view::GameView>()
+            .init_resource::<time_of_day::DayNight>()
+            .init_resource::<animation::AtlasCache>()
+            .init_asset::<aseprite::AsepriteSheet>()
+            .init_asset_loader::<aseprite::AsepriteSheetLoader>()
+            .init_resource::<aseprite::ImportedSheets>()
+            .add_systems(Startup, aseprite::load_imported_sheets)
+            .init_resource::<silhouette::SilhouetteMesh>()
+            .add_systems(PostUpdate, game_view::update_game_view)
+            .init_resource::<render_scale::UiView>()
+            .add_systems(Startup, render_scale::setup_cameras)
+            .add_systems(PreUpdate, render_scale::update_ui_view)
+            .add_systems(
+                FixedUpdate,
+                (movement::steer, velocity::translate)
+                    .chain()
+                    .in_set(SimSet::Movement),
+            )
+            .add_systems(OnEnter(AppState::Playing), load_chosen_level)
+            .add_systems(OnExit(AppState::Playing), leave_level)
             .add_systems(
                 Update,
                 (
                     gamestate::start_game_system,
                     gamestate::game_over_system,
                     gamestate::update_score_system,
-                    animation::animation_state_machine,
+                    mana::report_mana_changes,
+                    (
+                        animation::request_gameplay_animations,
+                        animation::play_requested_animations,
+                        animation::animation_state_machine,
+                    )
+                        .chain(),
                     animation::update_animation_visibility,
+                    (
+                        animation::mark_offscreen_units.run_if(animation::has_world_view),
+                        animation::update_animation_speed,
+                    )
+                        .before(animation::animate_sprite),
                     animation::animate_sprite,
-                    velocity::translate,
-                    acolyte::acolyte_mana_giver,
+                    animation::apply_tint,
+                    (silhouette::update_silhouettes, silhouette::update_outlines)
+                        .in_set(FrameSet::Presentation),
+                    time_of_day::advance_day_night,
+                    time_of_day::reset_day_night_system,
+                    time_of_day::apply_time_of_day_triggers,
+                    render_scale::update_render_scale.in_set(FrameSet::Presentation),
+                    level_assets::swap_level_assets_system,
+                    level_assets::report_level_assets_system,
                 ),
             );
+
+        // Lets whoever's drawing the sheets see them in game as soon as they're saved
+        #[cfg(debug_assertions)]
+        app.init_resource::<animation::SheetWatcher>()
+            .add_systems(Update, animation::reload_changed_sheets);
+
+        #[cfg(feature = "twitch")]
+        app.add_plugins(crate::twitch::TwitchPlugin);
+
+        #[cfg(feature = "debug_tools")]
+        app.add_plugins(crate::debug_overlay::DebugOverlayPlugin);
+
+        #[cfg(feature = "debug_tools")]
+        app.add_plugins(crate::console::plugin::ConsolePlugin);
+    }
+}
+
+// The level select only lets a level be picked once its definition has loaded
+#[allow(clippy::too_many_arguments)]
+fn load_chosen_level(
+    asset_server: Res<AssetServer>,
+    levels: Res<Levels>,
+    definitions: Res<Assets<LevelDefinition>>,
+    mut active_level: ResMut<ActiveLevel>,
+    mut current_map: ResMut<CurrentMap>,
+    mut rng: ResMut<GameRng>,
+    mut run_seed: ResMut<RunSeed>,
+    difficulty: Res<Difficulty>,
+    mode: Res<GameMode>,
+    mut events: EventWriter<GameEvent>,
+) {
+    let Some(definition) = levels
+        .selected_handle()
+        .and_then(|handle| definitions.get(handle))
+    else {
+        return;
+    };
+
+    info!(
+        "Loading level {} on {}, {}",
+        definition.name,
+        difficulty.name(),
+        mode.name()
+    );
+    *active_level = ActiveLevel::from_definition(definition);
+    current_map.handle = Some(asset_server.load(definition.map.clone()));
+    current_map.spawned = false;
+    rng::start_run(&mut rng, &mut run_seed, definition.seed);
+    events.send(GameEvent::StartGame);
+}
+
+fn leave_level(
+    mut commands: Commands,
+    mut current_map: ResMut<CurrentMap>,
+    mut level_assets: ResMut<level_assets::LevelAssets>,
+    mut pools: ResMut<pool::EntityPools>,
+    assets: level_assets::LevelAssetStores,
+    cleanup_query: Query<Entity, With<gamestate::Cleanup>>,
+) {
+    gamestate::cleanup_game_system(&mut commands, &cleanup_query);
+    // Parked units would otherwise keep the level's textures alive
+    for entity in pools.drain() {
+        commands.entity(entity).despawn_recursive();
     }
+    *current_map = CurrentMap::default();
+    level_assets.unload(&assets);
 }
@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy::sprite::Anchor;
+
+use crate::ai::aggro::Aggro;
+use crate::ai::behavior::CurrentBehavior;
+use crate::render_scale::{UiView, UI_LAYER};
+use crate::units::attack::AttackStats;
+use crate::units::health::Health;
+use crate::units::unit_types::CurrentUnitType;
+
+const OVERLAY_KEY: KeyCode = KeyCode::F4;
+const OVERLAY_MARGIN: f32 = 24.0;
+const OVERLAY_COLOR: Color = Color::rgb(1.0, 0.9, 0.4);
+const LABEL_FONT_SIZE: f32 = 16.0;
+// Under the unit, the nameplate is the one above it
+const LABEL_OFFSET_Y: f32 = -40.0;
+const AGGRO_COLOR: Color = Color::rgba(1.0, 0.9, 0.2, 0.5);
+const ATTACK_COLOR: Color = Color::rgba(1.0, 0.2, 0.2, 0.7);
+
+// Only built with the debug_tools feature, F4 shows what the units are thinking and how fast the
+// frames are going
+#[derive(Resource, Default)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+}
+
+#[derive(Component)]
+pub struct DebugOverlayText;
+
+// Which of a unit's children is its label, so it isn't given a second one
+#[derive(Component)]
+pub struct BehaviorLabelLink(Entity);
+
+#[derive(Component)]
+pub struct BehaviorLabel;
+
+pub fn setup_debug_overlay(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+                    font_size: 22.0,
+                    color: OVERLAY_COLOR,
+                },
+            )
+            .with_justify(JustifyText::Left),
+            text_anchor: Anchor::CenterLeft,
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        DebugOverlayText,
+        RenderLayers::layer(UI_LAYER),
+    ));
+}
+
+pub fn toggle_debug_overlay(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut text_query: Query<&mut Visibility, With<DebugOverlayText>>,
+    link_query: Query<(Entity, &BehaviorLabelLink)>,
+) {
+    if !keys.just_pressed(OVERLAY_KEY) {
+        return;
+    }
+
+    overlay.enabled = !overlay.enabled;
+    for mut visibility in text_query.iter_mut() {
+        *visibility = if overlay.enabled {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+    // The labels are handed out again the next time it's turned on
+    if !overlay.enabled {
+        for (entity, link) in link_query.iter() {
+            commands.entity(link.0).despawn_recursive();
+            commands.entity(entity).remove::<BehaviorLabelLink>();
+        }
+    }
+}
+
+pub fn update_debug_overlay_text(
+    overlay: Res<DebugOverlay>,
+    diagnostics: Res<DiagnosticsStore>,
+    ui_view: Res<UiView>,
+    entity_query: Query<Entity>,
+    unit_query: Query<(&CurrentUnitType, &Health)>,
+    mut text_query: Query<(&mut Text, &mut Transform), With<DebugOverlayText>>,
+) {
+    if !overlay.enabled {
+        return;
+    }
+
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or(0.0);
+    let frame_time = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|frame_time| frame_time.smoothed())
+        .unwrap_or(0.0);
+
+    // Sorted by name so the lines don't jump around
+    let mut counts: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for (unit_type, health) in unit_query.iter() {
+        let (alive, dead) = counts.entry(unit_type.0.name()).or_default();
+        if health.is_dead() {
+            *dead += 1;
+        } else {
+            *alive += 1;
+        }
+    }
+
+    let mut lines = vec![
+        format!("{:.0} fps ({:.1} ms)", fps, frame_time),
+        format!("{} entities", entity_query.iter().len()),
+        String::new(),
+    ];
+    lines.extend(
+        counts
+            .iter()
+            .map(|(name, (alive, dead))| format!("{}: {} ({} dead)", name, alive, dead)),
+    );
+
+    let value = lines.join("\n");
+    for (mut text, mut transform) in text_query.iter_mut() {
+        transform.translation = Vec3::new(-ui_view.half_size.x + OVERLAY_MARGIN, 0.0, 0.0);
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+pub fn update_behavior_labels(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    overlay: Res<DebugOverlay>,
+    unit_query: Query<(
+        Entity,
+        &CurrentBehavior,
+        &Transform,
+        Option<&BehaviorLabelLink>,
+    )>,
+    mut label_query: Query<&mut Text, With<BehaviorLabel>>,
+) {
+    if !overlay.enabled {
+        return;
+    }
+
+    for (entity, behavior, transform, link) in unit_query.iter() {
+        let name = behavior.0.name();
+        if let Some(mut text) = link.and_then(|link| label_query.get_mut(link.0).ok()) {
+            if text.sections[0].value != name {
+                text.sections[0].value = name.to_owned();
+            }
+            continue;
+        }
+
+        // A child like the nameplates, scaled back so every label is the same size
+        let inverse_scale = Vec3::ONE / transform.scale.max(Vec3::splat(0.01));
+        let label = commands
+            .spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        name,
+                        TextStyle {
+                            font: asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+                            font_size: LABEL_FONT_SIZE,
+                            color: OVERLAY_COLOR,
+                        },
+                    )
+                    .with_justify(JustifyText::Center),
+                    transform: Transform::from_xyz(0.0, LABEL_OFFSET_Y, 2.0)
+                        .with_scale(inverse_scale),
+                    ..default()
+                },
+                BehaviorLabel,
+            ))
+            .id();
+        commands
+            .entity(entity)
+            .add_child(label)
+            .insert(BehaviorLabelLink(label));
+    }
+}
+
+type RadiiData = (
+    &'static GlobalTransform,
+    Option<&'static Aggro>,
+    Option<&'static AttackStats>,
+    &'static Health,
+);
+
+// Where a unit starts picking fights, and how close it has to get to hit anything
+pub fn draw_unit_radii(
+    overlay: Res<DebugOverlay>,
+    mut gizmos: Gizmos,
+    query: Query<RadiiData, With<CurrentUnitType>>,
+) {
+    if !overlay.enabled {
+        return;
+    }
+
+    for (transform, aggro, attack_stats, health) in query.iter() {
+        if health.is_dead() {
+            continue;
+        }
+        let position = transform.translation().truncate();
+        if let Some(aggro) = aggro {
+            gizmos.circle_2d(position, aggro.acquisition_radius, AGGRO_COLOR);
+        }
+        if let Some(attack_stats) = attack_stats {
+            gizmos.circle_2d(position, attack_stats.range, ATTACK_COLOR);
+        }
+    }
+}
+
+pub struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin);
+        }
+        app.init_resource::<DebugOverlay>()
+            .add_systems(Startup, setup_debug_overlay)
+            .add_systems(
+                Update,
+                (
+                    toggle_debug_overlay,
+                    (
+                        update_debug_overlay_text,
+                        update_behavior_labels,
+                        draw_unit_radii,
+                    ),
+                )
+                    .chain(),
+            );
+    }
+}
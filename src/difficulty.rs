@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::save::checkpoints::Restored;
+use crate::units::stat_modifiers::{Modifier, ModifierSource, Stat, StatModifiers};
+use crate::units::team::{AllianceMatrix, CurrentTeam, Team};
+
+// Picked on the level select, and applied on top of whatever the level itself asks for
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Nightmare,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Nightmare];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Nightmare => "Nightmare",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|other| other == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(&self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|other| other == self)
+            .unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    pub fn enemy_health(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Nightmare => 1.5,
+        }
+    }
+
+    pub fn spawn_count(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Nightmare => 1.5,
+        }
+    }
+
+    pub fn mana_cost(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.8,
+            Difficulty::Normal => 1.0,
+            Difficulty::Nightmare => 1.25,
+        }
+    }
+
+    // Dying on Nightmare sends you back to the first wave, the others can retry from a checkpoint
+    pub fn allows_checkpoint_restart(&self) -> bool {
+        match self {
+            Difficulty::Easy | Difficulty::Normal => true,
+            Difficulty::Nightmare => false,
+        }
+    }
+
+    // A wave that had any of a kind of enemy in it still has at least one
+    pub fn scale_count(&self, count: u32) -> u32 {
+        if count == 0 {
+            return 0;
+        }
+        ((count as f32 * self.spawn_count()).round() as u32).max(1)
+    }
+
+    pub fn scale_cost(&self, cost: u8) -> u8 {
+        (cost as f32 * self.mana_cost())
+            .round()
+            .clamp(0.0, u8::MAX as f32) as u8
+    }
+}
+
+type UnscaledFilter = (Added<StatModifiers>, Without<Restored>);
+
+// Everything fighting the player comes out tougher or weaker, bosses included
+pub fn apply_difficulty_modifiers(
+    difficulty: Res<Difficulty>,
+    alliances: Res<AllianceMatrix>,
+    mut query: Query<(&mut StatModifiers, &CurrentTeam), UnscaledFilter>,
+) {
+    for (mut modifiers, team) in query.iter_mut() {
+        if !alliances.is_hostile(Team::Evil, team.0) {
+            continue;
+        }
+
+        modifiers.set(
+            ModifierSource::Difficulty,
+            &[Modifier::multiply(
+                Stat::MaxHealth,
+                difficulty.enemy_health(),
+            )],
+        );
+    }
+}
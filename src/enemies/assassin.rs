@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+
+use crate::ai::aggro::Aggro;
+use crate::units::health::Health;
+use crate::units::team::{CurrentTeam, Team};
+use crate::units::unit_types::{Acolyte, CurrentUnitType};
+use crate::utils::timing::Cooldown;
+
+const BLINK_COOLDOWN: f32 = 12.0;
+// Gives the player a moment to see it arrive before it's gone again
+const FIRST_BLINK_DELAY: f32 = 4.0;
+const BLINK_WINDUP: f32 = 1.5;
+// How far from the acolyte it lands, on the side it blinked in from
+const BLINK_OFFSET: f32 = 40.0;
+// Allies closer than this to an acolyte count towards keeping it company
+const ISOLATION_RADIUS: f32 = 200.0;
+// Piled onto the acolyte in the threat table, nothing else it runs into outweighs it for a while
+const MARK_THREAT: f32 = 1000.0;
+
+const TELEGRAPH_COLOR: Color = Color::rgb(0.7, 0.2, 0.9);
+// The ring around the acolyte closes in on it as the blink gets closer
+const TELEGRAPH_START_RADIUS: f32 = 56.0;
+const TELEGRAPH_END_RADIUS: f32 = 16.0;
+
+#[derive(Component, Clone)]
+pub struct Blink {
+    pub cooldown: Cooldown,
+    // The acolyte it's about to land next to, and how long until it does
+    pub windup: Option<(Entity, Timer)>,
+}
+
+impl Default for Blink {
+    fn default() -> Self {
+        let mut cooldown = Cooldown::new(BLINK_COOLDOWN);
+        cooldown.start_for(FIRST_BLINK_DELAY);
+        Self {
+            cooldown,
+            windup: None,
+        }
+    }
+}
+
+// The acolyte with the fewest of the player's units around it, the closest one if it's a tie
+fn most_isolated_acolyte(
+    from: Vec2,
+    acolytes: impl Iterator<Item = (Entity, Vec2)>,
+    allies: &[(Entity, Vec2)],
+) -> Option<Entity> {
+    acolytes
+        .map(|(entity, position)| {
+            let company = allies
+                .iter()
+                .filter(|(ally, ally_position)| {
+                    *ally != entity && ally_position.distance(position) <= ISOLATION_RADIUS
+                })
+                .count();
+            (entity, company, position.distance(from))
+        })
+        .min_by(|a, b| a.1.cmp(&b.1).then(a.2.total_cmp(&b.2)))
+        .map(|(entity, ..)| entity)
+}
+
+pub fn start_blinks(
+    time: Res<Time>,
+    mut assassin_query: Query<(&mut Blink, &Transform, &Health)>,
+    acolyte_query: Query<(Entity, &Transform, &CurrentTeam, &Health), With<Acolyte>>,
+    ally_query: Query<(Entity, &Transform, &CurrentTeam, &Health), With<CurrentUnitType>>,
+) {
+    let is_player_unit =
+        |team: &CurrentTeam, health: &Health| team.0 == Team::Evil && !health.is_dead();
+    let allies: Vec<(Entity, Vec2)> = ally_query
+        .iter()
+        .filter(|(_, _, team, health)| is_player_unit(team, health))
+        .map(|(entity, transform, ..)| (entity, transform.translation.truncate()))
+        .collect();
+
+    for (mut blink, transform, health) in assassin_query.iter_mut() {
+        if health.is_dead() || blink.windup.is_some() {
+            continue;
+        }
+        if !blink.cooldown.tick(time.delta()).is_ready() {
+            continue;
+        }
+
+        let acolytes = acolyte_query
+            .iter()
+            .filter(|(_, _, team, health)| is_player_unit(team, health))
+            .map(|(entity, transform, ..)| (entity, transform.translation.truncate()));
+        if let Some(target) =
+            most_isolated_acolyte(transform.translation.truncate(), acolytes, &allies)
+        {
+            blink.windup = Some((target, Timer::from_seconds(BLINK_WINDUP, TimerMode::Once)));
+        }
+    }
+}
+
+pub fn finish_blinks(
+    time: Res<Time>,
+    mut assassin_query: Query<(&mut Blink, &mut Transform, &mut Aggro, &Health)>,
+    target_query: Query<(&Transform, &Health), Without<Blink>>,
+) {
+    for (mut blink, mut transform, mut aggro, health) in assassin_query.iter_mut() {
+        let Some((target, timer)) = blink.windup.as_mut() else {
+            continue;
+        };
+        let target = *target;
+        if !timer.tick(time.delta()).finished() {
+            continue;
+        }
+
+        blink.windup = None;
+        blink.cooldown.start();
+        // Killed during the windup, or the acolyte was, and the blink fizzles
+        let Ok((target_transform, target_health)) = target_query.get(target) else {
+            continue;
+        };
+        if health.is_dead() || target_health.is_dead() {
+            continue;
+        }
+
+        let target_position = target_transform.translation.truncate();
+        let side = (transform.translation.truncate() - target_position).normalize_or_zero();
+        let landing = target_position + side * BLINK_OFFSET;
+        transform.translation = landing.extend(transform.translation.z);
+
+        aggro.threat.insert(target, MARK_THREAT);
+        aggro.target = Some(target);
+        aggro.anchor = Some(landing);
+    }
+}
+
+// A line from the assassin to its mark and a ring closing in on the mark, for as long as the
+// windup lasts, so there's time to pull the acolyte back or send someone over
+pub fn draw_blink_telegraphs(
+    mut gizmos: Gizmos,
+    assassin_query: Query<(&Blink, &Transform)>,
+    target_query: Query<&Transform, Without<Blink>>,
+) {
+    for (blink, transform) in assassin_query.iter() {
+        let Some((target, timer)) = &blink.windup else {
+            continue;
+        };
+        let Ok(target_transform) = target_query.get(*target) else {
+            continue;
+        };
+
+        let target_position = target_transform.translation.truncate();
+        let radius = TELEGRAPH_START_RADIUS.lerp(TELEGRAPH_END_RADIUS, timer.fraction());
+        gizmos.circle_2d(target_position, radius, TELEGRAPH_COLOR);
+        gizmos.line_2d(
+            transform.translation.truncate(),
+            target_position,
+            TELEGRAPH_COLOR.with_a(0.3),
+        );
+    }
+}
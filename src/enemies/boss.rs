@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+
+use crate::animation::{AtlasLayouts, Tint};
+use crate::events::{BossSpawned, Damaged, GameEvent};
+use crate::levels::definition::ActiveLevel;
+use crate::levels::triggers::TriggerAction;
+use crate::player::relics::{RelicChoices, Relics};
+use crate::rng::GameRng;
+use crate::units::damage::{Armor, Resistances};
+use crate::units::health::Health;
+use crate::units::team::Team;
+use crate::units::unit_types::{
+    spawn_unit_of_type, ArmoredKnight, UnitChildrenSpawnParamsFactory, UnitType,
+};
+use crate::utils::timing::Threshold;
+
+use super::enemy_spawner::EnemySpawner;
+
+const BOSS_NAME: &str = "Sir Aldric the Unbroken";
+const BOSS_HEALTH: i32 = 1200;
+const BOSS_SCALE: f32 = 2.6;
+// Health fractions the boss moves on to its next phase at
+const BOSS_PHASES: [f32; 2] = [0.66, 0.33];
+
+// A champion of the knights, its shield comes back every time it enters a new phase
+#[derive(Component, Debug, Clone)]
+pub struct Boss {
+    pub name: String,
+    // Crossed on the way down, how many have been crossed is the phase the boss is in
+    pub phases: Threshold,
+}
+
+impl Boss {
+    pub fn tint() -> Tint {
+        Tint(Color::rgb(1.0, 0.85, 0.4))
+    }
+
+    // Everything the knights wear, and too big to be shoved around
+    pub fn resistances() -> Resistances {
+        Resistances {
+            knockback: 1.0,
+            ..ArmoredKnight.create_unit_bundle().resistances
+        }
+    }
+
+    pub fn armor() -> Armor {
+        Armor {
+            flat_reduction: 10,
+            hits_to_break: 8,
+            hits_taken: 0,
+        }
+    }
+}
+
+// The last wave a boss was sent on, so one wave never gets two of them
+#[derive(Resource, Default)]
+pub struct BossWave(pub Option<u32>);
+
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_boss(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: AtlasLayouts,
+    level: Res<ActiveLevel>,
+    mut boss_wave: ResMut<BossWave>,
+    mut rng: ResMut<GameRng>,
+    window_query: Query<&Window>,
+    spawner_query: Query<&EnemySpawner>,
+    mut trigger_reader: EventReader<TriggerAction>,
+    mut event_writer: EventWriter<BossSpawned>,
+) {
+    // Levels can send in a boss of their own whenever they like, on top of the scheduled ones
+    let triggered = trigger_reader
+        .read()
+        .any(|action| matches!(action, TriggerAction::SpawnBoss));
+    let Some(spawner) = spawner_query.iter().next() else {
+        return;
+    };
+
+    let scheduled = level.waves.is_boss_wave(spawner.wave) && boss_wave.0 != Some(spawner.wave);
+    if !scheduled && !triggered {
+        return;
+    }
+    if scheduled {
+        boss_wave.0 = Some(spawner.wave);
+    }
+
+    // Endless runs can stack up more than one boss on the scheduled waves
+    let count = if scheduled {
+        spawner.escalation.bosses()
+    } else {
+        1
+    };
+    let window = window_query.single();
+    for _ in 0..count {
+        let position = level.spawn_position(&mut rng, Vec2::new(window.width(), window.height()));
+        let entity = spawn_unit_of_type(
+            &mut commands,
+            &asset_server,
+            &mut texture_atlas_layouts,
+            UnitType::ArmoredKnight,
+            Team::Good,
+            position,
+        )
+        .insert((
+            Boss {
+                name: BOSS_NAME.to_owned(),
+                phases: Threshold::falling(&BOSS_PHASES),
+            },
+            Boss::tint(),
+            Boss::armor(),
+            Boss::resistances(),
+            Health::new(BOSS_HEALTH),
+            Transform::from_translation(position.extend(0.0)).with_scale(Vec3::splat(BOSS_SCALE)),
+        ))
+        .id();
+
+        info!("{} joins wave {}", BOSS_NAME, spawner.wave);
+        event_writer.send(BossSpawned {
+            entity,
+            name: BOSS_NAME.to_owned(),
+        });
+    }
+}
+
+pub fn update_boss_phase(mut query: Query<(&mut Boss, &Health, &mut Armor, &mut Tint)>) {
+    for (mut boss, health, mut armor, mut tint) in query.iter_mut() {
+        if health.is_dead() {
+            continue;
+        }
+
+        // A big enough hit can skip a phase, the shield still only comes back the once
+        let mut entered_phase = false;
+        while boss.phases.cross(health.fraction()).is_some() {
+            entered_phase = true;
+        }
+        if !entered_phase {
+            continue;
+        }
+
+        armor.hits_taken = 0;
+        *tint = Boss::tint();
+    }
+}
+
+// Bringing a boss down is always worth a relic
+pub fn reward_boss_kills(
+    mut damaged_reader: EventReader<Damaged>,
+    relics: Res<Relics>,
+    mut choices: ResMut<RelicChoices>,
+    boss_query: Query<&Boss>,
+) {
+    for damaged in damaged_reader.read() {
+        let Ok(boss) = boss_query.get(damaged.target) else {
+            continue;
+        };
+        if !damaged.killed {
+            continue;
+        }
+
+        let offer = relics.offer();
+        if !offer.is_empty() {
+            info!("Defeated {}", boss.name);
+            choices.0.push_back(offer);
+        }
+    }
+}
+
+pub fn reset_boss_wave_system(
+    mut event_reader: EventReader<GameEvent>,
+    mut boss_wave: ResMut<BossWave>,
+) {
+    for event in event_reader.read() {
+        if let GameEvent::StartGame = event {
+            boss_wave.0 = None;
+        }
+    }
+}
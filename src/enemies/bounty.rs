@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+
+use crate::events::Damaged;
+use crate::player::relics::{RelicChoices, Relics};
+
+use super::enemy_spawner::EnemySpawner;
+
+const BOUNTY_ICON: &str = "\u{f068c}";
+const BOUNTY_ICON_OFFSET_Y: f32 = 56.0;
+const BOUNTY_COLOR: Color = Color::rgb(1.0, 0.8, 0.2);
+const MARKER_EDGE_MARGIN: f32 = 32.0;
+const MARKER_RADIUS: f32 = 14.0;
+
+// Only pays out if the marked enemy dies during the wave it came with
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Bounty {
+    pub wave: u32,
+}
+
+#[derive(Component)]
+pub struct BountyIcon;
+
+pub fn spawn_bounty_icons(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    query: Query<(Entity, &Transform), Added<Bounty>>,
+) {
+    for (entity, transform) in query.iter() {
+        let inverse_scale = Vec3::ONE / transform.scale.max(Vec3::splat(0.01));
+        let icon = commands
+            .spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        BOUNTY_ICON,
+                        TextStyle {
+                            font: asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+                            font_size: 28.0,
+                            color: BOUNTY_COLOR,
+                        },
+                    ),
+                    transform: Transform::from_xyz(
+                        0.0,
+                        BOUNTY_ICON_OFFSET_Y / transform.scale.y.max(0.01),
+                        2.0,
+                    )
+                    .with_scale(inverse_scale),
+                    ..default()
+                },
+                BountyIcon,
+            ))
+            .id();
+        commands.entity(entity).add_child(icon);
+    }
+}
+
+fn remove_bounty(
+    commands: &mut Commands,
+    entity: Entity,
+    children: Option<&Children>,
+    icon_query: &Query<(), With<BountyIcon>>,
+) {
+    commands.entity(entity).remove::<Bounty>();
+    for &child in children.into_iter().flatten() {
+        if icon_query.contains(child) {
+            commands.entity(child).despawn_recursive();
+        }
+    }
+}
+
+pub fn claim_bounties(
+    mut commands: Commands,
+    mut damaged_reader: EventReader<Damaged>,
+    relics: Res<Relics>,
+    mut choices: ResMut<RelicChoices>,
+    spawner_query: Query<&EnemySpawner>,
+    bounty_query: Query<(&Bounty, Option<&Children>)>,
+    icon_query: Query<(), With<BountyIcon>>,
+) {
+    let wave = spawner_query
+        .iter()
+        .next()
+        .map_or(0, |spawner| spawner.wave);
+
+    for damaged in damaged_reader.read() {
+        if !damaged.killed {
+            continue;
+        }
+        let Ok((bounty, children)) = bounty_query.get(damaged.target) else {
+            continue;
+        };
+
+        if bounty.wave == wave {
+            let offer = relics.offer();
+            if !offer.is_empty() {
+                info!("Claimed the bounty of wave {}", bounty.wave);
+                choices.0.push_back(offer);
+            }
+        }
+        remove_bounty(&mut commands, damaged.target, children, &icon_query);
+    }
+}
+
+// Whatever is still walking around once its wave is over isn't worth anything anymore
+pub fn expire_bounties(
+    mut commands: Commands,
+    spawner_query: Query<&EnemySpawner>,
+    bounty_query: Query<(Entity, &Bounty, Option<&Children>)>,
+    icon_query: Query<(), With<BountyIcon>>,
+) {
+    let wave = spawner_query
+        .iter()
+        .next()
+        .map_or(0, |spawner| spawner.wave);
+    for (entity, bounty, children) in bounty_query.iter() {
+        if bounty.wave != wave {
+            remove_bounty(&mut commands, entity, children, &icon_query);
+        }
+    }
+}
+
+// Off screen bounties are pointed out at the edge of the screen, the same way as incoming spawns
+pub fn draw_bounty_markers(
+    mut gizmos: Gizmos,
+    window_query: Query<&Window>,
+    bounty_query: Query<&GlobalTransform, With<Bounty>>,
+) {
+    let window = window_query.single();
+    let edge = Vec2::new(window.width(), window.height()) * 0.5 - MARKER_EDGE_MARGIN;
+
+    for transform in bounty_query.iter() {
+        let position = transform.translation().truncate();
+        let clamped = position.clamp(-edge, edge);
+        if clamped != position {
+            gizmos.circle_2d(clamped, MARKER_RADIUS, BOUNTY_COLOR);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::rng::GameRng;
+use crate::units::stat_modifiers::{Modifier, ModifierSource, Stat, StatModifiers};
+use crate::units::team::{AllianceMatrix, CurrentTeam, Team};
+
+use super::enemy_spawner::{EnemySpawner, WaveComposition};
+
+const HASTE_PER_STACK: f32 = 0.08;
+// Each stack puts another tenth of the knights into armor, until all of them are
+const ARMOR_PER_STACK: f32 = 0.1;
+const MAX_ARMOR_STACKS: u32 = 10;
+// One boss on its own, then two, then three
+const MAX_EXTRA_BOSSES: u32 = 2;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndlessModifier {
+    Haste,      // Every enemy moves and swings a bit faster
+    Armored,    // More of the knights show up in armor
+    TwinBosses, // Boss waves send one more boss
+}
+
+impl EndlessModifier {
+    pub const ALL: [EndlessModifier; 3] = [
+        EndlessModifier::Haste,
+        EndlessModifier::Armored,
+        EndlessModifier::TwinBosses,
+    ];
+}
+
+// What an endless run has stacked up so far, one more stack for every wave past the scripted ones
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Escalation {
+    pub haste: u32,
+    pub armored: u32,
+    pub extra_bosses: u32,
+}
+
+impl Escalation {
+    pub fn stacks(&self) -> u32 {
+        self.haste + self.armored + self.extra_bosses
+    }
+
+    fn is_maxed(&self, modifier: EndlessModifier) -> bool {
+        match modifier {
+            EndlessModifier::Haste => false,
+            EndlessModifier::Armored => self.armored >= MAX_ARMOR_STACKS,
+            EndlessModifier::TwinBosses => self.extra_bosses >= MAX_EXTRA_BOSSES,
+        }
+    }
+
+    // Rolled off its own stream from the run seed rather than the game's, so the same run always
+    // stacks the same way however far in it is picked up again, like after a checkpoint restart
+    pub fn at_depth(seed: u64, depth: u32) -> Self {
+        let mut rng = GameRng::from_seed(seed);
+        let mut escalation = Self::default();
+        for _ in 0..depth {
+            escalation.escalate(&mut rng);
+        }
+        escalation
+    }
+
+    // Rolls which modifier the wave stacks, out of the ones that still have room to grow
+    fn escalate(&mut self, rng: &mut GameRng) -> EndlessModifier {
+        let open: Vec<EndlessModifier> = EndlessModifier::ALL
+            .into_iter()
+            .filter(|modifier| !self.is_maxed(*modifier))
+            .collect();
+        let modifier = *open.choose(&mut rng.0).unwrap_or(&EndlessModifier::Haste);
+        match modifier {
+            EndlessModifier::Haste => self.haste += 1,
+            EndlessModifier::Armored => self.armored += 1,
+            EndlessModifier::TwinBosses => self.extra_bosses += 1,
+        }
+        modifier
+    }
+
+    pub fn apply(&self, composition: &mut WaveComposition) {
+        let armored = (composition.knights as f32 * ARMOR_PER_STACK * self.armored as f32) as u32;
+        let armored = armored.min(composition.knights);
+        composition.knights -= armored;
+        composition.armored_knights += armored;
+    }
+
+    pub fn bosses(&self) -> u32 {
+        1 + self.extra_bosses
+    }
+
+    fn modifiers(&self) -> [Modifier; 2] {
+        let haste = 1.0 + HASTE_PER_STACK * self.haste as f32;
+        [
+            Modifier::multiply(Stat::MoveSpeed, haste),
+            Modifier::multiply(Stat::AttackSpeed, haste),
+        ]
+    }
+}
+
+// Enemies spawned once the run has some haste stacked come in with it
+pub fn apply_endless_modifiers(
+    alliances: Res<AllianceMatrix>,
+    spawner_query: Query<&EnemySpawner>,
+    mut query: Query<(&mut StatModifiers, &CurrentTeam), Added<StatModifiers>>,
+) {
+    let Some(escalation) = spawner_query
+        .iter()
+        .next()
+        .map(|spawner| spawner.escalation)
+        .filter(|escalation| escalation.haste > 0)
+    else {
+        return;
+    };
+
+    for (mut modifiers, team) in query.iter_mut() {
+        if alliances.is_hostile(Team::Evil, team.0) {
+            modifiers.set(ModifierSource::Endless, &escalation.modifiers());
+        }
+    }
+}
@@ -1,9 +1,22 @@
 use bevy::prelude::*;
 use bevy::window::Window;
+use rand::seq::SliceRandom;
+use rand::Rng;
 
-use crate::enemies::plugin::SpawnTimer;
+use crate::difficulty::Difficulty;
+use crate::enemies::endless::Escalation;
+use crate::enemies::mutators::{NextWaveMutator, WaveMutator};
+use crate::enemies::spawn_queue::{SpawnQueue, SpawnRequest};
+use crate::events::WaveStarted;
+use crate::game_mode::GameMode;
+use crate::game_view::GameAction;
+use crate::levels::definition::{ActiveLevel, WaveSchedule};
+use crate::mana::Mana;
+use crate::player::plugin::Player;
+use crate::rng::{GameRng, RunSeed};
 use crate::units::team::Team;
-use crate::units::unit_types::{spawn_unit, Knight};
+use crate::units::unit_types::UnitType;
+use crate::utils::timing::Charge;
 
 enum EnemyDirection {
     Top,
@@ -13,8 +26,8 @@ enum EnemyDirection {
 }
 
 impl EnemyDirection {
-    fn new() -> Self {
-        match rand::random::<u8>() % 4 {
+    fn new(rng: &mut GameRng) -> Self {
+        match rng.0.gen_range(0..4) {
             0 => Self::Top,
             1 => Self::Right,
             2 => Self::Bottom,
@@ -25,62 +38,283 @@ impl EnemyDirection {
 }
 
 const ENEMY_SPAWN_OFFSET: f32 = 256.0;
+// Starting the next wave before it's due pays out mana for every second that was skipped
+const EARLY_START_MANA_PER_SECOND: f32 = 1.0;
 
 #[derive(Component)]
-pub struct EnemySpawner;
+pub struct EnemySpawner {
+    pub wave: u32,
+    pub wave_charge: Charge,
+    pub mutator: Option<WaveMutator>,
+    pub escalation: Escalation,
+}
+
+impl Default for EnemySpawner {
+    fn default() -> Self {
+        Self::new(&WaveSchedule::default())
+    }
+}
+
+impl EnemySpawner {
+    pub fn new(schedule: &WaveSchedule) -> Self {
+        Self {
+            wave: 0,
+            wave_charge: Charge::new(schedule.interval),
+            mutator: None,
+            escalation: Escalation::default(),
+        }
+    }
+
+    pub fn seconds_to_next_wave(&self) -> f32 {
+        self.wave_charge.interval() * (1.0 - self.wave_charge.fraction())
+    }
+
+    pub fn early_start_bonus(&self) -> u8 {
+        (self.seconds_to_next_wave() * EARLY_START_MANA_PER_SECOND).floor() as u8
+    }
+
+    // A campaign run has nothing more to send once the last scripted wave is out
+    pub fn has_next_wave(&self, level: &ActiveLevel, mode: GameMode) -> bool {
+        mode != GameMode::Campaign || level.waves.is_scripted(self.wave + 1)
+    }
+
+    // What the next wave brings, made up the same way spawn_enemies does it. An endless wave
+    // nobody picked a mutator for rolls one as it starts, so that part can't be known up front.
+    pub fn next_composition(
+        &self,
+        level: &ActiveLevel,
+        difficulty: &Difficulty,
+        run_seed: &RunSeed,
+        mutator: Option<WaveMutator>,
+    ) -> WaveComposition {
+        let wave = self.wave + 1;
+        let depth = level.waves.endless_depth(wave);
+        let escalation = if depth > 0 {
+            Escalation::at_depth(run_seed.current, depth)
+        } else {
+            self.escalation
+        };
+        compose_wave(level, difficulty, wave, mutator, &escalation)
+    }
+}
+
+pub fn apply_start_early_actions(
+    level: Res<ActiveLevel>,
+    mode: Res<GameMode>,
+    mut actions: EventReader<GameAction>,
+    mut spawner_query: Query<&mut EnemySpawner>,
+    mut player_query: Query<&mut Mana, With<Player>>,
+) {
+    // All of them read, a second press the same frame shouldn't skip the wave after as well
+    let requested = actions
+        .read()
+        .filter(|action| matches!(action, GameAction::StartWaveEarly))
+        .count();
+    if requested == 0 {
+        return;
+    }
+    let (Ok(mut spawner), Ok(mut mana)) = (
+        spawner_query.get_single_mut(),
+        player_query.get_single_mut(),
+    ) else {
+        return;
+    };
+    if spawner.wave_charge.is_paused() || !spawner.has_next_wave(&level, *mode) {
+        return;
+    }
+
+    let bonus = spawner.early_start_bonus();
+    mana.current_mana = mana.current_mana.saturating_add(bonus).min(mana.max_mana);
+    // Charged up all the way, so spawn_enemies sends the wave out this frame
+    let interval = spawner.wave_charge.interval();
+    spawner.wave_charge.set_elapsed(interval);
+}
+
+fn compose_wave(
+    level: &ActiveLevel,
+    difficulty: &Difficulty,
+    wave: u32,
+    mutator: Option<WaveMutator>,
+    escalation: &Escalation,
+) -> WaveComposition {
+    let mut composition = level.waves.composition(wave).scaled(difficulty);
+    if let Some(mutator) = mutator {
+        mutator.apply(&mut composition, wave);
+    }
+    escalation.apply(&mut composition);
+    composition
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WaveComposition {
+    pub knights: u32,
+    pub gargoyles: u32,
+    pub armored_knights: u32,
+    pub assassins: u32,
+}
 
+impl WaveComposition {
+    pub fn total(&self) -> u32 {
+        self.knights + self.gargoyles + self.armored_knights + self.assassins
+    }
+
+    pub fn scaled(&self, difficulty: &Difficulty) -> Self {
+        Self {
+            knights: difficulty.scale_count(self.knights),
+            gargoyles: difficulty.scale_count(self.gargoyles),
+            armored_knights: difficulty.scale_count(self.armored_knights),
+            assassins: difficulty.scale_count(self.assassins),
+        }
+    }
+
+    pub fn elite(&self) -> Option<UnitType> {
+        if self.armored_knights > 0 {
+            Some(UnitType::ArmoredKnight)
+        } else if self.gargoyles > 0 {
+            Some(UnitType::Gargoyle)
+        } else if self.knights > 0 {
+            Some(UnitType::Knight)
+        } else {
+            None
+        }
+    }
+
+    pub fn unit_types(&self) -> impl Iterator<Item = UnitType> {
+        std::iter::repeat_n(UnitType::Knight, self.knights as usize)
+            .chain(std::iter::repeat_n(
+                UnitType::Gargoyle,
+                self.gargoyles as usize,
+            ))
+            .chain(std::iter::repeat_n(
+                UnitType::ArmoredKnight,
+                self.armored_knights as usize,
+            ))
+            .chain(std::iter::repeat_n(
+                UnitType::Assassin,
+                self.assassins as usize,
+            ))
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_enemies(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
     time: Res<Time>,
-    mut timer: ResMut<SpawnTimer>,
+    mut spawn_queue: ResMut<SpawnQueue>,
+    mut next_mutator: ResMut<NextWaveMutator>,
+    level: Res<ActiveLevel>,
+    difficulty: Res<Difficulty>,
+    mode: Res<GameMode>,
+    run_seed: Res<RunSeed>,
+    mut rng: ResMut<GameRng>,
     window_query: Query<&Window>,
-    enemy_spawner_query: Query<&EnemySpawner>,
+    mut enemy_spawner_query: Query<&mut EnemySpawner>,
+    mut wave_writer: EventWriter<WaveStarted>,
 ) {
-    if enemy_spawner_query.iter().count() == 0 {
+    let Some(mut spawner) = enemy_spawner_query.iter_mut().next() else {
+        return;
+    };
+    if !spawner.has_next_wave(&level, *mode) {
         return;
     }
 
-    if !timer.0.tick(time.delta()).just_finished() {
+    if !spawner.wave_charge.tick(time.delta()).just_charged() {
         return;
     }
 
+    spawner.wave += 1;
+    spawner.mutator = next_mutator.0.take();
+
+    // Past the script the waves make themselves up, with a mutator of their own if nobody picked
+    // one and another modifier stacked on top of everything before
+    let depth = level.waves.endless_depth(spawner.wave);
+    if depth > 0 {
+        if spawner.mutator.is_none() {
+            spawner.mutator = WaveMutator::ALL.choose(&mut rng.0).copied();
+        }
+        spawner.escalation = Escalation::at_depth(run_seed.current, depth);
+        info!("Endless wave {} stacks {:?}", depth, spawner.escalation);
+    }
+    wave_writer.send(WaveStarted {
+        wave: spawner.wave,
+        endless_depth: depth,
+    });
+
     let window = window_query.single();
     let play_area = Vec2::new(window.width(), window.height());
 
+    if let Some(mutator) = spawner.mutator {
+        info!("Wave {} is mutated: {}", spawner.wave, mutator.name());
+    }
+    let composition = compose_wave(
+        &level,
+        &difficulty,
+        spawner.wave,
+        spawner.mutator,
+        &spawner.escalation,
+    );
+
+    // The toughest enemy in the wave carries the bounty
+    let mut bounty = level
+        .waves
+        .is_bounty_wave(spawner.wave)
+        .then(|| composition.elite())
+        .flatten();
+
+    // The whole wave is handed to the spawn queue, which spreads the actual spawning out over
+    // several frames instead of spawning everything on the frame the wave starts.
+    // Shuffled so the elites don't always trail in at the back of the wave
+    let mut unit_types: Vec<UnitType> = composition.unit_types().collect();
+    unit_types.shuffle(&mut rng.0);
+    // The first knights of the wave fill up the level's patrols, the rest go for the altar
+    let mut patrols = level
+        .patrols
+        .iter()
+        .enumerate()
+        .filter(|(_, patrol)| patrol.from_wave <= spawner.wave)
+        .flat_map(|(index, patrol)| std::iter::repeat_n(index, patrol.knights as usize));
+    for unit_type in unit_types {
+        let is_bounty = bounty == Some(unit_type);
+        if is_bounty {
+            bounty = None;
+        }
+        let patrol = (unit_type == UnitType::Knight && !is_bounty)
+            .then(|| patrols.next())
+            .flatten();
+
+        spawn_queue.push(SpawnRequest {
+            unit_type,
+            team: Team::Good,
+            position: level.spawn_position(&mut rng, play_area),
+            bounty: is_bounty.then_some(spawner.wave),
+            patrol,
+        });
+    }
+}
+
+pub fn random_spawn_position(rng: &mut GameRng, play_area: Vec2) -> Vec2 {
     // Randomize a direction for the enemy to spawn from, either top, right, bottom, or left
     // The enemies will have a random offset from the edge of the screen of the chosen direction.
     // The offset will be within the range of 0 to ENEMY_SPAWN_OFFSET
     // The enemy will spawn at a random position along the chosen edge, which will be from 0, and
     // and matching the play_area dimension perpendicular to the chosen edge.
-    let random_direction = EnemyDirection::new();
-    let random_offset = rand::random::<f32>() * ENEMY_SPAWN_OFFSET;
-    let spawn_position = match random_direction {
+    let random_direction = EnemyDirection::new(rng);
+    let random_offset = rng.0.gen::<f32>() * ENEMY_SPAWN_OFFSET;
+    match random_direction {
         EnemyDirection::Top => Vec2::new(
-            rand::random::<f32>() * play_area.x - play_area.x * 0.5,
+            rng.0.gen::<f32>() * play_area.x - play_area.x * 0.5,
             play_area.y * 0.5 + random_offset,
         ),
         EnemyDirection::Right => Vec2::new(
             play_area.x * 0.5 + random_offset,
-            rand::random::<f32>() * play_area.y - play_area.y * 0.5,
+            rng.0.gen::<f32>() * play_area.y - play_area.y * 0.5,
         ),
         EnemyDirection::Bottom => Vec2::new(
-            rand::random::<f32>() * play_area.x - play_area.x * 0.5,
+            rng.0.gen::<f32>() * play_area.x - play_area.x * 0.5,
             -play_area.y * 0.5 - random_offset,
         ),
         EnemyDirection::Left => Vec2::new(
             -play_area.x * 0.5 - random_offset,
-            rand::random::<f32>() * play_area.y - play_area.y * 0.5,
+            rng.0.gen::<f32>() * play_area.y - play_area.y * 0.5,
         ),
-    };
-
-    spawn_unit(
-        &mut commands,
-        &asset_server,
-        &mut texture_atlas_layouts,
-        Knight,
-        Team::Good,
-        spawn_position,
-    );
+    }
 }
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::events::GameEvent;
+
+use super::enemy_spawner::WaveComposition;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WaveMutator {
+    Swarm,    // Half again as many knights
+    Armored,  // Half of the knights show up in armor
+    Airborne, // Extra gargoyles, no matter the wave
+}
+
+impl WaveMutator {
+    pub const ALL: [WaveMutator; 3] = [
+        WaveMutator::Swarm,
+        WaveMutator::Armored,
+        WaveMutator::Airborne,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WaveMutator::Swarm => "swarm",
+            WaveMutator::Armored => "armored",
+            WaveMutator::Airborne => "airborne",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|mutator| mutator.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn apply(&self, composition: &mut WaveComposition, wave: u32) {
+        match self {
+            WaveMutator::Swarm => composition.knights += composition.knights / 2,
+            WaveMutator::Armored => {
+                let armored = composition.knights / 2;
+                composition.knights -= armored;
+                composition.armored_knights += armored;
+            }
+            WaveMutator::Airborne => composition.gargoyles += 1 + wave / 2,
+        }
+    }
+}
+
+// Consumed by the enemy spawner when the next wave starts
+#[derive(Resource, Default)]
+pub struct NextWaveMutator(pub Option<WaveMutator>);
+
+pub fn clear_next_mutator_system(
+    mut event_reader: EventReader<GameEvent>,
+    mut next_mutator: ResMut<NextWaveMutator>,
+) {
+    for event in event_reader.read() {
+        if let GameEvent::StartGame = event {
+            next_mutator.0 = None;
+        }
+    }
+}
@@ -1,15 +1,37 @@
 use bevy::prelude::*;
 
-use crate::enemies::enemy_spawner;
+use crate::enemies::{assassin, boss, bounty, enemy_spawner, mutators, spawn_queue};
 
 pub struct EnemyPlugin;
 
-#[derive(Resource)]
-pub struct SpawnTimer(pub Timer);
-
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(SpawnTimer(Timer::from_seconds(2.0, TimerMode::Repeating)))
-            .add_systems(Update, enemy_spawner::spawn_enemies);
+        app.init_resource::<spawn_queue::SpawnQueue>()
+            .init_resource::<spawn_queue::SpawnMetrics>()
+            .init_resource::<mutators::NextWaveMutator>()
+            .init_resource::<boss::BossWave>()
+            .add_systems(
+                Update,
+                (
+                    (
+                        enemy_spawner::apply_start_early_actions,
+                        enemy_spawner::spawn_enemies,
+                    )
+                        .chain(),
+                    spawn_queue::process_spawn_queue.after(enemy_spawner::spawn_enemies),
+                    spawn_queue::draw_spawn_telegraphs,
+                    spawn_queue::clear_spawn_queue_system,
+                    mutators::clear_next_mutator_system,
+                    boss::spawn_boss.after(enemy_spawner::spawn_enemies),
+                    boss::update_boss_phase,
+                    boss::reward_boss_kills,
+                    boss::reset_boss_wave_system,
+                    bounty::spawn_bounty_icons,
+                    (bounty::claim_bounties, bounty::expire_bounties).chain(),
+                    bounty::draw_bounty_markers,
+                    (assassin::start_blinks, assassin::finish_blinks).chain(),
+                    assassin::draw_blink_telegraphs,
+                ),
+            );
     }
 }
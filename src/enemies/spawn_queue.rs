@@ -0,0 +1,152 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::ai::behavior::Waypoints;
+use crate::animation::AtlasLayouts;
+use crate::enemies::bounty::Bounty;
+use crate::events::GameEvent;
+use crate::levels::definition::ActiveLevel;
+use crate::pool::EntityPools;
+use crate::units::team::Team;
+use crate::units::unit_types::{spawn_units_of_type, UnitType};
+
+const DEFAULT_SPAWNS_PER_FRAME: usize = 4;
+const TELEGRAPH_EDGE_MARGIN: f32 = 48.0;
+const TELEGRAPH_RADIUS: f32 = 24.0;
+
+#[derive(Clone, Copy, Debug)]
+pub struct SpawnRequest {
+    pub unit_type: UnitType,
+    pub team: Team,
+    pub position: Vec2,
+    // The wave this unit is the bounty of, if it is one
+    pub bounty: Option<u32>,
+    // Which of the level's patrols this unit walks, if any
+    pub patrol: Option<usize>,
+}
+
+#[derive(Resource)]
+pub struct SpawnQueue {
+    pub pending: VecDeque<SpawnRequest>,
+    pub spawns_per_frame: usize,
+}
+
+impl Default for SpawnQueue {
+    fn default() -> Self {
+        Self {
+            pending: VecDeque::new(),
+            spawns_per_frame: DEFAULT_SPAWNS_PER_FRAME,
+        }
+    }
+}
+
+impl SpawnQueue {
+    pub fn push(&mut self, request: SpawnRequest) {
+        self.pending.push_back(request);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+// Collected while the queue is draining, and logged once it is empty again. Compare the worst
+// frame with `spawns_per_frame` at different values to see how much the budget smooths things out.
+#[derive(Resource, Default, Debug)]
+pub struct SpawnMetrics {
+    pub total_spawned: usize,
+    pub frames_spawning: u32,
+    pub max_spawned_in_frame: usize,
+    pub worst_frame_seconds: f32,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn process_spawn_queue(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: AtlasLayouts,
+    mut pools: ResMut<EntityPools>,
+    level: Res<ActiveLevel>,
+    time: Res<Time>,
+    mut spawn_queue: ResMut<SpawnQueue>,
+    mut metrics: ResMut<SpawnMetrics>,
+) {
+    if spawn_queue.is_empty() {
+        if metrics.frames_spawning > 0 {
+            info!(
+                "Spawned {} units over {} frames, at most {} in one frame, worst frame {:.2} ms",
+                metrics.total_spawned,
+                metrics.frames_spawning,
+                metrics.max_spawned_in_frame,
+                metrics.worst_frame_seconds * 1000.0,
+            );
+            *metrics = SpawnMetrics::default();
+        }
+
+        return;
+    }
+
+    // The frame time of this frame reflects the spawns from the previous one
+    metrics.worst_frame_seconds = metrics.worst_frame_seconds.max(time.delta_seconds());
+
+    let budget = spawn_queue
+        .spawns_per_frame
+        .max(1)
+        .min(spawn_queue.pending.len());
+    let requests: Vec<SpawnRequest> = spawn_queue.pending.drain(..budget).collect();
+    let entities = spawn_units_of_type(
+        &mut commands,
+        &asset_server,
+        &mut texture_atlas_layouts,
+        &mut pools,
+        requests
+            .iter()
+            .map(|request| (request.unit_type, request.team, request.position)),
+    );
+    for (entity, request) in entities.into_iter().zip(requests.iter()) {
+        if let Some(wave) = request.bounty {
+            commands.entity(entity).insert(Bounty { wave });
+        }
+        if let Some(patrol) = request.patrol.and_then(|index| level.patrols.get(index)) {
+            commands.entity(entity).insert(Waypoints(patrol.route()));
+        }
+    }
+    let spawned = requests.len();
+
+    metrics.total_spawned += spawned;
+    metrics.frames_spawning += 1;
+    metrics.max_spawned_in_frame = metrics.max_spawned_in_frame.max(spawned);
+}
+
+// Enemies spawn outside of the screen, so the telegraph is clamped to the screen edge to show
+// where they will be coming from until they have actually been spawned.
+pub fn draw_spawn_telegraphs(
+    mut gizmos: Gizmos,
+    spawn_queue: Res<SpawnQueue>,
+    window_query: Query<&Window>,
+) {
+    let window = window_query.single();
+    let edge = Vec2::new(window.width(), window.height()) * 0.5 - TELEGRAPH_EDGE_MARGIN;
+
+    for request in spawn_queue.pending.iter() {
+        let telegraph_position = request.position.clamp(-edge, edge);
+        let color = if request.team == Team::Evil {
+            Color::PURPLE
+        } else {
+            Color::ORANGE_RED
+        };
+        gizmos.circle_2d(telegraph_position, TELEGRAPH_RADIUS, color);
+    }
+}
+
+pub fn clear_spawn_queue_system(
+    mut event_reader: EventReader<GameEvent>,
+    mut spawn_queue: ResMut<SpawnQueue>,
+) {
+    for event in event_reader.read() {
+        if let GameEvent::StartGame | GameEvent::RestartFromCheckpoint = event {
+            spawn_queue.pending.clear();
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use bevy::prelude::*;
+
+use crate::ai::behavior::Behavior;
+use crate::animation::AnimationType;
+use crate::pickups::drops::PickupKind;
+use crate::stats::grading::Grade;
+use crate::stats::run_stats::WaveWindow;
+use crate::units::damage::DamageKind;
+use crate::units::team::Team;
+use crate::units::unit_types::UnitType;
+
+// Every gameplay event lives here, so the ui, the stats and anything hooked on later like audio
+// or achievements have one place to listen to instead of reaching into every feature.
+//
+// Naming: requests are named for what they ask for (Damage, Heal, Convert) and are read by the
+// one system that owns what they change. Everything else reports something that already
+// happened and is named in the past tense (Damaged, UnitDied, WaveStarted), for anyone to read.
+//
+// Lifetime: an event can be read for the frame it was sent in and the whole frame after, then
+// it's gone. Readers after the sender in the frame order see it the same frame, readers before
+// it see it the next one, and a reader that skips two frames in a row (a run condition that was
+// off, a state that wasn't active) misses it for good. Player input goes through GameAction in
+// game_view instead, that one is what both the keyboard and the twitch chat drive.
+
+// The run as a whole starting, ending or scoring
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameEvent {
+    StartGame,
+    RestartFromCheckpoint,
+    GameOver,
+    IncreaseScore,
+}
+
+// Every hit in the game goes through this event instead of touching Health directly, the
+// pipeline in apply_damage then runs it through resistances and armor before subtracting.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Damage {
+    pub target: Entity,
+    pub amount: i32,
+    pub kind: DamageKind,
+    // Skips the armor stage, and doesn't count as a hit towards breaking it either
+    pub armor_piercing: bool,
+    // Whoever dealt it, if it was someone rather than a trap or an explosion
+    pub source: Option<Entity>,
+    // Already multiplied in by the attacker, this only tells everyone it happened
+    pub critical: bool,
+}
+
+// Sent after a hit went through the pipeline, with the health that was actually lost
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Damaged {
+    pub target: Entity,
+    pub amount: i32,
+    pub kind: DamageKind,
+    pub killed: bool,
+    pub source: Option<Entity>,
+    pub critical: bool,
+    pub dodged: bool,
+}
+
+// Right after the Damaged that killed it, while the entity is still around to look at
+#[derive(Event, Debug, Clone, Copy)]
+pub struct UnitDied {
+    pub entity: Entity,
+    // The summoner isn't a unit type, everything else is
+    pub unit_type: Option<UnitType>,
+    pub team: Team,
+    pub position: Vec2,
+    pub killer: Option<Entity>,
+}
+
+// Request to restore health, the counterpart to the Damage event
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Heal {
+    pub target: Entity,
+    pub amount: i32,
+    // Whoever channeled it, None for pickups and perks
+    pub source: Option<Entity>,
+}
+
+// Sent after a heal has been applied, with the amount that was actually restored
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Healed {
+    pub target: Entity,
+    pub amount: i32,
+    pub source: Option<Entity>,
+}
+
+// The summoner called on a unit, or brought one back from its gravestone
+#[derive(Event, Debug, Clone, Copy)]
+pub struct UnitSummoned {
+    pub entity: Entity,
+    pub unit_type: UnitType,
+    pub revived: bool,
+}
+
+// A summon the run just earned the right to call on
+#[derive(Event, Debug, Clone, Copy)]
+pub struct UnitUnlocked(pub UnitType);
+
+// Sent whenever the player casts anything, summons included. Power is how much dark magic went
+// into the cast, which is the mana cost for everything that costs mana.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SpellCast {
+    pub power: u8,
+}
+
+// The summoner's mana went up or down, whatever it was spent on or came from
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ManaChanged {
+    pub previous: u8,
+    pub current: u8,
+    pub max: u8,
+}
+
+// The summoner walked over something a fallen enemy dropped
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PickupCollected {
+    pub kind: PickupKind,
+    pub position: Vec2,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct WaveStarted {
+    pub wave: u32,
+    // How far past the level's script it is, 0 while it's still scripted
+    pub endless_depth: u32,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct BossSpawned {
+    pub entity: Entity,
+    pub name: String,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct WaveWindowClosed(pub WaveWindow);
+
+#[derive(Event, Debug, Clone)]
+pub struct WaveGraded {
+    pub wave: u32,
+    pub grade: Grade,
+    pub souls: u32,
+}
+
+// Sent whenever a unit switches behavior, for anything that wants to react to what the ai is
+// up to without polling CurrentBehavior every frame
+#[derive(Event, Clone, Debug)]
+pub struct BehaviorChanged {
+    pub entity: Entity,
+    pub from: Behavior,
+    pub to: Behavior,
+}
+
+// A unit's animation that doesn't loop got to the end of its last frame, sent once per play
+#[derive(Event, Debug, Clone)]
+pub struct AnimationFinished {
+    pub entity: Entity,
+    pub animation_type: AnimationType,
+}
+
+// Request to play an animation the state machine wouldn't pick on its own, like a cast
+#[derive(Event, Debug, Clone)]
+pub struct PlayAnimation {
+    pub entity: Entity,
+    pub animation_type: AnimationType,
+}
+
+// Request to win a unit over to another team
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Convert {
+    pub target: Entity,
+    pub team: Team,
+}
+
+// Registers every event up front, so a feature can listen to one whether or not the plugin that
+// sends it is in the app
+pub struct EventsPlugin;
+
+impl Plugin for EventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<GameEvent>()
+            .add_event::<Damage>()
+            .add_event::<Damaged>()
+            .add_event::<UnitDied>()
+            .add_event::<Heal>()
+            .add_event::<Healed>()
+            .add_event::<UnitSummoned>()
+            .add_event::<UnitUnlocked>()
+            .add_event::<SpellCast>()
+            .add_event::<ManaChanged>()
+            .add_event::<PickupCollected>()
+            .add_event::<WaveStarted>()
+            .add_event::<BossSpawned>()
+            .add_event::<WaveWindowClosed>()
+            .add_event::<WaveGraded>()
+            .add_event::<BehaviorChanged>()
+            .add_event::<AnimationFinished>()
+            .add_event::<PlayAnimation>()
+            .add_event::<Convert>();
+    }
+}
@@ -0,0 +1,28 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// Picked on the level select next to the difficulty
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GameMode {
+    // Survive the level's scripted waves and it's won
+    #[default]
+    Campaign,
+    // The waves keep coming after the scripted ones, getting worse every time, until the run ends
+    Endless,
+}
+
+impl GameMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Campaign => "Campaign",
+            GameMode::Endless => "Endless",
+        }
+    }
+
+    pub fn toggled(&self) -> Self {
+        match self {
+            GameMode::Campaign => GameMode::Endless,
+            GameMode::Endless => GameMode::Campaign,
+        }
+    }
+}
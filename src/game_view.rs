@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+
+use crate::ai::formations::FormationShape;
+use crate::enemies::enemy_spawner::EnemySpawner;
+use crate::gamestate::GameState;
+use crate::mana::Mana;
+use crate::player::plugin::Player;
+use crate::structures::structure_types::StructureType;
+use crate::units::{
+    health::Health,
+    team::{CurrentTeam, Team},
+    unit_types::{CurrentUnitType, UnitType},
+};
+
+// Read-only summary of the public game state, rebuilt at the end of every frame. Anything that
+// plays the game without a keyboard (bots, assists, external tooling) reads this and submits
+// GameActions, instead of each of them querying the world on its own.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct GameView {
+    pub player_position: Option<Vec2>,
+    pub player_health: i32,
+    pub mana: u8,
+    pub max_mana: u8,
+    pub score: u32,
+    pub wave: u32,
+    pub game_over: bool,
+    pub units: Vec<UnitView>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct UnitView {
+    pub entity: Entity,
+    pub unit_type: UnitType,
+    pub team: Team,
+    pub position: Vec2,
+    pub health: i32,
+}
+
+impl GameView {
+    pub fn units_of_team(&self, team: Team) -> impl Iterator<Item = &UnitView> {
+        self.units.iter().filter(move |unit| unit.team == team)
+    }
+
+    pub fn alive_units_of_team(&self, team: Team) -> impl Iterator<Item = &UnitView> {
+        self.units_of_team(team).filter(|unit| unit.health > 0)
+    }
+}
+
+// Everything that can be done to the game from the outside goes through here, the keyboard
+// included, so a bot and a player are handled by the exact same code paths.
+#[derive(Event, Debug, Clone, Copy)]
+pub enum GameAction {
+    Summon(UnitType),
+    Move(Vec2),
+    Charm,
+    Frenzy,
+    Build(StructureType, Vec2),
+    Rally(Option<Vec2>),
+    // The altar, the summoner or a structure for the summons to stand guard around
+    Guard(Option<Entity>),
+    // One more stop on the route the summons patrol, in the order they were added
+    Waypoint(Vec2),
+    UpgradeFamiliar,
+    Formation(FormationShape),
+    // Index into the relic choice currently on offer
+    ChooseRelic(usize),
+    // Index into the perk choice currently on offer, only once no relic is waiting
+    ChoosePerk(usize),
+    // A quick burst the way the summoner is walking, untouchable while it lasts
+    Dash,
+    // Sent every frame the summoner keeps channeling souls into the closest gravestone
+    ChannelRevival,
+    // Sends the next wave in right away, for a bit of mana
+    StartWaveEarly,
+}
+
+pub fn update_game_view(
+    mut game_view: ResMut<GameView>,
+    player_query: Query<(&Transform, &Health, &Mana), With<Player>>,
+    units_query: Query<(Entity, &CurrentUnitType, &CurrentTeam, &Transform, &Health)>,
+    game_state_query: Query<&GameState>,
+    spawner_query: Query<&EnemySpawner>,
+) {
+    let player = player_query.iter().next();
+    game_view.player_position = player.map(|(transform, _, _)| transform.translation.truncate());
+    game_view.player_health = player.map_or(0, |(_, health, _)| health.current);
+    game_view.mana = player.map_or(0, |(_, _, mana)| mana.current_mana);
+    game_view.max_mana = player.map_or(0, |(_, _, mana)| mana.max_mana);
+
+    let game_state = game_state_query.iter().next();
+    game_view.score = game_state.map_or(0, |state| state.score);
+    game_view.game_over = game_state.is_some_and(|state| state.game_over);
+    game_view.wave = spawner_query
+        .iter()
+        .next()
+        .map_or(0, |spawner| spawner.wave);
+
+    game_view.units.clear();
+    game_view.units.extend(units_query.iter().map(
+        |(entity, unit_type, team, transform, health)| UnitView {
+            entity,
+            unit_type: unit_type.0,
+            team: team.0,
+            position: transform.translation.truncate(),
+            health: health.current,
+        },
+    ));
+}
@@ -1,12 +1,31 @@
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
 
-use crate::animation::{spawn_animated_children, AnimatedChildSpawnParams, AnimationType};
+use crate::animation::{
+    spawn_animated_children, AnimatedChildSpawnParams, AnimationType, AtlasLayouts,
+};
+use crate::enemies::enemy_spawner::EnemySpawner;
+use crate::enemies::spawn_queue::SpawnQueue;
+use crate::events::GameEvent;
+use crate::game_mode::GameMode;
+use crate::levels::definition::ActiveLevel;
 use crate::mana::Mana;
+use crate::map::fog::FogRevealer;
+use crate::meta::progress::MetaProgress;
 use crate::movement::Movement;
+use crate::player::corruption::Corruption;
+use crate::player::movement::{Dash, DASH_SPEED_MULTIPLIER};
 use crate::player::plugin::Player;
+use crate::player::ultimate::UltimateCharge;
+use crate::units::altar::{spawn_altar, DarkAltar};
 use crate::units::health::Health;
-use crate::units::unit_types::UnitBundle;
-use crate::{dark_arts_defense::GameEvent, enemies::enemy_spawner::EnemySpawner};
+use crate::units::team::{AllianceMatrix, CurrentTeam, Team};
+use crate::units::unit_types::{CurrentUnitType, UnitBundle};
+use crate::velocity::Motion;
+
+// How far the summoner sees through the fog
+const PLAYER_SIGHT: f32 = 420.0;
+pub const STARTING_MANA: u8 = 100;
 
 #[derive(Component, Default)]
 pub struct Cleanup;
@@ -14,6 +33,8 @@ pub struct Cleanup;
 #[derive(Component)]
 pub struct GameState {
     pub game_over: bool,
+    // The run ended by surviving every scripted wave rather than by dying
+    pub victory: bool,
     pub show_end_timer: Timer,
     pub score: u32,
     pub end_screen_active: bool,
@@ -23,6 +44,7 @@ impl Default for GameState {
     fn default() -> Self {
         Self {
             game_over: false,
+            victory: false,
             show_end_timer: Timer::from_seconds(5.0, TimerMode::Once),
             score: 0,
             end_screen_active: false,
@@ -30,19 +52,38 @@ impl Default for GameState {
     }
 }
 
-pub fn init_game_system(mut commands: Commands, mut events: EventWriter<GameEvent>) {
-    commands.spawn(Camera2dBundle::default());
-    events.send(crate::dark_arts_defense::GameEvent::StartGame);
-}
-
+#[allow(clippy::too_many_arguments)]
 pub fn game_over_system(
     time: Res<Time>,
+    mode: Res<GameMode>,
+    level: Res<ActiveLevel>,
+    spawn_queue: Res<SpawnQueue>,
+    alliances: Res<AllianceMatrix>,
     query: Query<&Health, With<Player>>,
+    altar_query: Query<&Health, With<DarkAltar>>,
+    spawner_query: Query<&EnemySpawner>,
+    enemy_query: Query<(&Health, &CurrentTeam), With<CurrentUnitType>>,
     mut game_state_query: Query<&mut GameState>,
+    mut events: EventWriter<GameEvent>,
 ) {
+    let altar_destroyed = altar_query.iter().any(|health| health.is_dead());
+    // A campaign is won once the last scripted wave is out and every last one of it is dead
+    let won = *mode == GameMode::Campaign
+        && spawner_query
+            .iter()
+            .any(|spawner| !level.waves.is_scripted(spawner.wave + 1))
+        && spawn_queue.is_empty()
+        && !enemy_query
+            .iter()
+            .any(|(health, team)| !health.is_dead() && alliances.is_hostile(Team::Evil, team.0));
     if let Some(health) = query.iter().next() {
-        if health.is_dead() {
+        if health.is_dead() || altar_destroyed || won {
             for mut state in game_state_query.iter_mut() {
+                if !state.game_over {
+                    state.victory = won && !health.is_dead() && !altar_destroyed;
+                    events.send(GameEvent::GameOver);
+                }
+
                 state.game_over = true;
                 state.show_end_timer.tick(time.delta());
                 if state.show_end_timer.just_finished() {
@@ -68,11 +109,16 @@ pub fn update_score_system(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn start_game_system(
     mut commands: Commands,
     mut event_reader: EventReader<GameEvent>,
     asset_server: Res<AssetServer>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut texture_atlas_layouts: AtlasLayouts,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    level: Res<ActiveLevel>,
+    progress: Res<MetaProgress>,
     cleanup_char_query: Query<Entity, With<Cleanup>>,
 ) {
     for event in event_reader.read() {
@@ -80,75 +126,98 @@ pub fn start_game_system(
             cleanup_game_system(&mut commands, &cleanup_char_query);
 
             commands.spawn((GameState::default(), Cleanup {}));
-            commands.spawn((EnemySpawner {}, Cleanup {}));
-
-            commands
-                .spawn((
-                    UnitBundle {
-                        movement: Movement { speed: 150.0 },
-                        transform: Transform::from_scale(Vec3::splat(2.0)),
-                        ..default()
-                    },
-                    Player,
-                    Mana {
-                        current_mana: 100,
-                        max_mana: 100,
-                    },
-                ))
-                .with_children(|parent| {
-                    let children_params: Vec<AnimatedChildSpawnParams> = [
-                        (
-                            "player/player_idle.png",
-                            Vec2::new(96.0, 96.0),
-                            (50, 1),
-                            49,
-                            AnimationType::Idle,
-                            true,
-                            false,
-                        ),
-                        (
-                            "player/player_walk.png",
-                            Vec2::new(96.0, 96.0),
-                            (10, 1),
-                            9,
-                            AnimationType::Walk,
-                            true,
-                            false,
-                        ),
-                        (
-                            "player/player_hit.png",
-                            Vec2::new(96.0, 96.0),
-                            (9, 1),
-                            8,
-                            AnimationType::Hit,
-                            false,
-                            true,
-                        ),
-                        (
-                            "player/player_death.png",
-                            Vec2::new(96.0, 96.0),
-                            (52, 1),
-                            51,
-                            AnimationType::Death,
-                            false,
-                            false,
-                        ),
-                    ]
-                    .into_iter()
-                    .map(|data| data.into())
-                    .collect();
-
-                    spawn_animated_children(
-                        &asset_server,
-                        &mut texture_atlas_layouts,
-                        parent,
-                        children_params,
-                    );
-                });
+            commands.spawn((EnemySpawner::new(&level.waves), Cleanup {}));
+            // Upgrades bought between runs only go on a fresh run, checkpoints keep what they had
+            let mana = STARTING_MANA.saturating_add(progress.bonus_mana());
+            spawn_player(&mut commands, &asset_server, &mut texture_atlas_layouts).insert(Mana {
+                current_mana: mana,
+                max_mana: mana,
+            });
+            spawn_altar(&mut commands, &mut meshes, &mut materials, None);
         }
     }
 }
 
+pub fn spawn_player<'a>(
+    commands: &'a mut Commands,
+    asset_server: &Res<AssetServer>,
+    texture_atlas_layouts: &mut AtlasLayouts,
+) -> EntityCommands<'a> {
+    let mut player = commands.spawn((
+        UnitBundle {
+            movement: Movement { speed: 150.0 },
+            // Enough weight to feel, not so much that dodging a swing gets hard
+            motion: Motion::new(0.12, 0.1).with_max_speed(DASH_SPEED_MULTIPLIER),
+            transform: Transform::from_scale(Vec3::splat(2.0)),
+            ..default()
+        },
+        Player,
+        UltimateCharge::default(),
+        Corruption::default(),
+        Dash::default(),
+        Mana {
+            current_mana: STARTING_MANA,
+            max_mana: STARTING_MANA,
+        },
+        FogRevealer {
+            radius: PLAYER_SIGHT,
+        },
+    ));
+    player.with_children(|parent| {
+        spawn_animated_children(
+            asset_server,
+            texture_atlas_layouts,
+            parent,
+            create_player_children_spawn_params(),
+        );
+    });
+    player
+}
+
+pub fn create_player_children_spawn_params() -> Vec<AnimatedChildSpawnParams> {
+    [
+        (
+            "player/player_idle.png",
+            Vec2::new(96.0, 96.0),
+            (50, 1),
+            49,
+            AnimationType::Idle,
+            true,
+            false,
+        ),
+        (
+            "player/player_walk.png",
+            Vec2::new(96.0, 96.0),
+            (10, 1),
+            9,
+            AnimationType::Walk,
+            true,
+            false,
+        ),
+        (
+            "player/player_hit.png",
+            Vec2::new(96.0, 96.0),
+            (9, 1),
+            8,
+            AnimationType::Hit,
+            false,
+            true,
+        ),
+        (
+            "player/player_death.png",
+            Vec2::new(96.0, 96.0),
+            (52, 1),
+            51,
+            AnimationType::Death,
+            false,
+            false,
+        ),
+    ]
+    .into_iter()
+    .map(|data| data.into())
+    .collect()
+}
+
 pub fn cleanup_game_system(
     commands: &mut Commands,
     characters_query: &Query<Entity, With<Cleanup>>,
@@ -0,0 +1,321 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bevy::app::{AppExit, ScheduleRunnerPlugin};
+use bevy::asset::LoadState;
+use bevy::log::{Level, LogPlugin};
+use bevy::prelude::*;
+use bevy::render::settings::WgpuSettings;
+use bevy::render::RenderPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy::window::{ExitCondition, WindowResolution};
+use bevy::winit::WinitPlugin;
+
+use crate::dark_arts_defense::{AppState, DarkArtsDefensePlugin};
+use crate::difficulty::Difficulty;
+use crate::enemies::enemy_spawner::EnemySpawner;
+use crate::game_mode::GameMode;
+use crate::gamestate::GameState;
+use crate::headless::policy::{self, SummonPolicy};
+use crate::headless::report::{self, HeadlessReport};
+use crate::levels::definition::{ActiveLevel, LevelDefinition, Levels};
+use crate::narrative::conversation::{self, ConversationState};
+use crate::rng::RunSeed;
+use crate::save::checkpoints::CheckpointSettings;
+use crate::schedule::FrameSet;
+use crate::stats::run_stats::RunStats;
+
+// cargo run --release -- --headless --seed 7 --policy rotate --difficulty nightmare --max-waves 10
+// Add --endless for endless, --level to pick another arena and --max-minutes to cut it shorter.
+pub const HEADLESS_FLAG: &str = "--headless";
+const POLICY_FLAG: &str = "--policy";
+const LEVEL_FLAG: &str = "--level";
+const DIFFICULTY_FLAG: &str = "--difficulty";
+const ENDLESS_FLAG: &str = "--endless";
+const MAX_WAVES_FLAG: &str = "--max-waves";
+const MAX_MINUTES_FLAG: &str = "--max-minutes";
+// Endless never ends on its own if the policy is good enough
+const DEFAULT_ENDLESS_WAVES: u32 = 30;
+// Of game time, a summoner that only ever runs away can keep a run going forever
+const DEFAULT_MAX_MINUTES: f32 = 30.0;
+// Every frame is a 60th of a second of game time, however long it actually took to run
+const FRAME_SECONDS: f64 = 1.0 / 60.0;
+// The size the windowed game is played at, which is where enemies spawn around
+const PLAY_AREA: (f32, f32) = (1920.0, 1080.0);
+
+// What the run is, picked from the command line instead of the level select
+#[derive(Resource, Debug, Clone)]
+pub struct HeadlessOptions {
+    pub policy: SummonPolicy,
+    // Index into the level select's list
+    pub level: usize,
+    pub difficulty: Difficulty,
+    pub mode: GameMode,
+    // Stops once this wave is over even if the run isn't
+    pub max_waves: Option<u32>,
+    pub max_minutes: f32,
+}
+
+impl HeadlessOptions {
+    pub fn from_args() -> Result<Self, String> {
+        let args: Vec<String> = std::env::args().collect();
+        let value = |flag: &str| flag_value(&args, flag);
+
+        let policy = match value(POLICY_FLAG) {
+            Some(name) => SummonPolicy::from_name(&name).ok_or_else(|| {
+                let names: Vec<&str> = SummonPolicy::ALL.iter().map(|p| p.name()).collect();
+                format!("Unknown policy {}, try one of {}", name, names.join(", "))
+            })?,
+            None => SummonPolicy::default(),
+        };
+        let level = match value(LEVEL_FLAG) {
+            Some(level) => level
+                .parse()
+                .map_err(|_| format!("{} {} has to be a number", LEVEL_FLAG, level))?,
+            None => 0,
+        };
+        let difficulty = match value(DIFFICULTY_FLAG) {
+            Some(name) => Difficulty::ALL
+                .into_iter()
+                .find(|difficulty| difficulty.name().eq_ignore_ascii_case(&name))
+                .ok_or_else(|| format!("Unknown difficulty {}", name))?,
+            None => Difficulty::default(),
+        };
+        let mode = if args.iter().any(|arg| arg == ENDLESS_FLAG) {
+            GameMode::Endless
+        } else {
+            GameMode::Campaign
+        };
+        let max_waves = match value(MAX_WAVES_FLAG) {
+            Some(waves) => Some(
+                waves
+                    .parse()
+                    .map_err(|_| format!("{} {} has to be a number", MAX_WAVES_FLAG, waves))?,
+            ),
+            None if mode == GameMode::Endless => Some(DEFAULT_ENDLESS_WAVES),
+            None => None,
+        };
+        let max_minutes = match value(MAX_MINUTES_FLAG) {
+            Some(minutes) => minutes
+                .parse()
+                .map_err(|_| format!("{} {} has to be a number", MAX_MINUTES_FLAG, minutes))?,
+            None => DEFAULT_MAX_MINUTES,
+        };
+
+        Ok(Self {
+            policy,
+            level,
+            difficulty,
+            mode,
+            max_waves,
+            max_minutes,
+        })
+    }
+}
+
+// Either "--flag value" or "--flag=value", the same as --seed
+fn flag_value(args: &[String], flag: &str) -> Option<String> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next().cloned();
+        }
+        if let Some(value) = arg
+            .strip_prefix(flag)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(value.to_owned());
+        }
+    }
+    None
+}
+
+// Where the exit code ends up once the app stops running
+#[derive(Resource, Clone, Default)]
+pub struct HeadlessExitCode(Arc<AtomicI32>);
+
+// Saves, settings and unlocks go somewhere of their own, so a run neither touches the player's
+// save nor depends on what they've unlocked
+#[derive(Resource)]
+pub struct HeadlessSaveDirectory(PathBuf);
+
+// Plays one run with nobody at the keyboard, prints how every wave went and returns the exit
+// code. MinimalPlugins doesn't register the images, fonts and atlases the game's systems load,
+// so this is the default plugins without winit and without a render backend, which draws nothing
+// and opens no window all the same. Frames run back to back instead of waiting on the clock.
+pub fn run_headless() -> i32 {
+    let options = match HeadlessOptions::from_args() {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}", error);
+            return 2;
+        }
+    };
+
+    let save_directory =
+        std::env::temp_dir().join(format!("dark_arts_headless_{}", std::process::id()));
+    let exit_code = HeadlessExitCode::default();
+
+    App::new()
+        .add_plugins((
+            DefaultPlugins
+                .set(WindowPlugin {
+                    // Never opened, it only gives everything that asks a play area to work with
+                    primary_window: Some(Window {
+                        resolution: WindowResolution::new(PLAY_AREA.0, PLAY_AREA.1),
+                        ..default()
+                    }),
+                    exit_condition: ExitCondition::DontExit,
+                    close_when_requested: false,
+                })
+                .set(RenderPlugin {
+                    render_creation: WgpuSettings {
+                        backends: None,
+                        ..default()
+                    }
+                    .into(),
+                    ..default()
+                })
+                .set(LogPlugin {
+                    level: Level::WARN,
+                    ..default()
+                })
+                .set(ImagePlugin::default_nearest())
+                .disable::<WinitPlugin>(),
+            ScheduleRunnerPlugin::run_loop(Duration::ZERO),
+            DarkArtsDefensePlugin,
+        ))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            FRAME_SECONDS,
+        )))
+        .insert_resource(CheckpointSettings {
+            directory: save_directory.clone(),
+            ..default()
+        })
+        .insert_resource(HeadlessSaveDirectory(save_directory))
+        .insert_resource(options)
+        .insert_resource(exit_code.clone())
+        .add_plugins(HeadlessPlugin)
+        .run();
+
+    exit_code.0.load(Ordering::Relaxed)
+}
+
+// Picks the level once it has loaded, the same way SPACE on the level select would
+#[allow(clippy::too_many_arguments)]
+pub fn start_headless_run(
+    options: Res<HeadlessOptions>,
+    exit_code: Res<HeadlessExitCode>,
+    asset_server: Res<AssetServer>,
+    definitions: Res<Assets<LevelDefinition>>,
+    mut levels: ResMut<Levels>,
+    mut difficulty: ResMut<Difficulty>,
+    mut mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut exit_writer: EventWriter<AppExit>,
+) {
+    if levels.handles.is_empty() {
+        return;
+    }
+    if options.level >= levels.handles.len() {
+        eprintln!(
+            "There's no level {}, there are {} of them",
+            options.level,
+            levels.handles.len()
+        );
+        exit_code.0.store(2, Ordering::Relaxed);
+        exit_writer.send(AppExit);
+        return;
+    }
+
+    levels.selected = options.level;
+    let Some(handle) = levels.selected_handle() else {
+        return;
+    };
+    // Waiting on a level that's never coming would hang whatever started the run
+    if asset_server.load_state(handle) == LoadState::Failed {
+        eprintln!("Level {} couldn't be loaded", options.level);
+        exit_code.0.store(2, Ordering::Relaxed);
+        exit_writer.send(AppExit);
+        return;
+    }
+    if !definitions.contains(handle) {
+        return;
+    }
+    *difficulty = options.difficulty;
+    *mode = options.mode;
+    next_state.set(AppState::Playing);
+}
+
+// Once the end screen would have come up, the last wave asked for is over or it ran out of time
+#[allow(clippy::too_many_arguments)]
+pub fn finish_headless_run(
+    options: Res<HeadlessOptions>,
+    level: Res<ActiveLevel>,
+    run_seed: Res<RunSeed>,
+    stats: Res<RunStats>,
+    report: Res<HeadlessReport>,
+    save_directory: Res<HeadlessSaveDirectory>,
+    game_state_query: Query<&GameState>,
+    spawner_query: Query<&EnemySpawner>,
+    mut finished: Local<bool>,
+    mut exit_writer: EventWriter<AppExit>,
+) {
+    if *finished {
+        return;
+    }
+    let ended = game_state_query
+        .iter()
+        .find(|state| state.end_screen_active);
+    let past_max_waves = options
+        .max_waves
+        .is_some_and(|max_waves| spawner_query.iter().any(|spawner| spawner.wave > max_waves));
+    let outcome = match ended {
+        Some(state) if state.victory => "Victory",
+        Some(_) => "Defeat",
+        None if past_max_waves => "Stopped",
+        None if stats.seconds >= options.max_minutes * 60.0 => "Out of time",
+        None => return,
+    };
+
+    *finished = true;
+    let heading = format!(
+        "{} on {}, {}, seed {}, {} summoning",
+        level.name,
+        options.difficulty.name(),
+        options.mode.name(),
+        run_seed.current,
+        options.policy.name()
+    );
+    report.print(&heading, outcome, &stats);
+
+    if let Err(error) = std::fs::remove_dir_all(&save_directory.0) {
+        if error.kind() != std::io::ErrorKind::NotFound {
+            warn!("Couldn't remove {}: {}", save_directory.0.display(), error);
+        }
+    }
+    exit_writer.send(AppExit);
+}
+
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HeadlessReport>().add_systems(
+            Update,
+            (
+                start_headless_run.run_if(in_state(AppState::LevelSelect)),
+                // Nobody's there to read them
+                conversation::close_conversation.run_if(in_state(ConversationState::Open)),
+                policy::play_summon_policy
+                    .in_set(FrameSet::Input)
+                    .run_if(in_state(AppState::Playing)),
+                (report::record_wave_reports, finish_headless_run)
+                    .chain()
+                    .run_if(in_state(AppState::Playing)),
+            ),
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+
+use crate::difficulty::Difficulty;
+use crate::events::UnitSummoned;
+use crate::game_view::{GameAction, GameView};
+use crate::headless::plugin::HeadlessOptions;
+use crate::meta::progress::MetaProgress;
+use crate::player::perks::PerkChoices;
+use crate::player::plugin::Player;
+use crate::player::relics::RelicChoices;
+use crate::player::summoning::SUMMON_BINDS;
+use crate::units::stat_modifiers::StatModifiers;
+use crate::units::team::{AllianceMatrix, Team};
+use crate::units::unit_types::{UnitResource, UnitType};
+
+// Enemies closer than this send the summoner the other way
+const KITE_RADIUS: f32 = 250.0;
+
+// How the headless summoner spends its mana. Every policy backs away from enemies the same way,
+// so the runs only differ in what gets summoned and when.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SummonPolicy {
+    // Never summons, how long the summoner and the altar last on their own
+    Idle,
+    // The cheapest summon it has, as soon as there's the mana for it
+    #[default]
+    Cheapest,
+    // Saves up for the most expensive summon it has
+    Strongest,
+    // Goes through the roster one summon after the other
+    Rotate,
+}
+
+impl SummonPolicy {
+    pub const ALL: [SummonPolicy; 4] = [
+        SummonPolicy::Idle,
+        SummonPolicy::Cheapest,
+        SummonPolicy::Strongest,
+        SummonPolicy::Rotate,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SummonPolicy::Idle => "idle",
+            SummonPolicy::Cheapest => "cheapest",
+            SummonPolicy::Strongest => "strongest",
+            SummonPolicy::Rotate => "rotate",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|policy| policy.name().eq_ignore_ascii_case(name))
+    }
+}
+
+// Away from every enemy close enough to hit soon, the closer ones pushing harder
+fn kite_direction(game_view: &GameView, alliances: &AllianceMatrix) -> Option<Vec2> {
+    let player_position = game_view.player_position?;
+    let away: Vec2 = game_view
+        .units
+        .iter()
+        .filter(|unit| unit.health > 0 && alliances.is_hostile(Team::Evil, unit.team))
+        .map(|unit| player_position - unit.position)
+        .filter(|offset| offset.length() < KITE_RADIUS)
+        .map(|offset| offset.normalize_or_zero() * (KITE_RADIUS - offset.length()))
+        .sum();
+    (away != Vec2::ZERO).then_some(away)
+}
+
+// Plays through GameActions like any other bot. Relics and perks are always the first one on
+// offer, so the same seed makes the same choices.
+#[allow(clippy::too_many_arguments)]
+pub fn play_summon_policy(
+    options: Res<HeadlessOptions>,
+    game_view: Res<GameView>,
+    unit_configs: Res<UnitResource>,
+    difficulty: Res<Difficulty>,
+    progress: Res<MetaProgress>,
+    alliances: Res<AllianceMatrix>,
+    (relic_choices, perk_choices): (Res<RelicChoices>, Res<PerkChoices>),
+    modifiers_query: Query<&StatModifiers, With<Player>>,
+    mut summoned_reader: EventReader<UnitSummoned>,
+    mut rotation: Local<usize>,
+    mut actions: EventWriter<GameAction>,
+) {
+    *rotation += summoned_reader
+        .read()
+        .filter(|summoned| !summoned.revived)
+        .count();
+
+    if game_view.game_over {
+        return;
+    }
+    if relic_choices.current().is_some() {
+        actions.send(GameAction::ChooseRelic(0));
+        return;
+    }
+    if perk_choices.current().is_some() {
+        actions.send(GameAction::ChoosePerk(0));
+        return;
+    }
+
+    if let Some(away) = kite_direction(&game_view, &alliances) {
+        actions.send(GameAction::Move(away));
+    }
+
+    let Ok(modifiers) = modifiers_query.get_single() else {
+        return;
+    };
+    let roster: Vec<(UnitType, u8)> = SUMMON_BINDS
+        .iter()
+        .map(|(_, unit_type)| *unit_type)
+        .filter(|unit_type| {
+            unit_configs.is_unlocked(*unit_type) && progress.is_unit_unlocked(*unit_type)
+        })
+        .map(|unit_type| {
+            let cost = unit_configs.summon_cost(unit_type, &difficulty, modifiers);
+            (unit_type, cost)
+        })
+        .collect();
+    if roster.is_empty() {
+        return;
+    }
+
+    let choice = match options.policy {
+        SummonPolicy::Idle => None,
+        SummonPolicy::Cheapest => roster.iter().min_by_key(|(_, cost)| *cost),
+        SummonPolicy::Strongest => roster.iter().max_by_key(|(_, cost)| *cost),
+        SummonPolicy::Rotate => roster.get(*rotation % roster.len()),
+    };
+    if let Some((unit_type, cost)) = choice {
+        if game_view.mana >= *cost {
+            actions.send(GameAction::Summon(*unit_type));
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+
+use crate::events::WaveWindowClosed;
+use crate::game_view::GameView;
+use crate::stats::run_stats::{RunStats, WaveWindow};
+use crate::units::team::Team;
+
+// One line per wave, what the wave did to the summoner's side and what was left standing after
+#[derive(Debug, Clone)]
+pub struct WaveReport {
+    pub window: WaveWindow,
+    pub summons_alive: usize,
+    pub summoner_health: i32,
+}
+
+#[derive(Resource, Debug, Clone, Default)]
+pub struct HeadlessReport {
+    pub waves: Vec<WaveReport>,
+}
+
+impl HeadlessReport {
+    // Plain columns so two runs can be diffed against each other
+    pub fn print(&self, heading: &str, outcome: &str, stats: &RunStats) {
+        println!("{}", heading);
+        println!(
+            "{:>5} {:>8} {:>6} {:>7} {:>6} {:>6} {:>6} {:>8}",
+            "wave", "seconds", "kills", "damage", "lost", "alive", "health", "cleared"
+        );
+        for wave in self.waves.iter() {
+            println!(
+                "{:>5} {:>8.1} {:>6} {:>7} {:>6} {:>6} {:>6} {:>8}",
+                wave.window.wave,
+                wave.window.seconds,
+                wave.window.kills,
+                wave.window.damage_taken,
+                wave.window.summons_lost,
+                wave.summons_alive,
+                wave.summoner_health,
+                if wave.window.cleared { "yes" } else { "no" },
+            );
+        }
+        println!("{}: {}", outcome, stats.summary());
+    }
+}
+
+pub fn record_wave_reports(
+    game_view: Res<GameView>,
+    mut window_reader: EventReader<WaveWindowClosed>,
+    mut report: ResMut<HeadlessReport>,
+) {
+    for WaveWindowClosed(window) in window_reader.read() {
+        report.waves.push(WaveReport {
+            window: window.clone(),
+            summons_alive: game_view.alive_units_of_team(Team::Evil).count(),
+            summoner_health: game_view.player_health,
+        });
+    }
+}
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+
+use crate::units::attack::Projectile;
+use crate::velocity::Velocity;
+
+// Where something was after the last two fixed steps, and where it was drawn last. The
+// simulation steps at the same rate whatever the monitor does, so in between steps units are
+// drawn part of the way from one to the other instead of jumping every few frames.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Interpolated {
+    previous: Vec2,
+    current: Vec2,
+    drawn: Vec2,
+}
+
+impl Interpolated {
+    fn at(position: Vec2) -> Self {
+        Self {
+            previous: position,
+            current: position,
+            drawn: position,
+        }
+    }
+}
+
+type NotInterpolatedFilter = (
+    Or<(With<Velocity>, With<Projectile>)>,
+    Without<Interpolated>,
+);
+
+// Everything the steps move, which includes units and bolts coming back out of the pool
+pub fn start_interpolating(
+    mut commands: Commands,
+    query: Query<(Entity, &Transform), NotInterpolatedFilter>,
+) {
+    for (entity, transform) in query.iter() {
+        commands
+            .entity(entity)
+            .insert(Interpolated::at(transform.translation.truncate()));
+    }
+}
+
+// Puts back where the last step left it before the next one. Anything that moved it in between,
+// a blink, a checkpoint being loaded or a test, left it somewhere other than where it was drawn,
+// which is taken as where it is now without sliding it over there.
+pub fn restore_positions(mut query: Query<(&mut Transform, &mut Interpolated)>) {
+    for (mut transform, mut interpolated) in query.iter_mut() {
+        let position = transform.translation.truncate();
+        if position != interpolated.drawn {
+            interpolated.current = position;
+        } else if position != interpolated.current {
+            transform.translation = interpolated.current.extend(transform.translation.z);
+        }
+        interpolated.previous = interpolated.current;
+    }
+}
+
+pub fn record_positions(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in query.iter_mut() {
+        interpolated.current = transform.translation.truncate();
+        interpolated.drawn = interpolated.current;
+    }
+}
+
+// Only touches the ones that actually moved, the y sorting goes by which transforms changed
+pub fn interpolate_positions(
+    fixed_time: Res<Time<Fixed>>,
+    mut query: Query<(&mut Transform, &mut Interpolated)>,
+) {
+    let fraction = fixed_time.overstep_fraction();
+    for (mut transform, mut interpolated) in query.iter_mut() {
+        if transform.translation.truncate() != interpolated.drawn {
+            continue;
+        }
+        let position = interpolated.previous.lerp(interpolated.current, fraction);
+        if position != interpolated.drawn {
+            interpolated.drawn = position;
+            transform.translation = position.extend(transform.translation.z);
+        }
+    }
+}
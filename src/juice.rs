@@ -0,0 +1,177 @@
+use bevy::prelude::*;
+
+use crate::animation::{self, Animation};
+use crate::camera::CameraController;
+use crate::enemies::boss::Boss;
+use crate::events::Damaged;
+use crate::render_scale::WorldCamera;
+use crate::schedule::FrameSet;
+use crate::settings::accessibility::AccessibilitySettings;
+use crate::time_of_day::lerp_color;
+use crate::units::imp::Explosion;
+use crate::vfx::flash::{Flash, FlashLimiter, VfxSettings};
+
+// Trauma is 0 to 1, the shake itself goes with its square so small bumps stay small
+const MAX_SHAKE_OFFSET: f32 = 14.0;
+const MAX_SHAKE_ROTATION: f32 = 0.025;
+const SHAKE_FREQUENCY: f32 = 25.0;
+const TRAUMA_DECAY_PER_SECOND: f32 = 1.6;
+// Photosensitive safe mode keeps the shake but takes most of the violence out of it
+const SAFE_SHAKE_SCALE: f32 = 0.3;
+
+const EXPLOSION_TRAUMA: f32 = 0.35;
+const BOSS_HIT_TRAUMA: f32 = 0.2;
+const BOSS_KILL_TRAUMA: f32 = 0.8;
+
+// A few frames of the sprite blown out to white
+const HIT_FLASH_SECONDS: f32 = 0.07;
+const HIT_FLASH_COLOR: Color = Color::rgb(6.0, 6.0, 6.0);
+// About what a unit's sprite covers, for what the flash costs out of the flash budget
+const HIT_FLASH_AREA: f32 = 64.0 * 64.0;
+
+// Anything can add trauma, the camera shakes with whatever is left of it as it decays
+#[derive(Resource, Default)]
+pub struct ScreenShake {
+    pub trauma: f32,
+    elapsed: f32,
+}
+
+impl ScreenShake {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+}
+
+#[derive(Component)]
+pub struct HitFlash {
+    remaining: f32,
+    // How far towards white, less than all the way once a big wave has used up the budget
+    alpha: f32,
+}
+
+pub struct JuicePlugin;
+
+impl Plugin for JuicePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenShake>().add_systems(
+            Update,
+            (
+                shake_on_explosions,
+                react_to_hits,
+                apply_screen_shake,
+                // Goes over whatever tint the unit has for the frame
+                apply_hit_flash.after(animation::apply_tint),
+            )
+                .chain()
+                .in_set(FrameSet::Presentation),
+        );
+    }
+}
+
+pub fn shake_on_explosions(mut shake: ResMut<ScreenShake>, query: Query<(), Added<Explosion>>) {
+    for _ in query.iter() {
+        shake.add_trauma(EXPLOSION_TRAUMA);
+    }
+}
+
+pub fn react_to_hits(
+    mut commands: Commands,
+    vfx_settings: Res<VfxSettings>,
+    mut flash_limiter: ResMut<FlashLimiter>,
+    mut shake: ResMut<ScreenShake>,
+    mut damaged_reader: EventReader<Damaged>,
+    boss_query: Query<(), With<Boss>>,
+) {
+    for damaged in damaged_reader.read() {
+        if damaged.dodged || damaged.amount <= 0 {
+            continue;
+        }
+
+        if boss_query.contains(damaged.target) && damaged.killed {
+            shake.add_trauma(BOSS_KILL_TRAUMA);
+        } else if boss_query.contains(damaged.target)
+            || damaged
+                .source
+                .is_some_and(|source| boss_query.contains(source))
+        {
+            shake.add_trauma(BOSS_HIT_TRAUMA);
+        }
+
+        if vfx_settings.photosensitive_safe_mode {
+            continue;
+        }
+        // The target might be gone by the time this gets to it
+        let Some(mut entity) = commands.get_entity(damaged.target) else {
+            continue;
+        };
+        let flash = flash_limiter.limit(
+            &vfx_settings,
+            Flash {
+                alpha: 1.0,
+                duration: HIT_FLASH_SECONDS,
+            },
+            HIT_FLASH_AREA,
+        );
+        if flash.alpha > 0.0 {
+            entity.try_insert(HitFlash {
+                remaining: flash.duration,
+                alpha: flash.alpha,
+            });
+        }
+    }
+}
+
+// Goes on top of wherever the camera controller is looking
+pub fn apply_screen_shake(
+    time: Res<Time>,
+    vfx_settings: Res<VfxSettings>,
+    accessibility: Res<AccessibilitySettings>,
+    mut shake: ResMut<ScreenShake>,
+    mut camera_query: Query<(&mut Transform, Option<&CameraController>), With<WorldCamera>>,
+) {
+    shake.trauma = (shake.trauma - TRAUMA_DECAY_PER_SECOND * time.delta_seconds()).max(0.0);
+    shake.elapsed += time.delta_seconds();
+
+    // Turned off in the settings the camera still gets put back where it's looking
+    let scale = if !accessibility.screen_shake {
+        0.0
+    } else if vfx_settings.photosensitive_safe_mode {
+        SAFE_SHAKE_SCALE
+    } else {
+        1.0
+    };
+    let amount = shake.trauma * shake.trauma * scale;
+    // A few sines out of step with each other, smooth enough to not look like static
+    let t = shake.elapsed * SHAKE_FREQUENCY;
+    let noise = |seed: f32| ((t + seed).sin() + (t * 1.7 + seed * 3.1).sin() * 0.5) / 1.5;
+
+    for (mut transform, controller) in camera_query.iter_mut() {
+        let base = controller.map_or(Vec2::ZERO, |controller| controller.position);
+        transform.translation.x = base.x + noise(0.0) * MAX_SHAKE_OFFSET * amount;
+        transform.translation.y = base.y + noise(11.0) * MAX_SHAKE_OFFSET * amount;
+        transform.rotation = Quat::from_rotation_z(noise(23.0) * MAX_SHAKE_ROTATION * amount);
+    }
+}
+
+pub fn apply_hit_flash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut HitFlash, &Children)>,
+    mut sprite_query: Query<&mut Sprite, With<Animation>>,
+) {
+    for (entity, mut flash, children) in query.iter_mut() {
+        flash.remaining -= time.delta_seconds();
+        if flash.remaining <= 0.0 {
+            commands.entity(entity).remove::<HitFlash>();
+            continue;
+        }
+
+        for &child in children.iter() {
+            // Keeps the alpha, a flash in the middle of a crossfade shouldn't show both frames
+            if let Ok(mut sprite) = sprite_query.get_mut(child) {
+                let alpha = sprite.color.a();
+                sprite.color = lerp_color(sprite.color, HIT_FLASH_COLOR, flash.alpha).with_a(alpha);
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::events::GameEvent;
+use crate::gamestate::create_player_children_spawn_params;
+use crate::levels::definition::{LevelDefinition, Levels};
+use crate::map::tilemap::TileMap;
+use crate::units::unit_types::UnitType;
+
+const DEFAULT_MEMORY_BUDGET_BYTES: usize = 256 * 1024 * 1024;
+
+// Owns the strong handles for everything a level needs. Bevy unloads an asset as soon as the
+// last strong handle is dropped, so swapping this out when a level ends (and despawning the
+// level's entities through Cleanup) is what actually frees the memory between levels.
+#[derive(Resource)]
+pub struct LevelAssets {
+    pub images: Vec<Handle<Image>>,
+    pub audio: Vec<Handle<AudioSource>>,
+    // The level select keeps its own handle to every definition, so only the map really goes
+    pub definition: Option<Handle<LevelDefinition>>,
+    pub map: Option<Handle<TileMap>>,
+    pub memory_budget_bytes: usize,
+}
+
+impl Default for LevelAssets {
+    fn default() -> Self {
+        Self {
+            images: Vec::new(),
+            audio: Vec::new(),
+            definition: None,
+            map: None,
+            memory_budget_bytes: DEFAULT_MEMORY_BUDGET_BYTES,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AssetMemoryReport {
+    pub texture_bytes: usize,
+    pub audio_bytes: usize,
+    pub loaded: usize,
+    pub pending: usize,
+}
+
+impl AssetMemoryReport {
+    pub fn total_bytes(&self) -> usize {
+        self.texture_bytes + self.audio_bytes
+    }
+}
+
+impl LevelAssets {
+    pub fn memory_report(&self, assets: &LevelAssetStores) -> AssetMemoryReport {
+        let LevelAssetStores {
+            images,
+            audio,
+            definitions,
+            maps,
+        } = assets;
+        let mut report = AssetMemoryReport::default();
+        for handle in self.images.iter() {
+            match images.get(handle) {
+                Some(image) => {
+                    report.texture_bytes += image.data.len();
+                    report.loaded += 1;
+                }
+                None => report.pending += 1,
+            }
+        }
+        for handle in self.audio.iter() {
+            match audio.get(handle) {
+                Some(source) => {
+                    report.audio_bytes += source.bytes.len();
+                    report.loaded += 1;
+                }
+                None => report.pending += 1,
+            }
+        }
+
+        for loaded in [
+            self.definition
+                .as_ref()
+                .map(|handle| definitions.contains(handle)),
+            self.map.as_ref().map(|handle| maps.contains(handle)),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if loaded {
+                report.loaded += 1;
+            } else {
+                report.pending += 1;
+            }
+        }
+
+        report
+    }
+
+    fn is_empty(&self) -> bool {
+        self.images.is_empty()
+            && self.audio.is_empty()
+            && self.definition.is_none()
+            && self.map.is_none()
+    }
+
+    // Leaving the level drops every handle it held, anything nothing else uses gets unloaded
+    pub fn unload(&mut self, assets: &LevelAssetStores) {
+        if self.is_empty() {
+            return;
+        }
+
+        let report = self.memory_report(assets);
+        log_memory_report("Unloading", &report, self.memory_budget_bytes);
+        self.images.clear();
+        self.audio.clear();
+        self.definition = None;
+        self.map = None;
+    }
+}
+
+// The player and everything that can be summoned, the critters, and whichever enemies the
+// level's waves send
+pub fn level_unit_types(definition: &LevelDefinition) -> Vec<UnitType> {
+    let waves = &definition.waves;
+    let mut unit_types = vec![
+        UnitType::Acolyte,
+        UnitType::Warrior,
+        UnitType::Cat,
+        UnitType::Imp,
+        UnitType::DarkPriest,
+        UnitType::Critter,
+        UnitType::Knight,
+    ];
+    if waves.gargoyle_interval > 0 {
+        unit_types.push(UnitType::Gargoyle);
+    }
+    if waves.armored_knight_interval > 0 || waves.boss_interval > 0 {
+        unit_types.push(UnitType::ArmoredKnight);
+    }
+    if waves.assassin_interval > 0 {
+        unit_types.push(UnitType::Assassin);
+    }
+    unit_types
+}
+
+pub fn level_texture_paths(definition: &LevelDefinition) -> Vec<String> {
+    let mut paths: Vec<String> = level_unit_types(definition)
+        .iter()
+        .flat_map(UnitType::children_spawn_params)
+        .chain(create_player_children_spawn_params())
+        .map(|params| params.texture_path)
+        .collect();
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+fn log_memory_report(label: &str, report: &AssetMemoryReport, budget_bytes: usize) {
+    info!(
+        "{} level assets: {:.2} MiB textures, {:.2} MiB audio, {} loaded, {} pending",
+        label,
+        report.texture_bytes as f32 / (1024.0 * 1024.0),
+        report.audio_bytes as f32 / (1024.0 * 1024.0),
+        report.loaded,
+        report.pending,
+    );
+
+    if report.total_bytes() > budget_bytes {
+        warn!(
+            "{} level assets exceed the memory budget by {} bytes",
+            label,
+            report.total_bytes() - budget_bytes
+        );
+    }
+}
+
+// Where everything a level holds on to ends up once it's loaded
+#[derive(SystemParam)]
+pub struct LevelAssetStores<'w> {
+    images: Res<'w, Assets<Image>>,
+    audio: Res<'w, Assets<AudioSource>>,
+    definitions: Res<'w, Assets<LevelDefinition>>,
+    maps: Res<'w, Assets<TileMap>>,
+}
+
+pub fn swap_level_assets_system(
+    mut event_reader: EventReader<GameEvent>,
+    asset_server: Res<AssetServer>,
+    levels: Res<Levels>,
+    assets: LevelAssetStores,
+    mut level_assets: ResMut<LevelAssets>,
+) {
+    for event in event_reader.read() {
+        if let GameEvent::StartGame = event {
+            let Some((handle, definition)) = levels
+                .selected_handle()
+                .and_then(|handle| Some((handle, assets.definitions.get(handle)?)))
+            else {
+                continue;
+            };
+
+            if !level_assets.is_empty() {
+                let report = level_assets.memory_report(&assets);
+                log_memory_report("Unloading", &report, level_assets.memory_budget_bytes);
+            }
+
+            // Load the new handles before dropping the old ones, anything shared between the
+            // levels stays resident instead of being unloaded and immediately loaded again.
+            let new_images = level_texture_paths(definition)
+                .into_iter()
+                .map(|path| asset_server.load(path))
+                .collect();
+            let previous_images = std::mem::replace(&mut level_assets.images, new_images);
+            let previous_audio = std::mem::take(&mut level_assets.audio);
+            let previous_map = level_assets
+                .map
+                .replace(asset_server.load(definition.map.clone()));
+            level_assets.definition = Some(handle.clone());
+            drop((previous_images, previous_audio, previous_map));
+        }
+    }
+}
+
+pub fn report_level_assets_system(
+    mut event_reader: EventReader<GameEvent>,
+    assets: LevelAssetStores,
+    level_assets: Res<LevelAssets>,
+) {
+    for event in event_reader.read() {
+        if let GameEvent::GameOver = event {
+            let report = level_assets.memory_report(&assets);
+            log_memory_report("Finished", &report, level_assets.memory_budget_bytes);
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use std::fmt;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::BoxedFuture;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::enemies::enemy_spawner::{random_spawn_position, WaveComposition};
+use crate::player::summoning::SummonLimits;
+use crate::rng::GameRng;
+use crate::stats::grading::Grading;
+
+use super::triggers::Trigger;
+
+// Every arena the level select lists, in the order they are listed
+pub const LEVEL_PATHS: [&str; 2] = ["levels/crypt.level.ron", "levels/marsh.level.ron"];
+
+// Enemies spread out a bit around a spawn point instead of walking in as one clump
+const SPAWN_POINT_SPREAD: f32 = 96.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WaveSchedule {
+    pub interval: f32,
+    pub base_size: u32,
+    pub size_growth: u32,
+    pub gargoyle_interval: u32,
+    pub armored_knight_interval: u32,
+    // Every this many waves some assassins come along, 0 for a level without them
+    pub assassin_interval: u32,
+    // Waves that also bring a boss, 0 for a level without one
+    pub boss_interval: u32,
+    // Waves with one of their elites marked as a bounty, 0 for none
+    pub bounty_interval: u32,
+    // Surviving this many wins the level, endless runs keep going past them. 0 never runs out.
+    pub scripted_waves: u32,
+}
+
+impl Default for WaveSchedule {
+    fn default() -> Self {
+        Self {
+            interval: 10.0,
+            base_size: 3,
+            size_growth: 2,
+            gargoyle_interval: 3,
+            armored_knight_interval: 4,
+            assassin_interval: 5,
+            boss_interval: 10,
+            bounty_interval: 3,
+            scripted_waves: 20,
+        }
+    }
+}
+
+impl WaveSchedule {
+    pub fn wave_size(&self, wave: u32) -> u32 {
+        self.base_size + wave * self.size_growth
+    }
+
+    // Every few waves bring along some gargoyles, more of them the further in we get
+    pub fn gargoyle_count(&self, wave: u32) -> u32 {
+        if self.gargoyle_interval > 0 && wave.is_multiple_of(self.gargoyle_interval) {
+            wave / self.gargoyle_interval
+        } else {
+            0
+        }
+    }
+
+    // Armored knights show up after a while, and then one more every interval after that
+    pub fn armored_knight_count(&self, wave: u32) -> u32 {
+        wave.checked_div(self.armored_knight_interval).unwrap_or(0)
+    }
+
+    // One assassin the first time, another one every time after that
+    pub fn assassin_count(&self, wave: u32) -> u32 {
+        if self.assassin_interval > 0 && wave.is_multiple_of(self.assassin_interval) {
+            wave / self.assassin_interval
+        } else {
+            0
+        }
+    }
+
+    pub fn is_boss_wave(&self, wave: u32) -> bool {
+        wave > 0 && self.boss_interval > 0 && wave.is_multiple_of(self.boss_interval)
+    }
+
+    pub fn is_bounty_wave(&self, wave: u32) -> bool {
+        wave > 0 && self.bounty_interval > 0 && wave.is_multiple_of(self.bounty_interval)
+    }
+
+    pub fn is_scripted(&self, wave: u32) -> bool {
+        self.scripted_waves == 0 || wave <= self.scripted_waves
+    }
+
+    // How many waves past the scripted ones an endless run has made it
+    pub fn endless_depth(&self, wave: u32) -> u32 {
+        if self.scripted_waves == 0 {
+            return 0;
+        }
+        wave.saturating_sub(self.scripted_waves)
+    }
+
+    pub fn composition(&self, wave: u32) -> WaveComposition {
+        WaveComposition {
+            knights: self.wave_size(wave),
+            gargoyles: self.gargoyle_count(wave),
+            armored_knights: self.armored_knight_count(wave),
+            assassins: self.assassin_count(wave),
+        }
+    }
+}
+
+// Levels with a collapsing floor lose a few more tiles to holes every wave
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CollapseSchedule {
+    pub first_wave: u32,
+    pub tiles_per_wave: u32,
+    // Seconds a tile cracks for before it falls in
+    pub warning: f32,
+    // In tiles, the floor around the altar never falls in
+    pub safe_radius: f32,
+}
+
+impl Default for CollapseSchedule {
+    fn default() -> Self {
+        Self {
+            first_wave: 3,
+            tiles_per_wave: 8,
+            warning: 4.0,
+            safe_radius: 5.0,
+        }
+    }
+}
+
+// Knights the level sends round a route of waypoints instead of straight at the altar. They
+// come in from the spawn points like the rest and join the route wherever is closest.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LevelPatrol {
+    // The first wave that sends knights out on it, every wave after does as well
+    pub from_wave: u32,
+    pub knights: u32,
+    pub waypoints: Vec<(f32, f32)>,
+}
+
+impl LevelPatrol {
+    pub fn route(&self) -> Vec<Vec2> {
+        self.waypoints
+            .iter()
+            .map(|waypoint| Vec2::from(*waypoint))
+            .collect()
+    }
+}
+
+// An arena, written as ron in assets/levels. Levels without spawn points get enemies from every
+// edge of the screen like before.
+#[derive(Asset, TypePath, Debug, Clone, Deserialize)]
+pub struct LevelDefinition {
+    pub name: String,
+    pub map: String,
+    #[serde(default)]
+    pub spawn_points: Vec<(f32, f32)>,
+    #[serde(default)]
+    pub waves: WaveSchedule,
+    #[serde(default)]
+    pub triggers: Vec<Trigger>,
+    // Pins every run of the level to one seed, for challenge levels everyone plays the same way
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub collapse: Option<CollapseSchedule>,
+    #[serde(default)]
+    pub grading: Grading,
+    #[serde(default)]
+    pub summons: SummonLimits,
+    #[serde(default)]
+    pub patrols: Vec<LevelPatrol>,
+}
+
+// The parts of the chosen level the running game needs, kept around so restarts and checkpoints
+// don't have to go through the asset again
+#[derive(Resource, Default)]
+pub struct ActiveLevel {
+    pub name: String,
+    pub spawn_points: Vec<Vec2>,
+    pub waves: WaveSchedule,
+    pub triggers: Vec<Trigger>,
+    pub seed: Option<u64>,
+    pub collapse: Option<CollapseSchedule>,
+    pub grading: Grading,
+    pub summons: SummonLimits,
+    pub patrols: Vec<LevelPatrol>,
+}
+
+impl ActiveLevel {
+    pub fn from_definition(definition: &LevelDefinition) -> Self {
+        Self {
+            name: definition.name.clone(),
+            spawn_points: definition
+                .spawn_points
+                .iter()
+                .map(|point| Vec2::from(*point))
+                .collect(),
+            waves: definition.waves.clone(),
+            triggers: definition.triggers.clone(),
+            seed: definition.seed,
+            collapse: definition.collapse.clone(),
+            grading: definition.grading.clone(),
+            summons: definition.summons,
+            patrols: definition.patrols.clone(),
+        }
+    }
+
+    pub fn spawn_position(&self, rng: &mut GameRng, play_area: Vec2) -> Vec2 {
+        let Some(point) = self.spawn_points.choose(&mut rng.0) else {
+            return random_spawn_position(rng, play_area);
+        };
+
+        let spread = Vec2::new(rng.0.gen(), rng.0.gen()) - 0.5;
+        *point + spread * SPAWN_POINT_SPREAD
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Levels {
+    pub handles: Vec<Handle<LevelDefinition>>,
+    pub selected: usize,
+}
+
+impl Levels {
+    pub fn selected_handle(&self) -> Option<&Handle<LevelDefinition>> {
+        self.handles.get(self.selected)
+    }
+}
+
+pub fn load_levels_system(asset_server: Res<AssetServer>, mut levels: ResMut<Levels>) {
+    levels.handles = LEVEL_PATHS
+        .iter()
+        .map(|path| asset_server.load(*path))
+        .collect();
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(std::io::Error),
+    Deserialize(ron::error::SpannedError),
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Io(error) => write!(f, "io error: {}", error),
+            LevelError::Deserialize(error) => write!(f, "could not read level: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+impl From<std::io::Error> for LevelError {
+    fn from(error: std::io::Error) -> Self {
+        LevelError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for LevelError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        LevelError::Deserialize(error)
+    }
+}
+
+#[derive(Default)]
+pub struct LevelDefinitionLoader;
+
+impl AssetLoader for LevelDefinitionLoader {
+    type Asset = LevelDefinition;
+    type Settings = ();
+    type Error = LevelError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+
+use crate::dark_arts_defense::AppState;
+use crate::settings::menu::settings_closed;
+
+use super::definition::{self, ActiveLevel, LevelDefinition, LevelDefinitionLoader, Levels};
+use super::{select, triggers};
+
+pub struct LevelsPlugin;
+
+impl Plugin for LevelsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<LevelDefinition>()
+            .init_asset_loader::<LevelDefinitionLoader>()
+            .init_resource::<Levels>()
+            .init_resource::<ActiveLevel>()
+            .init_resource::<triggers::TriggerState>()
+            .add_event::<triggers::TriggerAction>()
+            .add_systems(Startup, definition::load_levels_system)
+            .add_systems(OnEnter(AppState::LevelSelect), select::spawn_level_select)
+            .add_systems(OnExit(AppState::LevelSelect), select::despawn_level_select)
+            .add_systems(
+                Update,
+                (
+                    select::level_select_system
+                        .run_if(in_state(AppState::LevelSelect))
+                        .run_if(settings_closed),
+                    triggers::evaluate_triggers.run_if(in_state(AppState::Playing)),
+                    triggers::reset_triggers_system,
+                ),
+            );
+    }
+}
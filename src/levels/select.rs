@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+
+use crate::dark_arts_defense::AppState;
+use crate::difficulty::Difficulty;
+use crate::game_mode::GameMode;
+use crate::meta::progress::MetaProgress;
+use crate::render_scale::UI_LAYER;
+use crate::settings::menu::SettingsState;
+use crate::stats::run_stats::Leaderboard;
+use crate::tutorial::TutorialState;
+
+use super::definition::{LevelDefinition, Levels};
+
+#[derive(Component)]
+pub struct LevelSelectText;
+
+pub fn spawn_level_select(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+                    font_size: 60.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_justify(JustifyText::Center),
+            ..default()
+        },
+        LevelSelectText,
+        RenderLayers::layer(UI_LAYER),
+    ));
+}
+
+pub fn despawn_level_select(mut commands: Commands, query: Query<Entity, With<LevelSelectText>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// W/S or the arrow keys to pick an arena, A/D to pick the difficulty, TAB to switch between the
+// campaign and endless, U for the upgrades, O for the settings, T for the tutorial, SPACE or ENTER
+// to play it
+#[allow(clippy::too_many_arguments)]
+pub fn level_select_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    definitions: Res<Assets<LevelDefinition>>,
+    leaderboard: Res<Leaderboard>,
+    progress: Res<MetaProgress>,
+    mut levels: ResMut<Levels>,
+    mut difficulty: ResMut<Difficulty>,
+    mut mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut settings_state: ResMut<NextState<SettingsState>>,
+    mut tutorial_state: ResMut<NextState<TutorialState>>,
+    mut text_query: Query<&mut Text, With<LevelSelectText>>,
+) {
+    let count = levels.handles.len();
+    if count == 0 {
+        return;
+    }
+
+    if keys.any_just_pressed([KeyCode::KeyW, KeyCode::ArrowUp]) {
+        levels.selected = (levels.selected + count - 1) % count;
+    }
+    if keys.any_just_pressed([KeyCode::KeyS, KeyCode::ArrowDown]) {
+        levels.selected = (levels.selected + 1) % count;
+    }
+    if keys.any_just_pressed([KeyCode::KeyA, KeyCode::ArrowLeft]) {
+        *difficulty = difficulty.previous();
+    }
+    if keys.any_just_pressed([KeyCode::KeyD, KeyCode::ArrowRight]) {
+        *difficulty = difficulty.next();
+    }
+    if keys.just_pressed(KeyCode::Tab) {
+        *mode = mode.toggled();
+    }
+
+    let selected_loaded = levels
+        .selected_handle()
+        .is_some_and(|handle| definitions.contains(handle));
+    if selected_loaded && keys.any_just_pressed([KeyCode::Space, KeyCode::Enter]) {
+        next_state.set(AppState::Playing);
+    } else if selected_loaded && keys.just_pressed(KeyCode::KeyT) {
+        // Played on the selected arena, the campaign's waves are held back until it's over
+        *mode = GameMode::Campaign;
+        tutorial_state.set(TutorialState::Running);
+        next_state.set(AppState::Playing);
+    } else if keys.just_pressed(KeyCode::KeyU) {
+        next_state.set(AppState::Upgrades);
+    } else if keys.just_pressed(KeyCode::KeyO) {
+        settings_state.set(SettingsState::Open);
+    }
+
+    let mut lines = vec!["Dark Arts Defense".to_owned(), String::new()];
+    for (index, handle) in levels.handles.iter().enumerate() {
+        let name = definitions
+            .get(handle)
+            .map_or("Loading...", |definition| definition.name.as_str());
+        let marker = if index == levels.selected { ">" } else { " " };
+        lines.push(format!("{} {}", marker, name));
+    }
+    lines.push(String::new());
+    lines.push(format!("< {} >", difficulty.name()));
+    lines.push(format!("{} (TAB)", mode.name()));
+    let best = levels
+        .selected_handle()
+        .and_then(|handle| definitions.get(handle))
+        .and_then(|definition| leaderboard.best(&definition.name))
+        .filter(|_| *mode == GameMode::Endless);
+    if let Some(best) = best {
+        lines.push(format!(
+            "Best: wave {} on {}, {} kills",
+            best.wave,
+            best.difficulty.name(),
+            best.kills
+        ));
+    }
+    lines.push(String::new());
+    lines.push("Press SPACE to play".to_owned());
+    lines.push(format!("{} souls, U for upgrades", progress.souls));
+    lines.push("O for settings, T for the tutorial".to_owned());
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = lines.join("\n");
+    }
+}
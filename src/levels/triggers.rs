@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::enemies::enemy_spawner::EnemySpawner;
+use crate::events::GameEvent;
+use crate::units::altar::DarkAltar;
+use crate::units::health::Health;
+
+use super::definition::ActiveLevel;
+
+// Scripted moments of a level, written in its ron file as
+//   (when: Wave(5), then: [Dialogue("The doors grind open"), OpenGates])
+// Every trigger fires once per run, the first frame its condition holds.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Trigger {
+    pub when: TriggerCondition,
+    pub then: Vec<TriggerAction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum TriggerCondition {
+    // This wave or any later one has started, so restarting from a later checkpoint still fires it
+    Wave(u32),
+    // The altar is down to less than this fraction of its health
+    AltarHealthBelow(f32),
+    // Seconds into the run
+    Time(f32),
+    All(Vec<TriggerCondition>),
+    Any(Vec<TriggerCondition>),
+}
+
+// Sent when a trigger fires, whichever part of the game an action is about picks it up from here
+#[derive(Event, Debug, Clone, Deserialize)]
+pub enum TriggerAction {
+    SpawnBoss,
+    Dialogue(String),
+    // Jump straight to midnight or noon, the cycle carries on from there
+    Night,
+    Day,
+    OpenGates,
+}
+
+// What the conditions are checked against, gathered once per frame
+struct TriggerContext {
+    wave: u32,
+    altar_health: Option<f32>,
+    elapsed: f32,
+}
+
+impl TriggerCondition {
+    fn holds(&self, context: &TriggerContext) -> bool {
+        match self {
+            TriggerCondition::Wave(wave) => context.wave >= *wave,
+            TriggerCondition::AltarHealthBelow(fraction) => context
+                .altar_health
+                .is_some_and(|health| health < *fraction),
+            TriggerCondition::Time(seconds) => context.elapsed >= *seconds,
+            TriggerCondition::All(conditions) => conditions.iter().all(|c| c.holds(context)),
+            TriggerCondition::Any(conditions) => conditions.iter().any(|c| c.holds(context)),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct TriggerState {
+    pub elapsed: f32,
+    // Indices into the level's triggers that already went off this run
+    pub fired: Vec<usize>,
+}
+
+pub fn evaluate_triggers(
+    time: Res<Time>,
+    level: Res<ActiveLevel>,
+    mut state: ResMut<TriggerState>,
+    spawner_query: Query<&EnemySpawner>,
+    altar_query: Query<&Health, With<DarkAltar>>,
+    mut action_writer: EventWriter<TriggerAction>,
+) {
+    // Nothing runs until the run is actually going
+    let Some(spawner) = spawner_query.iter().next() else {
+        return;
+    };
+
+    state.elapsed += time.delta_seconds();
+    let context = TriggerContext {
+        wave: spawner.wave,
+        altar_health: altar_query.iter().next().map(|health| health.fraction()),
+        elapsed: state.elapsed,
+    };
+
+    for (index, trigger) in level.triggers.iter().enumerate() {
+        if state.fired.contains(&index) || !trigger.when.holds(&context) {
+            continue;
+        }
+
+        state.fired.push(index);
+        for action in trigger.then.iter() {
+            action_writer.send(action.clone());
+        }
+    }
+}
+
+pub fn reset_triggers_system(
+    mut event_reader: EventReader<GameEvent>,
+    mut state: ResMut<TriggerState>,
+) {
+    for event in event_reader.read() {
+        if let GameEvent::StartGame | GameEvent::RestartFromCheckpoint = event {
+            *state = TriggerState::default();
+        }
+    }
+}
@@ -0,0 +1,179 @@
+pub mod animation;
+pub mod aseprite;
+pub mod camera;
+pub mod capture {
+    pub mod clips;
+    pub mod gif;
+    pub mod plugin;
+    pub mod screenshot;
+}
+#[cfg(feature = "debug_tools")]
+pub mod console {
+    pub mod commands;
+    pub mod plugin;
+    pub mod registry;
+}
+pub mod dark_arts_defense;
+#[cfg(feature = "debug_tools")]
+pub mod debug_overlay;
+pub mod difficulty;
+pub mod player {
+    pub mod build_mode;
+    pub mod charm;
+    pub mod command_mode;
+    pub mod corruption;
+    pub mod familiar;
+    pub mod gravestones;
+    pub mod hurt;
+    pub mod movement;
+    pub mod perks;
+    pub mod plugin;
+    pub mod relics;
+    pub mod spawn;
+    pub mod summoning;
+    pub mod ultimate;
+}
+pub mod units {
+    pub mod acolyte;
+    pub mod altar;
+    pub mod attack;
+    pub mod damage;
+    pub mod death;
+    pub mod flying;
+    pub mod frenzy;
+    pub mod health;
+    pub mod imp;
+    pub mod morale;
+    pub mod plugin;
+    pub mod quality;
+    pub mod spawning;
+    pub mod stat_modifiers;
+    pub mod team;
+    pub mod unit_types;
+    pub mod veterancy;
+    pub mod wildlife;
+    pub mod y_sort;
+}
+pub mod enemies {
+    pub mod assassin;
+    pub mod boss;
+    pub mod bounty;
+    pub mod endless;
+    pub mod enemy_spawner;
+    pub mod mutators;
+    pub mod plugin;
+    pub mod spawn_queue;
+}
+pub mod events;
+pub mod game_mode;
+pub mod interpolation;
+pub mod juice;
+pub mod level_assets;
+pub mod levels {
+    pub mod definition;
+    pub mod plugin;
+    pub mod select;
+    pub mod triggers;
+}
+pub mod map {
+    pub mod collapse;
+    pub mod fog;
+    pub mod plugin;
+    pub mod tilemap;
+}
+pub mod mana;
+pub mod meta {
+    pub mod plugin;
+    pub mod progress;
+    pub mod upgrade_menu;
+}
+pub mod movement;
+pub mod narrative {
+    pub mod conversation;
+    pub mod plugin;
+    pub mod script;
+}
+pub mod photo_mode;
+pub mod pool;
+pub mod pickups {
+    pub mod drops;
+    pub mod plugin;
+}
+#[cfg(feature = "twitch")]
+pub mod twitch;
+pub mod render_scale;
+pub mod rng;
+pub mod schedule;
+pub mod settings {
+    pub mod accessibility;
+    pub mod bindings;
+    pub mod config;
+    pub mod menu;
+    pub mod plugin;
+}
+pub mod silhouette;
+pub mod stats {
+    pub mod grading;
+    pub mod plugin;
+    pub mod run_stats;
+}
+pub mod save {
+    pub mod checkpoints;
+    pub mod plugin;
+    pub mod snapshot;
+}
+// Only built for the unit tests, and for the benches which drive the same TestApp
+#[cfg(any(test, feature = "bench"))]
+pub mod test_utils;
+pub mod time_of_day;
+pub mod tutorial;
+pub mod structures {
+    pub mod effects;
+    pub mod plugin;
+    pub mod structure_types;
+}
+pub mod utils {
+    pub mod timing;
+}
+pub mod validate;
+pub mod velocity;
+pub mod vfx {
+    pub mod beam;
+    pub mod flash;
+    pub mod particles;
+    pub mod plugin;
+}
+pub mod ai {
+    pub mod aggro;
+    pub mod behavior;
+    pub mod formations;
+    pub mod plugin;
+    pub mod sleep;
+}
+pub mod ui {
+    pub mod boss_bar;
+    pub mod damage_numbers;
+    pub mod dialogue;
+    pub mod health_text;
+    pub mod hud;
+    pub mod inspect;
+    pub mod kill_feed;
+    pub mod latency_probe;
+    pub mod mana_text;
+    pub mod minimap;
+    pub mod nameplate;
+    pub mod offscreen_arrows;
+    pub mod plugin;
+    pub mod relic_choice;
+    pub mod score_text;
+    pub mod summon_roster;
+    pub mod wave_grade;
+    pub mod wave_preview;
+}
+pub mod game_view;
+pub mod gamestate;
+pub mod headless {
+    pub mod plugin;
+    pub mod policy;
+    pub mod report;
+}
@@ -1,44 +1,22 @@
-pub mod animation;
-pub mod dark_arts_defense;
-pub mod player {
-    pub mod movement;
-    pub mod plugin;
-    pub mod spawn;
-    pub mod summoning;
-}
-pub mod units {
-    pub mod acolyte;
-    pub mod health;
-    pub mod team;
-    pub mod unit_types;
-}
-pub mod enemies {
-    pub mod enemy_spawner;
-    pub mod plugin;
-}
-pub mod mana;
-pub mod movement;
-pub mod velocity;
-pub mod ai {
-    pub mod behavior;
-    pub mod plugin;
-}
-pub mod ui {
-    pub mod health_text;
-    pub mod mana_text;
-    pub mod plugin;
-    pub mod score_text;
-}
-pub mod gamestate;
-
 use bevy::prelude::*;
 use bevy::window::{EnabledButtons, WindowMode, WindowResolution};
 
+use dark_arts_defense::{dark_arts_defense::DarkArtsDefensePlugin, headless, validate};
+
 fn main() {
+    // Lints the data files and exits instead of starting the game
+    if std::env::args().any(|arg| arg == validate::VALIDATE_ASSETS_FLAG) {
+        std::process::exit(validate::validate_assets());
+    }
+    // Plays a run without a window and prints how every wave went, see headless/plugin.rs
+    if std::env::args().any(|arg| arg == headless::plugin::HEADLESS_FLAG) {
+        std::process::exit(headless::plugin::run_headless());
+    }
+
     App::new()
         .add_plugins((
             DefaultPlugins.set(ImagePlugin::default_nearest()),
-            dark_arts_defense::DarkArtsDefensePlugin,
+            DarkArtsDefensePlugin,
         ))
         .add_systems(Startup, setup_window)
         .run();
@@ -1,7 +1,36 @@
 use bevy::prelude::*;
 
+use crate::events::ManaChanged;
+use crate::player::plugin::Player;
+
 #[derive(Component)]
 pub struct Mana {
     pub current_mana: u8,
     pub max_mana: u8,
 }
+
+// Mana is spent and given in too many places to send from each one, so this watches for it
+pub fn report_mana_changes(
+    mut last: Local<Option<u8>>,
+    query: Query<Ref<Mana>, With<Player>>,
+    mut mana_writer: EventWriter<ManaChanged>,
+) {
+    let Ok(mana) = query.get_single() else {
+        return;
+    };
+    // A new summoner starts from what it was given, not from where the last run left off
+    if mana.is_added() {
+        *last = Some(mana.current_mana);
+        return;
+    }
+    if !mana.is_changed() || *last == Some(mana.current_mana) {
+        return;
+    }
+
+    mana_writer.send(ManaChanged {
+        previous: last.unwrap_or(mana.current_mana),
+        current: mana.current_mana,
+        max: mana.max_mana,
+    });
+    *last = Some(mana.current_mana);
+}
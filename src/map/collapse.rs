@@ -0,0 +1,197 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+
+use crate::enemies::enemy_spawner::EnemySpawner;
+use crate::events::{Damage, GameEvent};
+use crate::gamestate::Cleanup;
+use crate::levels::definition::ActiveLevel;
+use crate::rng::GameRng;
+use crate::time_of_day::lerp_color;
+use crate::units::damage::DamageKind;
+use crate::units::flying::Flying;
+use crate::units::health::Health;
+use crate::units::unit_types::CurrentUnitType;
+
+use super::plugin::{tile_bundle, CurrentMap};
+use super::tilemap::{Tile, TileMap, TILE_SIZE};
+
+const CRACK_COLOR: Color = Color::rgb(0.55, 0.3, 0.15);
+const CRACK_ALPHA: f32 = 0.7;
+// Flickers faster the closer it gets to falling in
+const CRACK_FLICKER_SPEED: f32 = 4.0;
+// Out of the tiles furthest from the altar, the ones that fall are picked among this many times
+// as many as the wave needs, so the edge crumbles unevenly instead of peeling off in rings
+const CANDIDATE_FACTOR: usize = 3;
+// More than enough to kill anything, whatever armor or resistances it has
+const FALL_DAMAGE: i32 = 1_000_000;
+
+#[derive(Resource, Default)]
+pub struct CollapseState {
+    pub last_wave: u32,
+}
+
+// A tile about to fall in, flickering as a warning to get off it
+#[derive(Component)]
+pub struct CrackingTile {
+    pub tile: IVec2,
+    pub timer: Timer,
+}
+
+pub fn reset_collapse_system(
+    mut event_reader: EventReader<GameEvent>,
+    mut state: ResMut<CollapseState>,
+) {
+    for event in event_reader.read() {
+        if let GameEvent::StartGame | GameEvent::RestartFromCheckpoint = event {
+            *state = CollapseState::default();
+        }
+    }
+}
+
+// Every wave from the level's first collapsing one cracks some more of the floor, starting at
+// the edges and working in towards the altar
+#[allow(clippy::too_many_arguments)]
+pub fn crack_tiles(
+    mut commands: Commands,
+    level: Res<ActiveLevel>,
+    maps: Res<Assets<TileMap>>,
+    current_map: Res<CurrentMap>,
+    mut state: ResMut<CollapseState>,
+    mut rng: ResMut<GameRng>,
+    spawner_query: Query<&EnemySpawner>,
+    cracking_query: Query<&CrackingTile>,
+) {
+    let Some(schedule) = &level.collapse else {
+        return;
+    };
+    let Some(spawner) = spawner_query.iter().next() else {
+        return;
+    };
+    if spawner.wave <= state.last_wave {
+        return;
+    }
+    state.last_wave = spawner.wave;
+    if spawner.wave < schedule.first_wave {
+        return;
+    }
+    let Some(map) = current_map.get(&maps) else {
+        return;
+    };
+
+    let safe_distance = schedule.safe_radius * TILE_SIZE;
+    let mut candidates: Vec<(IVec2, f32)> = (0..map.height as i32)
+        .flat_map(|y| (0..map.width as i32).map(move |x| IVec2::new(x, y)))
+        .filter(|tile| map.tile_at(tile.x, tile.y) == Some(Tile::Ground))
+        .map(|tile| (tile, map.tile_to_world(tile).length()))
+        .filter(|(_, distance)| *distance > safe_distance)
+        .collect();
+    candidates.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+    let count = schedule.tiles_per_wave as usize;
+    let mut picked: Vec<IVec2> = cracking_query
+        .iter()
+        .map(|cracking| cracking.tile)
+        .collect();
+    let already_cracking = picked.len();
+    let mut pool: Vec<IVec2> = candidates
+        .iter()
+        .map(|(tile, _)| *tile)
+        .filter(|tile| !picked.contains(tile))
+        .take(count * CANDIDATE_FACTOR)
+        .collect();
+    pool.shuffle(&mut rng.0);
+
+    // Never cuts a part of the arena off from the altar, enemies need a way in
+    for tile in pool {
+        if picked.len() - already_cracking >= count {
+            break;
+        }
+
+        picked.push(tile);
+        if !map.stays_connected(&picked) {
+            picked.pop();
+        }
+    }
+
+    for tile in picked.into_iter().skip(already_cracking) {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: CRACK_COLOR.with_a(0.0),
+                    custom_size: Some(Vec2::splat(TILE_SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_translation(map.tile_to_world(tile).extend(-4.0)),
+                ..default()
+            },
+            CrackingTile {
+                tile,
+                timer: Timer::from_seconds(schedule.warning, TimerMode::Once),
+            },
+            Cleanup,
+        ));
+    }
+}
+
+type GroundUnitFilter = (With<CurrentUnitType>, Without<Flying>);
+
+pub fn collapse_tiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut maps: ResMut<Assets<TileMap>>,
+    current_map: Res<CurrentMap>,
+    mut cracking_query: Query<(Entity, &mut CrackingTile, &mut Sprite)>,
+    unit_query: Query<(Entity, &Transform, &Health), GroundUnitFilter>,
+    mut damage_writer: EventWriter<Damage>,
+) {
+    // Getting the map mutably marks it as changed, so only when there's something to change
+    if cracking_query.is_empty() {
+        return;
+    }
+    let Some(map) = current_map
+        .handle
+        .as_ref()
+        .and_then(|handle| maps.get_mut(handle))
+    else {
+        return;
+    };
+
+    for (entity, mut cracking, mut sprite) in cracking_query.iter_mut() {
+        if !cracking.timer.tick(time.delta()).finished() {
+            let progress = cracking.timer.fraction();
+            let flicker = (cracking.timer.elapsed_secs()
+                * CRACK_FLICKER_SPEED
+                * (1.0 + progress * 2.0)
+                * std::f32::consts::TAU)
+                .sin()
+                * 0.5
+                + 0.5;
+            sprite.color = lerp_color(CRACK_COLOR, Tile::Hole.color(), progress)
+                .with_a(CRACK_ALPHA * (0.4 + 0.6 * flicker));
+            continue;
+        }
+
+        commands.entity(entity).despawn();
+        map.set_tile(cracking.tile, Tile::Hole);
+        let position = map.tile_to_world(cracking.tile);
+        commands.spawn((tile_bundle(Tile::Hole, position), Cleanup));
+
+        // Whoever was still standing on it falls in with it
+        for (unit, transform, health) in unit_query.iter() {
+            if health.is_dead()
+                || map.world_to_tile(transform.translation.truncate()) != cracking.tile
+            {
+                continue;
+            }
+
+            damage_writer.send(Damage {
+                target: unit,
+                amount: FALL_DAMAGE,
+                kind: DamageKind::Physical,
+                armor_piercing: true,
+                source: None,
+                critical: false,
+            });
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+
+// Above the units and props, the ui is drawn by its own camera so it is never covered
+pub const FOG_Z: f32 = 10.0;
+const FOG_COLOR: Color = Color::rgb(0.05, 0.05, 0.08);
+const FOG_ALPHA: f32 = 0.6;
+// The fog thins out over this last part of a revealer's radius instead of ending in a hard edge
+const FOG_FADE: f32 = 0.35;
+
+#[derive(Component)]
+pub struct FogTile;
+
+// Clears the fog around whatever it is on
+#[derive(Component, Clone, Copy, Debug)]
+pub struct FogRevealer {
+    pub radius: f32,
+}
+
+pub fn fog_tile_bundle(position: Vec2, size: f32) -> impl Bundle {
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: FOG_COLOR.with_a(FOG_ALPHA),
+                custom_size: Some(Vec2::splat(size)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(FOG_Z)),
+            ..default()
+        },
+        FogTile,
+    )
+}
+
+pub fn update_fog(
+    revealer_query: Query<(&GlobalTransform, &FogRevealer)>,
+    mut fog_query: Query<(&Transform, &mut Sprite), With<FogTile>>,
+) {
+    let revealers = revealer_query
+        .iter()
+        .map(|(transform, revealer)| (transform.translation().truncate(), revealer.radius))
+        .collect::<Vec<_>>();
+
+    for (transform, mut sprite) in fog_query.iter_mut() {
+        let position = transform.translation.truncate();
+        let clear = revealers
+            .iter()
+            .map(|(center, radius)| {
+                let distance = position.distance(*center) / radius;
+                ((1.0 - distance) / FOG_FADE).clamp(0.0, 1.0)
+            })
+            .fold(0.0, f32::max);
+
+        let alpha = FOG_ALPHA * (1.0 - clear);
+        if sprite.color.a() != alpha {
+            sprite.color.set_a(alpha);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+
+use crate::dark_arts_defense::AppState;
+use crate::events::GameEvent;
+use crate::gamestate::Cleanup;
+use crate::levels::triggers::TriggerAction;
+use crate::schedule::{FrameSet, SimSet};
+use crate::units::flying::Flying;
+use crate::velocity::{self, Velocity};
+
+use super::collapse;
+use super::fog::{self, fog_tile_bundle};
+use super::tilemap::{Tile, TileMap, TileMapLoader, TILE_SIZE};
+
+const UNIT_RADIUS: f32 = 16.0;
+const TILE_Z: f32 = -5.0;
+
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<TileMap>()
+            .init_asset_loader::<TileMapLoader>()
+            .init_resource::<CurrentMap>()
+            .init_resource::<collapse::CollapseState>()
+            .add_systems(
+                Update,
+                (
+                    respawn_map_system,
+                    spawn_map_tiles,
+                    open_gates,
+                    collapse::reset_collapse_system,
+                    (collapse::crack_tiles, collapse::collapse_tiles)
+                        .chain()
+                        .run_if(in_state(AppState::Playing)),
+                    fog::update_fog.in_set(FrameSet::Presentation),
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
+                block_tiles
+                    .in_set(SimSet::Movement)
+                    .after(velocity::translate),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct GateTile;
+
+#[derive(Resource, Default)]
+pub struct CurrentMap {
+    pub handle: Option<Handle<TileMap>>,
+    pub spawned: bool,
+}
+
+impl CurrentMap {
+    pub fn get<'a>(&self, maps: &'a Assets<TileMap>) -> Option<&'a TileMap> {
+        maps.get(self.handle.as_ref()?)
+    }
+}
+
+// The map itself comes from the chosen level, but its tiles are cleaned up with everything else
+// whenever a run starts over
+fn respawn_map_system(
+    mut event_reader: EventReader<GameEvent>,
+    mut maps: ResMut<Assets<TileMap>>,
+    mut current_map: ResMut<CurrentMap>,
+) {
+    for event in event_reader.read() {
+        if let GameEvent::StartGame | GameEvent::RestartFromCheckpoint = event {
+            current_map.spawned = false;
+            // Gates start every run shut and the floor whole, whatever the last one left them as
+            if let Some(map) = current_map
+                .handle
+                .as_ref()
+                .and_then(|handle| maps.get_mut(handle))
+            {
+                map.reset();
+            }
+        }
+    }
+}
+
+fn open_gates(
+    mut commands: Commands,
+    mut action_reader: EventReader<TriggerAction>,
+    mut maps: ResMut<Assets<TileMap>>,
+    current_map: Res<CurrentMap>,
+    gate_query: Query<Entity, With<GateTile>>,
+) {
+    if !action_reader
+        .read()
+        .any(|action| matches!(action, TriggerAction::OpenGates))
+    {
+        return;
+    }
+
+    let Some(map) = current_map
+        .handle
+        .as_ref()
+        .and_then(|handle| maps.get_mut(handle))
+    else {
+        return;
+    };
+    map.set_gates_open(true);
+    for entity in gate_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+// The map loads asynchronously, so the tiles are spawned whenever it is ready
+fn spawn_map_tiles(
+    mut commands: Commands,
+    maps: Res<Assets<TileMap>>,
+    mut current_map: ResMut<CurrentMap>,
+) {
+    if current_map.spawned {
+        return;
+    }
+    let Some(map) = current_map.get(&maps) else {
+        return;
+    };
+
+    for y in 0..map.height as i32 {
+        for x in 0..map.width as i32 {
+            let position = map.tile_to_world(IVec2::new(x, y));
+            commands.spawn((fog_tile_bundle(position, TILE_SIZE), Cleanup));
+
+            let Some(tile) = map.tile_at(x, y).filter(|tile| !tile.is_walkable(false)) else {
+                continue;
+            };
+
+            let mut sprite = commands.spawn((tile_bundle(tile, position), Cleanup));
+            if tile == Tile::Gate {
+                sprite.insert(GateTile);
+            }
+        }
+    }
+
+    current_map.spawned = true;
+}
+
+pub fn tile_bundle(tile: Tile, position: Vec2) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color: tile.color(),
+            custom_size: Some(Vec2::splat(TILE_SIZE)),
+            ..default()
+        },
+        transform: Transform::from_translation(position.extend(TILE_Z)),
+        ..default()
+    }
+}
+
+// Same idea as the bone walls, anything that walked into a blocked tile is pushed back out
+// along the shortest way so it slides along the edge.
+fn block_tiles(
+    maps: Res<Assets<TileMap>>,
+    current_map: Res<CurrentMap>,
+    mut query: Query<&mut Transform, (With<Velocity>, Without<Flying>)>,
+) {
+    let Some(map) = current_map.get(&maps) else {
+        return;
+    };
+
+    for mut transform in query.iter_mut() {
+        let center = map.world_to_tile(transform.translation.truncate());
+        for y in -1..=1 {
+            for x in -1..=1 {
+                let tile = center + IVec2::new(x, y);
+                if map
+                    .tile_at(tile.x, tile.y)
+                    .is_none_or(|tile| tile.is_walkable(false))
+                {
+                    continue;
+                }
+
+                let half_extents = Vec2::splat(TILE_SIZE * 0.5 + UNIT_RADIUS);
+                let offset = transform.translation.truncate() - map.tile_to_world(tile);
+                let penetration = half_extents - offset.abs();
+                if penetration.x <= 0.0 || penetration.y <= 0.0 {
+                    continue;
+                }
+
+                if penetration.x < penetration.y {
+                    transform.translation.x += penetration.x * offset.x.signum();
+                } else {
+                    transform.translation.y += penetration.y * offset.y.signum();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::BoxedFuture;
+
+use crate::structures::structure_types::GRID_SIZE;
+
+// Same as the build grid, so walls snap neatly against rocks. Maps with an odd width and height
+// have a tile centered on origo, which keeps the two grids lined up.
+pub const TILE_SIZE: f32 = GRID_SIZE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+    Ground,
+    Rock,
+    Water,
+    Gate,
+    Hole,
+}
+
+impl Tile {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '.' => Some(Tile::Ground),
+            '#' => Some(Tile::Rock),
+            '~' => Some(Tile::Water),
+            '+' => Some(Tile::Gate),
+            'o' => Some(Tile::Hole),
+            _ => None,
+        }
+    }
+
+    // Flying units pass over everything, ground units only walk on ground
+    pub fn is_walkable(&self, flying: bool) -> bool {
+        flying || *self == Tile::Ground
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Tile::Ground => Color::NONE,
+            Tile::Rock => Color::rgb(0.22, 0.2, 0.24),
+            Tile::Water => Color::rgb(0.1, 0.16, 0.3),
+            Tile::Gate => Color::rgb(0.35, 0.25, 0.15),
+            Tile::Hole => Color::rgb(0.02, 0.02, 0.03),
+        }
+    }
+}
+
+// A level is a plain text grid, one character per tile, centered on origo where the altar
+// stands:
+//   .  ground
+//   #  rock, blocks walking
+//   ~  water, blocks walking but not flying
+//   +  gate, blocks walking until a level trigger opens it
+//   o  hole, blocks walking but not flying, levels with a collapsing floor make more of them
+#[derive(Asset, TypePath, Debug)]
+pub struct TileMap {
+    pub width: usize,
+    pub height: usize,
+    pub tiles: Vec<Tile>,
+    // Open gates are plain ground as far as everyone else is concerned
+    pub gates_open: bool,
+    // The tiles as the file has them, what a new run starts from after the floor fell in
+    initial_tiles: Vec<Tile>,
+    // Steps to the center tile for every walkable tile, None when it can't be reached
+    distances: Vec<Option<u32>>,
+}
+
+impl TileMap {
+    pub fn parse(text: &str) -> Result<Self, TileMapError> {
+        let rows: Vec<&str> = text
+            .lines()
+            .map(|line| line.trim_end())
+            .filter(|line| !line.is_empty())
+            .collect();
+
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.chars().count());
+        if width == 0 {
+            return Err(TileMapError::Empty);
+        }
+
+        let mut tiles = Vec::with_capacity(width * height);
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(TileMapError::UnevenRow(y + 1));
+            }
+
+            for (x, c) in row.chars().enumerate() {
+                let tile = Tile::from_char(c).ok_or(TileMapError::UnknownTile(c, x + 1, y + 1))?;
+                tiles.push(tile);
+            }
+        }
+
+        let mut map = Self {
+            width,
+            height,
+            initial_tiles: tiles.clone(),
+            tiles,
+            gates_open: false,
+            distances: Vec::new(),
+        };
+        map.distances = map.compute_distances();
+        Ok(map)
+    }
+
+    pub fn tile_at(&self, x: i32, y: i32) -> Option<Tile> {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return None;
+        }
+
+        match self.tiles[y as usize * self.width + x as usize] {
+            Tile::Gate if self.gates_open => Some(Tile::Ground),
+            tile => Some(tile),
+        }
+    }
+
+    pub fn set_gates_open(&mut self, open: bool) {
+        if self.gates_open != open {
+            self.gates_open = open;
+            self.distances = self.compute_distances();
+        }
+    }
+
+    pub fn set_tile(&mut self, tile: IVec2, to: Tile) {
+        if self.tile_at(tile.x, tile.y).is_none() {
+            return;
+        }
+
+        self.tiles[tile.y as usize * self.width + tile.x as usize] = to;
+        self.distances = self.compute_distances();
+    }
+
+    // Back to how the file has it, gates shut
+    pub fn reset(&mut self) {
+        self.tiles.clone_from(&self.initial_tiles);
+        self.gates_open = false;
+        self.distances = self.compute_distances();
+    }
+
+    // Whether every tile that can reach the altar now still could with these ones blocked too
+    pub fn stays_connected(&self, blocked: &[IVec2]) -> bool {
+        let reachable = |distances: &[Option<u32>]| distances.iter().flatten().count();
+        let still_reachable = reachable(&self.flood(blocked));
+        let lost = blocked
+            .iter()
+            .filter(|tile| self.distance(**tile).is_some())
+            .count();
+        still_reachable + lost == reachable(&self.distances)
+    }
+
+    // Row 0 is the top row of the file, which is the top of the screen
+    pub fn world_to_tile(&self, position: Vec2) -> IVec2 {
+        let x = position.x / TILE_SIZE + self.width as f32 * 0.5;
+        let y = self.height as f32 * 0.5 - position.y / TILE_SIZE;
+        IVec2::new(x.floor() as i32, y.floor() as i32)
+    }
+
+    pub fn tile_to_world(&self, tile: IVec2) -> Vec2 {
+        Vec2::new(
+            (tile.x as f32 + 0.5 - self.width as f32 * 0.5) * TILE_SIZE,
+            (self.height as f32 * 0.5 - tile.y as f32 - 0.5) * TILE_SIZE,
+        )
+    }
+
+    // Outside of the map counts as open ground, enemies spawn off screen and need to walk in
+    pub fn is_walkable(&self, position: Vec2, flying: bool) -> bool {
+        let tile = self.world_to_tile(position);
+        self.tile_at(tile.x, tile.y)
+            .is_none_or(|tile| tile.is_walkable(flying))
+    }
+
+    fn is_tile_walkable(&self, tile: IVec2) -> bool {
+        self.tile_at(tile.x, tile.y)
+            .is_some_and(|tile| tile.is_walkable(false))
+    }
+
+    fn distance(&self, tile: IVec2) -> Option<u32> {
+        self.tile_at(tile.x, tile.y)?;
+        self.distances[tile.y as usize * self.width + tile.x as usize]
+    }
+
+    // Breadth first flood fill out from the center tile, which gives every walkable tile the
+    // number of steps left to the altar. Walking downhill in that field is the path.
+    fn compute_distances(&self) -> Vec<Option<u32>> {
+        self.flood(&[])
+    }
+
+    fn flood(&self, blocked: &[IVec2]) -> Vec<Option<u32>> {
+        let walkable = |tile: IVec2| self.is_tile_walkable(tile) && !blocked.contains(&tile);
+        let mut distances = vec![None; self.tiles.len()];
+        let center = self.world_to_tile(Vec2::ZERO);
+        if !walkable(center) {
+            return distances;
+        }
+
+        let mut queue = VecDeque::from([center]);
+        distances[center.y as usize * self.width + center.x as usize] = Some(0);
+        while let Some(tile) = queue.pop_front() {
+            let distance = distances[tile.y as usize * self.width + tile.x as usize].unwrap();
+            for offset in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+                let neighbour = tile + offset;
+                if !walkable(neighbour) {
+                    continue;
+                }
+
+                let index = neighbour.y as usize * self.width + neighbour.x as usize;
+                if distances[index].is_none() {
+                    distances[index] = Some(distance + 1);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        distances
+    }
+
+    // Direction towards the neighbouring tile closest to the altar. None when there is nothing
+    // in the way to path around, then walking straight for origo is just as good.
+    pub fn direction_to_origo(&self, position: Vec2) -> Option<Vec2> {
+        let tile = self.world_to_tile(position);
+        let distance = self.distance(tile).filter(|distance| *distance > 0)?;
+
+        let mut best: Option<(IVec2, u32)> = None;
+        for y in -1..=1 {
+            for x in -1..=1 {
+                let offset = IVec2::new(x, y);
+                if offset == IVec2::ZERO {
+                    continue;
+                }
+
+                // Don't cut diagonally past the corner of a rock
+                if x != 0
+                    && y != 0
+                    && (!self.is_tile_walkable(tile + IVec2::new(x, 0))
+                        || !self.is_tile_walkable(tile + IVec2::new(0, y)))
+                {
+                    continue;
+                }
+
+                let Some(neighbour_distance) = self.distance(tile + offset) else {
+                    continue;
+                };
+                if neighbour_distance < distance
+                    && best.is_none_or(|(_, best_distance)| neighbour_distance < best_distance)
+                {
+                    best = Some((tile + offset, neighbour_distance));
+                }
+            }
+        }
+
+        let (next, _) = best?;
+        Some((self.tile_to_world(next) - position).normalize_or_zero())
+    }
+}
+
+#[derive(Debug)]
+pub enum TileMapError {
+    Io(std::io::Error),
+    Utf8(std::str::Utf8Error),
+    Empty,
+    UnevenRow(usize),
+    UnknownTile(char, usize, usize),
+}
+
+impl fmt::Display for TileMapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TileMapError::Io(error) => write!(f, "io error: {}", error),
+            TileMapError::Utf8(error) => write!(f, "map is not valid utf-8: {}", error),
+            TileMapError::Empty => write!(f, "map has no tiles"),
+            TileMapError::UnevenRow(row) => {
+                write!(f, "row {} is not as wide as the first row", row)
+            }
+            TileMapError::UnknownTile(c, x, y) => {
+                write!(f, "unknown tile '{}' at column {}, row {}", c, x, y)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TileMapError {}
+
+impl From<std::io::Error> for TileMapError {
+    fn from(error: std::io::Error) -> Self {
+        TileMapError::Io(error)
+    }
+}
+
+impl From<std::str::Utf8Error> for TileMapError {
+    fn from(error: std::str::Utf8Error) -> Self {
+        TileMapError::Utf8(error)
+    }
+}
+
+#[derive(Default)]
+pub struct TileMapLoader;
+
+impl AssetLoader for TileMapLoader {
+    type Asset = TileMap;
+    type Settings = ();
+    type Error = TileMapError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            TileMap::parse(std::str::from_utf8(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["map"]
+    }
+}
@@ -0,0 +1,29 @@
+use bevy::prelude::*;
+
+use crate::dark_arts_defense::AppState;
+use crate::meta::{progress, upgrade_menu};
+
+pub struct MetaPlugin;
+
+impl Plugin for MetaPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<progress::MetaProgress>()
+            .init_resource::<upgrade_menu::UpgradeMenu>()
+            .add_systems(Startup, progress::load_meta_progress_system)
+            .add_systems(
+                OnEnter(AppState::Upgrades),
+                upgrade_menu::spawn_upgrade_menu,
+            )
+            .add_systems(
+                OnExit(AppState::Upgrades),
+                upgrade_menu::despawn_upgrade_menu,
+            )
+            .add_systems(
+                Update,
+                (
+                    upgrade_menu::upgrade_menu_system.run_if(in_state(AppState::Upgrades)),
+                    progress::bank_run_souls,
+                ),
+            );
+    }
+}
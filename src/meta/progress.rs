@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::events::GameEvent;
+use crate::save::checkpoints::CheckpointSettings;
+use crate::save::snapshot::SaveError;
+use crate::stats::run_stats::RunStats;
+use crate::units::stat_modifiers::{Modifier, ModifierSource, Stat, StatModifiers};
+use crate::units::team::{CurrentTeam, Team};
+use crate::units::unit_types::{CurrentUnitType, UnitType};
+
+const META_FILE: &str = "meta.ron";
+const MANA_PER_RESERVE_RANK: u8 = 20;
+const SWIFT_CATS_SPEED: f32 = 1.1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Upgrade {
+    DeepReserves,
+    ImpPact,
+    SwiftCats,
+}
+
+pub struct UpgradeDefinition {
+    pub name: &'static str,
+    pub description: &'static str,
+    // Souls for every rank, the first one and every one after it
+    pub cost: u32,
+    pub max_rank: u32,
+    // Has to have at least one rank before this one can be bought
+    pub requires: Option<Upgrade>,
+}
+
+impl Upgrade {
+    pub const ALL: [Upgrade; 3] = [Upgrade::DeepReserves, Upgrade::ImpPact, Upgrade::SwiftCats];
+
+    pub fn definition(&self) -> UpgradeDefinition {
+        match self {
+            Upgrade::DeepReserves => UpgradeDefinition {
+                name: "Deep Reserves",
+                description: "Start every run with 20 more mana",
+                cost: 40,
+                max_rank: 3,
+                requires: None,
+            },
+            Upgrade::ImpPact => UpgradeDefinition {
+                name: "Imp Pact",
+                description: "Imps can be summoned",
+                cost: 60,
+                max_rank: 1,
+                requires: Some(Upgrade::DeepReserves),
+            },
+            Upgrade::SwiftCats => UpgradeDefinition {
+                name: "Swift Cats",
+                description: "Cats move 10% faster",
+                cost: 50,
+                max_rank: 1,
+                requires: Some(Upgrade::DeepReserves),
+            },
+        }
+    }
+
+    // The summons that stay locked in every run until this is bought
+    pub fn required_for(unit_type: UnitType) -> Option<Upgrade> {
+        match unit_type {
+            UnitType::Imp => Some(Upgrade::ImpPact),
+            _ => None,
+        }
+    }
+}
+
+// What carries over from one run to the next, kept next to the checkpoints
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct MetaProgress {
+    // Banked and not spent yet
+    pub souls: u32,
+    pub lifetime_souls: u32,
+    // What every run that ended so far brought in, oldest first
+    pub run_souls: Vec<u32>,
+    pub ranks: HashMap<Upgrade, u32>,
+}
+
+impl MetaProgress {
+    pub fn path(settings: &CheckpointSettings) -> PathBuf {
+        settings.directory.join(META_FILE)
+    }
+
+    pub fn read(path: &Path) -> Result<Self, SaveError> {
+        let contents = fs::read_to_string(path)?;
+        Ok(ron::from_str(&contents)?)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), SaveError> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    pub fn save(&self, settings: &CheckpointSettings) {
+        let path = Self::path(settings);
+        if let Err(error) = self.write(&path) {
+            warn!(
+                "Could not write meta progress {}: {}",
+                path.display(),
+                error
+            );
+        }
+    }
+
+    pub fn rank(&self, upgrade: Upgrade) -> u32 {
+        self.ranks.get(&upgrade).copied().unwrap_or(0)
+    }
+
+    pub fn can_buy(&self, upgrade: Upgrade) -> bool {
+        let definition = upgrade.definition();
+        self.rank(upgrade) < definition.max_rank
+            && self.souls >= definition.cost
+            && definition
+                .requires
+                .is_none_or(|required| self.rank(required) > 0)
+    }
+
+    pub fn buy(&mut self, upgrade: Upgrade) -> bool {
+        if !self.can_buy(upgrade) {
+            return false;
+        }
+
+        self.souls -= upgrade.definition().cost;
+        *self.ranks.entry(upgrade).or_insert(0) += 1;
+        true
+    }
+
+    pub fn bonus_mana(&self) -> u8 {
+        MANA_PER_RESERVE_RANK.saturating_mul(self.rank(Upgrade::DeepReserves) as u8)
+    }
+
+    pub fn is_unit_unlocked(&self, unit_type: UnitType) -> bool {
+        Upgrade::required_for(unit_type).is_none_or(|upgrade| self.rank(upgrade) > 0)
+    }
+}
+
+pub fn load_meta_progress_system(
+    settings: Res<CheckpointSettings>,
+    mut progress: ResMut<MetaProgress>,
+) {
+    let path = MetaProgress::path(&settings);
+    if !path.exists() {
+        return;
+    }
+
+    match MetaProgress::read(&path) {
+        Ok(loaded) => *progress = loaded,
+        Err(error) => warn!("Could not read meta progress {}: {}", path.display(), error),
+    }
+}
+
+// Whatever souls the raven brought in since the last time the run ended go in the bank, so a run
+// that's restarted from a checkpoint and ends again doesn't pay out the same souls twice
+pub fn bank_run_souls(
+    mut event_reader: EventReader<GameEvent>,
+    settings: Res<CheckpointSettings>,
+    mut stats: ResMut<RunStats>,
+    mut progress: ResMut<MetaProgress>,
+) {
+    for event in event_reader.read() {
+        let GameEvent::GameOver = event else {
+            continue;
+        };
+
+        let earned = stats.souls - stats.souls_banked;
+        stats.souls_banked = stats.souls;
+        progress.souls += earned;
+        progress.lifetime_souls += earned;
+        progress.run_souls.push(earned);
+        info!("Banked {} souls, {} to spend", earned, progress.souls);
+        progress.save(&settings);
+    }
+}
+
+pub fn apply_upgrade_modifiers(
+    progress: Res<MetaProgress>,
+    mut query: Query<(&mut StatModifiers, &CurrentTeam, &CurrentUnitType), Added<StatModifiers>>,
+) {
+    if progress.rank(Upgrade::SwiftCats) == 0 {
+        return;
+    }
+
+    for (mut modifiers, team, unit_type) in query.iter_mut() {
+        if team.0 == Team::Evil && unit_type.0 == UnitType::Cat {
+            modifiers.set(
+                ModifierSource::Upgrades,
+                &[Modifier::multiply(Stat::MoveSpeed, SWIFT_CATS_SPEED)],
+            );
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+
+use crate::dark_arts_defense::AppState;
+use crate::render_scale::UI_LAYER;
+use crate::save::checkpoints::CheckpointSettings;
+
+use super::progress::{MetaProgress, Upgrade};
+
+#[derive(Component)]
+pub struct UpgradeMenuText;
+
+#[derive(Resource, Default)]
+pub struct UpgradeMenu {
+    pub selected: usize,
+}
+
+pub fn spawn_upgrade_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+                    font_size: 40.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_justify(JustifyText::Center),
+            ..default()
+        },
+        UpgradeMenuText,
+        RenderLayers::layer(UI_LAYER),
+    ));
+}
+
+pub fn despawn_upgrade_menu(mut commands: Commands, query: Query<Entity, With<UpgradeMenuText>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// W/S or the arrow keys to pick an upgrade, SPACE or ENTER to buy a rank of it, U or ESCAPE to go
+// back to the level select
+pub fn upgrade_menu_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<CheckpointSettings>,
+    mut menu: ResMut<UpgradeMenu>,
+    mut progress: ResMut<MetaProgress>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut text_query: Query<&mut Text, With<UpgradeMenuText>>,
+) {
+    let count = Upgrade::ALL.len();
+    if keys.any_just_pressed([KeyCode::KeyW, KeyCode::ArrowUp]) {
+        menu.selected = (menu.selected + count - 1) % count;
+    }
+    if keys.any_just_pressed([KeyCode::KeyS, KeyCode::ArrowDown]) {
+        menu.selected = (menu.selected + 1) % count;
+    }
+    if keys.any_just_pressed([KeyCode::Space, KeyCode::Enter])
+        && progress.buy(Upgrade::ALL[menu.selected])
+    {
+        progress.save(&settings);
+    }
+    if keys.any_just_pressed([KeyCode::KeyU, KeyCode::Escape]) {
+        next_state.set(AppState::LevelSelect);
+    }
+
+    let mut lines = vec![
+        "Upgrades".to_owned(),
+        format!("{} souls", progress.souls),
+        String::new(),
+    ];
+    for (index, upgrade) in Upgrade::ALL.iter().enumerate() {
+        let definition = upgrade.definition();
+        let marker = if index == menu.selected { ">" } else { " " };
+        let rank = progress.rank(*upgrade);
+        let status = if rank >= definition.max_rank {
+            "owned".to_owned()
+        } else if let Some(required) = definition
+            .requires
+            .filter(|required| progress.rank(*required) == 0)
+        {
+            format!("needs {}", required.definition().name)
+        } else {
+            format!("{} souls", definition.cost)
+        };
+        lines.push(format!(
+            "{} {} {}/{} - {}",
+            marker, definition.name, rank, definition.max_rank, status
+        ));
+        lines.push(format!("  {}", definition.description));
+    }
+    lines.push(String::new());
+    lines.push("SPACE to buy, U to go back".to_owned());
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = lines.join("\n");
+    }
+}
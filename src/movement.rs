@@ -1,6 +1,173 @@
 use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::ai::sleep::Asleep;
+use crate::map::plugin::CurrentMap;
+use crate::map::tilemap::TileMap;
+use crate::player::plugin::Player;
+use crate::pool::Pooled;
+use crate::structures::structure_types::{BoneWall, Structure};
+use crate::units::flying::Flying;
+use crate::units::health::Health;
+use crate::velocity::Velocity;
+
+// Units closer than this start nudging each other apart, about shoulder to shoulder
+const SEPARATION_RADIUS: f32 = 28.0;
+const SEPARATION_WEIGHT: f32 = 0.8;
+// How far ahead a moving unit looks for walls and water to go around
+const AVOID_LOOKAHEAD: f32 = 48.0;
+const AVOID_WEIGHT: f32 = 1.2;
+// Half a unit, the same as walls and tiles push units out by
+const UNIT_RADIUS: f32 = 16.0;
 
 #[derive(Component, Default)]
 pub struct Movement {
     pub speed: f32,
 }
+
+// What keeps a unit out of the others' way and off walls, added on top of whatever its behavior
+// wants. Worked out fresh every step, so the behaviors never have to think about neighbours and
+// a crowd chasing the same knight fans out around it instead of stacking into one sprite.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Steering(pub Vec2);
+
+// Straight at it at full speed
+pub fn seek(from: Vec2, to: Vec2) -> Vec2 {
+    (to - from).normalize_or_zero()
+}
+
+// Straight at it, easing off within the slowing radius so it settles instead of overshooting
+pub fn arrive(from: Vec2, to: Vec2, slowing_radius: f32) -> Vec2 {
+    let to_target = to - from;
+    let distance = to_target.length();
+    if distance <= f32::EPSILON {
+        return Vec2::ZERO;
+    }
+    to_target / distance * (distance / slowing_radius).min(1.0)
+}
+
+// Units that are standing in the same spot, like a fresh batch out of the same portal, are
+// pushed apart by which of them is which so it comes out the same every run
+fn separation(position: Vec2, entity: Entity, neighbours: &[(Entity, Vec2)]) -> Vec2 {
+    let mut push = Vec2::ZERO;
+    for &(other, other_position) in neighbours {
+        if other == entity {
+            continue;
+        }
+        let away = position - other_position;
+        let distance = away.length();
+        if distance >= SEPARATION_RADIUS {
+            continue;
+        }
+        let direction = if distance > f32::EPSILON {
+            away / distance
+        } else if entity.index() < other.index() {
+            Vec2::X
+        } else {
+            Vec2::NEG_X
+        };
+        push += direction * (1.0 - distance / SEPARATION_RADIUS);
+    }
+    push
+}
+
+// Looks a little way down where it's going and turns aside from whatever is there, towards
+// whichever side of it the unit is already on
+fn avoidance(
+    position: Vec2,
+    velocity: Vec2,
+    flying: bool,
+    map: Option<&TileMap>,
+    walls: &[(Vec2, Vec2)],
+) -> Vec2 {
+    let direction = velocity.normalize_or_zero();
+    if direction == Vec2::ZERO {
+        return Vec2::ZERO;
+    }
+    let probe = position + direction * AVOID_LOOKAHEAD;
+
+    let obstacle = walls
+        .iter()
+        .find(|(center, half_extents)| {
+            let offset = (probe - *center).abs();
+            offset.x < half_extents.x + UNIT_RADIUS && offset.y < half_extents.y + UNIT_RADIUS
+        })
+        .map(|(center, _)| *center)
+        .or_else(|| {
+            map.filter(|map| !flying && !map.is_walkable(probe, false))
+                .map(|map| map.tile_to_world(map.world_to_tile(probe)))
+        });
+    let Some(obstacle) = obstacle else {
+        return Vec2::ZERO;
+    };
+
+    let side = direction.perp();
+    if side.dot(position - obstacle) < 0.0 {
+        -side
+    } else {
+        side
+    }
+}
+
+type NeighbourFilter = (With<Velocity>, Without<Pooled>);
+type SteeringData = (
+    Entity,
+    &'static Transform,
+    &'static Velocity,
+    &'static Health,
+    &'static mut Steering,
+    Has<Flying>,
+);
+
+pub fn steer(
+    maps: Res<Assets<TileMap>>,
+    current_map: Res<CurrentMap>,
+    wall_query: Query<(&Transform, &Structure, &Health), With<BoneWall>>,
+    // Sleeping units and the summoner are in the way too, they just don't step aside themselves
+    neighbour_query: Query<(Entity, &Transform, &Health), NeighbourFilter>,
+    mut query: Query<SteeringData, (Without<Asleep>, Without<Player>)>,
+) {
+    let map = current_map.get(&maps);
+    let walls: Vec<(Vec2, Vec2)> = wall_query
+        .iter()
+        .filter(|(_, _, health)| !health.is_dead())
+        .map(|(transform, structure, _)| {
+            (transform.translation.truncate(), structure.0.size() * 0.5)
+        })
+        .collect();
+
+    // Bucketed by the separation radius, so only the nine cells around a unit are looked at
+    let cell = |position: Vec2| (position / SEPARATION_RADIUS).floor().as_ivec2();
+    let mut grid: HashMap<IVec2, Vec<(Entity, Vec2)>> = HashMap::default();
+    for (entity, transform, health) in neighbour_query.iter() {
+        if health.is_dead() {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        grid.entry(cell(position))
+            .or_default()
+            .push((entity, position));
+    }
+
+    query.par_iter_mut().for_each(
+        |(entity, transform, velocity, health, mut steering, flying)| {
+            if health.is_dead() {
+                steering.0 = Vec2::ZERO;
+                return;
+            }
+            let position = transform.translation.truncate();
+            let center = cell(position);
+            let mut push = Vec2::ZERO;
+            for y in -1..=1 {
+                for x in -1..=1 {
+                    if let Some(neighbours) = grid.get(&(center + IVec2::new(x, y))) {
+                        push += separation(position, entity, neighbours);
+                    }
+                }
+            }
+
+            steering.0 = push * SEPARATION_WEIGHT
+                + avoidance(position, velocity.0, flying, map, &walls) * AVOID_WEIGHT;
+        },
+    );
+}
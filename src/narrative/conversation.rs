@@ -0,0 +1,337 @@
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy::sprite::Anchor;
+use bevy::text::Text2dBounds;
+
+use crate::enemies::enemy_spawner::EnemySpawner;
+use crate::events::GameEvent;
+use crate::game_mode::GameMode;
+use crate::gamestate::GameState;
+use crate::levels::definition::ActiveLevel;
+use crate::render_scale::{UiView, UI_LAYER};
+use crate::tutorial::TutorialState;
+
+use super::script::{Cue, Line, Scene, Story, StoryHandle};
+
+const CHARS_PER_SECOND: f32 = 45.0;
+// How long before a boss wave is due the warning about it is told
+const BOSS_CUE_SECONDS: f32 = 3.0;
+const BOX_HEIGHT: f32 = 220.0;
+const BOX_MAX_WIDTH: f32 = 1200.0;
+// Between the box and the bottom of the window, and between the box and what's in it
+const BOX_MARGIN: f32 = 24.0;
+const PORTRAIT_SIZE: f32 = 160.0;
+// The speaker's name sits on top, the line starts under it
+const SPEAKER_HEIGHT: f32 = 44.0;
+const BOX_COLOR: Color = Color::rgba(0.05, 0.03, 0.08, 0.9);
+const SPEAKER_COLOR: Color = Color::rgb(0.85, 0.8, 1.0);
+const HINT_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.5);
+// Over the hud, under the settings menu
+const CONVERSATION_Z: f32 = 40.0;
+
+// A scene being told, the run is held still until it's been read through or skipped
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ConversationState {
+    #[default]
+    Closed,
+    Open,
+}
+
+pub fn conversation_closed(state: Res<State<ConversationState>>) -> bool {
+    *state.get() == ConversationState::Closed
+}
+
+#[derive(Resource, Default)]
+pub struct Conversation {
+    lines: Vec<Line>,
+    index: usize,
+    // Characters of the current line typed out so far
+    revealed: f32,
+    // So a boss wave is only warned about once, even while its countdown is paused
+    boss_cued: Option<u32>,
+}
+
+impl Conversation {
+    fn start(&mut self, scene: &Scene) {
+        self.lines = scene.lines.clone();
+        self.index = 0;
+        self.revealed = 0.0;
+    }
+
+    fn line(&self) -> Option<&Line> {
+        self.lines.get(self.index)
+    }
+
+    fn fully_revealed(&self) -> bool {
+        self.line()
+            .is_none_or(|line| self.revealed >= line.text.chars().count() as f32)
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversationPart {
+    Box,
+    Portrait,
+    Speaker,
+    Body,
+    Hint,
+}
+
+fn play(
+    cue: Cue,
+    story: Option<&Story>,
+    conversation: &mut Conversation,
+    next_state: &mut NextState<ConversationState>,
+) {
+    let Some(scene) = story.and_then(|story| story.scene(cue)) else {
+        return;
+    };
+    conversation.start(scene);
+    next_state.set(ConversationState::Open);
+}
+
+// The start of a run, a boss on its way and the end of the run each have a scene of their own
+#[allow(clippy::too_many_arguments)]
+pub fn cue_conversations(
+    mut event_reader: EventReader<GameEvent>,
+    stories: Res<Assets<Story>>,
+    story_handle: Res<StoryHandle>,
+    (level, mode): (Res<ActiveLevel>, Res<GameMode>),
+    tutorial_state: Res<State<TutorialState>>,
+    mut conversation: ResMut<Conversation>,
+    mut next_state: ResMut<NextState<ConversationState>>,
+    spawner_query: Query<&EnemySpawner>,
+    game_state_query: Query<&GameState>,
+) {
+    let story = stories.get(&story_handle.0);
+    for event in event_reader.read() {
+        match event {
+            GameEvent::StartGame => {
+                conversation.boss_cued = None;
+                // The tutorial does its own talking
+                if *tutorial_state.get() == TutorialState::Off {
+                    play(Cue::RunStart, story, &mut conversation, &mut next_state);
+                }
+            }
+            GameEvent::RestartFromCheckpoint => conversation.boss_cued = None,
+            GameEvent::GameOver => {
+                let victory = game_state_query.iter().any(|state| state.victory);
+                let cue = if victory { Cue::Victory } else { Cue::Defeat };
+                play(cue, story, &mut conversation, &mut next_state);
+            }
+            GameEvent::IncreaseScore => {}
+        }
+    }
+
+    let game_over = game_state_query.iter().any(|state| state.game_over);
+    for spawner in spawner_query.iter() {
+        let wave = spawner.wave + 1;
+        let due = !spawner.wave_charge.is_paused()
+            && spawner.has_next_wave(&level, *mode)
+            && spawner.seconds_to_next_wave() <= BOSS_CUE_SECONDS;
+        if due
+            && !game_over
+            && level.waves.is_boss_wave(wave)
+            && conversation.boss_cued != Some(wave)
+        {
+            conversation.boss_cued = Some(wave);
+            play(Cue::BossWave, story, &mut conversation, &mut next_state);
+        }
+    }
+}
+
+pub fn spawn_conversation(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf");
+    let text = |size: f32, color: Color| {
+        Text::from_section(
+            "",
+            TextStyle {
+                font: font.clone(),
+                font_size: size,
+                color,
+            },
+        )
+    };
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: BOX_COLOR,
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, CONVERSATION_Z),
+            ..default()
+        },
+        ConversationPart::Box,
+        RenderLayers::layer(UI_LAYER),
+    ));
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(PORTRAIT_SIZE)),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        ConversationPart::Portrait,
+        RenderLayers::layer(UI_LAYER),
+    ));
+    for (part, size, color) in [
+        (ConversationPart::Speaker, 30.0, SPEAKER_COLOR),
+        (ConversationPart::Body, 28.0, Color::WHITE),
+        (ConversationPart::Hint, 20.0, HINT_COLOR),
+    ] {
+        let mut text = text(size, color);
+        if part == ConversationPart::Hint {
+            text.sections[0].value = "SPACE to continue, ESCAPE to skip".to_owned();
+        }
+        commands.spawn((
+            Text2dBundle {
+                text,
+                text_anchor: match part {
+                    ConversationPart::Hint => Anchor::BottomRight,
+                    _ => Anchor::TopLeft,
+                },
+                ..default()
+            },
+            part,
+            RenderLayers::layer(UI_LAYER),
+        ));
+    }
+}
+
+pub fn despawn_conversation(mut commands: Commands, query: Query<Entity, With<ConversationPart>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn close_conversation(mut next_state: ResMut<NextState<ConversationState>>) {
+    next_state.set(ConversationState::Closed);
+}
+
+// SPACE, ENTER or a click finishes typing the line out, then moves on to the next one. ESCAPE
+// skips the rest of the scene.
+pub fn advance_conversation(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    // The run's clock is paused while this is up
+    time: Res<Time<Real>>,
+    mut conversation: ResMut<Conversation>,
+    mut next_state: ResMut<NextState<ConversationState>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(ConversationState::Closed);
+        return;
+    }
+
+    let advance = keys.any_just_pressed([KeyCode::Space, KeyCode::Enter])
+        || mouse.just_pressed(MouseButton::Left);
+    if advance {
+        if conversation.fully_revealed() {
+            conversation.index += 1;
+            conversation.revealed = 0.0;
+        } else if let Some(line) = conversation.line() {
+            conversation.revealed = line.text.chars().count() as f32;
+        }
+    } else {
+        conversation.revealed += time.delta_seconds() * CHARS_PER_SECOND;
+    }
+
+    if conversation.line().is_none() {
+        next_state.set(ConversationState::Closed);
+    }
+}
+
+pub fn update_conversation_box(
+    asset_server: Res<AssetServer>,
+    conversation: Res<Conversation>,
+    ui_view: Res<UiView>,
+    mut transform_query: Query<(&ConversationPart, &mut Transform)>,
+    mut sprite_query: Query<(
+        &ConversationPart,
+        &mut Sprite,
+        &mut Handle<Image>,
+        &mut Visibility,
+    )>,
+    mut text_query: Query<(&ConversationPart, &mut Text, &mut Text2dBounds)>,
+) {
+    let Some(line) = conversation.line() else {
+        return;
+    };
+
+    let width = (ui_view.size().x - BOX_MARGIN * 2.0).min(BOX_MAX_WIDTH);
+    let center = Vec2::new(0.0, -ui_view.half_size.y + BOX_MARGIN + BOX_HEIGHT / 2.0);
+    let top_left =
+        center + Vec2::new(-width, BOX_HEIGHT) / 2.0 + Vec2::new(BOX_MARGIN, -BOX_MARGIN);
+    let text_left = if line.portrait.is_some() {
+        top_left.x + PORTRAIT_SIZE + BOX_MARGIN
+    } else {
+        top_left.x
+    };
+
+    for (part, mut transform) in transform_query.iter_mut() {
+        let position = match part {
+            ConversationPart::Box => center,
+            ConversationPart::Portrait => top_left + Vec2::new(PORTRAIT_SIZE, -PORTRAIT_SIZE) / 2.0,
+            ConversationPart::Speaker => Vec2::new(text_left, top_left.y),
+            ConversationPart::Body => Vec2::new(text_left, top_left.y - SPEAKER_HEIGHT),
+            ConversationPart::Hint => {
+                center + Vec2::new(width, -BOX_HEIGHT) / 2.0 + Vec2::new(-BOX_MARGIN, BOX_MARGIN)
+            }
+        };
+        let z = CONVERSATION_Z
+            + if *part == ConversationPart::Box {
+                0.0
+            } else {
+                1.0
+            };
+        transform.translation = position.extend(z);
+    }
+
+    for (part, mut sprite, mut image, mut visibility) in sprite_query.iter_mut() {
+        match part {
+            ConversationPart::Box => {
+                let size = Some(Vec2::new(width, BOX_HEIGHT));
+                if sprite.custom_size != size {
+                    sprite.custom_size = size;
+                }
+            }
+            ConversationPart::Portrait => match &line.portrait {
+                Some(portrait) => {
+                    let handle = asset_server.load(&portrait.image);
+                    if *image != handle {
+                        *image = handle;
+                        sprite.rect = Some(portrait.rect());
+                    }
+                    *visibility = Visibility::Visible;
+                }
+                None => *visibility = Visibility::Hidden,
+            },
+            _ => {}
+        }
+    }
+
+    let body = line
+        .text
+        .chars()
+        .take(conversation.revealed as usize)
+        .collect::<String>();
+    let text_width = width - (text_left - top_left.x) - BOX_MARGIN * 2.0;
+    for (part, mut text, mut bounds) in text_query.iter_mut() {
+        let value = match part {
+            ConversationPart::Speaker => &line.speaker,
+            ConversationPart::Body => {
+                if bounds.size.x != text_width {
+                    bounds.size.x = text_width;
+                }
+                &body
+            }
+            _ => continue,
+        };
+        if text.sections[0].value != *value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+
+use crate::dark_arts_defense::AppState;
+use crate::narrative::{conversation, script};
+use crate::schedule::FrameSet;
+use crate::settings::menu::settings_closed;
+
+pub struct NarrativePlugin;
+
+impl Plugin for NarrativePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<script::Story>()
+            .init_asset_loader::<script::StoryLoader>()
+            .init_resource::<script::StoryHandle>()
+            .init_resource::<conversation::Conversation>()
+            .init_state::<conversation::ConversationState>()
+            // The keys that move the conversation along shouldn't also play the game
+            .configure_sets(
+                Update,
+                FrameSet::Input.run_if(conversation::conversation_closed),
+            )
+            .add_systems(Startup, script::load_story_system)
+            .add_systems(
+                OnEnter(conversation::ConversationState::Open),
+                conversation::spawn_conversation,
+            )
+            .add_systems(
+                OnExit(conversation::ConversationState::Open),
+                conversation::despawn_conversation,
+            )
+            .add_systems(OnExit(AppState::Playing), conversation::close_conversation)
+            .add_systems(
+                Update,
+                (
+                    conversation::cue_conversations.run_if(in_state(AppState::Playing)),
+                    (
+                        conversation::advance_conversation.run_if(settings_closed),
+                        conversation::update_conversation_box,
+                    )
+                        .chain()
+                        .run_if(in_state(conversation::ConversationState::Open)),
+                ),
+            );
+    }
+}
@@ -0,0 +1,124 @@
+use std::fmt;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::BoxedFuture;
+use serde::{Deserialize, Serialize};
+
+// The one story every run tells, whichever arena it's on
+pub const STORY_PATH: &str = "story/dark_arts.story.ron";
+
+// When in a run a scene is played
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Cue {
+    RunStart,
+    // A few seconds before a wave with a boss in it
+    BossWave,
+    Defeat,
+    Victory,
+}
+
+// The first frame of a sprite sheet, the sheets have every frame side by side
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Portrait {
+    pub image: String,
+    pub frame_size: (u32, u32),
+}
+
+impl Portrait {
+    pub fn rect(&self) -> Rect {
+        Rect::new(0.0, 0.0, self.frame_size.0 as f32, self.frame_size.1 as f32)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Line {
+    pub speaker: String,
+    #[serde(default)]
+    pub portrait: Option<Portrait>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scene {
+    pub cue: Cue,
+    pub lines: Vec<Line>,
+}
+
+// Looks like:
+// (scenes: [(cue: RunStart, lines: [(speaker: "The Summoner", text: "Rise")])])
+#[derive(Asset, TypePath, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Story {
+    pub scenes: Vec<Scene>,
+}
+
+impl Story {
+    pub fn scene(&self, cue: Cue) -> Option<&Scene> {
+        self.scenes
+            .iter()
+            .find(|scene| scene.cue == cue && !scene.lines.is_empty())
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct StoryHandle(pub Handle<Story>);
+
+pub fn load_story_system(asset_server: Res<AssetServer>, mut story: ResMut<StoryHandle>) {
+    story.0 = asset_server.load(STORY_PATH);
+}
+
+#[derive(Default)]
+pub struct StoryLoader;
+
+impl AssetLoader for StoryLoader {
+    type Asset = Story;
+    type Settings = ();
+    type Error = StoryError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["story.ron"]
+    }
+}
+
+#[derive(Debug)]
+pub enum StoryError {
+    Io(std::io::Error),
+    Deserialize(ron::error::SpannedError),
+}
+
+impl fmt::Display for StoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoryError::Io(error) => write!(f, "io error: {}", error),
+            StoryError::Deserialize(error) => write!(f, "could not read story: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for StoryError {}
+
+impl From<std::io::Error> for StoryError {
+    fn from(error: std::io::Error) -> Self {
+        StoryError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for StoryError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        StoryError::Deserialize(error)
+    }
+}
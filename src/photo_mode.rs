@@ -0,0 +1,218 @@
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::render::view::RenderLayers;
+use bevy::window::PrimaryWindow;
+
+use crate::camera::{self, CameraController};
+use crate::capture::screenshot::save_screenshot;
+use crate::dark_arts_defense::AppState;
+use crate::narrative::conversation::conversation_closed;
+use crate::render_scale::{UiCamera, UiView, PHOTO_LAYER, UI_LAYER};
+use crate::save::checkpoints::CheckpointSettings;
+use crate::schedule::FrameSet;
+use crate::settings::bindings::{key_name, Binding};
+use crate::settings::config::GameSettings;
+use crate::settings::menu::settings_closed;
+use crate::ui::nameplate::not_renaming;
+
+pub const PHOTO_MODE_KEY: KeyCode = KeyCode::F10;
+const CAPTURE_KEY: KeyCode = KeyCode::Enter;
+// Further in and further out than a run lets the camera go
+const MIN_PHOTO_ZOOM: f32 = 0.2;
+const MAX_PHOTO_ZOOM: f32 = 2.0;
+const ZOOM_PER_LINE: f32 = 0.05;
+const PIXELS_PER_LINE: f32 = 40.0;
+// World units per second at a zoom of 1, slower the closer in it is
+const PAN_SPEED: f32 = 800.0;
+// Long enough to read, then it's out of the way of the shot
+const HINT_SECONDS: f32 = 4.0;
+const HINT_OFFSET_BOTTOM: f32 = 0.9;
+
+// Holds the run still and lets the camera go anywhere, with nothing but the world on screen
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum PhotoModeState {
+    #[default]
+    Off,
+    On,
+}
+
+pub fn photo_mode_off(state: Res<State<PhotoModeState>>) -> bool {
+    *state.get() == PhotoModeState::Off
+}
+
+#[derive(Component)]
+pub struct PhotoHint {
+    timer: Timer,
+}
+
+// F10 during a run, and again to get back to it
+pub fn toggle_photo_mode(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<PhotoModeState>>,
+    mut next_state: ResMut<NextState<PhotoModeState>>,
+) {
+    if !keys.just_pressed(PHOTO_MODE_KEY) {
+        return;
+    }
+    next_state.set(match state.get() {
+        PhotoModeState::Off => PhotoModeState::On,
+        PhotoModeState::On => PhotoModeState::Off,
+    });
+}
+
+pub fn enter_photo_mode(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<GameSettings>,
+    mut camera_query: Query<&mut RenderLayers, With<UiCamera>>,
+) {
+    // The world view is on this layer as well, everything else the ui draws isn't
+    for mut layers in camera_query.iter_mut() {
+        *layers = RenderLayers::layer(PHOTO_LAYER);
+    }
+
+    let pan = Binding::MOVEMENT
+        .map(|binding| key_name(settings.bindings.key(binding)))
+        .join(" ");
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                format!(
+                    "Photo mode: {} to pan, scroll to zoom, {} to save a screenshot, {} to leave",
+                    pan,
+                    key_name(CAPTURE_KEY),
+                    key_name(PHOTO_MODE_KEY)
+                ),
+                TextStyle {
+                    font: asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_justify(JustifyText::Center),
+            ..default()
+        },
+        PhotoHint {
+            timer: Timer::from_seconds(HINT_SECONDS, TimerMode::Once),
+        },
+        RenderLayers::layer(PHOTO_LAYER),
+    ));
+}
+
+// The camera eases back onto the summoner and its old zoom from wherever it was left
+pub fn leave_photo_mode(
+    mut commands: Commands,
+    hint_query: Query<Entity, With<PhotoHint>>,
+    mut camera_query: Query<&mut RenderLayers, With<UiCamera>>,
+) {
+    for entity in hint_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for mut layers in camera_query.iter_mut() {
+        *layers = RenderLayers::layer(UI_LAYER);
+    }
+}
+
+pub fn close_photo_mode(mut next_state: ResMut<NextState<PhotoModeState>>) {
+    next_state.set(PhotoModeState::Off);
+}
+
+// The run's clock is paused, so this moves on real time
+pub fn free_camera(
+    time: Res<Time<Real>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<GameSettings>,
+    mut wheel_reader: EventReader<MouseWheel>,
+    mut camera_query: Query<(&mut CameraController, &mut OrthographicProjection)>,
+) {
+    let zoom_delta: f32 = wheel_reader
+        .read()
+        .map(|wheel| match wheel.unit {
+            MouseScrollUnit::Line => -wheel.y * ZOOM_PER_LINE,
+            MouseScrollUnit::Pixel => -wheel.y / PIXELS_PER_LINE * ZOOM_PER_LINE,
+        })
+        .sum();
+
+    let mut direction = Vec2::ZERO;
+    for (binding, step) in
+        Binding::MOVEMENT
+            .into_iter()
+            .zip([Vec2::Y, Vec2::NEG_X, Vec2::NEG_Y, Vec2::X])
+    {
+        if settings.bindings.pressed(&keys, binding) {
+            direction += step;
+        }
+    }
+
+    for (mut controller, mut projection) in camera_query.iter_mut() {
+        controller.zoom = (controller.zoom + zoom_delta).clamp(MIN_PHOTO_ZOOM, MAX_PHOTO_ZOOM);
+        let pan = direction.normalize_or_zero() * PAN_SPEED * controller.zoom;
+        controller.position += pan * time.delta_seconds();
+        if projection.scale != controller.zoom {
+            projection.scale = controller.zoom;
+        }
+    }
+}
+
+// In the captures folder with the F12 screenshots
+pub fn capture_photo(
+    keys: Res<ButtonInput<KeyCode>>,
+    checkpoint_settings: Res<CheckpointSettings>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+    mut hint_query: Query<&mut Visibility, With<PhotoHint>>,
+) {
+    if !keys.just_pressed(CAPTURE_KEY) {
+        return;
+    }
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    save_screenshot(&checkpoint_settings, "photo", &mut screenshots, window);
+
+    // Out of the shot, it's the same frame the screenshot is taken of
+    for mut visibility in hint_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+pub fn update_photo_hint(
+    time: Res<Time<Real>>,
+    ui_view: Res<UiView>,
+    mut query: Query<(&mut PhotoHint, &mut Visibility, &mut Transform)>,
+) {
+    for (mut hint, mut visibility, mut transform) in query.iter_mut() {
+        if hint.timer.tick(time.delta()).finished() {
+            *visibility = Visibility::Hidden;
+        }
+        transform.translation = Vec3::new(0.0, -ui_view.half_size.y * HINT_OFFSET_BOTTOM, 0.0);
+    }
+}
+
+pub struct PhotoModePlugin;
+
+impl Plugin for PhotoModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<PhotoModeState>()
+            .configure_sets(Update, FrameSet::Input.run_if(photo_mode_off))
+            .add_systems(OnEnter(PhotoModeState::On), enter_photo_mode)
+            .add_systems(OnExit(PhotoModeState::On), leave_photo_mode)
+            .add_systems(OnExit(AppState::Playing), close_photo_mode)
+            .add_systems(
+                Update,
+                (
+                    toggle_photo_mode
+                        .run_if(in_state(AppState::Playing))
+                        .run_if(settings_closed)
+                        .run_if(conversation_closed)
+                        .run_if(not_renaming),
+                    (update_photo_hint, capture_photo, free_camera)
+                        .chain()
+                        .run_if(in_state(PhotoModeState::On))
+                        .in_set(FrameSet::Presentation)
+                        .before(camera::follow_player),
+                ),
+            );
+    }
+}
@@ -0,0 +1,238 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::events::{Heal, PickupCollected, UnitDied};
+use crate::gamestate::Cleanup;
+use crate::mana::Mana;
+use crate::player::plugin::Player;
+use crate::rng::GameRng;
+use crate::units::stat_modifiers::{Modifier, ModifierSource, Stat, StatModifiers};
+use crate::units::team::{AllianceMatrix, Team};
+
+const PICKUP_SIZE: f32 = 10.0;
+const PICKUP_Z: f32 = 2.0;
+// Dropped a little off to the side, so it doesn't sit right on top of the soul
+const PICKUP_OFFSET: Vec2 = Vec2::new(10.0, -6.0);
+const PICKUP_LIFETIME: f32 = 15.0;
+// Blinks for this long before it's gone, faster the closer it gets
+const EXPIRY_WARNING: f32 = 4.0;
+const BLINK_SPEED: f32 = 3.0;
+// Once the summoner is this close it drifts over, picking up speed the closer they are
+const MAGNET_RADIUS: f32 = 140.0;
+const MAGNET_MIN_SPEED: f32 = 60.0;
+const MAGNET_MAX_SPEED: f32 = 420.0;
+const COLLECT_DISTANCE: f32 = 16.0;
+
+const ORB_MANA: u8 = 10;
+const HEAL_AMOUNT: i32 = 15;
+const HASTE_SECONDS: f32 = 6.0;
+const HASTE_MOVE_SPEED: f32 = 1.4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickupKind {
+    ManaOrb,
+    Heal,
+    Haste,
+}
+
+impl PickupKind {
+    // Checked in order against a single roll, so a kill drops one thing at most
+    pub const DROP_TABLE: [(PickupKind, f32); 3] = [
+        (PickupKind::ManaOrb, 0.12),
+        (PickupKind::Heal, 0.05),
+        (PickupKind::Haste, 0.03),
+    ];
+
+    pub fn roll(rng: &mut GameRng) -> Option<Self> {
+        let mut roll = rng.0.gen::<f32>();
+        for (kind, chance) in Self::DROP_TABLE {
+            if roll < chance {
+                return Some(kind);
+            }
+            roll -= chance;
+        }
+        None
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PickupKind::ManaOrb => "Mana Orb",
+            PickupKind::Heal => "Heal",
+            PickupKind::Haste => "Haste",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            PickupKind::ManaOrb => Color::rgb(0.3, 0.45, 1.0),
+            PickupKind::Heal => Color::rgb(0.3, 0.9, 0.35),
+            PickupKind::Haste => Color::rgb(1.0, 0.85, 0.2),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    pub lifetime: Timer,
+}
+
+// The summoner is quicker on their feet for a while
+#[derive(Component)]
+pub struct Haste {
+    pub timer: Timer,
+}
+
+// Only what the summoner's side kills drops anything, rogues and wildlife included
+pub fn drop_pickups(
+    mut commands: Commands,
+    alliances: Res<AllianceMatrix>,
+    mut rng: ResMut<GameRng>,
+    mut died_reader: EventReader<UnitDied>,
+) {
+    for died in died_reader.read() {
+        if !alliances.is_hostile(Team::Evil, died.team) {
+            continue;
+        }
+        let Some(kind) = PickupKind::roll(&mut rng) else {
+            continue;
+        };
+
+        spawn_pickup(&mut commands, kind, died.position + PICKUP_OFFSET);
+    }
+}
+
+pub fn spawn_pickup(commands: &mut Commands, kind: PickupKind, position: Vec2) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: kind.color(),
+                custom_size: Some(Vec2::splat(PICKUP_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(PICKUP_Z))
+                .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+            ..default()
+        },
+        Pickup {
+            kind,
+            lifetime: Timer::from_seconds(PICKUP_LIFETIME, TimerMode::Once),
+        },
+        Cleanup,
+    ));
+}
+
+pub fn expire_pickups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Pickup, &mut Visibility)>,
+) {
+    for (entity, mut pickup, mut visibility) in query.iter_mut() {
+        if pickup.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let remaining = pickup.lifetime.remaining_secs();
+        if remaining > EXPIRY_WARNING {
+            continue;
+        }
+        let blinks = BLINK_SPEED * (1.0 + (EXPIRY_WARNING - remaining));
+        *visibility = if (remaining * blinks).fract() < 0.5 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+pub fn attract_pickups(
+    mut commands: Commands,
+    time: Res<Time>,
+    player_query: Query<&Transform, With<Player>>,
+    mut pickup_query: Query<(Entity, &Pickup, &mut Transform), Without<Player>>,
+    mut collected_writer: EventWriter<PickupCollected>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_position = player_transform.translation.truncate();
+
+    for (entity, pickup, mut transform) in pickup_query.iter_mut() {
+        let position = transform.translation.truncate();
+        let distance = position.distance(player_position);
+        if distance <= COLLECT_DISTANCE {
+            commands.entity(entity).despawn_recursive();
+            collected_writer.send(PickupCollected {
+                kind: pickup.kind,
+                position,
+            });
+            continue;
+        }
+        if distance > MAGNET_RADIUS {
+            continue;
+        }
+
+        let pull = 1.0 - distance / MAGNET_RADIUS;
+        let speed = MAGNET_MIN_SPEED + (MAGNET_MAX_SPEED - MAGNET_MIN_SPEED) * pull;
+        let step = (speed * time.delta_seconds()).min(distance);
+        let position = position + (player_position - position).normalize() * step;
+        transform.translation = position.extend(PICKUP_Z);
+    }
+}
+
+pub fn apply_pickups(
+    mut commands: Commands,
+    mut collected_reader: EventReader<PickupCollected>,
+    mut player_query: Query<(Entity, &mut Mana), With<Player>>,
+    mut heal_writer: EventWriter<Heal>,
+) {
+    let Ok((player, mut mana)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    for collected in collected_reader.read() {
+        match collected.kind {
+            PickupKind::ManaOrb => {
+                mana.current_mana = mana
+                    .current_mana
+                    .saturating_add(ORB_MANA)
+                    .min(mana.max_mana);
+            }
+            PickupKind::Heal => {
+                heal_writer.send(Heal {
+                    target: player,
+                    amount: HEAL_AMOUNT,
+                    source: None,
+                });
+            }
+            // Another one while it's still going starts it over rather than stacking
+            PickupKind::Haste => {
+                commands.entity(player).insert(Haste {
+                    timer: Timer::from_seconds(HASTE_SECONDS, TimerMode::Once),
+                });
+            }
+        }
+    }
+}
+
+pub fn apply_haste(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Haste, &mut StatModifiers)>,
+) {
+    for (entity, mut haste, mut modifiers) in query.iter_mut() {
+        if haste.timer.tick(time.delta()).finished() {
+            modifiers.remove(ModifierSource::Haste);
+            commands.entity(entity).remove::<Haste>();
+            continue;
+        }
+
+        if !modifiers.has(ModifierSource::Haste) {
+            modifiers.set(
+                ModifierSource::Haste,
+                &[Modifier::multiply(Stat::MoveSpeed, HASTE_MOVE_SPEED)],
+            );
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use bevy::prelude::*;
+
+use crate::pickups::drops;
+use crate::schedule::FrameSet;
+
+pub struct PickupsPlugin;
+
+impl Plugin for PickupsPlugin {
+    fn build(&self, app: &mut App) {
+        // Pulled towards wherever the summoner ended up this frame
+        app.add_systems(
+            Update,
+            (
+                drops::drop_pickups,
+                drops::expire_pickups,
+                drops::attract_pickups,
+                drops::apply_pickups,
+                drops::apply_haste,
+            )
+                .chain()
+                .after(FrameSet::Movement),
+        );
+    }
+}
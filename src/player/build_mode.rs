@@ -0,0 +1,171 @@
+use bevy::prelude::*;
+
+use crate::difficulty::Difficulty;
+use crate::game_view::GameAction;
+use crate::mana::Mana;
+use crate::map::plugin::CurrentMap;
+use crate::map::tilemap::TileMap;
+use crate::player::plugin::Player;
+use crate::render_scale::{cursor_to_world, WorldCamera};
+use crate::settings::bindings::Binding;
+use crate::settings::config::GameSettings;
+use crate::structures::structure_types::{snap_to_grid, spawn_structure, Structure, StructureType};
+use crate::units::team::Team;
+
+const BUILD_RANGE: f32 = 320.0;
+
+#[derive(Resource)]
+pub struct BuildMode {
+    pub active: bool,
+    pub selected: StructureType,
+    pub cursor_cell: Option<Vec2>,
+}
+
+impl Default for BuildMode {
+    fn default() -> Self {
+        Self {
+            active: false,
+            selected: StructureType::BoneWall,
+            cursor_cell: None,
+        }
+    }
+}
+
+pub fn not_building(build_mode: Res<BuildMode>) -> bool {
+    !build_mode.active
+}
+
+// Shared between the preview and the actual placement, so the preview never lies
+fn can_place(
+    cost: u8,
+    cell: Vec2,
+    mana: &Mana,
+    player_position: Vec2,
+    structures_query: &Query<&Transform, With<Structure>>,
+    map: Option<&TileMap>,
+) -> bool {
+    mana.current_mana >= cost
+        && (cell - player_position).length() <= BUILD_RANGE
+        && map.is_none_or(|map| map.is_walkable(cell, false))
+        && !structures_query
+            .iter()
+            .any(|transform| transform.translation.truncate() == cell)
+}
+
+pub fn system(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<GameSettings>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut build_mode: ResMut<BuildMode>,
+    window_query: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<WorldCamera>>,
+    mut actions: EventWriter<GameAction>,
+) {
+    if settings.bindings.just_pressed(&keys, Binding::BuildMode) {
+        build_mode.active = !build_mode.active;
+    }
+
+    if !build_mode.active {
+        build_mode.cursor_cell = None;
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Escape) || mouse.just_pressed(MouseButton::Right) {
+        build_mode.active = false;
+        return;
+    }
+
+    let structure_binds = [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3];
+    for (key, structure_type) in structure_binds.iter().zip(StructureType::ALL) {
+        if keys.just_pressed(*key) {
+            build_mode.selected = structure_type;
+        }
+    }
+
+    let window = window_query.single();
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+    build_mode.cursor_cell = cursor_to_world(window, camera, camera_transform).map(snap_to_grid);
+
+    if let Some(cell) = build_mode.cursor_cell {
+        if mouse.just_pressed(MouseButton::Left) {
+            actions.send(GameAction::Build(build_mode.selected, cell));
+        }
+    }
+}
+
+pub fn apply_build_actions(
+    mut commands: Commands,
+    mut actions: EventReader<GameAction>,
+    mut player_query: Query<(&mut Mana, &Transform), With<Player>>,
+    structures_query: Query<&Transform, With<Structure>>,
+    maps: Res<Assets<TileMap>>,
+    current_map: Res<CurrentMap>,
+    difficulty: Res<Difficulty>,
+) {
+    for action in actions.read() {
+        let GameAction::Build(structure_type, position) = action else {
+            continue;
+        };
+
+        let Ok((mut mana, player_transform)) = player_query.get_single_mut() else {
+            continue;
+        };
+
+        let cell = snap_to_grid(*position);
+        let player_position = player_transform.translation.truncate();
+        let cost = difficulty.scale_cost(structure_type.cost());
+        if !can_place(
+            cost,
+            cell,
+            &mana,
+            player_position,
+            &structures_query,
+            current_map.get(&maps),
+        ) {
+            continue;
+        }
+
+        spawn_structure(&mut commands, *structure_type, Team::Evil, cell);
+        mana.current_mana -= cost;
+    }
+}
+
+pub fn draw_build_preview(
+    mut gizmos: Gizmos,
+    build_mode: Res<BuildMode>,
+    player_query: Query<(&Mana, &Transform), With<Player>>,
+    structures_query: Query<&Transform, With<Structure>>,
+    maps: Res<Assets<TileMap>>,
+    current_map: Res<CurrentMap>,
+    difficulty: Res<Difficulty>,
+) {
+    let Some(cell) = build_mode.cursor_cell else {
+        return;
+    };
+    let Ok((mana, player_transform)) = player_query.get_single() else {
+        return;
+    };
+
+    let player_position = player_transform.translation.truncate();
+    let color = if can_place(
+        difficulty.scale_cost(build_mode.selected.cost()),
+        cell,
+        mana,
+        player_position,
+        &structures_query,
+        current_map.get(&maps),
+    ) {
+        Color::GREEN
+    } else {
+        Color::RED
+    };
+
+    gizmos.rect_2d(cell, 0.0, build_mode.selected.size(), color);
+    gizmos.circle_2d(
+        player_position,
+        BUILD_RANGE,
+        Color::rgba(1.0, 1.0, 1.0, 0.15),
+    );
+}
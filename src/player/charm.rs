@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+
+use crate::ai::behavior::Convertible;
+use crate::events::{Convert, SpellCast};
+use crate::game_view::GameAction;
+use crate::mana::Mana;
+use crate::player::plugin::Player;
+use crate::settings::bindings::Binding;
+use crate::settings::config::GameSettings;
+use crate::units::team::{CurrentTeam, Team};
+
+const CHARM_COST: u8 = 25;
+const CHARM_RADIUS: f32 = 200.0;
+
+pub fn system(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<GameSettings>,
+    mut actions: EventWriter<GameAction>,
+) {
+    if settings.bindings.just_pressed(&keys, Binding::Charm) {
+        actions.send(GameAction::Charm);
+    }
+}
+
+// Wins over every neutral unit around the player, mana is only spent if something was charmed
+pub fn apply_charm_actions(
+    mut actions: EventReader<GameAction>,
+    mut player_query: Query<(&mut Mana, &Transform), With<Player>>,
+    convertible_query: Query<(Entity, &Transform, &CurrentTeam), With<Convertible>>,
+    mut convert_writer: EventWriter<Convert>,
+    mut cast_writer: EventWriter<SpellCast>,
+) {
+    for action in actions.read() {
+        let GameAction::Charm = action else {
+            continue;
+        };
+
+        let Ok((mut mana, player_transform)) = player_query.get_single_mut() else {
+            continue;
+        };
+
+        if mana.current_mana < CHARM_COST {
+            continue;
+        }
+
+        let player_position = player_transform.translation.truncate();
+        let mut charmed = 0;
+        for (entity, transform, team) in convertible_query.iter() {
+            let distance = (transform.translation.truncate() - player_position).length();
+            if team.0 != Team::Neutral || distance > CHARM_RADIUS {
+                continue;
+            }
+
+            convert_writer.send(Convert {
+                target: entity,
+                team: Team::Evil,
+            });
+            charmed += 1;
+        }
+
+        if charmed > 0 {
+            mana.current_mana -= CHARM_COST;
+            cast_writer.send(SpellCast { power: CHARM_COST });
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use bevy::prelude::*;
+
+use crate::ai::behavior::{GuardAssignment, GuardBehavior};
+use crate::events::GameEvent;
+use crate::game_view::GameAction;
+use crate::player::plugin::Player;
+use crate::render_scale::{cursor_to_world, WorldCamera};
+use crate::settings::bindings::Binding;
+use crate::settings::config::GameSettings;
+use crate::structures::structure_types::Structure;
+use crate::units::altar::DarkAltar;
+use crate::units::team::{CurrentTeam, Team};
+
+const RALLY_MARKER_RADIUS: f32 = 20.0;
+const RALLY_COLOR: Color = Color::rgb(0.7, 0.3, 1.0);
+// Clicking this close to the altar, the summoner or a structure guards it instead of rallying
+const GUARD_PICK_RADIUS: f32 = 48.0;
+// Only the summons around the summoner when the order is given are sent off to guard
+const GUARD_ORDER_RADIUS: f32 = 400.0;
+
+#[derive(Resource, Default)]
+pub struct CommandMode {
+    pub active: bool,
+}
+
+pub fn not_commanding(command_mode: Res<CommandMode>) -> bool {
+    !command_mode.active
+}
+
+// Where the summons gather when there is nothing to fight, None lets them wander like before
+#[derive(Resource, Default)]
+pub struct RallyPoint(pub Option<Vec2>);
+
+// Or the waypoints they walk round and round, empty while there's no patrol. Guarding is handed
+// to each summon on its own as a GuardAssignment.
+#[derive(Resource, Default)]
+pub struct PatrolRoute(pub Vec<Vec2>);
+
+type GuardableFilter = Or<(With<DarkAltar>, With<Player>, With<Structure>)>;
+
+// G toggles command mode, left click sets the rally point or picks what to guard, shift click
+// adds a waypoint to the patrol and right click calls any of them off
+#[allow(clippy::too_many_arguments)]
+pub fn system(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<GameSettings>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut command_mode: ResMut<CommandMode>,
+    window_query: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<WorldCamera>>,
+    guardable_query: Query<(Entity, &Transform), GuardableFilter>,
+    mut actions: EventWriter<GameAction>,
+) {
+    if settings.bindings.just_pressed(&keys, Binding::CommandMode) {
+        command_mode.active = !command_mode.active;
+    }
+
+    if !command_mode.active {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        command_mode.active = false;
+        return;
+    }
+
+    if mouse.just_pressed(MouseButton::Right) {
+        actions.send(GameAction::Rally(None));
+        command_mode.active = false;
+        return;
+    }
+
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let window = window_query.single();
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+    let Some(position) = cursor_to_world(window, camera, camera_transform) else {
+        return;
+    };
+    if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        actions.send(GameAction::Waypoint(position));
+        return;
+    }
+    let guarded = guardable_query
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation.truncate().distance(position)))
+        .filter(|(_, distance)| *distance < GUARD_PICK_RADIUS)
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+    match guarded {
+        Some((entity, _)) => actions.send(GameAction::Guard(Some(entity))),
+        None => actions.send(GameAction::Rally(Some(position))),
+    };
+}
+
+// Only ever one of the orders is given, a rally or a patrol calls every guard off their post
+pub fn apply_rally_actions(
+    mut commands: Commands,
+    mut actions: EventReader<GameAction>,
+    mut rally_point: ResMut<RallyPoint>,
+    mut patrol_route: ResMut<PatrolRoute>,
+    player_query: Query<&Transform, With<Player>>,
+    guards_query: Query<(Entity, &Transform, &CurrentTeam), With<GuardBehavior>>,
+    assigned_query: Query<Entity, With<GuardAssignment>>,
+) {
+    for action in actions.read() {
+        match action {
+            GameAction::Rally(position) => {
+                rally_point.0 = *position;
+                patrol_route.0.clear();
+            }
+            GameAction::Guard(_) => {
+                rally_point.0 = None;
+                patrol_route.0.clear();
+            }
+            GameAction::Waypoint(position) => {
+                patrol_route.0.push(*position);
+                rally_point.0 = None;
+            }
+            _ => continue,
+        }
+
+        for entity in assigned_query.iter() {
+            commands.entity(entity).remove::<GuardAssignment>();
+        }
+        let GameAction::Guard(Some(target)) = action else {
+            continue;
+        };
+        let Ok(player_transform) = player_query.get_single() else {
+            continue;
+        };
+        let player_position = player_transform.translation.truncate();
+        for (entity, transform, team) in guards_query.iter() {
+            if team.0 == Team::Evil
+                && transform.translation.truncate().distance(player_position) <= GUARD_ORDER_RADIUS
+            {
+                commands.entity(entity).insert(GuardAssignment(*target));
+            }
+        }
+    }
+}
+
+pub fn draw_rally_point(
+    mut gizmos: Gizmos,
+    command_mode: Res<CommandMode>,
+    rally_point: Res<RallyPoint>,
+) {
+    let Some(rally) = rally_point.0 else {
+        return;
+    };
+
+    // Brighter while giving orders, faint otherwise so it doesn't clutter the battlefield
+    let alpha = if command_mode.active { 1.0 } else { 0.35 };
+    let color = RALLY_COLOR.with_a(alpha);
+    gizmos.circle_2d(rally, RALLY_MARKER_RADIUS, color);
+    gizmos.line_2d(
+        rally,
+        rally + Vec2::new(0.0, RALLY_MARKER_RADIUS * 2.0),
+        color,
+    );
+}
+
+// One ring around every post with anyone guarding it, as far out as the guards go after intruders
+pub fn draw_guard_posts(
+    mut gizmos: Gizmos,
+    command_mode: Res<CommandMode>,
+    assignment_query: Query<&GuardAssignment>,
+    transform_query: Query<&Transform>,
+) {
+    let mut posts: Vec<Entity> = assignment_query
+        .iter()
+        .map(|assignment| assignment.0)
+        .collect();
+    posts.sort();
+    posts.dedup();
+
+    let alpha = if command_mode.active { 1.0 } else { 0.35 };
+    for transform in posts
+        .into_iter()
+        .filter_map(|post| transform_query.get(post).ok())
+    {
+        gizmos.circle_2d(
+            transform.translation.truncate(),
+            GuardBehavior::default().radius,
+            RALLY_COLOR.with_a(alpha),
+        );
+    }
+}
+
+// The route as a closed loop, since that's how it's walked
+pub fn draw_patrol_route(
+    mut gizmos: Gizmos,
+    command_mode: Res<CommandMode>,
+    patrol_route: Res<PatrolRoute>,
+) {
+    let alpha = if command_mode.active { 1.0 } else { 0.35 };
+    let color = RALLY_COLOR.with_a(alpha);
+    let route = &patrol_route.0;
+    for (index, waypoint) in route.iter().enumerate() {
+        gizmos.circle_2d(*waypoint, RALLY_MARKER_RADIUS * 0.5, color);
+        if route.len() > 1 {
+            gizmos.line_2d(*waypoint, route[(index + 1) % route.len()], color);
+        }
+    }
+}
+
+pub fn clear_rally_system(
+    mut event_reader: EventReader<GameEvent>,
+    mut command_mode: ResMut<CommandMode>,
+    mut rally_point: ResMut<RallyPoint>,
+    mut patrol_route: ResMut<PatrolRoute>,
+) {
+    for event in event_reader.read() {
+        if let GameEvent::StartGame | GameEvent::RestartFromCheckpoint = event {
+            command_mode.active = false;
+            rally_point.0 = None;
+            patrol_route.0.clear();
+        }
+    }
+}
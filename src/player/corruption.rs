@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::animation::AtlasLayouts;
+use crate::events::SpellCast;
+use crate::player::plugin::Player;
+use crate::rng::GameRng;
+use crate::units::health::Health;
+use crate::units::team::ROGUE;
+use crate::units::unit_types::{spawn_unit_of_type, UnitType};
+use crate::utils::timing::Threshold;
+
+pub const MAX_CORRUPTION: f32 = 100.0;
+const CORRUPTION_PER_MANA: f32 = 0.4;
+// Casting again this soon after the last cast is heavy use, and corrupts a lot faster
+const HEAVY_USE_WINDOW: f32 = 2.0;
+const HEAVY_USE_MULTIPLIER: f32 = 2.0;
+const DECAY_DELAY: f32 = 3.0;
+const DECAY_PER_SECOND: f32 = 2.0;
+const CORRUPTION_THRESHOLDS: [f32; 3] = [40.0, 70.0, 100.0];
+// Hitting the last threshold purges some of the corruption along with the rogues it lets loose
+const PURGE_AFTER_MAX: f32 = 0.5;
+const ROGUE_SPAWN_DISTANCE: f32 = 160.0;
+const ROGUE_SUMMONS: [UnitType; 3] = [UnitType::Warrior, UnitType::Cat, UnitType::Imp];
+
+#[derive(Component)]
+pub struct Corruption {
+    pub current: f32,
+    pub since_last_cast: f32,
+    pub thresholds: Threshold,
+}
+
+impl Default for Corruption {
+    fn default() -> Self {
+        Self {
+            current: 0.0,
+            since_last_cast: HEAVY_USE_WINDOW,
+            thresholds: Threshold::rising(&CORRUPTION_THRESHOLDS),
+        }
+    }
+}
+
+impl Corruption {
+    pub fn fraction(&self) -> f32 {
+        (self.current / MAX_CORRUPTION).clamp(0.0, 1.0)
+    }
+}
+
+pub fn corrupt_on_cast(
+    time: Res<Time>,
+    mut casts: EventReader<SpellCast>,
+    mut query: Query<&mut Corruption, With<Player>>,
+) {
+    let Ok(mut corruption) = query.get_single_mut() else {
+        casts.clear();
+        return;
+    };
+
+    corruption.since_last_cast += time.delta_seconds();
+    for cast in casts.read() {
+        let multiplier = if corruption.since_last_cast < HEAVY_USE_WINDOW {
+            HEAVY_USE_MULTIPLIER
+        } else {
+            1.0
+        };
+
+        corruption.current += cast.power as f32 * CORRUPTION_PER_MANA * multiplier;
+        corruption.since_last_cast = 0.0;
+    }
+
+    if corruption.since_last_cast > DECAY_DELAY {
+        corruption.current -= DECAY_PER_SECOND * time.delta_seconds();
+    }
+    corruption.current = corruption.current.clamp(0.0, MAX_CORRUPTION);
+
+    // Falling back below a threshold arms it again
+    let current = corruption.current;
+    corruption.thresholds.rearm(current);
+}
+
+pub fn spawn_rogue_summons(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: AtlasLayouts,
+    mut rng: ResMut<GameRng>,
+    mut query: Query<(&mut Corruption, &Transform, &Health), With<Player>>,
+) {
+    let Ok((mut corruption, transform, health)) = query.get_single_mut() else {
+        return;
+    };
+
+    if health.is_dead() {
+        return;
+    }
+
+    loop {
+        let current = corruption.current;
+        let Some(threshold) = corruption.thresholds.cross(current) else {
+            break;
+        };
+
+        let rogues = corruption.thresholds.crossed();
+        warn!(
+            "Corruption reached {}, {} rogue summons broke free",
+            threshold, rogues
+        );
+
+        for _ in 0..rogues {
+            let angle = rng.0.gen::<f32>() * std::f32::consts::TAU;
+            let position =
+                transform.translation.truncate() + Vec2::from_angle(angle) * ROGUE_SPAWN_DISTANCE;
+            let unit_type = *ROGUE_SUMMONS.choose(&mut rng.0).unwrap();
+            spawn_unit_of_type(
+                &mut commands,
+                &asset_server,
+                &mut texture_atlas_layouts,
+                unit_type,
+                ROGUE,
+                position,
+            );
+        }
+
+        if corruption.thresholds.is_complete() {
+            corruption.current *= PURGE_AFTER_MAX;
+            let current = corruption.current;
+            corruption.thresholds.rearm(current);
+            break;
+        }
+    }
+}
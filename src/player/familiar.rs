@@ -0,0 +1,265 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::events::Damaged;
+use crate::game_view::GameAction;
+use crate::gamestate::Cleanup;
+use crate::mana::Mana;
+use crate::map::fog::FogRevealer;
+use crate::player::plugin::Player;
+use crate::player::relics::{Relic, Relics};
+use crate::settings::bindings::Binding;
+use crate::settings::config::GameSettings;
+use crate::stats::run_stats::RunStats;
+use crate::units::team::{AllianceMatrix, CurrentTeam, Team};
+
+const FAMILIAR_SPEED: f32 = 320.0;
+const FAMILIAR_Z: f32 = 4.0;
+const FAMILIAR_COLOR: Color = Color::rgb(0.08, 0.06, 0.1);
+// Where the raven hovers while it has nothing to do, just over the summoner's shoulder
+const FOLLOW_OFFSET: Vec2 = Vec2::new(-28.0, 44.0);
+// The raven only goes after souls this close to the summoner, so it never wanders off into a wave
+const COLLECT_RADIUS: f32 = 240.0;
+const COLLECT_DISTANCE: f32 = 12.0;
+
+const SCOUT_COST: u8 = 60;
+const SCOUT_ORBIT_RADIUS: f32 = 300.0;
+// Radians per second
+const SCOUT_ORBIT_SPEED: f32 = 0.6;
+const SCOUT_SIGHT: f32 = 260.0;
+
+const SOUL_MANA: u8 = 3;
+const SOUL_LIFETIME: f32 = 12.0;
+const SOUL_COLOR: Color = Color::rgb(0.6, 0.95, 1.0);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FamiliarState {
+    Follow,
+    Fetch(Entity),
+    Scout,
+}
+
+// The summoner's raven. It never fights and nothing fights it, it just picks up the souls left
+// behind by fallen enemies and, once trained, scouts out the fog around the summoner.
+#[derive(Component)]
+pub struct Familiar {
+    pub state: FamiliarState,
+    pub scout: bool,
+    orbit_angle: f32,
+}
+
+#[derive(Component)]
+pub struct SoulPickup {
+    pub mana: u8,
+    pub lifetime: Timer,
+}
+
+// The raven comes along with every new summoner, restarts and checkpoints included
+pub fn spawn_familiar(
+    mut commands: Commands,
+    player_query: Query<&Transform, With<Player>>,
+    familiar_query: Query<(), With<Familiar>>,
+) {
+    let Some(player_transform) = player_query.iter().next() else {
+        return;
+    };
+    if !familiar_query.is_empty() {
+        return;
+    }
+
+    let position = player_transform.translation.truncate() + FOLLOW_OFFSET;
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: FAMILIAR_COLOR,
+                custom_size: Some(Vec2::new(16.0, 10.0)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(FAMILIAR_Z)),
+            ..default()
+        },
+        Familiar {
+            state: FamiliarState::Follow,
+            scout: false,
+            orbit_angle: 0.0,
+        },
+        Cleanup,
+    ));
+}
+
+pub fn drop_souls(
+    mut commands: Commands,
+    alliances: Res<AllianceMatrix>,
+    mut damaged_reader: EventReader<Damaged>,
+    victims_query: Query<(&Transform, &CurrentTeam)>,
+) {
+    for damaged in damaged_reader.read() {
+        if !damaged.killed {
+            continue;
+        }
+        let Ok((transform, team)) = victims_query.get(damaged.target) else {
+            continue;
+        };
+        if !alliances.is_hostile(Team::Evil, team.0) {
+            continue;
+        }
+
+        spawn_soul(&mut commands, transform.translation.truncate(), SOUL_MANA);
+    }
+}
+
+pub fn spawn_soul(commands: &mut Commands, position: Vec2, mana: u8) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: SOUL_COLOR,
+                custom_size: Some(Vec2::splat(8.0)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(1.0)),
+            ..default()
+        },
+        SoulPickup {
+            mana,
+            lifetime: Timer::from_seconds(SOUL_LIFETIME, TimerMode::Once),
+        },
+        Cleanup,
+    ));
+}
+
+pub fn expire_souls(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut SoulPickup, &mut Sprite)>,
+) {
+    for (entity, mut soul, mut sprite) in query.iter_mut() {
+        if soul.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        // Fades out towards the end so it doesn't just pop out of existence
+        sprite
+            .color
+            .set_a((soul.lifetime.remaining_secs() / 3.0).min(1.0));
+    }
+}
+
+type OwnerFilter = (With<Player>, Without<Familiar>);
+
+pub fn familiar_behavior(
+    mut commands: Commands,
+    time: Res<Time>,
+    relics: Res<Relics>,
+    mut stats: ResMut<RunStats>,
+    mut player_query: Query<(&Transform, &mut Mana), OwnerFilter>,
+    mut familiar_query: Query<(&mut Familiar, &mut Transform, &mut Sprite)>,
+    soul_query: Query<(Entity, &Transform, &SoulPickup), Without<Familiar>>,
+) {
+    let Some((player_transform, mut mana)) = player_query.iter_mut().next() else {
+        return;
+    };
+    let player_position = player_transform.translation.truncate();
+
+    for (mut familiar, mut transform, mut sprite) in familiar_query.iter_mut() {
+        let position = transform.translation.truncate();
+
+        // Keeps going for the soul it picked until it has it, otherwise the closest one in reach
+        let fetching = match familiar.state {
+            FamiliarState::Fetch(soul) if soul_query.contains(soul) => Some(soul),
+            _ => soul_query
+                .iter()
+                .map(|(soul, soul_transform, _)| (soul, soul_transform.translation.truncate()))
+                .filter(|(_, soul_position)| {
+                    soul_position.distance(player_position) <= COLLECT_RADIUS
+                })
+                .map(|(soul, soul_position)| (soul, soul_position.distance(position)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(soul, _)| soul),
+        };
+        familiar.state = match fetching {
+            Some(soul) => FamiliarState::Fetch(soul),
+            None if familiar.scout => FamiliarState::Scout,
+            None => FamiliarState::Follow,
+        };
+
+        let target = match familiar.state {
+            FamiliarState::Fetch(soul) => soul_query.get(soul).unwrap().1.translation.truncate(),
+            FamiliarState::Follow => player_position + FOLLOW_OFFSET,
+            FamiliarState::Scout => {
+                familiar.orbit_angle =
+                    (familiar.orbit_angle + SCOUT_ORBIT_SPEED * time.delta_seconds()) % TAU;
+                let (sin, cos) = familiar.orbit_angle.sin_cos();
+                player_position + Vec2::new(cos, sin) * SCOUT_ORBIT_RADIUS
+            }
+        };
+
+        let to_target = target - position;
+        let step = FAMILIAR_SPEED * time.delta_seconds();
+        let new_position = if to_target.length() <= step {
+            target
+        } else {
+            position + to_target.normalize() * step
+        };
+        transform.translation = new_position.extend(FAMILIAR_Z);
+        if to_target.x.abs() > 1.0 {
+            sprite.flip_x = to_target.x < 0.0;
+        }
+
+        if let FamiliarState::Fetch(soul) = familiar.state {
+            if new_position.distance(target) <= COLLECT_DISTANCE {
+                let (_, _, pickup) = soul_query.get(soul).unwrap();
+                let amount = if relics.has(Relic::SoulHarvest) {
+                    pickup.mana.saturating_mul(2)
+                } else {
+                    pickup.mana
+                };
+                mana.current_mana = mana.current_mana.saturating_add(amount).min(mana.max_mana);
+                commands.entity(soul).despawn_recursive();
+                stats.souls += 1;
+                familiar.state = FamiliarState::Follow;
+            }
+        }
+    }
+}
+
+// U trains the raven to scout, it circles wide around the summoner and clears the fog as it goes
+pub fn system(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<GameSettings>,
+    mut actions: EventWriter<GameAction>,
+) {
+    if settings.bindings.just_pressed(&keys, Binding::Familiar) {
+        actions.send(GameAction::UpgradeFamiliar);
+    }
+}
+
+pub fn apply_familiar_actions(
+    mut commands: Commands,
+    mut actions: EventReader<GameAction>,
+    mut player_query: Query<&mut Mana, With<Player>>,
+    mut familiar_query: Query<(Entity, &mut Familiar)>,
+) {
+    for action in actions.read() {
+        let GameAction::UpgradeFamiliar = action else {
+            continue;
+        };
+
+        let Ok(mut mana) = player_query.get_single_mut() else {
+            continue;
+        };
+        let Some((entity, mut familiar)) = familiar_query.iter_mut().next() else {
+            continue;
+        };
+        if familiar.scout || mana.current_mana < SCOUT_COST {
+            continue;
+        }
+
+        mana.current_mana -= SCOUT_COST;
+        familiar.scout = true;
+        commands.entity(entity).insert(FogRevealer {
+            radius: SCOUT_SIGHT,
+        });
+    }
+}
@@ -0,0 +1,259 @@
+use bevy::prelude::*;
+
+use crate::animation::AtlasLayouts;
+use crate::difficulty::Difficulty;
+use crate::events::{Damaged, SpellCast, UnitSummoned};
+use crate::game_view::GameAction;
+use crate::gamestate::Cleanup;
+use crate::levels::definition::ActiveLevel;
+use crate::mana::Mana;
+use crate::player::plugin::Player;
+use crate::player::summoning::alive_summons;
+use crate::settings::bindings::Binding;
+use crate::settings::config::GameSettings;
+use crate::time_of_day::lerp_color;
+use crate::ui::nameplate::Nameplate;
+use crate::units::health::Health;
+use crate::units::quality::Gifted;
+use crate::units::spawning::rise_from_circle;
+use crate::units::stat_modifiers::{Modifier, ModifierSource, StatModifiers};
+use crate::units::team::{CurrentTeam, Team};
+use crate::units::unit_types::{spawn_unit_of_type, CurrentUnitType, UnitResource, UnitType};
+use crate::units::veterancy::Veterancy;
+
+const GRAVESTONE_SIZE: Vec2 = Vec2::new(14.0, 20.0);
+const GRAVESTONE_COLOR: Color = Color::rgb(0.4, 0.4, 0.45);
+const CHANNEL_COLOR: Color = Color::rgb(0.6, 0.95, 1.0);
+const GRAVESTONE_LABEL_OFFSET_Y: f32 = 24.0;
+const GRAVESTONE_FONT_SIZE: f32 = 18.0;
+// How close the summoner has to stand, and for how long, to call the unit back
+const REVIVE_REACH: f32 = 64.0;
+const REVIVE_CHANNEL_SECONDS: f32 = 2.5;
+// On top of what the unit costs to summon, veterans are worth more and cost more to bring back
+const REVIVE_COST_PER_RANK: u8 = 10;
+
+// Everything that made a summon that summon, kept so it comes back as the same unit
+#[derive(Clone, Debug)]
+pub struct FallenSummon {
+    pub unit_type: UnitType,
+    pub name: Option<String>,
+    pub veterancy: Veterancy,
+    pub quality: Vec<Modifier>,
+    pub gifted: bool,
+}
+
+// Left behind where a veteran summon fell, until the summoner channels souls into it
+#[derive(Component)]
+pub struct Gravestone {
+    pub fallen: FallenSummon,
+    pub channel: f32,
+}
+
+// Came back from a gravestone, so the ritual doesn't roll its stats over again
+#[derive(Component)]
+pub struct Revived;
+
+type FallenData = (
+    &'static Transform,
+    &'static CurrentTeam,
+    &'static CurrentUnitType,
+    &'static Veterancy,
+    &'static StatModifiers,
+    Option<&'static Nameplate>,
+    Has<Gifted>,
+);
+
+pub fn raise_gravestones(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut damaged_reader: EventReader<Damaged>,
+    fallen_query: Query<FallenData>,
+) {
+    for damaged in damaged_reader.read() {
+        if !damaged.killed {
+            continue;
+        }
+        let Ok((transform, team, unit_type, veterancy, modifiers, nameplate, gifted)) =
+            fallen_query.get(damaged.target)
+        else {
+            continue;
+        };
+        // Only the ones that made it through a few fights are worth coming back for
+        if team.0 != Team::Evil || !veterancy.is_veteran() {
+            continue;
+        }
+
+        let fallen = FallenSummon {
+            unit_type: unit_type.0,
+            name: nameplate.map(|nameplate| nameplate.0.clone()),
+            veterancy: *veterancy,
+            quality: modifiers.of(ModifierSource::Quality),
+            gifted,
+        };
+        let label = format!(
+            "{} {}",
+            fallen.name.as_deref().unwrap_or(fallen.unit_type.name()),
+            "*".repeat(fallen.veterancy.rank() as usize)
+        );
+
+        commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: GRAVESTONE_COLOR,
+                        custom_size: Some(GRAVESTONE_SIZE),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(
+                        transform.translation.truncate().extend(1.0),
+                    ),
+                    ..default()
+                },
+                Gravestone {
+                    fallen,
+                    channel: 0.0,
+                },
+                Cleanup,
+            ))
+            .with_children(|parent| {
+                parent.spawn(Text2dBundle {
+                    text: Text::from_section(
+                        label,
+                        TextStyle {
+                            font: asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+                            font_size: GRAVESTONE_FONT_SIZE,
+                            color: GRAVESTONE_COLOR,
+                        },
+                    )
+                    .with_justify(JustifyText::Center),
+                    transform: Transform::from_xyz(0.0, GRAVESTONE_LABEL_OFFSET_Y, 1.0),
+                    ..default()
+                });
+            });
+    }
+}
+
+// Held down, every frame it's held counts towards the channel
+pub fn system(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<GameSettings>,
+    mut actions: EventWriter<GameAction>,
+) {
+    if settings.bindings.pressed(&keys, Binding::Revive) {
+        actions.send(GameAction::ChannelRevival);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn apply_revival_actions(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: AtlasLayouts,
+    time: Res<Time>,
+    unit_configs: Res<UnitResource>,
+    difficulty: Res<Difficulty>,
+    level: Res<ActiveLevel>,
+    mut actions: EventReader<GameAction>,
+    mut player_query: Query<(&Transform, &mut Mana), With<Player>>,
+    mut gravestone_query: Query<(Entity, &Transform, &mut Gravestone, &mut Sprite)>,
+    summon_query: Query<(&CurrentTeam, &Health), With<CurrentUnitType>>,
+    mut cast_writer: EventWriter<SpellCast>,
+    mut summoned_writer: EventWriter<UnitSummoned>,
+) {
+    let channeling = actions
+        .read()
+        .any(|action| matches!(action, GameAction::ChannelRevival));
+    let Ok((player_transform, mut mana)) = player_query.get_single_mut() else {
+        return;
+    };
+    let player_position = player_transform.translation.truncate();
+
+    // Letting go or walking off lets the channel fade, so there's no saving up progress
+    let target = channeling
+        .then(|| {
+            gravestone_query
+                .iter()
+                .map(|(entity, transform, _, _)| {
+                    (
+                        entity,
+                        transform.translation.truncate().distance(player_position),
+                    )
+                })
+                .filter(|(_, distance)| *distance <= REVIVE_REACH)
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(entity, _)| entity)
+        })
+        .flatten();
+
+    for (entity, transform, mut gravestone, mut sprite) in gravestone_query.iter_mut() {
+        if Some(entity) != target {
+            gravestone.channel = 0.0;
+            sprite.color = GRAVESTONE_COLOR;
+            continue;
+        }
+
+        gravestone.channel =
+            (gravestone.channel + time.delta_seconds()).min(REVIVE_CHANNEL_SECONDS);
+        let progress = gravestone.channel / REVIVE_CHANNEL_SECONDS;
+        sprite.color = lerp_color(GRAVESTONE_COLOR, CHANNEL_COLOR, progress);
+        if progress < 1.0 {
+            continue;
+        }
+
+        // A finished channel waits at the stone until there's mana and room in the army for it
+        let fallen = &gravestone.fallen;
+        let cost = unit_configs
+            .cost(fallen.unit_type, &difficulty)
+            .saturating_add(REVIVE_COST_PER_RANK.saturating_mul(fallen.veterancy.rank() as u8));
+        if mana.current_mana < cost
+            || alive_summons(summon_query.iter()) >= level.summons.max_summons as usize
+        {
+            continue;
+        }
+
+        mana.current_mana -= cost;
+        cast_writer.send(SpellCast { power: cost });
+        let summon = revive(
+            &mut commands,
+            &asset_server,
+            &mut texture_atlas_layouts,
+            fallen,
+            transform.translation.truncate(),
+        );
+        summoned_writer.send(UnitSummoned {
+            entity: summon,
+            unit_type: fallen.unit_type,
+            revived: true,
+        });
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn revive(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    texture_atlas_layouts: &mut AtlasLayouts,
+    fallen: &FallenSummon,
+    position: Vec2,
+) -> Entity {
+    let mut modifiers = StatModifiers::default();
+    modifiers.set(ModifierSource::Quality, &fallen.quality);
+
+    let mut entity = spawn_unit_of_type(
+        commands,
+        asset_server,
+        texture_atlas_layouts,
+        fallen.unit_type,
+        Team::Evil,
+        position,
+    );
+    entity.insert((modifiers, fallen.veterancy, Revived));
+    rise_from_circle(&mut entity);
+    if let Some(name) = &fallen.name {
+        entity.insert(Nameplate(name.clone()));
+    }
+    if fallen.gifted {
+        entity.insert(Gifted);
+    }
+    entity.id()
+}
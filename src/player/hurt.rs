@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+
+use crate::animation::Tint;
+use crate::events::Damaged;
+use crate::units::damage::Invulnerable;
+use crate::units::health::Health;
+
+use super::plugin::Player;
+
+// Long enough for the hit animation to play out, and for the summoner to get away from whatever
+// landed it
+const HURT_SECONDS: f32 = 0.8;
+const FLICKER_PER_SECOND: f32 = 12.0;
+const FLICKER_ALPHA: f32 = 0.35;
+
+// The summoner just took a hit, plays the hit animation and can't be hit again until it's over
+#[derive(Component)]
+pub struct Hurt {
+    pub remaining: f32,
+}
+
+// A dead summoner doesn't get to do anything while the death animation plays out
+pub fn player_alive(query: Query<&Health, With<Player>>) -> bool {
+    query.iter().any(|health| !health.is_dead())
+}
+
+pub fn hurt_player(
+    mut commands: Commands,
+    mut damaged_reader: EventReader<Damaged>,
+    query: Query<(), With<Player>>,
+) {
+    for damaged in damaged_reader.read() {
+        if damaged.dodged || damaged.killed || damaged.amount <= 0 {
+            continue;
+        }
+        if !query.contains(damaged.target) {
+            continue;
+        }
+
+        commands.entity(damaged.target).insert((
+            Hurt {
+                remaining: HURT_SECONDS,
+            },
+            Invulnerable {
+                remaining: HURT_SECONDS,
+            },
+        ));
+    }
+}
+
+// Blinks the summoner while it lasts, so it's clear nothing is getting through
+pub fn tick_hurt(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Hurt, &Health)>,
+) {
+    for (entity, mut hurt, health) in query.iter_mut() {
+        hurt.remaining -= time.delta_seconds();
+        if hurt.remaining <= 0.0 || health.is_dead() {
+            commands.entity(entity).remove::<(Hurt, Tint)>();
+            continue;
+        }
+
+        let visible = ((hurt.remaining * FLICKER_PER_SECOND) as u32).is_multiple_of(2);
+        let alpha = if visible { 1.0 } else { FLICKER_ALPHA };
+        commands
+            .entity(entity)
+            .insert(Tint(Color::WHITE.with_a(alpha)));
+    }
+}
@@ -1,21 +1,137 @@
-use crate::velocity::Velocity;
+use crate::animation::Animation;
+use crate::game_view::GameAction;
+use crate::gamestate::Cleanup;
+use crate::movement::Movement;
+use crate::settings::bindings::Binding;
+use crate::settings::config::GameSettings;
+use crate::ui::nameplate::RenameState;
+use crate::units::damage::Invulnerable;
+use crate::utils::timing::Cooldown;
+use crate::velocity::{Motion, Velocity};
 use bevy::prelude::*;
 
 use super::plugin::Player;
+use super::summoning::Channeling;
 
 const WINDOW_BOUNDS_OFFSET: f32 = 96.0;
 
+const DASH_BUTTONS: [GamepadButtonType; 1] = [GamepadButtonType::South];
+pub const DASH_SPEED_MULTIPLIER: f32 = 4.0;
+const DASH_SECONDS: f32 = 0.15;
+const DASH_COOLDOWN_SECONDS: f32 = 1.5;
+// A bit longer than the dash itself, so landing next to an enemy mid-swing is still safe
+const DASH_IFRAME_SECONDS: f32 = 0.25;
+const AFTERIMAGE_INTERVAL_SECONDS: f32 = 0.03;
+const AFTERIMAGE_SECONDS: f32 = 0.25;
+const AFTERIMAGE_COLOR: Color = Color::rgba(0.6, 0.3, 1.0, 0.5);
+
+#[derive(Component)]
+pub struct Dash {
+    cooldown: Cooldown,
+    remaining: f32,
+    direction: Vec2,
+    // The last way the summoner walked, dashing from standing still goes that way
+    facing: Vec2,
+    afterimage: Timer,
+}
+
+impl Default for Dash {
+    fn default() -> Self {
+        Self {
+            cooldown: Cooldown::new(DASH_COOLDOWN_SECONDS),
+            remaining: 0.0,
+            direction: Vec2::ZERO,
+            facing: Vec2::X,
+            afterimage: Timer::from_seconds(AFTERIMAGE_INTERVAL_SECONDS, TimerMode::Repeating),
+        }
+    }
+}
+
+impl Dash {
+    pub fn is_dashing(&self) -> bool {
+        self.remaining > 0.0
+    }
+}
+
+// A faded copy of the summoner left behind along the dash
+#[derive(Component)]
+pub struct Afterimage {
+    lifetime: Timer,
+}
+
+type PlayerMovementData = (
+    Entity,
+    &'static mut Velocity,
+    &'static mut Motion,
+    &'static Movement,
+    &'static Transform,
+    &'static mut Dash,
+);
+
+#[allow(clippy::too_many_arguments)]
 pub fn system(
     keys: Res<ButtonInput<KeyCode>>,
-    query: Query<(&mut Velocity, &Transform), With<Player>>,
+    settings: Res<GameSettings>,
+    rename_state: Res<RenameState>,
+    time: Res<Time>,
+    mut commands: Commands,
+    mut actions: EventReader<GameAction>,
+    query: Query<PlayerMovementData, With<Player>>,
+    channeling_query: Query<(), (With<Player>, With<Channeling>)>,
     window_query: Query<&Window>,
 ) {
-    // let column_staggered_colemak_binds =
-    //     [KeyCode::KeyF, KeyCode::KeyR, KeyCode::KeyS, KeyCode::KeyT];
-    // let move_input = construct_input_vector(keys, column_staggered_colemak_binds);
-    let row_staggered_qwerty_binds = [KeyCode::KeyW, KeyCode::KeyA, KeyCode::KeyS, KeyCode::KeyD];
-    let move_input = construct_input_vector(keys, row_staggered_qwerty_binds);
-    handle_movement(query, window_query, move_input);
+    // Rebound in the settings, colemak and the like just move these
+    let move_binds = Binding::MOVEMENT.map(|binding| settings.bindings.key(binding));
+    // Typing a name for a summon shouldn't walk the player around
+    let mut move_input = if rename_state.is_active() {
+        Vec2::ZERO
+    } else {
+        construct_input_vector(keys, move_binds)
+    };
+
+    // Keyboard input wins, submitted move actions only steer the player when no key is held
+    let mut dash_requested = false;
+    for action in actions.read() {
+        match action {
+            GameAction::Move(direction) if move_input == Vec2::ZERO => {
+                move_input = direction.normalize_or_zero();
+            }
+            GameAction::Dash => dash_requested = true,
+            _ => {}
+        }
+    }
+
+    // Walking off would break the ritual, so the summoner holds still until it's done
+    if !channeling_query.is_empty() {
+        move_input = Vec2::ZERO;
+        dash_requested = false;
+    }
+
+    handle_movement(
+        &mut commands,
+        &time,
+        query,
+        window_query,
+        move_input,
+        dash_requested,
+    );
+}
+
+pub fn dash_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<GameSettings>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    mut actions: EventWriter<GameAction>,
+) {
+    let pressed_button = gamepads.iter().any(|gamepad| {
+        DASH_BUTTONS
+            .iter()
+            .any(|button| buttons.just_pressed(GamepadButton::new(gamepad, *button)))
+    });
+    if settings.bindings.just_pressed(&keys, Binding::Dash) || pressed_button {
+        actions.send(GameAction::Dash);
+    }
 }
 
 fn construct_input_vector(keys: Res<ButtonInput<KeyCode>>, binds: [KeyCode; 4]) -> Vec2 {
@@ -38,9 +154,12 @@ fn construct_input_vector(keys: Res<ButtonInput<KeyCode>>, binds: [KeyCode; 4])
 }
 
 fn handle_movement(
-    mut query: Query<(&mut Velocity, &Transform), With<Player>>,
+    commands: &mut Commands,
+    time: &Time,
+    mut query: Query<PlayerMovementData, With<Player>>,
     window_query: Query<&Window>,
     move_input: Vec2,
+    dash_requested: bool,
 ) {
     let window = window_query.single();
     let window_bounds = Vec2::new(
@@ -48,19 +167,113 @@ fn handle_movement(
         window.height() - WINDOW_BOUNDS_OFFSET,
     ) * 0.5;
 
-    for (mut velocity, transform) in query.iter_mut() {
-        velocity.0 = move_input;
+    for (entity, mut velocity, mut motion, movement, transform, mut dash) in query.iter_mut() {
+        dash.cooldown.tick(time.delta());
+        if move_input != Vec2::ZERO {
+            dash.facing = move_input;
+        }
+        if dash_requested && dash.cooldown.is_ready() && !dash.is_dashing() {
+            dash.direction = dash.facing;
+            dash.remaining = DASH_SECONDS;
+            dash.cooldown.start();
+            // Straight to full speed, a dash that has to get going first is no use for dodging
+            motion.current = dash.direction * movement.speed * DASH_SPEED_MULTIPLIER;
+            commands.entity(entity).insert(Invulnerable {
+                remaining: DASH_IFRAME_SECONDS,
+            });
+        }
+
+        // The dash carries the summoner on its own, walking input is ignored until it's over
+        velocity.0 = if dash.is_dashing() {
+            dash.remaining -= time.delta_seconds();
+            dash.direction * DASH_SPEED_MULTIPLIER
+        } else {
+            move_input
+        };
 
         if (transform.translation.x >= window_bounds.x && velocity.0.x > 0.0)
             || (transform.translation.x <= -window_bounds.x && velocity.0.x < 0.0)
         {
             velocity.0.x = 0.0;
+            motion.current.x = 0.0;
         }
 
         if (transform.translation.y >= window_bounds.y && velocity.0.y > 0.0)
             || (transform.translation.y <= -window_bounds.y && velocity.0.y < 0.0)
         {
             velocity.0.y = 0.0;
+            motion.current.y = 0.0;
+        }
+    }
+}
+
+type AfterimageData = (
+    &'static Handle<Image>,
+    &'static TextureAtlas,
+    &'static Sprite,
+    &'static GlobalTransform,
+    &'static Visibility,
+);
+
+// Copies whichever animation frame the summoner is showing, so the trail matches the pose
+pub fn spawn_afterimages(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_query: Query<(&mut Dash, &Children), With<Player>>,
+    child_query: Query<AfterimageData, With<Animation>>,
+) {
+    for (mut dash, children) in player_query.iter_mut() {
+        if !dash.is_dashing() {
+            dash.afterimage.reset();
+            continue;
+        }
+        if !dash.afterimage.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        for &child in children.iter() {
+            let Ok((texture, atlas, sprite, transform, visibility)) = child_query.get(child) else {
+                continue;
+            };
+            if visibility != Visibility::Visible {
+                continue;
+            }
+
+            let mut transform = transform.compute_transform();
+            transform.translation.z -= 0.1;
+            commands.spawn((
+                SpriteSheetBundle {
+                    sprite: Sprite {
+                        color: AFTERIMAGE_COLOR,
+                        flip_x: sprite.flip_x,
+                        ..default()
+                    },
+                    texture: texture.clone(),
+                    atlas: atlas.clone(),
+                    transform,
+                    ..default()
+                },
+                Afterimage {
+                    lifetime: Timer::from_seconds(AFTERIMAGE_SECONDS, TimerMode::Once),
+                },
+                Cleanup,
+            ));
+        }
+    }
+}
+
+pub fn fade_afterimages(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Afterimage, &mut Sprite)>,
+) {
+    for (entity, mut afterimage, mut sprite) in query.iter_mut() {
+        if afterimage.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
         }
+        sprite
+            .color
+            .set_a(AFTERIMAGE_COLOR.a() * afterimage.lifetime.fraction_remaining());
     }
 }
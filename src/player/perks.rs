@@ -0,0 +1,314 @@
+use std::collections::{HashMap, VecDeque};
+
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+
+use crate::events::{Damaged, GameEvent, Heal, UnitDied};
+use crate::game_view::GameAction;
+use crate::player::plugin::Player;
+use crate::rng::GameRng;
+use crate::units::stat_modifiers::{Modifier, ModifierSource, Stat, StatModifiers};
+use crate::units::team::{AllianceMatrix, CurrentTeam, Team};
+use crate::units::unit_types::UnitType;
+
+// The most perks a single level-up offers
+const PERK_CHOICE_SIZE: usize = 3;
+
+const XP_FIRST_LEVEL: u32 = 10;
+const XP_PER_LEVEL: u32 = 8;
+
+const CAT_COST_PER_RANK: f32 = 0.8;
+const SUMMON_RADIUS_PER_RANK: f32 = 48.0;
+const LIFESTEAL_PER_RANK: f32 = 0.15;
+// Only summons fighting close to the summoner drink from the aura
+pub const LIFESTEAL_AURA_RADIUS: f32 = 180.0;
+const LIFESTEAL_AURA_COLOR: Color = Color::rgba(0.8, 0.1, 0.2, 0.25);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Perk {
+    CheaperCats,
+    WideSummons,
+    LifestealAura,
+}
+
+// Everything the player gets to see about a perk, laid out the same way as the relic cards
+#[derive(Debug, Clone, Copy)]
+pub struct PerkDefinition {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub icon: &'static str,
+    pub color: Color,
+    pub max_rank: u32,
+}
+
+impl Perk {
+    pub const ALL: [Perk; 3] = [Perk::CheaperCats, Perk::WideSummons, Perk::LifestealAura];
+
+    pub fn definition(&self) -> PerkDefinition {
+        match self {
+            Perk::CheaperCats => PerkDefinition {
+                name: "Cat Bargain",
+                description: "Cats cost 20% less mana to summon",
+                icon: "\u{f011b}",
+                color: Color::rgb(0.9, 0.6, 0.9),
+                max_rank: 3,
+            },
+            Perk::WideSummons => PerkDefinition {
+                name: "Wide Circle",
+                description: "Summons step out of the ritual further from the summoner",
+                icon: "\u{f0766}",
+                color: Color::rgb(0.55, 0.4, 1.0),
+                max_rank: 3,
+            },
+            Perk::LifestealAura => PerkDefinition {
+                name: "Blood Pact",
+                description: "Summons close to the summoner heal for 15% of the damage they deal",
+                icon: "\u{f0e7a}",
+                color: Color::rgb(0.9, 0.15, 0.25),
+                max_rank: 3,
+            },
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.definition().name
+    }
+
+    fn modifiers(&self, rank: u32) -> Vec<Modifier> {
+        let rank = rank as i32;
+        match self {
+            Perk::CheaperCats => vec![Modifier::multiply(
+                Stat::SummonCost(UnitType::Cat),
+                CAT_COST_PER_RANK.powi(rank),
+            )],
+            Perk::WideSummons => vec![Modifier::add(
+                Stat::SummonRadius,
+                SUMMON_RADIUS_PER_RANK * rank as f32,
+            )],
+            Perk::LifestealAura => vec![Modifier::add(
+                Stat::Lifesteal,
+                LIFESTEAL_PER_RANK * rank as f32,
+            )],
+        }
+    }
+}
+
+// Kills fill it up, every level past the first offers a perk
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct Experience {
+    pub points: u32,
+    pub level: u32,
+}
+
+impl Default for Experience {
+    fn default() -> Self {
+        Self {
+            points: 0,
+            level: 1,
+        }
+    }
+}
+
+impl Experience {
+    pub fn to_next_level(&self) -> u32 {
+        XP_FIRST_LEVEL + XP_PER_LEVEL * (self.level - 1)
+    }
+
+    // Hands back how many levels the points were worth
+    pub fn gain(&mut self, points: u32) -> u32 {
+        self.points += points;
+        let mut levels = 0;
+        while self.points >= self.to_next_level() {
+            self.points -= self.to_next_level();
+            self.level += 1;
+            levels += 1;
+        }
+        levels
+    }
+}
+
+// Tougher enemies are worth more
+fn kill_experience(unit_type: Option<UnitType>) -> u32 {
+    match unit_type {
+        Some(UnitType::Critter) => 1,
+        Some(UnitType::Gargoyle) => 3,
+        Some(UnitType::Assassin) => 4,
+        Some(UnitType::ArmoredKnight) => 5,
+        _ => 2,
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Perks(pub HashMap<Perk, u32>);
+
+impl Perks {
+    pub fn rank(&self, perk: Perk) -> u32 {
+        self.0.get(&perk).copied().unwrap_or(0)
+    }
+
+    // A few at random out of the perks that can still go up a rank
+    pub fn offer(&self, rng: &mut GameRng) -> Vec<Perk> {
+        let mut open: Vec<Perk> = Perk::ALL
+            .into_iter()
+            .filter(|perk| self.rank(*perk) < perk.definition().max_rank)
+            .collect();
+        open.shuffle(&mut rng.0);
+        open.truncate(PERK_CHOICE_SIZE);
+        open
+    }
+
+    fn modifiers(&self) -> Vec<Modifier> {
+        Perk::ALL
+            .into_iter()
+            .filter(|perk| self.rank(*perk) > 0)
+            .flat_map(|perk| perk.modifiers(self.rank(perk)))
+            .collect()
+    }
+}
+
+// Level-ups waiting for the player to pick a perk out of, oldest first
+#[derive(Resource, Default)]
+pub struct PerkChoices(pub VecDeque<Vec<Perk>>);
+
+impl PerkChoices {
+    pub fn current(&self) -> Option<&Vec<Perk>> {
+        self.0.front()
+    }
+}
+
+pub fn gain_experience(
+    alliances: Res<AllianceMatrix>,
+    perks: Res<Perks>,
+    mut rng: ResMut<GameRng>,
+    mut experience: ResMut<Experience>,
+    mut choices: ResMut<PerkChoices>,
+    mut died_reader: EventReader<UnitDied>,
+) {
+    for died in died_reader.read() {
+        if !alliances.is_hostile(Team::Evil, died.team) {
+            continue;
+        }
+
+        for _ in 0..experience.gain(kill_experience(died.unit_type)) {
+            info!("Reached level {}", experience.level);
+            let offer = perks.offer(&mut rng);
+            if !offer.is_empty() {
+                choices.0.push_back(offer);
+            }
+        }
+    }
+}
+
+pub fn apply_perk_choice_actions(
+    mut actions: EventReader<GameAction>,
+    mut perks: ResMut<Perks>,
+    mut choices: ResMut<PerkChoices>,
+) {
+    for action in actions.read() {
+        let GameAction::ChoosePerk(index) = action else {
+            continue;
+        };
+        let Some(perk) = choices
+            .current()
+            .and_then(|offer| offer.get(*index))
+            .copied()
+        else {
+            continue;
+        };
+
+        info!("Chose the {} perk", perk.name());
+        *perks.0.entry(perk).or_insert(0) += 1;
+        choices.0.pop_front();
+    }
+}
+
+// Kept on the summoner's own modifiers, so a new summoner from a checkpoint gets them back too
+pub fn apply_perk_modifiers(perks: Res<Perks>, mut query: Query<&mut StatModifiers, With<Player>>) {
+    for mut modifiers in query.iter_mut() {
+        if !perks.is_changed() && !modifiers.is_added() {
+            continue;
+        }
+
+        let perk_modifiers = perks.modifiers();
+        if perk_modifiers.is_empty() {
+            modifiers.remove(ModifierSource::Perks);
+        } else {
+            modifiers.set(ModifierSource::Perks, &perk_modifiers);
+        }
+    }
+}
+
+pub fn lifesteal_aura(
+    mut damaged_reader: EventReader<Damaged>,
+    player_query: Query<(&Transform, &StatModifiers), With<Player>>,
+    summon_query: Query<(&Transform, &CurrentTeam)>,
+    mut heal_writer: EventWriter<Heal>,
+) {
+    let Ok((player_transform, player_modifiers)) = player_query.get_single() else {
+        return;
+    };
+    let lifesteal = player_modifiers.apply(Stat::Lifesteal, 0.0);
+    if lifesteal <= 0.0 {
+        return;
+    }
+    let player_position = player_transform.translation.truncate();
+
+    for damaged in damaged_reader.read() {
+        if damaged.amount <= 0 {
+            continue;
+        }
+        let Some(source) = damaged.source else {
+            continue;
+        };
+        let Ok((transform, team)) = summon_query.get(source) else {
+            continue;
+        };
+        if team.0 != Team::Evil
+            || transform.translation.truncate().distance(player_position) > LIFESTEAL_AURA_RADIUS
+        {
+            continue;
+        }
+
+        let amount = (damaged.amount as f32 * lifesteal).round() as i32;
+        if amount > 0 {
+            heal_writer.send(Heal {
+                target: source,
+                amount,
+                source: None,
+            });
+        }
+    }
+}
+
+pub fn draw_lifesteal_aura(
+    mut gizmos: Gizmos,
+    perks: Res<Perks>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    if perks.rank(Perk::LifestealAura) == 0 {
+        return;
+    }
+
+    for transform in player_query.iter() {
+        gizmos.circle_2d(
+            transform.translation.truncate(),
+            LIFESTEAL_AURA_RADIUS,
+            LIFESTEAL_AURA_COLOR,
+        );
+    }
+}
+
+pub fn clear_perks_system(
+    mut event_reader: EventReader<GameEvent>,
+    mut experience: ResMut<Experience>,
+    mut perks: ResMut<Perks>,
+    mut choices: ResMut<PerkChoices>,
+) {
+    for event in event_reader.read() {
+        if let GameEvent::StartGame = event {
+            *experience = Experience::default();
+            perks.0.clear();
+            choices.0.clear();
+        }
+    }
+}
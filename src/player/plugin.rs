@@ -1,6 +1,12 @@
 use bevy::prelude::*;
 
 use crate::player;
+use crate::player::build_mode::not_building;
+use crate::player::command_mode::not_commanding;
+use crate::player::hurt::player_alive;
+use crate::player::relics::not_choosing;
+use crate::schedule::FrameSet;
+use crate::ui::nameplate::not_renaming;
 use crate::units::unit_types::UnitResource;
 
 pub struct PlayerPlugin;
@@ -10,9 +16,138 @@ pub struct Player;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(UnitResource::default()).add_systems(
-            Update,
-            (player::movement::system, player::summoning::system),
-        );
+        app.insert_resource(UnitResource::default())
+            .init_resource::<player::build_mode::BuildMode>()
+            .init_resource::<player::relics::Relics>()
+            .init_resource::<player::relics::RelicChoices>()
+            .init_resource::<player::perks::Experience>()
+            .init_resource::<player::perks::Perks>()
+            .init_resource::<player::perks::PerkChoices>()
+            .init_resource::<player::command_mode::CommandMode>()
+            .init_resource::<player::command_mode::RallyPoint>()
+            .init_resource::<player::command_mode::PatrolRoute>()
+            .init_resource::<player::summoning::SelectedSummon>()
+            .add_systems(
+                Update,
+                (
+                    player::summoning::system
+                        .run_if(not_renaming)
+                        .run_if(not_building)
+                        .run_if(not_choosing),
+                    player::charm::system
+                        .run_if(not_renaming)
+                        .run_if(not_choosing),
+                    player::ultimate::system
+                        .run_if(not_renaming)
+                        .run_if(not_choosing),
+                    player::build_mode::system
+                        .run_if(not_renaming)
+                        .run_if(not_commanding)
+                        .run_if(not_choosing),
+                    player::command_mode::system
+                        .run_if(not_renaming)
+                        .run_if(not_building)
+                        .run_if(not_choosing),
+                    player::familiar::system
+                        .run_if(not_renaming)
+                        .run_if(not_choosing),
+                    player::gravestones::system
+                        .run_if(not_renaming)
+                        .run_if(not_choosing),
+                    player::movement::dash_input
+                        .run_if(not_renaming)
+                        .run_if(not_choosing),
+                )
+                    .run_if(player_alive)
+                    .in_set(FrameSet::Input),
+            )
+            .add_systems(
+                Update,
+                (
+                    player::movement::system,
+                    (
+                        player::summoning::select_summon,
+                        player::summoning::interrupt_channel,
+                        player::summoning::apply_summon_actions,
+                    )
+                        .chain(),
+                    player::charm::apply_charm_actions,
+                    player::ultimate::apply_frenzy_actions,
+                    (
+                        player::relics::apply_relic_choice_actions,
+                        player::perks::apply_perk_choice_actions,
+                        player::relics::pause_for_choices,
+                    )
+                        .chain(),
+                    (
+                        player::build_mode::apply_build_actions,
+                        player::build_mode::draw_build_preview,
+                    )
+                        .chain(),
+                    (
+                        player::command_mode::apply_rally_actions,
+                        player::command_mode::draw_rally_point,
+                        player::command_mode::draw_guard_posts,
+                        player::command_mode::draw_patrol_route,
+                    )
+                        .chain(),
+                    player::familiar::apply_familiar_actions,
+                    player::gravestones::apply_revival_actions,
+                )
+                    .in_set(FrameSet::Actions),
+            )
+            .add_systems(
+                Update,
+                (
+                    player::ultimate::charge_ultimate,
+                    (
+                        player::summoning::reset_unlocks_system,
+                        player::summoning::unlock_units,
+                    )
+                        .chain(),
+                    player::relics::grant_wave_relics,
+                    player::relics::clear_relics_system,
+                    (
+                        player::perks::clear_perks_system,
+                        player::perks::gain_experience,
+                        player::perks::apply_perk_modifiers,
+                    )
+                        .chain(),
+                    player::perks::lifesteal_aura,
+                    player::perks::draw_lifesteal_aura,
+                    player::command_mode::clear_rally_system,
+                    player::gravestones::raise_gravestones,
+                    (player::hurt::hurt_player, player::hurt::tick_hurt).chain(),
+                    (
+                        player::corruption::corrupt_on_cast,
+                        player::corruption::spawn_rogue_summons,
+                    )
+                        .chain(),
+                ),
+            )
+            // The raven keeps to the summoner's shoulder, so it goes after the summoner has moved
+            .add_systems(
+                Update,
+                (
+                    player::familiar::spawn_familiar,
+                    player::familiar::drop_souls,
+                    player::familiar::expire_souls,
+                    player::familiar::familiar_behavior,
+                )
+                    .chain()
+                    .in_set(FrameSet::Presentation),
+            )
+            .add_systems(
+                Update,
+                (
+                    player::summoning::draw_channel_ring,
+                    (
+                        player::movement::spawn_afterimages,
+                        player::movement::fade_afterimages,
+                    )
+                        .chain(),
+                )
+                    .in_set(FrameSet::Presentation),
+            );
     }
 }
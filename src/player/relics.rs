@@ -0,0 +1,184 @@
+use std::collections::{HashSet, VecDeque};
+
+use bevy::prelude::*;
+
+use crate::enemies::enemy_spawner::EnemySpawner;
+use crate::events::GameEvent;
+use crate::game_view::GameAction;
+use crate::gamestate::GameState;
+use crate::narrative::conversation::ConversationState;
+use crate::photo_mode::PhotoModeState;
+use crate::player::perks::PerkChoices;
+use crate::settings::menu::SettingsState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Relic {
+    // Damage left over from a killing blow carries on to the next enemy nearby
+    OverkillSplash,
+    // Souls picked up by the raven are worth twice the mana
+    SoulHarvest,
+    // Gifted summons come out of the ritual a lot more often
+    GildedSummons,
+}
+
+impl Relic {
+    pub const ALL: [Relic; 3] = [
+        Relic::OverkillSplash,
+        Relic::SoulHarvest,
+        Relic::GildedSummons,
+    ];
+
+    pub fn definition(&self) -> RelicDefinition {
+        match self {
+            Relic::OverkillSplash => RelicDefinition {
+                name: "Cleaving Skull",
+                description:
+                    "Damage left over from a killing blow carries on to the next enemy nearby",
+                icon: "\u{f04e5}",
+                color: Color::rgb(0.9, 0.3, 0.25),
+            },
+            Relic::SoulHarvest => RelicDefinition {
+                name: "Soul Lantern",
+                description: "Souls fetched by the raven are worth twice the mana",
+                icon: "\u{f02a0}",
+                color: Color::rgb(0.5, 0.8, 1.0),
+            },
+            Relic::GildedSummons => RelicDefinition {
+                name: "Gilded Chalice",
+                description: "Gifted summons come out of the ritual three times as often",
+                icon: "\u{f01a5}",
+                color: Color::rgb(1.0, 0.8, 0.2),
+            },
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.definition().name
+    }
+}
+
+// Everything the player gets to see about a relic, the icon is a glyph from the nerd font
+#[derive(Debug, Clone, Copy)]
+pub struct RelicDefinition {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub icon: &'static str,
+    pub color: Color,
+}
+
+// The most relics a single choice offers
+const RELIC_CHOICE_SIZE: usize = 3;
+
+// Relics are handed out for surviving long enough, the wave is the first one they are active in
+const RELIC_REWARDS: [(u32, Relic); 1] = [(5, Relic::OverkillSplash)];
+
+#[derive(Resource, Default)]
+pub struct Relics(pub HashSet<Relic>);
+
+impl Relics {
+    pub fn has(&self, relic: Relic) -> bool {
+        self.0.contains(&relic)
+    }
+
+    // Only relics that aren't owned yet are worth offering
+    pub fn offer(&self) -> Vec<Relic> {
+        Relic::ALL
+            .into_iter()
+            .filter(|relic| !self.has(*relic))
+            .take(RELIC_CHOICE_SIZE)
+            .collect()
+    }
+}
+
+// Rewards waiting for the player to pick one relic out of, oldest first
+#[derive(Resource, Default)]
+pub struct RelicChoices(pub VecDeque<Vec<Relic>>);
+
+impl RelicChoices {
+    pub fn current(&self) -> Option<&Vec<Relic>> {
+        self.0.front()
+    }
+}
+
+// Nothing else takes input while a relic or a perk is waiting to be picked
+pub fn not_choosing(choices: Res<RelicChoices>, perk_choices: Res<PerkChoices>) -> bool {
+    choices.current().is_none() && perk_choices.current().is_none()
+}
+
+// The game holds still while a relic or a perk is being picked, and carries on once there's
+// nothing left to choose. Nothing is picked on the game over screen, so the clock runs there as
+// usual.
+pub fn pause_for_choices(
+    choices: Res<RelicChoices>,
+    perk_choices: Res<PerkChoices>,
+    settings_state: Res<State<SettingsState>>,
+    conversation_state: Res<State<ConversationState>>,
+    photo_mode_state: Res<State<PhotoModeState>>,
+    game_state_query: Query<&GameState>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    let game_over = game_state_query.iter().any(|state| state.game_over);
+    let choosing = (choices.current().is_some() || perk_choices.current().is_some()) && !game_over;
+    let talking = *conversation_state.get() == ConversationState::Open && !game_over;
+    // The settings menu holds the run too, this is the one place the clock gets paused
+    let paused = choosing
+        || talking
+        || *settings_state.get() == SettingsState::Open
+        || *photo_mode_state.get() == PhotoModeState::On;
+    if paused && !time.is_paused() {
+        time.pause();
+    } else if !paused && time.is_paused() {
+        time.unpause();
+    }
+}
+
+// Based on the wave rather than on when it was reached, so restarting from a checkpoint keeps
+// the relics that wave had earned
+pub fn grant_wave_relics(spawner_query: Query<&EnemySpawner>, mut relics: ResMut<Relics>) {
+    let Some(spawner) = spawner_query.iter().next() else {
+        return;
+    };
+
+    for (wave, relic) in RELIC_REWARDS {
+        if spawner.wave >= wave && !relics.has(relic) {
+            info!("Found the {} relic", relic.name());
+            relics.0.insert(relic);
+        }
+    }
+}
+
+pub fn apply_relic_choice_actions(
+    mut actions: EventReader<GameAction>,
+    mut relics: ResMut<Relics>,
+    mut choices: ResMut<RelicChoices>,
+) {
+    for action in actions.read() {
+        let GameAction::ChooseRelic(index) = action else {
+            continue;
+        };
+        let Some(relic) = choices
+            .current()
+            .and_then(|offer| offer.get(*index))
+            .copied()
+        else {
+            continue;
+        };
+
+        info!("Chose the {} relic", relic.name());
+        relics.0.insert(relic);
+        choices.0.pop_front();
+    }
+}
+
+pub fn clear_relics_system(
+    mut event_reader: EventReader<GameEvent>,
+    mut relics: ResMut<Relics>,
+    mut choices: ResMut<RelicChoices>,
+) {
+    for event in event_reader.read() {
+        if let GameEvent::StartGame = event {
+            relics.0.clear();
+            choices.0.clear();
+        }
+    }
+}
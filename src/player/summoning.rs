@@ -1,103 +1,339 @@
+use crate::animation::AtlasLayouts;
+use crate::difficulty::Difficulty;
+use crate::enemies::enemy_spawner::EnemySpawner;
+use crate::events::{Damaged, GameEvent, SpellCast, UnitSummoned, UnitUnlocked};
+use crate::game_view::GameAction;
+use crate::levels::definition::ActiveLevel;
 use crate::mana::Mana;
+use crate::meta::progress::MetaProgress;
 use crate::player::plugin::Player;
-use crate::units::team::Team;
-use crate::units::unit_types::{
-    spawn_unit, Acolyte, Cat, Knight, UnitChildrenSpawnParamsFactory, UnitResource, UnitType,
-    Warrior,
-};
-use bevy::ecs::system::EntityCommands;
+use crate::rng::GameRng;
+use crate::settings::bindings::Binding;
+use crate::settings::config::GameSettings;
+use crate::ui::nameplate::{name_new_summon, LastSummon, NameplateSettings};
+use crate::units::health::Health;
+use crate::units::spawning::rise_from_circle;
+use crate::units::stat_modifiers::{Stat, StatModifiers};
+use crate::units::team::{CurrentTeam, Team};
+use crate::units::unit_types::{spawn_unit_of_type, CurrentUnitType, UnitResource, UnitType};
 use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+// Summons this expensive or more need the summoner to hold still and channel the ritual first,
+// from the shortest channel at the threshold up to the longest at twice it
+const CHANNEL_COST_THRESHOLD: u8 = 30;
+const CHANNEL_MIN_SECONDS: f32 = 1.0;
+const CHANNEL_MAX_SECONDS: f32 = 2.0;
+const CHANNEL_RING_RADIUS: f32 = 36.0;
+const CHANNEL_RING_COLOR: Color = Color::rgb(0.6, 0.3, 1.0);
+
+// The summoner standing still over a ritual, the summon only comes out once it fills up. The mana
+// is paid at the end, so a ritual broken by a hit only costs the time.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Channeling {
+    pub unit_type: UnitType,
+    pub elapsed: f32,
+    pub duration: f32,
+}
+
+impl Channeling {
+    pub fn progress(&self) -> f32 {
+        (self.elapsed / self.duration).min(1.0)
+    }
+}
+
+fn channel_seconds(cost: u8) -> Option<f32> {
+    if cost < CHANNEL_COST_THRESHOLD {
+        return None;
+    }
+    let t = (cost - CHANNEL_COST_THRESHOLD) as f32 / CHANNEL_COST_THRESHOLD as f32;
+    Some(CHANNEL_MIN_SECONDS + (CHANNEL_MAX_SECONDS - CHANNEL_MIN_SECONDS) * t.min(1.0))
+}
+
+// How big an army the summoner can keep at once and what keeping it costs, written into the
+// level's data. Upkeep is mana per second for every summon alive, taken out of what the acolytes
+// bring in, and never out of the mana already banked.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct SummonLimits {
+    pub max_summons: u32,
+    pub upkeep_per_summon: f32,
+}
+
+impl Default for SummonLimits {
+    fn default() -> Self {
+        Self {
+            max_summons: 16,
+            upkeep_per_summon: 0.0,
+        }
+    }
+}
+
+impl SummonLimits {
+    pub fn upkeep(&self, summons: usize) -> f32 {
+        self.upkeep_per_summon * summons as f32
+    }
+
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.max_summons == 0 {
+            problems.push("the summon cap has to be above zero".to_owned());
+        }
+        if self.upkeep_per_summon < 0.0 {
+            problems.push("summon upkeep can't be negative".to_owned());
+        }
+        problems
+    }
+}
+
+// Charmed and converted units count too, they cost the same to keep around
+pub fn alive_summons<'a>(units: impl Iterator<Item = (&'a CurrentTeam, &'a Health)>) -> usize {
+    units
+        .filter(|(team, health)| team.0 == Team::Evil && !health.is_dead())
+        .count()
+}
+
+pub const SUMMON_BINDS: [(Binding, UnitType); 5] = [
+    (Binding::Summon1, UnitType::Acolyte),
+    (Binding::Summon2, UnitType::Warrior),
+    (Binding::Summon3, UnitType::Cat),
+    (Binding::Summon4, UnitType::Imp),
+    (Binding::Summon5, UnitType::DarkPriest),
+];
+
+// The summon the summoner last asked for, whether or not it came out, which the hud shows the
+// cost of
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct SelectedSummon(pub UnitType);
+
+impl Default for SelectedSummon {
+    fn default() -> Self {
+        Self(SUMMON_BINDS[0].1)
+    }
+}
+
+pub fn select_summon(mut actions: EventReader<GameAction>, mut selected: ResMut<SelectedSummon>) {
+    for action in actions.read() {
+        if let GameAction::Summon(unit) = action {
+            selected.0 = *unit;
+        }
+    }
+}
 
 pub fn system(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<GameSettings>,
+    mut actions: EventWriter<GameAction>,
+) {
+    let pressed_units = handle_input(&keys, &settings, &SUMMON_BINDS);
+
+    pressed_units.into_iter().for_each(|(_, unit)| {
+        actions.send(GameAction::Summon(*unit));
+    });
+}
+
+type SummonerData = (
+    Entity,
+    &'static mut Mana,
+    &'static Transform,
+    &'static StatModifiers,
+    Option<&'static mut Channeling>,
+);
+
+#[allow(clippy::too_many_arguments)]
+pub fn apply_summon_actions(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-    keys: Res<ButtonInput<KeyCode>>,
+    mut texture_atlas_layouts: AtlasLayouts,
+    time: Res<Time>,
+    mut actions: EventReader<GameAction>,
     unit_configs: Res<UnitResource>,
-    mut query: Query<(&mut Mana, &Transform), With<Player>>,
+    difficulty: Res<Difficulty>,
+    progress: Res<MetaProgress>,
+    level: Res<ActiveLevel>,
+    mut rng: ResMut<GameRng>,
+    nameplate_settings: Res<NameplateSettings>,
+    mut last_summon: ResMut<LastSummon>,
+    mut query: Query<SummonerData, With<Player>>,
+    summon_query: Query<(&CurrentTeam, &Health), With<CurrentUnitType>>,
+    mut cast_writer: EventWriter<SpellCast>,
+    mut summoned_writer: EventWriter<UnitSummoned>,
 ) {
-    // let column_staggered_colemak_binds = vec![
-    //     (KeyCode::KeyN, UnitType::Acolyte),
-    //     (KeyCode::KeyE, UnitType::Warrior),
-    //     (KeyCode::KeyI, UnitType::Cat),
-    // ];
-    // let pressed_units = handle_input(&keys, &column_staggered_colemak_binds);
-
-    let row_staggered_qwerty_binds = vec![
-        (KeyCode::Digit1, UnitType::Acolyte),
-        (KeyCode::Digit2, UnitType::Warrior),
-        (KeyCode::Digit3, UnitType::Cat),
-    ];
-    let pressed_units = handle_input(&keys, &row_staggered_qwerty_binds);
+    let Ok((player, mut mana, transform, modifiers, mut channeling)) = query.get_single_mut()
+    else {
+        return;
+    };
 
-    pressed_units.into_iter().for_each(|(_, unit)| {
-        let (mut mana, transform) = query.single_mut();
-        let unit_cost = unit_configs.get(*unit).cost;
-        if mana.current_mana < unit_cost {
-            return;
+    let mut ready = Vec::new();
+    if let Some(channel) = channeling.as_mut() {
+        channel.elapsed += time.delta_seconds();
+        if channel.elapsed >= channel.duration {
+            ready.push(channel.unit_type);
+            commands.entity(player).remove::<Channeling>();
         }
+    }
+    // Nothing else gets summoned while a ritual is going
+    let mut busy = channeling.is_some();
+    let mut alive = alive_summons(summon_query.iter());
+    let max_summons = level.summons.max_summons as usize;
 
-        match unit {
-            UnitType::Acolyte => summon_unit(
-                &mut commands,
-                &asset_server,
-                &mut texture_atlas_layouts,
-                Acolyte::default(),
-                transform,
-            )
-            .insert(Acolyte::default()),
-            UnitType::Warrior => summon_unit(
-                &mut commands,
-                &asset_server,
-                &mut texture_atlas_layouts,
-                Warrior,
-                transform,
-            )
-            .insert(Warrior),
-            UnitType::Cat => summon_unit(
-                &mut commands,
-                &asset_server,
-                &mut texture_atlas_layouts,
-                Cat,
-                transform,
-            )
-            .insert(Cat),
-            UnitType::Knight => summon_unit(
-                &mut commands,
-                &asset_server,
-                &mut texture_atlas_layouts,
-                Knight,
-                transform,
-            )
-            .insert(Knight),
+    for action in actions.read() {
+        let GameAction::Summon(unit) = action else {
+            continue;
         };
+        if busy
+            || alive >= max_summons
+            || !unit_configs.is_unlocked(*unit)
+            || !progress.is_unit_unlocked(*unit)
+            || mana.current_mana < unit_configs.summon_cost(*unit, &difficulty, modifiers)
+        {
+            continue;
+        }
+
+        match channel_seconds(unit_configs.summon_cost(*unit, &difficulty, modifiers)) {
+            Some(duration) => {
+                commands.entity(player).insert(Channeling {
+                    unit_type: *unit,
+                    elapsed: 0.0,
+                    duration,
+                });
+                busy = true;
+            }
+            None => ready.push(*unit),
+        }
+    }
+
+    for unit in ready.iter() {
+        // Checked again, the mana might have gone elsewhere while the ritual was channeled
+        let unit_cost = unit_configs.summon_cost(*unit, &difficulty, modifiers);
+        if mana.current_mana < unit_cost || alive >= max_summons {
+            continue;
+        }
+        alive += 1;
+
+        let mut summon = spawn_unit_of_type(
+            &mut commands,
+            &asset_server,
+            &mut texture_atlas_layouts,
+            *unit,
+            Team::Evil,
+            transform.translation.truncate() + summon_offset(&mut rng, modifiers),
+        );
+        rise_from_circle(&mut summon);
+        let summon = summon.id();
+        name_new_summon(
+            &mut commands,
+            &mut rng,
+            &nameplate_settings,
+            &mut last_summon,
+            summon,
+        );
 
         mana.current_mana -= unit_cost;
-    });
+        cast_writer.send(SpellCast { power: unit_cost });
+        summoned_writer.send(UnitSummoned {
+            entity: summon,
+            unit_type: *unit,
+            revived: false,
+        });
+    }
+}
+
+// Any hit that gets through breaks the summoner's concentration
+pub fn interrupt_channel(
+    mut commands: Commands,
+    mut damaged_reader: EventReader<Damaged>,
+    query: Query<&Channeling, With<Player>>,
+) {
+    for damaged in damaged_reader.read() {
+        if damaged.dodged || damaged.amount <= 0 {
+            continue;
+        }
+        let Ok(channel) = query.get(damaged.target) else {
+            continue;
+        };
+
+        info!("The {} ritual was interrupted", channel.unit_type.name());
+        commands.entity(damaged.target).remove::<Channeling>();
+    }
+}
+
+// Fills up clockwise from the top as the ritual goes
+pub fn draw_channel_ring(mut gizmos: Gizmos, query: Query<(&Transform, &Channeling)>) {
+    for (transform, channel) in query.iter() {
+        let arc = channel.progress() * TAU;
+        gizmos.arc_2d(
+            transform.translation.truncate(),
+            arc * 0.5,
+            arc,
+            CHANNEL_RING_RADIUS,
+            CHANNEL_RING_COLOR,
+        );
+    }
+}
+
+// Right on top of the summoner, unless something widened the circle they step out of
+fn summon_offset(rng: &mut GameRng, modifiers: &StatModifiers) -> Vec2 {
+    let radius = modifiers.apply(Stat::SummonRadius, 0.0);
+    if radius <= 0.0 {
+        return Vec2::ZERO;
+    }
+
+    let angle = rng.0.gen::<f32>() * TAU;
+    let distance = radius * rng.0.gen::<f32>().sqrt();
+    Vec2::from_angle(angle) * distance
+}
+
+pub fn reset_unlocks_system(
+    mut event_reader: EventReader<GameEvent>,
+    mut unit_configs: ResMut<UnitResource>,
+) {
+    for event in event_reader.read() {
+        if let GameEvent::StartGame = event {
+            unit_configs.reset_unlocks();
+        }
+    }
+}
+
+pub fn unlock_units(
+    mut damaged_reader: EventReader<Damaged>,
+    mut unit_configs: ResMut<UnitResource>,
+    killer_query: Query<(&CurrentUnitType, &CurrentTeam)>,
+    spawner_query: Query<&EnemySpawner>,
+    mut unlocked_writer: EventWriter<UnitUnlocked>,
+) {
+    for damaged in damaged_reader.read() {
+        if !damaged.killed {
+            continue;
+        }
+        let Some((unit_type, team)) = damaged
+            .source
+            .and_then(|source| killer_query.get(source).ok())
+        else {
+            continue;
+        };
+
+        if team.0 == Team::Evil {
+            unit_configs.record_kill(unit_type.0);
+        }
+    }
+
+    let Some(spawner) = spawner_query.iter().next() else {
+        return;
+    };
+    for unit_type in unit_configs.unlock_earned(spawner.wave) {
+        unlocked_writer.send(UnitUnlocked(unit_type));
+    }
 }
 
 fn handle_input<'a>(
     keys: &'a Res<ButtonInput<KeyCode>>,
-    binds: &'a [(KeyCode, UnitType)],
-) -> impl Iterator<Item = &'a (KeyCode, UnitType)> + 'a {
+    settings: &'a GameSettings,
+    binds: &'a [(Binding, UnitType)],
+) -> impl Iterator<Item = &'a (Binding, UnitType)> + 'a {
     binds
         .iter()
-        .filter(move |(key, _unit)| keys.just_pressed(*key))
-}
-
-fn summon_unit<'a>(
-    commands: &'a mut Commands,
-    asset_server: &'a Res<AssetServer>,
-    texture_atlas_layouts: &'a mut ResMut<Assets<TextureAtlasLayout>>,
-    unit_component: impl UnitChildrenSpawnParamsFactory + Clone,
-    player_transform: &'a Transform,
-) -> EntityCommands<'a> {
-    spawn_unit(
-        commands,
-        asset_server,
-        texture_atlas_layouts,
-        unit_component,
-        Team::Evil,
-        player_transform.translation.truncate(),
-    )
+        .filter(move |(binding, _unit)| settings.bindings.just_pressed(keys, *binding))
 }
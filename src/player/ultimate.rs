@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+
+use crate::events::{Damaged, SpellCast};
+use crate::game_view::GameAction;
+use crate::player::plugin::Player;
+use crate::settings::bindings::Binding;
+use crate::settings::config::GameSettings;
+use crate::structures::structure_types::Structure;
+use crate::units::frenzy::Frenzy;
+use crate::units::health::Health;
+use crate::units::team::{AllianceMatrix, CurrentTeam, Team};
+
+const ULTIMATE_MAX_CHARGE: f32 = 100.0;
+const ULTIMATE_CHARGE_PER_SECOND: f32 = 1.0;
+const ULTIMATE_CHARGE_PER_KILL: f32 = 4.0;
+const FRENZY_DURATION: f32 = 8.0;
+// The frenzy doesn't cost mana, but it is about as dark as magic gets
+const FRENZY_POWER: u8 = 50;
+
+// Charged ability, fills up slowly over time and faster with every enemy that dies
+#[derive(Component)]
+pub struct UltimateCharge {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Default for UltimateCharge {
+    fn default() -> Self {
+        Self {
+            current: 0.0,
+            max: ULTIMATE_MAX_CHARGE,
+        }
+    }
+}
+
+impl UltimateCharge {
+    pub fn is_ready(&self) -> bool {
+        self.current >= self.max
+    }
+
+    pub fn fraction(&self) -> f32 {
+        (self.current / self.max).clamp(0.0, 1.0)
+    }
+
+    fn add(&mut self, amount: f32) {
+        self.current = (self.current + amount).min(self.max);
+    }
+}
+
+pub fn system(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<GameSettings>,
+    mut actions: EventWriter<GameAction>,
+) {
+    if settings.bindings.just_pressed(&keys, Binding::Ultimate) {
+        actions.send(GameAction::Frenzy);
+    }
+}
+
+pub fn charge_ultimate(
+    time: Res<Time>,
+    alliances: Res<AllianceMatrix>,
+    mut damaged_reader: EventReader<Damaged>,
+    team_query: Query<&CurrentTeam>,
+    mut query: Query<(&mut UltimateCharge, &Health), With<Player>>,
+) {
+    let kills = damaged_reader
+        .read()
+        .filter(|damaged| damaged.killed)
+        .filter(|damaged| {
+            team_query
+                .get(damaged.target)
+                .is_ok_and(|team| alliances.is_hostile(Team::Evil, team.0))
+        })
+        .count();
+
+    for (mut charge, health) in query.iter_mut() {
+        if health.is_dead() {
+            continue;
+        }
+
+        charge.add(
+            ULTIMATE_CHARGE_PER_SECOND * time.delta_seconds()
+                + ULTIMATE_CHARGE_PER_KILL * kills as f32,
+        );
+    }
+}
+
+// Sends everything on the player's team into a frenzy
+pub fn apply_frenzy_actions(
+    mut commands: Commands,
+    mut actions: EventReader<GameAction>,
+    mut player_query: Query<&mut UltimateCharge, With<Player>>,
+    units_query: Query<(Entity, &CurrentTeam, &Health), Without<Structure>>,
+    mut cast_writer: EventWriter<SpellCast>,
+) {
+    for action in actions.read() {
+        let GameAction::Frenzy = action else {
+            continue;
+        };
+
+        let Ok(mut charge) = player_query.get_single_mut() else {
+            continue;
+        };
+
+        if !charge.is_ready() {
+            continue;
+        }
+
+        charge.current = 0.0;
+        cast_writer.send(SpellCast {
+            power: FRENZY_POWER,
+        });
+        for (entity, team, health) in units_query.iter() {
+            if team.0 == Team::Evil && !health.is_dead() {
+                commands.entity(entity).insert(Frenzy::new(FRENZY_DURATION));
+            }
+        }
+    }
+}
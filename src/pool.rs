@@ -0,0 +1,166 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::animation::Animation;
+use crate::units::unit_types::{CurrentUnitType, UnitType};
+
+// Of each kind, past this the rest are despawned like before so one huge wave doesn't leave
+// thousands of hidden entities around for the rest of the run
+const MAX_POOLED: usize = 256;
+
+// Done with and hidden, waiting to be handed out again. Everything the gameplay systems look for
+// has been taken off, so nothing but the pool ever finds these.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Pooled;
+
+// Instead of despawning units, projectiles and damage numbers and spawning them again a moment
+// later, which in a big wave is hundreds of them moving in and out of their archetypes every
+// second. Pooled entities don't have Cleanup, so they're kept from one run to the next, until the
+// level is left and they're emptied out so the level's textures can be unloaded.
+#[derive(Resource, Default)]
+pub struct EntityPools {
+    units: HashMap<UnitType, Vec<Entity>>,
+    projectiles: Vec<Entity>,
+    damage_numbers: Vec<Entity>,
+}
+
+impl EntityPools {
+    // A unit of this type with its sprites still on it, put back together by whoever takes it
+    pub fn take_unit(&mut self, unit_type: UnitType) -> Option<Entity> {
+        self.units.get_mut(&unit_type)?.pop()
+    }
+
+    pub fn take_projectile(&mut self) -> Option<Entity> {
+        self.projectiles.pop()
+    }
+
+    pub fn take_damage_number(&mut self) -> Option<Entity> {
+        self.damage_numbers.pop()
+    }
+
+    // Everything parked, for despawning when the level is left. Their sprites still hold on to
+    // the level's textures.
+    pub fn drain(&mut self) -> Vec<Entity> {
+        let units = self.units.drain().flat_map(|(_, units)| units);
+        units
+            .chain(self.projectiles.drain(..))
+            .chain(self.damage_numbers.drain(..))
+            .collect()
+    }
+}
+
+// Takes the place of despawn_recursive for units. Only the root and its sprites are kept, the
+// sprites hidden, everything else on it or under it goes. Parked once the commands are applied,
+// so the pool never hands out one that's still halfway there.
+pub fn release_unit(commands: &mut Commands, entity: Entity) {
+    commands.add(move |world: &mut World| {
+        let Some(unit) = world.get_entity(entity) else {
+            return;
+        };
+        let unit_type = unit.get::<CurrentUnitType>().map(|unit_type| unit_type.0);
+        let full = |unit_type: &UnitType| {
+            let pools = world.resource::<EntityPools>();
+            pools.units.get(unit_type).map_or(0, Vec::len) >= MAX_POOLED
+        };
+        let Some(unit_type) = unit_type.filter(|unit_type| !full(unit_type)) else {
+            world.entity_mut(entity).despawn_recursive();
+            return;
+        };
+
+        let children: Vec<Entity> = world
+            .get::<Children>(entity)
+            .map_or_else(Vec::new, |children| children.to_vec());
+        for child in children {
+            if world.get::<Animation>(child).is_some() {
+                if let Some(mut visibility) = world.get_mut::<Visibility>(child) {
+                    *visibility = Visibility::Hidden;
+                }
+            } else {
+                // Auras, icons and outlines come back on their own if the next unit needs them
+                world.entity_mut(child).despawn_recursive();
+            }
+        }
+        world
+            .entity_mut(entity)
+            .retain::<(Children, Transform, GlobalTransform, InheritedVisibility)>()
+            .insert(Pooled);
+        world
+            .resource_mut::<EntityPools>()
+            .units
+            .entry(unit_type)
+            .or_default()
+            .push(entity);
+    });
+}
+
+pub fn release_projectile(commands: &mut Commands, entity: Entity) {
+    commands.add(move |world: &mut World| {
+        park::<SpriteBundle>(world, entity, |pools| &mut pools.projectiles);
+    });
+}
+
+pub fn release_damage_number(commands: &mut Commands, entity: Entity) {
+    commands.add(move |world: &mut World| {
+        park::<Text2dBundle>(world, entity, |pools| &mut pools.damage_numbers);
+    });
+}
+
+// Keeps what the entity is drawn with so taking it back out only overwrites the values
+fn park<B: Bundle>(
+    world: &mut World,
+    entity: Entity,
+    pool: impl Fn(&mut EntityPools) -> &mut Vec<Entity>,
+) {
+    if world.get_entity(entity).is_none() {
+        return;
+    }
+    if pool(&mut world.resource_mut::<EntityPools>()).len() >= MAX_POOLED {
+        world.entity_mut(entity).despawn_recursive();
+        return;
+    }
+
+    world
+        .entity_mut(entity)
+        .retain::<B>()
+        .insert((Pooled, Visibility::Hidden));
+    pool(&mut world.resource_mut::<EntityPools>()).push(entity);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::*;
+
+    use super::{release_unit, Pooled};
+    use crate::ai::behavior::CurrentBehavior;
+    use crate::test_utils::TestApp;
+    use crate::units::team::Team;
+    use crate::units::unit_types::UnitType;
+
+    #[test]
+    fn released_knight_comes_back_as_the_next_knight() {
+        let mut app = TestApp::new();
+        let knight = app.spawn_unit(UnitType::Knight, Team::Good, Vec2::new(900.0, 0.0));
+        app.tick(2);
+        let sprites = app.get::<Children>(knight).len();
+
+        app.app.world.run_system_once_with(
+            knight,
+            |In(knight): In<Entity>, mut commands: Commands| {
+                release_unit(&mut commands, knight);
+            },
+        );
+        app.tick(1);
+        assert!(app.app.world.get::<Pooled>(knight).is_some());
+        assert!(app.app.world.get::<CurrentBehavior>(knight).is_none());
+
+        let next = app.spawn_unit(UnitType::Knight, Team::Good, Vec2::new(-900.0, 0.0));
+        app.tick(2);
+
+        assert_eq!(next, knight);
+        assert!(app.app.world.get::<Pooled>(next).is_none());
+        assert_eq!(app.get::<Children>(next).len(), sprites);
+        app.assert_behavior(next, "Marching");
+        assert!(app.position(next).x > -900.0);
+    }
+}
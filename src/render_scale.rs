@@ -0,0 +1,313 @@
+use bevy::prelude::*;
+use bevy::render::camera::{RenderTarget, ScalingMode};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+use bevy::render::view::RenderLayers;
+use serde::{Deserialize, Serialize};
+
+use crate::camera::CameraController;
+use crate::settings::config::GameSettings;
+
+// Everything on this layer is drawn straight to the window at native resolution, the world is
+// drawn to an offscreen image first and then stretched over the window on this layer.
+pub const UI_LAYER: u8 = 1;
+// The world view is on this one too, photo mode draws it and nothing else
+pub const PHOTO_LAYER: u8 = 2;
+
+// A ui pixel is a window pixel at 1080p, the ui grows and shrinks from there so it reads the same
+// from 720p up to 4K
+const UI_REFERENCE_HEIGHT: f32 = 1080.0;
+pub const UI_SCALE_STEP: f32 = 0.25;
+pub const MIN_UI_SCALE: f32 = 0.75;
+pub const MAX_UI_SCALE: f32 = 2.0;
+
+// How heavily the last frame counts into the average, low enough that one hitch doesn't count
+const FRAME_TIME_SMOOTHING: f32 = 0.1;
+
+// Part of the display settings, the thresholds can be tuned in the settings file
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct RenderScaleSettings {
+    pub enabled: bool,
+    pub min_scale: f32,
+    pub max_scale: f32,
+    pub step: f32,
+    // Scale down when the average frame takes longer than this
+    pub downscale_frame_time: f32,
+    // And back up once it is below this again. With vsync the frame time never drops much under
+    // the refresh rate, so this has to sit a bit above 60 fps to ever be reached.
+    pub upscale_frame_time: f32,
+    // Seconds between two steps, gives the average time to settle on the new resolution
+    pub adjust_interval: f32,
+}
+
+impl Default for RenderScaleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_scale: 0.5,
+            max_scale: 1.0,
+            step: 0.1,
+            downscale_frame_time: 1.0 / 45.0,
+            upscale_frame_time: 1.0 / 55.0,
+            adjust_interval: 1.0,
+        }
+    }
+}
+
+impl RenderScaleSettings {
+    // The settings file can say anything, clamp wants its lower bound under the upper one
+    fn scale_range(&self) -> (f32, f32) {
+        let max_scale = self.max_scale.clamp(0.1, 1.0);
+        (self.min_scale.clamp(0.1, max_scale), max_scale)
+    }
+
+    fn adjust_timer(&self) -> Timer {
+        Timer::from_seconds(self.adjust_interval.max(0.0), TimerMode::Once)
+    }
+}
+
+#[derive(Resource)]
+pub struct RenderScale {
+    pub scale: f32,
+    pub average_frame_time: f32,
+    pub image: Handle<Image>,
+    cooldown: Timer,
+}
+
+#[derive(Component)]
+pub struct WorldCamera;
+
+#[derive(Component)]
+pub struct WorldView;
+
+#[derive(Component)]
+pub struct UiCamera;
+
+// The window the way the ui camera sees it once it's scaled, everything on the ui layer is laid
+// out in these units rather than in window pixels
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct UiView {
+    pub scale: f32,
+    pub half_size: Vec2,
+    // Where the cursor is in ui units, with the origin in the middle like the camera has it
+    pub cursor: Option<Vec2>,
+}
+
+impl Default for UiView {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            half_size: Vec2::ZERO,
+            cursor: None,
+        }
+    }
+}
+
+impl UiView {
+    pub fn size(&self) -> Vec2 {
+        self.half_size * 2.0
+    }
+}
+
+pub fn auto_ui_scale(window: &Window) -> f32 {
+    let scale = (window.height() / UI_REFERENCE_HEIGHT / UI_SCALE_STEP).round() * UI_SCALE_STEP;
+    scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE)
+}
+
+fn create_render_image(size: UVec2) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: size.x.max(1),
+            height: size.y.max(1),
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Bgra8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+fn render_size(window: &Window, scale: f32) -> UVec2 {
+    let physical = Vec2::new(
+        window.physical_width() as f32,
+        window.physical_height() as f32,
+    );
+    (physical * scale).round().as_uvec2().max(UVec2::ONE)
+}
+
+pub fn setup_cameras(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    settings: Res<GameSettings>,
+    window_query: Query<&Window>,
+) {
+    let settings = settings.display.render_scale;
+    let (_, max_scale) = settings.scale_range();
+    let window = window_query.single();
+    let image = images.add(create_render_image(render_size(window, max_scale)));
+
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                order: -1,
+                target: RenderTarget::Image(image.clone()),
+                ..default()
+            },
+            ..default()
+        },
+        WorldCamera,
+        CameraController::default(),
+    ));
+    commands.spawn((
+        Camera2dBundle::default(),
+        RenderLayers::layer(UI_LAYER),
+        UiCamera,
+    ));
+    commands.spawn((
+        SpriteBundle {
+            texture: image.clone(),
+            // Behind all of the text on the same layer
+            transform: Transform::from_xyz(0.0, 0.0, -100.0),
+            ..default()
+        },
+        RenderLayers::from_layers(&[UI_LAYER, PHOTO_LAYER]),
+        WorldView,
+    ));
+
+    commands.insert_resource(RenderScale {
+        scale: max_scale,
+        average_frame_time: 0.0,
+        image,
+        cooldown: settings.adjust_timer(),
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_render_scale(
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+    mut render_scale: ResMut<RenderScale>,
+    mut images: ResMut<Assets<Image>>,
+    ui_view: Res<UiView>,
+    window_query: Query<&Window>,
+    mut camera_query: Query<&mut OrthographicProjection, With<WorldCamera>>,
+    mut view_query: Query<&mut Sprite, With<WorldView>>,
+) {
+    let settings = settings.display.render_scale;
+    let (min_scale, max_scale) = settings.scale_range();
+    let frame_time = time.delta_seconds();
+    render_scale.average_frame_time +=
+        (frame_time - render_scale.average_frame_time) * FRAME_TIME_SMOOTHING;
+
+    let mut scale = if settings.enabled {
+        render_scale.scale
+    } else {
+        max_scale
+    };
+    if settings.enabled && render_scale.cooldown.tick(time.delta()).finished() {
+        if render_scale.average_frame_time > settings.downscale_frame_time {
+            scale -= settings.step;
+        } else if render_scale.average_frame_time < settings.upscale_frame_time {
+            scale += settings.step;
+        }
+    }
+    scale = scale.clamp(min_scale, max_scale);
+
+    if scale != render_scale.scale {
+        info!(
+            "Rendering the world at {:.0}% resolution, average frame {:.1} ms",
+            scale * 100.0,
+            render_scale.average_frame_time * 1000.0
+        );
+        render_scale.scale = scale;
+        render_scale.cooldown = settings.adjust_timer();
+    }
+
+    let window = window_query.single();
+    let size = render_size(window, render_scale.scale);
+    if let Some(image) = images.get(&render_scale.image) {
+        if image.size() != size {
+            let image = images.get_mut(&render_scale.image).unwrap();
+            image.resize(Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            });
+        }
+    }
+
+    // The world always covers the same area no matter how many pixels it is drawn with
+    let window_size = Vec2::new(window.width(), window.height());
+    for mut projection in camera_query.iter_mut() {
+        let up_to_date = matches!(
+            projection.scaling_mode,
+            ScalingMode::Fixed { width, height } if Vec2::new(width, height) == window_size
+        );
+        if !up_to_date {
+            projection.scaling_mode = ScalingMode::Fixed {
+                width: window_size.x,
+                height: window_size.y,
+            };
+        }
+    }
+    // Stretched over the whole window whatever the ui camera is scaled to
+    for mut sprite in view_query.iter_mut() {
+        if sprite.custom_size != Some(ui_view.size()) {
+            sprite.custom_size = Some(ui_view.size());
+        }
+    }
+}
+
+// Before anything in the frame lays the ui out, the scale is the one from the settings or picked
+// from the window's height when it's left on auto
+pub fn update_ui_view(
+    settings: Res<GameSettings>,
+    mut ui_view: ResMut<UiView>,
+    window_query: Query<&Window>,
+    mut camera_query: Query<&mut OrthographicProjection, With<UiCamera>>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let scale = settings
+        .display
+        .ui_scale
+        .unwrap_or_else(|| auto_ui_scale(window));
+    let view = UiView {
+        scale,
+        half_size: Vec2::new(window.width(), window.height()) * 0.5 / scale,
+        cursor: window.cursor_position().map(|cursor| {
+            Vec2::new(
+                cursor.x - window.width() * 0.5,
+                window.height() * 0.5 - cursor.y,
+            ) / scale
+        }),
+    };
+    if *ui_view != view {
+        *ui_view = view;
+    }
+
+    for mut projection in camera_query.iter_mut() {
+        if projection.scale != 1.0 / scale {
+            projection.scale = 1.0 / scale;
+        }
+    }
+}
+
+// Window cursor positions are in window pixels, but the world camera's viewport is the smaller
+// offscreen image, so the cursor has to be scaled down before it can be projected.
+pub fn cursor_to_world(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec2> {
+    let cursor = window.cursor_position()?;
+    let viewport_size = camera.logical_viewport_size()?;
+    let window_size = Vec2::new(window.width(), window.height());
+    camera.viewport_to_world_2d(camera_transform, cursor * viewport_size / window_size)
+}
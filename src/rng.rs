@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+// Plays a run from a known seed, "--seed 1234", instead of a fresh one every time
+pub const SEED_FLAG: &str = "--seed";
+
+// Every gameplay roll goes through this, so a run with the same seed and the same inputs plays
+// out the same way. Cosmetic randomness like damage number jitter stays on the thread rng so it
+// doesn't pull numbers out of the stream.
+#[derive(Resource)]
+pub struct GameRng(pub ChaCha8Rng);
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self(ChaCha8Rng::seed_from_u64(seed))
+    }
+}
+
+#[derive(Resource)]
+pub struct RunSeed {
+    // The seed the current run started from, shown so a bug report can say which one it was
+    pub current: u64,
+    // Given on the command line, it wins over both the level's seed and a fresh one
+    pub forced: Option<u64>,
+}
+
+impl RunSeed {
+    pub fn from_args() -> Self {
+        let forced = parse_seed_arg(std::env::args());
+        Self {
+            current: forced.unwrap_or_else(rand::random),
+            forced,
+        }
+    }
+}
+
+fn parse_seed_arg(mut args: impl Iterator<Item = String>) -> Option<u64> {
+    while let Some(arg) = args.next() {
+        let value = if arg == SEED_FLAG {
+            args.next()
+        } else {
+            arg.strip_prefix(SEED_FLAG)
+                .and_then(|rest| rest.strip_prefix('='))
+                .map(str::to_owned)
+        };
+        let Some(value) = value else {
+            continue;
+        };
+
+        match value.parse() {
+            Ok(seed) => return Some(seed),
+            Err(_) => warn!("Ignoring {} {}, it has to be a number", SEED_FLAG, value),
+        }
+    }
+    None
+}
+
+// Called wherever a run starts, before anything has rolled for it. A level with a seed of its
+// own, like a daily challenge, plays the same every time unless the command line says otherwise.
+pub fn start_run(rng: &mut GameRng, run_seed: &mut RunSeed, level_seed: Option<u64>) {
+    run_seed.current = run_seed.forced.or(level_seed).unwrap_or_else(rand::random);
+    *rng = GameRng::from_seed(run_seed.current);
+    info!("Run seed {}", run_seed.current);
+}
@@ -0,0 +1,464 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+
+use crate::animation::AtlasLayouts;
+use crate::difficulty::Difficulty;
+use crate::enemies::endless::Escalation;
+use crate::enemies::enemy_spawner::EnemySpawner;
+use crate::enemies::mutators::NextWaveMutator;
+use crate::events::GameEvent;
+use crate::gamestate::{cleanup_game_system, spawn_player, Cleanup, GameState};
+use crate::levels::definition::ActiveLevel;
+use crate::mana::Mana;
+use crate::player::plugin::Player;
+use crate::rng::RunSeed;
+use crate::structures::structure_types::{spawn_structure, Structure};
+use crate::ui::nameplate::Nameplate;
+use crate::units::altar::{spawn_altar, DarkAltar};
+use crate::units::health::Health;
+use crate::units::quality::Gifted;
+use crate::units::stat_modifiers::{BaseMaxHealth, StatModifiers};
+use crate::units::team::{CurrentTeam, Team};
+use crate::units::unit_types::{spawn_unit_of_type, CurrentUnitType};
+use crate::units::veterancy::Veterancy;
+
+use super::snapshot::{
+    capture_snapshot, capture_structures, read_snapshot, write_snapshot, RunSnapshot, UnitSnapshot,
+};
+
+const DEFAULT_MAX_CHECKPOINTS: usize = 3;
+const DEFAULT_SAVE_DIRECTORY: &str = "saves";
+// How long the player gets to look around after restarting before the checkpoint wave comes in
+const RESTART_GRACE_SECONDS: f32 = 3.0;
+
+#[derive(Resource)]
+pub struct CheckpointSettings {
+    pub max_checkpoints: usize,
+    pub directory: PathBuf,
+}
+
+impl Default for CheckpointSettings {
+    fn default() -> Self {
+        Self {
+            max_checkpoints: DEFAULT_MAX_CHECKPOINTS,
+            directory: PathBuf::from(DEFAULT_SAVE_DIRECTORY),
+        }
+    }
+}
+
+impl CheckpointSettings {
+    pub fn checkpoint_path(&self, wave: u32) -> PathBuf {
+        self.directory.join(format!("checkpoint_{:03}.ron", wave))
+    }
+}
+
+// Brought back from a checkpoint with the stats it had, so nothing rolls or scales them again
+#[derive(Component)]
+pub struct Restored;
+
+type SavedUnitData = (
+    &'static CurrentUnitType,
+    &'static CurrentTeam,
+    &'static Transform,
+    &'static Health,
+    Option<&'static Nameplate>,
+    Option<&'static BaseMaxHealth>,
+    Option<&'static StatModifiers>,
+    Option<&'static Veterancy>,
+    Has<Gifted>,
+);
+
+// The last few snapshots of the run, oldest first
+#[derive(Resource, Default)]
+pub struct Checkpoints {
+    pub snapshots: VecDeque<RunSnapshot>,
+    pub last_wave: u32,
+}
+
+impl Checkpoints {
+    pub fn latest(&self) -> Option<&RunSnapshot> {
+        self.snapshots.back()
+    }
+
+    pub fn can_restart(&self, difficulty: &Difficulty) -> bool {
+        difficulty.allows_checkpoint_restart() && !self.snapshots.is_empty()
+    }
+}
+
+fn remove_checkpoint_file(settings: &CheckpointSettings, wave: u32) {
+    let path = settings.checkpoint_path(wave);
+    if path.exists() {
+        if let Err(error) = fs::remove_file(&path) {
+            warn!("Could not remove checkpoint {}: {}", path.display(), error);
+        }
+    }
+}
+
+// Takes a snapshot on the frame a new wave starts, before any of its enemies have spawned
+#[allow(clippy::too_many_arguments)]
+pub fn autosave_checkpoint_system(
+    settings: Res<CheckpointSettings>,
+    mut checkpoints: ResMut<Checkpoints>,
+    spawner_query: Query<&EnemySpawner>,
+    game_state_query: Query<&GameState>,
+    player_query: Query<(&Transform, &Health, &Mana), With<Player>>,
+    altar_query: Query<&Health, With<DarkAltar>>,
+    units_query: Query<SavedUnitData>,
+    structures_query: Query<(&Structure, &Transform, Option<&Health>)>,
+) {
+    let Some(spawner) = spawner_query.iter().next() else {
+        return;
+    };
+
+    if spawner.wave == checkpoints.last_wave {
+        return;
+    }
+    checkpoints.last_wave = spawner.wave;
+
+    let Some(game_state) = game_state_query.iter().next() else {
+        return;
+    };
+    let Some(player) = player_query.iter().next() else {
+        return;
+    };
+    if spawner.wave == 0 || game_state.game_over || settings.max_checkpoints == 0 {
+        return;
+    }
+
+    let mut snapshot = capture_snapshot(
+        spawner,
+        game_state,
+        altar_query.iter().next(),
+        player,
+        units_query.iter(),
+    );
+    snapshot.structures = capture_structures(structures_query.iter());
+    let path = settings.checkpoint_path(snapshot.wave);
+    if let Err(error) = write_snapshot(&path, &snapshot) {
+        warn!("Could not write checkpoint {}: {}", path.display(), error);
+    }
+
+    // Coming back to a wave after restarting replaces its old checkpoint
+    if let Some(index) = checkpoints
+        .snapshots
+        .iter()
+        .position(|checkpoint| checkpoint.wave == snapshot.wave)
+    {
+        checkpoints.snapshots.remove(index);
+    }
+    checkpoints.snapshots.push_back(snapshot);
+
+    while checkpoints.snapshots.len() > settings.max_checkpoints {
+        if let Some(evicted) = checkpoints.snapshots.pop_front() {
+            remove_checkpoint_file(&settings, evicted.wave);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn restore_checkpoint_system(
+    mut commands: Commands,
+    mut event_reader: EventReader<GameEvent>,
+    settings: Res<CheckpointSettings>,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: AtlasLayouts,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut checkpoints: ResMut<Checkpoints>,
+    mut next_mutator: ResMut<NextWaveMutator>,
+    level: Res<ActiveLevel>,
+    run_seed: Res<RunSeed>,
+    cleanup_query: Query<Entity, With<Cleanup>>,
+) {
+    for event in event_reader.read() {
+        let GameEvent::RestartFromCheckpoint = event else {
+            continue;
+        };
+        let Some(latest) = checkpoints.latest() else {
+            continue;
+        };
+        let snapshot = checkpoint_to_restore(&settings, latest);
+
+        cleanup_game_system(&mut commands, &cleanup_query);
+        info!("Restarting from the checkpoint of wave {}", snapshot.wave);
+
+        commands.spawn((
+            GameState {
+                score: snapshot.score,
+                ..default()
+            },
+            Cleanup {},
+        ));
+
+        // Rewind the spawner to just before the checkpoint wave, so that wave spawns again with
+        // the same mutator once the grace period is over. Endless stacks come from the run seed,
+        // so they're rolled back up to where they were instead of being saved.
+        let wave = snapshot.wave - 1;
+        let mut spawner = EnemySpawner {
+            wave,
+            escalation: Escalation::at_depth(run_seed.current, level.waves.endless_depth(wave)),
+            ..EnemySpawner::new(&level.waves)
+        };
+        let wave_interval = spawner.wave_charge.interval();
+        spawner
+            .wave_charge
+            .set_elapsed(wave_interval - RESTART_GRACE_SECONDS);
+        commands.spawn((spawner, Cleanup {}));
+        next_mutator.0 = snapshot.mutator;
+        checkpoints.last_wave = snapshot.wave - 1;
+
+        spawn_altar(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            snapshot.altar_health,
+        );
+
+        let player = &snapshot.player;
+        let mut player_commands =
+            spawn_player(&mut commands, &asset_server, &mut texture_atlas_layouts);
+        player_commands.insert(Health {
+            current: player.health,
+            max: player.max_health,
+        });
+        player_commands.insert(Mana {
+            current_mana: player.mana,
+            max_mana: player.max_mana,
+        });
+        player_commands.insert(
+            Transform::from_translation(Vec2::from(player.position).extend(0.0))
+                .with_scale(Vec3::splat(2.0)),
+        );
+
+        for unit in snapshot.units.iter() {
+            restore_unit(
+                &mut commands,
+                &asset_server,
+                &mut texture_atlas_layouts,
+                unit,
+            );
+        }
+
+        for structure in snapshot.structures.iter() {
+            let mut structure_commands = spawn_structure(
+                &mut commands,
+                structure.structure_type,
+                Team::Evil,
+                Vec2::from(structure.position),
+            );
+            if let Some((current, max)) = structure.health {
+                structure_commands.insert(Health { current, max });
+            }
+        }
+    }
+}
+
+// The file is what a restart goes back to, the one kept around is only there in case it has gone
+// missing, can't be read anymore or doesn't hold a wave that could have been saved
+fn checkpoint_to_restore(settings: &CheckpointSettings, latest: &RunSnapshot) -> RunSnapshot {
+    let path = settings.checkpoint_path(latest.wave);
+    match read_snapshot(&path) {
+        Ok(snapshot) if snapshot.wave > 0 => snapshot,
+        Ok(_) => {
+            warn!("Checkpoint {} has no wave to restart", path.display());
+            latest.clone()
+        }
+        Err(error) => {
+            warn!("Could not read checkpoint {}: {}", path.display(), error);
+            latest.clone()
+        }
+    }
+}
+
+fn restore_unit(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    texture_atlas_layouts: &mut AtlasLayouts,
+    unit: &UnitSnapshot,
+) -> Entity {
+    let mut unit_commands = spawn_unit_of_type(
+        commands,
+        asset_server,
+        texture_atlas_layouts,
+        unit.unit_type,
+        unit.team,
+        Vec2::from(unit.position),
+    );
+    unit_commands.insert(Health {
+        current: unit.health,
+        max: unit.max_health,
+    });
+    if let Some(name) = unit.name.clone() {
+        unit_commands.insert(Nameplate(name));
+    }
+
+    // Older checkpoints only have the max health with everything already on top, those units
+    // roll their stats like a new one would
+    let Some(base_max_health) = unit.base_max_health else {
+        return unit_commands.id();
+    };
+    let mut modifiers = StatModifiers::default();
+    for (source, _) in unit.rolled.iter() {
+        if modifiers.has(*source) {
+            continue;
+        }
+        let rolled: Vec<_> = unit
+            .rolled
+            .iter()
+            .filter(|(other, _)| other == source)
+            .map(|(_, modifier)| *modifier)
+            .collect();
+        modifiers.set(*source, &rolled);
+    }
+    unit_commands.insert((modifiers, BaseMaxHealth(base_max_health), Restored));
+    if unit.team == Team::Evil {
+        unit_commands.insert(Veterancy { kills: unit.kills });
+    }
+    if unit.gifted {
+        unit_commands.insert(Gifted);
+    }
+    unit_commands.id()
+}
+
+pub fn clear_checkpoints_system(
+    mut event_reader: EventReader<GameEvent>,
+    settings: Res<CheckpointSettings>,
+    mut checkpoints: ResMut<Checkpoints>,
+) {
+    for event in event_reader.read() {
+        if let GameEvent::StartGame = event {
+            for snapshot in checkpoints.snapshots.drain(..) {
+                remove_checkpoint_file(&settings, snapshot.wave);
+            }
+            checkpoints.last_wave = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::*;
+
+    use super::{checkpoint_to_restore, restore_unit, CheckpointSettings};
+    use crate::animation::AtlasLayouts;
+    use crate::difficulty::Difficulty;
+    use crate::save::snapshot::{write_snapshot, PlayerSnapshot, RunSnapshot, UnitSnapshot};
+    use crate::test_utils::TestApp;
+    use crate::units::health::Health;
+    use crate::units::quality::Gifted;
+    use crate::units::stat_modifiers::{Modifier, ModifierSource, Stat, StatModifiers};
+    use crate::units::team::Team;
+    use crate::units::unit_types::UnitType;
+
+    fn saved_unit(
+        unit_type: UnitType,
+        team: Team,
+        max_health: i32,
+        rolled: Vec<(ModifierSource, Modifier)>,
+        gifted: bool,
+    ) -> UnitSnapshot {
+        UnitSnapshot {
+            unit_type,
+            team,
+            position: [0.0, 0.0],
+            health: max_health,
+            max_health,
+            name: None,
+            base_max_health: Some(100),
+            rolled,
+            gifted,
+            kills: 0,
+        }
+    }
+
+    fn restore(app: &mut TestApp, unit: UnitSnapshot) -> Entity {
+        app.app.world.run_system_once_with(
+            unit,
+            |In(unit): In<UnitSnapshot>,
+             mut commands: Commands,
+             asset_server: Res<AssetServer>,
+             mut texture_atlas_layouts: AtlasLayouts| {
+                restore_unit(
+                    &mut commands,
+                    &asset_server,
+                    &mut texture_atlas_layouts,
+                    &unit,
+                )
+            },
+        )
+    }
+
+    #[test]
+    fn gifted_summon_comes_back_with_the_stats_it_rolled() {
+        let mut app = TestApp::new();
+        let rolled = vec![(
+            ModifierSource::Quality,
+            Modifier::multiply(Stat::MaxHealth, 1.15),
+        )];
+        let warrior = restore(
+            &mut app,
+            saved_unit(UnitType::Warrior, Team::Evil, 115, rolled, true),
+        );
+        app.tick(5);
+
+        assert_eq!(app.get::<Health>(warrior).max, 115);
+        assert!(app.app.world.get::<Gifted>(warrior).is_some());
+        assert_eq!(
+            app.get::<StatModifiers>(warrior)
+                .of(ModifierSource::Quality)
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn enemy_on_easy_is_not_scaled_down_twice() {
+        let mut app = TestApp::new();
+        app.app.insert_resource(Difficulty::Easy);
+        let rolled = vec![(
+            ModifierSource::Difficulty,
+            Modifier::multiply(Stat::MaxHealth, Difficulty::Easy.enemy_health()),
+        )];
+        let knight = restore(
+            &mut app,
+            saved_unit(UnitType::Knight, Team::Good, 75, rolled, false),
+        );
+        app.tick(5);
+
+        assert_eq!(app.get::<Health>(knight).max, 75);
+    }
+
+    #[test]
+    fn checkpoint_file_without_a_wave_falls_back_to_the_kept_one() {
+        let settings = CheckpointSettings {
+            directory: std::env::temp_dir().join("dark_arts_defense_checkpoint_wave_zero"),
+            ..default()
+        };
+        let latest = RunSnapshot {
+            wave: 2,
+            mutator: None,
+            score: 0,
+            altar_health: None,
+            player: PlayerSnapshot {
+                position: [0.0, 0.0],
+                health: 10,
+                max_health: 10,
+                mana: 0,
+                max_mana: 10,
+            },
+            units: Vec::new(),
+            structures: Vec::new(),
+        };
+        let corrupted = RunSnapshot {
+            wave: 0,
+            ..latest.clone()
+        };
+        write_snapshot(&settings.checkpoint_path(latest.wave), &corrupted).unwrap();
+
+        assert_eq!(checkpoint_to_restore(&settings, &latest).wave, 2);
+    }
+}
@@ -0,0 +1,20 @@
+use bevy::prelude::*;
+
+use crate::save::checkpoints;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<checkpoints::CheckpointSettings>()
+            .init_resource::<checkpoints::Checkpoints>()
+            .add_systems(
+                Update,
+                (
+                    checkpoints::autosave_checkpoint_system,
+                    checkpoints::restore_checkpoint_system,
+                    checkpoints::clear_checkpoints_system,
+                ),
+            );
+    }
+}
@@ -0,0 +1,215 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::enemies::enemy_spawner::EnemySpawner;
+use crate::enemies::mutators::WaveMutator;
+use crate::gamestate::GameState;
+use crate::mana::Mana;
+use crate::structures::structure_types::{Structure, StructureType};
+use crate::ui::nameplate::Nameplate;
+use crate::units::health::Health;
+use crate::units::stat_modifiers::{BaseMaxHealth, Modifier, ModifierSource, StatModifiers};
+use crate::units::team::{CurrentTeam, Team};
+use crate::units::unit_types::{CurrentUnitType, UnitType};
+use crate::units::veterancy::Veterancy;
+
+// Modifiers that are rolled or picked once when a unit spawns, the rest either follow from the
+// run itself or come and go while it's played
+const ROLLED_SOURCES: [ModifierSource; 2] = [ModifierSource::Quality, ModifierSource::Difficulty];
+
+// Everything needed to put a run back the way it was. Only plain data goes in here, entities
+// are spawned from scratch through the usual spawn functions when a snapshot is restored.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RunSnapshot {
+    pub wave: u32,
+    pub mutator: Option<WaveMutator>,
+    pub score: u32,
+    #[serde(default)]
+    pub altar_health: Option<i32>,
+    pub player: PlayerSnapshot,
+    pub units: Vec<UnitSnapshot>,
+    #[serde(default)]
+    pub structures: Vec<StructureSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PlayerSnapshot {
+    pub position: [f32; 2],
+    pub health: i32,
+    pub max_health: i32,
+    pub mana: u8,
+    pub max_mana: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnitSnapshot {
+    pub unit_type: UnitType,
+    pub team: Team,
+    pub position: [f32; 2],
+    pub health: i32,
+    pub max_health: i32,
+    pub name: Option<String>,
+    // Max health before any modifiers, max_health already has them all on top. Missing from
+    // checkpoints written before it was saved, those units are spawned as new ones.
+    #[serde(default)]
+    pub base_max_health: Option<i32>,
+    #[serde(default)]
+    pub rolled: Vec<(ModifierSource, Modifier)>,
+    #[serde(default)]
+    pub gifted: bool,
+    #[serde(default)]
+    pub kills: u32,
+}
+
+// Structures are always the player's, so there is no team to keep track of
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StructureSnapshot {
+    pub structure_type: StructureType,
+    pub position: [f32; 2],
+    // Current and max health, traps don't have any
+    pub health: Option<(i32, i32)>,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Serialize(ron::Error),
+    Deserialize(ron::error::SpannedError),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(error) => write!(f, "io error: {}", error),
+            SaveError::Serialize(error) => write!(f, "could not serialize snapshot: {}", error),
+            SaveError::Deserialize(error) => write!(f, "could not read snapshot: {}", error),
+        }
+    }
+}
+
+impl From<std::io::Error> for SaveError {
+    fn from(error: std::io::Error) -> Self {
+        SaveError::Io(error)
+    }
+}
+
+impl From<ron::Error> for SaveError {
+    fn from(error: ron::Error) -> Self {
+        SaveError::Serialize(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for SaveError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        SaveError::Deserialize(error)
+    }
+}
+
+pub fn write_snapshot(path: &Path, snapshot: &RunSnapshot) -> Result<(), SaveError> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+
+    let contents = ron::ser::to_string_pretty(snapshot, ron::ser::PrettyConfig::default())?;
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+pub fn read_snapshot(path: &Path) -> Result<RunSnapshot, SaveError> {
+    let contents = fs::read_to_string(path)?;
+    Ok(ron::from_str(&contents)?)
+}
+
+pub fn capture_snapshot<'a>(
+    spawner: &EnemySpawner,
+    game_state: &GameState,
+    altar_health: Option<&Health>,
+    player: (&Transform, &Health, &Mana),
+    units: impl Iterator<
+        Item = (
+            &'a CurrentUnitType,
+            &'a CurrentTeam,
+            &'a Transform,
+            &'a Health,
+            Option<&'a Nameplate>,
+            Option<&'a BaseMaxHealth>,
+            Option<&'a StatModifiers>,
+            Option<&'a Veterancy>,
+            bool,
+        ),
+    >,
+) -> RunSnapshot {
+    let (player_transform, player_health, player_mana) = player;
+    RunSnapshot {
+        wave: spawner.wave,
+        mutator: spawner.mutator,
+        score: game_state.score,
+        altar_health: altar_health.map(|health| health.current),
+        player: PlayerSnapshot {
+            position: player_transform.translation.truncate().to_array(),
+            health: player_health.current,
+            max_health: player_health.max,
+            mana: player_mana.current_mana,
+            max_mana: player_mana.max_mana,
+        },
+        units: units
+            // Dead units are only waiting for their death animation, no point in bringing them back
+            .filter(|(_, _, _, health, ..)| !health.is_dead())
+            .map(
+                |(
+                    unit_type,
+                    team,
+                    transform,
+                    health,
+                    nameplate,
+                    base,
+                    modifiers,
+                    veterancy,
+                    gifted,
+                )| {
+                    UnitSnapshot {
+                        unit_type: unit_type.0,
+                        team: team.0,
+                        position: transform.translation.truncate().to_array(),
+                        health: health.current,
+                        max_health: health.max,
+                        name: nameplate.map(|nameplate| nameplate.0.clone()),
+                        base_max_health: base.map(|base| base.0),
+                        rolled: modifiers.map(rolled_modifiers).unwrap_or_default(),
+                        gifted,
+                        kills: veterancy.map_or(0, |veterancy| veterancy.kills),
+                    }
+                },
+            )
+            .collect(),
+        structures: Vec::new(),
+    }
+}
+
+fn rolled_modifiers(modifiers: &StatModifiers) -> Vec<(ModifierSource, Modifier)> {
+    ROLLED_SOURCES
+        .iter()
+        .flat_map(|source| {
+            modifiers
+                .of(*source)
+                .into_iter()
+                .map(move |modifier| (*source, modifier))
+        })
+        .collect()
+}
+
+pub fn capture_structures<'a>(
+    structures: impl Iterator<Item = (&'a Structure, &'a Transform, Option<&'a Health>)>,
+) -> Vec<StructureSnapshot> {
+    structures
+        .map(|(structure, transform, health)| StructureSnapshot {
+            structure_type: structure.0,
+            position: transform.translation.truncate().to_array(),
+            health: health.map(|health| (health.current, health.max)),
+        })
+        .collect()
+}
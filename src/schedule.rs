@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+
+use crate::interpolation;
+
+// Steps a second of the simulation, whatever the frame rate is. The tests and headless runs
+// advance the clock a 60th of a second at a time, so every update there is exactly one step.
+const SIM_HZ: f64 = 60.0;
+
+// The order a frame goes in. The keyboard is read and turned into GameActions, the actions are
+// applied, and whatever follows where things are drawn goes last, so a key pressed this frame is
+// on screen this frame instead of the next one. The army itself moves in the fixed steps below.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameSet {
+    Input,
+    Actions,
+    Movement,
+    Presentation,
+}
+
+// The order a fixed step goes in. Orders from the frames before are picked up, every unit
+// decides what to do and sets off, and only once everyone has moved are the hits from this step
+// dealt, so no unit ever attacks from where it was before the step or hits someone already dead.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SimSet {
+    Input,
+    Ai,
+    Movement,
+    Combat,
+    Cleanup,
+}
+
+pub fn configure_schedule(app: &mut App) {
+    app.insert_resource(Time::<Fixed>::from_hz(SIM_HZ))
+        .configure_sets(
+            Update,
+            (
+                FrameSet::Input,
+                FrameSet::Actions,
+                FrameSet::Movement,
+                FrameSet::Presentation,
+            )
+                .chain(),
+        )
+        .configure_sets(
+            FixedUpdate,
+            (
+                SimSet::Input,
+                SimSet::Ai,
+                SimSet::Movement,
+                SimSet::Combat,
+                SimSet::Cleanup,
+            )
+                .chain(),
+        )
+        .add_systems(
+            FixedFirst,
+            (
+                interpolation::start_interpolating,
+                interpolation::restore_positions,
+            )
+                .chain(),
+        )
+        .add_systems(FixedLast, interpolation::record_positions)
+        .add_systems(
+            Update,
+            interpolation::interpolate_positions.before(FrameSet::Input),
+        );
+}
@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::units::team::{Team, ROGUE, ROGUE_COLOR};
+
+use super::config::GameSettings;
+
+// The colors every team is told apart by, on the outlines, the minimap and the arrows
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TeamPalette {
+    #[default]
+    Classic,
+    // Red and green look alike for both of these, blue against orange doesn't
+    Deuteranopia,
+    Protanopia,
+    // Blue and yellow are the ones that get mixed up, red against teal is kept apart
+    Tritanopia,
+}
+
+impl TeamPalette {
+    pub const ALL: [TeamPalette; 4] = [
+        TeamPalette::Classic,
+        TeamPalette::Deuteranopia,
+        TeamPalette::Protanopia,
+        TeamPalette::Tritanopia,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TeamPalette::Classic => "Classic",
+            TeamPalette::Deuteranopia => "Deuteranopia",
+            TeamPalette::Protanopia => "Protanopia",
+            TeamPalette::Tritanopia => "Tritanopia",
+        }
+    }
+
+    pub fn color(&self, team: Team) -> Color {
+        match (self, team) {
+            (TeamPalette::Classic, Team::Evil) => Color::rgb(0.3, 0.9, 0.4),
+            (TeamPalette::Classic, Team::Good) => Color::rgb(0.95, 0.25, 0.2),
+            (TeamPalette::Deuteranopia | TeamPalette::Protanopia, Team::Evil) => {
+                Color::rgb(0.35, 0.6, 1.0)
+            }
+            (TeamPalette::Deuteranopia, Team::Good) => Color::rgb(1.0, 0.6, 0.1),
+            // Reds go dark for protanopes, so the enemies are a bright yellow instead
+            (TeamPalette::Protanopia, Team::Good) => Color::rgb(1.0, 0.9, 0.2),
+            (TeamPalette::Tritanopia, Team::Evil) => Color::rgb(0.2, 0.85, 0.85),
+            (TeamPalette::Tritanopia, Team::Good) => Color::rgb(1.0, 0.3, 0.45),
+            (_, Team::Neutral) => Color::rgb(0.6, 0.6, 0.6),
+            (TeamPalette::Classic, team) if team == ROGUE => ROGUE_COLOR,
+            (_, team) if team == ROGUE => Color::rgb(0.95, 0.95, 0.95),
+            (TeamPalette::Classic, Team::Faction(_)) => Color::rgb(1.0, 0.6, 0.1),
+            (_, Team::Faction(_)) => Color::rgb(0.8, 0.4, 0.9),
+        }
+    }
+}
+
+// What other systems read to make the game easier to follow, set from the settings menu and
+// kept in the settings file with the rest
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    pub palette: TeamPalette,
+    pub screen_shake: bool,
+    // Every unit gets a thick outline in its team's color
+    pub high_contrast_outlines: bool,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            palette: TeamPalette::default(),
+            screen_shake: true,
+            high_contrast_outlines: false,
+        }
+    }
+}
+
+pub fn sync_accessibility_settings(
+    settings: Res<GameSettings>,
+    mut accessibility: ResMut<AccessibilitySettings>,
+) {
+    if settings.is_changed() && *accessibility != settings.accessibility {
+        *accessibility = settings.accessibility;
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::menu::SETTINGS_KEY;
+
+// Everything the summoner does from the keyboard that can be moved to another key. The menus
+// keep their own keys so they can't be rebound into a corner.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Binding {
+    MoveUp,
+    MoveLeft,
+    MoveDown,
+    MoveRight,
+    Dash,
+    Summon1,
+    Summon2,
+    Summon3,
+    Summon4,
+    Summon5,
+    Charm,
+    Ultimate,
+    BuildMode,
+    CommandMode,
+    Formation,
+    Revive,
+    Familiar,
+    StartWaveEarly,
+    Inspect,
+    Rename,
+    ToggleNameplates,
+}
+
+// Keys that do the same thing all through a run and can't be taken by a binding: escape, the
+// settings menu, the latency probe, the debug overlay, flash safe mode, photo mode, screenshots
+// and the console. The end screen keys aren't here, by then there's nothing left to control.
+pub const RESERVED_KEYS: [KeyCode; 8] = [
+    KeyCode::Escape,
+    SETTINGS_KEY,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F8,
+    KeyCode::F10,
+    KeyCode::F12,
+    KeyCode::Backquote,
+];
+
+impl Binding {
+    pub const ALL: [Binding; 21] = [
+        Binding::MoveUp,
+        Binding::MoveLeft,
+        Binding::MoveDown,
+        Binding::MoveRight,
+        Binding::Dash,
+        Binding::Summon1,
+        Binding::Summon2,
+        Binding::Summon3,
+        Binding::Summon4,
+        Binding::Summon5,
+        Binding::Charm,
+        Binding::Ultimate,
+        Binding::BuildMode,
+        Binding::CommandMode,
+        Binding::Formation,
+        Binding::Revive,
+        Binding::Familiar,
+        Binding::StartWaveEarly,
+        Binding::Inspect,
+        Binding::Rename,
+        Binding::ToggleNameplates,
+    ];
+
+    // Up, left, down and right, the order the movement vector is built in
+    pub const MOVEMENT: [Binding; 4] = [
+        Binding::MoveUp,
+        Binding::MoveLeft,
+        Binding::MoveDown,
+        Binding::MoveRight,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Binding::MoveUp => "Move up",
+            Binding::MoveLeft => "Move left",
+            Binding::MoveDown => "Move down",
+            Binding::MoveRight => "Move right",
+            Binding::Dash => "Dash",
+            Binding::Summon1 => "Summon 1",
+            Binding::Summon2 => "Summon 2",
+            Binding::Summon3 => "Summon 3",
+            Binding::Summon4 => "Summon 4",
+            Binding::Summon5 => "Summon 5",
+            Binding::Charm => "Charm",
+            Binding::Ultimate => "Frenzy",
+            Binding::BuildMode => "Build mode",
+            Binding::CommandMode => "Command mode",
+            Binding::Formation => "Formation",
+            Binding::Revive => "Revive",
+            Binding::Familiar => "Train familiar",
+            Binding::StartWaveEarly => "Start wave early",
+            Binding::Inspect => "Lock inspection",
+            Binding::Rename => "Rename last summon",
+            Binding::ToggleNameplates => "Toggle nameplates",
+        }
+    }
+
+    pub fn default_key(&self) -> KeyCode {
+        match self {
+            Binding::MoveUp => KeyCode::KeyW,
+            Binding::MoveLeft => KeyCode::KeyA,
+            Binding::MoveDown => KeyCode::KeyS,
+            Binding::MoveRight => KeyCode::KeyD,
+            Binding::Dash => KeyCode::Space,
+            Binding::Summon1 => KeyCode::Digit1,
+            Binding::Summon2 => KeyCode::Digit2,
+            Binding::Summon3 => KeyCode::Digit3,
+            Binding::Summon4 => KeyCode::Digit4,
+            Binding::Summon5 => KeyCode::Digit5,
+            Binding::Charm => KeyCode::KeyQ,
+            Binding::Ultimate => KeyCode::KeyE,
+            Binding::BuildMode => KeyCode::KeyB,
+            Binding::CommandMode => KeyCode::KeyG,
+            Binding::Formation => KeyCode::KeyF,
+            Binding::Revive => KeyCode::KeyH,
+            Binding::Familiar => KeyCode::KeyU,
+            Binding::StartWaveEarly => KeyCode::KeyN,
+            Binding::Inspect => KeyCode::KeyK,
+            Binding::Rename => KeyCode::KeyR,
+            Binding::ToggleNameplates => KeyCode::KeyV,
+        }
+    }
+}
+
+// Only the keys that were moved are kept, so anything added later starts out on its default
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct KeyBindings {
+    keys: HashMap<Binding, KeyCode>,
+}
+
+impl KeyBindings {
+    pub fn key(&self, binding: Binding) -> KeyCode {
+        self.keys
+            .get(&binding)
+            .copied()
+            .unwrap_or_else(|| binding.default_key())
+    }
+
+    pub fn pressed(&self, keys: &ButtonInput<KeyCode>, binding: Binding) -> bool {
+        keys.pressed(self.key(binding))
+    }
+
+    pub fn just_pressed(&self, keys: &ButtonInput<KeyCode>, binding: Binding) -> bool {
+        keys.just_pressed(self.key(binding))
+    }
+
+    // Whatever had the key before gets the one this is moving off of, so no two bindings ever
+    // end up on the same key. Reserved keys are left alone.
+    pub fn rebind(&mut self, binding: Binding, key: KeyCode) {
+        if RESERVED_KEYS.contains(&key) {
+            return;
+        }
+
+        let previous = self.key(binding);
+        if let Some(other) = Binding::ALL
+            .into_iter()
+            .find(|other| *other != binding && self.key(*other) == key)
+        {
+            self.set(other, previous);
+        }
+        self.set(binding, key);
+    }
+
+    pub fn reset(&mut self) {
+        self.keys.clear();
+    }
+
+    fn set(&mut self, binding: Binding, key: KeyCode) {
+        if key == binding.default_key() {
+            self.keys.remove(&binding);
+        } else {
+            self.keys.insert(binding, key);
+        }
+    }
+}
+
+// KeyQ shows as Q and Digit1 as 1, the rest go by their own names
+pub fn key_name(key: KeyCode) -> String {
+    let name = format!("{:?}", key);
+    name.strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .unwrap_or(&name)
+        .to_owned()
+}
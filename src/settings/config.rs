@@ -0,0 +1,202 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bevy::audio::{GlobalVolume, Volume};
+use bevy::prelude::*;
+use bevy::window::{PresentMode, WindowMode};
+use serde::{Deserialize, Serialize};
+
+use crate::render_scale::RenderScaleSettings;
+use crate::save::checkpoints::CheckpointSettings;
+use crate::save::snapshot::SaveError;
+
+use super::accessibility::AccessibilitySettings;
+use super::bindings::KeyBindings;
+
+const SETTINGS_FILE: &str = "settings.ron";
+// Within the window's resize constraints
+pub const RESOLUTIONS: [(u32, u32); 5] = [
+    (1280, 720),
+    (1600, 900),
+    (1920, 1080),
+    (2560, 1440),
+    (3840, 2160),
+];
+pub const VOLUME_STEP: f32 = 0.1;
+// Auto first, then the fixed ones from the smallest up
+pub const UI_SCALES: [Option<f32>; 6] = [
+    None,
+    Some(0.75),
+    Some(1.0),
+    Some(1.25),
+    Some(1.5),
+    Some(2.0),
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub master: f32,
+    // Kept for the music and the sounds, which scale with the master volume once there are some
+    pub music: f32,
+    pub effects: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 0.8,
+            effects: 1.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DisplayMode {
+    Windowed,
+    #[default]
+    Borderless,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    pub const ALL: [DisplayMode; 3] = [
+        DisplayMode::Windowed,
+        DisplayMode::Borderless,
+        DisplayMode::Fullscreen,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DisplayMode::Windowed => "Windowed",
+            DisplayMode::Borderless => "Borderless",
+            DisplayMode::Fullscreen => "Fullscreen",
+        }
+    }
+
+    fn window_mode(&self) -> WindowMode {
+        match self {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen,
+            DisplayMode::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct DisplaySettings {
+    pub mode: DisplayMode,
+    // Only does anything in a window, borderless and fullscreen take the monitor's
+    pub resolution: (u32, u32),
+    pub vsync: bool,
+    // None picks it from the window's height
+    pub ui_scale: Option<f32>,
+    // Drops the world's resolution when the frame rate does
+    pub render_scale: RenderScaleSettings,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            mode: DisplayMode::default(),
+            resolution: (1920, 1080),
+            vsync: true,
+            ui_scale: None,
+            render_scale: RenderScaleSettings::default(),
+        }
+    }
+}
+
+// What the player set up for themselves, kept next to the checkpoints and the meta progress
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct GameSettings {
+    pub audio: AudioSettings,
+    pub display: DisplaySettings,
+    pub accessibility: AccessibilitySettings,
+    pub bindings: KeyBindings,
+}
+
+impl GameSettings {
+    pub fn path(settings: &CheckpointSettings) -> PathBuf {
+        settings.directory.join(SETTINGS_FILE)
+    }
+
+    pub fn read(path: &Path) -> Result<Self, SaveError> {
+        let contents = fs::read_to_string(path)?;
+        Ok(ron::from_str(&contents)?)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), SaveError> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    pub fn save(&self, settings: &CheckpointSettings) {
+        let path = Self::path(settings);
+        if let Err(error) = self.write(&path) {
+            warn!("Could not write settings {}: {}", path.display(), error);
+        }
+    }
+}
+
+pub fn load_settings_system(
+    checkpoint_settings: Res<CheckpointSettings>,
+    mut settings: ResMut<GameSettings>,
+) {
+    let path = GameSettings::path(&checkpoint_settings);
+    if !path.exists() {
+        return;
+    }
+
+    match GameSettings::read(&path) {
+        Ok(loaded) => *settings = loaded,
+        Err(error) => warn!("Could not read settings {}: {}", path.display(), error),
+    }
+}
+
+// Only touches what's different, the window gets recreated on some platforms when its mode is set
+pub fn apply_display_settings(settings: Res<GameSettings>, mut query: Query<&mut Window>) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let display = settings.display;
+    let present_mode = if display.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+    let (width, height) = (display.resolution.0 as f32, display.resolution.1 as f32);
+    for mut window in query.iter_mut() {
+        if window.mode != display.mode.window_mode() {
+            window.mode = display.mode.window_mode();
+        }
+        if window.present_mode != present_mode {
+            window.present_mode = present_mode;
+        }
+        if display.mode == DisplayMode::Windowed
+            && (window.resolution.width() != width || window.resolution.height() != height)
+        {
+            window.resolution.set(width, height);
+        }
+    }
+}
+
+pub fn apply_audio_settings(settings: Res<GameSettings>, mut volume: ResMut<GlobalVolume>) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let master = settings.audio.master.clamp(0.0, 1.0);
+    if volume.volume.get() != master {
+        volume.volume = Volume::new(master);
+    }
+}
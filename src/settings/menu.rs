@@ -0,0 +1,384 @@
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+
+use crate::render_scale::{UiView, UI_LAYER};
+use crate::save::checkpoints::CheckpointSettings;
+use crate::tutorial::TutorialState;
+
+use super::accessibility::TeamPalette;
+use super::bindings::{key_name, Binding, RESERVED_KEYS};
+use super::config::{DisplayMode, GameSettings, RESOLUTIONS, UI_SCALES, VOLUME_STEP};
+
+// Opens the menu mid run, the level select opens it with O
+pub const SETTINGS_KEY: KeyCode = KeyCode::KeyP;
+// Only while the tutorial is running, the run carries on from wherever it was left
+const SKIP_TUTORIAL_KEY: KeyCode = KeyCode::KeyT;
+// The controls don't all fit on a small window, the list scrolls with the selection
+const VISIBLE_ROWS: usize = 12;
+const BACKDROP_COLOR: Color = Color::rgba(0.03, 0.02, 0.05, 0.92);
+// Above everything else the ui draws
+const MENU_Z: f32 = 50.0;
+
+// Sits on top of whatever state the game is in, the run is paused while it's open
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SettingsState {
+    #[default]
+    Closed,
+    Open,
+}
+
+pub fn settings_closed(state: Res<State<SettingsState>>) -> bool {
+    *state.get() == SettingsState::Closed
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SettingsTab {
+    #[default]
+    Audio,
+    Display,
+    Accessibility,
+    Controls,
+}
+
+impl SettingsTab {
+    const ALL: [SettingsTab; 4] = [
+        SettingsTab::Audio,
+        SettingsTab::Display,
+        SettingsTab::Accessibility,
+        SettingsTab::Controls,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            SettingsTab::Audio => "Audio",
+            SettingsTab::Display => "Display",
+            SettingsTab::Accessibility => "Accessibility",
+            SettingsTab::Controls => "Controls",
+        }
+    }
+
+    fn next(&self) -> Self {
+        match self {
+            SettingsTab::Audio => SettingsTab::Display,
+            SettingsTab::Display => SettingsTab::Accessibility,
+            SettingsTab::Accessibility => SettingsTab::Controls,
+            SettingsTab::Controls => SettingsTab::Audio,
+        }
+    }
+
+    fn rows(&self) -> usize {
+        match self {
+            SettingsTab::Audio => 3,
+            SettingsTab::Display => 5,
+            SettingsTab::Accessibility => 3,
+            // Every binding and the reset under them
+            SettingsTab::Controls => Binding::ALL.len() + 1,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct SettingsMenu {
+    pub tab: SettingsTab,
+    pub selected: usize,
+    // Waiting on the next key pressed to put this binding on
+    pub rebinding: Option<Binding>,
+}
+
+#[derive(Component)]
+pub struct SettingsMenuText;
+
+#[derive(Component)]
+pub struct SettingsBackdrop;
+
+// P during a run
+pub fn open_settings(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<SettingsState>>,
+) {
+    if keys.just_pressed(SETTINGS_KEY) {
+        next_state.set(SettingsState::Open);
+    }
+}
+
+pub fn spawn_settings_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut menu: ResMut<SettingsMenu>,
+) {
+    *menu = SettingsMenu::default();
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: BACKDROP_COLOR,
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, MENU_Z),
+            ..default()
+        },
+        SettingsBackdrop,
+        RenderLayers::layer(UI_LAYER),
+    ));
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_justify(JustifyText::Center),
+            transform: Transform::from_xyz(0.0, 0.0, MENU_Z + 1.0),
+            ..default()
+        },
+        SettingsMenuText,
+        RenderLayers::layer(UI_LAYER),
+    ));
+}
+
+type SettingsMenuFilter = Or<(With<SettingsMenuText>, With<SettingsBackdrop>)>;
+
+pub fn despawn_settings_menu(
+    mut commands: Commands,
+    checkpoint_settings: Res<CheckpointSettings>,
+    settings: Res<GameSettings>,
+    query: Query<Entity, SettingsMenuFilter>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    settings.save(&checkpoint_settings);
+}
+
+fn step_volume(volume: &mut f32, direction: f32) {
+    // Rounded to the step so it doesn't drift after going back and forth
+    let steps = (*volume / VOLUME_STEP).round() + direction;
+    *volume = (steps * VOLUME_STEP).clamp(0.0, 1.0);
+}
+
+fn cycle<T: Copy + PartialEq>(all: &[T], current: T, direction: f32) -> T {
+    let index = all.iter().position(|value| *value == current).unwrap_or(0);
+    let count = all.len();
+    let next = if direction < 0.0 {
+        (index + count - 1) % count
+    } else {
+        (index + 1) % count
+    };
+    all[next]
+}
+
+fn change_value(settings: &mut GameSettings, tab: SettingsTab, row: usize, direction: f32) {
+    match (tab, row) {
+        (SettingsTab::Audio, 0) => step_volume(&mut settings.audio.master, direction),
+        (SettingsTab::Audio, 1) => step_volume(&mut settings.audio.music, direction),
+        (SettingsTab::Audio, 2) => step_volume(&mut settings.audio.effects, direction),
+        (SettingsTab::Display, 0) => {
+            settings.display.mode = cycle(&DisplayMode::ALL, settings.display.mode, direction);
+        }
+        (SettingsTab::Display, 1) => {
+            settings.display.resolution =
+                cycle(&RESOLUTIONS, settings.display.resolution, direction);
+        }
+        (SettingsTab::Display, 2) => settings.display.vsync = !settings.display.vsync,
+        (SettingsTab::Display, 3) => {
+            settings.display.ui_scale = cycle(&UI_SCALES, settings.display.ui_scale, direction);
+        }
+        (SettingsTab::Display, 4) => {
+            let render_scale = &mut settings.display.render_scale;
+            render_scale.enabled = !render_scale.enabled;
+        }
+        (SettingsTab::Accessibility, 0) => {
+            let accessibility = &mut settings.accessibility;
+            accessibility.palette = cycle(&TeamPalette::ALL, accessibility.palette, direction);
+        }
+        (SettingsTab::Accessibility, 1) => {
+            settings.accessibility.screen_shake = !settings.accessibility.screen_shake;
+        }
+        (SettingsTab::Accessibility, 2) => {
+            let accessibility = &mut settings.accessibility;
+            accessibility.high_contrast_outlines = !accessibility.high_contrast_outlines;
+        }
+        _ => {}
+    }
+}
+
+fn percent(volume: f32) -> String {
+    format!("{:.0}%", volume * 100.0)
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+fn row_lines(settings: &GameSettings, menu: &SettingsMenu, ui_view: &UiView) -> Vec<String> {
+    match menu.tab {
+        SettingsTab::Audio => vec![
+            format!("Master volume < {} >", percent(settings.audio.master)),
+            format!("Music volume < {} >", percent(settings.audio.music)),
+            format!("Effects volume < {} >", percent(settings.audio.effects)),
+        ],
+        SettingsTab::Display => {
+            let (width, height) = settings.display.resolution;
+            vec![
+                format!("Mode < {} >", settings.display.mode.name()),
+                format!("Resolution < {}x{} >", width, height),
+                format!("VSync < {} >", on_off(settings.display.vsync)),
+                match settings.display.ui_scale {
+                    Some(scale) => format!("UI scale < {:.2}x >", scale),
+                    None => format!("UI scale < Auto ({:.2}x) >", ui_view.scale),
+                },
+                format!(
+                    "Dynamic resolution < {} >",
+                    on_off(settings.display.render_scale.enabled)
+                ),
+            ]
+        }
+        SettingsTab::Accessibility => {
+            let accessibility = &settings.accessibility;
+            vec![
+                format!("Team colors < {} >", accessibility.palette.name()),
+                format!("Screen shake < {} >", on_off(accessibility.screen_shake)),
+                format!(
+                    "High contrast outlines < {} >",
+                    on_off(accessibility.high_contrast_outlines)
+                ),
+            ]
+        }
+        SettingsTab::Controls => Binding::ALL
+            .iter()
+            .map(|binding| {
+                let key = if menu.rebinding == Some(*binding) {
+                    "press a key...".to_owned()
+                } else {
+                    key_name(settings.bindings.key(*binding))
+                };
+                format!("{}: {}", binding.name(), key)
+            })
+            .chain(std::iter::once("Reset to defaults".to_owned()))
+            .collect(),
+    }
+}
+
+// TAB to switch tabs, W/S or the arrow keys to pick a setting, A/D to change it, ENTER to rebind
+// a key, T to skip the tutorial, ESCAPE or P to close and save
+#[allow(clippy::too_many_arguments)]
+pub fn settings_menu_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut menu: ResMut<SettingsMenu>,
+    mut settings: ResMut<GameSettings>,
+    mut next_state: ResMut<NextState<SettingsState>>,
+    (tutorial_state, mut next_tutorial_state): (
+        Res<State<TutorialState>>,
+        ResMut<NextState<TutorialState>>,
+    ),
+    ui_view: Res<UiView>,
+    mut backdrop_query: Query<&mut Sprite, With<SettingsBackdrop>>,
+    mut text_query: Query<&mut Text, With<SettingsMenuText>>,
+) {
+    if let Some(binding) = menu.rebinding {
+        // Escape can't be bound, it's how the rebind is called off. The other reserved keys are
+        // passed over and the menu keeps waiting for one that can be bound.
+        if keys.just_pressed(KeyCode::Escape) {
+            menu.rebinding = None;
+        } else if let Some(key) = keys
+            .get_just_pressed()
+            .find(|key| !RESERVED_KEYS.contains(key))
+            .copied()
+        {
+            settings.bindings.rebind(binding, key);
+            menu.rebinding = None;
+        }
+    } else {
+        let rows = menu.tab.rows();
+        if keys.just_pressed(KeyCode::Tab) {
+            menu.tab = menu.tab.next();
+            menu.selected = 0;
+        }
+        if keys.any_just_pressed([KeyCode::KeyW, KeyCode::ArrowUp]) {
+            menu.selected = (menu.selected + rows - 1) % rows;
+        }
+        if keys.any_just_pressed([KeyCode::KeyS, KeyCode::ArrowDown]) {
+            menu.selected = (menu.selected + 1) % rows;
+        }
+        if keys.any_just_pressed([KeyCode::KeyA, KeyCode::ArrowLeft]) {
+            change_value(&mut settings, menu.tab, menu.selected, -1.0);
+        }
+        if keys.any_just_pressed([KeyCode::KeyD, KeyCode::ArrowRight]) {
+            change_value(&mut settings, menu.tab, menu.selected, 1.0);
+        }
+        if keys.any_just_pressed([KeyCode::Space, KeyCode::Enter])
+            && menu.tab == SettingsTab::Controls
+        {
+            match Binding::ALL.get(menu.selected) {
+                Some(binding) => menu.rebinding = Some(*binding),
+                None => settings.bindings.reset(),
+            }
+        }
+        let in_tutorial = *tutorial_state.get() == TutorialState::Running;
+        if in_tutorial && keys.just_pressed(SKIP_TUTORIAL_KEY) {
+            next_tutorial_state.set(TutorialState::Off);
+            next_state.set(SettingsState::Closed);
+        }
+        if keys.any_just_pressed([KeyCode::Escape, SETTINGS_KEY]) {
+            next_state.set(SettingsState::Closed);
+        }
+    }
+
+    for mut sprite in backdrop_query.iter_mut() {
+        let size = Some(ui_view.size());
+        if sprite.custom_size != size {
+            sprite.custom_size = size;
+        }
+    }
+
+    let tabs = SettingsTab::ALL
+        .iter()
+        .map(|tab| {
+            if *tab == menu.tab {
+                format!("[{}]", tab.name())
+            } else {
+                format!(" {} ", tab.name())
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("  ");
+    let mut lines = vec!["Settings".to_owned(), tabs, String::new()];
+
+    let rows = row_lines(&settings, &menu, &ui_view);
+    let first = menu
+        .selected
+        .saturating_sub(VISIBLE_ROWS / 2)
+        .min(rows.len().saturating_sub(VISIBLE_ROWS));
+    for (index, row) in rows.iter().enumerate().skip(first).take(VISIBLE_ROWS) {
+        let marker = if index == menu.selected { ">" } else { " " };
+        lines.push(format!("{} {}", marker, row));
+    }
+
+    lines.push(String::new());
+    lines.push(
+        match menu.tab {
+            SettingsTab::Controls if menu.rebinding.is_some() => {
+                "ESCAPE to keep the old key, P, ` and the F keys are taken"
+            }
+            SettingsTab::Controls => "ENTER to rebind, TAB for the next tab, ESCAPE to close",
+            _ => "A/D to change, TAB for the next tab, ESCAPE to close",
+        }
+        .to_owned(),
+    );
+    if *tutorial_state.get() == TutorialState::Running && menu.rebinding.is_none() {
+        lines.push("T to skip the tutorial".to_owned());
+    }
+
+    let value = lines.join("\n");
+    for mut text in text_query.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
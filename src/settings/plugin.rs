@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+
+use crate::dark_arts_defense::AppState;
+use crate::schedule::FrameSet;
+use crate::settings::{accessibility, config, menu};
+use crate::ui::nameplate::not_renaming;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<config::GameSettings>()
+            .init_resource::<accessibility::AccessibilitySettings>()
+            .init_resource::<menu::SettingsMenu>()
+            .init_state::<menu::SettingsState>()
+            // Nothing in the run reads the keyboard while the menu has it
+            .configure_sets(Update, FrameSet::Input.run_if(menu::settings_closed))
+            .add_systems(Startup, config::load_settings_system)
+            .add_systems(
+                OnEnter(menu::SettingsState::Open),
+                menu::spawn_settings_menu,
+            )
+            .add_systems(
+                OnExit(menu::SettingsState::Open),
+                menu::despawn_settings_menu,
+            )
+            .add_systems(
+                Update,
+                (
+                    menu::open_settings
+                        .run_if(not_renaming)
+                        .run_if(in_state(AppState::Playing))
+                        .in_set(FrameSet::Input),
+                    menu::settings_menu_system.run_if(in_state(menu::SettingsState::Open)),
+                    config::apply_display_settings,
+                    config::apply_audio_settings,
+                    accessibility::sync_accessibility_settings.before(FrameSet::Input),
+                ),
+            );
+    }
+}
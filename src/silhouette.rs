@@ -0,0 +1,238 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::{AsBindGroup, ShaderRef};
+use bevy::sprite::{Material2d, MaterialMesh2dBundle, Mesh2dHandle};
+
+use crate::animation::Animation;
+use crate::settings::accessibility::AccessibilitySettings;
+use crate::structures::structure_types::Structure;
+use crate::units::team::{CurrentTeam, Team};
+
+// Above the tall props, so the silhouette shows through whatever hides the unit
+const SILHOUETTE_Z: f32 = 3.0;
+const SILHOUETTE_COLOR: Color = Color::rgba(0.45, 0.85, 1.0, 0.6);
+// The outline is the same flat shape drawn a bit bigger just behind the unit, in pixels each side
+const OUTLINE_WIDTH: f32 = 2.0;
+const OUTLINE_Z: f32 = -0.0001;
+
+// Anything drawn over the units that a unit can walk behind. The rect is the part of the sprite
+// that covers what is behind it, relative to the occluder's position.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Occluder {
+    pub rect: Rect,
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct SilhouetteMaterial {
+    #[uniform(0)]
+    pub color: Color,
+    // Offset and size of the current animation frame in the sprite sheet, in uv space
+    #[uniform(1)]
+    pub uv_rect: Vec4,
+    #[texture(2)]
+    #[sampler(3)]
+    pub texture: Handle<Image>,
+}
+
+impl Material2d for SilhouetteMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/silhouette.wgsl".into()
+    }
+}
+
+// Lives on the unit and points at its silhouette child while the unit is hidden
+#[derive(Component)]
+pub struct SilhouetteLink {
+    pub silhouette: Entity,
+    pub material: Handle<SilhouetteMaterial>,
+}
+
+// Lives on the unit and points at its outline child while high contrast outlines are on
+#[derive(Component)]
+pub struct OutlineLink {
+    pub outline: Entity,
+    pub material: Handle<SilhouetteMaterial>,
+}
+
+#[derive(Resource)]
+pub struct SilhouetteMesh(pub Mesh2dHandle);
+
+impl FromWorld for SilhouetteMesh {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        Self(Mesh2dHandle(meshes.add(Rectangle::new(1.0, 1.0))))
+    }
+}
+
+// Whichever animation is playing right now is the shape to draw, as its texture, where the frame
+// is in it in uv space and how big the frame is
+fn current_frame(
+    children: &Children,
+    animation_query: &Query<(&Handle<Image>, &TextureAtlas, &Sprite, &Visibility), With<Animation>>,
+    images: &Assets<Image>,
+    layouts: &Assets<TextureAtlasLayout>,
+) -> Option<(Handle<Image>, Vec4, Vec2)> {
+    children.iter().find_map(|&child| {
+        let (texture, atlas, sprite, visibility) = animation_query.get(child).ok()?;
+        if *visibility == Visibility::Hidden {
+            return None;
+        }
+
+        let image_size = images.get(texture)?.size().as_vec2();
+        let frame_rect = *layouts.get(&atlas.layout)?.textures.get(atlas.index)?;
+        let mut uv_rect = Vec4::new(
+            frame_rect.min.x / image_size.x,
+            frame_rect.min.y / image_size.y,
+            frame_rect.width() / image_size.x,
+            frame_rect.height() / image_size.y,
+        );
+        if sprite.flip_x {
+            uv_rect.x += uv_rect.z;
+            uv_rect.z = -uv_rect.z;
+        }
+        Some((texture.clone(), uv_rect, frame_rect.size()))
+    })
+}
+
+type SilhouetteUnitData = (
+    Entity,
+    &'static GlobalTransform,
+    &'static CurrentTeam,
+    &'static Children,
+    Option<&'static SilhouetteLink>,
+);
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_silhouettes(
+    mut commands: Commands,
+    mesh: Res<SilhouetteMesh>,
+    mut materials: ResMut<Assets<SilhouetteMaterial>>,
+    images: Res<Assets<Image>>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+    occluder_query: Query<(&GlobalTransform, &Occluder)>,
+    units_query: Query<SilhouetteUnitData, Without<Structure>>,
+    animation_query: Query<(&Handle<Image>, &TextureAtlas, &Sprite, &Visibility), With<Animation>>,
+) {
+    for (entity, transform, team, children, link) in units_query.iter() {
+        let position = transform.translation().truncate();
+        let occluded = team.0 == Team::Evil
+            && occluder_query.iter().any(|(occluder_transform, occluder)| {
+                let offset = position - occluder_transform.translation().truncate();
+                occluder.rect.contains(offset)
+            });
+
+        let frame = current_frame(children, &animation_query, &images, &layouts);
+        let Some((texture, uv_rect, frame_size)) = frame.filter(|_| occluded) else {
+            if let Some(link) = link {
+                commands.entity(link.silhouette).despawn_recursive();
+                commands.entity(entity).remove::<SilhouetteLink>();
+            }
+            continue;
+        };
+
+        let silhouette_transform =
+            Transform::from_xyz(0.0, 0.0, SILHOUETTE_Z).with_scale(frame_size.extend(1.0));
+        match link {
+            Some(link) => {
+                let outdated = materials.get(&link.material).is_some_and(|material| {
+                    material.uv_rect != uv_rect || material.texture != texture
+                });
+                if outdated {
+                    let material = materials.get_mut(&link.material).unwrap();
+                    material.uv_rect = uv_rect;
+                    material.texture = texture;
+                }
+                commands
+                    .entity(link.silhouette)
+                    .insert(silhouette_transform);
+            }
+            None => {
+                let material = materials.add(SilhouetteMaterial {
+                    color: SILHOUETTE_COLOR,
+                    uv_rect,
+                    texture,
+                });
+                let silhouette = commands
+                    .spawn(MaterialMesh2dBundle {
+                        mesh: mesh.0.clone(),
+                        material: material.clone(),
+                        transform: silhouette_transform,
+                        ..default()
+                    })
+                    .id();
+                commands
+                    .entity(entity)
+                    .add_child(silhouette)
+                    .insert(SilhouetteLink {
+                        silhouette,
+                        material,
+                    });
+            }
+        }
+    }
+}
+
+// With high contrast outlines on every unit is ringed in its team's color from the palette, so
+// who's on which side reads at a glance whatever the sprite looks like
+#[allow(clippy::too_many_arguments)]
+pub fn update_outlines(
+    mut commands: Commands,
+    accessibility: Res<AccessibilitySettings>,
+    mesh: Res<SilhouetteMesh>,
+    mut materials: ResMut<Assets<SilhouetteMaterial>>,
+    images: Res<Assets<Image>>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+    units_query: Query<(Entity, &CurrentTeam, &Children, Option<&OutlineLink>), Without<Structure>>,
+    animation_query: Query<(&Handle<Image>, &TextureAtlas, &Sprite, &Visibility), With<Animation>>,
+) {
+    for (entity, team, children, link) in units_query.iter() {
+        let frame = current_frame(children, &animation_query, &images, &layouts)
+            .filter(|_| accessibility.high_contrast_outlines);
+        let Some((texture, uv_rect, frame_size)) = frame else {
+            if let Some(link) = link {
+                commands.entity(link.outline).despawn_recursive();
+                commands.entity(entity).remove::<OutlineLink>();
+            }
+            continue;
+        };
+
+        let color = accessibility.palette.color(team.0);
+        let outline_transform = Transform::from_xyz(0.0, 0.0, OUTLINE_Z)
+            .with_scale((frame_size + Vec2::splat(OUTLINE_WIDTH * 2.0)).extend(1.0));
+        match link {
+            Some(link) => {
+                // Converted units and a new palette both change the color
+                let outdated = materials.get(&link.material).is_some_and(|material| {
+                    material.uv_rect != uv_rect
+                        || material.texture != texture
+                        || material.color != color
+                });
+                if outdated {
+                    let material = materials.get_mut(&link.material).unwrap();
+                    material.uv_rect = uv_rect;
+                    material.texture = texture;
+                    material.color = color;
+                }
+                commands.entity(link.outline).insert(outline_transform);
+            }
+            None => {
+                let material = materials.add(SilhouetteMaterial {
+                    color,
+                    uv_rect,
+                    texture,
+                });
+                let outline = commands
+                    .spawn(MaterialMesh2dBundle {
+                        mesh: mesh.0.clone(),
+                        material: material.clone(),
+                        transform: outline_transform,
+                        ..default()
+                    })
+                    .id();
+                commands
+                    .entity(entity)
+                    .add_child(outline)
+                    .insert(OutlineLink { outline, material });
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::events::{WaveGraded, WaveWindowClosed};
+use crate::levels::definition::ActiveLevel;
+use crate::player::familiar::spawn_soul;
+use crate::player::plugin::Player;
+use crate::rng::GameRng;
+
+use super::run_stats::WaveWindow;
+
+// Each bonus soul is worth what one dropped by a fallen enemy is
+const BONUS_SOUL_MANA: u8 = 3;
+// Scattered around the summoner so the raven picks them up without having to go looking
+const BONUS_SOUL_SPREAD: f32 = 96.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grade {
+    S,
+    A,
+    B,
+    C,
+}
+
+impl Grade {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Grade::S => "S",
+            Grade::A => "A",
+            Grade::B => "B",
+            Grade::C => "C",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Grade::S => Color::rgb(1.0, 0.85, 0.3),
+            Grade::A => Color::rgb(0.6, 0.95, 1.0),
+            Grade::B => Color::rgb(0.7, 0.9, 0.6),
+            Grade::C => Color::rgb(0.7, 0.7, 0.7),
+        }
+    }
+}
+
+// How much each part counts towards the score, they don't have to add up to anything
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct GradeWeights {
+    pub speed: f32,
+    pub damage: f32,
+    pub losses: f32,
+}
+
+impl Default for GradeWeights {
+    fn default() -> Self {
+        Self {
+            speed: 0.4,
+            damage: 0.35,
+            losses: 0.25,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct GradeReward {
+    pub grade: Grade,
+    // The lowest score, from 0 to 1, that still gets this grade
+    pub score: f32,
+    pub souls: u32,
+}
+
+// How a wave's stats turn into a grade, written into the level's data so every arena can set
+// its own bar
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Grading {
+    // Cleared this fast is full marks for speed, this slow or not cleared at all is none
+    pub par_seconds: f32,
+    pub slowest_seconds: f32,
+    // Damage the summoner's side can take for every enemy killed before there's none left of
+    // the damage part of the score
+    pub damage_per_kill: f32,
+    // Losing this many summons in one wave is none left of the losses part
+    pub max_losses: u32,
+    pub weights: GradeWeights,
+    // Best first, a wave scoring under all of them gets a C and nothing for it
+    pub grades: Vec<GradeReward>,
+}
+
+impl Default for Grading {
+    fn default() -> Self {
+        Self {
+            par_seconds: 12.0,
+            slowest_seconds: 40.0,
+            damage_per_kill: 20.0,
+            max_losses: 4,
+            weights: GradeWeights::default(),
+            grades: vec![
+                GradeReward {
+                    grade: Grade::S,
+                    score: 0.9,
+                    souls: 8,
+                },
+                GradeReward {
+                    grade: Grade::A,
+                    score: 0.75,
+                    souls: 5,
+                },
+                GradeReward {
+                    grade: Grade::B,
+                    score: 0.5,
+                    souls: 2,
+                },
+            ],
+        }
+    }
+}
+
+impl Grading {
+    pub fn score(&self, window: &WaveWindow) -> f32 {
+        let speed = if window.cleared {
+            let range = (self.slowest_seconds - self.par_seconds).max(f32::EPSILON);
+            1.0 - ((window.seconds - self.par_seconds) / range).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let damage_budget = self.damage_per_kill * window.kills.max(1) as f32;
+        let damage = 1.0 - (window.damage_taken as f32 / damage_budget).clamp(0.0, 1.0);
+        let losses = 1.0 - (window.summons_lost as f32 / self.max_losses.max(1) as f32).min(1.0);
+
+        let weights = &self.weights;
+        let total = weights.speed + weights.damage + weights.losses;
+        if total <= 0.0 {
+            return 0.0;
+        }
+        (speed * weights.speed + damage * weights.damage + losses * weights.losses) / total
+    }
+
+    pub fn grade(&self, window: &WaveWindow) -> (Grade, u32) {
+        let score = self.score(window);
+        self.grades
+            .iter()
+            .find(|reward| score >= reward.score)
+            .map_or((Grade::C, 0), |reward| (reward.grade, reward.souls))
+    }
+
+    // Anything about the formula that can't be right, for the asset validation
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.slowest_seconds <= self.par_seconds {
+            problems.push("slowest_seconds has to be more than par_seconds".to_owned());
+        }
+        let weights = &self.weights;
+        if weights.speed < 0.0 || weights.damage < 0.0 || weights.losses < 0.0 {
+            problems.push("grading weights can't be negative".to_owned());
+        } else if weights.speed + weights.damage + weights.losses <= 0.0 {
+            problems.push("at least one grading weight has to be above 0".to_owned());
+        }
+        if self
+            .grades
+            .windows(2)
+            .any(|pair| pair[0].score < pair[1].score)
+        {
+            problems.push("grades have to be listed best first".to_owned());
+        }
+        problems
+    }
+}
+
+pub fn grade_waves(
+    mut commands: Commands,
+    level: Res<ActiveLevel>,
+    mut rng: ResMut<GameRng>,
+    mut window_reader: EventReader<WaveWindowClosed>,
+    player_query: Query<&Transform, With<Player>>,
+    mut graded_writer: EventWriter<WaveGraded>,
+) {
+    for WaveWindowClosed(window) in window_reader.read() {
+        let (grade, souls) = level.grading.grade(window);
+        info!(
+            "Wave {} graded {} ({:.2}): {:?}",
+            window.wave,
+            grade.name(),
+            level.grading.score(window),
+            window
+        );
+
+        if let Ok(transform) = player_query.get_single() {
+            let center = transform.translation.truncate();
+            for _ in 0..souls {
+                let offset = Vec2::from_angle(rng.0.gen_range(0.0..std::f32::consts::TAU))
+                    * rng.0.gen_range(0.0..BONUS_SOUL_SPREAD);
+                spawn_soul(&mut commands, center + offset, BONUS_SOUL_MANA);
+            }
+        }
+
+        graded_writer.send(WaveGraded {
+            wave: window.wave,
+            grade,
+            souls,
+        });
+    }
+}
@@ -0,0 +1,23 @@
+use bevy::prelude::*;
+
+use crate::stats::{grading, run_stats};
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<run_stats::RunStats>()
+            .init_resource::<run_stats::Leaderboard>()
+            .add_systems(Startup, run_stats::load_leaderboard_system)
+            .add_systems(
+                Update,
+                (
+                    run_stats::reset_run_stats_system,
+                    run_stats::track_run_stats,
+                    grading::grade_waves,
+                    run_stats::record_leaderboard_run,
+                )
+                    .chain(),
+            );
+    }
+}
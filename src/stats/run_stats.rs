@@ -0,0 +1,329 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::difficulty::Difficulty;
+use crate::enemies::enemy_spawner::EnemySpawner;
+use crate::enemies::spawn_queue::SpawnQueue;
+use crate::events::{Damaged, GameEvent, WaveWindowClosed};
+use crate::game_mode::GameMode;
+use crate::gamestate::GameState;
+use crate::levels::definition::ActiveLevel;
+use crate::player::plugin::Player;
+use crate::rng::RunSeed;
+use crate::save::checkpoints::{CheckpointSettings, Checkpoints};
+use crate::save::snapshot::SaveError;
+use crate::units::health::Health;
+use crate::units::team::{AllianceMatrix, CurrentTeam, Team};
+use crate::units::unit_types::CurrentUnitType;
+
+const LEADERBOARD_FILE: &str = "leaderboard.ron";
+// Kept per level, anything below this many better runs falls off
+const LEADERBOARD_SIZE: usize = 10;
+// Spawned enemies only show up once the spawn commands have gone through, so a wave gets a moment
+// to arrive before an empty field counts as it being cleared
+const MIN_WINDOW_SECONDS: f32 = 1.0;
+
+// How the current run is going, reset whenever a new one starts
+#[derive(Resource, Debug, Clone, Default)]
+pub struct RunStats {
+    pub wave: u32,
+    pub endless_depth: u32,
+    pub kills: u32,
+    pub summons_lost: u32,
+    pub seconds: f32,
+    // Picked up by the raven, and how many of those already went to the meta progress
+    pub souls: u32,
+    pub souls_banked: u32,
+    // Picked back up from a checkpoint, so it doesn't count for the leaderboard
+    pub restarted: bool,
+    // Where the run landed on the leaderboard, once it's over
+    pub rank: Option<usize>,
+    // The wave being fought right now, and every one before it in the order they ended
+    pub window: Option<WaveWindow>,
+    pub windows: Vec<WaveWindow>,
+    pub last_window_wave: u32,
+}
+
+impl RunStats {
+    pub fn summary(&self) -> String {
+        let minutes = self.seconds as u32 / 60;
+        let seconds = self.seconds as u32 % 60;
+        format!(
+            "Wave {} in {}:{:02}, {} kills, {} summons lost, {} souls",
+            self.wave, minutes, seconds, self.kills, self.summons_lost, self.souls
+        )
+    }
+}
+
+// What happened over one wave, from when it came in until the field was clear of enemies or the
+// next wave came in on top of it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WaveWindow {
+    pub wave: u32,
+    pub seconds: f32,
+    pub kills: u32,
+    // Everything the summoner and the summons took, dodged hits count for nothing
+    pub damage_taken: i32,
+    pub summons_lost: u32,
+    pub cleared: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LeaderboardEntry {
+    pub level: String,
+    pub difficulty: Difficulty,
+    pub seed: u64,
+    pub wave: u32,
+    pub endless_depth: u32,
+    pub kills: u32,
+    pub seconds: f32,
+}
+
+impl LeaderboardEntry {
+    // Further in is better, and between two runs that got as far, the one that killed more
+    fn beats(&self, other: &LeaderboardEntry) -> bool {
+        (self.wave, self.kills) > (other.wave, other.kills)
+    }
+}
+
+// The best endless runs on every level, best first
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    pub fn path(settings: &CheckpointSettings) -> PathBuf {
+        settings.directory.join(LEADERBOARD_FILE)
+    }
+
+    pub fn read(path: &Path) -> Result<Self, SaveError> {
+        let contents = fs::read_to_string(path)?;
+        Ok(ron::from_str(&contents)?)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), SaveError> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    pub fn best(&self, level: &str) -> Option<&LeaderboardEntry> {
+        self.entries.iter().find(|entry| entry.level == level)
+    }
+
+    // Hands back where the run placed on its level's board, if it made it on at all
+    pub fn record(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+        let rank = self
+            .entries
+            .iter()
+            .filter(|other| other.level == entry.level && !entry.beats(other))
+            .count();
+        if rank >= LEADERBOARD_SIZE {
+            return None;
+        }
+
+        let index = self
+            .entries
+            .iter()
+            .position(|other| entry.beats(other))
+            .unwrap_or(self.entries.len());
+        let level = entry.level.clone();
+        self.entries.insert(index, entry);
+
+        let mut kept = 0;
+        self.entries.retain(|other| {
+            if other.level != level {
+                return true;
+            }
+            kept += 1;
+            kept <= LEADERBOARD_SIZE
+        });
+        Some(rank + 1)
+    }
+}
+
+pub fn load_leaderboard_system(
+    settings: Res<CheckpointSettings>,
+    mut leaderboard: ResMut<Leaderboard>,
+) {
+    let path = Leaderboard::path(&settings);
+    if !path.exists() {
+        return;
+    }
+
+    match Leaderboard::read(&path) {
+        Ok(loaded) => *leaderboard = loaded,
+        Err(error) => warn!("Could not read leaderboard {}: {}", path.display(), error),
+    }
+}
+
+pub fn reset_run_stats_system(
+    mut event_reader: EventReader<GameEvent>,
+    checkpoints: Res<Checkpoints>,
+    mut stats: ResMut<RunStats>,
+) {
+    for event in event_reader.read() {
+        match event {
+            GameEvent::StartGame => *stats = RunStats::default(),
+            GameEvent::RestartFromCheckpoint => {
+                stats.restarted = true;
+                stats.rank = None;
+                // Whatever the restored field looks like, grading picks up from the wave it
+                // restarts on
+                stats.window = None;
+                if let Some(checkpoint) = checkpoints.latest() {
+                    stats.last_window_wave = checkpoint.wave - 1;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn track_run_stats(
+    time: Res<Time>,
+    level: Res<ActiveLevel>,
+    alliances: Res<AllianceMatrix>,
+    spawn_queue: Res<SpawnQueue>,
+    mut stats: ResMut<RunStats>,
+    mut damaged_reader: EventReader<Damaged>,
+    spawner_query: Query<&EnemySpawner>,
+    game_state_query: Query<&GameState>,
+    team_query: Query<(&CurrentTeam, Has<CurrentUnitType>, Has<Player>)>,
+    enemy_query: Query<(&Health, &CurrentTeam), With<CurrentUnitType>>,
+    mut window_writer: EventWriter<WaveWindowClosed>,
+) {
+    // Winning still gets the last wave graded, losing doesn't
+    let game_over = game_state_query.iter().any(|state| state.game_over);
+    let won = game_state_query.iter().any(|state| state.victory);
+    if game_over && !won {
+        damaged_reader.clear();
+        return;
+    }
+
+    if !game_over {
+        stats.seconds += time.delta_seconds();
+        if let Some(window) = stats.window.as_mut() {
+            window.seconds += time.delta_seconds();
+        }
+    }
+
+    if let Some(spawner) = spawner_query.iter().next() {
+        stats.wave = spawner.wave;
+        stats.endless_depth = level.waves.endless_depth(spawner.wave);
+
+        if spawner.wave > stats.last_window_wave && !game_over {
+            // The next wave came in on top of one that never got cleared
+            if let Some(window) = stats.window.take() {
+                close_window(&mut stats, window, &mut window_writer);
+            }
+            stats.last_window_wave = spawner.wave;
+            stats.window = Some(WaveWindow {
+                wave: spawner.wave,
+                ..default()
+            });
+        }
+    }
+
+    for damaged in damaged_reader.read() {
+        let Ok((team, is_unit, is_player)) = team_query.get(damaged.target) else {
+            continue;
+        };
+
+        let lost_summon = team.0 == Team::Evil && is_unit && !is_player && damaged.killed;
+        let kill = alliances.is_hostile(Team::Evil, team.0) && is_unit && damaged.killed;
+        if lost_summon {
+            stats.summons_lost += 1;
+        }
+        if kill {
+            stats.kills += 1;
+        }
+
+        let Some(window) = stats.window.as_mut() else {
+            continue;
+        };
+        if team.0 == Team::Evil {
+            window.damage_taken += damaged.amount;
+        }
+        if lost_summon {
+            window.summons_lost += 1;
+        }
+        if kill {
+            window.kills += 1;
+        }
+    }
+
+    let field_clear = spawn_queue.is_empty()
+        && !enemy_query
+            .iter()
+            .any(|(health, team)| !health.is_dead() && alliances.is_hostile(Team::Evil, team.0));
+    let arrived = stats
+        .window
+        .as_ref()
+        .is_some_and(|window| window.seconds >= MIN_WINDOW_SECONDS);
+    if field_clear && arrived {
+        if let Some(mut window) = stats.window.take() {
+            window.cleared = true;
+            close_window(&mut stats, window, &mut window_writer);
+        }
+    }
+}
+
+fn close_window(
+    stats: &mut RunStats,
+    window: WaveWindow,
+    window_writer: &mut EventWriter<WaveWindowClosed>,
+) {
+    stats.windows.push(window.clone());
+    window_writer.send(WaveWindowClosed(window));
+}
+
+// Endless runs that went start to finish without a checkpoint restart go on the leaderboard
+#[allow(clippy::too_many_arguments)]
+pub fn record_leaderboard_run(
+    mut event_reader: EventReader<GameEvent>,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    level: Res<ActiveLevel>,
+    run_seed: Res<RunSeed>,
+    settings: Res<CheckpointSettings>,
+    mut stats: ResMut<RunStats>,
+    mut leaderboard: ResMut<Leaderboard>,
+) {
+    for event in event_reader.read() {
+        let GameEvent::GameOver = event else {
+            continue;
+        };
+        if *mode != GameMode::Endless || stats.restarted {
+            continue;
+        }
+
+        stats.rank = leaderboard.record(LeaderboardEntry {
+            level: level.name.clone(),
+            difficulty: *difficulty,
+            seed: run_seed.current,
+            wave: stats.wave,
+            endless_depth: stats.endless_depth,
+            kills: stats.kills,
+            seconds: stats.seconds,
+        });
+        info!("Endless run over: {}", stats.summary());
+        if stats.rank.is_none() {
+            continue;
+        }
+
+        let path = Leaderboard::path(&settings);
+        if let Err(error) = leaderboard.write(&path) {
+            warn!("Could not write leaderboard {}: {}", path.display(), error);
+        }
+    }
+}
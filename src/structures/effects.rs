@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+
+use crate::events::Damage;
+use crate::mana::Mana;
+use crate::player::plugin::Player;
+use crate::units::damage::DamageKind;
+use crate::units::flying::Flying;
+use crate::units::health::Health;
+use crate::units::team::{AllianceMatrix, CurrentTeam};
+use crate::velocity::Velocity;
+
+use super::structure_types::{BoneWall, ManaObelisk, SpikeTrap, Structure};
+
+// Roughly half the width of a unit, keeps them from visibly clipping into walls
+const UNIT_RADIUS: f32 = 16.0;
+
+type BlockedFilter = (With<Velocity>, Without<BoneWall>, Without<Flying>);
+
+// The map pathfinding doesn't know about walls, so they block by pushing anything that walked
+// into them back out along the shortest way, which makes units slide along the wall instead of
+// stopping dead. Flying units go over them.
+pub fn block_movement(
+    wall_query: Query<(&Transform, &Structure, &Health), With<BoneWall>>,
+    mut units_query: Query<&mut Transform, BlockedFilter>,
+) {
+    for mut transform in units_query.iter_mut() {
+        for (wall_transform, structure, wall_health) in wall_query.iter() {
+            if wall_health.is_dead() {
+                continue;
+            }
+
+            let half_extents = structure.0.size() * 0.5 + UNIT_RADIUS;
+            let offset = transform.translation.truncate() - wall_transform.translation.truncate();
+            let penetration = half_extents - offset.abs();
+            if penetration.x <= 0.0 || penetration.y <= 0.0 {
+                continue;
+            }
+
+            if penetration.x < penetration.y {
+                transform.translation.x += penetration.x * offset.x.signum();
+            } else {
+                transform.translation.y += penetration.y * offset.y.signum();
+            }
+        }
+    }
+}
+
+pub fn obelisk_mana(
+    time: Res<Time>,
+    mut query: Query<(&mut ManaObelisk, &Health)>,
+    mut player_query: Query<&mut Mana, With<Player>>,
+) {
+    let Ok(mut mana) = player_query.get_single_mut() else {
+        return;
+    };
+
+    for (mut obelisk, health) in query.iter_mut() {
+        if health.is_dead() || !obelisk.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        mana.current_mana = mana
+            .current_mana
+            .saturating_add(obelisk.mana_amount)
+            .min(mana.max_mana);
+    }
+}
+
+type GroundUnitFilter = (With<Velocity>, Without<Flying>);
+
+// Flying units are out of reach of the spikes
+pub fn spike_traps(
+    time: Res<Time>,
+    alliances: Res<AllianceMatrix>,
+    mut trap_query: Query<(&mut SpikeTrap, &Transform, &Structure)>,
+    units_query: Query<(Entity, &Transform, &CurrentTeam, &Health), GroundUnitFilter>,
+    mut damage_writer: EventWriter<Damage>,
+) {
+    for (mut trap, trap_transform, structure) in trap_query.iter_mut() {
+        if !trap.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let half_extents = structure.0.size() * 0.5;
+        for (entity, transform, team, health) in units_query.iter() {
+            let offset = transform.translation.truncate() - trap_transform.translation.truncate();
+            if health.is_dead()
+                || !alliances.is_hostile(trap.team, team.0)
+                || offset.x.abs() > half_extents.x
+                || offset.y.abs() > half_extents.y
+            {
+                continue;
+            }
+
+            damage_writer.send(Damage {
+                target: entity,
+                amount: trap.damage,
+                kind: DamageKind::Physical,
+                // Spikes come up from below, where there is no armor to stop them
+                armor_piercing: true,
+                source: None,
+                critical: false,
+            });
+        }
+    }
+}
+
+pub fn despawn_destroyed_structures(
+    mut commands: Commands,
+    query: Query<(Entity, &Health), With<Structure>>,
+) {
+    for (entity, health) in query.iter() {
+        if health.is_dead() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use bevy::prelude::*;
+
+use crate::schedule::SimSet;
+use crate::structures::effects;
+use crate::velocity;
+
+pub struct StructuresPlugin;
+
+impl Plugin for StructuresPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                effects::block_movement
+                    .in_set(SimSet::Movement)
+                    .after(velocity::translate),
+                effects::spike_traps.in_set(SimSet::Combat),
+                effects::despawn_destroyed_structures.in_set(SimSet::Cleanup),
+            ),
+        )
+        .add_systems(Update, effects::obelisk_mana);
+    }
+}
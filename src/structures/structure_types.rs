@@ -0,0 +1,158 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use serde::{Deserialize, Serialize};
+
+use crate::gamestate::Cleanup;
+use crate::silhouette::Occluder;
+use crate::units::health::Health;
+use crate::units::team::{CurrentTeam, Team};
+
+// Structures are placed on a grid so walls line up into something that actually blocks a path
+pub const GRID_SIZE: f32 = 48.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StructureType {
+    BoneWall,    // Blocks movement, enemies have to break it or walk around
+    ManaObelisk, // Trickles mana to the player
+    SpikeTrap,   // Hurts enemies walking over it, can't be attacked
+}
+
+impl StructureType {
+    pub const ALL: [StructureType; 3] = [
+        StructureType::BoneWall,
+        StructureType::ManaObelisk,
+        StructureType::SpikeTrap,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StructureType::BoneWall => "Bone Wall",
+            StructureType::ManaObelisk => "Mana Obelisk",
+            StructureType::SpikeTrap => "Spike Trap",
+        }
+    }
+
+    pub fn cost(&self) -> u8 {
+        match self {
+            StructureType::BoneWall => 15,
+            StructureType::ManaObelisk => 40,
+            StructureType::SpikeTrap => 20,
+        }
+    }
+
+    pub fn size(&self) -> Vec2 {
+        match self {
+            StructureType::BoneWall => Vec2::splat(GRID_SIZE),
+            StructureType::ManaObelisk => Vec2::new(GRID_SIZE * 0.5, GRID_SIZE),
+            StructureType::SpikeTrap => Vec2::splat(GRID_SIZE * 0.8),
+        }
+    }
+
+    // How far the sprite reaches up above its footprint, units walking behind it are hidden
+    pub fn height(&self) -> f32 {
+        match self {
+            StructureType::BoneWall => GRID_SIZE * 0.5,
+            StructureType::ManaObelisk => GRID_SIZE,
+            StructureType::SpikeTrap => 0.0,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            StructureType::BoneWall => Color::rgb(0.85, 0.82, 0.7),
+            StructureType::ManaObelisk => Color::rgb(0.3, 0.3, 0.9),
+            StructureType::SpikeTrap => Color::rgb(0.35, 0.35, 0.35),
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Structure(pub StructureType);
+
+#[derive(Component)]
+pub struct BoneWall;
+
+#[derive(Component)]
+pub struct ManaObelisk {
+    pub timer: Timer,
+    pub mana_amount: u8,
+}
+
+#[derive(Component)]
+pub struct SpikeTrap {
+    pub timer: Timer,
+    pub damage: i32,
+    pub team: Team,
+}
+
+pub fn snap_to_grid(position: Vec2) -> Vec2 {
+    (position / GRID_SIZE).round() * GRID_SIZE
+}
+
+pub fn spawn_structure<'a>(
+    commands: &'a mut Commands,
+    structure_type: StructureType,
+    team: Team,
+    position: Vec2,
+) -> EntityCommands<'a> {
+    // Traps sit under the units, tall props are drawn over them
+    let height = structure_type.height();
+    let z = if height > 0.0 { 2.0 } else { -0.5 };
+
+    // The footprint stays on the grid cell and the rest of the sprite sticks out above it
+    let footprint = structure_type.size();
+    let sprite_size = footprint + Vec2::new(0.0, height);
+    let anchor_y = footprint.y * 0.5 / sprite_size.y - 0.5;
+
+    let mut entity = commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: structure_type.color(),
+                custom_size: Some(sprite_size),
+                anchor: Anchor::Custom(Vec2::new(0.0, anchor_y)),
+                ..default()
+            },
+            transform: Transform::from_translation(snap_to_grid(position).extend(z)),
+            ..default()
+        },
+        Structure(structure_type),
+        Cleanup,
+    ));
+
+    if height > 0.0 {
+        entity.insert(Occluder {
+            rect: Rect::new(
+                -footprint.x * 0.5,
+                footprint.y * 0.5,
+                footprint.x * 0.5,
+                footprint.y * 0.5 + height,
+            ),
+        });
+    }
+
+    match structure_type {
+        StructureType::BoneWall => {
+            entity.insert((BoneWall, Health::new(200), CurrentTeam(team)));
+        }
+        StructureType::ManaObelisk => {
+            entity.insert((
+                ManaObelisk {
+                    timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+                    mana_amount: 2,
+                },
+                Health::new(100),
+                CurrentTeam(team),
+            ));
+        }
+        StructureType::SpikeTrap => {
+            entity.insert(SpikeTrap {
+                timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+                damage: 12,
+                team,
+            });
+        }
+    }
+
+    entity
+}
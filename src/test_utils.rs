@@ -0,0 +1,203 @@
+use std::time::Duration;
+
+use bevy::ecs::system::RunSystemOnce;
+use bevy::gizmos::GizmoPlugin;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy::window::WindowResolution;
+
+use crate::ai;
+use crate::ai::behavior::CurrentBehavior;
+use crate::animation::{self, AtlasLayouts};
+use crate::aseprite;
+use crate::dark_arts_defense::AppState;
+use crate::difficulty::Difficulty;
+use crate::events;
+use crate::game_view::GameAction;
+use crate::levels::definition::ActiveLevel;
+use crate::map::plugin::CurrentMap;
+use crate::map::tilemap::TileMap;
+use crate::meta::progress::MetaProgress;
+use crate::movement;
+use crate::player::command_mode::{PatrolRoute, RallyPoint};
+use crate::player::perks::PerkChoices;
+use crate::player::relics::{RelicChoices, Relics};
+use crate::pool::EntityPools;
+use crate::rng::GameRng;
+use crate::save::checkpoints::CheckpointSettings;
+use crate::schedule;
+use crate::settings::config::GameSettings;
+use crate::stats::run_stats::RunStats;
+use crate::time_of_day::DayNight;
+use crate::ui::nameplate::RenameState;
+use crate::units;
+use crate::units::team::Team;
+use crate::units::unit_types::{spawn_units_of_type, UnitResource, UnitType};
+use crate::velocity;
+use crate::vfx::flash::{FlashLimiter, VfxSettings};
+
+// Every tick is a 60th of a second, however long it took to run
+pub const FRAME_SECONDS: f32 = 1.0 / 60.0;
+// The size the game is played at, which some of the distances are worked out from
+const WINDOW_SIZE: (f32, f32) = (1920.0, 1080.0);
+
+// The gameplay plugins on their own, without a window, rendering or audio. Textures and sheets
+// have their asset types registered but nothing to load them, so units spawn with handles that
+// never resolve and the tests don't depend on what's in the assets folder.
+//
+//   let mut app = TestApp::new();
+//   let knight = app.spawn_unit(UnitType::Knight, Team::Good, Vec2::new(800.0, 0.0));
+//   app.tick(10);
+//   app.assert_behavior(knight, "Marching");
+pub struct TestApp {
+    pub app: App,
+}
+
+impl Default for TestApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestApp {
+    pub fn new() -> Self {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Shader>()
+            .add_plugins(GizmoPlugin)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                FRAME_SECONDS,
+            )))
+            .init_asset::<Image>()
+            .init_asset::<Font>()
+            .init_asset::<TextureAtlasLayout>()
+            .init_asset::<aseprite::AsepriteSheet>()
+            .init_asset::<Mesh>()
+            .init_asset::<ColorMaterial>()
+            .init_asset::<TileMap>()
+            .init_resource::<aseprite::ImportedSheets>()
+            .init_resource::<animation::AtlasCache>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<ClearColor>()
+            .init_state::<AppState>();
+        // What the gameplay systems read, which the rest of the game's plugins would set up
+        app.insert_resource(GameRng::from_seed(0))
+            .init_resource::<ActiveLevel>()
+            .init_resource::<CurrentMap>()
+            .init_resource::<CheckpointSettings>()
+            .init_resource::<Difficulty>()
+            .init_resource::<DayNight>()
+            .init_resource::<FlashLimiter>()
+            .init_resource::<VfxSettings>()
+            .init_resource::<GameSettings>()
+            .init_resource::<MetaProgress>()
+            .init_resource::<RunStats>()
+            .init_resource::<UnitResource>()
+            .init_resource::<Relics>()
+            .init_resource::<RelicChoices>()
+            .init_resource::<PerkChoices>()
+            .init_resource::<RallyPoint>()
+            .init_resource::<PatrolRoute>()
+            .init_resource::<RenameState>()
+            .add_event::<GameAction>();
+        schedule::configure_schedule(&mut app);
+        app.add_plugins((
+            events::EventsPlugin,
+            units::plugin::UnitsPlugin,
+            ai::plugin::AiPlugin,
+        ))
+        .add_systems(
+            FixedUpdate,
+            (movement::steer, velocity::translate)
+                .chain()
+                .in_set(schedule::SimSet::Movement),
+        );
+
+        app.world.spawn(Window {
+            resolution: WindowResolution::new(WINDOW_SIZE.0, WINDOW_SIZE.1),
+            ..default()
+        });
+
+        // The first update only starts the clock
+        app.update();
+        Self { app }
+    }
+
+    // Spawned the same way the waves and the summoner do it
+    pub fn spawn_unit(&mut self, unit_type: UnitType, team: Team, position: Vec2) -> Entity {
+        self.spawn_units(vec![(unit_type, team, position)])[0]
+    }
+
+    // All in one go the way waves come in, for when there are thousands of them
+    pub fn spawn_units(&mut self, units: Vec<(UnitType, Team, Vec2)>) -> Vec<Entity> {
+        self.app.world.run_system_once_with(
+            units,
+            |In(units): In<Vec<(UnitType, Team, Vec2)>>,
+             mut commands: Commands,
+             asset_server: Res<AssetServer>,
+             mut texture_atlas_layouts: AtlasLayouts,
+             mut pools: ResMut<EntityPools>| {
+                spawn_units_of_type(
+                    &mut commands,
+                    &asset_server,
+                    &mut texture_atlas_layouts,
+                    &mut pools,
+                    units,
+                )
+            },
+        )
+    }
+
+    pub fn tick(&mut self, frames: u32) {
+        for _ in 0..frames {
+            self.app.update();
+        }
+    }
+
+    pub fn tick_seconds(&mut self, seconds: f32) {
+        self.tick((seconds / FRAME_SECONDS).ceil() as u32);
+    }
+
+    pub fn send_event<E: Event>(&mut self, event: E) {
+        self.app.world.send_event(event);
+    }
+
+    pub fn get<T: Component>(&self, entity: Entity) -> &T {
+        self.app
+            .world
+            .get::<T>(entity)
+            .unwrap_or_else(|| panic!("{:?} has no {}", entity, std::any::type_name::<T>()))
+    }
+
+    pub fn get_mut<T: Component>(&mut self, entity: Entity) -> Mut<'_, T> {
+        self.app
+            .world
+            .get_mut::<T>(entity)
+            .unwrap_or_else(|| panic!("{:?} has no {}", entity, std::any::type_name::<T>()))
+    }
+
+    pub fn position(&self, entity: Entity) -> Vec2 {
+        self.get::<Transform>(entity).translation.truncate()
+    }
+
+    pub fn set_position(&mut self, entity: Entity, position: Vec2) {
+        let mut transform = self.get_mut::<Transform>(entity);
+        transform.translation = position.extend(transform.translation.z);
+    }
+
+    pub fn behavior(&self, entity: Entity) -> &'static str {
+        self.get::<CurrentBehavior>(entity).0.name()
+    }
+
+    #[track_caller]
+    pub fn assert_behavior(&self, entity: Entity, expected: &str) {
+        assert_eq!(
+            self.behavior(entity),
+            expected,
+            "{:?} is {} instead of {}",
+            entity,
+            self.behavior(entity),
+            expected
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::events::GameEvent;
+use crate::levels::triggers::TriggerAction;
+use crate::units::stat_modifiers::{Modifier, ModifierSource, Stat, StatModifiers};
+use crate::units::team::{CurrentTeam, Team};
+
+// One full day and night, runs start in broad daylight
+const CYCLE_SECONDS: f32 = 120.0;
+const DAY_CLEAR_COLOR: Color = Color::rgb(0.42, 0.45, 0.38);
+const NIGHT_CLEAR_COLOR: Color = Color::rgb(0.08, 0.07, 0.16);
+const NIGHT_AMBIENT: Color = Color::rgb(0.55, 0.5, 0.85);
+
+// The bonuses at the darkest (or brightest) point of the cycle, they fade in and out with the
+// light so nothing snaps when the sun goes down
+const MOVE_SPEED_BONUS: f32 = 0.2;
+const DAMAGE_BONUS: f32 = 0.25;
+
+#[derive(Resource)]
+pub struct DayNight {
+    pub elapsed: f32,
+    pub cycle_seconds: f32,
+}
+
+impl Default for DayNight {
+    fn default() -> Self {
+        Self {
+            elapsed: 0.0,
+            cycle_seconds: CYCLE_SECONDS,
+        }
+    }
+}
+
+impl DayNight {
+    // 0 at noon and 1 at midnight
+    pub fn darkness(&self) -> f32 {
+        let phase = self.elapsed / self.cycle_seconds;
+        0.5 - 0.5 * (phase * TAU).cos()
+    }
+
+    pub fn is_night(&self) -> bool {
+        self.darkness() > 0.5
+    }
+
+    // The dark arts are strongest at night, while the knights fight best in daylight
+    pub fn strength(&self, team: Team) -> f32 {
+        match team {
+            Team::Evil => self.darkness(),
+            Team::Good => 1.0 - self.darkness(),
+            _ => 0.0,
+        }
+    }
+
+    pub fn modifiers(&self, team: Team) -> [Modifier; 2] {
+        let strength = self.strength(team);
+        [
+            Modifier::multiply(Stat::MoveSpeed, 1.0 + MOVE_SPEED_BONUS * strength),
+            Modifier::multiply(Stat::Damage, 1.0 + DAMAGE_BONUS * strength),
+        ]
+    }
+
+    // Multiplied onto unit sprites, white at noon and a cold blue at midnight
+    pub fn ambient_light(&self) -> Color {
+        lerp_color(Color::WHITE, NIGHT_AMBIENT, self.darkness())
+    }
+}
+
+pub fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    Color::rgba(
+        from.r() + (to.r() - from.r()) * t,
+        from.g() + (to.g() - from.g()) * t,
+        from.b() + (to.b() - from.b()) * t,
+        from.a() + (to.a() - from.a()) * t,
+    )
+}
+
+pub fn advance_day_night(
+    time: Res<Time>,
+    mut day_night: ResMut<DayNight>,
+    mut clear_color: ResMut<ClearColor>,
+) {
+    day_night.elapsed = (day_night.elapsed + time.delta_seconds()) % day_night.cycle_seconds;
+    clear_color.0 = lerp_color(DAY_CLEAR_COLOR, NIGHT_CLEAR_COLOR, day_night.darkness());
+}
+
+pub fn apply_day_night_modifiers(
+    day_night: Res<DayNight>,
+    mut query: Query<(&mut StatModifiers, &CurrentTeam)>,
+) {
+    for (mut modifiers, team) in query.iter_mut() {
+        modifiers.set(ModifierSource::DayNight, &day_night.modifiers(team.0));
+    }
+}
+
+pub fn apply_time_of_day_triggers(
+    mut action_reader: EventReader<TriggerAction>,
+    mut day_night: ResMut<DayNight>,
+) {
+    for action in action_reader.read() {
+        match action {
+            TriggerAction::Night => day_night.elapsed = day_night.cycle_seconds * 0.5,
+            TriggerAction::Day => day_night.elapsed = 0.0,
+            _ => {}
+        }
+    }
+}
+
+pub fn reset_day_night_system(
+    mut event_reader: EventReader<GameEvent>,
+    mut day_night: ResMut<DayNight>,
+) {
+    for event in event_reader.read() {
+        if let GameEvent::StartGame = event {
+            day_night.elapsed = 0.0;
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+
+use crate::dark_arts_defense::AppState;
+use crate::difficulty::Difficulty;
+use crate::enemies::enemy_spawner::EnemySpawner;
+use crate::enemies::spawn_queue::{SpawnQueue, SpawnRequest};
+use crate::events::{GameEvent, UnitSummoned};
+use crate::levels::definition::ActiveLevel;
+use crate::levels::triggers::TriggerAction;
+use crate::mana::Mana;
+use crate::pickups::drops::{spawn_pickup, Pickup, PickupKind};
+use crate::player::plugin::Player;
+use crate::render_scale::{UiView, UI_LAYER};
+use crate::rng::GameRng;
+use crate::schedule::FrameSet;
+use crate::settings::bindings::{key_name, Binding};
+use crate::settings::config::GameSettings;
+use crate::units::health::Health;
+use crate::units::stat_modifiers::StatModifiers;
+use crate::units::team::{CurrentTeam, Team};
+use crate::units::unit_types::{UnitResource, UnitType};
+
+// How far the summoner has to walk before moving counts as learned
+const MOVE_DISTANCE: f32 = 300.0;
+const ORB_COUNT: usize = 4;
+const ORB_RING_RADIUS: f32 = 160.0;
+const TUTORIAL_UNIT: UnitType = UnitType::Cat;
+const WAVE_UNIT: UnitType = UnitType::Knight;
+const WAVE_SIZE: usize = 3;
+// The spawn queue takes a few frames to let the wave out, it isn't beaten before it's all here
+const WAVE_GRACE_SECONDS: f32 = 2.0;
+// Long enough to read the last line before it's back to the level select
+const DONE_SECONDS: f32 = 5.0;
+// Under the dialogue, as a fraction of half the window height
+const OBJECTIVE_OFFSET_TOP: f32 = 0.3;
+const OBJECTIVE_COLOR: Color = Color::rgb(1.0, 0.85, 0.3);
+
+// Picked with T on the level select, runs on top of the chosen level until it's done or skipped
+// from the settings menu, which leaves the level to carry on as a normal run
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TutorialState {
+    #[default]
+    Off,
+    Running,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TutorialStep {
+    #[default]
+    Move,
+    GainMana,
+    Summon,
+    SurviveWave,
+    Done,
+}
+
+impl TutorialStep {
+    fn next(&self) -> Self {
+        match self {
+            TutorialStep::Move => TutorialStep::GainMana,
+            TutorialStep::GainMana => TutorialStep::Summon,
+            TutorialStep::Summon => TutorialStep::SurviveWave,
+            TutorialStep::SurviveWave | TutorialStep::Done => TutorialStep::Done,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Tutorial {
+    pub step: TutorialStep,
+    // The step's setup only runs once, the first frame it's on
+    entered: bool,
+    step_elapsed: f32,
+    walked: f32,
+    last_position: Option<Vec2>,
+}
+
+impl Tutorial {
+    fn advance(&mut self) {
+        *self = Self {
+            step: self.step.next(),
+            ..default()
+        };
+    }
+}
+
+#[derive(Component)]
+pub struct ObjectiveText;
+
+pub fn tutorial_running(state: Res<State<TutorialState>>) -> bool {
+    *state.get() == TutorialState::Running
+}
+
+pub fn start_tutorial(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut tutorial: ResMut<Tutorial>,
+) {
+    *tutorial = Tutorial::default();
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+                    font_size: 28.0,
+                    color: OBJECTIVE_COLOR,
+                },
+            )
+            .with_justify(JustifyText::Center),
+            ..default()
+        },
+        ObjectiveText,
+        RenderLayers::layer(UI_LAYER),
+    ));
+}
+
+// Skipped or finished, the waves the tutorial held back are let go
+pub fn end_tutorial(
+    mut commands: Commands,
+    text_query: Query<Entity, With<ObjectiveText>>,
+    mut spawner_query: Query<&mut EnemySpawner>,
+) {
+    for entity in text_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for mut spawner in spawner_query.iter_mut() {
+        spawner.wave_charge.unpause();
+    }
+}
+
+pub fn leave_tutorial(mut next_state: ResMut<NextState<TutorialState>>) {
+    next_state.set(TutorialState::Off);
+}
+
+// Dying in the tutorial and starting over starts it over too
+pub fn restart_tutorial_system(
+    mut event_reader: EventReader<GameEvent>,
+    mut tutorial: ResMut<Tutorial>,
+) {
+    for event in event_reader.read() {
+        if let GameEvent::StartGame | GameEvent::RestartFromCheckpoint = event {
+            *tutorial = Tutorial::default();
+        }
+    }
+}
+
+pub fn hold_waves(mut spawner_query: Query<&mut EnemySpawner>) {
+    for mut spawner in spawner_query.iter_mut() {
+        if !spawner.wave_charge.is_paused() {
+            spawner.wave_charge.pause();
+        }
+    }
+}
+
+fn objective_line(step: TutorialStep, bindings: &GameSettings, tutorial: &Tutorial) -> String {
+    let key = |binding| key_name(bindings.bindings.key(binding));
+    match step {
+        TutorialStep::Move => format!(
+            "Walk around with {} {} {} {} ({:.0}%)",
+            key(Binding::MoveUp),
+            key(Binding::MoveLeft),
+            key(Binding::MoveDown),
+            key(Binding::MoveRight),
+            (tutorial.walked / MOVE_DISTANCE * 100.0).min(100.0)
+        ),
+        TutorialStep::GainMana => "Collect the mana orbs until you can afford a Cat".to_owned(),
+        TutorialStep::Summon => format!("Press {} to summon a Cat", key(Binding::Summon3)),
+        TutorialStep::SurviveWave => "Survive the knights".to_owned(),
+        TutorialStep::Done => "Tutorial complete".to_owned(),
+    }
+}
+
+fn intro_line(step: TutorialStep) -> &'static str {
+    match step {
+        TutorialStep::Move => "Welcome, summoner. Stretch your legs first",
+        TutorialStep::GainMana => "Every summon costs mana, gather some",
+        TutorialStep::Summon => "Now call on a servant",
+        TutorialStep::SurviveWave => "Knights approach, let your servants meet them",
+        TutorialStep::Done => "The altar is yours to defend",
+    }
+}
+
+// Each step sets itself up the first frame it's on, then waits on its objective
+#[allow(clippy::too_many_arguments)]
+pub fn advance_tutorial(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut tutorial: ResMut<Tutorial>,
+    mut unit_configs: ResMut<UnitResource>,
+    difficulty: Res<Difficulty>,
+    level: Res<ActiveLevel>,
+    mut rng: ResMut<GameRng>,
+    mut spawn_queue: ResMut<SpawnQueue>,
+    mut summoned_reader: EventReader<UnitSummoned>,
+    mut dialogue_writer: EventWriter<TriggerAction>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut player_query: Query<(&Transform, &mut Mana, &StatModifiers), With<Player>>,
+    pickup_query: Query<&Pickup>,
+    enemy_query: Query<(&CurrentTeam, &Health)>,
+    window_query: Query<&Window>,
+) {
+    let Ok((transform, mut mana, modifiers)) = player_query.get_single_mut() else {
+        summoned_reader.clear();
+        return;
+    };
+    let position = transform.translation.truncate();
+    let cost = unit_configs.summon_cost(TUTORIAL_UNIT, &difficulty, modifiers);
+    let summoned = summoned_reader
+        .read()
+        .any(|summoned| summoned.unit_type == TUTORIAL_UNIT);
+
+    let entering = !tutorial.entered;
+    if entering {
+        tutorial.entered = true;
+        dialogue_writer.send(TriggerAction::Dialogue(
+            intro_line(tutorial.step).to_owned(),
+        ));
+    }
+    tutorial.step_elapsed += time.delta_seconds();
+
+    let done = match tutorial.step {
+        TutorialStep::Move => {
+            if let Some(last) = tutorial.last_position {
+                tutorial.walked += last.distance(position);
+            }
+            tutorial.last_position = Some(position);
+            tutorial.walked >= MOVE_DISTANCE
+        }
+        TutorialStep::GainMana => {
+            // Starts from empty so there's something to gather, and the orbs come back if they
+            // ran out before there was enough
+            if entering {
+                mana.current_mana = 0;
+            }
+            let orbs_left = pickup_query
+                .iter()
+                .any(|pickup| pickup.kind == PickupKind::ManaOrb);
+            if !orbs_left && mana.current_mana < cost {
+                for index in 0..ORB_COUNT {
+                    let angle = index as f32 / ORB_COUNT as f32 * TAU;
+                    let offset = Vec2::from_angle(angle) * ORB_RING_RADIUS;
+                    spawn_pickup(&mut commands, PickupKind::ManaOrb, position + offset);
+                }
+            }
+            mana.current_mana >= cost
+        }
+        TutorialStep::Summon => {
+            // A Cat is normally earned, this one is lent out
+            if entering {
+                unit_configs.unlock(TUTORIAL_UNIT);
+            }
+            summoned
+        }
+        TutorialStep::SurviveWave => {
+            if entering {
+                let window = window_query.single();
+                let play_area = Vec2::new(window.width(), window.height());
+                for _ in 0..WAVE_SIZE {
+                    spawn_queue.push(SpawnRequest {
+                        unit_type: WAVE_UNIT,
+                        team: Team::Good,
+                        position: level.spawn_position(&mut rng, play_area),
+                        bounty: None,
+                        patrol: None,
+                    });
+                }
+            }
+            let enemies_left = enemy_query
+                .iter()
+                .any(|(team, health)| team.0 == Team::Good && !health.is_dead());
+            tutorial.step_elapsed >= WAVE_GRACE_SECONDS
+                && spawn_queue.pending.is_empty()
+                && !enemies_left
+        }
+        TutorialStep::Done => {
+            if tutorial.step_elapsed >= DONE_SECONDS {
+                next_state.set(AppState::LevelSelect);
+            }
+            false
+        }
+    };
+
+    if done {
+        tutorial.advance();
+    }
+}
+
+pub fn update_objective_text(
+    tutorial: Res<Tutorial>,
+    settings: Res<GameSettings>,
+    ui_view: Res<UiView>,
+    mut query: Query<(&mut Text, &mut Transform), With<ObjectiveText>>,
+) {
+    let value = objective_line(tutorial.step, &settings, &tutorial);
+    for (mut text, mut transform) in query.iter_mut() {
+        transform.translation = Vec3::new(0.0, ui_view.half_size.y * OBJECTIVE_OFFSET_TOP, 0.0);
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<TutorialState>()
+            .init_resource::<Tutorial>()
+            .add_systems(OnEnter(TutorialState::Running), start_tutorial)
+            .add_systems(OnExit(TutorialState::Running), end_tutorial)
+            .add_systems(OnExit(AppState::Playing), leave_tutorial)
+            .add_systems(
+                Update,
+                (
+                    restart_tutorial_system,
+                    (hold_waves, advance_tutorial)
+                        .chain()
+                        .in_set(FrameSet::Actions),
+                    update_objective_text.in_set(FrameSet::Presentation),
+                )
+                    .run_if(tutorial_running),
+            );
+    }
+}
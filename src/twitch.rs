@@ -0,0 +1,206 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+use std::thread;
+
+use bevy::prelude::*;
+
+use crate::animation::AtlasLayouts;
+use crate::enemies::enemy_spawner::{self, random_spawn_position};
+use crate::enemies::mutators::{NextWaveMutator, WaveMutator};
+use crate::events::{GameEvent, WaveStarted};
+use crate::rng::GameRng;
+use crate::ui::nameplate::Nameplate;
+use crate::units::team::Team;
+use crate::units::unit_types::{spawn_unit_of_type, UnitType};
+
+const TWITCH_IRC_ADDRESS: &str = "irc.chat.twitch.tv:6667";
+const TWITCH_CHANNEL_ENV: &str = "DARK_ARTS_TWITCH_CHANNEL";
+const MAX_QUEUED_VIEWER_KNIGHTS: usize = 16;
+
+// Reads chat anonymously, so there is no token to configure. Set DARK_ARTS_TWITCH_CHANNEL to
+// the channel name and chat can then use:
+//   !vote <swarm|armored|airborne>  votes for the mutator of the next wave
+//   !knight                         joins the next wave as a named knight
+pub struct TwitchPlugin;
+
+impl Plugin for TwitchPlugin {
+    fn build(&self, app: &mut App) {
+        let Ok(channel_name) = std::env::var(TWITCH_CHANNEL_ENV) else {
+            info!(
+                "{} is not set, twitch integration disabled",
+                TWITCH_CHANNEL_ENV
+            );
+            return;
+        };
+
+        let (sender, receiver) = channel();
+        let channel_name = channel_name.trim().trim_start_matches('#').to_lowercase();
+        thread::spawn(move || {
+            if let Err(error) = read_chat(&channel_name, sender) {
+                warn!("Twitch chat connection closed: {}", error);
+            }
+        });
+
+        app.insert_resource(TwitchChat(Mutex::new(receiver)))
+            .init_resource::<TwitchVotes>()
+            .add_systems(
+                Update,
+                (
+                    // After the spawner has taken this wave's mutator, so the vote that picked it
+                    // is closed before its leader could be handed to the next wave as well
+                    (
+                        receive_chat_messages,
+                        spawn_viewer_knights,
+                        apply_chat_votes,
+                    )
+                        .chain()
+                        .after(enemy_spawner::spawn_enemies),
+                    reset_votes_system,
+                ),
+            );
+    }
+}
+
+struct ChatMessage {
+    user: String,
+    text: String,
+}
+
+#[derive(Resource)]
+struct TwitchChat(Mutex<Receiver<ChatMessage>>);
+
+#[derive(Resource, Default)]
+pub struct TwitchVotes {
+    pub tally: HashMap<WaveMutator, u32>,
+    pub voters: HashSet<String>,
+    pub viewer_knights: VecDeque<String>,
+}
+
+impl TwitchVotes {
+    pub fn leader(&self) -> Option<WaveMutator> {
+        // Ties go to the mutator declared first, so the result doesn't flicker between frames
+        WaveMutator::ALL
+            .into_iter()
+            .filter(|mutator| self.tally.get(mutator).copied().unwrap_or(0) > 0)
+            .max_by_key(|mutator| (self.tally[mutator], std::cmp::Reverse(*mutator as u8)))
+    }
+}
+
+fn read_chat(channel_name: &str, sender: Sender<ChatMessage>) -> std::io::Result<()> {
+    let mut stream = TcpStream::connect(TWITCH_IRC_ADDRESS)?;
+    let nick = format!("justinfan{}", rand::random::<u32>() % 100000);
+    write!(stream, "NICK {}\r\nJOIN #{}\r\n", nick, channel_name)?;
+
+    let reader = BufReader::new(stream.try_clone()?);
+    for line in reader.lines() {
+        let line = line?;
+        if let Some(server) = line.strip_prefix("PING ") {
+            write!(stream, "PONG {}\r\n", server)?;
+            continue;
+        }
+
+        if let Some(message) = parse_privmsg(&line) {
+            if sender.send(message).is_err() {
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// :user!user@user.tmi.twitch.tv PRIVMSG #channel :message
+fn parse_privmsg(line: &str) -> Option<ChatMessage> {
+    let line = line.strip_prefix(':')?;
+    let (prefix, rest) = line.split_once(' ')?;
+    let rest = rest.strip_prefix("PRIVMSG ")?;
+    let (_channel, text) = rest.split_once(" :")?;
+    let user = prefix.split('!').next()?;
+
+    Some(ChatMessage {
+        user: user.to_owned(),
+        text: text.trim().to_owned(),
+    })
+}
+
+fn receive_chat_messages(chat: Res<TwitchChat>, mut votes: ResMut<TwitchVotes>) {
+    let Ok(receiver) = chat.0.lock() else {
+        return;
+    };
+
+    for message in receiver.try_iter() {
+        let mut words = message.text.split_whitespace();
+        match words.next() {
+            Some("!vote") => {
+                let Some(mutator) = words.next().and_then(WaveMutator::from_name) else {
+                    continue;
+                };
+
+                // One vote per viewer and wave
+                if votes.voters.insert(message.user.clone()) {
+                    *votes.tally.entry(mutator).or_insert(0) += 1;
+                }
+            }
+            Some("!knight")
+                if votes.viewer_knights.len() < MAX_QUEUED_VIEWER_KNIGHTS
+                    && !votes.viewer_knights.contains(&message.user) =>
+            {
+                votes.viewer_knights.push_back(message.user);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn apply_chat_votes(votes: Res<TwitchVotes>, mut next_mutator: ResMut<NextWaveMutator>) {
+    if let Some(leader) = votes.leader() {
+        next_mutator.0 = Some(leader);
+    }
+}
+
+// The wave starting is when the spawner takes the mutator, that closes the vote and lets one
+// viewer knight join in.
+fn spawn_viewer_knights(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: AtlasLayouts,
+    mut votes: ResMut<TwitchVotes>,
+    mut rng: ResMut<GameRng>,
+    mut wave_reader: EventReader<WaveStarted>,
+    window_query: Query<&Window>,
+) {
+    for _ in wave_reader.read() {
+        votes.tally.clear();
+        votes.voters.clear();
+
+        let Some(viewer) = votes.viewer_knights.pop_front() else {
+            continue;
+        };
+        let Ok(window) = window_query.get_single() else {
+            continue;
+        };
+
+        let play_area = Vec2::new(window.width(), window.height());
+        spawn_unit_of_type(
+            &mut commands,
+            &asset_server,
+            &mut texture_atlas_layouts,
+            UnitType::Knight,
+            Team::Good,
+            random_spawn_position(&mut rng, play_area),
+        )
+        .insert(Nameplate(viewer));
+    }
+}
+
+fn reset_votes_system(mut event_reader: EventReader<GameEvent>, mut votes: ResMut<TwitchVotes>) {
+    for event in event_reader.read() {
+        if let GameEvent::StartGame = event {
+            votes.tally.clear();
+            votes.voters.clear();
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy::sprite::Anchor;
+
+use crate::enemies::boss::Boss;
+use crate::events::BossSpawned;
+use crate::render_scale::{UiView, UI_LAYER};
+use crate::units::damage::Armor;
+use crate::units::health::Health;
+
+const BAR_WIDTH: f32 = 640.0;
+const BAR_HEIGHT: f32 = 20.0;
+// How far down from the top of the screen the bar sits, as a fraction of half the window height
+const BAR_OFFSET_TOP: f32 = 0.3;
+const SHIELD_HEIGHT: f32 = 6.0;
+const SHIELD_GAP: f32 = 4.0;
+const MARKER_WIDTH: f32 = 3.0;
+
+// The ghost holds on to the health that was just lost for a moment, then drains down to the bar
+const GHOST_DELAY: f32 = 0.6;
+const GHOST_DRAIN_SPEED: f32 = 0.4;
+
+const BACKGROUND_COLOR: Color = Color::rgba(0.1, 0.1, 0.1, 0.8);
+const FILL_COLOR: Color = Color::rgb(0.75, 0.1, 0.1);
+const GHOST_COLOR: Color = Color::rgb(1.0, 0.85, 0.55);
+const MARKER_COLOR: Color = Color::WHITE;
+const PASSED_MARKER_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.25);
+const SHIELD_COLOR: Color = Color::rgb(0.45, 0.7, 1.0);
+const BROKEN_SHIELD_COLOR: Color = Color::rgba(0.45, 0.7, 1.0, 0.15);
+
+#[derive(Component)]
+pub struct BossBar {
+    pub boss: Option<Entity>,
+    // The boss the markers and shield segments were laid out for
+    laid_out_for: Option<Entity>,
+    ghost: f32,
+    last_fraction: f32,
+    ghost_delay: Timer,
+}
+
+#[derive(Component, Clone, Copy, PartialEq)]
+pub enum BossBarPart {
+    Fill,
+    Ghost,
+    PhaseMarker(usize),
+    ShieldSegment(u32),
+}
+
+#[derive(Component)]
+pub struct BossBarName;
+
+fn ui_sprite(color: Color, size: Vec2, anchor: Anchor, translation: Vec3) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(size),
+            anchor,
+            ..default()
+        },
+        transform: Transform::from_translation(translation),
+        ..default()
+    }
+}
+
+pub fn setup_boss_bar(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let left = -BAR_WIDTH * 0.5;
+    commands
+        .spawn((
+            SpatialBundle {
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            BossBar {
+                boss: None,
+                laid_out_for: None,
+                ghost: 1.0,
+                last_fraction: 1.0,
+                ghost_delay: Timer::from_seconds(GHOST_DELAY, TimerMode::Once),
+            },
+            RenderLayers::layer(UI_LAYER),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ui_sprite(
+                    BACKGROUND_COLOR,
+                    Vec2::new(BAR_WIDTH + 6.0, BAR_HEIGHT + 6.0),
+                    Anchor::Center,
+                    Vec3::ZERO,
+                ),
+                RenderLayers::layer(UI_LAYER),
+            ));
+            parent.spawn((
+                ui_sprite(
+                    GHOST_COLOR,
+                    Vec2::new(BAR_WIDTH, BAR_HEIGHT),
+                    Anchor::CenterLeft,
+                    Vec3::new(left, 0.0, 0.1),
+                ),
+                BossBarPart::Ghost,
+                RenderLayers::layer(UI_LAYER),
+            ));
+            parent.spawn((
+                ui_sprite(
+                    FILL_COLOR,
+                    Vec2::new(BAR_WIDTH, BAR_HEIGHT),
+                    Anchor::CenterLeft,
+                    Vec3::new(left, 0.0, 0.2),
+                ),
+                BossBarPart::Fill,
+                RenderLayers::layer(UI_LAYER),
+            ));
+            parent.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+                            font_size: 32.0,
+                            color: Color::WHITE,
+                        },
+                    )
+                    .with_justify(JustifyText::Center),
+                    text_anchor: Anchor::BottomCenter,
+                    transform: Transform::from_xyz(0.0, BAR_HEIGHT * 0.5 + 6.0, 0.0),
+                    ..default()
+                },
+                BossBarName,
+                RenderLayers::layer(UI_LAYER),
+            ));
+        });
+}
+
+pub fn show_boss_bar(
+    mut event_reader: EventReader<BossSpawned>,
+    mut bar_query: Query<&mut BossBar>,
+    mut name_query: Query<&mut Text, With<BossBarName>>,
+) {
+    for event in event_reader.read() {
+        for mut bar in bar_query.iter_mut() {
+            bar.boss = Some(event.entity);
+            bar.ghost = 1.0;
+            bar.last_fraction = 1.0;
+        }
+        for mut text in name_query.iter_mut() {
+            text.sections[0].value = event.name.clone();
+        }
+    }
+}
+
+// The phase markers and shield segments depend on the boss, so they are spawned the first frame
+// the boss can be found instead of with the rest of the bar
+fn lay_out_boss_bar(commands: &mut Commands, bar_entity: Entity, boss: &Boss, shield: u32) {
+    let left = -BAR_WIDTH * 0.5;
+    commands.entity(bar_entity).with_children(|parent| {
+        for (index, threshold) in boss.phases.levels().iter().enumerate() {
+            parent.spawn((
+                ui_sprite(
+                    MARKER_COLOR,
+                    Vec2::new(MARKER_WIDTH, BAR_HEIGHT + 6.0),
+                    Anchor::Center,
+                    Vec3::new(left + BAR_WIDTH * threshold, 0.0, 0.3),
+                ),
+                BossBarPart::PhaseMarker(index),
+                RenderLayers::layer(UI_LAYER),
+            ));
+        }
+
+        let segment_width = (BAR_WIDTH - SHIELD_GAP * (shield as f32 - 1.0)) / shield as f32;
+        for index in 0..shield {
+            let x = left + (segment_width + SHIELD_GAP) * index as f32;
+            parent.spawn((
+                ui_sprite(
+                    SHIELD_COLOR,
+                    Vec2::new(segment_width, SHIELD_HEIGHT),
+                    Anchor::TopLeft,
+                    Vec3::new(x, -BAR_HEIGHT * 0.5 - SHIELD_GAP, 0.0),
+                ),
+                BossBarPart::ShieldSegment(index),
+                RenderLayers::layer(UI_LAYER),
+            ));
+        }
+    });
+}
+
+pub fn update_boss_bar(
+    mut commands: Commands,
+    time: Res<Time>,
+    ui_view: Res<UiView>,
+    boss_query: Query<(&Boss, &Health, Option<&Armor>)>,
+    mut bar_query: Query<(Entity, &mut BossBar, &mut Visibility, &mut Transform)>,
+    mut part_query: Query<(Entity, &BossBarPart, &mut Sprite)>,
+) {
+    let window_bounds = ui_view.half_size;
+
+    for (bar_entity, mut bar, mut visibility, mut transform) in bar_query.iter_mut() {
+        transform.translation = Vec3::new(0.0, window_bounds.y * (1.0 - BAR_OFFSET_TOP), 0.0);
+
+        let boss = bar
+            .boss
+            .and_then(|entity| boss_query.get(entity).ok())
+            .filter(|(_, health, _)| !health.is_dead());
+        let Some((boss, health, armor)) = boss else {
+            bar.boss = None;
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+
+        if bar.laid_out_for != bar.boss {
+            for (entity, part, _) in part_query.iter() {
+                if let BossBarPart::PhaseMarker(_) | BossBarPart::ShieldSegment(_) = part {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+            let shield = armor.map_or(0, |armor| armor.hits_to_break);
+            lay_out_boss_bar(&mut commands, bar_entity, boss, shield);
+            bar.laid_out_for = bar.boss;
+        }
+
+        let fraction = health.fraction();
+        if fraction < bar.last_fraction {
+            bar.ghost_delay.reset();
+        }
+        bar.last_fraction = fraction;
+        if bar.ghost_delay.tick(time.delta()).finished() {
+            bar.ghost -= GHOST_DRAIN_SPEED * time.delta_seconds();
+        }
+        bar.ghost = bar.ghost.max(fraction);
+
+        for (_, part, mut sprite) in part_query.iter_mut() {
+            match *part {
+                BossBarPart::Fill => {
+                    sprite.custom_size = Some(Vec2::new(BAR_WIDTH * fraction, BAR_HEIGHT));
+                }
+                BossBarPart::Ghost => {
+                    sprite.custom_size = Some(Vec2::new(BAR_WIDTH * bar.ghost, BAR_HEIGHT));
+                }
+                BossBarPart::PhaseMarker(index) => {
+                    sprite.color = if index < boss.phases.crossed() {
+                        PASSED_MARKER_COLOR
+                    } else {
+                        MARKER_COLOR
+                    };
+                }
+                BossBarPart::ShieldSegment(index) => {
+                    let intact = armor.is_some_and(|armor| {
+                        index < armor.hits_to_break.saturating_sub(armor.hits_taken)
+                    });
+                    sprite.color = if intact {
+                        SHIELD_COLOR
+                    } else {
+                        BROKEN_SHIELD_COLOR
+                    };
+                }
+            }
+        }
+    }
+}
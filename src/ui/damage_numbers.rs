@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+
+use crate::events::Damaged;
+use crate::gamestate::Cleanup;
+use crate::pool::{self, EntityPools, Pooled};
+
+const NUMBER_LIFETIME: f32 = 0.8;
+const NUMBER_RISE_SPEED: f32 = 60.0;
+const NUMBER_OFFSET_Y: f32 = 48.0;
+// Spread out sideways a little so hits landing together don't stack into one number
+const NUMBER_JITTER: f32 = 16.0;
+
+const NORMAL_COLOR: Color = Color::WHITE;
+const CRITICAL_COLOR: Color = Color::rgb(1.0, 0.45, 0.1);
+const DODGE_COLOR: Color = Color::rgb(0.6, 0.75, 0.9);
+
+#[derive(Component)]
+pub struct DamageNumber {
+    lifetime: Timer,
+    color: Color,
+}
+
+pub fn spawn_damage_numbers(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut pools: ResMut<EntityPools>,
+    mut damaged_reader: EventReader<Damaged>,
+    target_query: Query<&GlobalTransform>,
+) {
+    for damaged in damaged_reader.read() {
+        if damaged.amount <= 0 && !damaged.dodged {
+            continue;
+        }
+        let Ok(target_transform) = target_query.get(damaged.target) else {
+            continue;
+        };
+
+        let (value, font_size, color) = if damaged.dodged {
+            ("Dodge".to_owned(), 22.0, DODGE_COLOR)
+        } else if damaged.critical {
+            (format!("{}!", damaged.amount), 36.0, CRITICAL_COLOR)
+        } else {
+            (damaged.amount.to_string(), 24.0, NORMAL_COLOR)
+        };
+
+        let jitter = (rand::random::<f32>() - 0.5) * NUMBER_JITTER * 2.0;
+        let position =
+            target_transform.translation().truncate() + Vec2::new(jitter, NUMBER_OFFSET_Y);
+        let number = (
+            Text2dBundle {
+                text: Text::from_section(
+                    value,
+                    TextStyle {
+                        font: asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+                        font_size,
+                        color,
+                    },
+                ),
+                transform: Transform::from_translation(position.extend(5.0)),
+                ..default()
+            },
+            DamageNumber {
+                lifetime: Timer::from_seconds(NUMBER_LIFETIME, TimerMode::Once),
+                color,
+            },
+            Cleanup,
+        );
+        match pools.take_damage_number() {
+            Some(entity) => {
+                commands.entity(entity).remove::<Pooled>().insert(number);
+            }
+            None => {
+                commands.spawn(number);
+            }
+        }
+    }
+}
+
+pub fn update_damage_numbers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut DamageNumber, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut number, mut transform, mut text) in query.iter_mut() {
+        if number.lifetime.tick(time.delta()).finished() {
+            pool::release_damage_number(&mut commands, entity);
+            continue;
+        }
+
+        transform.translation.y += NUMBER_RISE_SPEED * time.delta_seconds();
+        let alpha = 1.0 - number.lifetime.fraction();
+        for section in text.sections.iter_mut() {
+            section.style.color = number.color.with_a(alpha);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+
+use crate::events::GameEvent;
+use crate::levels::triggers::TriggerAction;
+use crate::render_scale::{UiView, UI_LAYER};
+
+const LINE_SECONDS: f32 = 4.0;
+// Fades out over the last part of its time on screen
+const FADE_SECONDS: f32 = 1.0;
+// Below the boss bar, as a fraction of half the window height
+const DIALOGUE_OFFSET_TOP: f32 = 0.45;
+const DIALOGUE_COLOR: Color = Color::rgb(0.85, 0.8, 1.0);
+
+#[derive(Component)]
+pub struct DialogueText {
+    timer: Timer,
+}
+
+pub fn setup_dialogue(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let mut timer = Timer::from_seconds(LINE_SECONDS, TimerMode::Once);
+    timer.tick(timer.duration());
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+                    font_size: 40.0,
+                    color: DIALOGUE_COLOR,
+                },
+            )
+            .with_justify(JustifyText::Center),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        DialogueText { timer },
+        RenderLayers::layer(UI_LAYER),
+    ));
+}
+
+// The newest line always wins, there's no queue to wait through in the middle of a fight
+pub fn show_dialogue(
+    mut action_reader: EventReader<TriggerAction>,
+    mut query: Query<(&mut Text, &mut DialogueText)>,
+) {
+    for action in action_reader.read() {
+        let TriggerAction::Dialogue(line) = action else {
+            continue;
+        };
+        for (mut text, mut dialogue) in query.iter_mut() {
+            text.sections[0].value = line.clone();
+            dialogue.timer.reset();
+        }
+    }
+}
+
+pub fn update_dialogue(
+    time: Res<Time>,
+    ui_view: Res<UiView>,
+    mut query: Query<(
+        &mut Text,
+        &mut DialogueText,
+        &mut Visibility,
+        &mut Transform,
+    )>,
+) {
+    for (mut text, mut dialogue, mut visibility, mut transform) in query.iter_mut() {
+        if dialogue.timer.tick(time.delta()).finished() {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+
+        let alpha = (dialogue.timer.remaining_secs() / FADE_SECONDS).min(1.0);
+        text.sections[0].style.color = DIALOGUE_COLOR.with_a(alpha);
+        transform.translation = Vec3::new(0.0, ui_view.half_size.y * DIALOGUE_OFFSET_TOP, 0.0);
+        *visibility = Visibility::Visible;
+    }
+}
+
+pub fn clear_dialogue_system(
+    mut event_reader: EventReader<GameEvent>,
+    mut query: Query<&mut DialogueText>,
+) {
+    for event in event_reader.read() {
+        if let GameEvent::StartGame = event {
+            for mut dialogue in query.iter_mut() {
+                let duration = dialogue.timer.duration();
+                dialogue.timer.tick(duration);
+            }
+        }
+    }
+}
@@ -1,15 +1,22 @@
 use bevy::prelude::*;
 
-use crate::{player::plugin::Player, units::health::Health};
+use crate::{
+    player::plugin::Player,
+    units::{altar::DarkAltar, health::Health},
+};
 
 use super::plugin::HealthText;
 
 pub fn update_health_text(
     query: Query<&Health, With<Player>>,
+    altar_query: Query<&Health, With<DarkAltar>>,
     mut text_query: Query<&mut Text, With<HealthText>>,
 ) {
     if let Some(health) = query.iter().next() {
         let mut text = text_query.single_mut();
-        text.sections[0].value = format!("HP: {}", health.0);
+        text.sections[0].value = match altar_query.iter().next() {
+            Some(altar) => format!("HP: {}\nAltar: {}", health.current, altar.current.max(0)),
+            None => format!("HP: {}", health.current),
+        };
     }
 }
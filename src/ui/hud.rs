@@ -0,0 +1,228 @@
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy::sprite::Anchor;
+
+use crate::difficulty::Difficulty;
+use crate::enemies::enemy_spawner::EnemySpawner;
+use crate::game_mode::GameMode;
+use crate::levels::definition::ActiveLevel;
+use crate::mana::Mana;
+use crate::meta::progress::MetaProgress;
+use crate::player::plugin::Player;
+use crate::player::summoning::{SelectedSummon, SUMMON_BINDS};
+use crate::render_scale::{UiView, UI_LAYER};
+use crate::settings::bindings::key_name;
+use crate::settings::config::GameSettings;
+use crate::units::stat_modifiers::StatModifiers;
+use crate::units::unit_types::UnitResource;
+
+const HUD_MARGIN: f32 = 24.0;
+const BAR_WIDTH: f32 = 260.0;
+const BAR_HEIGHT: f32 = 18.0;
+const LINE_HEIGHT: f32 = 28.0;
+
+const BACKGROUND_COLOR: Color = Color::rgba(0.1, 0.1, 0.1, 0.8);
+const MANA_COLOR: Color = Color::rgb(0.25, 0.35, 0.95);
+const TEXT_COLOR: Color = Color::WHITE;
+const AFFORDABLE_COLOR: Color = Color::rgb(0.85, 0.8, 1.0);
+const UNAFFORDABLE_COLOR: Color = Color::rgb(0.45, 0.45, 0.5);
+
+// Up in the top right corner, the mana the summoner has, where the waves are at and what the
+// summon it last asked for costs
+#[derive(Component)]
+pub struct Hud;
+
+#[derive(Component, Clone, Copy, PartialEq)]
+pub enum HudPart {
+    ManaFill,
+    ManaText,
+    WaveText,
+    SummonText,
+}
+
+fn hud_text(font: &Handle<Font>, translation: Vec3, anchor: Anchor) -> Text2dBundle {
+    Text2dBundle {
+        text: Text::from_section(
+            "",
+            TextStyle {
+                font: font.clone(),
+                font_size: 22.0,
+                color: TEXT_COLOR,
+            },
+        ),
+        text_anchor: anchor,
+        transform: Transform::from_translation(translation),
+        ..default()
+    }
+}
+
+pub fn setup_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf");
+    commands
+        .spawn((
+            SpatialBundle {
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            Hud,
+            RenderLayers::layer(UI_LAYER),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: BACKGROUND_COLOR,
+                        custom_size: Some(Vec2::new(BAR_WIDTH + 6.0, BAR_HEIGHT + 6.0)),
+                        anchor: Anchor::CenterRight,
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(3.0, 0.0, 0.0),
+                    ..default()
+                },
+                RenderLayers::layer(UI_LAYER),
+            ));
+            parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: MANA_COLOR,
+                        custom_size: Some(Vec2::new(BAR_WIDTH, BAR_HEIGHT)),
+                        anchor: Anchor::CenterLeft,
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(-BAR_WIDTH, 0.0, 0.1),
+                    ..default()
+                },
+                HudPart::ManaFill,
+                RenderLayers::layer(UI_LAYER),
+            ));
+            parent.spawn((
+                hud_text(&font, Vec3::new(-BAR_WIDTH * 0.5, 0.0, 0.2), Anchor::Center),
+                HudPart::ManaText,
+                RenderLayers::layer(UI_LAYER),
+            ));
+            parent.spawn((
+                hud_text(
+                    &font,
+                    Vec3::new(0.0, -LINE_HEIGHT, 0.0),
+                    Anchor::CenterRight,
+                ),
+                HudPart::WaveText,
+                RenderLayers::layer(UI_LAYER),
+            ));
+            parent.spawn((
+                hud_text(
+                    &font,
+                    Vec3::new(0.0, -LINE_HEIGHT * 2.0, 0.0),
+                    Anchor::CenterRight,
+                ),
+                HudPart::SummonText,
+                RenderLayers::layer(UI_LAYER),
+            ));
+        });
+}
+
+fn wave_text(spawner: &EnemySpawner, level: &ActiveLevel, mode: GameMode) -> String {
+    let next_in = spawner.seconds_to_next_wave();
+    if spawner.wave == 0 {
+        return format!("First wave in {:.0}s", next_in.ceil());
+    }
+    if !spawner.has_next_wave(level, mode) {
+        return format!("Wave {}, the last one", spawner.wave);
+    }
+    if spawner.wave_charge.is_paused() {
+        return format!("Wave {}", spawner.wave);
+    }
+    format!("Wave {}, next in {:.0}s", spawner.wave, next_in.ceil())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_hud(
+    selected: Res<SelectedSummon>,
+    settings: Res<GameSettings>,
+    unit_configs: Res<UnitResource>,
+    progress: Res<MetaProgress>,
+    difficulty: Res<Difficulty>,
+    level: Res<ActiveLevel>,
+    mode: Res<GameMode>,
+    ui_view: Res<UiView>,
+    player_query: Query<(&Mana, &StatModifiers), With<Player>>,
+    spawner_query: Query<&EnemySpawner>,
+    mut hud_query: Query<(&mut Visibility, &mut Transform), With<Hud>>,
+    mut part_query: Query<(&HudPart, Option<&mut Sprite>, Option<&mut Text>)>,
+) {
+    let window_bounds = ui_view.half_size;
+    let player = player_query.get_single().ok();
+
+    for (mut visibility, mut transform) in hud_query.iter_mut() {
+        transform.translation = Vec3::new(
+            window_bounds.x - HUD_MARGIN,
+            window_bounds.y - HUD_MARGIN - BAR_HEIGHT * 0.5,
+            0.0,
+        );
+        // Only there while there's a summoner to show it for
+        let wanted = if player.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+    let Some((mana, modifiers)) = player else {
+        return;
+    };
+
+    let unit_type = selected.0;
+    let cost = unit_configs.summon_cost(unit_type, &difficulty, modifiers);
+    let unlocked = unit_configs.is_unlocked(unit_type) && progress.is_unit_unlocked(unit_type);
+    let key = SUMMON_BINDS
+        .iter()
+        .find(|(_, bound)| *bound == unit_type)
+        .map_or(String::new(), |(binding, _)| {
+            format!("[{}] ", key_name(settings.bindings.key(*binding)))
+        });
+    let summon_value = if unlocked {
+        format!("{}{} {} MP", key, unit_type.name(), cost)
+    } else {
+        format!("{}{} locked", key, unit_type.name())
+    };
+    let summon_color = if unlocked && mana.current_mana >= cost {
+        AFFORDABLE_COLOR
+    } else {
+        UNAFFORDABLE_COLOR
+    };
+    let wave_value = spawner_query
+        .get_single()
+        .map_or(String::new(), |spawner| wave_text(spawner, &level, *mode));
+    let fraction = mana.current_mana as f32 / mana.max_mana.max(1) as f32;
+
+    for (part, sprite, text) in part_query.iter_mut() {
+        let (value, color) = match part {
+            HudPart::ManaFill => {
+                if let Some(mut sprite) = sprite {
+                    let size = Some(Vec2::new(BAR_WIDTH * fraction.min(1.0), BAR_HEIGHT));
+                    if sprite.custom_size != size {
+                        sprite.custom_size = size;
+                    }
+                }
+                continue;
+            }
+            HudPart::ManaText => (
+                format!("{} / {} MP", mana.current_mana, mana.max_mana),
+                TEXT_COLOR,
+            ),
+            HudPart::WaveText => (wave_value.clone(), TEXT_COLOR),
+            HudPart::SummonText => (summon_value.clone(), summon_color),
+        };
+        // Only touching the text when it changed, so it isn't laid out again every frame
+        if let Some(mut text) = text {
+            let section = &text.sections[0];
+            if section.value != value || section.style.color != color {
+                let section = &mut text.sections[0];
+                section.value = value;
+                section.style.color = color;
+            }
+        }
+    }
+}
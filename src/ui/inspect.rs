@@ -0,0 +1,257 @@
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy::sprite::Anchor;
+
+use crate::ai::behavior::CurrentBehavior;
+use crate::player::gravestones::Revived;
+use crate::player::plugin::Player;
+use crate::player::summoning::Channeling;
+use crate::render_scale::{cursor_to_world, UiView, WorldCamera, UI_LAYER};
+use crate::settings::bindings::Binding;
+use crate::settings::config::GameSettings;
+use crate::units::altar::DarkAltar;
+use crate::units::damage::{Armor, Invulnerable};
+use crate::units::flying::Flying;
+use crate::units::health::Health;
+use crate::units::quality::Gifted;
+use crate::units::stat_modifiers::StatModifiers;
+use crate::units::team::{CurrentTeam, Team, ROGUE};
+use crate::units::unit_types::CurrentUnitType;
+use crate::units::veterancy::Veterancy;
+
+use super::nameplate::Nameplate;
+
+// How close to a unit the cursor has to be to pick it, in world units
+const HOVER_RADIUS: f32 = 32.0;
+const PANEL_MARGIN: f32 = 24.0;
+// Leaves room for the latency probe under it
+const PANEL_OFFSET_BOTTOM: f32 = 48.0;
+const MARKER_RADIUS: f32 = 28.0;
+const HOVER_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.5);
+const LOCKED_COLOR: Color = Color::rgb(1.0, 0.85, 0.3);
+
+// The unit under the cursor, or the one K locked on to, which stays inspected wherever the
+// cursor goes until K is pressed again
+#[derive(Resource, Default)]
+pub struct Inspection {
+    pub hovered: Option<Entity>,
+    pub locked: Option<Entity>,
+}
+
+impl Inspection {
+    pub fn target(&self) -> Option<Entity> {
+        self.locked.or(self.hovered)
+    }
+}
+
+#[derive(Component)]
+pub struct InspectionText;
+
+pub fn setup_inspection(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+                    font_size: 22.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_justify(JustifyText::Left),
+            text_anchor: Anchor::BottomLeft,
+            ..default()
+        },
+        InspectionText,
+        RenderLayers::layer(UI_LAYER),
+    ));
+}
+
+type InspectableFilter = Or<(With<CurrentUnitType>, With<Player>, With<DarkAltar>)>;
+
+pub fn pick_inspected(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<GameSettings>,
+    mut inspection: ResMut<Inspection>,
+    window_query: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<WorldCamera>>,
+    target_query: Query<(Entity, &GlobalTransform, &Health), InspectableFilter>,
+) {
+    let cursor = match (window_query.get_single(), camera_query.get_single()) {
+        (Ok(window), Ok((camera, camera_transform))) => {
+            cursor_to_world(window, camera, camera_transform)
+        }
+        _ => None,
+    };
+    inspection.hovered = cursor.and_then(|cursor| {
+        target_query
+            .iter()
+            .filter(|(_, _, health)| !health.is_dead())
+            .map(|(entity, transform, _)| {
+                (entity, transform.translation().truncate().distance(cursor))
+            })
+            .filter(|(_, distance)| *distance <= HOVER_RADIUS)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(entity, _)| entity)
+    });
+
+    if settings.bindings.just_pressed(&keys, Binding::Inspect) {
+        inspection.locked = match inspection.locked {
+            Some(_) => None,
+            None => inspection.hovered,
+        };
+    }
+    // Lets go of whatever died or was cleaned up
+    if inspection
+        .locked
+        .is_some_and(|entity| !target_query.contains(entity))
+    {
+        inspection.locked = None;
+    }
+}
+
+fn team_name(team: Team) -> String {
+    match team {
+        Team::Evil => "Evil".to_owned(),
+        Team::Good => "Good".to_owned(),
+        Team::Neutral => "Neutral".to_owned(),
+        team if team == ROGUE => "Rogue".to_owned(),
+        Team::Faction(faction) => format!("Faction {}", faction),
+    }
+}
+
+type InspectedData = (
+    Option<&'static Nameplate>,
+    Option<&'static CurrentUnitType>,
+    Has<Player>,
+    Has<DarkAltar>,
+    &'static Health,
+    Option<&'static CurrentTeam>,
+    Option<&'static CurrentBehavior>,
+);
+type StatusData = (
+    Option<&'static StatModifiers>,
+    Option<&'static Invulnerable>,
+    Option<&'static Armor>,
+    Option<&'static Veterancy>,
+    Option<&'static Channeling>,
+    Has<Flying>,
+    Has<Gifted>,
+    Has<Revived>,
+);
+
+pub fn update_inspection_panel(
+    inspection: Res<Inspection>,
+    ui_view: Res<UiView>,
+    unit_query: Query<InspectedData>,
+    status_query: Query<StatusData>,
+    mut query: Query<(&mut Text, &mut Transform), With<InspectionText>>,
+) {
+    let window_bounds = ui_view.half_size;
+
+    let value = inspection
+        .target()
+        .and_then(|entity| Some((unit_query.get(entity).ok()?, status_query.get(entity).ok()?)))
+        .map_or(String::new(), |(unit, status)| {
+            let (nameplate, unit_type, is_player, is_altar, health, team, behavior) = unit;
+            let (modifiers, invulnerable, armor, veterancy, channeling, flying, gifted, revived) =
+                status;
+
+            let kind = if is_player {
+                "Summoner"
+            } else if is_altar {
+                "Dark Altar"
+            } else {
+                unit_type.map_or("Unit", |unit_type| unit_type.0.name())
+            };
+            let name = match nameplate {
+                Some(nameplate) => format!("{} the {}", nameplate.0, kind),
+                None => kind.to_owned(),
+            };
+
+            let mut statuses = Vec::new();
+            if let Some(invulnerable) = invulnerable {
+                statuses.push(format!("Invulnerable {:.1}s", invulnerable.remaining));
+            }
+            if let Some(armor) = armor.filter(|armor| !armor.is_broken()) {
+                statuses.push(format!(
+                    "Armored {}/{}",
+                    armor.hits_to_break - armor.hits_taken,
+                    armor.hits_to_break
+                ));
+            }
+            if let Some(channeling) = channeling {
+                statuses.push(format!("Channeling {:.0}%", channeling.progress() * 100.0));
+            }
+            if let Some(veterancy) = veterancy.filter(|veterancy| veterancy.is_veteran()) {
+                statuses.push(format!("Veteran {}", veterancy.rank()));
+            }
+            if flying {
+                statuses.push("Flying".to_owned());
+            }
+            if gifted {
+                statuses.push("Gifted".to_owned());
+            }
+            if revived {
+                statuses.push("Revived".to_owned());
+            }
+            if let Some(modifiers) = modifiers {
+                statuses.extend(
+                    modifiers
+                        .sources()
+                        .iter()
+                        .map(|source| format!("{:?}", source)),
+                );
+            }
+
+            let locked = if inspection.locked.is_some() {
+                " [locked]"
+            } else {
+                ""
+            };
+            format!(
+                "{}{}\nHP: {}/{}\nTeam: {}\nDoing: {}\nStatus: {}",
+                name,
+                locked,
+                health.current.max(0),
+                health.max,
+                team.map_or("None".to_owned(), |team| team_name(team.0)),
+                behavior.map_or("Nothing", |behavior| behavior.0.name()),
+                if statuses.is_empty() {
+                    "None".to_owned()
+                } else {
+                    statuses.join(", ")
+                }
+            )
+        });
+
+    for (mut text, mut transform) in query.iter_mut() {
+        transform.translation = Vec3::new(
+            -window_bounds.x + PANEL_MARGIN,
+            -window_bounds.y + PANEL_MARGIN + PANEL_OFFSET_BOTTOM,
+            0.0,
+        );
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+pub fn draw_inspection_marker(
+    mut gizmos: Gizmos,
+    inspection: Res<Inspection>,
+    query: Query<&GlobalTransform>,
+) {
+    let Some(transform) = inspection
+        .target()
+        .and_then(|entity| query.get(entity).ok())
+    else {
+        return;
+    };
+    let color = if inspection.locked.is_some() {
+        LOCKED_COLOR
+    } else {
+        HOVER_COLOR
+    };
+    gizmos.circle_2d(transform.translation().truncate(), MARKER_RADIUS, color);
+}
@@ -0,0 +1,157 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy::sprite::Anchor;
+
+use crate::events::Damaged;
+use crate::render_scale::{UiView, UI_LAYER};
+use crate::units::damage::DamageKind;
+use crate::units::unit_types::CurrentUnitType;
+
+use super::nameplate::Nameplate;
+
+const COMBAT_LOG_CAPACITY: usize = 200;
+const KILL_FEED_CAPACITY: usize = 5;
+const KILL_FEED_DURATION: f32 = 5.0;
+const KILL_FEED_MARGIN: f32 = 24.0;
+
+#[derive(Resource, Default)]
+pub struct CombatLog {
+    pub entries: VecDeque<String>,
+}
+
+impl CombatLog {
+    pub fn push(&mut self, entry: String) {
+        if self.entries.len() == COMBAT_LOG_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct KillFeed {
+    pub entries: VecDeque<(String, Timer)>,
+}
+
+impl KillFeed {
+    pub fn push(&mut self, entry: String) {
+        if self.entries.len() == KILL_FEED_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((
+            entry,
+            Timer::from_seconds(KILL_FEED_DURATION, TimerMode::Once),
+        ));
+    }
+}
+
+#[derive(Component)]
+pub struct KillFeedText;
+
+pub fn display_name(nameplate: Option<&Nameplate>, unit_type: Option<&CurrentUnitType>) -> String {
+    match (nameplate, unit_type) {
+        (Some(nameplate), Some(unit_type)) => {
+            format!("{} the {}", nameplate.0, unit_type.0.name())
+        }
+        (Some(nameplate), None) => nameplate.0.clone(),
+        (None, Some(unit_type)) => unit_type.0.name().to_owned(),
+        (None, None) => "Someone".to_owned(),
+    }
+}
+
+fn damage_kind_name(kind: DamageKind) -> &'static str {
+    match kind {
+        DamageKind::Physical => "physical",
+        DamageKind::Dark => "dark",
+        DamageKind::Fire => "fire",
+    }
+}
+
+pub fn record_combat_log(
+    mut damaged_reader: EventReader<Damaged>,
+    names_query: Query<(Option<&Nameplate>, Option<&CurrentUnitType>)>,
+    mut combat_log: ResMut<CombatLog>,
+    mut kill_feed: ResMut<KillFeed>,
+) {
+    for damaged in damaged_reader.read() {
+        let Ok((nameplate, unit_type)) = names_query.get(damaged.target) else {
+            continue;
+        };
+
+        let name = display_name(nameplate, unit_type);
+        if damaged.dodged {
+            combat_log.push(format!("{} dodged an attack", name));
+            continue;
+        }
+        combat_log.push(format!(
+            "{} took {} {} damage{}",
+            name,
+            damaged.amount,
+            damage_kind_name(damaged.kind),
+            if damaged.critical { " (critical)" } else { "" }
+        ));
+
+        if !damaged.killed {
+            continue;
+        }
+
+        combat_log.push(format!("{} was slain", name));
+        // Only named units make it into the kill feed, otherwise late waves drown it
+        if nameplate.is_some() {
+            kill_feed.push(format!("{} was slain", name));
+        }
+    }
+}
+
+pub fn setup_kill_feed(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+                    font_size: 28.0,
+                    color: Color::rgb(0.9, 0.3, 0.3),
+                },
+            )
+            .with_justify(JustifyText::Left),
+            text_anchor: Anchor::TopLeft,
+            ..default()
+        },
+        KillFeedText,
+        RenderLayers::layer(UI_LAYER),
+    ));
+}
+
+pub fn update_kill_feed(
+    time: Res<Time>,
+    ui_view: Res<UiView>,
+    mut kill_feed: ResMut<KillFeed>,
+    mut query: Query<(&mut Text, &mut Transform), With<KillFeedText>>,
+) {
+    for (_, timer) in kill_feed.entries.iter_mut() {
+        timer.tick(time.delta());
+    }
+    kill_feed.entries.retain(|(_, timer)| !timer.finished());
+
+    let window_bounds = ui_view.half_size;
+    for (mut text, mut transform) in query.iter_mut() {
+        transform.translation = Vec3::new(
+            -window_bounds.x + KILL_FEED_MARGIN,
+            window_bounds.y - KILL_FEED_MARGIN,
+            0.0,
+        );
+
+        let value = kill_feed
+            .entries
+            .iter()
+            .map(|(entry, _)| entry.as_str())
+            .collect::<Vec<&str>>()
+            .join("\n");
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::VecDeque;
+
+use bevy::core::FrameCount;
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy::sprite::Anchor;
+
+use crate::player::plugin::Player;
+use crate::render_scale::{UiView, UI_LAYER};
+use crate::settings::bindings::Binding;
+use crate::settings::config::GameSettings;
+
+use super::nameplate::RenameState;
+
+// Walking into a wall never moves the summoner, so a press that goes nowhere is let go of
+const MAX_WAIT_FRAMES: u32 = 30;
+const SAMPLE_CAPACITY: usize = 20;
+const PROBE_MARGIN: f32 = 24.0;
+
+// Counts the frames from a movement key going down to the summoner having moved, 0 being the
+// same frame. Off unless toggled, it's for checking the frame order hasn't regressed.
+#[derive(Resource, Default)]
+pub struct LatencyProbe {
+    pub enabled: bool,
+    pressed: Option<(u32, Vec2)>,
+    samples: VecDeque<u32>,
+}
+
+impl LatencyProbe {
+    fn record(&mut self, frames: u32) {
+        if self.samples.len() == SAMPLE_CAPACITY {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(frames);
+    }
+
+    fn latest(&self) -> Option<u32> {
+        self.samples.back().copied()
+    }
+
+    fn worst(&self) -> Option<u32> {
+        self.samples.iter().max().copied()
+    }
+}
+
+#[derive(Component)]
+pub struct LatencyProbeText;
+
+pub fn setup_latency_probe(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+                    font_size: 24.0,
+                    color: Color::rgb(0.5, 1.0, 0.5),
+                },
+            )
+            .with_justify(JustifyText::Left),
+            text_anchor: Anchor::BottomLeft,
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        LatencyProbeText,
+        RenderLayers::layer(UI_LAYER),
+    ));
+}
+
+pub fn toggle_latency_probe(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut probe: ResMut<LatencyProbe>,
+    mut query: Query<&mut Visibility, With<LatencyProbeText>>,
+) {
+    if !keys.just_pressed(KeyCode::F3) {
+        return;
+    }
+
+    *probe = LatencyProbe {
+        enabled: !probe.enabled,
+        ..default()
+    };
+    for mut visibility in query.iter_mut() {
+        *visibility = if probe.enabled {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+// Runs with the rest of the input, so it sees the press on the frame it arrives
+pub fn probe_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<GameSettings>,
+    frame_count: Res<FrameCount>,
+    rename_state: Res<RenameState>,
+    mut probe: ResMut<LatencyProbe>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    if !probe.enabled || probe.pressed.is_some() || rename_state.is_active() {
+        return;
+    }
+    if !keys.any_just_pressed(Binding::MOVEMENT.map(|binding| settings.bindings.key(binding))) {
+        return;
+    }
+    let Ok(transform) = player_query.get_single() else {
+        return;
+    };
+
+    probe.pressed = Some((frame_count.0, transform.translation.truncate()));
+}
+
+// Runs after everything has moved, the same point the fog and the silhouettes look at positions
+pub fn probe_movement(
+    frame_count: Res<FrameCount>,
+    mut probe: ResMut<LatencyProbe>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let Some((pressed_frame, start)) = probe.pressed else {
+        return;
+    };
+    let frames = frame_count.0.wrapping_sub(pressed_frame);
+    let moved = player_query
+        .get_single()
+        .is_ok_and(|transform| transform.translation.truncate() != start);
+
+    if moved {
+        probe.record(frames);
+        probe.pressed = None;
+    } else if frames > MAX_WAIT_FRAMES {
+        probe.pressed = None;
+    }
+}
+
+pub fn update_latency_probe_text(
+    time: Res<Time<Real>>,
+    probe: Res<LatencyProbe>,
+    ui_view: Res<UiView>,
+    mut query: Query<(&mut Text, &mut Transform), With<LatencyProbeText>>,
+) {
+    if !probe.enabled {
+        return;
+    }
+
+    let window_bounds = ui_view.half_size;
+    let frame_ms = time.delta_seconds() * 1000.0;
+    let value = match (probe.latest(), probe.worst()) {
+        (Some(latest), Some(worst)) => format!(
+            "Input lag: {} frames, worst {} of last {} ({:.1} ms/frame)",
+            latest,
+            worst,
+            probe.samples.len(),
+            frame_ms
+        ),
+        _ => format!("Input lag: press WASD ({:.1} ms/frame)", frame_ms),
+    };
+
+    for (mut text, mut transform) in query.iter_mut() {
+        transform.translation = Vec3::new(
+            -window_bounds.x + PROBE_MARGIN,
+            -window_bounds.y + PROBE_MARGIN,
+            0.0,
+        );
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
@@ -1,15 +1,46 @@
 use bevy::prelude::*;
 
-use crate::{mana::Mana, player::plugin::Player};
+use crate::levels::definition::ActiveLevel;
+use crate::player::summoning::alive_summons;
+use crate::units::{health::Health, team::CurrentTeam, unit_types::CurrentUnitType};
+
+use crate::player::{
+    corruption::Corruption, perks::Experience, plugin::Player, ultimate::UltimateCharge,
+};
 
 use super::plugin::ManaText;
 
 pub fn update_mana_text(
-    query: Query<&Mana, With<Player>>,
+    experience: Res<Experience>,
+    level: Res<ActiveLevel>,
+    summon_query: Query<(&CurrentTeam, &Health), With<CurrentUnitType>>,
+    query: Query<(&UltimateCharge, &Corruption), With<Player>>,
     mut text_query: Query<&mut Text, With<ManaText>>,
 ) {
-    if let Some(mana) = query.iter().next() {
+    if let Some((ultimate, corruption)) = query.iter().next() {
         let mut text = text_query.single_mut();
-        text.sections[0].value = format!("MP: {}", mana.current_mana);
+        let ultimate_text = if ultimate.is_ready() {
+            "ULT: READY".to_owned()
+        } else {
+            format!("ULT: {:.0}%", ultimate.fraction() * 100.0)
+        };
+        let summons = alive_summons(summon_query.iter());
+        let upkeep = level.summons.upkeep(summons);
+        let upkeep_text = if upkeep > 0.0 {
+            format!(" (-{:.1} MP/s)", upkeep)
+        } else {
+            String::new()
+        };
+        text.sections[0].value = format!(
+            "SUMMONS: {}/{}{}\n{}\nCORRUPTION: {:.0}%\nLV {}: {}/{} XP",
+            summons,
+            level.summons.max_summons,
+            upkeep_text,
+            ultimate_text,
+            corruption.fraction() * 100.0,
+            experience.level,
+            experience.points,
+            experience.to_next_level()
+        );
     }
 }
@@ -0,0 +1,216 @@
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy::utils::{HashMap, HashSet};
+
+use crate::camera::arena_half_size;
+use crate::player::plugin::Player;
+use crate::render_scale::{UiView, UI_LAYER};
+use crate::settings::accessibility::{AccessibilitySettings, TeamPalette};
+use crate::units::altar::DarkAltar;
+use crate::units::team::{CurrentTeam, Team};
+
+// The map keeps the arena's shape, this is how wide it is on screen
+const MINIMAP_WIDTH: f32 = 240.0;
+const MINIMAP_MARGIN: f32 = 20.0;
+const BACKGROUND_COLOR: Color = Color::rgba(0.05, 0.05, 0.08, 0.75);
+const BORDER_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.3);
+const BORDER_WIDTH: f32 = 2.0;
+
+const UNIT_DOT_SIZE: f32 = 4.0;
+const PLAYER_DOT_SIZE: f32 = 7.0;
+const ALTAR_DOT_SIZE: f32 = 10.0;
+const PLAYER_COLOR: Color = Color::WHITE;
+const ALTAR_COLOR: Color = Color::rgb(0.7, 0.3, 0.9);
+
+#[derive(Component, Default)]
+pub struct Minimap {
+    // The dot for everything on the map, by what it's tracking
+    dots: HashMap<Entity, Entity>,
+}
+
+#[derive(Component)]
+pub struct MinimapBackground;
+
+#[derive(Component, Clone, Copy, PartialEq)]
+pub enum DotKind {
+    Unit(Team),
+    Player,
+    Altar,
+}
+
+impl DotKind {
+    fn color(&self, palette: TeamPalette) -> Color {
+        match self {
+            DotKind::Unit(team) => palette.color(*team),
+            DotKind::Player => PLAYER_COLOR,
+            DotKind::Altar => ALTAR_COLOR,
+        }
+    }
+
+    fn size(&self) -> f32 {
+        match self {
+            DotKind::Unit(_) => UNIT_DOT_SIZE,
+            DotKind::Player => PLAYER_DOT_SIZE,
+            DotKind::Altar => ALTAR_DOT_SIZE,
+        }
+    }
+
+    // The player and the altar always stay on top of the crowd
+    fn z(&self) -> f32 {
+        match self {
+            DotKind::Unit(_) => 1.0,
+            DotKind::Altar => 2.0,
+            DotKind::Player => 3.0,
+        }
+    }
+}
+
+fn minimap_size(window: &Window) -> Vec2 {
+    let arena = arena_half_size(window);
+    Vec2::new(MINIMAP_WIDTH, MINIMAP_WIDTH * arena.y / arena.x.max(1.0))
+}
+
+pub fn setup_minimap(mut commands: Commands) {
+    commands
+        .spawn((
+            SpatialBundle::default(),
+            Minimap::default(),
+            RenderLayers::layer(UI_LAYER),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: BORDER_COLOR,
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, 0.0, -0.1),
+                    ..default()
+                },
+                MinimapBackground,
+                RenderLayers::layer(UI_LAYER),
+            ));
+            parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: BACKGROUND_COLOR,
+                        ..default()
+                    },
+                    ..default()
+                },
+                MinimapBackground,
+                RenderLayers::layer(UI_LAYER),
+            ));
+        });
+}
+
+type TrackedData = (
+    Entity,
+    &'static GlobalTransform,
+    Option<&'static CurrentTeam>,
+    Has<Player>,
+    Has<DarkAltar>,
+);
+type TrackedFilter = Or<(With<CurrentTeam>, With<Player>, With<DarkAltar>)>;
+type BackgroundFilter = (With<MinimapBackground>, Without<Minimap>, Without<DotKind>);
+type DotFilter = (Without<Minimap>, Without<MinimapBackground>);
+
+// Squashes world positions down to the map, which sits in the bottom right corner and follows
+// the window around when it's resized
+#[allow(clippy::too_many_arguments)]
+pub fn update_minimap(
+    mut commands: Commands,
+    ui_view: Res<UiView>,
+    accessibility: Res<AccessibilitySettings>,
+    window_query: Query<&Window>,
+    tracked_query: Query<TrackedData, TrackedFilter>,
+    mut minimap_query: Query<(Entity, &mut Minimap, &mut Transform), Without<DotKind>>,
+    mut background_query: Query<(&mut Sprite, &Transform), BackgroundFilter>,
+    mut dot_query: Query<(&mut Transform, &mut Sprite, &mut DotKind), DotFilter>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let Ok((minimap_entity, mut minimap, mut minimap_transform)) = minimap_query.get_single_mut()
+    else {
+        return;
+    };
+
+    let window_bounds = ui_view.half_size;
+    let size = minimap_size(window);
+    let corner = window_bounds - size * 0.5 - Vec2::splat(MINIMAP_MARGIN);
+    minimap_transform.translation = Vec3::new(corner.x, -corner.y, 0.0);
+    for (mut sprite, transform) in background_query.iter_mut() {
+        // The one behind is the border, it pokes out around the other
+        let background_size = if transform.translation.z < 0.0 {
+            size + Vec2::splat(BORDER_WIDTH * 2.0)
+        } else {
+            size
+        };
+        if sprite.custom_size != Some(background_size) {
+            sprite.custom_size = Some(background_size);
+        }
+    }
+
+    let scale = size * 0.5 / arena_half_size(window);
+    let mut seen = HashSet::new();
+    for (entity, transform, team, is_player, is_altar) in tracked_query.iter() {
+        let kind = if is_player {
+            DotKind::Player
+        } else if is_altar {
+            DotKind::Altar
+        } else if let Some(team) = team {
+            DotKind::Unit(team.0)
+        } else {
+            continue;
+        };
+        seen.insert(entity);
+
+        let position = (transform.translation().truncate() * scale).clamp(-size * 0.5, size * 0.5);
+        let translation = position.extend(kind.z());
+        let color = kind.color(accessibility.palette);
+        match minimap
+            .dots
+            .get(&entity)
+            .and_then(|dot| dot_query.get_mut(*dot).ok())
+        {
+            Some((mut dot_transform, mut sprite, mut dot_kind)) => {
+                dot_transform.translation = translation;
+                // Converted units change color with their team, and every dot with the palette
+                if *dot_kind != kind || sprite.color != color {
+                    *dot_kind = kind;
+                    sprite.color = color;
+                    sprite.custom_size = Some(Vec2::splat(kind.size()));
+                }
+            }
+            None => {
+                let dot = commands
+                    .spawn((
+                        SpriteBundle {
+                            sprite: Sprite {
+                                color,
+                                custom_size: Some(Vec2::splat(kind.size())),
+                                ..default()
+                            },
+                            transform: Transform::from_translation(translation),
+                            ..default()
+                        },
+                        kind,
+                        RenderLayers::layer(UI_LAYER),
+                    ))
+                    .id();
+                commands.entity(minimap_entity).add_child(dot);
+                minimap.dots.insert(entity, dot);
+            }
+        }
+    }
+
+    // Whatever died or was cleaned up since the last frame
+    minimap.dots.retain(|tracked, dot| {
+        let keep = seen.contains(tracked);
+        if !keep {
+            commands.entity(*dot).despawn_recursive();
+        }
+        keep
+    });
+}
@@ -0,0 +1,216 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::rng::GameRng;
+use crate::settings::bindings::Binding;
+use crate::settings::config::GameSettings;
+
+const NAMEPLATE_OFFSET_Y: f32 = 40.0;
+const NAMEPLATE_FONT_SIZE: f32 = 22.0;
+const MAX_NAME_LENGTH: usize = 16;
+
+const NAME_PREFIXES: [&str; 10] = [
+    "Mor", "Gra", "Zul", "Vex", "Kra", "Ul", "Nek", "Sha", "Dra", "Bel",
+];
+const NAME_SUFFIXES: [&str; 10] = [
+    "gath", "mir", "dus", "zor", "rak", "thul", "ix", "oth", "vane", "gul",
+];
+
+#[derive(Component, Clone, Debug)]
+pub struct Nameplate(pub String);
+
+#[derive(Component)]
+pub struct NameplateText;
+
+#[derive(Resource)]
+pub struct NameplateSettings {
+    pub visible: bool,
+    pub auto_name_summons: bool,
+}
+
+impl Default for NameplateSettings {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            auto_name_summons: true,
+        }
+    }
+}
+
+// The most recently summoned unit, which is the one the rename key renames
+#[derive(Resource, Default)]
+pub struct LastSummon(pub Option<Entity>);
+
+#[derive(Resource, Default)]
+pub struct RenameState {
+    pub target: Option<Entity>,
+    pub buffer: String,
+}
+
+impl RenameState {
+    pub fn is_active(&self) -> bool {
+        self.target.is_some()
+    }
+}
+
+pub fn not_renaming(rename_state: Res<RenameState>) -> bool {
+    !rename_state.is_active()
+}
+
+pub fn generate_summon_name(rng: &mut impl Rng) -> String {
+    format!(
+        "{}{}",
+        NAME_PREFIXES[rng.gen_range(0..NAME_PREFIXES.len())],
+        NAME_SUFFIXES[rng.gen_range(0..NAME_SUFFIXES.len())]
+    )
+}
+
+pub fn name_new_summon(
+    commands: &mut Commands,
+    rng: &mut GameRng,
+    settings: &NameplateSettings,
+    last_summon: &mut LastSummon,
+    entity: Entity,
+) {
+    last_summon.0 = Some(entity);
+    if settings.auto_name_summons {
+        commands
+            .entity(entity)
+            .insert(Nameplate(generate_summon_name(&mut rng.0)));
+    }
+}
+
+pub fn spawn_nameplates(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<NameplateSettings>,
+    query: Query<(Entity, &Nameplate, &Transform), Added<Nameplate>>,
+) {
+    for (entity, nameplate, transform) in query.iter() {
+        // The text is a child of the unit, undo the unit scale so all names are the same size
+        let inverse_scale = Vec3::ONE / transform.scale.max(Vec3::splat(0.01));
+        let text = commands
+            .spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        nameplate.0.clone(),
+                        TextStyle {
+                            font: asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+                            font_size: NAMEPLATE_FONT_SIZE,
+                            color: Color::WHITE,
+                        },
+                    )
+                    .with_justify(JustifyText::Center),
+                    transform: Transform::from_xyz(0.0, NAMEPLATE_OFFSET_Y, 2.0)
+                        .with_scale(inverse_scale),
+                    visibility: nameplate_visibility(&settings),
+                    ..default()
+                },
+                NameplateText,
+            ))
+            .id();
+        commands.entity(entity).add_child(text);
+    }
+}
+
+fn nameplate_visibility(settings: &NameplateSettings) -> Visibility {
+    if settings.visible {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
+
+pub fn toggle_nameplates(
+    keys: Res<ButtonInput<KeyCode>>,
+    game_settings: Res<GameSettings>,
+    rename_state: Res<RenameState>,
+    mut settings: ResMut<NameplateSettings>,
+    mut query: Query<&mut Visibility, With<NameplateText>>,
+) {
+    let toggled = game_settings
+        .bindings
+        .just_pressed(&keys, Binding::ToggleNameplates);
+    if rename_state.is_active() || !toggled {
+        return;
+    }
+
+    settings.visible = !settings.visible;
+    for mut visibility in query.iter_mut() {
+        *visibility = nameplate_visibility(&settings);
+    }
+}
+
+pub fn rename_summon(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<GameSettings>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut rename_state: ResMut<RenameState>,
+    last_summon: Res<LastSummon>,
+    mut commands: Commands,
+    nameplate_query: Query<Option<&Nameplate>>,
+) {
+    let Some(target) = rename_state.target else {
+        characters.clear();
+        if !settings.bindings.just_pressed(&keys, Binding::Rename) {
+            return;
+        }
+
+        // Only start renaming if the last summon is still around
+        if let Some(Ok(nameplate)) = last_summon.0.map(|entity| nameplate_query.get(entity)) {
+            rename_state.target = last_summon.0;
+            rename_state.buffer = nameplate.map_or(String::new(), |name| name.0.clone());
+        }
+        return;
+    };
+
+    if nameplate_query.get(target).is_err() || keys.just_pressed(KeyCode::Escape) {
+        *rename_state = RenameState::default();
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Enter) {
+        let name = rename_state.buffer.trim().to_owned();
+        if !name.is_empty() {
+            commands.entity(target).insert(Nameplate(name));
+        }
+        *rename_state = RenameState::default();
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Backspace) {
+        rename_state.buffer.pop();
+    }
+
+    for event in characters.read() {
+        for character in event.char.chars() {
+            if (character.is_alphanumeric() || character == ' ')
+                && rename_state.buffer.chars().count() < MAX_NAME_LENGTH
+            {
+                rename_state.buffer.push(character);
+            }
+        }
+    }
+}
+
+pub fn update_nameplate_text(
+    rename_state: Res<RenameState>,
+    query: Query<(Entity, &Nameplate, &Children)>,
+    mut text_query: Query<&mut Text, With<NameplateText>>,
+) {
+    for (entity, nameplate, children) in query.iter() {
+        let value = if rename_state.target == Some(entity) {
+            format!("{}_", rename_state.buffer)
+        } else {
+            nameplate.0.clone()
+        };
+
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                if text.sections[0].value != value {
+                    text.sections[0].value = value.clone();
+                }
+            }
+        }
+    }
+}
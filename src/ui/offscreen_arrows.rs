@@ -0,0 +1,132 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::camera::CameraController;
+use crate::enemies::enemy_spawner::EnemySpawner;
+use crate::enemies::spawn_queue::SpawnQueue;
+use crate::game_mode::GameMode;
+use crate::levels::definition::ActiveLevel;
+use crate::render_scale::WorldCamera;
+use crate::settings::accessibility::AccessibilitySettings;
+use crate::units::health::Health;
+use crate::units::team::{AllianceMatrix, CurrentTeam, Team};
+
+// Enemies off screen are grouped by which way they are from the middle of the view, one arrow
+// for every slice of the circle that has any
+const SECTORS: usize = 16;
+// In screen pixels, scaled with the zoom so they look the same size however far in it is
+const ARROW_MARGIN: f32 = 20.0;
+const ARROW_LENGTH: f32 = 18.0;
+const ARROW_WIDTH: f32 = 14.0;
+// Further away than this past the edge of the screen and the arrow is as faint as it gets
+const FADE_DISTANCE: f32 = 800.0;
+const MIN_ALPHA: f32 = 0.25;
+// The spawn points get their arrows this long before the next wave comes out of them
+const WAVE_WARNING_SECONDS: f32 = 5.0;
+const SPAWN_ARROW_COLOR: Color = Color::rgb(1.0, 0.7, 0.2);
+
+// Where the edge of the view is crossed going from its middle towards the target, None when the
+// target is on screen
+fn edge_point(center: Vec2, edge: Vec2, target: Vec2) -> Option<(Vec2, Vec2)> {
+    let offset = target - center;
+    if offset.x.abs() <= edge.x && offset.y.abs() <= edge.y {
+        return None;
+    }
+
+    let direction = offset.normalize_or_zero();
+    let along_x = if direction.x == 0.0 {
+        f32::INFINITY
+    } else {
+        edge.x / direction.x.abs()
+    };
+    let along_y = if direction.y == 0.0 {
+        f32::INFINITY
+    } else {
+        edge.y / direction.y.abs()
+    };
+    Some((center + direction * along_x.min(along_y), direction))
+}
+
+fn draw_arrow(gizmos: &mut Gizmos, tip: Vec2, direction: Vec2, zoom: f32, color: Color) {
+    let back = tip - direction * ARROW_LENGTH * zoom;
+    let side = direction.perp() * ARROW_WIDTH * 0.5 * zoom;
+    gizmos.linestrip_2d([tip, back + side, back - side, tip], color);
+}
+
+fn faded(color: Color, tip: Vec2, target: Vec2) -> Color {
+    let fade = (tip.distance(target) / FADE_DISTANCE).clamp(0.0, 1.0);
+    color.with_a(1.0 - fade * (1.0 - MIN_ALPHA))
+}
+
+// So the summoner isn't caught out by what's coming from outside the view now that the camera
+// follows them around
+#[allow(clippy::too_many_arguments)]
+pub fn draw_offscreen_arrows(
+    mut gizmos: Gizmos,
+    accessibility: Res<AccessibilitySettings>,
+    alliances: Res<AllianceMatrix>,
+    spawn_queue: Res<SpawnQueue>,
+    level: Res<ActiveLevel>,
+    mode: Res<GameMode>,
+    window_query: Query<&Window>,
+    camera_query: Query<&CameraController, With<WorldCamera>>,
+    spawner_query: Query<&EnemySpawner>,
+    unit_query: Query<(&Transform, &CurrentTeam, &Health)>,
+) {
+    let (Ok(window), Ok(camera)) = (window_query.get_single(), camera_query.get_single()) else {
+        return;
+    };
+    let center = camera.position;
+    let half_view = Vec2::new(window.width(), window.height()) * 0.5 * camera.zoom;
+    let edge = (half_view - Vec2::splat(ARROW_MARGIN * camera.zoom)).max(Vec2::ZERO);
+
+    // The arrow for a group points at its middle, and fades with how far its closest one is
+    // In the enemies' color from the palette, the same as their outlines and minimap dots
+    let enemy_color = accessibility.palette.color(Team::Good);
+    let mut sectors = [(Vec2::ZERO, 0u32, f32::INFINITY); SECTORS];
+    for (transform, team, health) in unit_query.iter() {
+        if health.is_dead() || !alliances.is_hostile(Team::Evil, team.0) {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        if edge_point(center, half_view, position).is_none() {
+            continue;
+        }
+        let offset = position - center;
+        let angle = offset.y.atan2(offset.x).rem_euclid(TAU);
+        let sector = &mut sectors[(angle / TAU * SECTORS as f32) as usize % SECTORS];
+        sector.0 += position;
+        sector.1 += 1;
+        sector.2 = sector.2.min(offset.length());
+    }
+    for (sum, count, closest) in sectors.iter() {
+        if *count == 0 {
+            continue;
+        }
+        let middle = *sum / *count as f32;
+        if let Some((tip, direction)) = edge_point(center, edge, middle) {
+            // Faded by the closest one, a big group far off with one runner close still shows
+            let closest_target = center + direction * *closest;
+            let color = faded(enemy_color, tip, closest_target);
+            draw_arrow(&mut gizmos, tip, direction, camera.zoom, color);
+        }
+    }
+
+    let wave_coming = spawner_query.get_single().is_ok_and(|spawner| {
+        spawner.has_next_wave(&level, *mode)
+            && spawner.seconds_to_next_wave() <= WAVE_WARNING_SECONDS
+    });
+    let spawn_points = level.spawn_points.iter().filter(|_| wave_coming);
+    let queued = spawn_queue
+        .pending
+        .iter()
+        .filter(|request| alliances.is_hostile(Team::Evil, request.team))
+        .map(|request| &request.position);
+    for target in spawn_points.chain(queued) {
+        if let Some((tip, direction)) = edge_point(center, edge, *target) {
+            let color = faded(SPAWN_ARROW_COLOR, tip, *target);
+            draw_arrow(&mut gizmos, tip, direction, camera.zoom, color);
+        }
+    }
+}
@@ -1,8 +1,24 @@
 use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
 
-use crate::{dark_arts_defense::GameEvent, gamestate::GameState};
+use crate::camera;
+use crate::difficulty::Difficulty;
+use crate::levels::definition::ActiveLevel;
+use crate::narrative::conversation::conversation_closed;
+use crate::render_scale::{UiView, UI_LAYER};
+use crate::rng::{self, GameRng, RunSeed};
+use crate::save::checkpoints::Checkpoints;
+use crate::schedule::FrameSet;
+use crate::settings::menu::settings_closed;
+use crate::stats::run_stats::RunStats;
+use crate::{dark_arts_defense::AppState, events::GameEvent, gamestate::GameState};
 
-use super::{health_text, mana_text, score_text};
+use super::nameplate::not_renaming;
+use super::{
+    boss_bar, damage_numbers, dialogue, health_text, hud, inspect, kill_feed, latency_probe,
+    mana_text, minimap, nameplate, offscreen_arrows, relic_choice, score_text, summon_roster,
+    wave_grade, wave_preview,
+};
 
 pub struct UiPlugin;
 
@@ -20,18 +36,102 @@ pub struct GameOverText;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup).add_systems(
-            Update,
-            (
-                update_health_pos,
-                update_mana_pos,
-                update_score_pos,
-                health_text::update_health_text,
-                mana_text::update_mana_text,
-                score_text::update_mana_text,
-                game_over_ui,
-            ),
-        );
+        app.init_resource::<nameplate::NameplateSettings>()
+            .init_resource::<nameplate::LastSummon>()
+            .init_resource::<nameplate::RenameState>()
+            .init_resource::<kill_feed::CombatLog>()
+            .init_resource::<kill_feed::KillFeed>()
+            .init_resource::<latency_probe::LatencyProbe>()
+            .init_resource::<inspect::Inspection>()
+            .add_systems(
+                Startup,
+                (
+                    setup,
+                    kill_feed::setup_kill_feed,
+                    boss_bar::setup_boss_bar,
+                    relic_choice::setup_relic_choice,
+                    dialogue::setup_dialogue,
+                    wave_grade::setup_wave_grade,
+                    latency_probe::setup_latency_probe,
+                    summon_roster::setup_summon_roster,
+                    minimap::setup_minimap,
+                    hud::setup_hud,
+                    inspect::setup_inspection,
+                    wave_preview::setup_wave_preview,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    update_health_pos,
+                    update_mana_pos,
+                    update_score_pos,
+                    health_text::update_health_text,
+                    mana_text::update_mana_text,
+                    score_text::update_mana_text,
+                    game_over_ui
+                        .run_if(settings_closed)
+                        .run_if(conversation_closed),
+                    nameplate::spawn_nameplates,
+                    (nameplate::toggle_nameplates, nameplate::rename_summon)
+                        .run_if(settings_closed)
+                        .run_if(conversation_closed),
+                    nameplate::update_nameplate_text,
+                    kill_feed::record_combat_log,
+                    kill_feed::update_kill_feed,
+                    damage_numbers::spawn_damage_numbers,
+                    damage_numbers::update_damage_numbers,
+                    (boss_bar::show_boss_bar, boss_bar::update_boss_bar).chain(),
+                    relic_choice::relic_choice_system
+                        .run_if(not_renaming)
+                        .in_set(FrameSet::Input),
+                    relic_choice::update_relic_choice_screen,
+                    (
+                        dialogue::clear_dialogue_system,
+                        dialogue::show_dialogue,
+                        dialogue::update_dialogue,
+                    )
+                        .chain(),
+                    (
+                        wave_grade::clear_wave_grade_system,
+                        wave_grade::show_wave_grade,
+                        wave_grade::update_wave_grade,
+                    )
+                        .chain(),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    summon_roster::update_summon_roster,
+                    summon_roster::announce_unlocks,
+                    minimap::update_minimap.in_set(FrameSet::Presentation),
+                    hud::update_hud.in_set(FrameSet::Presentation),
+                    inspect::pick_inspected
+                        .run_if(not_renaming)
+                        .in_set(FrameSet::Input),
+                    (
+                        inspect::update_inspection_panel,
+                        inspect::draw_inspection_marker,
+                    )
+                        .in_set(FrameSet::Presentation),
+                    wave_preview::start_early_input
+                        .run_if(not_renaming)
+                        .in_set(FrameSet::Input),
+                    wave_preview::update_wave_preview.in_set(FrameSet::Presentation),
+                    offscreen_arrows::draw_offscreen_arrows
+                        .in_set(FrameSet::Presentation)
+                        .after(camera::follow_player),
+                    latency_probe::toggle_latency_probe,
+                    latency_probe::probe_input.in_set(FrameSet::Input),
+                    (
+                        latency_probe::probe_movement,
+                        latency_probe::update_latency_probe_text,
+                    )
+                        .chain()
+                        .in_set(FrameSet::Presentation),
+                ),
+            );
     }
 }
 
@@ -61,6 +161,7 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, window_query: Q
             ..default()
         },
         ManaText,
+        RenderLayers::layer(UI_LAYER),
     ));
     commands.spawn((
         Text2dBundle {
@@ -80,6 +181,7 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, window_query: Q
             ..default()
         },
         HealthText,
+        RenderLayers::layer(UI_LAYER),
     ));
     commands.spawn((
         Text2dBundle {
@@ -99,6 +201,7 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, window_query: Q
             ..default()
         },
         ScoreText,
+        RenderLayers::layer(UI_LAYER),
     ));
     commands.spawn((
         Text2dBundle {
@@ -115,12 +218,12 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, window_query: Q
             ..default()
         },
         GameOverText,
+        RenderLayers::layer(UI_LAYER),
     ));
 }
 
-fn update_text_pos(window_query: Query<&Window>, transform: &mut Transform, direction: f32) {
-    let window = window_query.single();
-    let window_bounds = Vec2::new(window.width(), window.height()) * 0.5;
+fn update_text_pos(ui_view: Res<UiView>, transform: &mut Transform, direction: f32) {
+    let window_bounds = ui_view.half_size;
 
     transform.translation = Vec3::new(
         window_bounds.x * direction * TEXT_OFFSET_CENTER,
@@ -129,23 +232,16 @@ fn update_text_pos(window_query: Query<&Window>, transform: &mut Transform, dire
     );
 }
 
-fn update_mana_pos(window_query: Query<&Window>, mut query: Query<&mut Transform, With<ManaText>>) {
-    update_text_pos(window_query, &mut query.single_mut(), 1.0);
+fn update_mana_pos(ui_view: Res<UiView>, mut query: Query<&mut Transform, With<ManaText>>) {
+    update_text_pos(ui_view, &mut query.single_mut(), 1.0);
 }
 
-fn update_health_pos(
-    window_query: Query<&Window>,
-    mut query: Query<&mut Transform, With<HealthText>>,
-) {
-    update_text_pos(window_query, &mut query.single_mut(), -1.0);
+fn update_health_pos(ui_view: Res<UiView>, mut query: Query<&mut Transform, With<HealthText>>) {
+    update_text_pos(ui_view, &mut query.single_mut(), -1.0);
 }
 
-fn update_score_pos(
-    window_query: Query<&Window>,
-    mut query: Query<&mut Transform, With<ScoreText>>,
-) {
-    let window = window_query.single();
-    let window_bounds = Vec2::new(window.width(), window.height()) * 0.5;
+fn update_score_pos(ui_view: Res<UiView>, mut query: Query<&mut Transform, With<ScoreText>>) {
+    let window_bounds = ui_view.half_size;
 
     let mut transform = query.single_mut();
     transform.translation = Vec3::new(
@@ -155,22 +251,70 @@ fn update_score_pos(
     );
 }
 
+#[allow(clippy::too_many_arguments)]
 fn game_over_ui(
     keys: Res<ButtonInput<KeyCode>>,
-    mut visible_query: Query<&mut Visibility, With<GameOverText>>,
+    difficulty: Res<Difficulty>,
+    checkpoints: Res<Checkpoints>,
+    level: Res<ActiveLevel>,
+    mut rng: ResMut<GameRng>,
+    mut run_seed: ResMut<RunSeed>,
+    stats: Res<RunStats>,
+    mut visible_query: Query<(&mut Visibility, &mut Text), With<GameOverText>>,
     mut game_state_query: Query<&mut GameState>,
     mut event_writer: EventWriter<GameEvent>,
+    mut next_state: ResMut<NextState<AppState>>,
 ) {
+    let checkpoint_wave = checkpoints
+        .latest()
+        .filter(|_| checkpoints.can_restart(&difficulty))
+        .map(|checkpoint| checkpoint.wave);
+
     for mut game_state in game_state_query.iter_mut() {
         if game_state.end_screen_active {
-            for mut visibility in visible_query.iter_mut() {
+            for (mut visibility, mut text) in visible_query.iter_mut() {
                 *visibility = Visibility::Visible; // Dereference and assign the value
+                let retry = match checkpoint_wave {
+                    Some(wave) => format!("\nPress C to retry from wave {}", wave),
+                    None => String::new(),
+                };
+                let title = if game_state.victory {
+                    "Victory"
+                } else {
+                    "Game Over"
+                };
+                let rank = match stats.rank {
+                    Some(rank) => format!("\n#{} on the leaderboard", rank),
+                    None => String::new(),
+                };
+                text.sections[0].value = format!(
+                    "{}\n{}{}\nPress SPACE to restart{}\nPress L for level select",
+                    title,
+                    stats.summary(),
+                    rank,
+                    retry
+                );
             }
 
-            if keys.just_pressed(KeyCode::Space) {
-                game_state.end_screen_active = false;
-                *visible_query.single_mut() = Visibility::Hidden;
-                event_writer.send(GameEvent::StartGame);
+            let event = if keys.just_pressed(KeyCode::Space) {
+                rng::start_run(&mut rng, &mut run_seed, level.seed);
+                Some(GameEvent::StartGame)
+            } else if keys.just_pressed(KeyCode::KeyC) && checkpoint_wave.is_some() {
+                Some(GameEvent::RestartFromCheckpoint)
+            } else if keys.just_pressed(KeyCode::KeyL) {
+                None
+            } else {
+                continue;
+            };
+
+            game_state.end_screen_active = false;
+            *visible_query.single_mut().0 = Visibility::Hidden;
+            match event {
+                Some(event) => {
+                    event_writer.send(event);
+                }
+                // Leaving the level cleans up the run, the level select takes it from there
+                None => next_state.set(AppState::LevelSelect),
             }
         }
     }
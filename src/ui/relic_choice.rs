@@ -0,0 +1,337 @@
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy::sprite::Anchor;
+use bevy::text::Text2dBounds;
+
+use crate::game_view::GameAction;
+use crate::gamestate::GameState;
+use crate::player::perks::{Perk, PerkChoices, Perks};
+use crate::player::relics::{Relic, RelicChoices};
+use crate::render_scale::{UiView, UI_LAYER};
+
+// Z, X and C pick the first, second and third card on offer. C also retries from a checkpoint
+// on the game over screen, so the choice waits while that is up.
+const CHOICE_KEYS: [(KeyCode, &str); 3] = [
+    (KeyCode::KeyZ, "Z"),
+    (KeyCode::KeyX, "X"),
+    (KeyCode::KeyC, "C"),
+];
+
+const CARD_WIDTH: f32 = 300.0;
+const CARD_HEIGHT: f32 = 420.0;
+const CARD_GAP: f32 = 40.0;
+const CARD_BORDER: f32 = 4.0;
+const CARD_PADDING: f32 = 24.0;
+const TITLE_OFFSET_Y: f32 = CARD_HEIGHT * 0.5 + 70.0;
+
+const BACKDROP_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.7);
+const CARD_COLOR: Color = Color::rgb(0.12, 0.1, 0.14);
+const TITLE_COLOR: Color = Color::rgb(1.0, 0.8, 0.2);
+const DESCRIPTION_COLOR: Color = Color::rgb(0.8, 0.8, 0.8);
+const KEY_COLOR: Color = Color::WHITE;
+
+// Relics are picked first, a level-up waits its turn behind them
+#[derive(Clone, Debug, PartialEq)]
+enum Offer {
+    Relics(Vec<Relic>),
+    // Along with the rank each perk is at right now
+    Perks(Vec<(Perk, u32)>),
+}
+
+impl Offer {
+    fn current(
+        relic_choices: &RelicChoices,
+        perk_choices: &PerkChoices,
+        perks: &Perks,
+    ) -> Option<Self> {
+        if let Some(relics) = relic_choices.current() {
+            return Some(Offer::Relics(relics.clone()));
+        }
+        perk_choices.current().map(|offer| {
+            Offer::Perks(
+                offer
+                    .iter()
+                    .map(|perk| (*perk, perks.rank(*perk)))
+                    .collect(),
+            )
+        })
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Offer::Relics(relics) => relics.len(),
+            Offer::Perks(perks) => perks.len(),
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Offer::Relics(_) => "Pick a relic",
+            Offer::Perks(_) => "Level up! Pick a perk",
+        }
+    }
+
+    fn cards(&self) -> Vec<CardContent> {
+        match self {
+            Offer::Relics(relics) => relics
+                .iter()
+                .map(|relic| {
+                    let definition = relic.definition();
+                    CardContent {
+                        name: definition.name.to_owned(),
+                        description: definition.description,
+                        icon: definition.icon,
+                        color: definition.color,
+                    }
+                })
+                .collect(),
+            Offer::Perks(perks) => perks
+                .iter()
+                .map(|(perk, rank)| {
+                    let definition = perk.definition();
+                    CardContent {
+                        name: format!("{} {}/{}", definition.name, rank + 1, definition.max_rank),
+                        description: definition.description,
+                        icon: definition.icon,
+                        color: definition.color,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+struct CardContent {
+    name: String,
+    description: &'static str,
+    icon: &'static str,
+    color: Color,
+}
+
+// The screen the cards are laid out on, rebuilt whenever a different offer comes up
+#[derive(Component, Default)]
+pub struct RelicChoiceScreen {
+    shown: Option<Offer>,
+}
+
+#[derive(Component)]
+pub struct RelicChoiceTitle;
+
+#[derive(Component)]
+pub struct RelicCard;
+
+#[derive(Component)]
+pub struct RelicChoiceBackdrop;
+
+pub fn setup_relic_choice(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            SpatialBundle {
+                visibility: Visibility::Hidden,
+                // Drawn on top of the rest of the ui
+                transform: Transform::from_xyz(0.0, 0.0, 10.0),
+                ..default()
+            },
+            RelicChoiceScreen::default(),
+            RenderLayers::layer(UI_LAYER),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: BACKDROP_COLOR,
+                        ..default()
+                    },
+                    ..default()
+                },
+                RelicChoiceBackdrop,
+                RenderLayers::layer(UI_LAYER),
+            ));
+            parent.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        "Pick a relic",
+                        TextStyle {
+                            font: asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+                            font_size: 60.0,
+                            color: TITLE_COLOR,
+                        },
+                    )
+                    .with_justify(JustifyText::Center),
+                    transform: Transform::from_xyz(0.0, TITLE_OFFSET_Y, 1.0),
+                    ..default()
+                },
+                RelicChoiceTitle,
+                RenderLayers::layer(UI_LAYER),
+            ));
+        });
+}
+
+fn spawn_card(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    definition: &CardContent,
+    key: &str,
+    position: Vec3,
+) {
+    let text = |value: &str, font_size: f32, color: Color| {
+        Text::from_section(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color,
+            },
+        )
+        .with_justify(JustifyText::Center)
+    };
+
+    parent
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: definition.color,
+                    custom_size: Some(Vec2::new(CARD_WIDTH, CARD_HEIGHT)),
+                    ..default()
+                },
+                transform: Transform::from_translation(position),
+                ..default()
+            },
+            RelicCard,
+            RenderLayers::layer(UI_LAYER),
+        ))
+        .with_children(|card| {
+            card.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: CARD_COLOR,
+                        custom_size: Some(Vec2::new(
+                            CARD_WIDTH - CARD_BORDER * 2.0,
+                            CARD_HEIGHT - CARD_BORDER * 2.0,
+                        )),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                    ..default()
+                },
+                RenderLayers::layer(UI_LAYER),
+            ));
+            card.spawn((
+                Text2dBundle {
+                    text: text(definition.icon, 120.0, definition.color),
+                    transform: Transform::from_xyz(0.0, CARD_HEIGHT * 0.2, 0.2),
+                    ..default()
+                },
+                RenderLayers::layer(UI_LAYER),
+            ));
+            card.spawn((
+                Text2dBundle {
+                    text: text(&definition.name, 32.0, definition.color),
+                    transform: Transform::from_xyz(0.0, -CARD_HEIGHT * 0.02, 0.2),
+                    ..default()
+                },
+                RenderLayers::layer(UI_LAYER),
+            ));
+            card.spawn((
+                Text2dBundle {
+                    text: text(definition.description, 20.0, DESCRIPTION_COLOR),
+                    text_anchor: Anchor::TopCenter,
+                    text_2d_bounds: Text2dBounds {
+                        size: Vec2::new(CARD_WIDTH - CARD_PADDING * 2.0, CARD_HEIGHT * 0.3),
+                    },
+                    transform: Transform::from_xyz(0.0, -CARD_HEIGHT * 0.1, 0.2),
+                    ..default()
+                },
+                RenderLayers::layer(UI_LAYER),
+            ));
+            card.spawn((
+                Text2dBundle {
+                    text: text(&format!("[{}]", key), 32.0, KEY_COLOR),
+                    transform: Transform::from_xyz(
+                        0.0,
+                        -CARD_HEIGHT * 0.5 + CARD_PADDING * 1.5,
+                        0.2,
+                    ),
+                    ..default()
+                },
+                RenderLayers::layer(UI_LAYER),
+            ));
+        });
+}
+
+pub fn relic_choice_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    choices: Res<RelicChoices>,
+    perk_choices: Res<PerkChoices>,
+    perks: Res<Perks>,
+    game_state_query: Query<&GameState>,
+    mut actions: EventWriter<GameAction>,
+) {
+    let game_over = game_state_query.iter().any(|state| state.game_over);
+    let Some(offer) = Offer::current(&choices, &perk_choices, &perks).filter(|_| !game_over) else {
+        return;
+    };
+
+    for (index, (key, _)) in CHOICE_KEYS.iter().enumerate().take(offer.len()) {
+        if keys.just_pressed(*key) {
+            actions.send(match offer {
+                Offer::Relics(_) => GameAction::ChooseRelic(index),
+                Offer::Perks(_) => GameAction::ChoosePerk(index),
+            });
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_relic_choice_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    choices: Res<RelicChoices>,
+    perk_choices: Res<PerkChoices>,
+    perks: Res<Perks>,
+    ui_view: Res<UiView>,
+    game_state_query: Query<&GameState>,
+    mut screen_query: Query<(Entity, &mut RelicChoiceScreen, &mut Visibility)>,
+    mut backdrop_query: Query<&mut Sprite, With<RelicChoiceBackdrop>>,
+    mut title_query: Query<&mut Text, With<RelicChoiceTitle>>,
+    card_query: Query<Entity, With<RelicCard>>,
+) {
+    let game_over = game_state_query.iter().any(|state| state.game_over);
+    let offer = Offer::current(&choices, &perk_choices, &perks).filter(|_| !game_over);
+
+    for mut sprite in backdrop_query.iter_mut() {
+        sprite.custom_size = Some(ui_view.size());
+    }
+
+    for (entity, mut screen, mut visibility) in screen_query.iter_mut() {
+        *visibility = if offer.is_some() {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+        if screen.shown == offer {
+            continue;
+        }
+
+        for card in card_query.iter() {
+            commands.entity(card).despawn_recursive();
+        }
+        screen.shown = offer.clone();
+        let Some(offer) = &offer else {
+            continue;
+        };
+        for mut title in title_query.iter_mut() {
+            title.sections[0].value = offer.title().to_owned();
+        }
+
+        let font = asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf");
+        let row_width = offer.len() as f32 * (CARD_WIDTH + CARD_GAP) - CARD_GAP;
+        commands.entity(entity).with_children(|parent| {
+            for (index, (card, (_, key))) in offer.cards().iter().zip(CHOICE_KEYS).enumerate() {
+                let x =
+                    -row_width * 0.5 + CARD_WIDTH * 0.5 + index as f32 * (CARD_WIDTH + CARD_GAP);
+                spawn_card(parent, &font, card, key, Vec3::new(x, 0.0, 1.0));
+            }
+        });
+    }
+}
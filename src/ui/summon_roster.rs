@@ -0,0 +1,140 @@
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy::sprite::Anchor;
+
+use crate::difficulty::Difficulty;
+use crate::events::UnitUnlocked;
+use crate::meta::progress::{MetaProgress, Upgrade};
+use crate::player::plugin::Player;
+use crate::player::summoning::SUMMON_BINDS;
+use crate::render_scale::{UiView, UI_LAYER};
+use crate::settings::bindings::key_name;
+use crate::settings::config::GameSettings;
+use crate::units::stat_modifiers::StatModifiers;
+use crate::units::unit_types::{UnitResource, UnitType};
+
+use super::kill_feed::KillFeed;
+
+const ROSTER_MARGIN: f32 = 24.0;
+const UNLOCKED_COLOR: Color = Color::rgb(0.85, 0.8, 1.0);
+const LOCKED_COLOR: Color = Color::rgb(0.45, 0.45, 0.5);
+
+// Along the bottom of the screen, what each summon key calls on and what the locked ones still
+// need before they can be
+#[derive(Component)]
+pub struct SummonRosterText;
+
+pub fn setup_summon_roster(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf");
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_sections(SUMMON_BINDS.iter().map(|_| {
+                TextSection::from_style(TextStyle {
+                    font: font.clone(),
+                    font_size: 24.0,
+                    color: UNLOCKED_COLOR,
+                })
+            }))
+            .with_justify(JustifyText::Center),
+            text_anchor: Anchor::BottomCenter,
+            ..default()
+        },
+        SummonRosterText,
+        RenderLayers::layer(UI_LAYER),
+    ));
+}
+
+fn roster_entry(
+    index: usize,
+    key: &str,
+    unit_type: UnitType,
+    unit_configs: &UnitResource,
+    progress: &MetaProgress,
+    difficulty: &Difficulty,
+    summoner: Option<&StatModifiers>,
+) -> (String, bool) {
+    let separator = if index == 0 { "" } else { "   " };
+    let name = unit_type.name();
+    if let Some(upgrade) =
+        Upgrade::required_for(unit_type).filter(|_| !progress.is_unit_unlocked(unit_type))
+    {
+        return (
+            format!(
+                "{}{} {} (needs {})",
+                separator,
+                key,
+                name,
+                upgrade.definition().name
+            ),
+            false,
+        );
+    }
+
+    let config = unit_configs.get(unit_type);
+    match config.unlock.filter(|_| !config.unlocked) {
+        Some(condition) => (
+            format!("{}{} {} ({})", separator, key, name, condition.describe()),
+            false,
+        ),
+        None => (
+            format!(
+                "{}{} {} {}",
+                separator,
+                key,
+                name,
+                summoner.map_or(unit_configs.cost(unit_type, difficulty), |summoner| {
+                    unit_configs.summon_cost(unit_type, difficulty, summoner)
+                })
+            ),
+            true,
+        ),
+    }
+}
+
+pub fn update_summon_roster(
+    unit_configs: Res<UnitResource>,
+    progress: Res<MetaProgress>,
+    difficulty: Res<Difficulty>,
+    settings: Res<GameSettings>,
+    ui_view: Res<UiView>,
+    player_query: Query<&StatModifiers, With<Player>>,
+    mut query: Query<(&mut Text, &mut Transform), With<SummonRosterText>>,
+) {
+    let summoner = player_query.get_single().ok();
+    for (mut text, mut transform) in query.iter_mut() {
+        transform.translation = Vec3::new(0.0, -ui_view.half_size.y + ROSTER_MARGIN, 0.0);
+
+        for (index, (binding, unit_type)) in SUMMON_BINDS.iter().enumerate() {
+            let (value, unlocked) = roster_entry(
+                index,
+                &key_name(settings.bindings.key(*binding)),
+                *unit_type,
+                &unit_configs,
+                &progress,
+                &difficulty,
+                summoner,
+            );
+            let color = if unlocked {
+                UNLOCKED_COLOR
+            } else {
+                LOCKED_COLOR
+            };
+            // Only touching the text when it changed, so it isn't laid out again every frame
+            let section = &text.sections[index];
+            if section.value != value || section.style.color != color {
+                let section = &mut text.sections[index];
+                section.value = value;
+                section.style.color = color;
+            }
+        }
+    }
+}
+
+pub fn announce_unlocks(
+    mut unlocked_reader: EventReader<UnitUnlocked>,
+    mut kill_feed: ResMut<KillFeed>,
+) {
+    for unlocked in unlocked_reader.read() {
+        kill_feed.push(format!("{}s can now be summoned", unlocked.0.name()));
+    }
+}
@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+
+use crate::events::{GameEvent, WaveGraded};
+use crate::render_scale::{UiView, UI_LAYER};
+
+const GRADE_SECONDS: f32 = 3.0;
+const FADE_SECONDS: f32 = 0.8;
+// Above the boss bar and the dialogue, as a fraction of half the window height
+const GRADE_OFFSET_TOP: f32 = 0.6;
+// Pops in big and settles down to its normal size
+const POP_SCALE: f32 = 1.6;
+const POP_SECONDS: f32 = 0.25;
+
+#[derive(Component)]
+pub struct WaveGradeText {
+    timer: Timer,
+    color: Color,
+}
+
+pub fn setup_wave_grade(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let mut timer = Timer::from_seconds(GRADE_SECONDS, TimerMode::Once);
+    timer.tick(timer.duration());
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+                    font_size: 48.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_justify(JustifyText::Center),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        WaveGradeText {
+            timer,
+            color: Color::WHITE,
+        },
+        RenderLayers::layer(UI_LAYER),
+    ));
+}
+
+pub fn show_wave_grade(
+    mut graded_reader: EventReader<WaveGraded>,
+    mut query: Query<(&mut Text, &mut WaveGradeText)>,
+) {
+    for graded in graded_reader.read() {
+        let souls = match graded.souls {
+            0 => String::new(),
+            1 => "  +1 soul".to_owned(),
+            souls => format!("  +{} souls", souls),
+        };
+        for (mut text, mut wave_grade) in query.iter_mut() {
+            text.sections[0].value =
+                format!("Wave {}: {}{}", graded.wave, graded.grade.name(), souls);
+            wave_grade.color = graded.grade.color();
+            wave_grade.timer.reset();
+        }
+    }
+}
+
+pub fn update_wave_grade(
+    time: Res<Time>,
+    ui_view: Res<UiView>,
+    mut query: Query<(
+        &mut Text,
+        &mut WaveGradeText,
+        &mut Visibility,
+        &mut Transform,
+    )>,
+) {
+    for (mut text, mut wave_grade, mut visibility, mut transform) in query.iter_mut() {
+        if wave_grade.timer.tick(time.delta()).finished() {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+
+        let alpha = (wave_grade.timer.remaining_secs() / FADE_SECONDS).min(1.0);
+        let pop = (wave_grade.timer.elapsed_secs() / POP_SECONDS).min(1.0);
+        text.sections[0].style.color = wave_grade.color.with_a(alpha);
+        transform.translation = Vec3::new(0.0, ui_view.half_size.y * GRADE_OFFSET_TOP, 0.0);
+        transform.scale = Vec3::splat(POP_SCALE + (1.0 - POP_SCALE) * pop);
+        *visibility = Visibility::Visible;
+    }
+}
+
+pub fn clear_wave_grade_system(
+    mut event_reader: EventReader<GameEvent>,
+    mut query: Query<&mut WaveGradeText>,
+) {
+    for event in event_reader.read() {
+        if let GameEvent::StartGame = event {
+            for mut wave_grade in query.iter_mut() {
+                let duration = wave_grade.timer.duration();
+                wave_grade.timer.tick(duration);
+            }
+        }
+    }
+}
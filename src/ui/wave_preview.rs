@@ -0,0 +1,381 @@
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use bevy::sprite::Anchor;
+
+use crate::animation::AtlasLayouts;
+use crate::difficulty::Difficulty;
+use crate::enemies::enemy_spawner::{EnemySpawner, WaveComposition};
+use crate::enemies::mutators::NextWaveMutator;
+use crate::game_mode::GameMode;
+use crate::game_view::GameAction;
+use crate::levels::definition::ActiveLevel;
+use crate::player::plugin::Player;
+use crate::render_scale::{UiView, UI_LAYER};
+use crate::rng::RunSeed;
+use crate::settings::bindings::{key_name, Binding};
+use crate::settings::config::GameSettings;
+use crate::units::unit_types::UnitType;
+
+// The panel comes up this long before the wave, and stays until it's out
+const PREVIEW_SECONDS: f32 = 8.0;
+
+const PANEL_WIDTH: f32 = 260.0;
+const PANEL_MARGIN: f32 = 24.0;
+// Below the hud in the same corner
+const PANEL_OFFSET_TOP: f32 = 120.0;
+const PANEL_PADDING: f32 = 12.0;
+const TITLE_HEIGHT: f32 = 32.0;
+const ROW_HEIGHT: f32 = 36.0;
+const ICON_SIZE: f32 = 32.0;
+const BUTTON_HEIGHT: f32 = 32.0;
+
+const PANEL_COLOR: Color = Color::rgba(0.1, 0.1, 0.1, 0.8);
+const BUTTON_COLOR: Color = Color::rgb(0.35, 0.15, 0.45);
+const BUTTON_HOVER_COLOR: Color = Color::rgb(0.5, 0.25, 0.65);
+const TEXT_COLOR: Color = Color::WHITE;
+const NOTE_COLOR: Color = Color::rgb(1.0, 0.7, 0.2);
+
+// What the next wave is made of, the panel is only rebuilt when this changes
+#[derive(Clone, Debug, PartialEq)]
+struct Preview {
+    wave: u32,
+    units: Vec<(UnitType, u32)>,
+    notes: Vec<String>,
+}
+
+impl Preview {
+    fn new(wave: u32, composition: &WaveComposition, notes: Vec<String>) -> Self {
+        let units = [
+            (UnitType::Knight, composition.knights),
+            (UnitType::Gargoyle, composition.gargoyles),
+            (UnitType::ArmoredKnight, composition.armored_knights),
+            (UnitType::Assassin, composition.assassins),
+        ]
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .collect();
+        Self { wave, units, notes }
+    }
+
+    fn height(&self) -> f32 {
+        TITLE_HEIGHT
+            + ROW_HEIGHT * (self.units.len() + self.notes.len()) as f32
+            + BUTTON_HEIGHT
+            + PANEL_PADDING * 3.0
+    }
+}
+
+#[derive(Component, Default)]
+pub struct WavePreviewPanel {
+    shown: Option<Preview>,
+}
+
+// Everything that gets thrown away and laid out again for a different wave
+#[derive(Component)]
+pub struct WavePreviewContent;
+
+#[derive(Component)]
+pub struct WavePreviewCountdown;
+
+#[derive(Component)]
+pub struct StartEarlyButton {
+    size: Vec2,
+}
+
+#[derive(Component)]
+pub struct StartEarlyText;
+
+fn preview_text(
+    font: &Handle<Font>,
+    value: String,
+    color: Color,
+    anchor: Anchor,
+    translation: Vec3,
+) -> Text2dBundle {
+    Text2dBundle {
+        text: Text::from_section(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size: 22.0,
+                color,
+            },
+        ),
+        text_anchor: anchor,
+        transform: Transform::from_translation(translation),
+        ..default()
+    }
+}
+
+pub fn setup_wave_preview(mut commands: Commands) {
+    commands.spawn((
+        SpatialBundle {
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        WavePreviewPanel::default(),
+        RenderLayers::layer(UI_LAYER),
+    ));
+}
+
+fn lay_out_wave_preview(
+    commands: &mut Commands,
+    panel: Entity,
+    preview: &Preview,
+    font: &Handle<Font>,
+    asset_server: &AssetServer,
+    texture_atlas_layouts: &mut AtlasLayouts,
+) {
+    let left = -PANEL_WIDTH;
+    let height = preview.height();
+    commands.entity(panel).with_children(|parent| {
+        parent.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: PANEL_COLOR,
+                    custom_size: Some(Vec2::new(PANEL_WIDTH, height)),
+                    anchor: Anchor::TopRight,
+                    ..default()
+                },
+                ..default()
+            },
+            WavePreviewContent,
+            RenderLayers::layer(UI_LAYER),
+        ));
+        parent.spawn((
+            preview_text(
+                font,
+                String::new(),
+                TEXT_COLOR,
+                Anchor::TopLeft,
+                Vec3::new(left + PANEL_PADDING, -PANEL_PADDING, 0.1),
+            ),
+            WavePreviewContent,
+            WavePreviewCountdown,
+            RenderLayers::layer(UI_LAYER),
+        ));
+
+        let mut y = -PANEL_PADDING * 2.0 - TITLE_HEIGHT - ROW_HEIGHT * 0.5;
+        for (unit_type, count) in preview.units.iter() {
+            // The first frame of the unit's idle animation stands in for an icon
+            if let Some(params) = unit_type.children_spawn_params().into_iter().next() {
+                let params = texture_atlas_layouts.import(params);
+                let layout = texture_atlas_layouts.get_or_add(&params);
+                parent.spawn((
+                    SpriteSheetBundle {
+                        texture: asset_server.load(params.texture_path),
+                        atlas: TextureAtlas {
+                            layout,
+                            index: params.first_atlas_index,
+                        },
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::splat(ICON_SIZE)),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(
+                            left + PANEL_PADDING + ICON_SIZE * 0.5,
+                            y,
+                            0.1,
+                        ),
+                        ..default()
+                    },
+                    WavePreviewContent,
+                    RenderLayers::layer(UI_LAYER),
+                ));
+            }
+            parent.spawn((
+                preview_text(
+                    font,
+                    format!("{} x{}", unit_type.name(), count),
+                    TEXT_COLOR,
+                    Anchor::CenterLeft,
+                    Vec3::new(left + PANEL_PADDING * 2.0 + ICON_SIZE, y, 0.1),
+                ),
+                WavePreviewContent,
+                RenderLayers::layer(UI_LAYER),
+            ));
+            y -= ROW_HEIGHT;
+        }
+        for note in preview.notes.iter() {
+            parent.spawn((
+                preview_text(
+                    font,
+                    note.clone(),
+                    NOTE_COLOR,
+                    Anchor::CenterLeft,
+                    Vec3::new(left + PANEL_PADDING, y, 0.1),
+                ),
+                WavePreviewContent,
+                RenderLayers::layer(UI_LAYER),
+            ));
+            y -= ROW_HEIGHT;
+        }
+
+        let button_size = Vec2::new(PANEL_WIDTH - PANEL_PADDING * 2.0, BUTTON_HEIGHT);
+        let button_y = -height + PANEL_PADDING + BUTTON_HEIGHT * 0.5;
+        parent.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: BUTTON_COLOR,
+                    custom_size: Some(button_size),
+                    ..default()
+                },
+                transform: Transform::from_xyz(-PANEL_WIDTH * 0.5, button_y, 0.1),
+                ..default()
+            },
+            StartEarlyButton { size: button_size },
+            WavePreviewContent,
+            RenderLayers::layer(UI_LAYER),
+        ));
+        parent.spawn((
+            preview_text(
+                font,
+                String::new(),
+                TEXT_COLOR,
+                Anchor::Center,
+                Vec3::new(-PANEL_WIDTH * 0.5, button_y, 0.2),
+            ),
+            StartEarlyText,
+            WavePreviewContent,
+            RenderLayers::layer(UI_LAYER),
+        ));
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_wave_preview(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: AtlasLayouts,
+    (level, mode): (Res<ActiveLevel>, Res<GameMode>),
+    settings: Res<GameSettings>,
+    difficulty: Res<Difficulty>,
+    run_seed: Res<RunSeed>,
+    next_mutator: Res<NextWaveMutator>,
+    ui_view: Res<UiView>,
+    player_query: Query<(), With<Player>>,
+    spawner_query: Query<&EnemySpawner>,
+    mut panel_query: Query<(
+        Entity,
+        &mut WavePreviewPanel,
+        &mut Visibility,
+        &mut Transform,
+    )>,
+    content_query: Query<Entity, With<WavePreviewContent>>,
+    mut countdown_query: Query<&mut Text, (With<WavePreviewCountdown>, Without<StartEarlyText>)>,
+    mut button_query: Query<(&StartEarlyButton, &mut Sprite, &GlobalTransform)>,
+    mut button_text_query: Query<&mut Text, (With<StartEarlyText>, Without<WavePreviewCountdown>)>,
+) {
+    let window_bounds = ui_view.half_size;
+    let spawner = spawner_query
+        .get_single()
+        .ok()
+        .filter(|_| !player_query.is_empty())
+        .filter(|spawner| spawner.has_next_wave(&level, *mode))
+        .filter(|spawner| !spawner.wave_charge.is_paused())
+        .filter(|spawner| spawner.seconds_to_next_wave() <= PREVIEW_SECONDS);
+
+    for (panel_entity, mut panel, mut visibility, mut transform) in panel_query.iter_mut() {
+        transform.translation = Vec3::new(
+            window_bounds.x - PANEL_MARGIN,
+            window_bounds.y - PANEL_OFFSET_TOP,
+            0.0,
+        );
+
+        let Some(spawner) = spawner else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+
+        let composition = spawner.next_composition(&level, &difficulty, &run_seed, next_mutator.0);
+        let wave = spawner.wave + 1;
+        let mut notes = Vec::new();
+        if let Some(mutator) = next_mutator.0 {
+            notes.push(format!("Mutated: {}", mutator.name()));
+        } else if level.waves.endless_depth(wave) > 0 {
+            notes.push("Mutated: ???".to_owned());
+        }
+        if level.waves.is_boss_wave(wave) {
+            notes.push("A boss joins in".to_owned());
+        }
+        let preview = Preview::new(wave, &composition, notes);
+
+        if panel.shown.as_ref() != Some(&preview) {
+            for entity in content_query.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+            let font = asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf");
+            lay_out_wave_preview(
+                &mut commands,
+                panel_entity,
+                &preview,
+                &font,
+                &asset_server,
+                &mut texture_atlas_layouts,
+            );
+            panel.shown = Some(preview);
+        }
+
+        let seconds = spawner.seconds_to_next_wave().ceil();
+        for mut text in countdown_query.iter_mut() {
+            let value = format!("Wave {} in {:.0}s", wave, seconds);
+            if text.sections[0].value != value {
+                text.sections[0].value = value;
+            }
+        }
+        for mut text in button_text_query.iter_mut() {
+            let value = format!(
+                "Start early ({}) +{} MP",
+                key_name(settings.bindings.key(Binding::StartWaveEarly)),
+                spawner.early_start_bonus()
+            );
+            if text.sections[0].value != value {
+                text.sections[0].value = value;
+            }
+        }
+
+        for (button, mut sprite, transform) in button_query.iter_mut() {
+            let color = if is_hovered(&ui_view, button, transform) {
+                BUTTON_HOVER_COLOR
+            } else {
+                BUTTON_COLOR
+            };
+            if sprite.color != color {
+                sprite.color = color;
+            }
+        }
+    }
+}
+
+fn is_hovered(ui_view: &UiView, button: &StartEarlyButton, transform: &GlobalTransform) -> bool {
+    ui_view.cursor.is_some_and(|cursor| {
+        let offset = cursor - transform.translation().truncate();
+        offset.abs().cmple(button.size * 0.5).all()
+    })
+}
+
+// The start early key or a click on the button, only while the panel is up
+pub fn start_early_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    settings: Res<GameSettings>,
+    ui_view: Res<UiView>,
+    button_query: Query<(&StartEarlyButton, &GlobalTransform, &InheritedVisibility)>,
+    mut actions: EventWriter<GameAction>,
+) {
+    for (button, transform, visibility) in button_query.iter() {
+        if !visibility.get() {
+            continue;
+        }
+        let clicked =
+            mouse.just_pressed(MouseButton::Left) && is_hovered(&ui_view, button, transform);
+        if clicked
+            || settings
+                .bindings
+                .just_pressed(&keys, Binding::StartWaveEarly)
+        {
+            actions.send(GameAction::StartWaveEarly);
+        }
+    }
+}
@@ -1,24 +1,139 @@
 use bevy::prelude::*;
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
 
+use crate::levels::definition::ActiveLevel;
 use crate::mana::Mana;
 use crate::player::plugin::Player;
+use crate::player::summoning::alive_summons;
 use crate::units::health::Health;
+use crate::units::team::CurrentTeam;
+use crate::vfx::flash::{Flash, FlashLimiter, VfxSettings};
 
-use super::unit_types::Acolyte;
+use super::unit_types::{Acolyte, CurrentUnitType, UnitResource, UnitType};
+
+const AURA_RADIUS: f32 = 36.0;
+const AURA_COLOR: Color = Color::rgb(0.45, 0.35, 1.0);
+const AURA_ALPHA: f32 = 0.4;
+// The ring starts this small on the mana tick and grows to full size as it fades
+const AURA_MIN_SCALE: f32 = 0.3;
+
+#[derive(Component)]
+pub struct AcolyteAura {
+    pub material: Handle<ColorMaterial>,
+    pub alpha: f32,
+}
 
 pub fn acolyte_mana_giver(
     time: Res<Time>,
+    unit_configs: Res<UnitResource>,
+    level: Res<ActiveLevel>,
     mut query: Query<(&mut Acolyte, &Health)>,
+    summon_query: Query<(&CurrentTeam, &Health), With<CurrentUnitType>>,
     mut player_query: Query<&mut Mana, With<Player>>,
 ) {
+    let Some(channel) = unit_configs.get(UnitType::Acolyte).mana else {
+        return;
+    };
+
+    let count = query.iter().filter(|(_, health)| !health.is_dead()).count();
+    let efficiency = channel.efficiency(count);
+    // The army's upkeep is split between the acolytes, each tick pays its share of it
+    let upkeep = level.summons.upkeep(alive_summons(summon_query.iter())) * channel.interval
+        / count.max(1) as f32;
+
     for (mut acolyte, health) in query.iter_mut() {
         if health.is_dead() {
             continue;
         }
 
-        if acolyte.give_mana_timer.tick(time.delta()).just_finished() {
-            let mut mana = player_query.single_mut();
-            mana.current_mana = (mana.current_mana + acolyte.mana_amount).min(mana.max_mana);
+        // Picks up changes to the config without having to respawn the acolytes
+        if acolyte.give_mana.interval() != channel.interval {
+            acolyte.give_mana.set_interval(channel.interval);
+        }
+        acolyte.efficiency = efficiency;
+
+        let charged = acolyte.give_mana.tick(time.delta()).times_charged();
+        if charged > 0 {
+            acolyte.mana_remainder +=
+                (channel.amount as f32 * efficiency - upkeep).max(0.0) * charged as f32;
+            let amount = acolyte.mana_remainder.floor();
+            acolyte.mana_remainder -= amount;
+
+            // Nobody to give it to without a summoner around, the same as in the tests
+            let Ok(mut mana) = player_query.get_single_mut() else {
+                continue;
+            };
+            mana.current_mana = mana
+                .current_mana
+                .saturating_add(amount as u8)
+                .min(mana.max_mana);
+        }
+    }
+}
+
+pub fn spawn_acolyte_auras(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut flash_limiter: ResMut<FlashLimiter>,
+    vfx_settings: Res<VfxSettings>,
+    query: Query<(Entity, &Transform), Added<Acolyte>>,
+) {
+    for (entity, transform) in query.iter() {
+        let radius = AURA_RADIUS * transform.scale.x;
+        let flash = flash_limiter.limit(
+            &vfx_settings,
+            Flash {
+                alpha: AURA_ALPHA,
+                duration: 0.0,
+            },
+            std::f32::consts::PI * radius * radius,
+        );
+
+        let material = materials.add(AURA_COLOR.with_a(0.0));
+        let aura = commands
+            .spawn((
+                MaterialMesh2dBundle {
+                    mesh: Mesh2dHandle(meshes.add(Circle::new(AURA_RADIUS))),
+                    material: material.clone(),
+                    transform: Transform::from_xyz(0.0, 0.0, -0.5),
+                    ..default()
+                },
+                AcolyteAura {
+                    material,
+                    alpha: flash.alpha,
+                },
+            ))
+            .id();
+        commands.entity(entity).add_child(aura);
+    }
+}
+
+// Each mana tick sends out a ring that grows and fades until the next one, the fainter the ring
+// the less the acolyte is giving because of how many others there are
+pub fn pulse_acolyte_auras(
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    acolyte_query: Query<(&Acolyte, &Health, &Children)>,
+    mut aura_query: Query<(&AcolyteAura, &mut Transform)>,
+) {
+    for (acolyte, health, children) in acolyte_query.iter() {
+        let progress = acolyte.give_mana.fraction();
+        let alpha = if health.is_dead() {
+            0.0
+        } else {
+            (1.0 - progress) * acolyte.efficiency
+        };
+
+        for &child in children.iter() {
+            let Ok((aura, mut transform)) = aura_query.get_mut(child) else {
+                continue;
+            };
+
+            let scale = AURA_MIN_SCALE + (1.0 - AURA_MIN_SCALE) * progress;
+            transform.scale = Vec3::new(scale, scale, 1.0);
+            if let Some(material) = materials.get_mut(&aura.material) {
+                material.color.set_a(aura.alpha * alpha);
+            }
         }
     }
 }
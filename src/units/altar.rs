@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+
+use crate::ai::behavior::{Behavior, CurrentBehavior};
+use crate::events::Damage;
+use crate::gamestate::Cleanup;
+
+use super::damage::DamageKind;
+use super::health::Health;
+
+const ALTAR_HEALTH: i32 = 400;
+const ALTAR_RADIUS: f32 = 36.0;
+// Enemies this close to the altar stop walking and start hacking at it
+pub const ALTAR_SIEGE_DISTANCE: f32 = 72.0;
+const ALTAR_SIEGE_INTERVAL: f32 = 1.0;
+const ALTAR_SIEGE_DAMAGE: i32 = 5;
+
+// What the enemies are marching towards, the run is over if it falls
+#[derive(Component)]
+pub struct DarkAltar {
+    pub siege_timer: Timer,
+}
+
+pub fn spawn_altar(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    current_health: Option<i32>,
+) {
+    let mut health = Health::new(ALTAR_HEALTH);
+    if let Some(current_health) = current_health {
+        health.current = current_health.min(health.max);
+    }
+
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: Mesh2dHandle(meshes.add(Circle::new(ALTAR_RADIUS))),
+            material: materials.add(Color::rgb(0.35, 0.1, 0.45)),
+            transform: Transform::from_xyz(0.0, 0.0, -1.0),
+            ..default()
+        },
+        DarkAltar {
+            siege_timer: Timer::from_seconds(ALTAR_SIEGE_INTERVAL, TimerMode::Repeating),
+        },
+        health,
+        Cleanup,
+    ));
+}
+
+pub fn siege_altar(
+    time: Res<Time>,
+    mut altar_query: Query<(Entity, &mut DarkAltar, &Transform, &Health)>,
+    attackers_query: Query<(Entity, &CurrentBehavior, &Transform, &Health)>,
+    mut damage_writer: EventWriter<Damage>,
+) {
+    for (altar, mut dark_altar, altar_transform, altar_health) in altar_query.iter_mut() {
+        if altar_health.is_dead() || !dark_altar.siege_timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let altar_position = altar_transform.translation.truncate();
+        for (attacker, behavior, transform, health) in attackers_query.iter() {
+            let Behavior::MoveOrigo(_) = behavior.0 else {
+                continue;
+            };
+
+            let distance = (transform.translation.truncate() - altar_position).length();
+            if health.is_dead() || distance > ALTAR_SIEGE_DISTANCE {
+                continue;
+            }
+
+            damage_writer.send(Damage {
+                target: altar,
+                amount: ALTAR_SIEGE_DAMAGE,
+                kind: DamageKind::Physical,
+                armor_piercing: false,
+                source: Some(attacker),
+                critical: false,
+            });
+        }
+    }
+}
+
+type ChangedAltarFilter = (With<DarkAltar>, Changed<Health>);
+
+// Darkens the altar as it takes damage
+pub fn update_altar_color(
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<(&Health, &Handle<ColorMaterial>), ChangedAltarFilter>,
+) {
+    for (health, material) in query.iter() {
+        if let Some(material) = materials.get_mut(material) {
+            let brightness = 0.3 + 0.7 * health.fraction();
+            material.color = Color::rgb(0.35 * brightness, 0.1 * brightness, 0.45 * brightness);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::prelude::*;
+
+    use super::{spawn_altar, DarkAltar, ALTAR_HEALTH};
+    use crate::test_utils::TestApp;
+    use crate::units::health::Health;
+    use crate::units::team::Team;
+    use crate::units::unit_types::UnitType;
+
+    #[test]
+    fn knight_at_the_altar_wears_it_down() {
+        let mut app = TestApp::new();
+        app.app.world.run_system_once(
+            |mut commands: Commands,
+             mut meshes: ResMut<Assets<Mesh>>,
+             mut materials: ResMut<Assets<ColorMaterial>>| {
+                spawn_altar(&mut commands, &mut meshes, &mut materials, None);
+            },
+        );
+        let knight = app.spawn_unit(UnitType::Knight, Team::Good, Vec2::new(40.0, 0.0));
+        app.tick_seconds(2.5);
+
+        app.assert_behavior(knight, "Marching");
+        let health = app
+            .app
+            .world
+            .query_filtered::<&Health, With<DarkAltar>>()
+            .single(&app.app.world);
+        assert!(health.current < ALTAR_HEALTH);
+    }
+}
@@ -0,0 +1,154 @@
+use bevy::prelude::*;
+
+use crate::ai::behavior::AttackBehavior;
+use crate::events::Damage;
+use crate::gamestate::Cleanup;
+use crate::pool::{self, EntityPools, Pooled};
+use crate::utils::timing::Cooldown;
+
+use super::damage::DamageKind;
+use super::unit_types::{CurrentUnitType, UnitResource};
+
+const BOLT_SPEED: f32 = 420.0;
+const BOLT_SIZE: f32 = 10.0;
+const BOLT_COLOR: Color = Color::rgb(0.6, 0.55, 0.5);
+// Close enough to count as a hit, so a bolt doesn't orbit a target it keeps overshooting
+const BOLT_HIT_DISTANCE: f32 = 16.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProjectileType {
+    // The hit lands straight away
+    #[default]
+    Melee,
+    // Flies over to the target and hits it on arrival, or misses if the target died on the way
+    Bolt,
+}
+
+// How a unit fights, each unit type gets its own from its UnitConfig
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct AttackStats {
+    pub damage: i32,
+    // Up to this much extra damage is rolled on top of every hit
+    pub damage_variance: i32,
+    pub damage_kind: DamageKind,
+    pub range: f32,
+    pub cooldown: f32,
+    // Up to this many seconds are added to every cooldown, so a group doesn't swing in lockstep
+    pub cooldown_variance: f32,
+    pub projectile: ProjectileType,
+    pub crit_chance: f32,
+    pub crit_multiplier: f32,
+}
+
+impl Default for AttackStats {
+    fn default() -> Self {
+        Self {
+            damage: 10,
+            damage_variance: 5,
+            damage_kind: DamageKind::Physical,
+            range: 96.0,
+            cooldown: 4.0,
+            cooldown_variance: 0.5,
+            projectile: ProjectileType::Melee,
+            crit_chance: 0.05,
+            crit_multiplier: 1.5,
+        }
+    }
+}
+
+// The chance of an attack missing this unit altogether
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Evasion {
+    pub dodge_chance: f32,
+}
+
+#[derive(Component)]
+pub struct Projectile {
+    pub target: Entity,
+    pub damage: Damage,
+}
+
+pub fn apply_combat_stats(
+    mut commands: Commands,
+    unit_configs: Res<UnitResource>,
+    mut query: Query<
+        (Entity, &CurrentUnitType, Option<&mut AttackBehavior>),
+        Added<CurrentUnitType>,
+    >,
+) {
+    for (entity, unit_type, attack_behavior) in query.iter_mut() {
+        let config = unit_configs.get(unit_type.0);
+        let stats = config.attack;
+        // The first swing waits out a full cooldown, same as every one after it
+        if let Some(mut attack_behavior) = attack_behavior {
+            attack_behavior.cooldown = Cooldown::started(stats.cooldown);
+        }
+        commands.entity(entity).insert((
+            stats,
+            Evasion {
+                dodge_chance: config.dodge_chance,
+            },
+        ));
+    }
+}
+
+pub fn spawn_projectile(
+    commands: &mut Commands,
+    pools: &mut EntityPools,
+    from: Vec2,
+    damage: Damage,
+) {
+    let projectile = (
+        SpriteBundle {
+            sprite: Sprite {
+                color: BOLT_COLOR,
+                custom_size: Some(Vec2::splat(BOLT_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(from.extend(2.0)),
+            ..default()
+        },
+        Projectile {
+            target: damage.target,
+            damage,
+        },
+        Cleanup,
+    );
+    match pools.take_projectile() {
+        Some(entity) => {
+            commands
+                .entity(entity)
+                .remove::<Pooled>()
+                .insert(projectile);
+        }
+        None => {
+            commands.spawn(projectile);
+        }
+    }
+}
+
+pub fn move_projectiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &Projectile, &mut Transform)>,
+    // A target that has been pooled since is as good as gone
+    target_query: Query<&Transform, (Without<Pooled>, Without<Projectile>)>,
+    mut damage_writer: EventWriter<Damage>,
+) {
+    for (entity, projectile, mut transform) in query.iter_mut() {
+        let Ok(target_transform) = target_query.get(projectile.target) else {
+            pool::release_projectile(&mut commands, entity);
+            continue;
+        };
+
+        let to_target = target_transform.translation.truncate() - transform.translation.truncate();
+        let step = BOLT_SPEED * time.delta_seconds();
+        if to_target.length() <= BOLT_HIT_DISTANCE.max(step) {
+            damage_writer.send(projectile.damage);
+            pool::release_projectile(&mut commands, entity);
+            continue;
+        }
+
+        transform.translation += (to_target.normalize() * step).extend(0.0);
+    }
+}
@@ -0,0 +1,347 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use rand::Rng;
+
+use crate::animation::Tint;
+use crate::events::{Damage, Damaged, GameEvent, UnitDied};
+use crate::player::relics::{Relic, Relics};
+use crate::rng::GameRng;
+use crate::velocity::Knockback;
+
+use super::{
+    attack::Evasion,
+    health::Health,
+    stat_modifiers::{Stat, StatModifiers},
+    team::{AllianceMatrix, CurrentTeam, Team},
+    unit_types::CurrentUnitType,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DamageKind {
+    #[default]
+    Physical,
+    Dark,
+    Fire,
+}
+
+// How far the leftover damage of a killing blow can jump with the overkill relic
+const OVERKILL_SPLASH_RADIUS: f32 = 160.0;
+// Shove speed for every point of health a hit takes off, so only the big ones really move anyone
+const KNOCKBACK_PER_DAMAGE: f32 = 20.0;
+const MAX_KNOCKBACK: f32 = 800.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageStage {
+    Dodge,
+    Amplify,
+    Resist,
+    Armor,
+    Health,
+    Knockback,
+    Overkill,
+}
+
+// Every hit runs through the stages in this order, each one works on what the previous left over
+pub const DAMAGE_PIPELINE: [DamageStage; 7] = [
+    DamageStage::Dodge,
+    DamageStage::Amplify,
+    DamageStage::Resist,
+    DamageStage::Armor,
+    DamageStage::Health,
+    DamageStage::Knockback,
+    DamageStage::Overkill,
+];
+
+// Fraction of incoming damage of each kind that is ignored, negative values are weaknesses.
+// Knockback works the same way on how far a hit shoves the unit, 1.0 stands its ground.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Resistances {
+    pub physical: f32,
+    pub dark: f32,
+    pub fire: f32,
+    pub knockback: f32,
+}
+
+impl Resistances {
+    pub fn against(&self, kind: DamageKind) -> f32 {
+        match kind {
+            DamageKind::Physical => self.physical,
+            DamageKind::Dark => self.dark,
+            DamageKind::Fire => self.fire,
+        }
+    }
+}
+
+pub fn resist_damage(damage: i32, kind: DamageKind, resistances: Option<&Resistances>) -> i32 {
+    let Some(resistances) = resistances else {
+        return damage;
+    };
+
+    let resistance = resistances.against(kind).min(1.0);
+    if resistance >= 1.0 {
+        return 0;
+    }
+
+    let resisted = (damage as f32 * (1.0 - resistance)).round();
+    (resisted as i32).max(1)
+}
+
+// Nothing gets through while it lasts, not even traps and explosions
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Invulnerable {
+    pub remaining: f32,
+}
+
+pub fn tick_invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Invulnerable)>,
+) {
+    for (entity, mut invulnerable) in query.iter_mut() {
+        invulnerable.remaining -= time.delta_seconds();
+        if invulnerable.remaining <= 0.0 {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
+
+// Flat damage reduction that holds for a number of hits, after which the shield breaks and the
+// unit takes full damage for the rest of its life.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Armor {
+    pub flat_reduction: i32,
+    pub hits_to_break: u32,
+    pub hits_taken: u32,
+}
+
+impl Armor {
+    pub fn is_broken(&self) -> bool {
+        self.hits_taken >= self.hits_to_break
+    }
+}
+
+pub fn mitigate_damage(damage: i32, armor: Option<&mut Armor>) -> i32 {
+    let Some(armor) = armor else {
+        return damage;
+    };
+
+    if armor.is_broken() || damage <= 0 {
+        return damage;
+    }
+
+    armor.hits_taken += 1;
+    // Armor never fully negates a hit, otherwise weak units could never break it
+    (damage - armor.flat_reduction).max(1)
+}
+
+type DamageableData = (
+    Entity,
+    &'static mut Health,
+    Option<&'static CurrentTeam>,
+    &'static Transform,
+    Option<&'static Resistances>,
+    Option<&'static mut Armor>,
+    Option<&'static StatModifiers>,
+    Option<&'static Evasion>,
+    Option<&'static CurrentUnitType>,
+    Has<Invulnerable>,
+    Option<&'static mut Knockback>,
+);
+
+#[allow(clippy::too_many_arguments)]
+pub fn apply_damage(
+    mut damage_reader: EventReader<Damage>,
+    alliances: Res<AllianceMatrix>,
+    relics: Res<Relics>,
+    mut rng: ResMut<GameRng>,
+    mut query: Query<DamageableData>,
+    mut event_writer: EventWriter<GameEvent>,
+    mut damaged_writer: EventWriter<Damaged>,
+    mut died_writer: EventWriter<UnitDied>,
+) {
+    // Overkill splashes are queued up behind the hits that caused them, the bool tells if a hit
+    // may splash, which keeps one big hit from chaining through a whole wave
+    let mut pending: VecDeque<(Damage, bool)> =
+        damage_reader.read().map(|damage| (*damage, true)).collect();
+
+    while let Some((damage, can_splash)) = pending.pop_front() {
+        // Hits without someone behind them, like traps, have nowhere to shove from
+        let source_position = damage
+            .source
+            .and_then(|source| query.get(source).ok())
+            .map(|(_, _, _, transform, ..)| transform.translation.truncate());
+        let Ok((
+            _,
+            mut health,
+            team,
+            transform,
+            resistances,
+            mut armor,
+            stats,
+            evasion,
+            unit_type,
+            invulnerable,
+            mut knockback,
+        )) = query.get_mut(damage.target)
+        else {
+            continue;
+        };
+
+        // Nothing lands during i-frames, and it isn't a dodge either, so there's nothing to show
+        if health.is_dead() || invulnerable {
+            continue;
+        }
+
+        // The altar isn't on a side so units don't go after it like any other target, it's only
+        // ever hit by the siege and it's the summoner's
+        let team = team.map_or(Team::Evil, |team| team.0);
+        let position = transform.translation.truncate();
+        let mut amount = damage.amount;
+        let mut lost = 0;
+        let mut splash = 0;
+        let mut dodged = false;
+        for stage in DAMAGE_PIPELINE {
+            match stage {
+                // Only attacks can be dodged, there's no stepping out of a trap or an explosion
+                DamageStage::Dodge => {
+                    dodged = damage.source.is_some()
+                        && evasion.is_some_and(|evasion| {
+                            rng.0.gen_bool(evasion.dodge_chance.clamp(0.0, 1.0) as f64)
+                        });
+                    if dodged {
+                        break;
+                    }
+                }
+                DamageStage::Amplify => {
+                    if let Some(stats) = stats {
+                        amount = stats.apply(Stat::DamageTaken, amount as f32).round() as i32;
+                    }
+                }
+                DamageStage::Resist => amount = resist_damage(amount, damage.kind, resistances),
+                DamageStage::Armor => {
+                    if !damage.armor_piercing {
+                        amount = mitigate_damage(amount, armor.as_deref_mut());
+                    }
+                }
+                DamageStage::Health => lost = health.damage(amount),
+                DamageStage::Knockback => {
+                    if let (Some(knockback), Some(source_position)) =
+                        (knockback.as_deref_mut(), source_position)
+                    {
+                        let resistance = resistances
+                            .map_or(0.0, |resistances| resistances.knockback.clamp(0.0, 1.0));
+                        let strength = (lost as f32 * KNOCKBACK_PER_DAMAGE * (1.0 - resistance))
+                            .min(MAX_KNOCKBACK);
+                        knockback.0 += (position - source_position).normalize_or_zero() * strength;
+                    }
+                }
+                DamageStage::Overkill => {
+                    if can_splash
+                        && health.is_dead()
+                        && relics.has(Relic::OverkillSplash)
+                        && alliances.is_hostile(Team::Evil, team)
+                    {
+                        splash = amount - lost;
+                    }
+                }
+            }
+        }
+
+        let killed = health.is_dead();
+        damaged_writer.send(Damaged {
+            target: damage.target,
+            amount: lost,
+            kind: damage.kind,
+            killed,
+            source: damage.source,
+            critical: damage.critical,
+            dodged,
+        });
+        if killed {
+            died_writer.send(UnitDied {
+                entity: damage.target,
+                unit_type: unit_type.map(|unit_type| unit_type.0),
+                team,
+                position,
+                killer: damage.source,
+            });
+        }
+
+        // Only kills of the player's enemies are worth any score
+        if killed && alliances.is_hostile(Team::Evil, team) {
+            event_writer.send(GameEvent::IncreaseScore);
+        }
+
+        if splash <= 0 {
+            continue;
+        }
+
+        // The next enemy is the closest one still fighting on the same side as the one that died
+        let next_target = query
+            .iter()
+            .filter(|(_, other_health, other_team, ..)| {
+                !other_health.is_dead() && other_team.is_some_and(|other_team| other_team.0 == team)
+            })
+            .map(|(other, _, _, other_transform, ..)| {
+                (
+                    other,
+                    (other_transform.translation.truncate() - position).length(),
+                )
+            })
+            .filter(|(_, distance)| *distance <= OVERKILL_SPLASH_RADIUS)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+        if let Some((target, _)) = next_target {
+            pending.push_back((
+                Damage {
+                    target,
+                    amount: splash,
+                    ..damage
+                },
+                false,
+            ));
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn apply_area_damage<'a>(
+    alliances: &AllianceMatrix,
+    center: Vec2,
+    radius: f32,
+    damage: i32,
+    kind: DamageKind,
+    team: &CurrentTeam,
+    targets: impl Iterator<Item = (Entity, &'a Transform, &'a CurrentTeam, &'a Health)>,
+    damage_writer: &mut EventWriter<Damage>,
+) {
+    for (target, target_transform, target_team, target_health) in targets {
+        if !team.is_hostile_to(target_team, alliances) || target_health.is_dead() {
+            continue;
+        }
+
+        let distance = (target_transform.translation.truncate() - center).length();
+        if distance > radius {
+            continue;
+        }
+
+        damage_writer.send(Damage {
+            target,
+            amount: damage,
+            kind,
+            armor_piercing: false,
+            source: None,
+            critical: false,
+        });
+    }
+}
+
+pub fn show_broken_armor(mut query: Query<(&Armor, &mut Tint), Changed<Armor>>) {
+    for (armor, mut tint) in query.iter_mut() {
+        if armor.is_broken() && tint.0 != Color::WHITE {
+            tint.0 = Color::WHITE;
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+
+use crate::ai::behavior::{Behavior, CurrentBehavior, DeadBehavior};
+use crate::animation::{Animation, AnimationType, Tint};
+use crate::events::AnimationFinished;
+use crate::gamestate::Cleanup;
+use crate::pool;
+
+const FADE_SECONDS: f32 = 1.2;
+// What a body fades down to when it's left behind, the corpse picks up from there
+const CORPSE_ALPHA: f32 = 0.45;
+const CORPSE_SECONDS: f32 = 20.0;
+const CORPSE_FADE_SECONDS: f32 = 3.0;
+// Just under the living, so nobody is drawn underneath the fallen
+const CORPSE_Z_OFFSET: f32 = -0.05;
+
+#[derive(Resource)]
+pub struct CorpseSettings {
+    pub leave_corpses: bool,
+    // Past this many the oldest are cleared away early, long fights don't pile up sprites forever
+    pub max_corpses: usize,
+}
+
+impl Default for CorpseSettings {
+    fn default() -> Self {
+        Self {
+            leave_corpses: true,
+            max_corpses: 40,
+        }
+    }
+}
+
+// The death animation played out, the unit fades and is despawned once it's gone
+#[derive(Component)]
+pub struct Fading {
+    timer: Timer,
+}
+
+// A still of the last frame of a unit's death, nothing else about the unit is left
+#[derive(Component)]
+pub struct Corpse {
+    lifetime: Timer,
+}
+
+// Dead units stay put until their death animation has finished, however long it is
+pub fn start_fading(
+    mut commands: Commands,
+    mut finished_reader: EventReader<AnimationFinished>,
+    query: Query<&CurrentBehavior, (With<DeadBehavior>, Without<Fading>)>,
+) {
+    for finished in finished_reader.read() {
+        if finished.animation_type != AnimationType::Death {
+            continue;
+        }
+        let Ok(current_behavior) = query.get(finished.entity) else {
+            continue;
+        };
+        if !matches!(current_behavior.0, Behavior::Dead(_)) {
+            continue;
+        }
+
+        commands.entity(finished.entity).insert(Fading {
+            timer: Timer::from_seconds(FADE_SECONDS, TimerMode::Once),
+        });
+    }
+}
+
+type FadingSpriteData = (
+    &'static Handle<Image>,
+    &'static TextureAtlas,
+    &'static Sprite,
+    &'static GlobalTransform,
+    &'static Visibility,
+);
+
+pub fn fade_dead_units(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<CorpseSettings>,
+    mut query: Query<(Entity, &mut Fading, Option<&mut Tint>, &Children)>,
+    child_query: Query<FadingSpriteData, With<Animation>>,
+) {
+    let target_alpha = if settings.leave_corpses {
+        CORPSE_ALPHA
+    } else {
+        0.0
+    };
+
+    for (entity, mut fading, tint, children) in query.iter_mut() {
+        let alpha = 1.0 - (1.0 - target_alpha) * fading.timer.tick(time.delta()).fraction();
+        match tint {
+            Some(mut tint) => {
+                tint.0.set_a(alpha);
+            }
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(Tint(Color::WHITE.with_a(alpha)));
+            }
+        }
+        if !fading.timer.finished() {
+            continue;
+        }
+
+        if settings.leave_corpses {
+            for &child in children.iter() {
+                let Ok((texture, atlas, sprite, transform, visibility)) = child_query.get(child)
+                else {
+                    continue;
+                };
+                if visibility != Visibility::Visible {
+                    continue;
+                }
+
+                let mut transform = transform.compute_transform();
+                transform.translation.z += CORPSE_Z_OFFSET;
+                commands.spawn((
+                    SpriteSheetBundle {
+                        sprite: Sprite {
+                            color: sprite.color.with_a(CORPSE_ALPHA),
+                            flip_x: sprite.flip_x,
+                            ..default()
+                        },
+                        texture: texture.clone(),
+                        atlas: atlas.clone(),
+                        transform,
+                        ..default()
+                    },
+                    Corpse {
+                        lifetime: Timer::from_seconds(CORPSE_SECONDS, TimerMode::Once),
+                    },
+                    Cleanup,
+                ));
+            }
+        }
+        pool::release_unit(&mut commands, entity);
+    }
+}
+
+pub fn expire_corpses(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<CorpseSettings>,
+    mut query: Query<(Entity, &mut Corpse, &mut Sprite)>,
+) {
+    let mut corpses: Vec<_> = query.iter_mut().collect();
+    // Oldest first
+    corpses.sort_by_key(|(_, corpse, _)| std::cmp::Reverse(corpse.lifetime.elapsed()));
+    let excess = corpses.len().saturating_sub(settings.max_corpses);
+
+    for (index, (entity, mut corpse, mut sprite)) in corpses.into_iter().enumerate() {
+        if index < excess || corpse.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let remaining = corpse.lifetime.remaining_secs();
+        if remaining < CORPSE_FADE_SECONDS {
+            sprite
+                .color
+                .set_a(CORPSE_ALPHA * remaining / CORPSE_FADE_SECONDS);
+        }
+    }
+}
@@ -0,0 +1,10 @@
+use bevy::prelude::*;
+
+// Flying units are out of reach of ground units, only units with CanTargetAir (the dark priests
+// and their bolts) go after them. Area damage such as the imp explosion still catches them, the
+// bone walls and spike traps don't.
+#[derive(Component, Clone, Copy, Default)]
+pub struct Flying;
+
+#[derive(Component, Clone, Copy, Default)]
+pub struct CanTargetAir;
@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+
+use crate::vfx::flash::{Flash, FlashLimiter, VfxSettings};
+
+use super::health::Health;
+use super::stat_modifiers::{Modifier, ModifierSource, Stat, StatModifiers};
+
+const FRENZY_MOVE_SPEED: f32 = 1.4;
+const FRENZY_ATTACK_SPEED: f32 = 1.75;
+const FRENZY_DAMAGE_TAKEN: f32 = 1.3;
+const FRENZY_AURA_RADIUS: f32 = 40.0;
+const FRENZY_AURA_ALPHA: f32 = 0.35;
+
+// Faster and angrier, but a lot less careful
+#[derive(Component)]
+pub struct Frenzy {
+    pub timer: Timer,
+}
+
+impl Frenzy {
+    pub fn new(duration: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct FrenzyAura;
+
+pub fn apply_frenzy_modifiers(mut query: Query<(&mut StatModifiers, Has<Frenzy>)>) {
+    for (mut modifiers, frenzy) in query.iter_mut() {
+        if frenzy {
+            modifiers.set(
+                ModifierSource::Frenzy,
+                &[
+                    Modifier::multiply(Stat::MoveSpeed, FRENZY_MOVE_SPEED),
+                    Modifier::multiply(Stat::AttackSpeed, FRENZY_ATTACK_SPEED),
+                    Modifier::multiply(Stat::DamageTaken, FRENZY_DAMAGE_TAKEN),
+                ],
+            );
+        } else if modifiers.has(ModifierSource::Frenzy) {
+            modifiers.remove(ModifierSource::Frenzy);
+        }
+    }
+}
+
+pub fn spawn_frenzy_auras(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut flash_limiter: ResMut<FlashLimiter>,
+    vfx_settings: Res<VfxSettings>,
+    query: Query<(Entity, &Transform), Added<Frenzy>>,
+) {
+    for (entity, transform) in query.iter() {
+        // The whole army lights up on the same frame, which counts as one big flash
+        let radius = FRENZY_AURA_RADIUS * transform.scale.x;
+        let flash = flash_limiter.limit(
+            &vfx_settings,
+            Flash {
+                alpha: FRENZY_AURA_ALPHA,
+                duration: 0.0,
+            },
+            std::f32::consts::PI * radius * radius,
+        );
+
+        let aura = commands
+            .spawn((
+                MaterialMesh2dBundle {
+                    mesh: Mesh2dHandle(meshes.add(Circle::new(FRENZY_AURA_RADIUS))),
+                    material: materials.add(Color::rgba(0.9, 0.1, 0.1, flash.alpha)),
+                    transform: Transform::from_xyz(0.0, 0.0, -0.5),
+                    ..default()
+                },
+                FrenzyAura,
+            ))
+            .id();
+        commands.entity(entity).add_child(aura);
+    }
+}
+
+pub fn tick_frenzy(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Frenzy, &Health, &Children)>,
+    aura_query: Query<Entity, With<FrenzyAura>>,
+) {
+    for (entity, mut frenzy, health, children) in query.iter_mut() {
+        if !frenzy.timer.tick(time.delta()).finished() && !health.is_dead() {
+            continue;
+        }
+
+        commands.entity(entity).remove::<Frenzy>();
+        for &child in children.iter() {
+            if aura_query.contains(child) {
+                commands.entity(child).despawn_recursive();
+            }
+        }
+    }
+}
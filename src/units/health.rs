@@ -1,16 +1,73 @@
 use bevy::prelude::*;
 
-#[derive(Component)]
-pub struct Health(pub u8);
+use crate::events::{Heal, Healed};
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Health {
+    pub current: i32,
+    pub max: i32,
+}
 
 impl Default for Health {
     fn default() -> Self {
-        Health(100)
+        Health::new(100)
     }
 }
 
 impl Health {
+    pub fn new(max: i32) -> Self {
+        Self { current: max, max }
+    }
+
     pub fn is_dead(&self) -> bool {
-        self.0 == 0
+        self.current <= 0
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.max <= 0 {
+            return 0.0;
+        }
+
+        self.current.max(0) as f32 / self.max as f32
+    }
+
+    // Returns how much health was actually lost, never more than what was left
+    pub fn damage(&mut self, amount: i32) -> i32 {
+        let applied = amount.clamp(0, self.current.max(0));
+        self.current -= applied;
+        applied
+    }
+
+    // Returns how much health was actually restored, healing is clamped to max and the dead
+    // stay dead
+    pub fn heal(&mut self, amount: i32) -> i32 {
+        if self.is_dead() {
+            return 0;
+        }
+
+        let applied = amount.clamp(0, self.max - self.current);
+        self.current += applied;
+        applied
+    }
+}
+
+pub fn apply_heal(
+    mut heal_reader: EventReader<Heal>,
+    mut query: Query<&mut Health>,
+    mut healed_writer: EventWriter<Healed>,
+) {
+    for heal in heal_reader.read() {
+        let Ok(mut health) = query.get_mut(heal.target) else {
+            continue;
+        };
+
+        let amount = health.heal(heal.amount);
+        if amount > 0 {
+            healed_writer.send(Healed {
+                target: heal.target,
+                amount,
+                source: heal.source,
+            });
+        }
     }
 }
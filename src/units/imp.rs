@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+
+use crate::gamestate::Cleanup;
+use crate::vfx::flash::{Flash, FlashLimiter, VfxSettings};
+
+const EXPLOSION_DURATION: f32 = 0.4;
+const EXPLOSION_ALPHA: f32 = 0.8;
+
+#[derive(Component)]
+pub struct Explosion {
+    pub timer: Timer,
+    pub peak_alpha: f32,
+}
+
+pub fn spawn_explosion(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    flash_limiter: &mut FlashLimiter,
+    vfx_settings: &VfxSettings,
+    position: Vec2,
+    radius: f32,
+) {
+    let flash = flash_limiter.limit(
+        vfx_settings,
+        Flash {
+            alpha: EXPLOSION_ALPHA,
+            duration: EXPLOSION_DURATION,
+        },
+        std::f32::consts::PI * radius * radius,
+    );
+
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: Mesh2dHandle(meshes.add(Circle::new(radius))),
+            material: materials.add(Color::rgba(1.0, 0.35, 0.1, flash.alpha)),
+            transform: Transform::from_translation(position.extend(1.0))
+                .with_scale(Vec3::splat(0.2)),
+            ..default()
+        },
+        Explosion {
+            timer: Timer::from_seconds(flash.duration, TimerMode::Once),
+            peak_alpha: flash.alpha,
+        },
+        Cleanup,
+    ));
+}
+
+pub fn update_explosions(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut query: Query<(
+        Entity,
+        &mut Explosion,
+        &mut Transform,
+        &Handle<ColorMaterial>,
+    )>,
+) {
+    for (entity, mut explosion, mut transform, material) in query.iter_mut() {
+        if explosion.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        // Grow quickly to the full blast radius, then fade out
+        let progress = explosion.timer.fraction();
+        transform.scale = Vec3::splat(0.2 + 0.8 * progress.sqrt());
+        if let Some(material) = materials.get_mut(material) {
+            material
+                .color
+                .set_a(explosion.peak_alpha * (1.0 - progress));
+        }
+    }
+}
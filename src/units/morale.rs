@@ -0,0 +1,214 @@
+use bevy::prelude::*;
+
+use crate::events::Damaged;
+use crate::player::plugin::Player;
+
+use super::health::Health;
+use super::stat_modifiers::{Modifier, ModifierSource, Stat, StatModifiers};
+use super::team::CurrentTeam;
+
+pub const MAX_MORALE: f32 = 100.0;
+const BASELINE_MORALE: f32 = 50.0;
+const LOW_MORALE: f32 = 25.0;
+const HIGH_MORALE: f32 = 75.0;
+const MORALE_RECOVERY_PER_SECOND: f32 = 2.0;
+const ALLY_DEATH_MORALE_LOSS: f32 = 15.0;
+const ALLY_DEATH_RADIUS: f32 = 200.0;
+const PLAYER_PROXIMITY_RADIUS: f32 = 250.0;
+const PLAYER_PROXIMITY_MORALE_PER_SECOND: f32 = 6.0;
+const MORALE_ICON_OFFSET_Y: f32 = 28.0;
+
+// How willing a unit is to keep fighting. Drifts back towards the baseline on its own, drops
+// when allies die close by, and is kept up by banners and, for the player's units, the player.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Morale {
+    pub value: f32,
+}
+
+impl Default for Morale {
+    fn default() -> Self {
+        Self {
+            value: BASELINE_MORALE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoraleState {
+    Low,
+    Steady,
+    High,
+}
+
+impl Morale {
+    pub fn state(&self) -> MoraleState {
+        if self.value <= LOW_MORALE {
+            MoraleState::Low
+        } else if self.value >= HIGH_MORALE {
+            MoraleState::High
+        } else {
+            MoraleState::Steady
+        }
+    }
+
+    // 0.75 at no morale up to 1.25 at full morale
+    pub fn attack_speed_factor(&self) -> f32 {
+        0.75 + 0.5 * (self.value / MAX_MORALE).clamp(0.0, 1.0)
+    }
+
+    // Units with low morale start running from enemies that are further away
+    pub fn flee_distance_factor(&self) -> f32 {
+        1.5 - 0.75 * (self.value / MAX_MORALE).clamp(0.0, 1.0)
+    }
+
+    fn add(&mut self, amount: f32) {
+        self.value = (self.value + amount).clamp(0.0, MAX_MORALE);
+    }
+}
+
+// Raises the morale of allies around whoever carries it
+#[derive(Component, Clone, Copy, Debug)]
+pub struct MoraleBanner {
+    pub radius: f32,
+    pub morale_per_second: f32,
+}
+
+impl Default for MoraleBanner {
+    fn default() -> Self {
+        Self {
+            radius: 200.0,
+            morale_per_second: 8.0,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct MoraleIcon;
+
+pub fn apply_morale_modifiers(mut query: Query<(&mut StatModifiers, &Morale)>) {
+    for (mut modifiers, morale) in query.iter_mut() {
+        modifiers.set(
+            ModifierSource::Morale,
+            &[Modifier::multiply(
+                Stat::AttackSpeed,
+                morale.attack_speed_factor(),
+            )],
+        );
+    }
+}
+
+pub fn update_morale(
+    time: Res<Time>,
+    banner_query: Query<(&MoraleBanner, &Transform, &CurrentTeam, &Health)>,
+    player_query: Query<(&Transform, &CurrentTeam), With<Player>>,
+    mut query: Query<(&mut Morale, &Transform, &CurrentTeam, &Health), Without<Player>>,
+) {
+    let delta = time.delta_seconds();
+    let player = player_query.iter().next();
+
+    for (mut morale, transform, team, health) in query.iter_mut() {
+        if health.is_dead() {
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+        let recovery = (BASELINE_MORALE - morale.value)
+            .clamp(-MORALE_RECOVERY_PER_SECOND, MORALE_RECOVERY_PER_SECOND);
+        let mut change = recovery * delta;
+
+        for (banner, banner_transform, banner_team, banner_health) in banner_query.iter() {
+            let distance = (banner_transform.translation.truncate() - position).length();
+            if banner_team.0 == team.0 && !banner_health.is_dead() && distance <= banner.radius {
+                change += banner.morale_per_second * delta;
+            }
+        }
+
+        if let Some((player_transform, player_team)) = player {
+            let distance = (player_transform.translation.truncate() - position).length();
+            if player_team.0 == team.0 && distance <= PLAYER_PROXIMITY_RADIUS {
+                change += PLAYER_PROXIMITY_MORALE_PER_SECOND * delta;
+            }
+        }
+
+        morale.add(change);
+    }
+}
+
+pub fn ally_death_morale(
+    mut damaged_reader: EventReader<Damaged>,
+    dead_query: Query<(&Transform, &CurrentTeam)>,
+    mut query: Query<(Entity, &mut Morale, &Transform, &CurrentTeam)>,
+) {
+    for damaged in damaged_reader.read() {
+        if !damaged.killed {
+            continue;
+        }
+
+        let Ok((dead_transform, dead_team)) = dead_query.get(damaged.target) else {
+            continue;
+        };
+
+        let dead_position = dead_transform.translation.truncate();
+        for (entity, mut morale, transform, team) in query.iter_mut() {
+            let distance = (transform.translation.truncate() - dead_position).length();
+            if entity != damaged.target && team.0 == dead_team.0 && distance <= ALLY_DEATH_RADIUS {
+                morale.add(-ALLY_DEATH_MORALE_LOSS);
+            }
+        }
+    }
+}
+
+pub fn spawn_morale_icons(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    query: Query<(Entity, &Transform), Added<Morale>>,
+) {
+    for (entity, transform) in query.iter() {
+        let inverse_scale = Vec3::ONE / transform.scale.max(Vec3::splat(0.01));
+        let icon = commands
+            .spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("fonts/JetBrainsMonoNerdFont-Regular.ttf"),
+                            font_size: 20.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    transform: Transform::from_xyz(0.0, MORALE_ICON_OFFSET_Y, 2.0)
+                        .with_scale(inverse_scale),
+                    ..default()
+                },
+                MoraleIcon,
+            ))
+            .id();
+        commands.entity(entity).add_child(icon);
+    }
+}
+
+// Only wavering and inspired units get an icon, steady ones are left alone to keep it readable
+pub fn update_morale_icons(
+    query: Query<(&Morale, &Health, &Children), Changed<Morale>>,
+    mut icon_query: Query<&mut Text, With<MoraleIcon>>,
+) {
+    for (morale, health, children) in query.iter() {
+        let (icon, color) = match morale.state() {
+            _ if health.is_dead() => ("", Color::WHITE),
+            MoraleState::Low => ("!", Color::rgb(0.9, 0.2, 0.2)),
+            MoraleState::Steady => ("", Color::WHITE),
+            MoraleState::High => ("^", Color::rgb(1.0, 0.85, 0.2)),
+        };
+
+        for &child in children.iter() {
+            let Ok(mut text) = icon_query.get_mut(child) else {
+                continue;
+            };
+
+            if text.sections[0].value != icon {
+                text.sections[0].value = icon.to_owned();
+                text.sections[0].style.color = color;
+            }
+        }
+    }
+}
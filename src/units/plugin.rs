@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+
+use crate::dark_arts_defense::AppState;
+use crate::difficulty;
+use crate::enemies::endless;
+use crate::meta::progress;
+use crate::pool;
+use crate::schedule::{FrameSet, SimSet};
+use crate::time_of_day;
+use crate::units::{
+    acolyte, altar, attack, damage, death, frenzy, health, imp, morale, quality, spawning,
+    stat_modifiers, team, veterancy, wildlife, y_sort,
+};
+
+pub struct UnitsPlugin;
+
+impl Plugin for UnitsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<team::AllianceMatrix>()
+            .init_resource::<wildlife::Wildlife>()
+            .init_resource::<quality::UnitQualitySettings>()
+            .init_resource::<death::CorpseSettings>()
+            .init_resource::<pool::EntityPools>()
+            .add_systems(
+                Update,
+                (
+                    (
+                        acolyte::acolyte_mana_giver,
+                        acolyte::spawn_acolyte_auras,
+                        acolyte::pulse_acolyte_auras,
+                    )
+                        .chain(),
+                    imp::update_explosions,
+                    altar::siege_altar,
+                    altar::update_altar_color,
+                    wildlife::spawn_wildlife.run_if(in_state(AppState::Playing)),
+                    attack::apply_combat_stats,
+                    damage::show_broken_armor,
+                    veterancy::record_kills,
+                    (
+                        quality::roll_unit_quality,
+                        quality::tint_gifted_units,
+                        veterancy::track_veterancy,
+                        veterancy::apply_veterancy_modifiers,
+                        frenzy::apply_frenzy_modifiers,
+                        morale::apply_morale_modifiers,
+                        time_of_day::apply_day_night_modifiers,
+                        difficulty::apply_difficulty_modifiers,
+                        endless::apply_endless_modifiers,
+                        progress::apply_upgrade_modifiers,
+                        stat_modifiers::track_base_max_health,
+                        stat_modifiers::apply_max_health_modifiers,
+                    )
+                        .chain(),
+                    frenzy::spawn_frenzy_auras,
+                    frenzy::tick_frenzy,
+                    morale::update_morale,
+                    morale::ally_death_morale,
+                    morale::spawn_morale_icons,
+                    morale::update_morale_icons,
+                ),
+            )
+            // Bolts land in the same step the attack that fired them does
+            .add_systems(
+                FixedUpdate,
+                (
+                    (
+                        attack::move_projectiles,
+                        damage::apply_damage,
+                        health::apply_heal,
+                    )
+                        .chain()
+                        .in_set(SimSet::Combat),
+                    damage::tick_invulnerability.in_set(SimSet::Cleanup),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    (death::start_fading, death::fade_dead_units).chain(),
+                    death::expire_corpses,
+                    (
+                        spawning::rise_out_of_circle,
+                        spawning::draw_summoning_circles,
+                        y_sort::y_sort_units,
+                    )
+                        .in_set(FrameSet::Presentation),
+                ),
+            );
+    }
+}
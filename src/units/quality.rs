@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::animation::Tint;
+use crate::player::gravestones::Revived;
+use crate::player::relics::{Relic, Relics};
+use crate::rng::GameRng;
+use crate::save::checkpoints::Restored;
+use crate::time_of_day::lerp_color;
+
+use super::stat_modifiers::{Modifier, ModifierSource, Stat, StatModifiers};
+use super::team::{CurrentTeam, Team};
+
+pub const GIFTED_TINT: Color = Color::rgb(1.0, 0.85, 0.3);
+// How far a unit's own tint is pulled towards gold, imps and dark priests still need to be told
+// apart by theirs
+const GIFTED_TINT_BLEND: f32 = 0.5;
+const GILDED_GIFTED_CHANCE_FACTOR: f64 = 3.0;
+const ROLLED_STATS: [Stat; 4] = [
+    Stat::MoveSpeed,
+    Stat::AttackSpeed,
+    Stat::Damage,
+    Stat::MaxHealth,
+];
+
+// No two summons come out of the ritual quite the same, and every now and then one of them
+// comes out a lot better than the rest
+#[derive(Resource)]
+pub struct UnitQualitySettings {
+    pub enabled: bool,
+    // Each stat is rolled somewhere within this fraction above or below normal
+    pub variance: f32,
+    pub gifted_chance: f64,
+    pub gifted_bonus: f32,
+}
+
+impl Default for UnitQualitySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            variance: 0.05,
+            gifted_chance: 0.03,
+            gifted_bonus: 0.15,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Gifted;
+
+type UnrolledFilter = (Added<StatModifiers>, Without<Revived>, Without<Restored>);
+
+// Rolled once when a summon is spawned, through the seeded rng so a run plays out the same way
+pub fn roll_unit_quality(
+    mut commands: Commands,
+    settings: Res<UnitQualitySettings>,
+    relics: Res<Relics>,
+    mut rng: ResMut<GameRng>,
+    mut query: Query<(Entity, &CurrentTeam, &mut StatModifiers), UnrolledFilter>,
+) {
+    if !settings.enabled {
+        return;
+    }
+
+    for (entity, team, mut modifiers) in query.iter_mut() {
+        if team.0 != Team::Evil {
+            continue;
+        }
+
+        let gifted_chance = if relics.has(Relic::GildedSummons) {
+            settings.gifted_chance * GILDED_GIFTED_CHANCE_FACTOR
+        } else {
+            settings.gifted_chance
+        };
+        let gifted = rng.0.gen_bool(gifted_chance.clamp(0.0, 1.0));
+        let bonus = if gifted {
+            1.0 + settings.gifted_bonus
+        } else {
+            1.0
+        };
+
+        let mut rolled = Vec::with_capacity(ROLLED_STATS.len());
+        for stat in ROLLED_STATS {
+            let variance = if settings.variance > 0.0 {
+                rng.0.gen_range(-settings.variance..=settings.variance)
+            } else {
+                0.0
+            };
+            rolled.push(Modifier::multiply(stat, (1.0 + variance) * bonus));
+        }
+        modifiers.set(ModifierSource::Quality, &rolled);
+
+        if gifted {
+            commands.entity(entity).insert(Gifted);
+        }
+    }
+}
+
+// However a unit came to be gifted, rolled, revived or restored, the gold goes on top of the tint
+// it already has instead of replacing it
+pub fn tint_gifted_units(
+    mut commands: Commands,
+    mut query: Query<(Entity, Option<&mut Tint>), Added<Gifted>>,
+) {
+    for (entity, tint) in query.iter_mut() {
+        match tint {
+            Some(mut tint) => tint.0 = lerp_color(tint.0, GIFTED_TINT, GIFTED_TINT_BLEND),
+            None => {
+                commands.entity(entity).insert(Tint(GIFTED_TINT));
+            }
+        }
+    }
+}
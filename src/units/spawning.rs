@@ -0,0 +1,103 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use std::f32::consts::TAU;
+
+use crate::ai::behavior::{Behavior, CurrentBehavior, SpawningBehavior, SupportedBehaviors};
+use crate::animation::Animation;
+use crate::units::damage::Invulnerable;
+
+const SPAWN_SECONDS: f32 = 0.8;
+// Over everything but dying, not that anything can hurt a unit that's still rising
+const SPAWNING_PRIORITY: u8 = 30;
+// How far under the circle the sprite starts out
+const RISE_DEPTH: f32 = 24.0;
+const CIRCLE_RADIUS: f32 = 30.0;
+const CIRCLE_COLOR: Color = Color::rgb(0.6, 0.3, 1.0);
+const CIRCLE_SPIN: f32 = 3.0;
+
+// Has the unit rise out of a summoning circle before it starts acting. Goes on right after the
+// unit is spawned, the behaviors it got from its type are already there to add this one to.
+pub fn rise_from_circle(entity: &mut EntityCommands) {
+    let spawning = SpawningBehavior {
+        remaining: SPAWN_SECONDS,
+    };
+    entity.insert((
+        spawning,
+        Invulnerable {
+            remaining: SPAWN_SECONDS,
+        },
+    ));
+    entity.add(move |entity: Entity, world: &mut World| {
+        if let Some(mut supported) = world.get_mut::<SupportedBehaviors>(entity) {
+            let behavior = Behavior::Spawning(spawning);
+            if !supported
+                .0
+                .iter()
+                .any(|(other, _)| other.is_same_kind(&behavior))
+            {
+                supported.0.push((behavior, SPAWNING_PRIORITY));
+            }
+        }
+        // Straight in, so it doesn't wander off for a frame first
+        if let Some(mut current) = world.get_mut::<CurrentBehavior>(entity) {
+            current.0 = Behavior::Spawning(spawning);
+        }
+    });
+}
+
+fn progress(spawning: &SpawningBehavior) -> f32 {
+    (1.0 - spawning.remaining / SPAWN_SECONDS).clamp(0.0, 1.0)
+}
+
+// Grows the sprites up out of the ground, the circle is where the feet are
+pub fn rise_out_of_circle(
+    query: Query<(&SpawningBehavior, &Children)>,
+    mut child_query: Query<&mut Transform, With<Animation>>,
+) {
+    for (spawning, children) in query.iter() {
+        let progress = progress(spawning);
+        let risen = Transform {
+            translation: Vec3::new(0.0, -RISE_DEPTH * (1.0 - progress), 0.0),
+            scale: Vec3::new(1.0, progress, 1.0),
+            ..default()
+        };
+
+        for &child in children.iter() {
+            if let Ok(mut transform) = child_query.get_mut(child) {
+                if *transform != risen {
+                    *transform = risen;
+                }
+            }
+        }
+    }
+}
+
+pub fn draw_summoning_circles(
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    query: Query<(&Transform, &SpawningBehavior)>,
+) {
+    for (transform, spawning) in query.iter() {
+        if spawning.remaining <= 0.0 {
+            continue;
+        }
+
+        // Opens up quickly, then fades as the unit finishes rising
+        let progress = progress(spawning);
+        let radius = CIRCLE_RADIUS * (progress * 4.0).min(1.0);
+        let color = CIRCLE_COLOR.with_a(1.0 - progress);
+        let position = transform.translation.truncate();
+        let spin = time.elapsed_seconds() * CIRCLE_SPIN;
+        gizmos.circle_2d(position, radius, color);
+        gizmos.circle_2d(position, radius * 0.7, color);
+        for side in 0..3 {
+            gizmos.arc_2d(
+                position,
+                spin + side as f32 * TAU / 3.0,
+                TAU / 6.0,
+                radius * 0.85,
+                color,
+            );
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::health::Health;
+use super::unit_types::UnitType;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stat {
+    MoveSpeed,
+    // A rate, 1.0 is the unit's normal attack cooldown
+    AttackSpeed,
+    Damage,
+    DamageTaken,
+    MaxHealth,
+    // Only the summoner has these, they're about what the ritual does rather than any one unit
+    SummonCost(UnitType),
+    SummonRadius,
+    Lifesteal,
+}
+
+// Whatever put a modifier on a unit, each source owns its modifiers and replaces or removes them
+// without touching anyone else's
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModifierSource {
+    Frenzy,
+    Morale,
+    DayNight,
+    Quality,
+    Veterancy,
+    Difficulty,
+    Endless,
+    Upgrades,
+    Haste,
+    Perks,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct Modifier {
+    pub stat: Stat,
+    pub additive: f32,
+    pub multiplier: f32,
+}
+
+impl Modifier {
+    pub fn add(stat: Stat, amount: f32) -> Self {
+        Self {
+            stat,
+            additive: amount,
+            multiplier: 1.0,
+        }
+    }
+
+    pub fn multiply(stat: Stat, multiplier: f32) -> Self {
+        Self {
+            stat,
+            additive: 0.0,
+            multiplier,
+        }
+    }
+}
+
+// Buffs and debuffs register here instead of changing Movement, AttackBehavior or Health, and
+// the systems that move, attack or take damage run their base value through apply. All the
+// additive modifiers go on first and the multipliers are applied on top of that sum, so the
+// order sources register in never matters.
+#[derive(Component, Clone, Debug, Default)]
+pub struct StatModifiers {
+    modifiers: Vec<(ModifierSource, Modifier)>,
+}
+
+impl StatModifiers {
+    pub fn set(&mut self, source: ModifierSource, modifiers: &[Modifier]) {
+        self.remove(source);
+        self.modifiers
+            .extend(modifiers.iter().map(|modifier| (source, *modifier)));
+    }
+
+    pub fn remove(&mut self, source: ModifierSource) {
+        self.modifiers.retain(|(other, _)| *other != source);
+    }
+
+    pub fn of(&self, source: ModifierSource) -> Vec<Modifier> {
+        self.modifiers
+            .iter()
+            .filter(|(other, _)| *other == source)
+            .map(|(_, modifier)| *modifier)
+            .collect()
+    }
+
+    pub fn has(&self, source: ModifierSource) -> bool {
+        self.modifiers.iter().any(|(other, _)| *other == source)
+    }
+
+    // Every source with something on the unit, each once
+    pub fn sources(&self) -> Vec<ModifierSource> {
+        let mut sources = Vec::new();
+        for (source, _) in self.modifiers.iter() {
+            if !sources.contains(source) {
+                sources.push(*source);
+            }
+        }
+        sources
+    }
+
+    pub fn apply(&self, stat: Stat, base: f32) -> f32 {
+        let (additive, multiplier) = self
+            .modifiers
+            .iter()
+            .filter(|(_, modifier)| modifier.stat == stat)
+            .fold((0.0, 1.0), |(additive, multiplier), (_, modifier)| {
+                (
+                    additive + modifier.additive,
+                    multiplier * modifier.multiplier,
+                )
+            });
+
+        ((base + additive) * multiplier).max(0.0)
+    }
+}
+
+// The max health a unit was spawned with, Health.max is what it is after modifiers
+#[derive(Component, Clone, Copy, Debug)]
+pub struct BaseMaxHealth(pub i32);
+
+type UntrackedFilter = (With<StatModifiers>, Without<BaseMaxHealth>);
+
+pub fn track_base_max_health(
+    mut commands: Commands,
+    query: Query<(Entity, &Health), UntrackedFilter>,
+) {
+    for (entity, health) in query.iter() {
+        commands.entity(entity).insert(BaseMaxHealth(health.max));
+    }
+}
+
+// Gaining max health heals by the same amount, losing it only caps the current health
+pub fn apply_max_health_modifiers(
+    mut query: Query<(&mut Health, &BaseMaxHealth, &StatModifiers), Changed<StatModifiers>>,
+) {
+    for (mut health, base, modifiers) in query.iter_mut() {
+        let max = (modifiers.apply(Stat::MaxHealth, base.0 as f32).round() as i32).max(1);
+        if max == health.max {
+            continue;
+        }
+
+        if max > health.max && !health.is_dead() {
+            health.current += max - health.max;
+        }
+        health.max = max;
+        health.current = health.current.min(max);
+    }
+}
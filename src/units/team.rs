@@ -1,12 +1,32 @@
+use std::collections::HashMap;
+
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Eq, PartialEq, Default, Clone)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Default, Clone, Copy, Debug)]
 pub enum Team {
     #[default]
     Evil, // In this game, the player is evil
     Good,
+    Neutral,     // Creeps and critters that mind their own business
+    Faction(u8), // Third parties, hostile to everyone but themselves unless told otherwise
+}
+
+impl Team {
+    pub fn id(&self) -> usize {
+        match self {
+            Team::Evil => 0,
+            Team::Good => 1,
+            Team::Neutral => 2,
+            Team::Faction(faction) => 3 + *faction as usize,
+        }
+    }
 }
 
+// Summons that broke free of the player, see player/corruption.rs
+pub const ROGUE: Team = Team::Faction(0);
+pub const ROGUE_COLOR: Color = Color::rgb(0.6, 0.3, 0.9);
+
 #[derive(Component, Default, Clone)]
 pub struct CurrentTeam(pub Team);
 
@@ -14,4 +34,58 @@ impl CurrentTeam {
     pub fn is_friendly(&self, other: &CurrentTeam) -> bool {
         self.0 == other.0
     }
+
+    pub fn is_hostile_to(&self, other: &CurrentTeam, alliances: &AllianceMatrix) -> bool {
+        alliances.is_hostile(self.0, other.0)
+    }
+}
+
+// Who is hostile to who. Pairs that haven't been set explicitly fall back to a team never being
+// hostile to itself or to the neutral team, and being hostile to everyone else.
+#[derive(Resource, Debug, Clone)]
+pub struct AllianceMatrix {
+    relations: HashMap<(Team, Team), bool>,
+}
+
+impl Default for AllianceMatrix {
+    fn default() -> Self {
+        let mut alliances = Self {
+            relations: HashMap::new(),
+        };
+        // Rogue summons don't even leave the wildlife alone
+        alliances.set_hostile(ROGUE, Team::Neutral, true);
+        alliances
+    }
+}
+
+impl AllianceMatrix {
+    fn key(a: Team, b: Team) -> (Team, Team) {
+        // Hostility always goes both ways, so both orders share an entry
+        if a.id() <= b.id() {
+            (a, b)
+        } else {
+            (b, a)
+        }
+    }
+
+    pub fn is_hostile(&self, a: Team, b: Team) -> bool {
+        if a == b {
+            return false;
+        }
+
+        match self.relations.get(&Self::key(a, b)) {
+            Some(hostile) => *hostile,
+            None => a != Team::Neutral && b != Team::Neutral,
+        }
+    }
+
+    pub fn set_hostile(&mut self, a: Team, b: Team, hostile: bool) {
+        if a != b {
+            self.relations.insert(Self::key(a, b), hostile);
+        }
+    }
+
+    pub fn ally(&mut self, a: Team, b: Team) {
+        self.set_hostile(a, b, false);
+    }
 }
@@ -1,37 +1,119 @@
+use crate::ai::aggro::{Aggro, ThreatMultiplier};
 use crate::ai::behavior::{
-    AttackBehavior, Behavior, BehaviorBundle, ChaseBehavior, CurrentBehavior, DeadBehavior,
-    FleeBehavior, IdleBehavior, MoveOrigoBehavior, SupportedBehaviors, WanderBehavior,
+    AttackBehavior, Behavior, BehaviorBundle, ChaseBehavior, Convertible, CurrentBehavior,
+    DeadBehavior, FleeBehavior, FollowFormationBehavior, GuardBehavior, HealBehavior, IdleBehavior,
+    InsertBundle, KamikazeBehavior, MoveOrigoBehavior, MoveToRallyBehavior, PatrolBehavior,
+    SupportedBehaviors, WanderBehavior,
+};
+use crate::animation::{
+    resolve_animated_children, AnimationBundle, AtlasLayouts, CurrentAnimation, Tint,
 };
-use crate::animation::{spawn_animated_children, CurrentAnimation};
 use crate::animation::{AnimatedChildSpawnParams, AnimationType};
+use crate::difficulty::Difficulty;
+use crate::enemies::assassin::Blink;
 use crate::gamestate::Cleanup;
-use crate::movement::Movement;
-use crate::units::{health::Health, team::CurrentTeam};
-use crate::velocity::Velocity;
+use crate::movement::{Movement, Steering};
+use crate::pool::{EntityPools, Pooled};
+use crate::units::{
+    attack::{AttackStats, ProjectileType},
+    damage::{Armor, DamageKind, Resistances},
+    flying::{CanTargetAir, Flying},
+    health::Health,
+    morale::{Morale, MoraleBanner},
+    stat_modifiers::{Stat, StatModifiers},
+    team::CurrentTeam,
+};
+use crate::utils::timing::Charge;
+use crate::velocity::{Knockback, Motion, Velocity};
 use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use super::team::Team;
+use super::team::{Team, ROGUE, ROGUE_COLOR};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum UnitType {
     Acolyte,
     Warrior,
     Cat,
+    Imp,
+    DarkPriest,
 
     Knight,
+    Gargoyle,
+    ArmoredKnight,
+    Assassin,
+
+    Critter,
+}
+
+impl UnitType {
+    pub const ALL: [UnitType; 10] = [
+        UnitType::Acolyte,
+        UnitType::Warrior,
+        UnitType::Cat,
+        UnitType::Imp,
+        UnitType::DarkPriest,
+        UnitType::Knight,
+        UnitType::Gargoyle,
+        UnitType::ArmoredKnight,
+        UnitType::Assassin,
+        UnitType::Critter,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            UnitType::Acolyte => "Acolyte",
+            UnitType::Warrior => "Warrior",
+            UnitType::Cat => "Cat",
+            UnitType::Imp => "Imp",
+            UnitType::DarkPriest => "Dark Priest",
+            UnitType::Knight => "Knight",
+            UnitType::Gargoyle => "Gargoyle",
+            UnitType::ArmoredKnight => "Armored Knight",
+            UnitType::Assassin => "Assassin",
+            UnitType::Critter => "Critter",
+        }
+    }
+
+    pub fn children_spawn_params(&self) -> Vec<AnimatedChildSpawnParams> {
+        self.factory().create_children_spawn_params()
+    }
+
+    fn factory(&self) -> Box<dyn UnitChildrenSpawnParamsFactory> {
+        match self {
+            UnitType::Acolyte => Box::new(Acolyte::default()),
+            UnitType::Warrior => Box::new(Warrior),
+            UnitType::Cat => Box::new(Cat),
+            UnitType::Imp => Box::new(Imp),
+            UnitType::DarkPriest => Box::new(DarkPriest),
+            UnitType::Knight => Box::new(Knight),
+            UnitType::Gargoyle => Box::new(Gargoyle),
+            UnitType::ArmoredKnight => Box::new(ArmoredKnight),
+            UnitType::Assassin => Box::new(Assassin),
+            UnitType::Critter => Box::new(Critter),
+        }
+    }
 }
 
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurrentUnitType(pub UnitType);
+
 #[derive(Bundle, Default)]
 pub struct UnitBundle {
     pub movement: Movement,
     pub velocity: Velocity,
+    pub motion: Motion,
+    pub steering: Steering,
+    pub knockback: Knockback,
     pub current_animation: CurrentAnimation,
     pub transform: Transform,
     pub global_transform: GlobalTransform,
     pub inherited_visibility: InheritedVisibility,
     pub health: Health,
+    pub resistances: Resistances,
+    pub stats: StatModifiers,
     pub team: CurrentTeam,
     pub cleanup: Cleanup,
 }
@@ -43,27 +125,37 @@ pub trait UnitChildrenSpawnParamsFactory {
     fn create_children_spawn_params(&self) -> Vec<AnimatedChildSpawnParams>;
 }
 
+// How much an Acolyte gives and how often comes from its ManaChannel in the UnitResource
 #[derive(Component, Clone)]
 pub struct Acolyte {
-    pub give_mana_timer: Timer,
-    pub mana_amount: u8,
+    pub give_mana: Charge,
+    // Mana is whole numbers, whatever diminishing returns shave off is saved up here
+    pub mana_remainder: f32,
+    // The share of the full amount this acolyte gave on its last tick
+    pub efficiency: f32,
 }
 
-impl Default for Acolyte {
-    fn default() -> Self {
-        let mana_cooldown = 1.0;
+impl Acolyte {
+    pub fn new(channel: &ManaChannel) -> Self {
         Self {
-            give_mana_timer: Timer::from_seconds(mana_cooldown, TimerMode::Repeating),
-            mana_amount: 5,
+            give_mana: Charge::new(channel.interval),
+            mana_remainder: 0.0,
+            efficiency: 1.0,
         }
     }
 }
 
+impl Default for Acolyte {
+    fn default() -> Self {
+        Self::new(&ManaChannel::default())
+    }
+}
+
 impl UnitChildrenSpawnParamsFactory for Acolyte {
     fn create_unit_bundle(&self) -> UnitBundle {
         UnitBundle {
             movement: Movement { speed: 75.0 },
-            health: Health(50),
+            health: Health::new(50),
             transform: Transform::from_scale(Vec3::splat(0.8)),
             ..default()
         }
@@ -74,7 +166,15 @@ impl UnitChildrenSpawnParamsFactory for Acolyte {
             current_behavior: CurrentBehavior(Behavior::Idle(IdleBehavior {})),
             supported_behaviors: SupportedBehaviors(vec![
                 (Behavior::Idle(IdleBehavior {}), 5),
-                (Behavior::Flee(FleeBehavior {}), 10),
+                (
+                    Behavior::FollowFormation(FollowFormationBehavior::default()),
+                    6,
+                ),
+                (Behavior::MoveToRally(MoveToRallyBehavior::default()), 7),
+                // Under fleeing, an acolyte stands by its post but still runs when it has to
+                (Behavior::Guard(GuardBehavior::default()), 8),
+                (Behavior::Patrol(PatrolBehavior::default()), 8),
+                (Behavior::Flee(FleeBehavior::default()), 10),
                 (Behavior::Dead(DeadBehavior {}), 15),
             ]),
         }
@@ -122,7 +222,12 @@ impl UnitChildrenSpawnParamsFactory for Warrior {
     fn create_unit_bundle(&self) -> UnitBundle {
         UnitBundle {
             movement: Movement { speed: 200.0 },
-            health: Health(255),
+            motion: Motion::new(0.25, 0.2),
+            health: Health::new(255),
+            resistances: Resistances {
+                physical: 0.1,
+                ..default()
+            },
             transform: Transform::from_scale(Vec3::splat(1.8)),
             ..default()
         }
@@ -183,14 +288,32 @@ impl UnitChildrenSpawnParamsFactory for Cat {
     fn create_unit_bundle(&self) -> UnitBundle {
         UnitBundle {
             movement: Movement { speed: 300.0 },
-            health: Health(125),
+            // Off the mark in a blink, but all that speed takes a skid to shed
+            motion: Motion::new(0.05, 0.25),
+            health: Health::new(125),
             transform: Transform::from_scale(Vec3::splat(1.4)),
             ..default()
         }
     }
 
     fn create_behavior_bundle(&self) -> BehaviorBundle {
-        BehaviorBundle::default()
+        BehaviorBundle {
+            supported_behaviors: SupportedBehaviors(vec![
+                (Behavior::Wander(WanderBehavior::default()), 5),
+                (
+                    Behavior::FollowFormation(FollowFormationBehavior::default()),
+                    6,
+                ),
+                (Behavior::MoveToRally(MoveToRallyBehavior::default()), 7),
+                (Behavior::Patrol(PatrolBehavior::default()), 8),
+                (Behavior::Chase(ChaseBehavior {}), 10),
+                (Behavior::Guard(GuardBehavior::default()), 11),
+                (Behavior::Flee(FleeBehavior::routing()), 12),
+                (Behavior::Attack(AttackBehavior::default()), 15),
+                (Behavior::Dead(DeadBehavior {}), 20),
+            ]),
+            ..default()
+        }
     }
 
     fn create_children_spawn_params(&self) -> Vec<AnimatedChildSpawnParams> {
@@ -238,13 +361,148 @@ impl UnitChildrenSpawnParamsFactory for Cat {
     }
 }
 
+#[derive(Component, Clone)]
+pub struct Imp;
+impl Imp {
+    pub fn tint() -> Tint {
+        Tint(Color::rgb(1.0, 0.45, 0.35))
+    }
+}
+
+impl UnitChildrenSpawnParamsFactory for Imp {
+    fn create_unit_bundle(&self) -> UnitBundle {
+        UnitBundle {
+            movement: Movement { speed: 275.0 },
+            health: Health::new(30),
+            transform: Transform::from_scale(Vec3::splat(0.9)),
+            ..default()
+        }
+    }
+
+    fn create_behavior_bundle(&self) -> BehaviorBundle {
+        BehaviorBundle {
+            current_behavior: CurrentBehavior(Behavior::Wander(WanderBehavior::default())),
+            supported_behaviors: SupportedBehaviors(vec![
+                (Behavior::Wander(WanderBehavior::default()), 5),
+                (
+                    Behavior::FollowFormation(FollowFormationBehavior::default()),
+                    6,
+                ),
+                (Behavior::MoveToRally(MoveToRallyBehavior::default()), 7),
+                // Imps still throw themselves at anything close, guarding or not
+                (Behavior::Guard(GuardBehavior::default()), 8),
+                (Behavior::Patrol(PatrolBehavior::default()), 8),
+                (Behavior::Kamikaze(KamikazeBehavior::default()), 10),
+                (Behavior::Dead(DeadBehavior {}), 20),
+            ]),
+        }
+    }
+
+    // The imp borrows the cat sheets, tinted red by Imp::tint()
+    fn create_children_spawn_params(&self) -> Vec<AnimatedChildSpawnParams> {
+        [
+            (
+                "cat/cat_idle.png",
+                Vec2::new(96.0, 96.0),
+                (10, 1),
+                9,
+                AnimationType::Idle,
+                true,
+                false,
+            ),
+            (
+                "cat/cat_walk.png",
+                Vec2::new(96.0, 96.0),
+                (8, 1),
+                7,
+                AnimationType::Walk,
+                true,
+                false,
+            ),
+            (
+                "cat/cat_death.png",
+                Vec2::new(96.0, 96.0),
+                (18, 1),
+                17,
+                AnimationType::Death,
+                false,
+                false,
+            ),
+        ]
+        .into_iter()
+        .map(|data| data.into())
+        .collect()
+    }
+}
+
+// Keeps the rest of the army on its feet, and casts at whatever comes close when nobody needs it
+#[derive(Component, Clone)]
+pub struct DarkPriest;
+impl DarkPriest {
+    pub fn tint() -> Tint {
+        Tint(Color::rgb(0.5, 0.85, 0.6))
+    }
+}
+
+impl UnitChildrenSpawnParamsFactory for DarkPriest {
+    fn create_unit_bundle(&self) -> UnitBundle {
+        UnitBundle {
+            movement: Movement { speed: 110.0 },
+            health: Health::new(70),
+            transform: Transform::from_scale(Vec3::splat(0.9)),
+            ..default()
+        }
+    }
+
+    fn create_behavior_bundle(&self) -> BehaviorBundle {
+        BehaviorBundle {
+            current_behavior: CurrentBehavior(Behavior::Idle(IdleBehavior {})),
+            supported_behaviors: SupportedBehaviors(vec![
+                (Behavior::Idle(IdleBehavior {}), 5),
+                (
+                    Behavior::FollowFormation(FollowFormationBehavior::default()),
+                    6,
+                ),
+                (Behavior::MoveToRally(MoveToRallyBehavior::default()), 7),
+                (Behavior::Guard(GuardBehavior::default()), 8),
+                (Behavior::Patrol(PatrolBehavior::default()), 8),
+                // Only casts at what comes into range, it never goes looking for a fight
+                (Behavior::Attack(AttackBehavior::default()), 9),
+                (Behavior::Heal(HealBehavior::default()), 10),
+                (Behavior::Flee(FleeBehavior::routing()), 12),
+                (Behavior::Dead(DeadBehavior {}), 20),
+            ]),
+        }
+    }
+
+    // Uses the acolyte sheets, tinted by DarkPriest::tint(). Until it has a cast of its own it
+    // plays the idle once through.
+    fn create_children_spawn_params(&self) -> Vec<AnimatedChildSpawnParams> {
+        let mut params = Acolyte::default().create_children_spawn_params();
+        params.push(
+            (
+                "acolyte/acolyte_idle.png",
+                Vec2::new(80.0, 80.0),
+                (3, 4),
+                9,
+                AnimationType::Attack,
+                false,
+                true,
+            )
+                .into(),
+        );
+        params
+    }
+}
+
 #[derive(Component, Clone)]
 pub struct Knight;
 impl UnitChildrenSpawnParamsFactory for Knight {
     fn create_unit_bundle(&self) -> UnitBundle {
         UnitBundle {
             movement: Movement { speed: 250.0 },
-            health: Health(90),
+            motion: Motion::new(0.2, 0.15),
+            health: Health::new(90),
             transform: Transform::from_scale(Vec3::splat(1.5)),
             ..default()
         }
@@ -255,7 +513,10 @@ impl UnitChildrenSpawnParamsFactory for Knight {
             supported_behaviors: SupportedBehaviors(vec![
                 (Behavior::Wander(WanderBehavior::default()), 3),
                 (Behavior::MoveOrigo(MoveOrigoBehavior {}), 5),
+                // Only knights the level sends out on a route have anywhere to patrol
+                (Behavior::Patrol(PatrolBehavior::default()), 6),
                 (Behavior::Chase(ChaseBehavior {}), 10),
+                (Behavior::Flee(FleeBehavior::routing()), 12),
                 (Behavior::Attack(AttackBehavior::default()), 15),
                 (Behavior::Dead(DeadBehavior {}), 20),
             ]),
@@ -307,88 +568,696 @@ impl UnitChildrenSpawnParamsFactory for Knight {
         .collect()
     }
 }
+#[derive(Component, Clone)]
+pub struct Gargoyle;
+impl Gargoyle {
+    pub fn tint() -> Tint {
+        Tint(Color::rgb(0.55, 0.6, 0.75))
+    }
+}
+
+impl UnitChildrenSpawnParamsFactory for Gargoyle {
+    fn create_unit_bundle(&self) -> UnitBundle {
+        UnitBundle {
+            movement: Movement { speed: 180.0 },
+            // Glides, it drifts a little past wherever it meant to stop
+            motion: Motion::new(0.3, 0.4),
+            health: Health::new(70),
+            // Stone skin shrugs off fire and some of the physical hits
+            resistances: Resistances {
+                physical: 0.2,
+                fire: 0.5,
+                knockback: 0.3,
+                ..default()
+            },
+            // Flying units are drawn above the ground units
+            transform: Transform::from_xyz(0.0, 0.0, 1.0).with_scale(Vec3::splat(1.7)),
+            ..default()
+        }
+    }
+
+    fn create_behavior_bundle(&self) -> BehaviorBundle {
+        Knight.create_behavior_bundle()
+    }
+
+    // Until the gargoyle has its own art it uses the knight sheets, tinted by Gargoyle::tint()
+    fn create_children_spawn_params(&self) -> Vec<AnimatedChildSpawnParams> {
+        Knight.create_children_spawn_params()
+    }
+}
+
+#[derive(Component, Clone)]
+pub struct ArmoredKnight;
+impl ArmoredKnight {
+    pub fn tint() -> Tint {
+        Tint(Color::rgb(0.7, 0.75, 0.85))
+    }
+
+    pub fn armor() -> Armor {
+        Armor {
+            flat_reduction: 6,
+            hits_to_break: 5,
+            hits_taken: 0,
+        }
+    }
+}
+
+impl UnitChildrenSpawnParamsFactory for ArmoredKnight {
+    fn create_unit_bundle(&self) -> UnitBundle {
+        UnitBundle {
+            movement: Movement { speed: 190.0 },
+            // All that plate takes a while to get going and to stop
+            motion: Motion::new(0.4, 0.3),
+            health: Health::new(120),
+            // Blessed plate, sturdy against steel but it burns when touched by dark magic
+            resistances: Resistances {
+                physical: 0.25,
+                dark: -0.25,
+                knockback: 0.6,
+                ..default()
+            },
+            transform: Transform::from_scale(Vec3::splat(1.7)),
+            ..default()
+        }
+    }
+
+    fn create_behavior_bundle(&self) -> BehaviorBundle {
+        Knight.create_behavior_bundle()
+    }
+
+    // Uses the knight sheets, tinted by ArmoredKnight::tint() until the shield breaks
+    fn create_children_spawn_params(&self) -> Vec<AnimatedChildSpawnParams> {
+        Knight.create_children_spawn_params()
+    }
+}
+
+// Slips past the front line by blinking next to whichever acolyte is left on its own
+#[derive(Component, Clone)]
+pub struct Assassin;
+impl Assassin {
+    pub fn tint() -> Tint {
+        Tint(Color::rgb(0.45, 0.3, 0.6))
+    }
+}
+
+impl UnitChildrenSpawnParamsFactory for Assassin {
+    fn create_unit_bundle(&self) -> UnitBundle {
+        UnitBundle {
+            movement: Movement { speed: 230.0 },
+            health: Health::new(60),
+            transform: Transform::from_scale(Vec3::splat(1.3)),
+            ..default()
+        }
+    }
+
+    fn create_behavior_bundle(&self) -> BehaviorBundle {
+        Knight.create_behavior_bundle()
+    }
+
+    // Uses the knight sheets, tinted by Assassin::tint()
+    fn create_children_spawn_params(&self) -> Vec<AnimatedChildSpawnParams> {
+        Knight.create_children_spawn_params()
+    }
+}
+
+// Wildlife on the neutral team, it wanders around until someone charms it into fighting for them
+#[derive(Component, Clone)]
+pub struct Critter;
+impl Critter {
+    pub fn tint() -> Tint {
+        Tint(Color::rgb(0.55, 0.8, 0.45))
+    }
+
+    pub fn converted_behavior_bundle() -> BehaviorBundle {
+        BehaviorBundle {
+            current_behavior: CurrentBehavior(Behavior::Wander(WanderBehavior::default())),
+            supported_behaviors: SupportedBehaviors(vec![
+                (Behavior::Wander(WanderBehavior::default()), 5),
+                (
+                    Behavior::FollowFormation(FollowFormationBehavior::default()),
+                    6,
+                ),
+                (Behavior::MoveToRally(MoveToRallyBehavior::default()), 7),
+                (Behavior::Patrol(PatrolBehavior::default()), 8),
+                (Behavior::Chase(ChaseBehavior {}), 10),
+                (Behavior::Guard(GuardBehavior::default()), 11),
+                (Behavior::Attack(AttackBehavior::default()), 15),
+                (Behavior::Dead(DeadBehavior {}), 20),
+            ]),
+        }
+    }
+}
+
+impl UnitChildrenSpawnParamsFactory for Critter {
+    fn create_unit_bundle(&self) -> UnitBundle {
+        UnitBundle {
+            movement: Movement { speed: 120.0 },
+            health: Health::new(40),
+            transform: Transform::from_scale(Vec3::splat(0.7)),
+            ..default()
+        }
+    }
+
+    fn create_behavior_bundle(&self) -> BehaviorBundle {
+        BehaviorBundle {
+            current_behavior: CurrentBehavior(Behavior::Wander(WanderBehavior::default())),
+            supported_behaviors: SupportedBehaviors(vec![
+                (Behavior::Wander(WanderBehavior::default()), 5),
+                (Behavior::Dead(DeadBehavior {}), 20),
+            ]),
+        }
+    }
+
+    // Uses the cat sheets, tinted by Critter::tint()
+    fn create_children_spawn_params(&self) -> Vec<AnimatedChildSpawnParams> {
+        Cat.create_children_spawn_params()
+    }
+}
+
 #[derive(Resource)]
-pub struct UnitResource(HashMap<UnitType, UnitConfig>);
+pub struct UnitResource {
+    configs: HashMap<UnitType, UnitConfig>,
+    // How many kills each kind of summon has made this run, towards what they unlock
+    kills: HashMap<UnitType, u32>,
+}
 
 impl UnitResource {
     pub fn get(&self, unit_type: UnitType) -> &UnitConfig {
-        &self.0[&unit_type]
+        &self.configs[&unit_type]
+    }
+
+    pub fn is_unlocked(&self, unit_type: UnitType) -> bool {
+        self.configs
+            .get(&unit_type)
+            .is_some_and(|config| config.unlocked)
+    }
+
+    pub fn kills(&self, unit_type: UnitType) -> u32 {
+        self.kills.get(&unit_type).copied().unwrap_or(0)
+    }
+
+    pub fn record_kill(&mut self, unit_type: UnitType) {
+        *self.kills.entry(unit_type).or_insert(0) += 1;
+    }
+
+    // Unlocks whatever the run has earned by now, and hands back what was new
+    pub fn unlock_earned(&mut self, wave: u32) -> Vec<UnitType> {
+        let kills = &self.kills;
+        let mut unlocked: Vec<UnitType> = self
+            .configs
+            .iter_mut()
+            .filter(|(_, config)| !config.unlocked)
+            .filter(|(_, config)| {
+                config.unlock.is_some_and(|condition| match condition {
+                    UnlockCondition::WaveReached(needed) => wave >= needed,
+                    UnlockCondition::KillsWith(unit_type, needed) => {
+                        kills.get(&unit_type).copied().unwrap_or(0) >= needed
+                    }
+                })
+            })
+            .map(|(unit_type, config)| {
+                config.unlocked = true;
+                *unit_type
+            })
+            .collect();
+        unlocked.sort_by_key(|unit_type| UnitType::ALL.iter().position(|other| other == unit_type));
+        unlocked
+    }
+
+    // Handed out without being earned, the tutorial lends one out
+    pub fn unlock(&mut self, unit_type: UnitType) {
+        if let Some(config) = self.configs.get_mut(&unit_type) {
+            config.unlocked = true;
+        }
+    }
+
+    // A new run has to earn them all over again
+    pub fn reset_unlocks(&mut self) {
+        self.kills.clear();
+        for config in self.configs.values_mut() {
+            config.unlocked = config.unlock.is_none();
+        }
+    }
+
+    // What summoning one costs on the difficulty being played
+    pub fn cost(&self, unit_type: UnitType, difficulty: &Difficulty) -> u8 {
+        difficulty.scale_cost(self.get(unit_type).cost)
+    }
+
+    // What the summoner actually pays, after whatever makes their summons cheaper
+    pub fn summon_cost(
+        &self,
+        unit_type: UnitType,
+        difficulty: &Difficulty,
+        summoner: &StatModifiers,
+    ) -> u8 {
+        summoner
+            .apply(
+                Stat::SummonCost(unit_type),
+                self.cost(unit_type, difficulty) as f32,
+            )
+            .round()
+            .clamp(0.0, u8::MAX as f32) as u8
+    }
+
+    pub fn contains(&self, unit_type: UnitType) -> bool {
+        self.configs.contains_key(&unit_type)
+    }
+}
+
+// What a run has to do before a summon can be called on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnlockCondition {
+    WaveReached(u32),
+    KillsWith(UnitType, u32),
+}
+
+impl UnlockCondition {
+    pub fn describe(&self) -> String {
+        match self {
+            UnlockCondition::WaveReached(wave) => format!("reach wave {}", wave),
+            UnlockCondition::KillsWith(unit_type, kills) => {
+                format!("{} kills with a {}", kills, unit_type.name())
+            }
+        }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct UnitConfig {
     pub cost: u8,
+    // Only set for the units that give the player mana
+    pub mana: Option<ManaChannel>,
+    pub attack: AttackStats,
+    pub dodge_chance: f32,
+    // Locked summons can't be called on until the run meets their condition
+    pub unlocked: bool,
+    pub unlock: Option<UnlockCondition>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ManaChannel {
+    pub interval: f32,
+    pub amount: u8,
+    // Every acolyte after the first gives this much of what the one before it gave, so stacking
+    // them keeps paying off but can never outpace spending the mana on an army
+    pub stacking: f32,
+}
+
+impl Default for ManaChannel {
+    fn default() -> Self {
+        Self {
+            interval: 1.0,
+            amount: 5,
+            stacking: 0.85,
+        }
+    }
+}
+
+impl ManaChannel {
+    // The share of the full amount each of `count` acolytes gives when they split the total evenly
+    pub fn efficiency(&self, count: usize) -> f32 {
+        if count <= 1 {
+            return 1.0;
+        }
+
+        let total = if self.stacking < 1.0 {
+            (1.0 - self.stacking.powi(count as i32)) / (1.0 - self.stacking)
+        } else {
+            count as f32
+        };
+        total / count as f32
+    }
 }
 
 impl Default for UnitResource {
     fn default() -> Self {
-        Self(
-            [
-                (UnitType::Acolyte, UnitConfig { cost: 40 }),
-                (UnitType::Warrior, UnitConfig { cost: 30 }),
-                (UnitType::Cat, UnitConfig { cost: 20 }),
+        Self {
+            configs: [
+                (
+                    UnitType::Acolyte,
+                    UnitConfig {
+                        cost: 40,
+                        mana: Some(ManaChannel::default()),
+                        attack: AttackStats {
+                            damage: 4,
+                            ..default()
+                        },
+                        dodge_chance: 0.0,
+                        unlocked: true,
+                        unlock: None,
+                    },
+                ),
+                (
+                    UnitType::Warrior,
+                    UnitConfig {
+                        cost: 30,
+                        mana: None,
+                        attack: AttackStats {
+                            damage: 12,
+                            ..default()
+                        },
+                        dodge_chance: 0.05,
+                        unlocked: true,
+                        unlock: None,
+                    },
+                ),
+                // Cats hit softer but a lot more often, and are hard to pin down
+                (
+                    UnitType::Cat,
+                    UnitConfig {
+                        cost: 20,
+                        mana: None,
+                        attack: AttackStats {
+                            damage: 7,
+                            damage_variance: 3,
+                            damage_kind: DamageKind::Dark,
+                            range: 80.0,
+                            cooldown: 2.5,
+                            crit_chance: 0.15,
+                            crit_multiplier: 2.0,
+                            ..default()
+                        },
+                        dodge_chance: 0.2,
+                        unlocked: false,
+                        unlock: Some(UnlockCondition::KillsWith(UnitType::Warrior, 5)),
+                    },
+                ),
+                (
+                    UnitType::Imp,
+                    UnitConfig {
+                        cost: 15,
+                        mana: None,
+                        attack: AttackStats::default(),
+                        dodge_chance: 0.1,
+                        unlocked: false,
+                        unlock: Some(UnlockCondition::WaveReached(4)),
+                    },
+                ),
+                // A dark bolt, the one thing the army has that reaches the gargoyles
+                (
+                    UnitType::DarkPriest,
+                    UnitConfig {
+                        cost: 35,
+                        mana: None,
+                        attack: AttackStats {
+                            damage: 6,
+                            damage_kind: DamageKind::Dark,
+                            range: 220.0,
+                            cooldown: 2.0,
+                            projectile: ProjectileType::Bolt,
+                            ..default()
+                        },
+                        dodge_chance: 0.05,
+                        unlocked: false,
+                        unlock: Some(UnlockCondition::WaveReached(6)),
+                    },
+                ),
+                // The enemies aren't summoned, so there's nothing for them to cost
+                (
+                    UnitType::Knight,
+                    UnitConfig {
+                        cost: 0,
+                        mana: None,
+                        attack: AttackStats::default(),
+                        dodge_chance: 0.05,
+                        unlocked: true,
+                        unlock: None,
+                    },
+                ),
+                // Gargoyles keep their distance and spit stone at whatever is below them
+                (
+                    UnitType::Gargoyle,
+                    UnitConfig {
+                        cost: 0,
+                        mana: None,
+                        attack: AttackStats {
+                            damage: 8,
+                            damage_variance: 2,
+                            range: 260.0,
+                            cooldown: 3.0,
+                            projectile: ProjectileType::Bolt,
+                            ..default()
+                        },
+                        dodge_chance: 0.1,
+                        unlocked: true,
+                        unlock: None,
+                    },
+                ),
+                (
+                    UnitType::ArmoredKnight,
+                    UnitConfig {
+                        cost: 0,
+                        mana: None,
+                        attack: AttackStats {
+                            damage: 16,
+                            cooldown: 5.0,
+                            ..default()
+                        },
+                        dodge_chance: 0.0,
+                        unlocked: true,
+                        unlock: None,
+                    },
+                ),
+                // Fragile, but whatever it lands next to doesn't last long
+                (
+                    UnitType::Assassin,
+                    UnitConfig {
+                        cost: 0,
+                        mana: None,
+                        attack: AttackStats {
+                            damage: 14,
+                            damage_variance: 4,
+                            cooldown: 1.2,
+                            crit_chance: 0.25,
+                            crit_multiplier: 2.0,
+                            ..default()
+                        },
+                        dodge_chance: 0.15,
+                        unlocked: true,
+                        unlock: None,
+                    },
+                ),
+                (
+                    UnitType::Critter,
+                    UnitConfig {
+                        cost: 0,
+                        mana: None,
+                        attack: AttackStats {
+                            damage: 6,
+                            ..default()
+                        },
+                        dodge_chance: 0.1,
+                        unlocked: true,
+                        unlock: None,
+                    },
+                ),
             ]
-            .iter()
-            .cloned()
+            .into_iter()
             .collect(),
-        )
+            kills: HashMap::new(),
+        }
     }
 }
 
-pub fn spawn_unit<'a>(
+// Every unit has these whatever its type, so a whole wave of them is one bundle type and can be
+// spawned in one batch
+#[derive(Bundle)]
+struct UnitCoreBundle {
+    unit: UnitBundle,
+    morale: Morale,
+    behavior: BehaviorBundle,
+    unit_type: CurrentUnitType,
+}
+
+fn unit_core_bundle(unit_type: UnitType, team: Team, spawn_position: Vec2) -> UnitCoreBundle {
+    let factory = unit_type.factory();
+    let mut unit = factory.create_unit_bundle();
+    unit.team = CurrentTeam(team);
+    unit.transform.translation = spawn_position.extend(unit.transform.translation.z);
+
+    UnitCoreBundle {
+        unit,
+        morale: Morale::default(),
+        behavior: factory.create_behavior_bundle(),
+        unit_type: CurrentUnitType(unit_type),
+    }
+}
+
+pub fn spawn_unit_of_type<'a>(
     commands: &'a mut Commands,
     asset_server: &'a Res<AssetServer>,
-    texture_atlas_layouts: &'a mut ResMut<Assets<TextureAtlasLayout>>,
-    unit_component: impl UnitChildrenSpawnParamsFactory + Clone,
+    texture_atlas_layouts: &'a mut AtlasLayouts,
+    unit_type: UnitType,
     team: Team,
     spawn_position: Vec2,
 ) -> EntityCommands<'a> {
-    let mut unit_bundle = unit_component.create_unit_bundle();
-    unit_bundle.team = CurrentTeam(team);
-    unit_bundle.transform.translation = Vec3::new(spawn_position.x, spawn_position.y, 0.0);
-
-    let behavior_bundle = unit_component.create_behavior_bundle();
-    let mut entity = commands.spawn((unit_bundle, behavior_bundle.clone()));
-
-    behavior_bundle
-        .supported_behaviors
-        .0
-        .iter()
-        .for_each(|behavior| {
-            match behavior {
-                (Behavior::Idle(behavior), _) => {
-                    entity.insert(*behavior);
-                }
-                (Behavior::MoveOrigo(behavior), _) => {
-                    entity.insert(*behavior);
-                }
-                (Behavior::Wander(behavior), _) => {
-                    entity.insert(behavior.clone());
-                }
-                (Behavior::Chase(behavior), _) => {
-                    entity.insert(*behavior);
-                }
-                (Behavior::Flee(behavior), _) => {
-                    entity.insert(*behavior);
-                }
-                (Behavior::Attack(behavior), _) => {
-                    entity.insert(behavior.clone());
+    let sprites = resolve_animated_children(
+        asset_server,
+        texture_atlas_layouts,
+        unit_type.children_spawn_params(),
+    );
+    let mut entity =
+        spawn_unit_of_type_without_sprites(commands.spawn_empty(), unit_type, team, spawn_position);
+    entity.with_children(|parent| {
+        for sprite in sprites {
+            parent.spawn(sprite);
+        }
+    });
+    entity
+}
+
+// A whole wave's worth in one go. The sprites are only looked up once per unit type, the bundles
+// every unit has are built up front and go in with one batch, and so do the sprites, instead of
+// a command each, which is what used to make a big wave hitch the frame it came in on. What only
+// some unit types have is put on straight after, in the same command. Units that died earlier are
+// taken out of the pool and go in with the same batch, those only get their sprites reset.
+pub fn spawn_units_of_type(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    texture_atlas_layouts: &mut AtlasLayouts,
+    pools: &mut EntityPools,
+    units: impl IntoIterator<Item = (UnitType, Team, Vec2)>,
+) -> Vec<Entity> {
+    let mut resolved: HashMap<UnitType, Vec<AnimationBundle>> = HashMap::new();
+    let mut cores = Vec::new();
+    let mut extras = Vec::new();
+    let mut sprites = Vec::new();
+    let mut parents = Vec::new();
+    let mut recycled = Vec::new();
+    let entities: Vec<Entity> = units
+        .into_iter()
+        .map(|(unit_type, team, spawn_position)| {
+            let unit_sprites = resolved.entry(unit_type).or_insert_with(|| {
+                resolve_animated_children(
+                    asset_server,
+                    texture_atlas_layouts,
+                    unit_type.children_spawn_params(),
+                )
+            });
+            // New units only have their id reserved here, they're spawned with the batch
+            let entity = match pools.take_unit(unit_type) {
+                Some(pooled) => {
+                    recycled.push((pooled, unit_sprites.clone()));
+                    pooled
                 }
-                (Behavior::Dead(behavior), _) => {
-                    entity.insert(behavior.clone());
+                None => {
+                    let entity = commands.spawn_empty().id();
+                    sprites.extend(unit_sprites.iter().cloned());
+                    parents.push((entity, unit_sprites.len()));
+                    entity
                 }
             };
-        });
 
-    entity.with_children(|parent| {
-        spawn_animated_children(
-            asset_server,
-            texture_atlas_layouts,
-            parent,
-            unit_component.create_children_spawn_params(),
-        );
+            let core = unit_core_bundle(unit_type, team, spawn_position);
+            extras.push((entity, unit_type, team, core.behavior.clone()));
+            cores.push((entity, core));
+            entity
+        })
+        .collect();
+
+    // Commands::spawn_batch doesn't hand back the entities, and the callers and the sprites need
+    // them straight away, so the ids are reserved above and everything goes in here on the world
+    commands.add(move |world: &mut World| {
+        for (unit, _) in recycled.iter() {
+            world.entity_mut(*unit).remove::<Pooled>();
+        }
+        if let Err(missing) = world.insert_or_spawn_batch(cores) {
+            warn!("Could not spawn {} units of the batch", missing.len());
+        }
+        for (entity, unit_type, team, behavior) in extras {
+            if let Some(mut entity) = world.get_entity_mut(entity) {
+                insert_unit_extras(&mut entity, unit_type, team, &behavior);
+            }
+        }
+
+        for (unit, unit_sprites) in recycled {
+            let children = world
+                .get::<Children>(unit)
+                .map_or_else(Vec::new, |children| children.to_vec());
+            for (child, sprite) in children.into_iter().zip(unit_sprites) {
+                world.entity_mut(child).insert(sprite);
+            }
+        }
+
+        let sprites: Vec<Entity> = world.spawn_batch(sprites).collect();
+        let mut sprites = sprites.as_slice();
+        for (parent, count) in parents {
+            let (children, rest) = sprites.split_at(count);
+            sprites = rest;
+            if let Some(mut parent) = world.get_entity_mut(parent) {
+                parent.push_children(children);
+            }
+        }
     });
 
-    entity
+    entities
+}
+
+fn spawn_unit_of_type_without_sprites<'a>(
+    mut unit: EntityCommands<'a>,
+    unit_type: UnitType,
+    team: Team,
+    spawn_position: Vec2,
+) -> EntityCommands<'a> {
+    let core = unit_core_bundle(unit_type, team, spawn_position);
+    let behavior = core.behavior.clone();
+    unit.insert(core);
+    insert_unit_extras(&mut unit, unit_type, team, &behavior);
+    unit
+}
+
+// What's left once the core is in, the state of every behavior the unit supports and whatever
+// only its type has
+fn insert_unit_extras(
+    entity: &mut impl InsertBundle,
+    unit_type: UnitType,
+    team: Team,
+    behavior: &BehaviorBundle,
+) {
+    behavior.insert_behavior_components(entity);
+    match unit_type {
+        UnitType::Acolyte => entity.insert_bundle(Acolyte::default()),
+        // The warrior carries the banner for the summoned army
+        // and makes sure the enemies are looking at it rather than the rest
+        UnitType::Warrior => {
+            entity.insert_bundle((Warrior, MoraleBanner::default(), ThreatMultiplier(2.0)))
+        }
+        UnitType::Cat => entity.insert_bundle(Cat),
+        UnitType::Imp => entity.insert_bundle((Imp, Imp::tint())),
+        // Its bolts fly high enough to hit flying units
+        UnitType::DarkPriest => {
+            entity.insert_bundle((DarkPriest, DarkPriest::tint(), CanTargetAir))
+        }
+        UnitType::Knight => entity.insert_bundle((Knight, Aggro::default())),
+        UnitType::Gargoyle => {
+            entity.insert_bundle((Gargoyle, Gargoyle::tint(), Flying, Aggro::default()))
+        }
+        UnitType::ArmoredKnight => entity.insert_bundle((
+            ArmoredKnight,
+            ArmoredKnight::tint(),
+            ArmoredKnight::armor(),
+            MoraleBanner::default(),
+            Aggro::default(),
+        )),
+        UnitType::Assassin => entity.insert_bundle((
+            Assassin,
+            Assassin::tint(),
+            Blink::default(),
+            Aggro::default(),
+        )),
+        UnitType::Critter => {
+            entity.insert_bundle((Critter, Critter::tint()));
+            if team == Team::Neutral {
+                entity.insert_bundle(Convertible(Critter::converted_behavior_bundle()));
+            } else {
+                // Restored from a save after being charmed
+                Critter::converted_behavior_bundle().insert_into(entity);
+            }
+        }
+    }
+
+    if team == ROGUE {
+        entity.insert_bundle(Tint(ROGUE_COLOR));
+    }
 }
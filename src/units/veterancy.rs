@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+
+use crate::events::Damaged;
+
+use super::stat_modifiers::{Modifier, ModifierSource, Stat, StatModifiers};
+use super::team::{CurrentTeam, Team};
+
+const KILLS_PER_RANK: u32 = 3;
+const MAX_RANK: u32 = 3;
+// Per rank, on top of whatever else the unit has going for it
+const RANK_DAMAGE_BONUS: f32 = 0.1;
+const RANK_HEALTH_BONUS: f32 = 0.1;
+
+// Summons that keep surviving fights get better at them
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Veterancy {
+    pub kills: u32,
+}
+
+impl Veterancy {
+    pub fn rank(&self) -> u32 {
+        (self.kills / KILLS_PER_RANK).min(MAX_RANK)
+    }
+
+    pub fn is_veteran(&self) -> bool {
+        self.rank() > 0
+    }
+
+    fn modifiers(&self) -> [Modifier; 2] {
+        let rank = self.rank() as f32;
+        [
+            Modifier::multiply(Stat::Damage, 1.0 + RANK_DAMAGE_BONUS * rank),
+            Modifier::multiply(Stat::MaxHealth, 1.0 + RANK_HEALTH_BONUS * rank),
+        ]
+    }
+}
+
+type UntrackedFilter = (With<StatModifiers>, Without<Veterancy>);
+
+pub fn track_veterancy(
+    mut commands: Commands,
+    query: Query<(Entity, &CurrentTeam), UntrackedFilter>,
+) {
+    for (entity, team) in query.iter() {
+        if team.0 == Team::Evil {
+            commands.entity(entity).insert(Veterancy::default());
+        }
+    }
+}
+
+pub fn record_kills(mut damaged_reader: EventReader<Damaged>, mut query: Query<&mut Veterancy>) {
+    for damaged in damaged_reader.read() {
+        if !damaged.killed {
+            continue;
+        }
+        let Some(mut veterancy) = damaged.source.and_then(|source| query.get_mut(source).ok())
+        else {
+            continue;
+        };
+
+        veterancy.kills += 1;
+    }
+}
+
+pub fn apply_veterancy_modifiers(
+    mut query: Query<(&Veterancy, &mut StatModifiers), Changed<Veterancy>>,
+) {
+    for (veterancy, mut modifiers) in query.iter_mut() {
+        if veterancy.is_veteran() {
+            modifiers.set(ModifierSource::Veterancy, &veterancy.modifiers());
+        } else if modifiers.has(ModifierSource::Veterancy) {
+            modifiers.remove(ModifierSource::Veterancy);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::animation::AtlasLayouts;
+use crate::events::GameEvent;
+use crate::rng::GameRng;
+use crate::units::team::{CurrentTeam, Team};
+use crate::units::unit_types::{spawn_unit_of_type, Critter, UnitType};
+
+const WILDLIFE_AT_START: usize = 4;
+const MAX_WILDLIFE: usize = 8;
+const WILDLIFE_SPAWN_INTERVAL: f32 = 12.0;
+// Keeps critters from spawning on top of the player at the start of a run
+const WILDLIFE_MIN_DISTANCE_TO_ORIGO: f32 = 150.0;
+
+#[derive(Resource)]
+pub struct Wildlife {
+    pub spawn_timer: Timer,
+}
+
+impl Default for Wildlife {
+    fn default() -> Self {
+        Self {
+            spawn_timer: Timer::from_seconds(WILDLIFE_SPAWN_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+fn random_wildlife_position(rng: &mut GameRng, play_area: Vec2) -> Vec2 {
+    // A window smaller than the keep out circle still gets its critters, just right outside of it
+    let max_distance = (play_area.min_element() * 0.5).max(WILDLIFE_MIN_DISTANCE_TO_ORIGO);
+    let angle = rng.0.gen_range(0.0..TAU);
+    let distance = rng
+        .0
+        .gen_range(WILDLIFE_MIN_DISTANCE_TO_ORIGO..=max_distance);
+    Vec2::from_angle(angle) * distance
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_wildlife(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: AtlasLayouts,
+    time: Res<Time>,
+    mut event_reader: EventReader<GameEvent>,
+    mut wildlife: ResMut<Wildlife>,
+    mut rng: ResMut<GameRng>,
+    window_query: Query<&Window>,
+    critter_query: Query<&CurrentTeam, With<Critter>>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let play_area = Vec2::new(window.width(), window.height());
+
+    let mut to_spawn = 0;
+    for event in event_reader.read() {
+        if let GameEvent::StartGame = event {
+            wildlife.spawn_timer.reset();
+            to_spawn = WILDLIFE_AT_START;
+        }
+    }
+
+    let neutral_critters = critter_query
+        .iter()
+        .filter(|team| team.0 == Team::Neutral)
+        .count();
+    if wildlife.spawn_timer.tick(time.delta()).just_finished() && neutral_critters < MAX_WILDLIFE {
+        to_spawn = to_spawn.max(1);
+    }
+
+    for _ in 0..to_spawn {
+        spawn_unit_of_type(
+            &mut commands,
+            &asset_server,
+            &mut texture_atlas_layouts,
+            UnitType::Critter,
+            Team::Neutral,
+            random_wildlife_position(&mut rng, play_area),
+        );
+    }
+}
@@ -0,0 +1,28 @@
+use bevy::prelude::*;
+
+use crate::animation::CurrentAnimation;
+use crate::units::flying::Flying;
+
+// How far from the middle of the map a unit can be and still sort against the rest
+const Y_SORT_RANGE: f32 = 4096.0;
+// The share of a layer the sorting spreads units over, the rest is left for what's drawn on top
+const Y_SORT_DEPTH: f32 = 0.9;
+const GROUND_LAYER: f32 = 0.0;
+// Flying units are drawn above every ground unit, and sort among themselves
+const AIR_LAYER: f32 = 1.0;
+
+type MovedFilter = (With<CurrentAnimation>, Changed<Transform>);
+
+// Units lower on the screen are in front, so a clump of melees overlaps the way it's standing.
+// Stays under the projectiles and pickups at 2.
+pub fn y_sort_units(mut query: Query<(&mut Transform, Has<Flying>), MovedFilter>) {
+    for (mut transform, flying) in query.iter_mut() {
+        let layer = if flying { AIR_LAYER } else { GROUND_LAYER };
+        let depth =
+            ((Y_SORT_RANGE - transform.translation.y) / (2.0 * Y_SORT_RANGE)).clamp(0.0, 1.0);
+        let z = layer + depth * Y_SORT_DEPTH;
+        if transform.translation.z != z {
+            transform.translation.z = z;
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+// Small building blocks for the waits and limits gameplay keeps needing. They only move when
+// ticked, and are ticked with the virtual clock, so pausing the game pauses them too. Each can
+// also be paused on its own, and they're plain data so a save can write them out.
+
+// Ready once it runs out, and waits out the whole duration again every time it's started
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Cooldown {
+    duration: f32,
+    remaining: f32,
+    paused: bool,
+}
+
+impl Cooldown {
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            remaining: 0.0,
+            paused: false,
+        }
+    }
+
+    // For when the first use should have to wait too
+    pub fn started(duration: f32) -> Self {
+        Self {
+            remaining: duration,
+            ..Self::new(duration)
+        }
+    }
+
+    pub fn tick(&mut self, delta: Duration) -> &mut Self {
+        if !self.paused {
+            self.remaining = (self.remaining - delta.as_secs_f32()).max(0.0);
+        }
+        self
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.remaining <= 0.0
+    }
+
+    pub fn start(&mut self) {
+        self.remaining = self.duration;
+    }
+
+    // This one time only, like a cooldown with some variance rolled on top
+    pub fn start_for(&mut self, seconds: f32) {
+        self.remaining = seconds;
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    pub fn set_duration(&mut self, duration: f32) {
+        self.duration = duration;
+    }
+
+    pub fn remaining(&self) -> f32 {
+        self.remaining
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn unpause(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+}
+
+// Builds up over an interval, goes off and starts building up again, like a mana tick or the
+// time between waves
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Charge {
+    interval: f32,
+    elapsed: f32,
+    // How many times it went off on the last tick, more than once if the tick was a long one
+    charged: u32,
+    paused: bool,
+}
+
+impl Charge {
+    pub fn new(interval: f32) -> Self {
+        Self {
+            interval,
+            elapsed: 0.0,
+            charged: 0,
+            paused: false,
+        }
+    }
+
+    pub fn tick(&mut self, delta: Duration) -> &mut Self {
+        self.charged = 0;
+        if self.paused || self.interval <= 0.0 {
+            return self;
+        }
+
+        self.elapsed += delta.as_secs_f32();
+        while self.elapsed >= self.interval {
+            self.elapsed -= self.interval;
+            self.charged += 1;
+        }
+        self
+    }
+
+    pub fn just_charged(&self) -> bool {
+        self.charged > 0
+    }
+
+    pub fn times_charged(&self) -> u32 {
+        self.charged
+    }
+
+    // How far along it is towards going off next
+    pub fn fraction(&self) -> f32 {
+        if self.interval <= 0.0 {
+            return 1.0;
+        }
+        (self.elapsed / self.interval).clamp(0.0, 1.0)
+    }
+
+    pub fn interval(&self) -> f32 {
+        self.interval
+    }
+
+    // Keeps what's built up so far, so tuning the interval doesn't restart it
+    pub fn set_interval(&mut self, interval: f32) {
+        self.interval = interval;
+    }
+
+    pub fn set_elapsed(&mut self, elapsed: f32) {
+        self.elapsed = elapsed.max(0.0);
+    }
+
+    pub fn reset(&mut self) {
+        self.elapsed = 0.0;
+        self.charged = 0;
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn unpause(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+}
+
+// A row of levels a value moves past one at a time, each one going off once. The levels are in
+// the order they're reached, so a falling value like health lists them from high to low.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Threshold {
+    levels: Vec<f32>,
+    falling: bool,
+    crossed: usize,
+}
+
+impl Threshold {
+    pub fn rising(levels: &[f32]) -> Self {
+        Self {
+            levels: levels.to_vec(),
+            falling: false,
+            crossed: 0,
+        }
+    }
+
+    pub fn falling(levels: &[f32]) -> Self {
+        Self {
+            falling: true,
+            ..Self::rising(levels)
+        }
+    }
+
+    fn is_past(&self, level: f32, value: f32) -> bool {
+        if self.falling {
+            value <= level
+        } else {
+            value >= level
+        }
+    }
+
+    // Moves past the next level if the value has got there, and hands back that level
+    pub fn cross(&mut self, value: f32) -> Option<f32> {
+        let level = *self.levels.get(self.crossed)?;
+        if !self.is_past(level, value) {
+            return None;
+        }
+
+        self.crossed += 1;
+        Some(level)
+    }
+
+    // Levels the value has gone back over are armed again, to go off the next time it gets there
+    pub fn rearm(&mut self, value: f32) {
+        self.crossed = self.crossed.min(self.reached(value));
+    }
+
+    // How many of the levels the value is past right now, crossed or not
+    pub fn reached(&self, value: f32) -> usize {
+        self.levels
+            .iter()
+            .filter(|level| self.is_past(**level, value))
+            .count()
+    }
+
+    pub fn crossed(&self) -> usize {
+        self.crossed
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.crossed >= self.levels.len()
+    }
+
+    pub fn levels(&self) -> &[f32] {
+        &self.levels
+    }
+}
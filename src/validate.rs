@@ -0,0 +1,406 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+
+use crate::animation::AnimatedChildSpawnParams;
+use crate::aseprite::{sidecar_path, AsepriteSheet};
+use crate::gamestate::create_player_children_spawn_params;
+use crate::levels::definition::{LevelDefinition, LEVEL_PATHS};
+use crate::levels::triggers::TriggerAction;
+use crate::map::tilemap::{Tile, TileMap};
+use crate::narrative::script::{Cue, Story, STORY_PATH};
+use crate::player::relics::Relic;
+use crate::player::summoning::SUMMON_BINDS;
+use crate::units::unit_types::{UnitResource, UnitType, UnlockCondition};
+
+pub const VALIDATE_ASSETS_FLAG: &str = "--validate-assets";
+
+const FONT_PATH: &str = "fonts/JetBrainsMonoNerdFont-Regular.ttf";
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+// Goes through every data file the game reads without starting it up, so broken references
+// are caught before they ship instead of when someone happens to play that level. Returns the
+// exit code, nonzero when anything is wrong.
+pub fn validate_assets() -> i32 {
+    let mut report = Report::new(asset_root());
+
+    report.check_file(FONT_PATH);
+    report.check_levels();
+    report.check_units();
+    report.check_relics();
+    report.check_story();
+
+    report.print();
+    if report.problems.is_empty() {
+        0
+    } else {
+        1
+    }
+}
+
+// Same place bevy looks for the assets folder
+pub fn asset_root() -> PathBuf {
+    std::env::var_os("BEVY_ASSET_ROOT")
+        .or_else(|| std::env::var_os("CARGO_MANIFEST_DIR"))
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::current_exe()
+                .ok()
+                .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        })
+        .unwrap_or_default()
+        .join("assets")
+}
+
+struct Report {
+    root: PathBuf,
+    checked: usize,
+    problems: Vec<String>,
+}
+
+impl Report {
+    fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            checked: 0,
+            problems: Vec::new(),
+        }
+    }
+
+    fn problem(&mut self, source: &str, message: impl Into<String>) {
+        self.problems
+            .push(format!("{}: {}", source, message.into()));
+    }
+
+    fn read(&mut self, path: &str) -> Option<Vec<u8>> {
+        self.checked += 1;
+        match fs::read(self.root.join(path)) {
+            Ok(bytes) => Some(bytes),
+            Err(error) => {
+                self.problem(path, format!("could not be read ({})", error));
+                None
+            }
+        }
+    }
+
+    fn check_file(&mut self, path: &str) {
+        self.read(path);
+    }
+
+    fn check_levels(&mut self) {
+        for path in LEVEL_PATHS {
+            let Some(bytes) = self.read(path) else {
+                continue;
+            };
+            let level = match ron::de::from_bytes::<LevelDefinition>(&bytes) {
+                Ok(level) => level,
+                Err(error) => {
+                    self.problem(path, format!("could not be parsed ({})", error));
+                    continue;
+                }
+            };
+
+            let waves = &level.waves;
+            if waves.interval <= 0.0 {
+                self.problem(path, "the wave interval has to be above zero");
+            }
+            if waves.base_size == 0 && waves.size_growth == 0 {
+                self.problem(path, "waves never have any enemies in them");
+            }
+            for problem in level.grading.problems() {
+                self.problem(path, problem);
+            }
+            for problem in level.summons.problems() {
+                self.problem(path, problem);
+            }
+
+            let Some(map_bytes) = self.read(&level.map) else {
+                self.problem(path, format!("refers to the missing map {}", level.map));
+                continue;
+            };
+            let map = match std::str::from_utf8(&map_bytes)
+                .map_err(|error| error.to_string())
+                .and_then(|text| TileMap::parse(text).map_err(|error| error.to_string()))
+            {
+                Ok(map) => map,
+                Err(error) => {
+                    self.problem(&level.map, error);
+                    continue;
+                }
+            };
+
+            let opens_gates = level.triggers.iter().any(|trigger| {
+                trigger
+                    .then
+                    .iter()
+                    .any(|action| matches!(action, TriggerAction::OpenGates))
+            });
+            if opens_gates && !map.tiles.contains(&Tile::Gate) {
+                self.problem(path, format!("opens gates but {} has none", level.map));
+            }
+
+            for point in level.spawn_points.iter() {
+                if !map.is_walkable(Vec2::from(*point), false) {
+                    self.problem(
+                        path,
+                        format!("spawn point {:?} is inside a wall of {}", point, level.map),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_units(&mut self) {
+        let unit_configs = UnitResource::default();
+        for (key, unit_type) in SUMMON_BINDS {
+            if !unit_configs.contains(unit_type) {
+                self.problem(
+                    "units",
+                    format!("{:?} summons a {} without a config", key, unit_type.name()),
+                );
+                continue;
+            }
+
+            // Kills can only be made with something that can be summoned before it
+            let config = unit_configs.get(unit_type);
+            if let Some(UnlockCondition::KillsWith(killer, _)) = config.unlock {
+                let summonable = SUMMON_BINDS.iter().any(|(_, other)| *other == killer);
+                if killer == unit_type || !summonable {
+                    self.problem(
+                        "units",
+                        format!(
+                            "{} unlocks with kills by {}, which can't be summoned first",
+                            unit_type.name(),
+                            killer.name()
+                        ),
+                    );
+                }
+            }
+            if config.unlocked != config.unlock.is_none() {
+                self.problem(
+                    "units",
+                    format!(
+                        "{} should start locked exactly when it has an unlock condition",
+                        unit_type.name()
+                    ),
+                );
+            }
+        }
+
+        let mut sheets = vec![("Player", create_player_children_spawn_params())];
+        sheets.extend(
+            UnitType::ALL
+                .iter()
+                .map(|unit_type| (unit_type.name(), unit_type.children_spawn_params())),
+        );
+
+        // Units share sheets, no need to look at the same file twice
+        let mut seen = HashSet::new();
+        for (unit, params) in sheets {
+            if params.is_empty() {
+                self.problem(unit, "has no animations");
+            }
+            for params_with_next in params.iter().filter(|params| params.on_finish.is_some()) {
+                let next = params_with_next.on_finish.clone().unwrap_or_default();
+                if !params.iter().any(|other| other.animation_type == next) {
+                    self.problem(
+                        unit,
+                        format!(
+                            "goes from {:?} to {:?} but has no {:?} animation",
+                            params_with_next.animation_type, next, next
+                        ),
+                    );
+                }
+            }
+            // Facings without a sheet of their own fall back to the flipped one
+            for directional in params.iter().filter(|params| params.direction.is_some()) {
+                let fallback = params.iter().any(|other| {
+                    other.animation_type == directional.animation_type && other.direction.is_none()
+                });
+                if !fallback {
+                    self.problem(
+                        unit,
+                        format!(
+                            "has a {:?} {:?} sheet but nothing to fall back to for other facings",
+                            directional.direction.unwrap_or_default(),
+                            directional.animation_type
+                        ),
+                    );
+                }
+            }
+            for mut params in params {
+                self.import_export(unit, &mut params);
+                if seen.insert(params.texture_path.clone()) {
+                    self.check_sprite_sheet(unit, &params);
+                }
+            }
+        }
+    }
+
+    // The same as spawning does, a sheet with an Aseprite export next to it is cut up the way the
+    // export says instead of what the unit has written down
+    fn import_export(&mut self, unit: &str, params: &mut AnimatedChildSpawnParams) {
+        let export = sidecar_path(&params.texture_path);
+        if !self.root.join(&export).exists() {
+            return;
+        }
+        let Some(bytes) = self.read(&export) else {
+            return;
+        };
+
+        match AsepriteSheet::parse(&bytes, &export) {
+            Ok(sheet) => {
+                if sheet.image != params.texture_path {
+                    self.problem(
+                        &export,
+                        format!("was exported from {} instead", sheet.image),
+                    );
+                }
+                if !sheet.apply_to(params) {
+                    self.problem(
+                        &export,
+                        format!("has no {:?} tag for {}", params.animation_type, unit),
+                    );
+                }
+            }
+            Err(error) => self.problem(&export, format!("could not be parsed ({})", error)),
+        }
+    }
+
+    fn check_sprite_sheet(&mut self, unit: &str, params: &AnimatedChildSpawnParams) {
+        let path = params.texture_path.as_str();
+        let (columns, rows) = params.grid;
+        if params.last_atlas_index >= columns * rows {
+            self.problem(
+                path,
+                format!(
+                    "{} animates up to frame {} but the grid only has {} frames",
+                    unit,
+                    params.last_atlas_index + 1,
+                    columns * rows
+                ),
+            );
+        }
+
+        if params.fps <= 0.0 {
+            self.problem(path, format!("{} plays it at {} fps", unit, params.fps));
+        }
+
+        let Some(bytes) = self.read(path) else {
+            return;
+        };
+        let Some(size) = png_size(&bytes) else {
+            self.problem(path, "is not a png");
+            return;
+        };
+
+        let needed = params.tile_size * Vec2::new(columns as f32, rows as f32);
+        if needed.x > size.x as f32 || needed.y > size.y as f32 {
+            self.problem(
+                path,
+                format!(
+                    "{} expects a {}x{} grid of {}x{} frames, but the sheet is only {}x{}",
+                    unit, columns, rows, params.tile_size.x, params.tile_size.y, size.x, size.y
+                ),
+            );
+        }
+    }
+
+    fn check_relics(&mut self) {
+        let mut names = HashSet::new();
+        for relic in Relic::ALL {
+            let definition = relic.definition();
+            let source = format!("{:?}", relic);
+            if definition.name.is_empty() || definition.description.is_empty() {
+                self.problem(&source, "is missing its name or description");
+            }
+            if !names.insert(definition.name) {
+                self.problem(&source, format!("shares its name {}", definition.name));
+            }
+            self.checked += 1;
+        }
+    }
+
+    fn check_story(&mut self) {
+        let Some(bytes) = self.read(STORY_PATH) else {
+            return;
+        };
+        let story = match ron::de::from_bytes::<Story>(&bytes) {
+            Ok(story) => story,
+            Err(error) => {
+                self.problem(STORY_PATH, format!("could not be parsed ({})", error));
+                return;
+            }
+        };
+
+        for cue in [Cue::RunStart, Cue::BossWave, Cue::Defeat, Cue::Victory] {
+            if story.scene(cue).is_none() {
+                self.problem(STORY_PATH, format!("has nothing to say for {:?}", cue));
+            }
+        }
+
+        // Portraits are shared between lines, no need to look at the same file twice
+        let mut seen = HashSet::new();
+        let portraits = story
+            .scenes
+            .iter()
+            .flat_map(|scene| scene.lines.iter())
+            .filter_map(|line| line.portrait.clone());
+        for portrait in portraits {
+            if !seen.insert((portrait.image.clone(), portrait.frame_size)) {
+                continue;
+            }
+            let Some(bytes) = self.read(&portrait.image) else {
+                continue;
+            };
+            let (width, height) = portrait.frame_size;
+            match png_size(&bytes) {
+                Some(size) if size.x >= width && size.y >= height => {}
+                Some(size) => self.problem(
+                    &portrait.image,
+                    format!(
+                        "is {}x{}, too small for a {}x{} portrait",
+                        size.x, size.y, width, height
+                    ),
+                ),
+                None => self.problem(&portrait.image, "is not a png"),
+            }
+        }
+    }
+
+    fn print(&self) {
+        println!(
+            "Validated {} data files in {}",
+            self.checked,
+            self.root.display()
+        );
+        if self.problems.is_empty() {
+            println!("Everything checks out");
+            return;
+        }
+
+        eprintln!("Found {} problems:", self.problems.len());
+        for problem in self.problems.iter() {
+            eprintln!("  {}", problem);
+        }
+    }
+}
+
+// Width and height straight from the IHDR chunk, which always comes first
+fn png_size(bytes: &[u8]) -> Option<UVec2> {
+    if bytes.len() < 24 || bytes[..8] != PNG_SIGNATURE || &bytes[12..16] != b"IHDR" {
+        return None;
+    }
+
+    let read_u32 = |offset: usize| {
+        u32::from_be_bytes([
+            bytes[offset],
+            bytes[offset + 1],
+            bytes[offset + 2],
+            bytes[offset + 3],
+        ])
+    };
+    Some(UVec2::new(read_u32(16), read_u32(20)))
+}
@@ -1,20 +1,121 @@
 use bevy::prelude::*;
 
-use crate::{movement::Movement, units::health::Health};
+use crate::{
+    ai::sleep::Asleep,
+    movement::{Movement, Steering},
+    units::{
+        health::Health,
+        stat_modifiers::{Stat, StatModifiers},
+    },
+};
+
+// How fast a shove dies off, most of it is gone after a tenth of a second
+const KNOCKBACK_DECAY: f32 = 10.0;
+const MIN_KNOCKBACK: f32 = 1.0;
 
 #[derive(Component, Default)]
 pub struct Velocity(pub Vec2);
 
-pub fn translate(
-    time: Res<Time>,
-    mut query: Query<(&Velocity, &Movement, &Health, &mut Transform)>,
-) {
-    for (velocity, movement, health, mut transform) in query.iter_mut() {
+// How the unit gets to the velocity it wants instead of jumping straight to it, so turning
+// around and stopping take a moment. Without one a unit snaps to whatever it was told.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Motion {
+    // From standing still up to full speed
+    pub acceleration_seconds: f32,
+    // From full speed down to a stop, which is also what turning around starts with
+    pub stopping_seconds: f32,
+    // Of the unit's speed, however far past it the velocity asks for
+    pub max_speed: f32,
+    // Pixels per second it's actually moving with, speed modifiers and all
+    pub current: Vec2,
+}
+
+impl Motion {
+    pub fn new(acceleration_seconds: f32, stopping_seconds: f32) -> Self {
+        Self {
+            acceleration_seconds,
+            stopping_seconds,
+            max_speed: 1.0,
+            current: Vec2::ZERO,
+        }
+    }
+
+    pub fn with_max_speed(mut self, max_speed: f32) -> Self {
+        self.max_speed = max_speed;
+        self
+    }
+
+    // Closes in on the wanted velocity by as much as a step allows. Anything that isn't speeding
+    // up in the same direction goes by the stopping time, so a sharp turn is a stop and a start.
+    fn integrate(&mut self, wanted: Vec2, speed: f32, delta_seconds: f32) {
+        let slowing = wanted.length_squared() < self.current.length_squared()
+            || wanted.dot(self.current) < 0.0;
+        let seconds = if slowing {
+            self.stopping_seconds
+        } else {
+            self.acceleration_seconds
+        };
+        if seconds <= 0.0 {
+            self.current = wanted;
+            return;
+        }
+        let change = wanted - self.current;
+        self.current += change.clamp_length_max(speed / seconds * delta_seconds);
+    }
+}
+
+// Most units are light on their feet
+impl Default for Motion {
+    fn default() -> Self {
+        Self::new(0.1, 0.08)
+    }
+}
+
+// Pixels per second a hit is shoving the unit with, on top of wherever it's walking, dying off
+// on its own. Kept apart from Velocity since the behaviors set that fresh every frame.
+#[derive(Component, Default)]
+pub struct Knockback(pub Vec2);
+
+type TranslateData = (
+    &'static Velocity,
+    &'static Movement,
+    &'static Health,
+    Option<&'static StatModifiers>,
+    Option<&'static mut Knockback>,
+    Option<&'static mut Motion>,
+    Option<&'static Steering>,
+    &'static mut Transform,
+);
+
+pub fn translate(time: Res<Time>, mut query: Query<TranslateData, Without<Asleep>>) {
+    for (velocity, movement, health, stats, knockback, motion, steering, mut transform) in
+        query.iter_mut()
+    {
         if health.is_dead() {
             continue;
         }
 
-        transform.translation.x += velocity.0.x * movement.speed * time.delta_seconds();
-        transform.translation.y += velocity.0.y * movement.speed * time.delta_seconds();
+        if let Some(mut knockback) = knockback.filter(|knockback| knockback.0 != Vec2::ZERO) {
+            transform.translation += (knockback.0 * time.delta_seconds()).extend(0.0);
+            knockback.0 *= (-KNOCKBACK_DECAY * time.delta_seconds()).exp();
+            if knockback.0.length() < MIN_KNOCKBACK {
+                knockback.0 = Vec2::ZERO;
+            }
+        }
+
+        let speed = stats.map_or(movement.speed, |stats| {
+            stats.apply(Stat::MoveSpeed, movement.speed)
+        });
+        let steering = steering.map_or(Vec2::ZERO, |steering| steering.0);
+        let wanted = (velocity.0 + steering) * speed;
+        let moving = match motion {
+            Some(mut motion) => {
+                let wanted = wanted.clamp_length_max(speed * motion.max_speed);
+                motion.integrate(wanted, speed, time.delta_seconds());
+                motion.current
+            }
+            None => wanted,
+        };
+        transform.translation += (moving * time.delta_seconds()).extend(0.0);
     }
 }
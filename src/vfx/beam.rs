@@ -0,0 +1,45 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::ai::behavior::{Behavior, CurrentBehavior, HealBehavior};
+
+const BEAM_COLOR: Color = Color::rgb(0.45, 1.0, 0.55);
+// How far the beam wavers to either side, and how many times along its length
+const BEAM_AMPLITUDE: f32 = 4.0;
+const BEAM_WAVES: f32 = 3.0;
+const BEAM_SEGMENTS: usize = 16;
+// Waves per second running down the beam towards whoever is being healed
+const BEAM_SPEED: f32 = 2.0;
+
+// From every healer to whoever it's channeling on, fading in as the channel fills up so the
+// heal landing is right when it's brightest
+pub fn draw_heal_beams(
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    healer_query: Query<(&CurrentBehavior, &HealBehavior, &Transform)>,
+    target_query: Query<&Transform>,
+) {
+    let phase = time.elapsed_seconds() * BEAM_SPEED;
+    for (current_behavior, heal, transform) in healer_query.iter() {
+        let Behavior::Heal(_) = current_behavior.0 else {
+            continue;
+        };
+        let Some(target) = heal.target.and_then(|target| target_query.get(target).ok()) else {
+            continue;
+        };
+
+        let from = transform.translation.truncate();
+        let to = target.translation.truncate();
+        let side = (to - from).normalize_or_zero().perp();
+        let points = (0..=BEAM_SEGMENTS).map(|segment| {
+            let along = segment as f32 / BEAM_SEGMENTS as f32;
+            // Pinned at both ends, so it always looks like it's coming out of the healer
+            let sway = ((along * BEAM_WAVES - phase) * TAU).sin() * (along * TAU * 0.5).sin();
+            from.lerp(to, along) + side * sway * BEAM_AMPLITUDE
+        });
+
+        let charge = (heal.channel / heal.channel_seconds).clamp(0.0, 1.0);
+        gizmos.linestrip_2d(points, BEAM_COLOR.with_a(0.3 + charge * 0.7));
+    }
+}
@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+
+// Brightness budget, in screens worth of full brightness change per second. A flash covering
+// the whole screen at full opacity uses up 1.0, a small one at half opacity only a fraction.
+const FLASH_BUDGET_PER_SECOND: f32 = 3.0;
+// Photosensitive safe mode keeps well under the three flashes per second that the common
+// guidelines warn about, and never lets a single flash go fully opaque.
+const SAFE_FLASH_BUDGET_PER_SECOND: f32 = 0.3;
+const SAFE_MAX_FLASH_ALPHA: f32 = 0.3;
+const SAFE_MIN_FLASH_DURATION: f32 = 0.8;
+
+#[derive(Resource, Default)]
+pub struct VfxSettings {
+    pub photosensitive_safe_mode: bool,
+}
+
+impl VfxSettings {
+    fn budget_per_second(&self) -> f32 {
+        if self.photosensitive_safe_mode {
+            SAFE_FLASH_BUDGET_PER_SECOND
+        } else {
+            FLASH_BUDGET_PER_SECOND
+        }
+    }
+}
+
+// Every flash in the game asks this for permission before it is spawned, so the limit holds no
+// matter how many effects go off in the same frame.
+#[derive(Resource)]
+pub struct FlashLimiter {
+    pub budget: f32,
+    pub screen_area: f32,
+}
+
+impl Default for FlashLimiter {
+    fn default() -> Self {
+        Self {
+            budget: FLASH_BUDGET_PER_SECOND,
+            screen_area: 1280.0 * 720.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Flash {
+    pub alpha: f32,
+    pub duration: f32,
+}
+
+impl FlashLimiter {
+    // Takes the flash an effect would like to show and returns the one it is allowed to show
+    pub fn limit(&mut self, settings: &VfxSettings, requested: Flash, area: f32) -> Flash {
+        let mut flash = requested;
+        if settings.photosensitive_safe_mode {
+            flash.alpha = flash.alpha.min(SAFE_MAX_FLASH_ALPHA);
+            flash.duration = flash.duration.max(SAFE_MIN_FLASH_DURATION);
+        }
+
+        let coverage = (area / self.screen_area).clamp(0.0, 1.0);
+        if coverage <= 0.0 {
+            return flash;
+        }
+
+        let cost = flash.alpha * coverage;
+        if cost > self.budget {
+            flash.alpha = self.budget / coverage;
+        }
+        self.budget -= flash.alpha * coverage;
+        flash
+    }
+}
+
+pub fn refill_flash_budget(
+    time: Res<Time>,
+    settings: Res<VfxSettings>,
+    window_query: Query<&Window>,
+    mut limiter: ResMut<FlashLimiter>,
+) {
+    if let Ok(window) = window_query.get_single() {
+        limiter.screen_area = (window.width() * window.height()).max(1.0);
+    }
+
+    // Capped at one second worth of budget, saving up must not allow a burst of flashes later
+    let budget_per_second = settings.budget_per_second();
+    limiter.budget =
+        (limiter.budget + budget_per_second * time.delta_seconds()).min(budget_per_second);
+}
+
+pub fn toggle_photosensitive_mode(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<VfxSettings>,
+    mut limiter: ResMut<FlashLimiter>,
+) {
+    if !keys.just_pressed(KeyCode::F8) {
+        return;
+    }
+
+    settings.photosensitive_safe_mode = !settings.photosensitive_safe_mode;
+    limiter.budget = limiter.budget.min(settings.budget_per_second());
+    info!(
+        "Photosensitive safe mode {}",
+        if settings.photosensitive_safe_mode {
+            "enabled"
+        } else {
+            "disabled"
+        }
+    );
+}
@@ -0,0 +1,236 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::events::{Healed, UnitDied, UnitSummoned};
+use crate::gamestate::Cleanup;
+use crate::time_of_day::lerp_color;
+use crate::units::health::Health;
+use crate::units::unit_types::{Acolyte, UnitType};
+
+// Past this many on screen new bursts are skipped, a wave wiping out at once stays playable
+const MAX_PARTICLES: usize = 600;
+// Above the units they come off of, below the ui
+const PARTICLE_Z: f32 = 2.0;
+
+// Everything one burst of sprite particles needs, the effects below are all just one of these.
+// Ranges are rolled per particle.
+#[derive(Debug, Clone, Copy)]
+pub struct Emitter {
+    pub count: u32,
+    pub start_color: Color,
+    pub end_color: Color,
+    pub size: f32,
+    pub speed: (f32, f32),
+    pub lifetime: (f32, f32),
+    // Particles start out this far from the center, on a circle around it
+    pub ring_radius: f32,
+    // Flies straight out from the center when 0, straight up when 1
+    pub lift: f32,
+    pub gravity: f32,
+}
+
+pub const SUMMON_BURST: Emitter = Emitter {
+    count: 24,
+    start_color: Color::rgba(0.7, 0.4, 1.0, 0.9),
+    end_color: Color::rgba(0.3, 0.1, 0.6, 0.0),
+    size: 5.0,
+    speed: (40.0, 90.0),
+    lifetime: (0.4, 0.7),
+    ring_radius: 28.0,
+    lift: 0.2,
+    gravity: 0.0,
+};
+
+pub const BLOOD_PUFF: Emitter = Emitter {
+    count: 14,
+    start_color: Color::rgba(0.7, 0.05, 0.05, 0.9),
+    end_color: Color::rgba(0.35, 0.0, 0.0, 0.0),
+    size: 4.0,
+    speed: (60.0, 140.0),
+    lifetime: (0.3, 0.6),
+    ring_radius: 0.0,
+    lift: 0.5,
+    gravity: -300.0,
+};
+
+pub const BONE_PUFF: Emitter = Emitter {
+    count: 10,
+    start_color: Color::rgba(0.9, 0.88, 0.8, 1.0),
+    end_color: Color::rgba(0.6, 0.58, 0.5, 0.0),
+    size: 5.0,
+    speed: (50.0, 120.0),
+    lifetime: (0.4, 0.8),
+    ring_radius: 0.0,
+    lift: 0.6,
+    gravity: -350.0,
+};
+
+pub const MANA_SPARKLES: Emitter = Emitter {
+    count: 6,
+    start_color: Color::rgba(0.5, 0.6, 1.0, 1.0),
+    end_color: Color::rgba(0.8, 0.9, 1.0, 0.0),
+    size: 3.0,
+    speed: (20.0, 50.0),
+    lifetime: (0.5, 0.9),
+    ring_radius: 12.0,
+    lift: 1.0,
+    gravity: 40.0,
+};
+
+pub const HEAL_MOTES: Emitter = Emitter {
+    count: 8,
+    start_color: Color::rgba(0.45, 1.0, 0.55, 1.0),
+    end_color: Color::rgba(0.8, 1.0, 0.85, 0.0),
+    size: 4.0,
+    speed: (25.0, 60.0),
+    lifetime: (0.4, 0.8),
+    ring_radius: 16.0,
+    lift: 1.0,
+    gravity: 30.0,
+};
+
+#[derive(Component)]
+pub struct Particle {
+    velocity: Vec2,
+    gravity: f32,
+    start_color: Color,
+    end_color: Color,
+    lifetime: Timer,
+}
+
+fn roll((min, max): (f32, f32)) -> f32 {
+    min + (max - min) * rand::random::<f32>()
+}
+
+// Cosmetic, so it rolls on the thread rng and never touches the run's seed
+pub fn emit(commands: &mut Commands, emitter: &Emitter, position: Vec2) {
+    for _ in 0..emitter.count {
+        let direction = Vec2::from_angle(rand::random::<f32>() * TAU);
+        let heading = direction.lerp(Vec2::Y, emitter.lift).normalize_or_zero();
+        let start = position + direction * emitter.ring_radius;
+
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: emitter.start_color,
+                    custom_size: Some(Vec2::splat(emitter.size)),
+                    ..default()
+                },
+                transform: Transform::from_translation(start.extend(PARTICLE_Z)),
+                ..default()
+            },
+            Particle {
+                velocity: heading * roll(emitter.speed),
+                gravity: emitter.gravity,
+                start_color: emitter.start_color,
+                end_color: emitter.end_color,
+                lifetime: Timer::from_seconds(roll(emitter.lifetime), TimerMode::Once),
+            },
+            Cleanup,
+        ));
+    }
+}
+
+fn death_emitter(unit_type: Option<UnitType>) -> &'static Emitter {
+    match unit_type {
+        // Stone and bound imps crumble rather than bleed
+        Some(UnitType::Gargoyle) | Some(UnitType::Imp) => &BONE_PUFF,
+        _ => &BLOOD_PUFF,
+    }
+}
+
+pub fn summon_bursts(
+    mut commands: Commands,
+    mut summoned_reader: EventReader<UnitSummoned>,
+    transform_query: Query<&Transform>,
+    particle_query: Query<(), With<Particle>>,
+) {
+    for summoned in summoned_reader.read() {
+        if particle_query.iter().count() >= MAX_PARTICLES {
+            return;
+        }
+        let Ok(transform) = transform_query.get(summoned.entity) else {
+            continue;
+        };
+        emit(
+            &mut commands,
+            &SUMMON_BURST,
+            transform.translation.truncate(),
+        );
+    }
+}
+
+pub fn death_puffs(
+    mut commands: Commands,
+    mut died_reader: EventReader<UnitDied>,
+    particle_query: Query<(), With<Particle>>,
+) {
+    for died in died_reader.read() {
+        if particle_query.iter().count() >= MAX_PARTICLES {
+            return;
+        }
+        emit(&mut commands, death_emitter(died.unit_type), died.position);
+    }
+}
+
+pub fn mana_sparkles(
+    mut commands: Commands,
+    query: Query<(&Acolyte, &Health, &Transform)>,
+    particle_query: Query<(), With<Particle>>,
+) {
+    if particle_query.iter().count() >= MAX_PARTICLES {
+        return;
+    }
+    for (acolyte, health, transform) in query.iter() {
+        if health.is_dead() || !acolyte.give_mana.just_charged() {
+            continue;
+        }
+        emit(
+            &mut commands,
+            &MANA_SPARKLES,
+            transform.translation.truncate(),
+        );
+    }
+}
+
+// Only the channeled ones, a heal from a pickup or lifesteal has nothing to show it came from
+pub fn heal_motes(
+    mut commands: Commands,
+    mut healed_reader: EventReader<Healed>,
+    transform_query: Query<&Transform>,
+    particle_query: Query<(), With<Particle>>,
+) {
+    for healed in healed_reader.read() {
+        if particle_query.iter().count() >= MAX_PARTICLES {
+            return;
+        }
+        if healed.source.is_none() {
+            continue;
+        }
+        let Ok(transform) = transform_query.get(healed.target) else {
+            continue;
+        };
+        emit(&mut commands, &HEAL_MOTES, transform.translation.truncate());
+    }
+}
+
+pub fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut particle, mut transform, mut sprite) in query.iter_mut() {
+        if particle.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        particle.velocity.y += particle.gravity * delta;
+        transform.translation += (particle.velocity * delta).extend(0.0);
+        let progress = particle.lifetime.fraction();
+        sprite.color = lerp_color(particle.start_color, particle.end_color, progress);
+        transform.scale = Vec3::splat(1.0 - progress * 0.5);
+    }
+}
@@ -0,0 +1,30 @@
+use bevy::prelude::*;
+
+use crate::schedule::FrameSet;
+
+use super::{beam, flash, particles};
+
+pub struct VfxPlugin;
+
+impl Plugin for VfxPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<flash::VfxSettings>()
+            .init_resource::<flash::FlashLimiter>()
+            .add_systems(
+                Update,
+                (
+                    flash::refill_flash_budget,
+                    flash::toggle_photosensitive_mode,
+                    (
+                        particles::summon_bursts,
+                        particles::death_puffs,
+                        particles::mana_sparkles,
+                        particles::heal_motes,
+                        particles::update_particles,
+                        beam::draw_heal_beams,
+                    )
+                        .in_set(FrameSet::Presentation),
+                ),
+            );
+    }
+}